@@ -0,0 +1,311 @@
+//! Serialized AppleScript execution.
+//!
+//! Every `osascript` call in the accessibility layer goes through one worker
+//! thread, so rapid hotkey presses queue up instead of piling up concurrent
+//! children. A script that runs longer than [`SCRIPT_TIMEOUT`] is killed,
+//! usually because System Events is waiting on a permission prompt or the
+//! system is busy. Read-only scripts submitted with [`query`] while an
+//! identical one is queued or running share its result rather than running
+//! again; keystrokes go through [`perform`] and always run.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// How long one script may run before it is killed
+pub const SCRIPT_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// How often a running script is checked for exit
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptError {
+    /// Killed after running longer than the timeout
+    TimedOut,
+    /// Couldn't start, or exited with an error; holds its stderr
+    Failed(String),
+}
+
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TimedOut => write!(f, "AppleScript timed out after {:?}", SCRIPT_TIMEOUT),
+            Self::Failed(message) => write!(f, "AppleScript failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+pub type ScriptResult = Result<String, ScriptError>;
+
+/// Called with the name of every script that timed out
+type TimeoutObserver = Box<dyn Fn(&str) + Send + Sync>;
+
+static EXECUTOR: OnceLock<Executor> = OnceLock::new();
+static TIMEOUT_OBSERVER: OnceLock<TimeoutObserver> = OnceLock::new();
+
+/// Run a script that only reads state, sharing the result of an identical
+/// script already waiting or running. `name` identifies it in logs.
+pub fn query(name: &'static str, script: &str) -> ScriptResult {
+    executor().submit(name, script, true)
+}
+
+/// Run a script with side effects, such as a keystroke, even when an
+/// identical one is already queued
+pub fn perform(name: &'static str, script: &str) -> ScriptResult {
+    executor().submit(name, script, false)
+}
+
+/// Report timed-out scripts, e.g. as an event for the frontend. Only the
+/// first observer is kept.
+pub fn on_timeout(observer: impl Fn(&str) + Send + Sync + 'static) {
+    if TIMEOUT_OBSERVER.set(Box::new(observer)).is_err() {
+        tracing::warn!("AppleScript timeout observer already set");
+    }
+}
+
+fn executor() -> &'static Executor {
+    EXECUTOR.get_or_init(|| Executor::spawn(run_osascript))
+}
+
+fn run_osascript(name: &'static str, script: &str) -> ScriptResult {
+    let mut command = Command::new("osascript");
+    command.arg("-e").arg(script);
+    let result = run_with_timeout(command, SCRIPT_TIMEOUT);
+
+    if result == Err(ScriptError::TimedOut) {
+        tracing::warn!(
+            "AppleScript '{}' timed out after {:?}",
+            name,
+            SCRIPT_TIMEOUT
+        );
+        if let Some(observer) = TIMEOUT_OBSERVER.get() {
+            observer(name);
+        }
+    }
+    result
+}
+
+/// Run `command` to completion and return its trimmed stdout, killing it
+/// once `timeout` has passed
+pub(crate) fn run_with_timeout(mut command: Command, timeout: Duration) -> ScriptResult {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| ScriptError::Failed(e.to_string()))?;
+
+    // Drained on their own threads so a large selection can't fill the
+    // pipe and stall the script
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(ScriptError::TimedOut);
+            }
+            Ok(None) => std::thread::sleep(POLL_INTERVAL),
+            Err(e) => return Err(ScriptError::Failed(e.to_string())),
+        }
+    };
+
+    let stdout = stdout.join().unwrap_or_default();
+    if status.success() {
+        Ok(String::from_utf8_lossy(&stdout).trim().to_string())
+    } else {
+        let stderr = stderr.join().unwrap_or_default();
+        Err(ScriptError::Failed(
+            String::from_utf8_lossy(&stderr).trim().to_string(),
+        ))
+    }
+}
+
+fn drain(pipe: Option<impl Read + Send + 'static>) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+type Runner = fn(&'static str, &str) -> ScriptResult;
+
+struct Job {
+    name: &'static str,
+    script: String,
+    /// Gets the result directly; `None` for a query, whose waiters are
+    /// listed in `in_flight` under its script
+    reply: Option<Sender<ScriptResult>>,
+}
+
+/// Single worker running scripts in submission order
+struct Executor {
+    jobs: Mutex<Sender<Job>>,
+    /// Callers waiting on each queued or running query
+    in_flight: Arc<Mutex<HashMap<String, Vec<Sender<ScriptResult>>>>>,
+}
+
+impl Executor {
+    fn spawn(runner: Runner) -> Self {
+        let (jobs, queue) = mpsc::channel();
+        let in_flight = Arc::new(Mutex::new(HashMap::new()));
+
+        let worker_in_flight = Arc::clone(&in_flight);
+        let spawned = std::thread::Builder::new()
+            .name("applescript".to_string())
+            .spawn(move || Self::work(runner, queue, worker_in_flight));
+        if let Err(e) = spawned {
+            tracing::error!("Failed to start AppleScript worker: {}", e);
+        }
+
+        Self {
+            jobs: Mutex::new(jobs),
+            in_flight,
+        }
+    }
+
+    fn work(
+        runner: Runner,
+        queue: Receiver<Job>,
+        in_flight: Arc<Mutex<HashMap<String, Vec<Sender<ScriptResult>>>>>,
+    ) {
+        for job in queue {
+            let result = runner(job.name, &job.script);
+            let waiters = match job.reply {
+                Some(reply) => vec![reply],
+                None => in_flight
+                    .lock()
+                    .ok()
+                    .and_then(|mut in_flight| in_flight.remove(&job.script))
+                    .unwrap_or_default(),
+            };
+            for waiter in waiters {
+                let _ = waiter.send(result.clone());
+            }
+        }
+    }
+
+    fn submit(&self, name: &'static str, script: &str, coalesce: bool) -> ScriptResult {
+        let (reply, result) = mpsc::channel();
+
+        let job = if coalesce {
+            let mut in_flight = self
+                .in_flight
+                .lock()
+                .map_err(|e| ScriptError::Failed(e.to_string()))?;
+            if let Some(waiters) = in_flight.get_mut(script) {
+                tracing::debug!("Sharing the result of a running '{}' script", name);
+                waiters.push(reply);
+                None
+            } else {
+                in_flight.insert(script.to_string(), vec![reply]);
+                Some(Job {
+                    name,
+                    script: script.to_string(),
+                    reply: None,
+                })
+            }
+        } else {
+            Some(Job {
+                name,
+                script: script.to_string(),
+                reply: Some(reply),
+            })
+        };
+
+        if let Some(job) = job {
+            let sent = self
+                .jobs
+                .lock()
+                .map_err(|e| ScriptError::Failed(e.to_string()))?
+                .send(job);
+            if sent.is_err() {
+                if coalesce {
+                    if let Ok(mut in_flight) = self.in_flight.lock() {
+                        in_flight.remove(script);
+                    }
+                }
+                return Err(ScriptError::Failed(
+                    "AppleScript worker isn't running".to_string(),
+                ));
+            }
+        }
+
+        result
+            .recv()
+            .map_err(|_| ScriptError::Failed("AppleScript worker stopped".to_string()))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn shell(script: &str) -> Command {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(script);
+        command
+    }
+
+    #[test]
+    fn test_run_with_timeout_returns_trimmed_output() {
+        let result = run_with_timeout(shell("echo '  hello  '"), Duration::from_secs(5));
+        assert_eq!(result, Ok("hello".to_string()));
+
+        let result = run_with_timeout(shell("echo denied >&2; exit 1"), Duration::from_secs(5));
+        assert_eq!(result, Err(ScriptError::Failed("denied".to_string())));
+    }
+
+    #[test]
+    fn test_run_with_timeout_kills_slow_scripts() {
+        let started = Instant::now();
+        let result = run_with_timeout(shell("sleep 5"), Duration::from_millis(100));
+
+        assert_eq!(result, Err(ScriptError::TimedOut));
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+    fn slow_runner(_name: &'static str, script: &str) -> ScriptResult {
+        RUNS.fetch_add(1, Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(200));
+        Ok(script.to_uppercase())
+    }
+
+    #[test]
+    fn test_executor_coalesces_queries_but_not_actions() {
+        let executor = Arc::new(Executor::spawn(slow_runner));
+
+        let submit = |script: &'static str, coalesce: bool| {
+            let executor = Arc::clone(&executor);
+            std::thread::spawn(move || executor.submit("test", script, coalesce))
+        };
+        let first = submit("frontmost", true);
+        // Let the first query reach the worker
+        std::thread::sleep(Duration::from_millis(50));
+        let queries: Vec<_> = (0..3).map(|_| submit("frontmost", true)).collect();
+        let actions: Vec<_> = (0..2).map(|_| submit("paste", false)).collect();
+
+        assert_eq!(first.join().unwrap(), Ok("FRONTMOST".to_string()));
+        for handle in queries.into_iter().chain(actions) {
+            assert!(handle.join().unwrap().is_ok());
+        }
+        // One query shared by all four callers, and both actions
+        assert_eq!(RUNS.load(Ordering::SeqCst), 3);
+        assert!(executor.in_flight.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,76 @@
+//! Bookkeeping for clipboard writes made by the app itself.
+//!
+//! Auto-paste and selected-text capture both go through the clipboard. The
+//! clipboard watcher uses this module to tell those writes apart from text
+//! the user copied.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a value we wrote is remembered as our own
+const OWN_WRITE_TTL: Duration = Duration::from_secs(5);
+
+static OWN_WRITES: Mutex<Vec<(u64, Instant)>> = Mutex::new(Vec::new());
+static SUPPRESS_UNTIL: Mutex<Option<Instant>> = Mutex::new(None);
+
+fn hash_text(text: &str) -> u64 {
+    // Trimmed so values captured by AppleScript (which trims) still match
+    let mut hasher = DefaultHasher::new();
+    text.trim().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Remember that the app itself put `text` on the clipboard
+pub fn record_own_write(text: &str) {
+    let now = Instant::now();
+    if let Ok(mut writes) = OWN_WRITES.lock() {
+        writes.retain(|(_, at)| now.duration_since(*at) < OWN_WRITE_TTL);
+        writes.push((hash_text(text), now));
+    }
+}
+
+/// Treat every clipboard change in the next `duration` as our own.
+/// Used around synthetic Cmd+C, where we don't know the value in advance.
+pub fn suppress_watch(duration: Duration) {
+    if let Ok(mut until) = SUPPRESS_UNTIL.lock() {
+        *until = Some(Instant::now() + duration);
+    }
+}
+
+/// Check whether a clipboard value came from the app's own paste/restore machinery
+pub fn is_own_write(text: &str) -> bool {
+    let now = Instant::now();
+
+    if let Ok(until) = SUPPRESS_UNTIL.lock() {
+        if until.map(|u| now < u).unwrap_or(false) {
+            return true;
+        }
+    }
+
+    let hash = hash_text(text);
+    OWN_WRITES
+        .lock()
+        .map(|writes| {
+            writes
+                .iter()
+                .any(|(h, at)| *h == hash && now.duration_since(*at) < OWN_WRITE_TTL)
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_own_write() {
+        let text = "clipboard::tests::test_record_own_write";
+        assert!(!is_own_write(text));
+
+        record_own_write(text);
+        assert!(is_own_write(text));
+        assert!(!is_own_write("something the user copied"));
+    }
+}
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
-use std::process::Command;
+use std::sync::Mutex;
+
+/// The last other app seen in front of ours, see `remember_previous_app`
+static PREVIOUS_APP: Mutex<Option<FrontmostAppInfo>> = Mutex::new(None);
 
 /// Information about the frontmost (active) application
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -8,6 +11,14 @@ pub struct FrontmostAppInfo {
     pub bundle_id: String,
 }
 
+/// `get_frontmost_app` on a blocking thread, for async callers
+pub async fn get_frontmost_app_async() -> Option<FrontmostAppInfo> {
+    tauri::async_runtime::spawn_blocking(get_frontmost_app)
+        .await
+        .ok()
+        .flatten()
+}
+
 /// Get information about the frontmost application using AppleScript
 pub fn get_frontmost_app() -> Option<FrontmostAppInfo> {
     // AppleScript to get frontmost app name and bundle ID
@@ -20,22 +31,14 @@ pub fn get_frontmost_app() -> Option<FrontmostAppInfo> {
         end tell
     "#;
 
-    let output = Command::new("osascript")
-        .arg("-e")
-        .arg(script)
-        .output()
-        .ok()?;
-
-    if !output.status.success() {
-        tracing::warn!(
-            "Failed to get frontmost app: {}",
-            String::from_utf8_lossy(&output.stderr)
-        );
-        return None;
-    }
-
-    let result = String::from_utf8_lossy(&output.stdout);
-    let result = result.trim();
+    let result = match super::applescript::query("frontmost app", script) {
+        Ok(result) => result,
+        Err(e) => {
+            tracing::warn!("Failed to get frontmost app: {}", e);
+            return None;
+        }
+    };
+    let result = result.as_str();
 
     // Parse "AppName|com.app.bundleid"
     let parts: Vec<&str> = result.splitn(2, '|').collect();
@@ -52,3 +55,53 @@ pub fn get_frontmost_app() -> Option<FrontmostAppInfo> {
         })
     }
 }
+
+/// Note the frontmost app as the one the user last worked in, unless it is
+/// this app (`own_bundle_id`). Called as the main window loses focus, so
+/// `previous_app` can name the app the user was in when they come back.
+pub fn remember_previous_app(own_bundle_id: &str) {
+    if let Some(app) = get_frontmost_app() {
+        set_previous_app(app, own_bundle_id);
+    }
+}
+
+fn set_previous_app(app: FrontmostAppInfo, own_bundle_id: &str) {
+    if app.bundle_id == own_bundle_id {
+        return;
+    }
+    tracing::debug!("Previous app: {} ({})", app.name, app.bundle_id);
+    if let Ok(mut previous) = PREVIOUS_APP.lock() {
+        *previous = Some(app);
+    }
+}
+
+/// The last other app remembered by `remember_previous_app`, if any
+pub fn previous_app() -> Option<FrontmostAppInfo> {
+    PREVIOUS_APP
+        .lock()
+        .ok()
+        .and_then(|previous| previous.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_previous_app_skips_own_app() {
+        let notes = FrontmostAppInfo {
+            name: "Notes".to_string(),
+            bundle_id: "com.apple.Notes".to_string(),
+        };
+        set_previous_app(notes, "com.blahcubed.app");
+
+        let own = FrontmostAppInfo {
+            name: "Blah³".to_string(),
+            bundle_id: "com.blahcubed.app".to_string(),
+        };
+        set_previous_app(own, "com.blahcubed.app");
+
+        let previous = previous_app().unwrap();
+        assert_eq!(previous.bundle_id, "com.apple.Notes");
+    }
+}
@@ -1,6 +1,34 @@
 #![allow(dead_code)]
 
+use serde::{Deserialize, Serialize};
 use std::process::Command;
+use std::time::Duration;
+
+use super::FrontmostAppInfo;
+
+/// Time for the target app to insert pasted text before Enter is pressed
+const PASTE_SETTLE: Duration = Duration::from_millis(150);
+
+/// Text added to the end of an auto-pasted dictation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PasteSuffix {
+    #[default]
+    None,
+    Space,
+    Newline,
+}
+
+impl PasteSuffix {
+    /// `text` with the suffix added
+    pub fn apply(self, text: &str) -> String {
+        match self {
+            Self::None => text.to_string(),
+            Self::Space => format!("{} ", text),
+            Self::Newline => format!("{}\n", text),
+        }
+    }
+}
 
 /// Paste text at the current cursor position.
 /// Uses the clipboard + Cmd+V method for reliability.
@@ -12,30 +40,101 @@ pub fn paste_text(text: &str) -> Result<(), String> {
     std::thread::sleep(std::time::Duration::from_millis(50));
 
     // Simulate Cmd+V
-    let script = r#"
-        tell application "System Events"
-            keystroke "v" using {command down}
-        end tell
-    "#;
+    run_system_events("paste", r#"keystroke "v" using {command down}"#)
+}
 
-    let output = Command::new("osascript")
-        .arg("-e")
-        .arg(script)
-        .output()
-        .map_err(|e| e.to_string())?;
+/// Press Return once a paste has settled, but only if `target`, the app
+/// the dictation was meant for, is still frontmost. Returns whether Return
+/// was pressed.
+pub fn press_enter_after_paste(target: Option<&FrontmostAppInfo>) -> Result<bool, String> {
+    std::thread::sleep(PASTE_SETTLE);
 
-    if output.status.success() {
-        Ok(())
+    let frontmost = super::get_frontmost_app();
+    if !is_same_app(target, frontmost.as_ref()) {
+        tracing::info!(
+            "Not pressing Enter: dictation target {:?} is no longer frontmost ({:?})",
+            target.map(|app| &app.name),
+            frontmost.map(|app| app.name)
+        );
+        return Ok(false);
+    }
+
+    // Key code 36 is Return
+    run_system_events("press Enter", "key code 36")?;
+    Ok(true)
+}
+
+/// Bring `app` to the front, e.g. to undo a paste after the user switched
+/// away. Waits for the switch to settle.
+pub fn activate_app(app: &FrontmostAppInfo) -> Result<(), String> {
+    let target = if app.bundle_id.is_empty() {
+        format!("application \"{}\"", escape_applescript(&app.name))
     } else {
-        Err(String::from_utf8_lossy(&output.stderr).to_string())
+        format!("application id \"{}\"", escape_applescript(&app.bundle_id))
+    };
+    let script = format!("tell {} to activate", target);
+
+    super::applescript::perform("activate app", &script).map_err(|e| e.to_string())?;
+    std::thread::sleep(PASTE_SETTLE);
+    Ok(())
+}
+
+/// Press Delete `count` times, removing that many characters before the
+/// cursor
+pub fn delete_backward(count: usize) -> Result<(), String> {
+    // Key code 51 is Delete (backspace)
+    let command = format!("repeat {} times\n key code 51\n end repeat", count);
+    run_system_events("delete backward", &command)
+}
+
+/// Press Cmd+Z in the frontmost app
+pub fn press_undo() -> Result<(), String> {
+    run_system_events("undo", r#"keystroke "z" using {command down}"#)
+}
+
+/// `text` as the inside of an AppleScript string literal
+fn escape_applescript(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Whether `frontmost` is the `target` app. An unknown app on either side
+/// never matches, so Enter is never pressed blind.
+pub fn is_same_app(
+    target: Option<&FrontmostAppInfo>,
+    frontmost: Option<&FrontmostAppInfo>,
+) -> bool {
+    match (target, frontmost) {
+        (Some(target), Some(frontmost)) if !target.bundle_id.is_empty() => {
+            target.bundle_id == frontmost.bundle_id
+        }
+        (Some(target), Some(frontmost)) => !target.name.is_empty() && target.name == frontmost.name,
+        _ => false,
     }
 }
 
+/// Run one AppleScript command inside `tell application "System Events"`
+fn run_system_events(name: &'static str, command: &str) -> Result<(), String> {
+    let script = format!(
+        r#"
+        tell application "System Events"
+            {}
+        end tell
+    "#,
+        command
+    );
+
+    super::applescript::perform(name, &script)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
 /// Set the clipboard contents
-fn set_clipboard(text: &str) -> Result<(), std::io::Error> {
+pub fn set_clipboard(text: &str) -> Result<(), std::io::Error> {
     use std::io::Write;
     use std::process::Stdio;
 
+    super::clipboard::record_own_write(text);
+
     let mut child = Command::new("pbcopy")
         .stdin(Stdio::piped())
         .spawn()?;
@@ -64,3 +163,47 @@ pub fn type_text(_text: &str) -> Result<(), String> {
 
     Err("Not implemented - use paste_text instead".to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(name: &str, bundle_id: &str) -> FrontmostAppInfo {
+        FrontmostAppInfo {
+            name: name.to_string(),
+            bundle_id: bundle_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_paste_suffix() {
+        assert_eq!(PasteSuffix::None.apply("Hi there."), "Hi there.");
+        assert_eq!(PasteSuffix::Space.apply("Hi there."), "Hi there. ");
+        assert_eq!(PasteSuffix::Newline.apply("Hi there."), "Hi there.\n");
+        assert_eq!(
+            serde_json::to_value(PasteSuffix::Newline).unwrap(),
+            serde_json::json!("newline")
+        );
+    }
+
+    #[test]
+    fn test_escape_applescript() {
+        assert_eq!(escape_applescript("Notes"), "Notes");
+        assert_eq!(escape_applescript(r#"My "App" \ 2"#), r#"My \"App\" \\ 2"#);
+    }
+
+    #[test]
+    fn test_is_same_app() {
+        let slack = app("Slack", "com.tinyspeck.slackmacgap");
+        let notes = app("Notes", "com.apple.Notes");
+
+        assert!(is_same_app(Some(&slack), Some(&slack.clone())));
+        assert!(!is_same_app(Some(&slack), Some(&notes)));
+        // Without a target or a frontmost app there's nothing to confirm
+        assert!(!is_same_app(None, Some(&slack)));
+        assert!(!is_same_app(Some(&slack), None));
+        // Name only when the bundle id couldn't be read
+        assert!(is_same_app(Some(&app("Slack", "")), Some(&slack)));
+        assert!(!is_same_app(Some(&app("", "")), Some(&app("", ""))));
+    }
+}
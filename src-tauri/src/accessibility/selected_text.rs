@@ -2,6 +2,14 @@
 
 use std::process::Command;
 
+/// `get_selected_text` on a blocking thread, for async callers
+pub async fn get_selected_text_async() -> Option<String> {
+    tauri::async_runtime::spawn_blocking(get_selected_text)
+        .await
+        .ok()
+        .flatten()
+}
+
 /// Get the currently selected text from the frontmost application.
 /// Uses AppleScript as a reliable cross-app method.
 pub fn get_selected_text() -> Option<String> {
@@ -24,15 +32,15 @@ pub fn get_selected_text() -> Option<String> {
     // Save current clipboard
     let old_clipboard = get_clipboard();
 
-    // Run the AppleScript to copy selection
-    let output = Command::new("osascript")
-        .arg("-e")
-        .arg(script)
-        .output()
-        .ok()?;
+    // The synthetic Cmd+C and the restore below aren't user copies
+    super::clipboard::suppress_watch(std::time::Duration::from_millis(1000));
 
-    if output.status.success() {
-        let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    // Run the AppleScript to copy selection. Presses while one copy is
+    // running share its result instead of copying again.
+    let output = super::applescript::query("selected text", script);
+
+    if let Ok(text) = output {
+        super::clipboard::record_own_write(&text);
 
         // Restore old clipboard after a short delay
         if let Some(old) = old_clipboard {
@@ -51,7 +59,7 @@ pub fn get_selected_text() -> Option<String> {
 }
 
 /// Get the current clipboard contents
-fn get_clipboard() -> Option<String> {
+pub fn get_clipboard() -> Option<String> {
     let output = Command::new("pbpaste").output().ok()?;
     if output.status.success() {
         Some(String::from_utf8_lossy(&output.stdout).to_string())
@@ -65,6 +73,8 @@ fn set_clipboard(text: &str) -> Result<(), std::io::Error> {
     use std::io::Write;
     use std::process::Stdio;
 
+    super::clipboard::record_own_write(text);
+
     let mut child = Command::new("pbcopy")
         .stdin(Stdio::piped())
         .spawn()?;
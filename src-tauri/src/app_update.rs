@@ -0,0 +1,200 @@
+//! Checking GitHub Releases for a newer version of the app.
+//!
+//! The answer is kept for a day, so opening the settings repeatedly doesn't
+//! hit GitHub's rate limit. Failed checks aren't kept and are retried on
+//! the next call.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::BlahError;
+
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/Anomali007/blah3/releases/latest";
+
+/// How long a check's answer is reused
+const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+static LAST_CHECK: Mutex<Option<UpdateCheck>> = Mutex::new(None);
+
+/// A release newer than the running version
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UpdateInfo {
+    /// Release version without the leading `v`, e.g. `0.3.0`
+    pub version: String,
+    /// Release page on GitHub
+    pub release_url: String,
+    /// Release description, in Markdown
+    pub release_notes: Option<String>,
+}
+
+/// The fields used from GitHub's release JSON
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    html_url: String,
+    body: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct UpdateCheck {
+    checked_at: Instant,
+    update: Option<UpdateInfo>,
+}
+
+/// `major.minor.patch` of a version or tag such as `v1.2.3`. A pre-release
+/// or build suffix is ignored and missing parts count as 0.
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let version = version.trim().trim_start_matches(['v', 'V']);
+    let core = version.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((major, minor, patch))
+}
+
+/// `release` as an update if it is newer than `current`
+fn newer_release(release: Release, current: &str) -> Option<UpdateInfo> {
+    let latest = parse_version(&release.tag_name);
+    if latest.is_none() {
+        tracing::warn!("Ignoring release with unexpected tag {}", release.tag_name);
+    }
+    if latest? <= parse_version(current)? {
+        return None;
+    }
+
+    Some(UpdateInfo {
+        version: release
+            .tag_name
+            .trim()
+            .trim_start_matches(['v', 'V'])
+            .to_string(),
+        release_url: release.html_url,
+        release_notes: release.body.filter(|notes| !notes.trim().is_empty()),
+    })
+}
+
+/// The previous check's answer if it is younger than `interval` at `now`
+fn fresh_check(
+    slot: &Option<UpdateCheck>,
+    now: Instant,
+    interval: Duration,
+) -> Option<Option<UpdateInfo>> {
+    let check = slot.as_ref()?;
+    (now.saturating_duration_since(check.checked_at) < interval).then(|| check.update.clone())
+}
+
+async fn fetch_latest_release(http_proxy: Option<&str>) -> Result<Release, BlahError> {
+    let mut builder = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        // GitHub's API refuses requests without a user agent
+        .user_agent(concat!("blah3/", env!("CARGO_PKG_VERSION")));
+    if let Some(url) = http_proxy.map(str::trim).filter(|url| !url.is_empty()) {
+        let proxy = reqwest::Proxy::all(url).map_err(|e| BlahError::InvalidSetting {
+            field: "http_proxy".to_string(),
+            message: format!("Invalid proxy URL: {}", e),
+        })?;
+        builder = builder.proxy(proxy.no_proxy(reqwest::NoProxy::from_env()));
+    }
+    let client = builder
+        .build()
+        .map_err(|e| BlahError::Internal(format!("Failed to create HTTP client: {}", e)))?;
+
+    let unreachable =
+        |e: reqwest::Error| BlahError::IoError(format!("Couldn't check for updates: {}", e));
+    let body = client
+        .get(LATEST_RELEASE_URL)
+        .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(unreachable)?
+        .bytes()
+        .await
+        .map_err(unreachable)?;
+    serde_json::from_slice(&body)
+        .map_err(|e| BlahError::IoError(format!("Unexpected response from GitHub: {}", e)))
+}
+
+/// The latest release if it is newer than this build, asking GitHub at
+/// most once a day
+pub async fn check_for_update(http_proxy: Option<&str>) -> Result<Option<UpdateInfo>, BlahError> {
+    if let Ok(last) = LAST_CHECK.lock() {
+        if let Some(update) = fresh_check(&last, Instant::now(), CHECK_INTERVAL) {
+            return Ok(update);
+        }
+    }
+
+    let release = fetch_latest_release(http_proxy).await?;
+    let update = newer_release(release, env!("CARGO_PKG_VERSION"));
+    match &update {
+        Some(update) => tracing::info!("Version {} is available", update.version),
+        None => tracing::info!("No newer release than {}", env!("CARGO_PKG_VERSION")),
+    }
+
+    if let Ok(mut last) = LAST_CHECK.lock() {
+        *last = Some(UpdateCheck {
+            checked_at: Instant::now(),
+            update: update.clone(),
+        });
+    }
+    Ok(update)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(tag: &str) -> Release {
+        Release {
+            tag_name: tag.to_string(),
+            html_url: format!("https://github.com/Anomali007/blah3/releases/tag/{}", tag),
+            body: Some("- Faster dictation".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("v1.2.3"), Some((1, 2, 3)));
+        assert_eq!(parse_version("0.10.0"), Some((0, 10, 0)));
+        assert_eq!(parse_version("2.0"), Some((2, 0, 0)));
+        assert_eq!(parse_version("1.4.0-beta.2"), Some((1, 4, 0)));
+        assert_eq!(parse_version("nightly"), None);
+        assert_eq!(parse_version("1.2.3.4"), None);
+    }
+
+    #[test]
+    fn test_newer_release_is_an_update() {
+        let update = newer_release(release("v0.10.0"), "0.9.1").unwrap();
+        assert_eq!(update.version, "0.10.0");
+        assert!(update.release_url.ends_with("/v0.10.0"));
+        assert_eq!(update.release_notes.as_deref(), Some("- Faster dictation"));
+
+        assert!(newer_release(release("v0.9.1"), "0.9.1").is_none());
+        assert!(newer_release(release("v0.8.0"), "0.9.1").is_none());
+        assert!(newer_release(release("latest"), "0.9.1").is_none());
+    }
+
+    #[test]
+    fn test_check_is_reused_for_a_day() {
+        let now = Instant::now();
+        let slot = Some(UpdateCheck {
+            checked_at: now,
+            update: None,
+        });
+        assert_eq!(
+            fresh_check(&slot, now + Duration::from_secs(60), CHECK_INTERVAL),
+            Some(None)
+        );
+        let tomorrow = now + CHECK_INTERVAL;
+        assert_eq!(fresh_check(&slot, tomorrow, CHECK_INTERVAL), None);
+        assert_eq!(fresh_check(&None, now, CHECK_INTERVAL), None);
+    }
+}
@@ -2,13 +2,137 @@
 
 use anyhow::{anyhow, Result};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use serde::{Deserialize, Serialize};
 use std::sync::{
-    atomic::{AtomicBool, AtomicU32, Ordering},
+    atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
     Arc, Mutex,
 };
 use std::thread;
 
-use super::silence::SilenceDetector;
+use super::processing::audio_duration_secs;
+use super::silence::{calculate_rms, rms_to_db, AdaptiveSilenceDetector, SilenceDetector};
+
+/// Sample rate of captured audio; Whisper expects 16kHz
+pub const CAPTURE_SAMPLE_RATE: u32 = 16000;
+
+/// Samples at or above this magnitude count as clipped
+pub const CLIP_LEVEL: f32 = 0.99;
+
+/// Share of clipped samples above which the input gain is too hot for Whisper
+pub const CLIPPED_RATIO_WARNING: f32 = 0.001;
+
+/// Floor for reported levels, so silence isn't `-inf` (sent as `null`)
+pub const MIN_LEVEL_DB: f32 = -100.0;
+
+/// Window the RMS is measured over in `measure_input_level`
+const LEVEL_WINDOW_MS: usize = 100;
+
+/// How long `AudioCapture::start` waits for the input stream to open
+const STREAM_OPEN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Peak and clipping of a recording
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecordingLevels {
+    /// Largest sample magnitude, 0.0 to 1.0
+    pub peak: f32,
+    /// Share of samples at or near full scale
+    pub clipped_ratio: f32,
+}
+
+impl RecordingLevels {
+    /// Whether enough of the recording clipped to hurt transcription
+    pub fn is_clipping(&self) -> bool {
+        self.clipped_ratio > CLIPPED_RATIO_WARNING
+    }
+}
+
+/// Running peak and clip count, updated from the capture callback.
+/// Only atomics, so recording a block never allocates or blocks.
+#[derive(Debug, Default)]
+pub struct LevelMeter {
+    /// Bits of the peak as f32; for non-negative floats the bit patterns
+    /// order the same as the values, so `fetch_max` works on them
+    peak: AtomicU32,
+    clipped: AtomicU64,
+    samples: AtomicU64,
+}
+
+impl LevelMeter {
+    pub fn record(&self, data: &[f32]) {
+        let mut peak = 0.0f32;
+        let mut clipped = 0u64;
+        for sample in data {
+            let magnitude = sample.abs();
+            if magnitude >= CLIP_LEVEL {
+                clipped += 1;
+            }
+            peak = peak.max(magnitude);
+        }
+
+        self.peak.fetch_max(peak.to_bits(), Ordering::Relaxed);
+        self.clipped.fetch_add(clipped, Ordering::Relaxed);
+        self.samples.fetch_add(data.len() as u64, Ordering::Relaxed);
+    }
+
+    pub fn levels(&self) -> RecordingLevels {
+        let samples = self.samples.load(Ordering::Relaxed);
+        RecordingLevels {
+            peak: f32::from_bits(self.peak.load(Ordering::Relaxed)),
+            clipped_ratio: if samples == 0 {
+                0.0
+            } else {
+                self.clipped.load(Ordering::Relaxed) as f32 / samples as f32
+            },
+        }
+    }
+
+    pub fn reset(&self) {
+        self.peak.store(0, Ordering::Relaxed);
+        self.clipped.store(0, Ordering::Relaxed);
+        self.samples.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Quietest, average and loudest RMS of a recording in dB, measured over
+/// 100 ms windows
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct InputLevel {
+    pub min_db: f32,
+    pub avg_db: f32,
+    pub peak_db: f32,
+}
+
+/// Measure the input level of `samples`, e.g. for a microphone check
+pub fn measure_input_level(samples: &[f32], sample_rate: u32) -> InputLevel {
+    let window = (sample_rate as usize * LEVEL_WINDOW_MS / 1000).max(1);
+    let to_db = |rms: f32| rms_to_db(rms).max(MIN_LEVEL_DB);
+
+    let mut min = f32::MAX;
+    let mut max = 0.0f32;
+    let mut sum = 0.0f32;
+    let mut windows = 0;
+    for chunk in samples.chunks(window) {
+        let rms = calculate_rms(chunk);
+        min = min.min(rms);
+        max = max.max(rms);
+        sum += rms;
+        windows += 1;
+    }
+
+    if windows == 0 {
+        return InputLevel {
+            min_db: MIN_LEVEL_DB,
+            avg_db: MIN_LEVEL_DB,
+            peak_db: MIN_LEVEL_DB,
+        };
+    }
+
+    InputLevel {
+        min_db: to_db(min),
+        avg_db: to_db(sum / windows as f32),
+        peak_db: to_db(max),
+    }
+}
 
 /// Configuration for silence detection auto-stop.
 #[derive(Debug, Clone)]
@@ -19,6 +143,8 @@ pub struct SilenceConfig {
     pub threshold: f32,
     /// Seconds of silence before auto-stop (0.5 to 5.0)
     pub duration_secs: f32,
+    /// Follow the background noise instead of using `threshold`
+    pub adaptive: bool,
 }
 
 impl Default for SilenceConfig {
@@ -27,26 +153,95 @@ impl Default for SilenceConfig {
             enabled: true,
             threshold: super::silence::DEFAULT_SILENCE_THRESHOLD,
             duration_secs: super::silence::DEFAULT_SILENCE_DURATION,
+            adaptive: false,
         }
     }
 }
 
+/// Silence detector running inside a capture.
+enum ActiveDetector {
+    Fixed(SilenceDetector),
+    Adaptive(AdaptiveSilenceDetector),
+}
+
+impl ActiveDetector {
+    fn new(silence_config: &SilenceConfig, sample_rate: u32) -> Option<Self> {
+        if !silence_config.enabled {
+            None
+        } else if silence_config.adaptive {
+            Some(Self::Adaptive(AdaptiveSilenceDetector::new(
+                silence_config.duration_secs,
+                sample_rate,
+            )))
+        } else {
+            Some(Self::Fixed(SilenceDetector::new(
+                silence_config.threshold,
+                silence_config.duration_secs,
+                sample_rate,
+            )))
+        }
+    }
+
+    fn process(&mut self, samples: &[f32]) -> bool {
+        match self {
+            Self::Fixed(detector) => detector.process(samples),
+            Self::Adaptive(detector) => detector.process_adaptive(samples),
+        }
+    }
+
+    fn has_speech(&self) -> bool {
+        match self {
+            Self::Fixed(detector) => detector.has_speech(),
+            Self::Adaptive(detector) => detector.has_speech(),
+        }
+    }
+}
+
+/// Change in whether a capture's input stream is open, reported from the
+/// capture thread
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamEvent {
+    Opened,
+    /// The stream closed, after `stop` or a silence auto-stop
+    Closed,
+    /// The stream never opened, e.g. because microphone access was denied
+    Failed(String),
+}
+
+/// Called from the capture thread with every `StreamEvent`
+pub type StreamObserver = Arc<dyn Fn(&StreamEvent) + Send + Sync>;
+
+/// Called from the audio callback with each block of samples
+type BlockCallback = Box<dyn FnMut(&[f32]) + Send>;
+
 /// Audio capture handle that can be sent across threads.
 /// The actual cpal::Stream runs in a dedicated thread.
 pub struct AudioCapture {
     buffer: Arc<Mutex<Vec<f32>>>,
-    is_recording: Arc<AtomicBool>,
+    /// Asks the capture thread to keep the stream open; cleared to stop it
+    keep_running: Arc<AtomicBool>,
+    /// Whether the stream is open right now. Only the capture thread writes
+    /// it, so it can't disagree with the device.
+    stream_active: Arc<AtomicBool>,
     silence_triggered: Arc<AtomicBool>,
     current_level: Arc<AtomicU32>,
+    level_meter: Arc<LevelMeter>,
     sample_rate: u32,
-    silence_config: SilenceConfig,
+    /// Name of the input device recorded from
+    device_name: String,
+    silence_config: Mutex<SilenceConfig>,
+    /// Detector used by the capture thread, swappable while recording
+    silence_detector: Arc<Mutex<Option<ActiveDetector>>>,
+    observer: Option<StreamObserver>,
+    worker: Mutex<Option<thread::JoinHandle<()>>>,
 }
 
-// Implement Send + Sync for AudioCapture
-// This is safe because we don't store the cpal::Stream directly,
-// instead it runs in a dedicated thread controlled by AtomicBool
-unsafe impl Send for AudioCapture {}
-unsafe impl Sync for AudioCapture {}
+// The cpal::Stream (which isn't Send) lives only on the capture thread, so
+// every field here is Send + Sync without any unsafe impls
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<AudioCapture>()
+};
 
 impl AudioCapture {
     /// Create a new audio capture with default settings.
@@ -61,26 +256,62 @@ impl AudioCapture {
             .default_input_device()
             .ok_or_else(|| anyhow!("No input device available"))?;
 
-        tracing::info!("Using input device: {}", device.name().unwrap_or_default());
+        let device_name = device.name().unwrap_or_else(|_| "Unknown".into());
+        tracing::info!("Using input device: {}", device_name);
         tracing::info!(
-            "Silence detection: enabled={}, threshold={:.4}, duration={:.1}s",
+            "Silence detection: enabled={}, threshold={:.4}, duration={:.1}s, adaptive={}",
             silence_config.enabled,
             silence_config.threshold,
-            silence_config.duration_secs
+            silence_config.duration_secs,
+            silence_config.adaptive
         );
 
-        Ok(Self {
+        Ok(Self::from_config(silence_config, device_name))
+    }
+
+    fn from_config(silence_config: SilenceConfig, device_name: String) -> Self {
+        Self {
             buffer: Arc::new(Mutex::new(Vec::new())),
-            is_recording: Arc::new(AtomicBool::new(false)),
+            keep_running: Arc::new(AtomicBool::new(false)),
+            stream_active: Arc::new(AtomicBool::new(false)),
             silence_triggered: Arc::new(AtomicBool::new(false)),
             current_level: Arc::new(AtomicU32::new(0)),
-            sample_rate: 16000, // Whisper expects 16kHz
-            silence_config,
-        })
+            level_meter: Arc::new(LevelMeter::default()),
+            sample_rate: CAPTURE_SAMPLE_RATE,
+            device_name,
+            silence_config: Mutex::new(silence_config),
+            silence_detector: Arc::new(Mutex::new(None)),
+            observer: None,
+            worker: Mutex::new(None),
+        }
     }
 
+    /// Report each `StreamEvent` to `observer`, e.g. to drive the
+    /// microphone-in-use indicator
+    pub fn with_observer(
+        mut self,
+        observer: impl Fn(&StreamEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Open the default input device and start recording. Returns once the
+    /// stream is open, or with the error that kept it from opening.
     pub fn start(&self) -> Result<()> {
-        if self.is_recording.load(Ordering::SeqCst) {
+        let sample_rate = self.sample_rate;
+        self.start_with(move |on_data| open_input_stream(sample_rate, on_data))
+    }
+
+    /// `start` with the stream opened by `open`, which runs on the capture
+    /// thread and is handed the callback for each block of samples. The
+    /// stream it returns is kept until the capture stops.
+    fn start_with<S, F>(&self, open: F) -> Result<()>
+    where
+        S: 'static,
+        F: FnOnce(BlockCallback) -> Result<S> + Send + 'static,
+    {
+        if self.keep_running.load(Ordering::SeqCst) || self.is_stream_active() {
             return Err(anyhow!("Already recording"));
         }
 
@@ -91,35 +322,198 @@ impl AudioCapture {
             buf.clear();
         }
         self.silence_triggered.store(false, Ordering::SeqCst);
-
-        self.is_recording.store(true, Ordering::SeqCst);
-
-        let buffer = Arc::clone(&self.buffer);
-        let is_recording = Arc::clone(&self.is_recording);
+        self.level_meter.reset();
+
+        // Fresh detector for each recording
+        let silence_enabled = {
+            let silence_config = self.silence_config()?;
+            let mut detector = self.silence_detector.lock()
+                .map_err(|e| anyhow!("Failed to acquire silence detector lock: {}", e))?;
+            *detector = ActiveDetector::new(&silence_config, self.sample_rate);
+            detector.is_some()
+        };
+
+        self.keep_running.store(true, Ordering::SeqCst);
+
+        let on_data = self.block_processor();
+        let keep_running = Arc::clone(&self.keep_running);
+        let stream_active = Arc::clone(&self.stream_active);
         let silence_triggered = Arc::clone(&self.silence_triggered);
-        let current_level = Arc::clone(&self.current_level);
+        let observer = self.observer.clone();
         let sample_rate = self.sample_rate;
-        let silence_config = self.silence_config.clone();
+        let (ready_tx, ready_rx) = std::sync::mpsc::channel();
 
         // Spawn a dedicated thread for audio capture
         // This keeps the non-Send cpal::Stream contained
-        thread::spawn(move || {
-            let result = run_capture_loop(
-                buffer,
-                is_recording,
-                silence_triggered,
-                current_level,
+        let worker = thread::spawn(move || {
+            let notify = |event: StreamEvent| {
+                if let Some(observer) = &observer {
+                    observer(&event);
+                }
+            };
+
+            let stream = match open(on_data) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    tracing::error!("Audio capture error: {}", e);
+                    keep_running.store(false, Ordering::SeqCst);
+                    notify(StreamEvent::Failed(e.to_string()));
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            };
+
+            stream_active.store(true, Ordering::SeqCst);
+            tracing::info!(
+                "Audio capture started at {}Hz (silence detection: {})",
                 sample_rate,
-                silence_config,
+                if silence_enabled { "enabled" } else { "disabled" }
             );
-            if let Err(e) = result {
-                tracing::error!("Audio capture error: {}", e);
+            notify(StreamEvent::Opened);
+            let _ = ready_tx.send(Ok(()));
+
+            // Keep the stream alive while recording
+            while keep_running.load(Ordering::SeqCst) {
+                thread::sleep(std::time::Duration::from_millis(10));
+            }
+            drop(stream);
+            stream_active.store(false, Ordering::SeqCst);
+
+            // Log reason for stop
+            if silence_triggered.load(Ordering::SeqCst) {
+                tracing::info!("Audio capture stopped (silence auto-stop)");
+            } else {
+                tracing::info!("Audio capture stopped (manual)");
             }
+            notify(StreamEvent::Closed);
         });
 
+        if let Ok(mut guard) = self.worker.lock() {
+            *guard = Some(worker);
+        }
+
+        match ready_rx.recv_timeout(STREAM_OPEN_TIMEOUT) {
+            Ok(result) => result,
+            Err(_) => {
+                // Still opening: close it as soon as it does
+                self.keep_running.store(false, Ordering::SeqCst);
+                Err(anyhow!("Timed out opening the microphone"))
+            }
+        }
+    }
+
+    /// Callback for each block of captured samples: buffers them, updates
+    /// the levels and runs silence detection
+    fn block_processor(&self) -> BlockCallback {
+        let buffer = Arc::clone(&self.buffer);
+        let keep_running = Arc::clone(&self.keep_running);
+        let silence_triggered = Arc::clone(&self.silence_triggered);
+        let current_level = Arc::clone(&self.current_level);
+        let level_meter = Arc::clone(&self.level_meter);
+        let silence_detector = Arc::clone(&self.silence_detector);
+
+        Box::new(move |data: &[f32]| {
+            // Store audio data - use try_lock to avoid blocking, and handle errors gracefully
+            match buffer.lock() {
+                Ok(mut buf) => {
+                    buf.extend_from_slice(data);
+                }
+                Err(e) => {
+                    // Log once and continue - don't panic in audio callback
+                    tracing::error!("Audio buffer lock poisoned, data lost: {}", e);
+                    return;
+                }
+            }
+
+            // Compute RMS level for visualization
+            let rms = calculate_rms(data);
+            current_level.store(rms.to_bits(), Ordering::Relaxed);
+            level_meter.record(data);
+
+            // Process through silence detector (may be swapped while recording)
+            match silence_detector.lock() {
+                Ok(mut detector) => {
+                    if let Some(detector) = detector.as_mut() {
+                        if detector.process(data) {
+                            // Silence duration exceeded - trigger auto-stop
+                            silence_triggered.store(true, Ordering::SeqCst);
+                            keep_running.store(false, Ordering::SeqCst);
+                        }
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("Silence detector lock poisoned: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Whether the input stream is open right now. Goes false as soon as
+    /// the stream closes for any reason, including a silence auto-stop.
+    pub fn is_stream_active(&self) -> bool {
+        self.stream_active.load(Ordering::SeqCst)
+    }
+
+    /// Current silence detection settings.
+    pub fn silence_config(&self) -> Result<SilenceConfig> {
+        self.silence_config.lock()
+            .map(|config| config.clone())
+            .map_err(|e| anyhow!("Failed to acquire silence config lock: {}", e))
+    }
+
+    /// Change silence detection settings, taking effect immediately if recording.
+    ///
+    /// A running detector keeps its progress (speech seen, current silent
+    /// stretch) and only swaps threshold and duration. Enabling detection
+    /// mid-recording starts a fresh detector; disabling drops it.
+    pub fn set_silence_config(&self, silence_config: SilenceConfig) -> Result<()> {
+        tracing::info!(
+            "Updating silence detection: enabled={}, threshold={:.4}, duration={:.1}s, adaptive={}",
+            silence_config.enabled,
+            silence_config.threshold,
+            silence_config.duration_secs,
+            silence_config.adaptive
+        );
+
+        if self.keep_running.load(Ordering::SeqCst) {
+            let mut detector = self.silence_detector.lock()
+                .map_err(|e| anyhow!("Failed to acquire silence detector lock: {}", e))?;
+            match (detector.as_mut(), silence_config.enabled, silence_config.adaptive) {
+                (Some(ActiveDetector::Fixed(running)), true, false) => {
+                    running.reconfigure(silence_config.threshold, silence_config.duration_secs);
+                }
+                (Some(ActiveDetector::Adaptive(running)), true, true) => {
+                    running.set_duration(silence_config.duration_secs);
+                }
+                _ => *detector = ActiveDetector::new(&silence_config, self.sample_rate),
+            }
+        }
+
+        *self.silence_config.lock()
+            .map_err(|e| anyhow!("Failed to acquire silence config lock: {}", e))? = silence_config;
+
         Ok(())
     }
 
+    /// Background noise floor in dB measured by adaptive silence detection,
+    /// or `None` when the capture isn't using it or hasn't heard audio yet.
+    pub fn noise_floor_db(&self) -> Option<f32> {
+        let detector = self.silence_detector.lock().ok()?;
+        match detector.as_ref()? {
+            ActiveDetector::Adaptive(adaptive) => {
+                Some(adaptive.noise_floor_db()).filter(|db| db.is_finite())
+            }
+            ActiveDetector::Fixed(_) => None,
+        }
+    }
+
+    /// Whether the silence detector has heard speech in this recording, or
+    /// `None` when silence detection is off.
+    pub fn has_speech(&self) -> Option<bool> {
+        let detector = self.silence_detector.lock().ok()?;
+        detector.as_ref().map(ActiveDetector::has_speech)
+    }
+
     /// Check if silence detection triggered an auto-stop.
     pub fn is_silence_triggered(&self) -> bool {
         self.silence_triggered.load(Ordering::SeqCst)
@@ -130,33 +524,121 @@ impl AudioCapture {
         f32::from_bits(self.current_level.load(Ordering::Relaxed))
     }
 
+    /// Peak and clipping of the recording so far.
+    pub fn levels(&self) -> RecordingLevels {
+        self.level_meter.levels()
+    }
+
+    /// Copy of the audio recorded so far, taken without stopping.
+    ///
+    /// This clones the whole buffer under the lock the capture thread writes
+    /// through, so it grows with the recording (about 3.8 MB a minute at
+    /// 16 kHz) and holds up the audio callback while copying. Don't call it
+    /// every frame: use `get_buffer_len` for progress or `levels` for a
+    /// meter.
+    pub fn get_buffer_snapshot(&self) -> Vec<f32> {
+        match self.buffer.lock() {
+            Ok(buffer) => buffer.clone(),
+            Err(e) => {
+                tracing::warn!("Audio buffer lock poisoned, no snapshot: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Number of samples recorded so far, without copying them.
+    pub fn get_buffer_len(&self) -> usize {
+        self.buffer.lock().map(|buffer| buffer.len()).unwrap_or(0)
+    }
+
     pub fn stop(self) -> Result<Vec<f32>> {
-        self.is_recording.store(false, Ordering::SeqCst);
+        self.stop_with_levels().map(|(buffer, _)| buffer)
+    }
+
+    /// Stop recording, returning the audio with its peak and clipping.
+    pub fn stop_with_levels(self) -> Result<(Vec<f32>, RecordingLevels)> {
+        self.keep_running.store(false, Ordering::SeqCst);
 
-        // Give the capture thread time to finish
-        thread::sleep(std::time::Duration::from_millis(100));
+        // Wait for the capture thread to close the stream
+        let worker = self.worker.lock().ok().and_then(|mut worker| worker.take());
+        if let Some(worker) = worker {
+            if worker.join().is_err() {
+                tracing::error!("Audio capture thread panicked");
+            }
+        }
 
         let buffer = self.buffer.lock()
             .map_err(|e| anyhow!("Failed to acquire audio buffer lock: {}", e))?
             .clone();
-        tracing::info!("Captured {} samples", buffer.len());
+        let levels = self.level_meter.levels();
+        tracing::info!(
+            "Captured {} samples ({:.2}s), peak {:.3}, {:.3}% clipped",
+            buffer.len(),
+            audio_duration_secs(buffer.len(), self.sample_rate),
+            levels.peak,
+            levels.clipped_ratio * 100.0
+        );
 
-        Ok(buffer)
+        Ok((buffer, levels))
     }
 
     pub fn sample_rate(&self) -> u32 {
         self.sample_rate
     }
+
+    /// Name of the input device this capture records from
+    pub fn device_name(&self) -> &str {
+        &self.device_name
+    }
 }
 
-fn run_capture_loop(
-    buffer: Arc<Mutex<Vec<f32>>>,
-    is_recording: Arc<AtomicBool>,
-    silence_triggered: Arc<AtomicBool>,
-    current_level: Arc<AtomicU32>,
-    sample_rate: u32,
-    silence_config: SilenceConfig,
-) -> Result<()> {
+/// A microphone or other audio input the system offers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AudioDeviceInfo {
+    pub name: String,
+    /// Whether this is the system's default input, which recordings use
+    pub is_default: bool,
+}
+
+/// The audio input devices of the default host, with the default marked
+pub fn list_input_devices() -> Result<Vec<AudioDeviceInfo>> {
+    let host = cpal::default_host();
+    let default_name = host.default_input_device().and_then(|d| d.name().ok());
+
+    let devices = host
+        .input_devices()?
+        .map(|device| {
+            let name = device.name().unwrap_or_else(|_| "Unknown".into());
+            AudioDeviceInfo {
+                is_default: default_name.as_deref() == Some(name.as_str()),
+                name,
+            }
+        })
+        .collect();
+    Ok(devices)
+}
+
+/// Record `duration` of audio from the default input device, blocking until done.
+/// Silence detection is off so the whole duration is captured.
+pub fn record_for(duration: std::time::Duration) -> Result<Vec<f32>> {
+    let capture = AudioCapture::with_silence_config(SilenceConfig {
+        enabled: false,
+        ..SilenceConfig::default()
+    })?;
+    capture.start()?;
+    thread::sleep(duration);
+    capture.stop()
+}
+
+/// A capture dropped without `stop` still releases the microphone
+impl Drop for AudioCapture {
+    fn drop(&mut self) {
+        self.keep_running.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Open and start a mono input stream on the default device
+fn open_input_stream(sample_rate: u32, mut on_data: BlockCallback) -> Result<cpal::Stream> {
     let host = cpal::default_host();
     let device = host
         .default_input_device()
@@ -168,57 +650,9 @@ fn run_capture_loop(
         buffer_size: cpal::BufferSize::Default,
     };
 
-    let buffer_clone = Arc::clone(&buffer);
-    let silence_triggered_clone = Arc::clone(&silence_triggered);
-    let is_recording_clone = Arc::clone(&is_recording);
-    let current_level_clone = Arc::clone(&current_level);
-
-    // Create silence detector if enabled
-    let silence_detector = if silence_config.enabled {
-        Some(Mutex::new(SilenceDetector::new(
-            silence_config.threshold,
-            silence_config.duration_secs,
-            sample_rate,
-        )))
-    } else {
-        None
-    };
-
     let stream = device.build_input_stream(
         &config,
-        move |data: &[f32], _: &cpal::InputCallbackInfo| {
-            // Store audio data - use try_lock to avoid blocking, and handle errors gracefully
-            match buffer_clone.lock() {
-                Ok(mut buf) => {
-                    buf.extend_from_slice(data);
-                }
-                Err(e) => {
-                    // Log once and continue - don't panic in audio callback
-                    tracing::error!("Audio buffer lock poisoned, data lost: {}", e);
-                    return;
-                }
-            }
-
-            // Compute RMS level for visualization
-            let rms = super::silence::calculate_rms(data);
-            current_level_clone.store(rms.to_bits(), Ordering::Relaxed);
-
-            // Process through silence detector
-            if let Some(ref detector_mutex) = silence_detector {
-                match detector_mutex.lock() {
-                    Ok(mut detector) => {
-                        if detector.process(data) {
-                            // Silence duration exceeded - trigger auto-stop
-                            silence_triggered_clone.store(true, Ordering::SeqCst);
-                            is_recording_clone.store(false, Ordering::SeqCst);
-                        }
-                    }
-                    Err(e) => {
-                        tracing::error!("Silence detector lock poisoned: {}", e);
-                    }
-                }
-            }
-        },
+        move |data: &[f32], _: &cpal::InputCallbackInfo| on_data(data),
         |err| {
             tracing::error!("Audio stream error: {}", err);
         },
@@ -226,23 +660,172 @@ fn run_capture_loop(
     )?;
 
     stream.play()?;
-    tracing::info!(
-        "Audio capture started at {}Hz (silence detection: {})",
-        sample_rate,
-        if silence_config.enabled { "enabled" } else { "disabled" }
-    );
+    Ok(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // Keep the stream alive while recording
-    while is_recording.load(Ordering::SeqCst) {
-        thread::sleep(std::time::Duration::from_millis(10));
+    #[test]
+    fn test_level_meter_tracks_peak_and_clipping() {
+        let meter = LevelMeter::default();
+        meter.record(&[0.1, -0.5, 0.2, 0.0]);
+        meter.record(&[1.0, -0.995, 0.3, 0.1]);
+
+        let levels = meter.levels();
+        assert_eq!(levels.peak, 1.0);
+        assert_eq!(levels.clipped_ratio, 0.25);
+        assert!(levels.is_clipping());
+
+        meter.reset();
+        assert_eq!(meter.levels(), RecordingLevels::default());
     }
 
-    // Log reason for stop
-    if silence_triggered.load(Ordering::SeqCst) {
-        tracing::info!("Audio capture stopped (silence auto-stop)");
-    } else {
-        tracing::info!("Audio capture stopped (manual)");
+    #[test]
+    fn test_level_meter_clean_signal() {
+        let meter = LevelMeter::default();
+        meter.record(&[0.4; 1600]);
+        let levels = meter.levels();
+        assert_eq!(levels.clipped_ratio, 0.0);
+        assert!(!levels.is_clipping());
     }
 
-    Ok(())
+    #[test]
+    fn test_measure_input_level() {
+        // 100 ms of silence then 100 ms at a constant 0.1
+        let mut samples = vec![0.0; 1600];
+        samples.extend(vec![0.1; 1600]);
+
+        let level = measure_input_level(&samples, CAPTURE_SAMPLE_RATE);
+        assert_eq!(level.min_db, MIN_LEVEL_DB);
+        assert!((level.peak_db - -20.0).abs() < 0.01);
+        assert!((level.avg_db - rms_to_db(0.05)).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_measure_input_level_empty() {
+        let level = measure_input_level(&[], CAPTURE_SAMPLE_RATE);
+        assert_eq!(level.peak_db, MIN_LEVEL_DB);
+    }
+
+    /// Capture that records its stream events, with silence detection off
+    fn observed_capture(
+        silence_config: SilenceConfig,
+    ) -> (AudioCapture, Arc<Mutex<Vec<StreamEvent>>>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let capture = AudioCapture::from_config(silence_config, "Test microphone".to_string())
+            .with_observer(move |event| sink.lock().unwrap().push(event.clone()));
+        (capture, events)
+    }
+
+    fn no_silence_detection() -> SilenceConfig {
+        SilenceConfig {
+            enabled: false,
+            ..SilenceConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_stream_open_failure() {
+        let (capture, events) = observed_capture(no_silence_detection());
+
+        // What a denied microphone permission looks like from cpal
+        let err = capture
+            .start_with(|_| Err::<(), _>(anyhow!("Microphone access denied")))
+            .unwrap_err();
+
+        assert_eq!(err.to_string(), "Microphone access denied");
+        assert!(!capture.is_stream_active());
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![StreamEvent::Failed("Microphone access denied".to_string())]
+        );
+
+        // Nothing is left half-started, so trying again isn't "Already recording"
+        capture.start_with(|_| Ok(())).unwrap();
+        assert!(capture.is_stream_active());
+        capture.stop().unwrap();
+    }
+
+    #[test]
+    fn test_stream_state_follows_stream() {
+        let (capture, events) = observed_capture(no_silence_detection());
+
+        capture
+            .start_with(|mut on_data| {
+                on_data(&[0.2; 160]);
+                Ok(())
+            })
+            .unwrap();
+        assert!(capture.is_stream_active());
+        assert!(capture.start_with(|_| Ok(())).is_err());
+
+        let stream_active = Arc::clone(&capture.stream_active);
+        let samples = capture.stop().unwrap();
+
+        assert_eq!(samples.len(), 160);
+        assert!(!stream_active.load(Ordering::SeqCst));
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![StreamEvent::Opened, StreamEvent::Closed]
+        );
+    }
+
+    #[test]
+    fn test_buffer_snapshot_while_recording() {
+        let (capture, _) = observed_capture(no_silence_detection());
+        assert_eq!(capture.get_buffer_len(), 0);
+        assert!(capture.get_buffer_snapshot().is_empty());
+
+        capture
+            .start_with(|mut on_data| {
+                on_data(&[0.1; 160]);
+                on_data(&[0.2; 80]);
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(capture.get_buffer_len(), 240);
+        let snapshot = capture.get_buffer_snapshot();
+        assert_eq!(snapshot.len(), 240);
+        assert_eq!(snapshot[200], 0.2);
+        // Reading doesn't drain: stopping still returns everything
+        assert!(capture.is_stream_active());
+        assert_eq!(capture.stop().unwrap(), snapshot);
+    }
+
+    #[test]
+    fn test_silence_auto_stop_closes_stream() {
+        let (capture, events) = observed_capture(SilenceConfig {
+            enabled: true,
+            threshold: 0.01,
+            duration_secs: 0.5,
+            adaptive: false,
+        });
+
+        // Speech, then a second of silence
+        capture
+            .start_with(|mut on_data| {
+                on_data(&[0.3; 1600]);
+                on_data(&[0.0; 16000]);
+                Ok(())
+            })
+            .unwrap();
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(2);
+        while capture.is_stream_active() && std::time::Instant::now() < deadline {
+            thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        assert!(!capture.is_stream_active());
+        assert!(capture.is_silence_triggered());
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![StreamEvent::Opened, StreamEvent::Closed]
+        );
+        // The audio is still there to collect
+        assert_eq!(capture.stop().unwrap().len(), 17600);
+    }
 }
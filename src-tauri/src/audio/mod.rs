@@ -2,3 +2,4 @@ pub mod capture;
 pub mod playback;
 pub mod processing;
 pub mod silence;
+pub mod speech_cache;
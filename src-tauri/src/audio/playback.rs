@@ -2,17 +2,49 @@
 
 use anyhow::Result;
 use rodio::{buffer::SamplesBuffer, OutputStream, Sink};
+use serde::Serialize;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
-    Arc,
+    Arc, Mutex,
 };
 use std::thread;
+use std::time::Instant;
+
+/// How far playback of the current audio has got
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PlaybackProgress {
+    /// Seconds played so far
+    pub elapsed: f32,
+    /// Length of the audio in seconds
+    pub total: f32,
+    pub percentage: u8,
+}
+
+impl PlaybackProgress {
+    fn new(elapsed: f32, total: f32) -> Self {
+        let elapsed = elapsed.clamp(0.0, total.max(0.0));
+        let percentage = if total > 0.0 {
+            (elapsed / total * 100.0).round() as u8
+        } else {
+            0
+        };
+        Self {
+            elapsed,
+            total,
+            percentage,
+        }
+    }
+}
 
 /// Audio player that can be safely sent across threads.
 /// Playback runs in a dedicated thread.
 pub struct AudioPlayer {
     is_playing: Arc<AtomicBool>,
     should_stop: Arc<AtomicBool>,
+    /// When the current audio started playing
+    play_start: Arc<Mutex<Option<Instant>>>,
+    /// Length of the current audio, worked out when it is handed to `play`
+    duration_secs: Mutex<f32>,
 }
 
 impl AudioPlayer {
@@ -20,6 +52,8 @@ impl AudioPlayer {
         Ok(Self {
             is_playing: Arc::new(AtomicBool::new(false)),
             should_stop: Arc::new(AtomicBool::new(false)),
+            play_start: Arc::new(Mutex::new(None)),
+            duration_secs: Mutex::new(0.0),
         })
     }
 
@@ -30,6 +64,12 @@ impl AudioPlayer {
 
         should_stop.store(false, Ordering::SeqCst);
         is_playing.store(true, Ordering::SeqCst);
+        if let Ok(mut duration) = self.duration_secs.lock() {
+            *duration = samples.len() as f32 / sample_rate.max(1) as f32;
+        }
+        if let Ok(mut start) = self.play_start.lock() {
+            *start = Some(Instant::now());
+        }
 
         // Spawn playback in a dedicated thread
         thread::spawn(move || {
@@ -54,6 +94,53 @@ impl AudioPlayer {
     pub fn is_playing(&self) -> bool {
         self.is_playing.load(Ordering::SeqCst)
     }
+
+    /// Seconds since `play` was called, capped at the audio's length; 0
+    /// before anything was played
+    pub fn elapsed_secs(&self) -> f32 {
+        let started = self.play_start.lock().ok().and_then(|start| *start);
+        match started {
+            Some(start) => start
+                .elapsed()
+                .as_secs_f32()
+                .min(self.total_duration_secs()),
+            None => 0.0,
+        }
+    }
+
+    /// Length in seconds of the audio last handed to `play`
+    pub fn total_duration_secs(&self) -> f32 {
+        self.duration_secs.lock().map(|d| *d).unwrap_or(0.0)
+    }
+
+    /// Progress through the current audio, `None` when nothing is playing
+    pub fn playback_progress(&self) -> Option<PlaybackProgress> {
+        self.is_playing()
+            .then(|| PlaybackProgress::new(self.elapsed_secs(), self.total_duration_secs()))
+    }
+}
+
+/// Sample rate of `completion_chime`
+pub const CHIME_SAMPLE_RATE: u32 = 24000;
+
+/// A short rising two-note chime, for confirming something happened in
+/// another app
+pub fn completion_chime() -> Vec<f32> {
+    // E6 then A6 in Hz, with their lengths in milliseconds
+    const NOTES: [(f32, u32); 2] = [(1318.5, 70), (1760.0, 120)];
+    const VOLUME: f32 = 0.25;
+
+    let rate = CHIME_SAMPLE_RATE as f32;
+    let mut samples = Vec::new();
+    for (frequency, ms) in NOTES {
+        let len = (CHIME_SAMPLE_RATE * ms / 1000) as usize;
+        samples.extend((0..len).map(|i| {
+            let t = i as f32 / rate;
+            let fade = 1.0 - i as f32 / len as f32;
+            (2.0 * std::f32::consts::PI * frequency * t).sin() * VOLUME * fade * fade
+        }));
+    }
+    samples
 }
 
 fn play_audio_sync(
@@ -83,3 +170,39 @@ fn play_audio_sync(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_playback_progress_percentage() {
+        let progress = PlaybackProgress::new(2.5, 10.0);
+        assert_eq!(progress.elapsed, 2.5);
+        assert_eq!(progress.total, 10.0);
+        assert_eq!(progress.percentage, 25);
+
+        // Clamped to the audio's length
+        assert_eq!(PlaybackProgress::new(12.0, 10.0).percentage, 100);
+        assert_eq!(PlaybackProgress::new(12.0, 10.0).elapsed, 10.0);
+        assert_eq!(PlaybackProgress::new(1.0, 0.0).percentage, 0);
+    }
+
+    #[test]
+    fn test_completion_chime() {
+        let chime = completion_chime();
+        assert_eq!(chime.len(), CHIME_SAMPLE_RATE as usize * 190 / 1000);
+        let peak = chime.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert!(peak > 0.1 && peak <= 0.25, "peak {}", peak);
+        // Fades out rather than ending on a click
+        assert!(chime.last().unwrap().abs() < 0.001);
+    }
+
+    #[test]
+    fn test_idle_player_has_no_progress() {
+        let player = AudioPlayer::new().unwrap();
+        assert_eq!(player.elapsed_secs(), 0.0);
+        assert_eq!(player.total_duration_secs(), 0.0);
+        assert_eq!(player.playback_progress(), None);
+    }
+}
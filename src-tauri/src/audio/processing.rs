@@ -4,7 +4,16 @@
 
 #![allow(dead_code)]
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::DecoderOptions;
+use symphonia::core::errors::Error as DecodeError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+use crate::engines::STT_SAMPLE_RATE;
 
 /// Convert audio samples from one sample rate to another
 pub fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
@@ -30,6 +39,24 @@ pub fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
     resampled
 }
 
+/// Duration in seconds of `num_samples` mono samples at `sample_rate`.
+/// Returns 0.0 for a zero sample rate.
+pub fn audio_duration_secs(num_samples: usize, sample_rate: u32) -> f32 {
+    if sample_rate == 0 {
+        return 0.0;
+    }
+    num_samples as f32 / sample_rate as f32
+}
+
+/// Duration in whole milliseconds of `num_samples` mono samples at
+/// `sample_rate`. Returns 0 for a zero sample rate.
+pub fn audio_duration_ms(num_samples: usize, sample_rate: u32) -> u64 {
+    if sample_rate == 0 {
+        return 0;
+    }
+    num_samples as u64 * 1000 / sample_rate as u64
+}
+
 /// Convert stereo audio to mono by averaging channels
 pub fn stereo_to_mono(samples: &[f32]) -> Vec<f32> {
     samples
@@ -44,13 +71,34 @@ pub fn stereo_to_mono(samples: &[f32]) -> Vec<f32> {
         .collect()
 }
 
-/// Normalize audio samples to [-1.0, 1.0] range
+/// Normalize audio samples to [-1.0, 1.0] range (peak of 1.0).
+/// Prefer [`normalize_peak`] with an explicit target.
 pub fn normalize(samples: &mut [f32]) {
+    normalize_peak(samples, 1.0);
+}
+
+/// Scale samples so the loudest one has absolute value `target_peak`.
+/// Silent input is left unchanged.
+pub fn normalize_peak(samples: &mut [f32], target_peak: f32) {
     let max = samples.iter().map(|s| s.abs()).fold(0.0f32, f32::max);
-    if max > 0.0 && max != 1.0 {
-        for sample in samples.iter_mut() {
-            *sample /= max;
-        }
+    if max > 0.0 && max != target_peak {
+        scale(samples, target_peak / max);
+    }
+}
+
+/// Scale samples so their RMS level is `target_rms`.
+/// Silent input is left unchanged. Peaks are not limited, so a high target
+/// on very dynamic audio can push samples past 1.0.
+pub fn normalize_rms(samples: &mut [f32], target_rms: f32) {
+    let rms = super::silence::calculate_rms(samples);
+    if rms > 0.0 && rms != target_rms {
+        scale(samples, target_rms / rms);
+    }
+}
+
+fn scale(samples: &mut [f32], gain: f32) {
+    for sample in samples.iter_mut() {
+        *sample *= gain;
     }
 }
 
@@ -70,6 +118,17 @@ pub fn f32_to_i16(samples: &[f32]) -> Vec<i16> {
         .collect()
 }
 
+/// Widen f32 samples to f64, for processing that would accumulate rounding
+/// error in single precision
+pub fn f32_to_f64(samples: &[f32]) -> Vec<f64> {
+    samples.iter().map(|&s| s as f64).collect()
+}
+
+/// Narrow f64 samples back to f32, clamped to [-1.0, 1.0]
+pub fn f64_to_f32(samples: &[f64]) -> Vec<f32> {
+    samples.iter().map(|&s| s.clamp(-1.0, 1.0) as f32).collect()
+}
+
 /// Write audio samples to a WAV file
 pub fn write_wav(path: &std::path::Path, samples: &[f32], sample_rate: u32) -> Result<()> {
     let spec = hound::WavSpec {
@@ -116,3 +175,225 @@ pub fn read_wav(path: &std::path::Path) -> Result<(Vec<f32>, u32)> {
 
     Ok((samples, sample_rate))
 }
+
+/// Decode an MP3 file into mono samples resampled to 16 kHz.
+///
+/// Frames that fail to decode are skipped, like a player would, so a file
+/// with a damaged frame or two still transcribes.
+pub fn read_mp3(path: &std::path::Path) -> Result<(Vec<f32>, u32)> {
+    let file = std::fs::File::open(path)?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    hint.with_extension("mp3");
+
+    let probed = symphonia::default::get_probe().format(
+        &hint,
+        stream,
+        &FormatOptions::default(),
+        &MetadataOptions::default(),
+    )?;
+    let mut format = probed.format;
+    let track = format
+        .default_track()
+        .ok_or_else(|| anyhow!("No audio track in {:?}", path))?;
+    let track_id = track.id;
+    let mut decoder =
+        symphonia::default::get_codecs().make(&track.codec_params, &DecoderOptions::default())?;
+
+    let mut interleaved = Vec::new();
+    let mut channels = 1;
+    let mut sample_rate = track.codec_params.sample_rate.unwrap_or(STT_SAMPLE_RATE);
+
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            // The reader signals the end of the file with an EOF error
+            Err(DecodeError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        match decoder.decode(&packet) {
+            Ok(decoded) => {
+                let spec = *decoded.spec();
+                channels = spec.channels.count();
+                sample_rate = spec.rate;
+
+                let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+                buffer.copy_interleaved_ref(decoded);
+                interleaved.extend_from_slice(buffer.samples());
+            }
+            Err(DecodeError::DecodeError(e)) => {
+                tracing::warn!("Skipping undecodable MP3 frame in {:?}: {}", path, e);
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    let samples = if channels == 2 {
+        stereo_to_mono(&interleaved)
+    } else {
+        interleaved
+    };
+
+    Ok((
+        resample(&samples, sample_rate, STT_SAMPLE_RATE),
+        STT_SAMPLE_RATE,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::silence::calculate_rms;
+
+    fn fixture(name: &str) -> std::path::PathBuf {
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name)
+    }
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples.iter().map(|s| s.abs()).fold(0.0f32, f32::max)
+    }
+
+    #[test]
+    fn test_audio_duration_secs() {
+        assert!((audio_duration_secs(16000, 16000) - 1.0).abs() < f32::EPSILON);
+        assert!((audio_duration_secs(24000, 48000) - 0.5).abs() < f32::EPSILON);
+        assert_eq!(audio_duration_secs(0, 16000), 0.0);
+        assert_eq!(audio_duration_secs(16000, 0), 0.0);
+    }
+
+    #[test]
+    fn test_audio_duration_ms() {
+        assert_eq!(audio_duration_ms(16000, 16000), 1000);
+        assert_eq!(audio_duration_ms(24000, 16000), 1500);
+        // Partial milliseconds are dropped
+        assert_eq!(audio_duration_ms(1, 16000), 0);
+        assert_eq!(audio_duration_ms(16000, 0), 0);
+    }
+
+    #[test]
+    fn test_normalize_peak_quiet_input() {
+        let mut samples = vec![0.1, -0.2, 0.05];
+        normalize_peak(&mut samples, 0.8);
+
+        assert!((peak(&samples) - 0.8).abs() < 1e-6);
+        // Relative levels are preserved
+        assert!((samples[0] - 0.4).abs() < 1e-6);
+        assert!((samples[1] + 0.8).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_normalize_peak_loud_input() {
+        let mut samples = vec![1.5, -3.0, 0.75];
+        normalize_peak(&mut samples, 0.5);
+
+        assert!((peak(&samples) - 0.5).abs() < 1e-6);
+        assert!((samples[0] - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_normalize_peak_silence_unchanged() {
+        let mut samples = vec![0.0; 100];
+        normalize_peak(&mut samples, 0.9);
+        assert!(samples.iter().all(|&s| s == 0.0));
+
+        let mut empty: Vec<f32> = Vec::new();
+        normalize_peak(&mut empty, 0.9);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_normalize_is_unit_peak() {
+        let mut samples = vec![0.25, -0.5];
+        normalize(&mut samples);
+        assert!((peak(&samples) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_normalize_rms() {
+        for amplitude in [0.01, 0.3, 2.0] {
+            let mut samples: Vec<f32> = (0..1600)
+                .map(|i| amplitude * (i as f32 * 0.1).sin())
+                .collect();
+            normalize_rms(&mut samples, 0.1);
+            assert!((calculate_rms(&samples) - 0.1).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_normalize_rms_constant_signal() {
+        let mut samples = vec![-0.5; 100];
+        normalize_rms(&mut samples, 0.2);
+        assert!(samples.iter().all(|&s| (s + 0.2).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_normalize_rms_silence_unchanged() {
+        let mut samples = vec![0.0; 100];
+        normalize_rms(&mut samples, 0.1);
+        assert!(samples.iter().all(|&s| s == 0.0));
+    }
+
+    /// One second of a decaying 220 Hz tone
+    fn tone() -> Vec<f32> {
+        (0..16000)
+            .map(|i| {
+                let t = i as f32 / 16000.0;
+                (t * 220.0 * std::f32::consts::TAU).sin() * 0.8 * (1.0 - t)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_f64_round_trip() {
+        let samples = tone();
+        let round_trip = f64_to_f32(&f32_to_f64(&samples));
+        for (original, converted) in samples.iter().zip(&round_trip) {
+            assert!((original - converted).abs() <= f32::EPSILON);
+        }
+
+        assert_eq!(f64_to_f32(&[1.5, -2.0, 0.25]), vec![1.0, -1.0, 0.25]);
+    }
+
+    #[test]
+    fn test_read_mp3_mono() {
+        let (samples, sample_rate) = read_mp3(&fixture("tone_mono.mp3")).unwrap();
+
+        assert_eq!(sample_rate, STT_SAMPLE_RATE);
+        // 24 frames of 1152 samples at 44.1 kHz
+        let expected = 24 * 1152 * 16000 / 44100;
+        assert!(
+            samples.len().abs_diff(expected) < 1152,
+            "{} samples",
+            samples.len()
+        );
+        // The fixture is a steady tone, not silence
+        assert!(calculate_rms(&samples) > 0.1);
+    }
+
+    #[test]
+    fn test_read_mp3_stereo_downmix() {
+        let (mono, _) = read_mp3(&fixture("tone_mono.mp3")).unwrap();
+        let (stereo, sample_rate) = read_mp3(&fixture("tone_stereo.mp3")).unwrap();
+
+        // Both channels carry the mono fixture's signal
+        assert_eq!(sample_rate, STT_SAMPLE_RATE);
+        assert_eq!(stereo.len(), mono.len());
+        assert!(stereo.iter().zip(&mono).all(|(s, m)| (s - m).abs() < 1e-4));
+    }
+
+    #[test]
+    fn test_read_mp3_not_an_mp3() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.mp3");
+        std::fs::write(&path, b"not audio at all").unwrap();
+
+        assert!(read_mp3(&path).is_err());
+        assert!(read_mp3(&dir.path().join("missing.mp3")).is_err());
+    }
+}
@@ -1,10 +1,18 @@
 //! Silence detection for automatic recording stop.
 //!
 //! Uses RMS (Root Mean Square) to detect audio levels and track
-//! consecutive silent frames to determine when speech has ended.
+//! consecutive silent frames to determine when speech has ended. Audio is
+//! measured in fixed 20 ms frames rather than per callback, so the result
+//! doesn't depend on the buffer size the audio device happens to use.
 
 #![allow(dead_code)]
 
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use super::processing::audio_duration_secs;
+
 /// Default silence threshold in RMS units (0.0 to 1.0)
 /// 0.01 is a reasonable default for typical microphone input
 pub const DEFAULT_SILENCE_THRESHOLD: f32 = 0.01;
@@ -24,6 +32,32 @@ pub const MIN_SILENCE_DURATION: f32 = 0.5;
 /// Maximum allowed silence duration (seconds)
 pub const MAX_SILENCE_DURATION: f32 = 5.0;
 
+/// Multiplier applied to the ambient noise level when calibrating
+pub const CALIBRATION_HEADROOM: f32 = 2.0;
+
+/// Multiplier applied to the ambient RMS by a noise profile; a little more
+/// than calibration, since the profile measures the whole recording rather
+/// than averaging windows
+pub const NOISE_PROFILE_HEADROOM: f32 = 2.5;
+
+/// Level reported for digital silence, so `db` stays a finite number
+const NOISE_PROFILE_FLOOR_DB: f32 = -100.0;
+
+/// Length (seconds) of the frames the silence detector classifies
+pub const SILENCE_FRAME_SECS: f32 = 0.02;
+
+/// Window length (seconds) over which RMS is measured during calibration
+const CALIBRATION_WINDOW_SECS: f32 = 0.1;
+
+/// Seconds of audio the adaptive noise floor is estimated over
+pub const ADAPTIVE_WINDOW_SECS: f32 = 5.0;
+
+/// Percentile of recent chunk levels taken as the noise floor
+pub const NOISE_FLOOR_PERCENTILE: f32 = 0.25;
+
+/// Multiplier applied to the noise floor to get the adaptive threshold
+pub const ADAPTIVE_THRESHOLD_MULTIPLIER: f32 = 3.0;
+
 /// Silence detector that tracks audio levels and detects extended silence.
 #[derive(Debug, Clone)]
 pub struct SilenceDetector {
@@ -33,7 +67,13 @@ pub struct SilenceDetector {
     sample_rate: u32,
     /// Number of consecutive silent samples needed to trigger
     samples_needed: usize,
-    /// Current count of consecutive silent samples
+    /// Samples in each classified frame
+    frame_len: usize,
+    /// Sum of squares of the samples in the frame being filled
+    frame_sum_squares: f32,
+    /// Samples in the frame being filled
+    frame_filled: usize,
+    /// Current count of consecutive silent samples, in whole frames
     silent_samples: usize,
     /// Whether speech has been detected at least once
     speech_detected: bool,
@@ -59,6 +99,9 @@ impl SilenceDetector {
             threshold: clamped_threshold,
             sample_rate,
             samples_needed,
+            frame_len: ((sample_rate as f32 * SILENCE_FRAME_SECS) as usize).max(1),
+            frame_sum_squares: 0.0,
+            frame_filled: 0,
             silent_samples: 0,
             speech_detected: false,
             triggered: false,
@@ -72,6 +115,10 @@ impl SilenceDetector {
 
     /// Process a chunk of audio samples and return whether auto-stop should trigger.
     ///
+    /// Samples are collected into 20 ms frames and each complete frame is
+    /// classified, so a partial frame carries over to the next chunk and
+    /// chunks of any size trigger at the same point in the audio.
+    ///
     /// # Arguments
     /// * `samples` - Audio samples (f32, mono)
     ///
@@ -83,28 +130,51 @@ impl SilenceDetector {
             return self.triggered;
         }
 
-        let rms = calculate_rms(samples);
-        let is_silent = rms < self.threshold;
-
-        if is_silent {
-            self.silent_samples += samples.len();
-
-            // Only trigger if we've detected speech before
-            // This prevents triggering on initial silence before user speaks
-            if self.speech_detected && self.silent_samples >= self.samples_needed {
-                self.triggered = true;
-                tracing::info!(
-                    "Silence detected for {:.1}s (threshold: {:.4}), triggering auto-stop",
-                    self.silent_samples as f32 / self.sample_rate as f32,
-                    self.threshold
-                );
+        // A silent stretch may already be long enough after `reconfigure`
+        if self.check_trigger() {
+            return true;
+        }
+
+        for &sample in samples {
+            self.frame_sum_squares += sample * sample;
+            self.frame_filled += 1;
+            if self.frame_filled == self.frame_len && self.finish_frame() {
+                break;
             }
+        }
+
+        self.triggered
+    }
+
+    /// Classify the frame that was just filled and start a new one.
+    /// Returns whether auto-stop triggered.
+    fn finish_frame(&mut self) -> bool {
+        let rms = (self.frame_sum_squares / self.frame_len as f32).sqrt();
+        self.frame_sum_squares = 0.0;
+        self.frame_filled = 0;
+
+        if rms < self.threshold {
+            self.silent_samples += self.frame_len;
+            self.check_trigger()
         } else {
             // Speech detected - reset silence counter
             self.silent_samples = 0;
             self.speech_detected = true;
+            false
         }
+    }
 
+    fn check_trigger(&mut self) -> bool {
+        // Only trigger if we've detected speech before
+        // This prevents triggering on initial silence before user speaks
+        if self.speech_detected && self.silent_samples >= self.samples_needed {
+            self.triggered = true;
+            tracing::info!(
+                "Silence detected for {:.1}s (threshold: {:.4}), triggering auto-stop",
+                audio_duration_secs(self.silent_samples, self.sample_rate),
+                self.threshold
+            );
+        }
         self.triggered
     }
 
@@ -120,7 +190,7 @@ impl SilenceDetector {
 
     /// Get the current silence duration in seconds.
     pub fn silence_duration(&self) -> f32 {
-        self.silent_samples as f32 / self.sample_rate as f32
+        audio_duration_secs(self.silent_samples, self.sample_rate)
     }
 
     /// Get the current RMS threshold.
@@ -128,14 +198,176 @@ impl SilenceDetector {
         self.threshold
     }
 
+    /// Set the threshold from a recording of ambient noise.
+    ///
+    /// The threshold becomes the mean RMS of 100 ms windows with 2× headroom,
+    /// clamped to the allowed range. Returns the new threshold in dB.
+    pub fn calibrate(&mut self, ambient: &[f32]) -> f32 {
+        let window = ((self.sample_rate as f32 * CALIBRATION_WINDOW_SECS) as usize).max(1);
+        let levels: Vec<f32> = ambient.chunks(window).map(calculate_rms).collect();
+        let mean = if levels.is_empty() {
+            0.0
+        } else {
+            levels.iter().sum::<f32>() / levels.len() as f32
+        };
+
+        self.threshold = suggest_threshold(mean);
+        tracing::info!(
+            "Calibrated silence threshold: ambient RMS {:.4}, threshold {:.4} ({:.1} dB)",
+            mean,
+            self.threshold,
+            rms_to_db(self.threshold)
+        );
+
+        rms_to_db(self.threshold)
+    }
+
+    /// Record `duration_secs` of ambient noise from the default microphone and
+    /// calibrate from it. Blocks for the length of the recording, so call it
+    /// from a blocking thread.
+    pub fn calibrate_from_microphone(&mut self, duration_secs: f32) -> Result<f32> {
+        let duration = std::time::Duration::from_secs_f32(duration_secs.max(0.1));
+        let ambient = super::capture::record_for(duration)?;
+        Ok(self.calibrate(&ambient))
+    }
+
+    /// Change threshold and duration without losing the detector's progress.
+    ///
+    /// Values are clamped as in [`SilenceDetector::new`]. A silent stretch that
+    /// already exceeds a shorter new duration triggers on the next chunk.
+    pub fn reconfigure(&mut self, threshold: f32, duration_secs: f32) {
+        let clamped_duration = duration_secs.clamp(MIN_SILENCE_DURATION, MAX_SILENCE_DURATION);
+
+        self.threshold = threshold.clamp(MIN_SILENCE_THRESHOLD, MAX_SILENCE_THRESHOLD);
+        self.samples_needed = (self.sample_rate as f32 * clamped_duration) as usize;
+    }
+
     /// Reset the detector state (but keep configuration).
     pub fn reset(&mut self) {
+        self.frame_sum_squares = 0.0;
+        self.frame_filled = 0;
         self.silent_samples = 0;
         self.speech_detected = false;
         self.triggered = false;
     }
 }
 
+/// Silence detector whose threshold follows the background noise.
+///
+/// Keeps the RMS of every chunk from the last 5 seconds, takes the 25th
+/// percentile as the current noise floor, and smooths it with an exponential
+/// moving average whose time constant is also 5 seconds. The threshold is
+/// the smoothed floor times 3, so a room getting louder (or quieter) mid-
+/// recording doesn't leave auto-stop stuck.
+#[derive(Debug, Clone)]
+pub struct AdaptiveSilenceDetector {
+    detector: SilenceDetector,
+    /// Seconds of silence before auto-stop
+    duration_secs: f32,
+    /// (RMS, sample count) of recent chunks, oldest first
+    levels: VecDeque<(f32, usize)>,
+    /// Total samples covered by `levels`
+    level_samples: usize,
+    /// Samples in the rolling window (and the EMA time constant)
+    window_samples: usize,
+    /// Smoothed noise floor RMS, `None` until audio arrives
+    noise_floor: Option<f32>,
+}
+
+impl AdaptiveSilenceDetector {
+    /// Create an adaptive detector.
+    ///
+    /// Until the first chunk arrives the threshold is
+    /// [`DEFAULT_SILENCE_THRESHOLD`].
+    pub fn new(duration_secs: f32, sample_rate: u32) -> Self {
+        Self {
+            detector: SilenceDetector::new(DEFAULT_SILENCE_THRESHOLD, duration_secs, sample_rate),
+            duration_secs,
+            levels: VecDeque::new(),
+            level_samples: 0,
+            window_samples: ((sample_rate as f32 * ADAPTIVE_WINDOW_SECS) as usize).max(1),
+            noise_floor: None,
+        }
+    }
+
+    /// Update the noise floor from a chunk, then run silence detection with
+    /// the adjusted threshold. Returns whether auto-stop should trigger.
+    pub fn process_adaptive(&mut self, samples: &[f32]) -> bool {
+        if samples.is_empty() {
+            return self.detector.is_triggered();
+        }
+
+        self.update_noise_floor(samples);
+        self.detector.reconfigure(self.threshold_for_floor(), self.duration_secs);
+        self.detector.process(samples)
+    }
+
+    fn update_noise_floor(&mut self, samples: &[f32]) {
+        self.levels.push_back((calculate_rms(samples), samples.len()));
+        self.level_samples += samples.len();
+
+        // Drop chunks that fall entirely outside the window
+        while let Some(&(_, len)) = self.levels.front() {
+            if self.level_samples - len < self.window_samples {
+                break;
+            }
+            self.levels.pop_front();
+            self.level_samples -= len;
+        }
+
+        let mut sorted: Vec<f32> = self.levels.iter().map(|&(rms, _)| rms).collect();
+        sorted.sort_by(f32::total_cmp);
+        let index = ((sorted.len() - 1) as f32 * NOISE_FLOOR_PERCENTILE).round() as usize;
+        let percentile = sorted[index];
+
+        // EMA with a 5 s time constant, scaled by how much audio this chunk covers
+        let alpha = 1.0 - (-(samples.len() as f32) / self.window_samples as f32).exp();
+        self.noise_floor = Some(match self.noise_floor {
+            Some(floor) => floor + alpha * (percentile - floor),
+            None => percentile,
+        });
+    }
+
+    fn threshold_for_floor(&self) -> f32 {
+        match self.noise_floor {
+            Some(floor) => (floor * ADAPTIVE_THRESHOLD_MULTIPLIER)
+                .clamp(MIN_SILENCE_THRESHOLD, MAX_SILENCE_THRESHOLD),
+            None => DEFAULT_SILENCE_THRESHOLD,
+        }
+    }
+
+    /// Change the silence duration, keeping the noise estimate and progress.
+    pub fn set_duration(&mut self, duration_secs: f32) {
+        self.duration_secs = duration_secs;
+        self.detector.reconfigure(self.detector.threshold(), duration_secs);
+    }
+
+    /// Current smoothed noise floor (RMS), 0.0 before any audio.
+    pub fn noise_floor(&self) -> f32 {
+        self.noise_floor.unwrap_or(0.0)
+    }
+
+    /// Current noise floor in dB (negative infinity before any audio).
+    pub fn noise_floor_db(&self) -> f32 {
+        rms_to_db(self.noise_floor())
+    }
+
+    /// Threshold currently in use.
+    pub fn threshold(&self) -> f32 {
+        self.detector.threshold()
+    }
+
+    /// Check if auto-stop has been triggered.
+    pub fn is_triggered(&self) -> bool {
+        self.detector.is_triggered()
+    }
+
+    /// Check if speech has been detected at least once.
+    pub fn has_speech(&self) -> bool {
+        self.detector.has_speech()
+    }
+}
+
 /// Calculate the Root Mean Square (RMS) of audio samples.
 ///
 /// RMS provides a measure of the "average" signal level,
@@ -149,6 +381,39 @@ pub fn calculate_rms(samples: &[f32]) -> f32 {
     (sum_of_squares / samples.len() as f32).sqrt()
 }
 
+/// Suggest a silence threshold for the given ambient noise RMS: the noise
+/// level with [`CALIBRATION_HEADROOM`], clamped to the allowed range.
+pub fn suggest_threshold(ambient_rms: f32) -> f32 {
+    (ambient_rms * CALIBRATION_HEADROOM).clamp(MIN_SILENCE_THRESHOLD, MAX_SILENCE_THRESHOLD)
+}
+
+/// Ambient noise measured while the user stays quiet
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NoiseProfile {
+    pub rms: f32,
+    /// Largest sample magnitude
+    pub peak: f32,
+    /// `rms` in dB, floored at -100
+    pub db: f32,
+    /// Silence threshold for this room: `rms` with [`NOISE_PROFILE_HEADROOM`],
+    /// clamped to the allowed range
+    pub recommended_threshold: f32,
+}
+
+impl NoiseProfile {
+    pub fn measure(ambient: &[f32]) -> Self {
+        let rms = calculate_rms(ambient);
+        let peak = ambient.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        Self {
+            rms,
+            peak,
+            db: rms_to_db(rms).max(NOISE_PROFILE_FLOOR_DB),
+            recommended_threshold: (rms * NOISE_PROFILE_HEADROOM)
+                .clamp(MIN_SILENCE_THRESHOLD, MAX_SILENCE_THRESHOLD),
+        }
+    }
+}
+
 /// Calculate decibels from RMS value (relative to full scale).
 pub fn rms_to_db(rms: f32) -> f32 {
     if rms <= 0.0 {
@@ -290,8 +555,8 @@ mod tests {
     fn test_silence_duration() {
         let mut detector = SilenceDetector::new(0.01, 2.0, 16000);
 
-        // Speak first
-        let speech: Vec<f32> = vec![0.5; 4000];
+        // Speak first, for a whole number of 20 ms frames
+        let speech: Vec<f32> = vec![0.5; 4800];
         detector.process(&speech);
 
         // 0.5 seconds of silence
@@ -301,6 +566,80 @@ mod tests {
         assert!((detector.silence_duration() - 0.5).abs() < 0.01);
     }
 
+    #[test]
+    fn test_silence_duration_counts_whole_frames() {
+        let mut detector = SilenceDetector::new(0.01, 2.0, 16000);
+        detector.process(&[0.5; 320]);
+
+        // Half a frame of silence isn't counted until the frame is complete
+        detector.process(&[0.0; 160]);
+        assert_eq!(detector.silence_duration(), 0.0);
+        detector.process(&[0.0; 160]);
+        assert!((detector.silence_duration() - 0.02).abs() < 1e-6);
+    }
+
+    /// Speech, a pause, a click, then silence until auto-stop
+    fn dictation_audio() -> Vec<f32> {
+        let mut audio: Vec<f32> = (0..8000).map(|i| 0.5 * (i as f32 * 0.1).sin()).collect();
+        audio.extend(vec![0.002; 5000]);
+        audio.extend(vec![0.4; 40]);
+        audio.extend(vec![0.002; 20000]);
+        audio
+    }
+
+    /// Feed `audio` in chunks of `chunk_len` and return how many samples had
+    /// been fed when auto-stop triggered, with the silence duration then
+    fn trigger_point(audio: &[f32], chunk_len: usize) -> Option<(usize, f32)> {
+        let mut detector = SilenceDetector::new(0.01, 0.5, 16000);
+        let mut fed = 0;
+        for chunk in audio.chunks(chunk_len) {
+            fed += chunk.len();
+            if detector.process(chunk) {
+                return Some((fed, detector.silence_duration()));
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn test_trigger_independent_of_chunk_size() {
+        let audio = dictation_audio();
+        let (exact, duration) = trigger_point(&audio, 1).unwrap();
+
+        // The click's frame ends at sample 13120 and restarts the count, so
+        // 0.5 s (8000 samples) of silent frames later auto-stop triggers
+        assert_eq!(exact, 13120 + 8000);
+        assert!((duration - 0.5).abs() < 1e-6);
+
+        // 10 ms, 20 ms, 441-sample and 500 ms buffers all stop at the same
+        // sample, within the chunk that contains it
+        for chunk_len in [160, 320, 441, 1024, 8000] {
+            let (fed, chunk_duration) = trigger_point(&audio, chunk_len).unwrap();
+            assert_eq!(fed, exact.div_ceil(chunk_len) * chunk_len, "{}", chunk_len);
+            assert_eq!(chunk_duration, duration, "{}", chunk_len);
+        }
+    }
+
+    #[test]
+    fn test_no_trigger_without_speech_in_small_chunks() {
+        let mut detector = SilenceDetector::new(0.01, 0.5, 16000);
+        for _ in 0..400 {
+            assert!(!detector.process(&[0.0; 100]));
+        }
+        assert!(!detector.has_speech());
+    }
+
+    #[test]
+    fn test_reset_discards_partial_frame() {
+        let mut detector = SilenceDetector::new(0.01, 0.5, 16000);
+        detector.process(&[0.5; 300]);
+        detector.reset();
+
+        // Without the leftover speech the next frame is silent, so nothing counts as speech
+        detector.process(&[0.0; 320]);
+        assert!(!detector.has_speech());
+    }
+
     #[test]
     fn test_rms_to_db() {
         // Full scale (1.0) = 0 dB
@@ -316,9 +655,175 @@ mod tests {
         assert!(rms_to_db(0.0).is_infinite());
     }
 
+    #[test]
+    fn test_calibrate_uses_mean_rms_with_headroom() {
+        let mut detector = SilenceDetector::new(0.05, 1.5, 16000);
+
+        // Constant noise at 0.01 RMS
+        let ambient = vec![0.01; 16000];
+        let db = detector.calibrate(&ambient);
+
+        assert!((detector.threshold() - 0.02).abs() < 1e-6);
+        assert!((db - rms_to_db(0.02)).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_calibrate_averages_windows() {
+        let mut detector = SilenceDetector::new(0.05, 1.5, 16000);
+
+        // Half quiet, half louder: windows average to 0.015
+        let mut ambient = vec![0.01; 8000];
+        ambient.extend(vec![0.02; 8000]);
+        detector.calibrate(&ambient);
+
+        assert!((detector.threshold() - 0.03).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_calibrate_clamps_threshold() {
+        let mut detector = SilenceDetector::new(0.05, 1.5, 16000);
+
+        detector.calibrate(&[0.0; 1600]);
+        assert!((detector.threshold() - MIN_SILENCE_THRESHOLD).abs() < f32::EPSILON);
+
+        detector.calibrate(&[0.5; 1600]);
+        assert!((detector.threshold() - MAX_SILENCE_THRESHOLD).abs() < f32::EPSILON);
+
+        // No samples at all counts as silence
+        detector.calibrate(&[]);
+        assert!((detector.threshold() - MIN_SILENCE_THRESHOLD).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_noise_profile() {
+        let mut ambient = vec![0.01; 1600];
+        ambient.extend([-0.03, 0.02]);
+        let profile = NoiseProfile::measure(&ambient);
+
+        assert!((profile.rms - 0.01).abs() < 1e-4);
+        assert!((profile.peak - 0.03).abs() < f32::EPSILON);
+        assert!((profile.db - rms_to_db(profile.rms)).abs() < 0.01);
+        assert!((profile.recommended_threshold - profile.rms * 2.5).abs() < 1e-6);
+
+        // Digital silence gets the lowest threshold and a finite level
+        let silent = NoiseProfile::measure(&[0.0; 1600]);
+        assert_eq!(silent.db, -100.0);
+        assert!((silent.recommended_threshold - MIN_SILENCE_THRESHOLD).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_reconfigure_keeps_progress() {
+        let mut detector = SilenceDetector::new(0.01, 2.0, 16000);
+
+        // Speech, then 1s of silence: not enough for the 2s duration
+        assert!(!detector.process(&[0.5; 1600]));
+        assert!(!detector.process(&[0.0; 16000]));
+
+        // Shortening the duration keeps the silent stretch, so the next chunk triggers
+        detector.reconfigure(0.01, 0.5);
+        assert!(detector.has_speech());
+        assert!(detector.process(&[0.0; 160]));
+    }
+
+    #[test]
+    fn test_reconfigure_raises_threshold() {
+        let mut detector = SilenceDetector::new(0.01, 0.5, 16000);
+        assert!(!detector.process(&[0.5; 1600]));
+
+        // Cafe noise at 0.03 counts as speech until the threshold goes up
+        assert!(!detector.process(&[0.03; 8000]));
+        detector.reconfigure(0.05, 0.5);
+        assert!(detector.process(&[0.03; 8000]));
+
+        detector.reconfigure(1.0, 10.0);
+        assert!((detector.threshold() - MAX_SILENCE_THRESHOLD).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_suggest_threshold() {
+        assert!((suggest_threshold(0.01) - 0.02).abs() < 1e-6);
+        assert!((suggest_threshold(0.0) - MIN_SILENCE_THRESHOLD).abs() < f32::EPSILON);
+        assert!((suggest_threshold(0.2) - MAX_SILENCE_THRESHOLD).abs() < f32::EPSILON);
+    }
+
     #[test]
     fn test_with_defaults() {
         let detector = SilenceDetector::with_defaults(16000);
         assert!((detector.threshold() - DEFAULT_SILENCE_THRESHOLD).abs() < f32::EPSILON);
     }
+
+    #[test]
+    fn test_adaptive_threshold_follows_noise_floor() {
+        let mut detector = AdaptiveSilenceDetector::new(1.5, 16000);
+        assert!(detector.noise_floor_db().is_infinite());
+
+        // Steady background noise at 0.01 RMS
+        for _ in 0..50 {
+            detector.process_adaptive(&[0.01; 1600]);
+        }
+
+        assert!((detector.noise_floor() - 0.01).abs() < 1e-4);
+        assert!((detector.threshold() - 0.03).abs() < 1e-3);
+        assert!((detector.noise_floor_db() - rms_to_db(0.01)).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_adaptive_floor_ignores_speech_bursts() {
+        let mut detector = AdaptiveSilenceDetector::new(1.5, 16000);
+
+        // Mostly quiet with occasional loud chunks: the 25th percentile stays quiet
+        for i in 0..50 {
+            let level = if i % 3 == 2 { 0.3 } else { 0.01 };
+            detector.process_adaptive(&vec![level; 1600]);
+        }
+
+        assert!(detector.noise_floor() < 0.02);
+        assert!(detector.has_speech());
+    }
+
+    #[test]
+    fn test_adaptive_floor_moves_gradually() {
+        let mut detector = AdaptiveSilenceDetector::new(1.5, 16000);
+        for _ in 0..50 {
+            detector.process_adaptive(&[0.01; 1600]);
+        }
+
+        // Once louder noise dominates the window, the estimate only moves part way
+        for _ in 0..45 {
+            detector.process_adaptive(&[0.02; 1600]);
+        }
+        let floor = detector.noise_floor();
+        assert!(floor > 0.01 && floor < 0.02);
+    }
+
+    #[test]
+    fn test_adaptive_triggers_in_noisy_room() {
+        let mut detector = AdaptiveSilenceDetector::new(0.5, 16000);
+
+        // Cafe noise at 0.02 would never count as silence with the default threshold
+        for _ in 0..30 {
+            assert!(!detector.process_adaptive(&[0.02; 1600]));
+        }
+        assert!(!detector.process_adaptive(&[0.3; 1600]));
+
+        let mut triggered = false;
+        for _ in 0..10 {
+            triggered |= detector.process_adaptive(&[0.02; 1600]);
+        }
+        assert!(triggered);
+        assert!(detector.is_triggered());
+    }
+
+    #[test]
+    fn test_adaptive_set_duration() {
+        let mut detector = AdaptiveSilenceDetector::new(5.0, 16000);
+        for _ in 0..10 {
+            detector.process_adaptive(&[0.01; 1600]);
+        }
+        let threshold = detector.threshold();
+
+        detector.set_duration(0.5);
+        assert!((detector.threshold() - threshold).abs() < f32::EPSILON);
+        assert!((detector.noise_floor() - 0.01).abs() < 1e-4);
+    }
 }
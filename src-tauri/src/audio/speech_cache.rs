@@ -0,0 +1,219 @@
+//! Recently synthesized speech, kept in memory for instant replay.
+//!
+//! Synthesis takes seconds for a long paragraph, so speaking the same text
+//! with the same voice and speed again plays the cached audio instead.
+//! Entries are keyed on the text actually synthesized (after normalization)
+//! and evicted least recently used first once their samples outgrow the
+//! memory budget.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use crate::engines::AudioBuffer;
+
+/// Memory the cached samples may take up
+pub const DEFAULT_BUDGET_BYTES: usize = 50 * 1024 * 1024;
+
+/// What a cached utterance was synthesized from
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpeechKey {
+    text_hash: u64,
+    voice: String,
+    /// `f32::to_bits` of the speed, so the key can be hashed
+    speed_bits: u32,
+}
+
+impl SpeechKey {
+    pub fn new(text: &str, voice: &str, speed: f32) -> Self {
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        Self {
+            text_hash: hasher.finish(),
+            voice: voice.to_string(),
+            speed_bits: speed.to_bits(),
+        }
+    }
+}
+
+/// A synthesized utterance and the text it was spoken for
+#[derive(Debug)]
+pub struct CachedSpeech {
+    /// Text as given to the speak call, before normalization
+    pub text: String,
+    pub voice_id: String,
+    pub audio: AudioBuffer,
+}
+
+impl CachedSpeech {
+    fn size_bytes(&self) -> usize {
+        self.audio.samples.len() * std::mem::size_of::<f32>()
+    }
+}
+
+#[derive(Debug)]
+pub struct SpeechCache {
+    budget_bytes: usize,
+    used_bytes: usize,
+    /// Least recently used first
+    entries: VecDeque<(SpeechKey, Arc<CachedSpeech>)>,
+    /// Most recently spoken, kept even if evicted so replay still works
+    last: Option<Arc<CachedSpeech>>,
+}
+
+impl SpeechCache {
+    pub const fn new(budget_bytes: usize) -> Self {
+        Self {
+            budget_bytes,
+            used_bytes: 0,
+            entries: VecDeque::new(),
+            last: None,
+        }
+    }
+
+    /// Cached speech for `key`, marking it as the most recently used and
+    /// the one to replay
+    pub fn get(&mut self, key: &SpeechKey) -> Option<Arc<CachedSpeech>> {
+        let index = self.entries.iter().position(|(k, _)| k == key)?;
+        let entry = self.entries.remove(index)?;
+        let speech = Arc::clone(&entry.1);
+        self.entries.push_back(entry);
+        self.last = Some(Arc::clone(&speech));
+        Some(speech)
+    }
+
+    /// Cache speech that was just synthesized, evicting the least recently
+    /// used entries to stay within the budget. Speech larger than the whole
+    /// budget is only kept for replay.
+    pub fn insert(&mut self, key: SpeechKey, speech: CachedSpeech) -> Arc<CachedSpeech> {
+        let speech = Arc::new(speech);
+        self.last = Some(Arc::clone(&speech));
+        self.remove(&key);
+
+        let size = speech.size_bytes();
+        if size > self.budget_bytes {
+            return speech;
+        }
+        while self.used_bytes + size > self.budget_bytes {
+            match self.entries.pop_front() {
+                Some((_, evicted)) => self.used_bytes -= evicted.size_bytes(),
+                None => break,
+            }
+        }
+
+        self.used_bytes += size;
+        self.entries.push_back((key, Arc::clone(&speech)));
+        speech
+    }
+
+    /// The utterance spoken most recently, from the cache or not
+    pub fn last(&self) -> Option<Arc<CachedSpeech>> {
+        self.last.clone()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.used_bytes = 0;
+        self.last = None;
+    }
+
+    pub fn used_bytes(&self) -> usize {
+        self.used_bytes
+    }
+
+    fn remove(&mut self, key: &SpeechKey) {
+        if let Some(index) = self.entries.iter().position(|(k, _)| k == key) {
+            if let Some((_, removed)) = self.entries.remove(index) {
+                self.used_bytes -= removed.size_bytes();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Speech whose samples take `kb` kilobytes
+    fn speech(text: &str, kb: usize) -> CachedSpeech {
+        CachedSpeech {
+            text: text.to_string(),
+            voice_id: "af_heart".to_string(),
+            audio: AudioBuffer::new(vec![0.0; kb * 256], 24000),
+        }
+    }
+
+    fn key(text: &str) -> SpeechKey {
+        SpeechKey::new(text, "af_heart", 1.0)
+    }
+
+    #[test]
+    fn test_key_covers_text_voice_and_speed() {
+        assert_eq!(key("Hello."), SpeechKey::new("Hello.", "af_heart", 1.0));
+        assert_ne!(key("Hello."), key("Hello!"));
+        assert_ne!(key("Hello."), SpeechKey::new("Hello.", "bf_emma", 1.0));
+        assert_ne!(key("Hello."), SpeechKey::new("Hello.", "af_heart", 1.1));
+    }
+
+    #[test]
+    fn test_get_returns_cached_speech() {
+        let mut cache = SpeechCache::new(10 * 1024);
+        cache.insert(key("one"), speech("one", 1));
+
+        assert_eq!(cache.get(&key("one")).unwrap().text, "one");
+        assert!(cache.get(&key("two")).is_none());
+        assert_eq!(cache.used_bytes(), 1024);
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache = SpeechCache::new(3 * 1024);
+        cache.insert(key("one"), speech("one", 1));
+        cache.insert(key("two"), speech("two", 1));
+        cache.insert(key("three"), speech("three", 1));
+
+        // Using "one" makes "two" the oldest
+        cache.get(&key("one"));
+        cache.insert(key("four"), speech("four", 1));
+
+        assert!(cache.get(&key("two")).is_none());
+        assert!(cache.get(&key("one")).is_some());
+        assert!(cache.get(&key("three")).is_some());
+        assert!(cache.get(&key("four")).is_some());
+        assert_eq!(cache.used_bytes(), 3 * 1024);
+    }
+
+    #[test]
+    fn test_oversized_speech_is_only_kept_for_replay() {
+        let mut cache = SpeechCache::new(2 * 1024);
+        cache.insert(key("short"), speech("short", 1));
+        cache.insert(key("long"), speech("long", 4));
+
+        assert!(cache.get(&key("long")).is_none());
+        assert_eq!(cache.last().unwrap().text, "long");
+        // The smaller entry wasn't evicted for nothing
+        assert!(cache.get(&key("short")).is_some());
+    }
+
+    #[test]
+    fn test_last_follows_cache_hits_and_clear() {
+        let mut cache = SpeechCache::new(10 * 1024);
+        assert!(cache.last().is_none());
+
+        cache.insert(key("one"), speech("one", 1));
+        cache.insert(key("two"), speech("two", 1));
+        assert_eq!(cache.last().unwrap().text, "two");
+
+        cache.get(&key("one"));
+        assert_eq!(cache.last().unwrap().text, "one");
+
+        // Re-inserting replaces the old entry rather than counting it twice
+        cache.insert(key("one"), speech("one", 1));
+        assert_eq!(cache.used_bytes(), 2 * 1024);
+
+        cache.clear();
+        assert!(cache.last().is_none());
+        assert_eq!(cache.used_bytes(), 0);
+    }
+}
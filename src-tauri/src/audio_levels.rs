@@ -0,0 +1,118 @@
+//! How often `stt-audio-level` is sent during a hotkey dictation.
+//!
+//! Levels only matter to a window that can show them. The overlay and main
+//! window note here when they are shown or hidden, and any webview can ask
+//! for levels with `subscribe_audio_levels`. While none of them is watching,
+//! the level loop slows to a heartbeat that still checks for a recording
+//! held too long without speech.
+
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Between level events while a window shows them
+pub const ACTIVE_INTERVAL: Duration = Duration::from_millis(50);
+/// Between checks while nothing shows the levels
+pub const IDLE_INTERVAL: Duration = Duration::from_millis(500);
+
+static OVERLAY_VISIBLE: AtomicBool = AtomicBool::new(false);
+static MAIN_VISIBLE: AtomicBool = AtomicBool::new(false);
+
+/// Labels of the webviews that asked for levels
+static SUBSCRIBERS: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// Wakes a sleeping level loop, so it speeds up or stops without waiting
+/// out its interval
+static WAKE: Notify = Notify::const_new();
+
+/// How long the level loop waits before its next event
+fn level_interval(overlay_visible: bool, main_visible: bool, subscribers: usize) -> Duration {
+    if overlay_visible || main_visible || subscribers > 0 {
+        ACTIVE_INTERVAL
+    } else {
+        IDLE_INTERVAL
+    }
+}
+
+/// The interval for whatever is watching right now
+pub(crate) fn current_interval() -> Duration {
+    let subscribers = SUBSCRIBERS.lock().map(|s| s.len()).unwrap_or(0);
+    level_interval(
+        OVERLAY_VISIBLE.load(Ordering::SeqCst),
+        MAIN_VISIBLE.load(Ordering::SeqCst),
+        subscribers,
+    )
+}
+
+/// Sleep for `interval`, or until `wake` is called
+pub(crate) async fn sleep(interval: Duration) {
+    tokio::select! {
+        _ = tokio::time::sleep(interval) => {}
+        _ = WAKE.notified() => {}
+    }
+}
+
+/// Wake the level loop to check again, e.g. because the recording stopped
+pub(crate) fn wake() {
+    WAKE.notify_waiters();
+}
+
+pub(crate) fn set_overlay_visible(visible: bool) {
+    if OVERLAY_VISIBLE.swap(visible, Ordering::SeqCst) != visible {
+        wake();
+    }
+}
+
+pub(crate) fn set_main_visible(visible: bool) {
+    if MAIN_VISIBLE.swap(visible, Ordering::SeqCst) != visible {
+        wake();
+    }
+}
+
+/// Stop sending levels for the webview `label`, e.g. once it is closed
+pub(crate) fn unsubscribe(label: &str) {
+    if let Ok(mut subscribers) = SUBSCRIBERS.lock() {
+        subscribers.remove(label);
+    }
+}
+
+/// Ask for `stt-audio-level` at full rate in the calling webview while
+/// `subscribed`, whether or not its window is visible
+#[tauri::command]
+pub fn subscribe_audio_levels(window: tauri::Window, subscribed: bool) {
+    let label = window.label().to_string();
+    if !subscribed {
+        unsubscribe(&label);
+        return;
+    }
+    if let Ok(mut subscribers) = SUBSCRIBERS.lock() {
+        subscribers.insert(label);
+    }
+    wake();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_interval() {
+        assert_eq!(level_interval(false, false, 0), IDLE_INTERVAL);
+        assert_eq!(level_interval(true, false, 0), ACTIVE_INTERVAL);
+        assert_eq!(level_interval(false, true, 0), ACTIVE_INTERVAL);
+        assert_eq!(level_interval(false, false, 1), ACTIVE_INTERVAL);
+    }
+
+    #[tokio::test]
+    async fn test_wake_cuts_sleep_short() {
+        let sleeping = tokio::spawn(sleep(Duration::from_secs(10)));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        wake();
+        tokio::time::timeout(Duration::from_secs(1), sleeping)
+            .await
+            .expect("sleep should end on wake")
+            .unwrap();
+    }
+}
@@ -0,0 +1,219 @@
+//! Speak-on-copy: poll the clipboard and read newly copied text aloud.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tauri::menu::CheckMenuItem;
+use tauri::{AppHandle, Manager};
+
+use crate::accessibility::clipboard::is_own_write;
+use crate::commands::settings::{get_settings, modify_settings};
+use crate::commands::tts::enqueue_speech;
+use crate::engines::EngineRegistry;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// Stop flag of the running watcher thread, if any
+static WATCHER_STOP: Mutex<Option<Arc<AtomicBool>>> = Mutex::new(None);
+
+/// The tray's "Speak on Copy" item, kept so the check mark follows the setting
+pub struct SpeakOnCopyMenuItem(pub CheckMenuItem<tauri::Wry>);
+
+/// Start or stop the watcher and sync the tray check mark.
+pub fn set_enabled(app: &AppHandle, enabled: bool) {
+    if enabled {
+        start(app);
+    } else {
+        stop();
+    }
+
+    if let Some(item) = app.try_state::<SpeakOnCopyMenuItem>() {
+        if let Err(e) = item.0.set_checked(enabled) {
+            tracing::warn!("Failed to update Speak on Copy menu item: {}", e);
+        }
+    }
+}
+
+/// Flip the `speak_on_copy` setting from the tray menu
+pub fn toggle(app: &AppHandle) {
+    let toggled = modify_settings(|settings| {
+        settings.speak_on_copy = !settings.speak_on_copy;
+        Ok(settings.speak_on_copy)
+    });
+
+    match toggled {
+        Ok(enabled) => set_enabled(app, enabled),
+        Err(e) => tracing::error!("Failed to save Speak on Copy setting: {}", e),
+    }
+}
+
+fn start(app: &AppHandle) {
+    let Ok(mut guard) = WATCHER_STOP.lock() else {
+        return;
+    };
+    if guard.is_some() {
+        return;
+    }
+
+    let stop = Arc::new(AtomicBool::new(false));
+    *guard = Some(Arc::clone(&stop));
+
+    let engines = Arc::clone(&app.state::<Arc<EngineRegistry>>());
+    std::thread::spawn(move || watch_clipboard(&engines, &stop));
+    tracing::info!("Clipboard watcher started");
+}
+
+fn stop() {
+    if let Ok(mut guard) = WATCHER_STOP.lock() {
+        if let Some(stop) = guard.take() {
+            // The thread exits on its next poll
+            stop.store(true, Ordering::SeqCst);
+            tracing::info!("Clipboard watcher stopped");
+        }
+    }
+}
+
+fn watch_clipboard(engines: &Arc<EngineRegistry>, stop: &AtomicBool) {
+    // arboard's clipboard handle isn't Send on every platform, so it lives on this thread
+    let mut clipboard = match arboard::Clipboard::new() {
+        Ok(clipboard) => clipboard,
+        Err(e) => {
+            tracing::error!("Failed to open clipboard: {}", e);
+            return;
+        }
+    };
+
+    let mut last_change_count = pasteboard_change_count();
+    let mut last_text = clipboard.get_text().ok();
+
+    while !stop.load(Ordering::SeqCst) {
+        std::thread::sleep(POLL_INTERVAL);
+        if stop.load(Ordering::SeqCst) {
+            break;
+        }
+
+        // Where the change count is available it's cheaper than reading the
+        // contents and also catches the same text being copied twice
+        let change_count = pasteboard_change_count();
+        if change_count.is_some() && change_count == last_change_count {
+            continue;
+        }
+        last_change_count = change_count;
+
+        // Non-text contents (images, files) just fail here
+        let Ok(text) = clipboard.get_text() else {
+            continue;
+        };
+        if change_count.is_none() && last_text.as_deref() == Some(text.as_str()) {
+            continue;
+        }
+        last_text = Some(text.clone());
+
+        if is_own_write(&text) {
+            tracing::debug!("Ignoring clipboard change made by the app");
+            continue;
+        }
+
+        let settings = get_settings().unwrap_or_default();
+        let Some(text) = speakable_text(&text, settings.speak_on_copy_max_chars) else {
+            continue;
+        };
+
+        let speed = settings.tts_speed;
+        if let Err(e) = enqueue_speech(engines, text.to_string(), settings.tts_voice, speed) {
+            tracing::error!("Failed to queue copied text for speech: {}", e);
+        }
+    }
+}
+
+/// Trimmed clipboard text if it should be spoken, `None` if it's empty or
+/// longer than `max_chars`.
+fn speakable_text(text: &str, max_chars: usize) -> Option<&str> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+
+    let len = text.chars().count();
+    if len > max_chars {
+        tracing::debug!(
+            "Skipping copied text: {} chars exceeds limit of {}",
+            len,
+            max_chars
+        );
+        return None;
+    }
+
+    Some(text)
+}
+
+/// NSPasteboard's change count, bumped on every write to the general pasteboard
+#[cfg(target_os = "macos")]
+fn pasteboard_change_count() -> Option<i64> {
+    use std::ffi::{c_char, c_void};
+
+    #[link(name = "AppKit", kind = "framework")]
+    extern "C" {}
+
+    #[link(name = "objc")]
+    extern "C" {
+        fn objc_getClass(name: *const c_char) -> *mut c_void;
+        fn sel_registerName(name: *const c_char) -> *mut c_void;
+        fn objc_msgSend();
+    }
+
+    unsafe {
+        let class = objc_getClass(b"NSPasteboard\0".as_ptr() as *const c_char);
+        if class.is_null() {
+            return None;
+        }
+
+        let send_id: unsafe extern "C" fn(*mut c_void, *mut c_void) -> *mut c_void =
+            std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        let pasteboard = send_id(
+            class,
+            sel_registerName(b"generalPasteboard\0".as_ptr() as *const c_char),
+        );
+        if pasteboard.is_null() {
+            return None;
+        }
+
+        let send_count: unsafe extern "C" fn(*mut c_void, *mut c_void) -> isize =
+            std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        let count = send_count(
+            pasteboard,
+            sel_registerName(b"changeCount\0".as_ptr() as *const c_char),
+        );
+        Some(count as i64)
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn pasteboard_change_count() -> Option<i64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_speakable_text_trims() {
+        assert_eq!(speakable_text("  hello world \n", 100), Some("hello world"));
+    }
+
+    #[test]
+    fn test_speakable_text_skips_empty() {
+        assert_eq!(speakable_text("", 100), None);
+        assert_eq!(speakable_text(" \n\t ", 100), None);
+    }
+
+    #[test]
+    fn test_speakable_text_respects_char_cap() {
+        assert_eq!(speakable_text("abcde", 5), Some("abcde"));
+        assert_eq!(speakable_text("abcdef", 5), None);
+        // Counted in characters, not bytes
+        assert_eq!(speakable_text("ééééé", 5), Some("ééééé"));
+    }
+}
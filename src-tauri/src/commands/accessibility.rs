@@ -0,0 +1,164 @@
+//! Reading the selection and pasting from the main window.
+//!
+//! The hotkey handlers reach the accessibility helpers directly; these
+//! commands give the frontend the same two actions. Both need Accessibility
+//! access and fail with `accessibility_denied` without it, so the UI can
+//! send the user to that pane of System Settings.
+
+use tauri::Manager;
+
+use crate::accessibility::{self, paste, FrontmostAppInfo};
+use crate::commands::permissions::{accessibility_state, AccessibilityState};
+use crate::error::BlahError;
+
+/// The text selected in the frontmost app, or `None` when nothing is
+/// selected.
+///
+/// When the main window is frontmost, which it is whenever the user clicks a
+/// button in it, the selection read is usually our own and empty. With
+/// `from_previous_app` the app the user was in before is brought to the
+/// front first, its selection read, and the main window focused again.
+#[tauri::command]
+pub async fn get_selection(
+    app: tauri::AppHandle,
+    from_previous_app: Option<bool>,
+) -> Result<Option<String>, BlahError> {
+    ensure_accessibility()?;
+
+    let frontmost = accessibility::get_frontmost_app_async().await;
+    let source = selection_source(
+        frontmost.as_ref(),
+        &app.config().identifier,
+        from_previous_app.unwrap_or(false),
+        accessibility::frontmost_app::previous_app(),
+    )?;
+    let Some(source) = source else {
+        return Ok(accessibility::get_selected_text_async().await);
+    };
+
+    tracing::info!("Reading the selection from {}", source.name);
+    let text = tauri::async_runtime::spawn_blocking(move || {
+        activate(&source)?;
+        Ok::<_, BlahError>(accessibility::selected_text::get_selected_text())
+    })
+    .await
+    .map_err(|e| BlahError::Internal(format!("Selection task failed: {}", e)))?;
+
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.set_focus();
+    }
+    text
+}
+
+/// Paste `text` into the frontmost app, first bringing the app with
+/// `activate_bundle_id` to the front when one is given
+#[tauri::command]
+pub async fn paste_into_frontmost(
+    text: String,
+    activate_bundle_id: Option<String>,
+) -> Result<(), BlahError> {
+    if text.is_empty() {
+        return Err(BlahError::InvalidInput("Nothing to paste".to_string()));
+    }
+    ensure_accessibility()?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Some(bundle_id) = activate_bundle_id.filter(|id| !id.is_empty()) {
+            activate(&FrontmostAppInfo {
+                name: String::new(),
+                bundle_id,
+            })?;
+        }
+        accessibility::paste_text(&text)
+            .map_err(|e| BlahError::Internal(format!("Couldn't paste: {}", e)))
+    })
+    .await
+    .map_err(|e| BlahError::Internal(format!("Paste task failed: {}", e)))?
+}
+
+fn ensure_accessibility() -> Result<(), BlahError> {
+    match accessibility_state() {
+        AccessibilityState::Granted => Ok(()),
+        AccessibilityState::Denied => Err(BlahError::AccessibilityDenied(
+            "Blah³ needs Accessibility access to read selections and paste. \
+             Allow it in System Settings > Privacy & Security > Accessibility."
+                .to_string(),
+        )),
+        AccessibilityState::GrantedButNotWorking => Err(BlahError::AccessibilityDenied(
+            "Blah³'s Accessibility access no longer applies, usually after an update. \
+             Remove Blah³ from System Settings > Privacy & Security > Accessibility \
+             and add it again."
+                .to_string(),
+        )),
+    }
+}
+
+/// The app to bring forward before reading the selection, or `None` to read
+/// it from whatever is frontmost
+fn selection_source(
+    frontmost: Option<&FrontmostAppInfo>,
+    own_bundle_id: &str,
+    from_previous_app: bool,
+    previous: Option<FrontmostAppInfo>,
+) -> Result<Option<FrontmostAppInfo>, BlahError> {
+    let ours_in_front = frontmost.is_some_and(|app| app.bundle_id == own_bundle_id);
+    if !ours_in_front || !from_previous_app {
+        return Ok(None);
+    }
+    previous.map(Some).ok_or_else(|| {
+        BlahError::InvalidInput("There's no other app to read the selection from".to_string())
+    })
+}
+
+/// Bring `target` to the front, failing unless it really is frontmost
+/// afterwards so keystrokes never land in the wrong app
+fn activate(target: &FrontmostAppInfo) -> Result<(), BlahError> {
+    let activated = paste::activate_app(target);
+    let frontmost = accessibility::get_frontmost_app();
+    if activated.is_err() || !paste::is_same_app(Some(target), frontmost.as_ref()) {
+        let name = if target.name.is_empty() {
+            &target.bundle_id
+        } else {
+            &target.name
+        };
+        return Err(BlahError::InvalidInput(format!(
+            "Couldn't switch to {}",
+            name
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OWN: &str = "com.blahcubed.app";
+
+    fn app(name: &str, bundle_id: &str) -> FrontmostAppInfo {
+        FrontmostAppInfo {
+            name: name.to_string(),
+            bundle_id: bundle_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_selection_source() {
+        let ours = app("Blah³", OWN);
+        let safari = app("Safari", "com.apple.Safari");
+        let notes = app("Notes", "com.apple.Notes");
+
+        // Another app is in front: read from it as is
+        let source = selection_source(Some(&safari), OWN, true, Some(notes.clone())).unwrap();
+        assert!(source.is_none());
+        // Our window is in front but the caller didn't ask to switch
+        let source = selection_source(Some(&ours), OWN, false, Some(notes.clone())).unwrap();
+        assert!(source.is_none());
+
+        let source = selection_source(Some(&ours), OWN, true, Some(notes)).unwrap();
+        assert_eq!(source.unwrap().bundle_id, "com.apple.Notes");
+
+        let err = selection_source(Some(&ours), OWN, true, None).unwrap_err();
+        assert_eq!(err.code(), "invalid_input");
+    }
+}
@@ -0,0 +1,702 @@
+//! Batch transcription of a folder of audio files.
+//!
+//! Files are processed one at a time through the cached Whisper engine and a
+//! `transcripts/` folder is written next to the inputs, together with a
+//! `manifest.json` summarising the run.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::Manager;
+
+use crate::audio::processing::{audio_duration_secs, read_mp3, read_wav, resample};
+use crate::commands::models::get_models_dir;
+use crate::commands::settings::get_settings;
+use crate::commands::stt::{decode_options, TranscriptionRunning};
+use crate::engines::{EngineRegistry, TranscriptSegment};
+use crate::error::BlahError;
+use crate::events;
+
+/// Sample rate Whisper expects its input in
+const WHISPER_SAMPLE_RATE: u32 = 16000;
+
+/// Audio file extensions the batch runner knows how to decode
+const SUPPORTED_EXTENSIONS: &[&str] = &["wav", "mp3"];
+
+/// Name of the output folder created inside the batch folder
+const TRANSCRIPTS_DIR: &str = "transcripts";
+
+static BATCH_RUNNING: AtomicBool = AtomicBool::new(false);
+static BATCH_CANCELLED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchFileStatus {
+    Processing,
+    Succeeded,
+    Failed,
+}
+
+/// Payload for the `batch-progress` event ([`events::BatchProgressUpdate`])
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchProgress {
+    pub index: usize,
+    pub total: usize,
+    pub file_name: String,
+    pub status: BatchFileStatus,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchFileResult {
+    pub source_path: String,
+    pub status: BatchFileStatus,
+    pub transcript_path: Option<String>,
+    pub srt_path: Option<String>,
+    pub error: Option<String>,
+    pub audio_secs: f32,
+    pub processing_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchManifest {
+    pub folder: String,
+    pub model_id: String,
+    pub files: Vec<BatchFileResult>,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub cancelled: bool,
+    pub total_audio_secs: f32,
+    pub total_processing_ms: u64,
+}
+
+/// Transcribe every supported audio file in `path` and write the results
+/// into a `transcripts/` subfolder. Individual failures are recorded in the
+/// returned manifest instead of aborting the batch.
+#[tauri::command]
+pub async fn transcribe_folder(
+    path: String,
+    recursive: bool,
+    model_id: String,
+    include_srt: bool,
+    window: tauri::Window,
+) -> Result<BatchManifest, BlahError> {
+    let folder = PathBuf::from(&path);
+    if !folder.is_dir() {
+        return Err(BlahError::InvalidInput(format!("Not a folder: {}", path)));
+    }
+
+    let model_path = get_models_dir().join("stt").join(&model_id);
+    if !model_path.exists() {
+        return Err(BlahError::ModelNotDownloaded(format!(
+            "Model not found: {}. Please download it from the Models tab.",
+            model_id
+        )));
+    }
+
+    if BATCH_RUNNING.swap(true, Ordering::SeqCst) {
+        return Err(BlahError::Busy(
+            "A batch transcription is already running".to_string(),
+        ));
+    }
+    BATCH_CANCELLED.store(false, Ordering::SeqCst);
+
+    let result = run_folder(folder, recursive, model_id, model_path, include_srt, window).await;
+
+    BATCH_RUNNING.store(false, Ordering::SeqCst);
+    result
+}
+
+/// Stop the running batch after the file currently being transcribed.
+#[tauri::command]
+pub fn cancel_batch() -> bool {
+    if BATCH_RUNNING.load(Ordering::SeqCst) {
+        tracing::info!("Batch transcription cancellation requested");
+        BATCH_CANCELLED.store(true, Ordering::SeqCst);
+        true
+    } else {
+        false
+    }
+}
+
+async fn run_folder(
+    folder: PathBuf,
+    recursive: bool,
+    model_id: String,
+    model_path: PathBuf,
+    include_srt: bool,
+    window: tauri::Window,
+) -> Result<BatchManifest, BlahError> {
+    let files = discover_audio_files(&folder, recursive)
+        .map_err(|e| BlahError::IoError(format!("Failed to scan folder: {}", e)))?;
+    tracing::info!(
+        "Batch transcription of {} files in {:?} with model {}",
+        files.len(),
+        folder,
+        model_id
+    );
+
+    let engines = Arc::clone(&window.state::<Arc<EngineRegistry>>());
+    let engine = engines
+        .get_or_load_whisper(&model_path.to_string_lossy())
+        .await?;
+    let options = decode_options(&get_settings().unwrap_or_default());
+    let _running = TranscriptionRunning::start();
+
+    // The batch keeps its own handle on the engine, so switching models in
+    // the meantime doesn't affect files that are still queued. Each file
+    // takes its turn with other transcriptions, so a dictation made during
+    // a batch waits for the current file rather than the whole folder.
+    let manifest = tauri::async_runtime::spawn_blocking(move || {
+        let output_dir = folder.join(TRANSCRIPTS_DIR);
+
+        let mut manifest = run_batch(
+            &files,
+            &folder,
+            &output_dir,
+            include_srt,
+            &BATCH_CANCELLED,
+            |audio| {
+                let transcribe = || engine.transcribe_segments(audio, &options);
+                engines.whisper().blocking_turn(transcribe)
+            },
+            |progress| {
+                events::emit(&window, events::BatchProgressUpdate(progress));
+            },
+        );
+        manifest.model_id = model_id;
+
+        if let Err(e) = write_manifest(&output_dir, &manifest) {
+            tracing::warn!("Failed to write batch manifest: {}", e);
+        }
+
+        manifest
+    })
+    .await
+    .map_err(|e| BlahError::Internal(format!("Batch transcription task failed: {}", e)))?;
+
+    tracing::info!(
+        "Batch transcription finished: {} succeeded, {} failed{}",
+        manifest.succeeded,
+        manifest.failed,
+        if manifest.cancelled {
+            " (cancelled)"
+        } else {
+            ""
+        }
+    );
+
+    Ok(manifest)
+}
+
+/// Find supported audio files in `dir`, sorted by path. The transcripts
+/// output folder is never descended into.
+pub fn discover_audio_files(dir: &Path, recursive: bool) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    collect_audio_files(dir, recursive, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+fn collect_audio_files(dir: &Path, recursive: bool, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            let is_output_dir = path
+                .file_name()
+                .map(|n| n == TRANSCRIPTS_DIR)
+                .unwrap_or(false);
+            if recursive && !is_output_dir {
+                collect_audio_files(&path, recursive, files)?;
+            }
+        } else if is_supported_audio(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Check whether a path has one of the supported audio extensions
+pub fn is_supported_audio(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| SUPPORTED_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Decode an audio file into 16 kHz mono samples ready for Whisper
+pub fn load_audio_file(path: &Path) -> Result<Vec<f32>> {
+    let is_mp3 = path
+        .extension()
+        .map(|ext| ext.eq_ignore_ascii_case("mp3"))
+        .unwrap_or(false);
+    let (samples, sample_rate) = if is_mp3 {
+        read_mp3(path)?
+    } else {
+        read_wav(path)?
+    };
+    if samples.is_empty() {
+        return Err(anyhow!("File contains no audio samples"));
+    }
+
+    Ok(resample(&samples, sample_rate, WHISPER_SAMPLE_RATE))
+}
+
+/// Run a batch over `files`, writing transcripts into `output_dir` and
+/// mirroring each file's location relative to `root`.
+///
+/// The cancel flag is checked before each file, so a cancellation takes
+/// effect once the current file is done.
+pub fn run_batch<T, P>(
+    files: &[PathBuf],
+    root: &Path,
+    output_dir: &Path,
+    include_srt: bool,
+    cancelled: &AtomicBool,
+    mut transcribe: T,
+    mut on_progress: P,
+) -> BatchManifest
+where
+    T: FnMut(&[f32]) -> Result<Vec<TranscriptSegment>>,
+    P: FnMut(BatchProgress),
+{
+    let mut manifest = BatchManifest {
+        folder: root.to_string_lossy().to_string(),
+        model_id: String::new(),
+        files: Vec::with_capacity(files.len()),
+        succeeded: 0,
+        failed: 0,
+        cancelled: false,
+        total_audio_secs: 0.0,
+        total_processing_ms: 0,
+    };
+
+    for (index, path) in files.iter().enumerate() {
+        if cancelled.load(Ordering::SeqCst) {
+            tracing::info!("Batch cancelled before {:?}", path);
+            manifest.cancelled = true;
+            break;
+        }
+
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        on_progress(BatchProgress {
+            index,
+            total: files.len(),
+            file_name: file_name.clone(),
+            status: BatchFileStatus::Processing,
+            error: None,
+        });
+
+        let start = std::time::Instant::now();
+        let result = transcribe_file(path, root, output_dir, include_srt, &mut transcribe);
+        let processing_ms = start.elapsed().as_millis() as u64;
+
+        let file_result = match result {
+            Ok(output) => BatchFileResult {
+                source_path: path.to_string_lossy().to_string(),
+                status: BatchFileStatus::Succeeded,
+                transcript_path: Some(output.transcript_path.to_string_lossy().to_string()),
+                srt_path: output.srt_path.map(|p| p.to_string_lossy().to_string()),
+                error: None,
+                audio_secs: output.audio_secs,
+                processing_ms,
+            },
+            Err(e) => {
+                tracing::warn!("Batch transcription failed for {:?}: {}", path, e);
+                BatchFileResult {
+                    source_path: path.to_string_lossy().to_string(),
+                    status: BatchFileStatus::Failed,
+                    transcript_path: None,
+                    srt_path: None,
+                    error: Some(e.to_string()),
+                    audio_secs: 0.0,
+                    processing_ms,
+                }
+            }
+        };
+
+        if file_result.status == BatchFileStatus::Succeeded {
+            manifest.succeeded += 1;
+        } else {
+            manifest.failed += 1;
+        }
+        manifest.total_audio_secs += file_result.audio_secs;
+        manifest.total_processing_ms += processing_ms;
+
+        on_progress(BatchProgress {
+            index,
+            total: files.len(),
+            file_name,
+            status: file_result.status.clone(),
+            error: file_result.error.clone(),
+        });
+
+        manifest.files.push(file_result);
+    }
+
+    manifest
+}
+
+struct FileOutput {
+    transcript_path: PathBuf,
+    srt_path: Option<PathBuf>,
+    audio_secs: f32,
+}
+
+fn transcribe_file<T>(
+    path: &Path,
+    root: &Path,
+    output_dir: &Path,
+    include_srt: bool,
+    transcribe: &mut T,
+) -> Result<FileOutput>
+where
+    T: FnMut(&[f32]) -> Result<Vec<TranscriptSegment>>,
+{
+    let audio = load_audio_file(path)?;
+    let audio_secs = audio_duration_secs(audio.len(), WHISPER_SAMPLE_RATE);
+    let segments = transcribe(&audio)?;
+
+    // Keep subfolder structure so same-named files don't overwrite each other
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let base = output_dir.join(relative);
+    if let Some(parent) = base.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let transcript_path = with_added_extension(&base, "txt");
+    std::fs::write(&transcript_path, segments_to_text(&segments))?;
+
+    let srt_path = if include_srt {
+        let srt_path = with_added_extension(&base, "srt");
+        std::fs::write(&srt_path, format_srt(&segments))?;
+        Some(srt_path)
+    } else {
+        None
+    };
+
+    Ok(FileOutput {
+        transcript_path,
+        srt_path,
+        audio_secs,
+    })
+}
+
+/// `path` with `extension` added after its own, so `memo.wav` and
+/// `memo.mp3` in the same folder get separate transcripts
+fn with_added_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(extension);
+    PathBuf::from(name)
+}
+
+fn write_manifest(output_dir: &Path, manifest: &BatchManifest) -> Result<()> {
+    std::fs::create_dir_all(output_dir)?;
+    let content = serde_json::to_string_pretty(manifest)?;
+    std::fs::write(output_dir.join("manifest.json"), content)?;
+    Ok(())
+}
+
+/// Join segment texts into a single plain-text transcript
+pub fn segments_to_text(segments: &[TranscriptSegment]) -> String {
+    segments
+        .iter()
+        .map(|s| s.text.trim())
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Render segments as a SubRip (.srt) subtitle file
+pub fn format_srt(segments: &[TranscriptSegment]) -> String {
+    let mut srt = String::new();
+    for (i, segment) in segments.iter().enumerate() {
+        srt.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            format_srt_timestamp(segment.start_ms),
+            format_srt_timestamp(segment.end_ms),
+            segment.text.trim()
+        ));
+    }
+    srt
+}
+
+/// Format milliseconds as an SRT timestamp (`HH:MM:SS,mmm`)
+fn format_srt_timestamp(ms: i64) -> String {
+    let ms = ms.max(0);
+    let hours = ms / 3_600_000;
+    let minutes = (ms % 3_600_000) / 60_000;
+    let seconds = (ms % 60_000) / 1000;
+    let millis = ms % 1000;
+    format!("{:02}:{:02}:{:02},{:03}", hours, minutes, seconds, millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::processing::write_wav;
+    use tempfile::TempDir;
+
+    fn fake_segments(_audio: &[f32]) -> Result<Vec<TranscriptSegment>> {
+        Ok(vec![
+            TranscriptSegment {
+                text: " Hello there.".to_string(),
+                start_ms: 0,
+                end_ms: 1200,
+                confidence: None,
+                words: Vec::new(),
+            },
+            TranscriptSegment {
+                text: " General Kenobi.".to_string(),
+                start_ms: 1200,
+                end_ms: 2500,
+                confidence: None,
+                words: Vec::new(),
+            },
+        ])
+    }
+
+    /// Create a folder with two valid WAVs, one corrupt WAV and a non-audio file
+    fn create_fixture_folder() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        let one_second = vec![0.1; 16000];
+        write_wav(&root.join("a_valid.wav"), &one_second, 16000).unwrap();
+
+        std::fs::create_dir(root.join("nested")).unwrap();
+        let half_second = vec![0.1; 4000];
+        write_wav(&root.join("nested").join("c_valid.wav"), &half_second, 8000).unwrap();
+
+        std::fs::write(root.join("b_corrupt.wav"), b"definitely not a RIFF header").unwrap();
+        std::fs::write(root.join("notes.txt"), b"ignore me").unwrap();
+
+        temp_dir
+    }
+
+    #[test]
+    fn test_is_supported_audio() {
+        assert!(is_supported_audio(Path::new("memo.wav")));
+        assert!(is_supported_audio(Path::new("MEMO.WAV")));
+        assert!(is_supported_audio(Path::new("voice memo.mp3")));
+        assert!(!is_supported_audio(Path::new("notes.txt")));
+        assert!(!is_supported_audio(Path::new("no_extension")));
+    }
+
+    #[test]
+    fn test_discover_audio_files_recursive() {
+        let temp_dir = create_fixture_folder();
+        let root = temp_dir.path();
+
+        let flat = discover_audio_files(root, false).unwrap();
+        assert_eq!(flat.len(), 2);
+
+        let recursive = discover_audio_files(root, true).unwrap();
+        assert_eq!(recursive.len(), 3);
+        assert!(recursive.iter().any(|p| p.ends_with("nested/c_valid.wav")));
+    }
+
+    #[test]
+    fn test_discover_skips_transcripts_folder() {
+        let temp_dir = create_fixture_folder();
+        let root = temp_dir.path();
+
+        std::fs::create_dir(root.join(TRANSCRIPTS_DIR)).unwrap();
+        write_wav(
+            &root.join(TRANSCRIPTS_DIR).join("old.wav"),
+            &[0.0; 100],
+            16000,
+        )
+        .unwrap();
+
+        let files = discover_audio_files(root, true).unwrap();
+        assert_eq!(files.len(), 3);
+    }
+
+    #[test]
+    fn test_run_batch_manifest_with_mixed_files() {
+        let temp_dir = create_fixture_folder();
+        let root = temp_dir.path();
+        let output_dir = root.join(TRANSCRIPTS_DIR);
+        let files = discover_audio_files(root, true).unwrap();
+        let cancelled = AtomicBool::new(false);
+        let mut events = Vec::new();
+
+        let manifest = run_batch(
+            &files,
+            root,
+            &output_dir,
+            true,
+            &cancelled,
+            fake_segments,
+            |p| events.push(p),
+        );
+
+        assert_eq!(manifest.files.len(), 3);
+        assert_eq!(manifest.succeeded, 2);
+        assert_eq!(manifest.failed, 1);
+        assert!(!manifest.cancelled);
+
+        // The corrupt file fails with a reason and doesn't abort the batch
+        let corrupt = manifest
+            .files
+            .iter()
+            .find(|f| f.source_path.ends_with("b_corrupt.wav"))
+            .unwrap();
+        assert_eq!(corrupt.status, BatchFileStatus::Failed);
+        assert!(corrupt.error.is_some());
+        assert!(corrupt.transcript_path.is_none());
+
+        // Audio totals: 1.0s + 0.5s (the nested file is resampled from 8 kHz)
+        assert!((manifest.total_audio_secs - 1.5).abs() < 0.01);
+
+        // Outputs mirror the input layout
+        let text = std::fs::read_to_string(output_dir.join("a_valid.wav.txt")).unwrap();
+        assert_eq!(text, "Hello there. General Kenobi.");
+        assert!(output_dir.join("a_valid.wav.srt").exists());
+        assert!(output_dir.join("nested").join("c_valid.wav.txt").exists());
+        assert!(!output_dir.join("b_corrupt.wav.txt").exists());
+
+        // Two events per file: processing, then the final status
+        assert_eq!(events.len(), 6);
+        assert_eq!(events[0].status, BatchFileStatus::Processing);
+        assert_eq!(events[3].status, BatchFileStatus::Failed);
+    }
+
+    #[test]
+    fn test_run_batch_transcribes_mp3() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tone_mono.mp3");
+        std::fs::copy(fixture, root.join("memo.mp3")).unwrap();
+        let output_dir = root.join(TRANSCRIPTS_DIR);
+        let files = discover_audio_files(root, false).unwrap();
+        let cancelled = AtomicBool::new(false);
+
+        let manifest = run_batch(
+            &files,
+            root,
+            &output_dir,
+            false,
+            &cancelled,
+            fake_segments,
+            |_| {},
+        );
+
+        assert_eq!(manifest.succeeded, 1);
+        // 24 MP3 frames at 44.1 kHz
+        assert!((manifest.total_audio_secs - 0.627).abs() < 0.03);
+        assert!(output_dir.join("memo.mp3.txt").exists());
+    }
+
+    #[test]
+    fn test_run_batch_keeps_same_named_files_apart() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tone_mono.mp3");
+        std::fs::copy(fixture, root.join("memo.mp3")).unwrap();
+        write_wav(&root.join("memo.wav"), &[0.0; 16000], 16000).unwrap();
+        let output_dir = root.join(TRANSCRIPTS_DIR);
+        let files = discover_audio_files(root, false).unwrap();
+        let cancelled = AtomicBool::new(false);
+
+        let manifest = run_batch(
+            &files,
+            root,
+            &output_dir,
+            true,
+            &cancelled,
+            fake_segments,
+            |_| {},
+        );
+
+        assert_eq!(manifest.succeeded, 2);
+        for name in ["memo.mp3", "memo.wav"] {
+            assert!(output_dir.join(format!("{}.txt", name)).exists());
+            assert!(output_dir.join(format!("{}.srt", name)).exists());
+        }
+        let transcripts: Vec<_> = manifest
+            .files
+            .iter()
+            .map(|f| f.transcript_path.clone().unwrap())
+            .collect();
+        assert_ne!(transcripts[0], transcripts[1]);
+    }
+
+    #[test]
+    fn test_run_batch_transcription_error_is_recorded() {
+        let temp_dir = create_fixture_folder();
+        let root = temp_dir.path();
+        let files = discover_audio_files(root, false).unwrap();
+        let cancelled = AtomicBool::new(false);
+
+        let manifest = run_batch(
+            &files,
+            root,
+            &root.join(TRANSCRIPTS_DIR),
+            false,
+            &cancelled,
+            |_| Err(anyhow!("model exploded")),
+            |_| {},
+        );
+
+        assert_eq!(manifest.succeeded, 0);
+        assert_eq!(manifest.failed, 2);
+        assert!(manifest
+            .files
+            .iter()
+            .any(|f| f.error.as_deref() == Some("model exploded")));
+    }
+
+    #[test]
+    fn test_run_batch_cancel_stops_after_current_file() {
+        let temp_dir = create_fixture_folder();
+        let root = temp_dir.path();
+        let files = discover_audio_files(root, true).unwrap();
+        let cancelled = AtomicBool::new(false);
+
+        let manifest = run_batch(
+            &files,
+            root,
+            &root.join(TRANSCRIPTS_DIR),
+            false,
+            &cancelled,
+            |audio| {
+                // Cancel while the first file is being transcribed
+                cancelled.store(true, Ordering::SeqCst);
+                fake_segments(audio)
+            },
+            |_| {},
+        );
+
+        assert!(manifest.cancelled);
+        assert_eq!(manifest.files.len(), 1);
+        assert_eq!(manifest.succeeded, 1);
+    }
+
+    #[test]
+    fn test_format_srt() {
+        let segments = fake_segments(&[]).unwrap();
+        let srt = format_srt(&segments);
+        assert_eq!(
+            srt,
+            "1\n00:00:00,000 --> 00:00:01,200\nHello there.\n\n\
+             2\n00:00:01,200 --> 00:00:02,500\nGeneral Kenobi.\n\n"
+        );
+    }
+
+    #[test]
+    fn test_format_srt_timestamp() {
+        assert_eq!(format_srt_timestamp(0), "00:00:00,000");
+        assert_eq!(format_srt_timestamp(3_723_004), "01:02:03,004");
+        assert_eq!(format_srt_timestamp(-5), "00:00:00,000");
+    }
+}
@@ -0,0 +1,350 @@
+//! Moving over from another Whisper dictation app.
+//!
+//! Known data folders of the other app are searched for GGML model files,
+//! which are linked (or copied) into the models folder, and for a JSON
+//! settings file whose fields are mapped onto ours where they mean the same
+//! thing. Settings are matched by key name, so values from apps that keep
+//! their preferences in another format are reported as not found rather
+//! than guessed at.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use crate::commands::models::import_models_from_dir;
+use crate::commands::settings::AppSettings;
+use crate::engines::whisper::MAX_INITIAL_PROMPT_CHARS;
+use crate::error::BlahError;
+use crate::models::registry::ModelRegistry;
+
+/// Settings files looked for in each data folder
+const SETTINGS_FILES: [&str; 3] = ["settings.json", "config.json", "preferences.json"];
+
+/// App to migrate from
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationSource {
+    SuperWhisper,
+    WhisperDictation,
+    /// Folder holding the models (directly or in `models/`) and settings
+    Custom(PathBuf),
+}
+
+impl MigrationSource {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::SuperWhisper => "SuperWhisper",
+            Self::WhisperDictation => "Whisper Dictation",
+            Self::Custom(_) => "the chosen folder",
+        }
+    }
+
+    /// Folders the app may keep its data in
+    fn roots(&self, home: &Path) -> Vec<PathBuf> {
+        let app_support = home.join("Library").join("Application Support");
+        match self {
+            Self::SuperWhisper => {
+                vec![home.join(".superwhisper"), app_support.join("superwhisper")]
+            }
+            Self::WhisperDictation => vec![
+                home.join(".whisper-dictation"),
+                app_support.join("Whisper Dictation"),
+            ],
+            Self::Custom(path) => vec![path.clone()],
+        }
+    }
+}
+
+/// What `migrate_from_competitor` brought over
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MigrationReport {
+    /// Ids of the models now in the models folder
+    pub models_imported: Vec<String>,
+    /// Names of the settings that were changed
+    pub settings_copied: Vec<String>,
+    /// Files and values that were left out, and why
+    pub warnings: Vec<String>,
+}
+
+/// Import the models and settings of `source` into `settings` and
+/// `registry`. Fails if none of the source's data folders exist.
+pub(crate) fn migrate(
+    source: &MigrationSource,
+    home: &Path,
+    models_dir: &Path,
+    settings: &mut AppSettings,
+    registry: &mut ModelRegistry,
+) -> Result<MigrationReport, BlahError> {
+    let roots: Vec<PathBuf> = source
+        .roots(home)
+        .into_iter()
+        .filter(|root| root.is_dir())
+        .collect();
+    if roots.is_empty() {
+        return Err(BlahError::InvalidInput(format!(
+            "No data from {} found",
+            source.name()
+        )));
+    }
+
+    let mut report = MigrationReport::default();
+    for root in &roots {
+        for dir in [root.join("models"), root.clone()] {
+            if !dir.is_dir() {
+                continue;
+            }
+            let found = import_models_from_dir(&dir, models_dir, registry);
+            for id in found.imported {
+                if !report.models_imported.contains(&id) {
+                    report.models_imported.push(id);
+                }
+            }
+            report.warnings.extend(found.warnings);
+        }
+    }
+
+    for path in roots
+        .iter()
+        .flat_map(|root| SETTINGS_FILES.map(|f| root.join(f)))
+    {
+        if !path.is_file() {
+            continue;
+        }
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()));
+        match parsed {
+            Ok(json) => map_settings(&json, settings, registry, &mut report),
+            Err(e) => report
+                .warnings
+                .push(format!("Couldn't read {}: {}", path.display(), e)),
+        }
+    }
+
+    tracing::info!(
+        "Migrated from {}: {} models, {} settings, {} warnings",
+        source.name(),
+        report.models_imported.len(),
+        report.settings_copied.len(),
+        report.warnings.len()
+    );
+    Ok(report)
+}
+
+/// `key` lowercased without separators, so `auto_paste`, `autoPaste` and
+/// `auto-paste` compare equal
+fn normalize_key(key: &str) -> String {
+    key.chars()
+        .filter(|c| !matches!(c, '_' | '-' | ' '))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Copy the fields of a competitor's settings `json` that have an
+/// equivalent here into `settings`, noting each in `report`
+fn map_settings(
+    json: &Value,
+    settings: &mut AppSettings,
+    registry: &ModelRegistry,
+    report: &mut MigrationReport,
+) {
+    let Some(fields) = json.as_object() else {
+        report
+            .warnings
+            .push("Settings file isn't a JSON object".to_string());
+        return;
+    };
+
+    for (key, value) in fields {
+        let copied = match normalize_key(key).as_str() {
+            "hotkey" | "shortcut" | "dictationhotkey" | "dictationshortcut" | "recordshortcut" => {
+                value.as_str().map(|hotkey| {
+                    crate::hotkeys::validate_hotkeys(hotkey, &settings.tts_hotkey)?;
+                    settings.stt_hotkey = hotkey.to_string();
+                    Ok("stt_hotkey")
+                })
+            }
+            "autopaste" | "pasteresult" | "pastetranscription" => value.as_bool().map(|paste| {
+                settings.auto_paste = paste;
+                Ok("auto_paste")
+            }),
+            "launchatlogin" | "openatlogin" | "startatlogin" => value.as_bool().map(|launch| {
+                settings.launch_at_login = launch;
+                Ok("launch_at_login")
+            }),
+            "prompt" | "initialprompt" | "vocabulary" => value.as_str().map(|prompt| {
+                if prompt.chars().count() > MAX_INITIAL_PROMPT_CHARS {
+                    return Err(BlahError::InvalidInput(format!(
+                        "longer than {} characters",
+                        MAX_INITIAL_PROMPT_CHARS
+                    )));
+                }
+                settings.whisper_initial_prompt = Some(prompt.trim())
+                    .filter(|p| !p.is_empty())
+                    .map(String::from);
+                Ok("whisper_initial_prompt")
+            }),
+            "model" | "selectedmodel" | "modelname" => value.as_str().map(|model| {
+                let Some(id) = model_id(model, registry) else {
+                    return Err(BlahError::ModelNotFound(model.to_string()));
+                };
+                settings.stt_model = id;
+                Ok("stt_model")
+            }),
+            _ => continue,
+        };
+
+        match copied {
+            Some(Ok(field)) => {
+                if !report.settings_copied.iter().any(|f| f == field) {
+                    report.settings_copied.push(field.to_string());
+                }
+            }
+            Some(Err(e)) => report.warnings.push(format!("Skipped {}: {}", key, e)),
+            None => report
+                .warnings
+                .push(format!("Skipped {}: unexpected value {}", key, value)),
+        }
+    }
+}
+
+/// Our id for a model another app calls `name`, e.g. `base.en` or
+/// `ggml-base.en.bin`
+fn model_id(name: &str, registry: &ModelRegistry) -> Option<String> {
+    let name = name.trim();
+    [
+        name.to_string(),
+        format!("{}.bin", name),
+        format!("ggml-{}.bin", name),
+    ]
+    .into_iter()
+    .find(|id| registry.get_model(id).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn mapped(json: Value) -> (AppSettings, MigrationReport) {
+        let mut settings = AppSettings::default();
+        let mut report = MigrationReport::default();
+        map_settings(&json, &mut settings, &ModelRegistry::new(), &mut report);
+        (settings, report)
+    }
+
+    #[test]
+    fn test_source_roots() {
+        let home = Path::new("/Users/me");
+        assert_eq!(
+            MigrationSource::SuperWhisper.roots(home),
+            vec![
+                PathBuf::from("/Users/me/.superwhisper"),
+                PathBuf::from("/Users/me/Library/Application Support/superwhisper"),
+            ]
+        );
+        let custom = MigrationSource::Custom(PathBuf::from("/Volumes/Backup/whisper"));
+        assert_eq!(
+            custom.roots(home),
+            vec![PathBuf::from("/Volumes/Backup/whisper")]
+        );
+    }
+
+    #[test]
+    fn test_source_deserializes_snake_case() {
+        let source: MigrationSource = serde_json::from_value(json!("super_whisper")).unwrap();
+        assert_eq!(source, MigrationSource::SuperWhisper);
+        let source: MigrationSource =
+            serde_json::from_value(json!({ "custom": "/tmp/models" })).unwrap();
+        assert_eq!(
+            source,
+            MigrationSource::Custom(PathBuf::from("/tmp/models"))
+        );
+    }
+
+    #[test]
+    fn test_map_settings() {
+        let (settings, report) = mapped(json!({
+            "dictationShortcut": "CommandOrControl+Alt+D",
+            "auto_paste": false,
+            "launch-at-login": true,
+            "vocabulary": "  Tauri, Kokoro  ",
+            "selectedModel": "small.en",
+            "theme": "dark",
+        }));
+
+        assert_eq!(settings.stt_hotkey, "CommandOrControl+Alt+D");
+        assert!(!settings.auto_paste);
+        assert!(settings.launch_at_login);
+        assert_eq!(
+            settings.whisper_initial_prompt.as_deref(),
+            Some("Tauri, Kokoro")
+        );
+        assert_eq!(settings.stt_model, "ggml-small.en.bin");
+        assert_eq!(
+            report.settings_copied,
+            [
+                "auto_paste",
+                "stt_hotkey",
+                "launch_at_login",
+                "stt_model",
+                "whisper_initial_prompt"
+            ]
+        );
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+    }
+
+    #[test]
+    fn test_map_settings_warns_about_unusable_values() {
+        let (settings, report) = mapped(json!({
+            "hotkey": "Cmd+Space",
+            "autoPaste": "yes",
+            "model": "whisper-giant",
+        }));
+
+        let defaults = AppSettings::default();
+        assert_eq!(settings.stt_hotkey, defaults.stt_hotkey);
+        assert_eq!(settings.auto_paste, defaults.auto_paste);
+        assert_eq!(settings.stt_model, defaults.stt_model);
+        assert!(report.settings_copied.is_empty());
+        assert_eq!(report.warnings.len(), 3, "{:?}", report.warnings);
+
+        let (_, report) = mapped(json!(["not", "an", "object"]));
+        assert_eq!(report.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_migrate_requires_source_data() {
+        let home = TempDir::new().unwrap();
+        let err = migrate(
+            &MigrationSource::WhisperDictation,
+            home.path(),
+            &home.path().join("ours"),
+            &mut AppSettings::default(),
+            &mut ModelRegistry::new(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, BlahError::InvalidInput(_)));
+    }
+
+    #[test]
+    fn test_migrate_reads_unreadable_settings_as_warning() {
+        let home = TempDir::new().unwrap();
+        let root = home.path().join(".superwhisper");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("settings.json"), "not json").unwrap();
+
+        let report = migrate(
+            &MigrationSource::SuperWhisper,
+            home.path(),
+            &home.path().join("ours"),
+            &mut AppSettings::default(),
+            &mut ModelRegistry::new(),
+        )
+        .unwrap();
+        assert!(report.models_imported.is_empty());
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].contains("settings.json"));
+    }
+}
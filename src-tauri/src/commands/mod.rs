@@ -1,3 +1,7 @@
+pub mod accessibility;
+pub mod batch;
+pub mod config;
+pub mod migration;
 pub mod models;
 pub mod permissions;
 pub mod settings;
@@ -1,13 +1,25 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use tauri::Emitter;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tokio_util::sync::CancellationToken;
 
+use crate::commands::settings::DEFAULT_DOWNLOAD_MAX_RETRIES;
+use crate::engines::EngineRegistry;
+use crate::error::BlahError;
+use crate::events;
 use crate::models::{
-    download::{extract_zip, ModelDownloader},
-    registry::ModelRegistry,
+    download::{
+        extract_zip_atomic, finalize_download, format_size, is_download_artifact,
+        temp_download_path, DownloadManager, DownloadProgress, FileTooLargeError, ModelDownloader,
+        MAX_MODEL_SIZE,
+    },
+    hardware::HardwareDetector,
+    registry::{custom_models_path, ModelRegistry},
 };
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ModelInfo {
     pub id: String,
     pub name: String,
@@ -17,6 +29,20 @@ pub struct ModelInfo {
     pub download_url: String,
     pub status: ModelStatus,
     pub description: String,
+    /// Ids of models that have to be downloaded too for this one to work
+    #[serde(default)]
+    pub requires: Vec<String>,
+    /// Whether everything in `requires` is downloaded, set by `list_models`
+    #[serde(default = "default_true")]
+    pub dependencies_met: bool,
+    /// Whether this machine has the memory to run the model, set by
+    /// `list_models`
+    #[serde(default = "default_true")]
+    pub hardware_compatible: bool,
+}
+
+fn default_true() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -36,53 +62,191 @@ pub enum ModelStatus {
 
 #[tauri::command]
 pub fn list_models() -> Vec<ModelInfo> {
-    let registry = ModelRegistry::new();
+    let registry = ModelRegistry::default();
     let models_dir = get_models_dir();
+    let hardware = HardwareDetector::detect();
 
-    registry
+    let models = registry
         .get_all_models()
         .into_iter()
         .map(|mut model| {
-            let model_path = models_dir
-                .join(match model.model_type {
-                    ModelType::Stt => "stt",
-                    ModelType::Tts => "tts",
-                })
-                .join(&model.id);
-
-            model.status = if model_path.exists() {
-                ModelStatus::Downloaded
-            } else {
-                ModelStatus::Available
-            };
+            model.hardware_compatible = hardware.has_memory_for(model.size_bytes);
+            with_download_status(model, &models_dir)
+        })
+        .collect();
+    with_dependency_status(models)
+}
 
-            model
+/// Whether this machine has the memory to run `model_id`, with room to
+/// spare for inference. False for unknown models.
+#[tauri::command]
+pub fn can_run_model(model_id: String) -> bool {
+    HardwareDetector::detect().can_run_model(&model_id)
+}
+
+/// Where `model` is stored under `models_dir`
+fn model_path(models_dir: &Path, model: &ModelInfo) -> PathBuf {
+    models_dir
+        .join(match model.model_type {
+            ModelType::Stt => "stt",
+            ModelType::Tts => "tts",
         })
-        .collect()
+        .join(&model.id)
 }
 
+/// `model` with its status set from whether it is in `models_dir`
+fn with_download_status(mut model: ModelInfo, models_dir: &Path) -> ModelInfo {
+    model.status = if model_path(models_dir, &model).exists() {
+        ModelStatus::Downloaded
+    } else {
+        ModelStatus::Available
+    };
+
+    model
+}
+
+/// `models` with `dependencies_met` set from the status of the models they
+/// require. A requirement missing from `models` counts as not downloaded.
+fn with_dependency_status(mut models: Vec<ModelInfo>) -> Vec<ModelInfo> {
+    let downloaded: Vec<String> = models
+        .iter()
+        .filter(|m| m.status == ModelStatus::Downloaded)
+        .map(|m| m.id.clone())
+        .collect();
+    for model in &mut models {
+        model.dependencies_met = model.requires.iter().all(|r| downloaded.contains(r));
+    }
+    models
+}
+
+/// Total download size in bytes of the models recommended for this
+/// machine, for showing before downloading them all
+#[tauri::command]
+pub fn get_recommended_download_size() -> u64 {
+    let profile = HardwareDetector::detect();
+    ModelRegistry::default().recommended_models_size(&profile)
+}
+
+/// Longest display name a model can be given
+pub const MAX_MODEL_NAME_CHARS: usize = 100;
+
+/// Give a custom model a new display name. Only the name in the custom
+/// models list changes, not the file on disk.
+#[tauri::command]
+pub fn rename_custom_model(model_id: String, new_name: String) -> Result<ModelInfo, BlahError> {
+    let mut registry = ModelRegistry::default();
+    let model = rename_in_registry(&mut registry, &model_id, &new_name)?;
+    registry
+        .save_to_disk(&custom_models_path())
+        .map_err(|e| BlahError::IoError(format!("Failed to save custom models: {}", e)))?;
+
+    tracing::info!("Renamed custom model {} to {:?}", model_id, model.name);
+    Ok(with_download_status(model, &get_models_dir()))
+}
+
+/// Check `new_name` and rename the custom model `model_id` to it
+fn rename_in_registry(
+    registry: &mut ModelRegistry,
+    model_id: &str,
+    new_name: &str,
+) -> Result<ModelInfo, BlahError> {
+    let name = new_name.trim();
+    if name.is_empty() {
+        return Err(BlahError::InvalidInput(
+            "Model name is required".to_string(),
+        ));
+    }
+    if name.chars().count() > MAX_MODEL_NAME_CHARS {
+        return Err(BlahError::InvalidInput(format!(
+            "Model name must be at most {} characters",
+            MAX_MODEL_NAME_CHARS
+        )));
+    }
+    let duplicate = registry
+        .get_all_models()
+        .into_iter()
+        .any(|m| m.id != model_id && m.name.to_lowercase() == name.to_lowercase());
+    if duplicate {
+        return Err(BlahError::InvalidInput(format!(
+            "Another model is already called {}",
+            name
+        )));
+    }
+
+    match registry.get_model(model_id) {
+        Some(_) => registry
+            .rename_custom_model(model_id, name)
+            .map_err(|e| BlahError::InvalidInput(e.to_string())),
+        None => Err(BlahError::ModelNotFound(format!(
+            "Unknown model: {}",
+            model_id
+        ))),
+    }
+}
+
+/// Download a model. With `include_dependencies`, models it requires that
+/// aren't downloaded yet are downloaded first; otherwise only the model
+/// itself is, and `list_models` reports what is still missing.
 #[tauri::command]
 pub async fn download_model(
     model_id: String,
+    include_dependencies: Option<bool>,
+    window: tauri::Window,
+) -> Result<String, BlahError> {
+    let models_dir = get_models_dir();
+    if include_dependencies.unwrap_or(false) {
+        let missing = ModelRegistry::default()
+            .missing_dependencies(&model_id, |m| model_path(&models_dir, m).exists());
+        for dependency in missing {
+            tracing::info!("Downloading {}, required by {}", dependency.id, model_id);
+            download_model_into(dependency.id, models_dir.clone(), window.clone()).await?;
+        }
+    }
+    download_model_into(model_id, models_dir, window).await
+}
+
+/// Download a model into `dest_dir` rather than the configured models directory.
+/// `dest_dir` gets the same `stt`/`tts` layout, so it can later be used as
+/// `custom_models_dir`.
+#[tauri::command]
+pub async fn download_model_to_path(
+    model_id: String,
+    dest_dir: String,
     window: tauri::Window,
-) -> Result<String, String> {
-    tracing::info!("Downloading model: {}", model_id);
+) -> Result<String, BlahError> {
+    let dest_dir = PathBuf::from(dest_dir);
+    ensure_writable(&dest_dir)?;
 
-    let registry = ModelRegistry::new();
+    download_model_into(model_id, dest_dir, window).await
+}
+
+async fn download_model_into(
+    model_id: String,
+    models_dir: PathBuf,
+    window: tauri::Window,
+) -> Result<String, BlahError> {
+    tracing::info!("Downloading model: {} into {:?}", model_id, models_dir);
+
+    let registry = ModelRegistry::default();
     let model = registry
         .get_model(&model_id)
-        .ok_or_else(|| format!("Model not found: {}", model_id))?;
+        .ok_or_else(|| BlahError::ModelNotFound(format!("Model not found: {}", model_id)))?;
 
-    let models_dir = get_models_dir();
     let type_dir = models_dir.join(match model.model_type {
         ModelType::Stt => "stt",
         ModelType::Tts => "tts",
     });
 
-    std::fs::create_dir_all(&type_dir).map_err(|e| e.to_string())?;
+    // Registered for the whole download so `cancel_all_downloads` can stop it
+    let download = window
+        .state::<DownloadManager>()
+        .register(&model_id)
+        .map_err(|e| BlahError::Busy(e.to_string()))?;
+
+    std::fs::create_dir_all(&type_dir)?;
 
     let dest_path = type_dir.join(&model_id);
-    let downloader = ModelDownloader::new();
+    let downloader = configured_downloader()?;
     let model_id_for_progress = model_id.clone();
 
     // Check if this is a CoreML model (zip file that needs extraction)
@@ -92,16 +256,26 @@ pub async fn download_model(
         // Download to a temp zip file
         let zip_path = type_dir.join(format!("{}.zip", model_id));
 
-        downloader
-            .download(&model.download_url, &zip_path, move |progress| {
-                let _ = window.emit("model-download-progress", (&model_id_for_progress, progress));
-            })
-            .await
-            .map_err(|e| e.to_string())?;
+        let transfer = download_resuming(
+            &downloader,
+            &model.download_url,
+            &zip_path,
+            move |progress| {
+                events::emit(
+                    &window,
+                    events::ModelDownloadProgress {
+                        model_id: model_id_for_progress.clone(),
+                        progress,
+                    },
+                );
+            },
+        );
+        cancellable(download.token(), &model_id, &zip_path, transfer).await?;
 
-        // Extract the zip to the destination directory
+        // Extract via a temp directory so a failure never leaves a partial .mlmodelc
         tracing::info!("Extracting CoreML model: {} -> {:?}", model_id, dest_path);
-        extract_zip(&zip_path, &dest_path).map_err(|e| format!("Failed to extract: {}", e))?;
+        extract_zip_atomic(&zip_path, &dest_path)
+            .map_err(|e| BlahError::DownloadFailed(format!("Failed to extract: {}", e)))?;
 
         // Clean up the zip file
         if let Err(e) = std::fs::remove_file(&zip_path) {
@@ -110,13 +284,27 @@ pub async fn download_model(
 
         tracing::info!("CoreML model extracted: {}", model_id);
     } else {
-        // Regular file download
-        downloader
-            .download(&model.download_url, &dest_path, move |progress| {
-                let _ = window.emit("model-download-progress", (&model_id_for_progress, progress));
-            })
-            .await
-            .map_err(|e| e.to_string())?;
+        // Stream into a temp file and only move it into place once complete,
+        // so an interrupted download never looks like an installed model
+        let temp_path = temp_download_path(&dest_path);
+        let transfer = download_resuming(
+            &downloader,
+            &model.download_url,
+            &temp_path,
+            move |progress| {
+                events::emit(
+                    &window,
+                    events::ModelDownloadProgress {
+                        model_id: model_id_for_progress.clone(),
+                        progress,
+                    },
+                );
+            },
+        );
+        cancellable(download.token(), &model_id, &temp_path, transfer).await?;
+
+        finalize_download(&temp_path, &dest_path)
+            .map_err(|e| BlahError::IoError(e.to_string()))?;
 
         tracing::info!("Model downloaded: {}", model_id);
     }
@@ -124,41 +312,353 @@ pub async fn download_model(
     Ok(dest_path.to_string_lossy().to_string())
 }
 
+/// Register a model that isn't in the built-in list and download it. Lets
+/// users pull models from private or self-hosted repositories; the model is
+/// saved with the custom models, so it can be re-downloaded or deleted like
+/// any other. Returns the path of the downloaded model.
+#[tauri::command]
+pub async fn import_from_url(
+    url: String,
+    model_id: String,
+    model_type: ModelType,
+    name: String,
+    window: tauri::Window,
+) -> Result<String, BlahError> {
+    let url = url.trim().to_string();
+    let name = name.trim().to_string();
+    validate_import(&url, &model_id, &name)?;
+    tracing::info!("Importing model {} from {}", model_id, url);
+
+    // Refuse oversized files before registering anything
+    let downloader = configured_downloader()?;
+    let size_bytes = downloader
+        .remote_size(&url)
+        .await
+        .map_err(|e| BlahError::DownloadFailed(e.to_string()))?;
+    if let Some(size) = size_bytes {
+        downloader.check_size(size).map_err(download_error)?;
+    }
+
+    let model = ModelInfo {
+        id: model_id.clone(),
+        name,
+        model_type,
+        size_bytes: size_bytes.unwrap_or(0),
+        size_display: size_bytes
+            .map(format_size)
+            .unwrap_or_else(|| "Unknown size".to_string()),
+        description: format!("Imported from {}", url),
+        download_url: url,
+        status: ModelStatus::Available,
+        requires: Vec::new(),
+        dependencies_met: true,
+        hardware_compatible: true,
+    };
+
+    let mut registry = ModelRegistry::default();
+    registry
+        .add_custom_model(model)
+        .map_err(|e| BlahError::InvalidInput(e.to_string()))?;
+    registry
+        .save_to_disk(&custom_models_path())
+        .map_err(|e| BlahError::IoError(format!("Failed to save custom models: {}", e)))?;
+
+    download_model_into(model_id, get_models_dir(), window).await
+}
+
+/// Check the arguments to `import_from_url`. The id becomes the file name
+/// under the models directory, so it can't contain a path.
+pub(crate) fn validate_import(url: &str, model_id: &str, name: &str) -> Result<(), BlahError> {
+    if !url.starts_with("https://") {
+        return Err(BlahError::InvalidInput(
+            "Model URL must start with https://".to_string(),
+        ));
+    }
+    if reqwest::Url::parse(url).is_err() {
+        return Err(BlahError::InvalidInput(format!(
+            "Invalid model URL: {}",
+            url
+        )));
+    }
+    if model_id.trim().is_empty()
+        || model_id != model_id.trim()
+        || model_id.starts_with('.')
+        || model_id.contains(['/', '\\'])
+    {
+        return Err(BlahError::InvalidInput(format!(
+            "Invalid model id: {:?}",
+            model_id
+        )));
+    }
+    if name.is_empty() {
+        return Err(BlahError::InvalidInput(
+            "Model name is required".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Downloader for model downloads, honouring the proxy setting and capped
+/// at `MAX_MODEL_SIZE`
+fn configured_downloader() -> Result<ModelDownloader, BlahError> {
+    let http_proxy = crate::commands::settings::get_settings()
+        .ok()
+        .and_then(|settings| settings.http_proxy);
+    Ok(model_downloader(http_proxy.as_deref())?.with_max_size(MAX_MODEL_SIZE))
+}
+
+/// Map a downloader error, calling out files over the size limit
+fn download_error(error: anyhow::Error) -> BlahError {
+    match error.downcast_ref::<FileTooLargeError>() {
+        Some(e) => BlahError::InvalidInput(format!("Model is too large: {}", e)),
+        None => BlahError::DownloadFailed(error.to_string()),
+    }
+}
+
+/// Downloader going through `http_proxy` when one is configured, otherwise
+/// using the system proxy settings
+pub(crate) fn model_downloader(http_proxy: Option<&str>) -> Result<ModelDownloader, BlahError> {
+    match http_proxy.map(str::trim).filter(|url| !url.is_empty()) {
+        Some(url) => ModelDownloader::with_proxy(url).map_err(|e| BlahError::InvalidSetting {
+            field: "http_proxy".to_string(),
+            message: e.to_string(),
+        }),
+        None => Ok(ModelDownloader::new()),
+    }
+}
+
+/// Run `transfer` until it finishes or `token` is cancelled. A cancelled
+/// transfer is dropped mid-stream and its partial file at `partial_path` is
+/// deleted, so the next attempt starts from scratch.
+async fn cancellable(
+    token: &CancellationToken,
+    model_id: &str,
+    partial_path: &Path,
+    transfer: impl Future<Output = Result<(), BlahError>>,
+) -> Result<(), BlahError> {
+    tokio::select! {
+        result = transfer => result,
+        _ = token.cancelled() => {
+            tracing::info!("Download of {} cancelled", model_id);
+            if let Err(e) = std::fs::remove_file(partial_path) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    tracing::warn!("Failed to remove partial download {:?}: {}", partial_path, e);
+                }
+            }
+            Err(BlahError::DownloadFailed(format!(
+                "Download of {} was cancelled",
+                model_id
+            )))
+        }
+    }
+}
+
+/// Stop every model download in progress, deleting their partial files.
+/// Returns the ids of the cancelled models.
 #[tauri::command]
-pub fn delete_model(model_id: String) -> Result<(), String> {
+pub fn cancel_all_downloads(app: AppHandle) -> Result<Vec<String>, BlahError> {
+    let model_ids = app.state::<DownloadManager>().cancel_all();
+    tracing::info!("Cancelling {} model downloads", model_ids.len());
+
+    events::emit(
+        &app,
+        events::ModelDownloadAllCancelled {
+            model_ids: model_ids.clone(),
+        },
+    );
+    Ok(model_ids)
+}
+
+/// Download into `dest`, resuming any partial file and retrying transient
+/// failures up to `download_max_retries` times
+async fn download_resuming<F>(
+    downloader: &ModelDownloader,
+    url: &str,
+    dest: &Path,
+    progress_callback: F,
+) -> Result<(), BlahError>
+where
+    F: Fn(DownloadProgress) + Send + Clone + 'static,
+{
+    let max_retries = crate::commands::settings::get_settings()
+        .map(|settings| settings.download_max_retries)
+        .unwrap_or(DEFAULT_DOWNLOAD_MAX_RETRIES);
+
+    downloader
+        .download_with_retry(url, dest, progress_callback, max_retries)
+        .await
+        .map_err(download_error)
+}
+
+/// Delete a downloaded model. Returns the ids of downloaded models that
+/// require it and won't work until it is downloaded again.
+#[tauri::command]
+pub fn delete_model(model_id: String) -> Result<Vec<String>, BlahError> {
     tracing::info!("Deleting model: {}", model_id);
 
-    let registry = ModelRegistry::new();
+    let registry = ModelRegistry::default();
     let model = registry
         .get_model(&model_id)
-        .ok_or_else(|| format!("Model not found: {}", model_id))?;
+        .ok_or_else(|| BlahError::ModelNotFound(format!("Model not found: {}", model_id)))?;
 
     let models_dir = get_models_dir();
-    let model_path = models_dir
-        .join(match model.model_type {
-            ModelType::Stt => "stt",
-            ModelType::Tts => "tts",
-        })
-        .join(&model_id);
+    let model_path = model_path(&models_dir, &model);
 
     if model_path.exists() {
         if model_path.is_dir() {
-            std::fs::remove_dir_all(&model_path).map_err(|e| e.to_string())?;
+            std::fs::remove_dir_all(&model_path)?;
         } else {
-            std::fs::remove_file(&model_path).map_err(|e| e.to_string())?;
+            std::fs::remove_file(&model_path)?;
         }
         tracing::info!("Model deleted: {}", model_id);
     }
+    if model.model_type == ModelType::Tts {
+        // Cached speech may have been made with the deleted voices
+        crate::commands::tts::clear_speech_cache();
+    }
 
-    Ok(())
+    let broken = downloaded_dependents(&registry, &models_dir, &model_id);
+    if !broken.is_empty() {
+        tracing::warn!("Deleted {}, which {} need", model_id, broken.join(", "));
+    }
+    Ok(broken)
 }
 
+/// Ids of the downloaded models that require `model_id`
+fn downloaded_dependents(
+    registry: &ModelRegistry,
+    models_dir: &Path,
+    model_id: &str,
+) -> Vec<String> {
+    registry
+        .dependents(model_id)
+        .into_iter()
+        .filter(|m| model_path(models_dir, m).exists())
+        .map(|m| m.id)
+        .collect()
+}
+
+/// What `delete_all_models` removed
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DeletionResult {
+    /// Models, and any other files or folders, deleted
+    pub deleted_count: u32,
+    pub freed_bytes: u64,
+}
+
+/// Delete every downloaded model, or only those of `model_type`, and unload
+/// the engines using them. `confirm` has to be true, so the call can't be
+/// made by accident. Refused while a download is running.
 #[tauri::command]
-pub fn get_model_status(model_id: String) -> Result<ModelStatus, String> {
-    let registry = ModelRegistry::new();
+pub async fn delete_all_models(
+    app: AppHandle,
+    model_type: Option<ModelType>,
+    confirm: bool,
+) -> Result<DeletionResult, BlahError> {
+    if !confirm {
+        return Err(BlahError::InvalidInput(
+            "Deleting all models needs confirm set to true".to_string(),
+        ));
+    }
+    let downloading = app.state::<DownloadManager>().active();
+    if !downloading.is_empty() {
+        return Err(BlahError::InvalidInput(format!(
+            "Can't delete models while {} is downloading",
+            downloading.join(", ")
+        )));
+    }
+
+    let models_dir = get_models_dir();
+    tracing::info!("Deleting all {:?} models in {:?}", model_type, models_dir);
+    let result = delete_models_in(&models_dir, model_type.as_ref())?;
+
+    let engines = app.state::<Arc<EngineRegistry>>();
+    if model_type != Some(ModelType::Tts) {
+        engines.invalidate_whisper().await;
+    }
+    if model_type != Some(ModelType::Stt) {
+        crate::commands::tts::unload_tts_engines(&engines).await;
+    }
+    tracing::info!(
+        "Deleted {} models, freeing {}",
+        result.deleted_count,
+        format_size(result.freed_bytes)
+    );
+    Ok(result)
+}
+
+/// Delete the contents of the `stt/` and `tts/` folders of `models_dir`, or
+/// only the one for `model_type`. With no type, partial downloads and the
+/// version cache in `models_dir` go too. Anything else there is left alone,
+/// since a custom models folder can be shared with the user's own files.
+fn delete_models_in(
+    models_dir: &Path,
+    model_type: Option<&ModelType>,
+) -> Result<DeletionResult, BlahError> {
+    let folders: &[&str] = match model_type {
+        Some(ModelType::Stt) => &["stt"],
+        Some(ModelType::Tts) => &["tts"],
+        None => &["stt", "tts"],
+    };
+
+    let mut result = DeletionResult::default();
+    let mut delete_entries = |dir: &Path, delete: &dyn Fn(&str) -> bool| -> std::io::Result<()> {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        for entry in entries {
+            let entry = entry?;
+            if !delete(&entry.file_name().to_string_lossy()) {
+                continue;
+            }
+            let path = entry.path();
+            let size = size_on_disk(&path);
+            if entry.file_type()?.is_dir() {
+                std::fs::remove_dir_all(&path)?;
+            } else {
+                std::fs::remove_file(&path)?;
+            }
+            result.deleted_count += 1;
+            result.freed_bytes += size;
+        }
+        Ok(())
+    };
+
+    for folder in folders {
+        delete_entries(&models_dir.join(folder), &|_| true)?;
+    }
+    if model_type.is_none() {
+        delete_entries(models_dir, &is_download_artifact)?;
+    }
+    Ok(result)
+}
+
+/// Total size of the files at `path`, following into folders
+fn size_on_disk(path: &Path) -> u64 {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| size_on_disk(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+#[tauri::command]
+pub fn get_model_status(model_id: String) -> Result<ModelStatus, BlahError> {
+    let registry = ModelRegistry::default();
     let model = registry
         .get_model(&model_id)
-        .ok_or_else(|| format!("Model not found: {}", model_id))?;
+        .ok_or_else(|| BlahError::ModelNotFound(format!("Model not found: {}", model_id)))?;
 
     let models_dir = get_models_dir();
     let model_path = models_dir
@@ -175,9 +675,512 @@ pub fn get_model_status(model_id: String) -> Result<ModelStatus, String> {
     })
 }
 
-fn get_models_dir() -> PathBuf {
+/// Whether a model chosen in settings can be loaded
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ModelAvailability {
+    Downloaded,
+    /// Known to the registry but not downloaded yet
+    NotDownloaded(ModelInfo),
+    /// Neither a registered STT model nor a file in the models directory
+    Unknown,
+}
+
+/// Where `model_id` stands as the STT model. A file put in the models
+/// directory by hand counts as downloaded even without a registry entry.
+pub(crate) fn stt_model_availability(
+    registry: &ModelRegistry,
+    models_dir: &Path,
+    model_id: &str,
+) -> ModelAvailability {
+    if model_id.trim().is_empty() {
+        return ModelAvailability::Unknown;
+    }
+    if models_dir.join("stt").join(model_id).is_file() {
+        return ModelAvailability::Downloaded;
+    }
+    match registry.get_model(model_id) {
+        Some(model) if model.model_type == ModelType::Stt => {
+            ModelAvailability::NotDownloaded(model)
+        }
+        _ => ModelAvailability::Unknown,
+    }
+}
+
+/// Check the STT model from settings at startup. A missing model is
+/// flagged on the tray icon and reported as `configured-model-missing`,
+/// rather than waiting for the first dictation to fail.
+pub(crate) fn report_missing_stt_model(app: &AppHandle) {
+    let settings = crate::commands::settings::get_settings().unwrap_or_default();
+    let models_dir = resolve_models_dir(settings.custom_models_dir.as_deref());
+    let availability =
+        stt_model_availability(&ModelRegistry::default(), &models_dir, &settings.stt_model);
+    let downloadable = match availability {
+        ModelAvailability::Downloaded => return,
+        ModelAvailability::NotDownloaded(_) => true,
+        ModelAvailability::Unknown => false,
+    };
+
+    tracing::warn!(
+        "Configured STT model {} is missing (downloadable: {})",
+        settings.stt_model,
+        downloadable
+    );
+    if let Some(tray) = app.tray_by_id(crate::TRAY_ID) {
+        let tooltip = format!("Blah³ - Speech model {} is missing", settings.stt_model);
+        if let Err(e) = tray.set_tooltip(Some(tooltip)) {
+            tracing::warn!("Failed to update tray tooltip: {}", e);
+        }
+        if let Err(e) = tray.set_title(Some("!")) {
+            tracing::warn!("Failed to update tray title: {}", e);
+        }
+    }
+    events::emit(
+        app,
+        events::ConfiguredModelMissing {
+            model_id: settings.stt_model,
+            downloadable,
+        },
+    );
+}
+
+/// Where models live: `custom_models_dir` from settings if set, otherwise
+/// the app data directory
+pub(crate) fn get_models_dir() -> PathBuf {
+    let settings = crate::commands::settings::get_settings().unwrap_or_default();
+    resolve_models_dir(settings.custom_models_dir.as_deref())
+}
+
+pub(crate) fn resolve_models_dir(custom_dir: Option<&str>) -> PathBuf {
+    match custom_dir.map(str::trim) {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => default_models_dir(),
+    }
+}
+
+fn default_models_dir() -> PathBuf {
     dirs::data_dir()
         .unwrap_or_else(|| PathBuf::from("."))
         .join("com.blahcubed.app")
         .join("models")
 }
+
+/// Models directory currently in use, for the frontend
+#[tauri::command]
+pub fn get_models_path() -> String {
+    get_models_dir().to_string_lossy().to_string()
+}
+
+/// Create `dir` if needed and check a file can be written inside it
+pub(crate) fn ensure_writable(dir: &Path) -> Result<(), BlahError> {
+    let to_error = |e: std::io::Error| {
+        let message = format!("Can't write to {}: {}", dir.display(), e);
+        if e.kind() == std::io::ErrorKind::PermissionDenied {
+            BlahError::PermissionDenied(message)
+        } else {
+            BlahError::IoError(message)
+        }
+    };
+
+    std::fs::create_dir_all(dir).map_err(to_error)?;
+
+    let probe = dir.join(".blah3-write-test");
+    std::fs::write(&probe, b"").map_err(to_error)?;
+    if let Err(e) = std::fs::remove_file(&probe) {
+        tracing::warn!("Failed to remove write test file {:?}: {}", probe, e);
+    }
+
+    Ok(())
+}
+
+/// Whisper models found by `import_models_from_dir`
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct ImportedModels {
+    /// Ids of the models now in the models folder
+    pub imported: Vec<String>,
+    /// Files that were skipped, and why
+    pub warnings: Vec<String>,
+}
+
+/// Bring the Whisper model files in `dir` into `models_dir`, hard-linking
+/// them when both are on the same disk and copying them otherwise. Files
+/// that aren't Whisper models are skipped with a warning. Models the
+/// registry doesn't know are added to it as custom models, ready to use.
+pub(crate) fn import_models_from_dir(
+    dir: &Path,
+    models_dir: &Path,
+    registry: &mut ModelRegistry,
+) -> ImportedModels {
+    let mut result = ImportedModels::default();
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            result
+                .warnings
+                .push(format!("Can't read {}: {}", dir.display(), e));
+            return result;
+        }
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "bin"))
+        .collect();
+    files.sort();
+
+    let stt_dir = models_dir.join("stt");
+    for path in files {
+        let Some(name) = path.file_name() else {
+            continue;
+        };
+        let id = name.to_string_lossy().into_owned();
+        let check = crate::commands::stt::check_model_file(&path);
+        if !check.is_valid {
+            let reason = check.error.unwrap_or_default();
+            result.warnings.push(format!("Skipped {}: {}", id, reason));
+            continue;
+        }
+
+        let dest = stt_dir.join(&id);
+        if !dest.exists() {
+            let linked = std::fs::create_dir_all(&stt_dir)
+                .and_then(|()| std::fs::hard_link(&path, &dest))
+                .or_else(|_| std::fs::copy(&path, &dest).map(drop));
+            if let Err(e) = linked {
+                result.warnings.push(format!("Couldn't copy {}: {}", id, e));
+                continue;
+            }
+        }
+
+        if registry.get_model(&id).is_none() {
+            let name = id
+                .trim_end_matches(".bin")
+                .trim_start_matches("ggml-")
+                .to_string();
+            let model = ModelInfo {
+                id: id.clone(),
+                name,
+                model_type: ModelType::Stt,
+                size_bytes: check.size_bytes,
+                size_display: format_size(check.size_bytes),
+                download_url: String::new(),
+                status: ModelStatus::Downloaded,
+                description: format!("Imported from {}", dir.display()),
+                requires: Vec::new(),
+                dependencies_met: true,
+                hardware_compatible: true,
+            };
+            if let Err(e) = registry.add_custom_model(model) {
+                result
+                    .warnings
+                    .push(format!("Couldn't register {}: {}", id, e));
+                continue;
+            }
+        }
+        tracing::info!("Imported model {} from {}", id, dir.display());
+        result.imported.push(id);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn stt_fixture() -> (ModelRegistry, TempDir) {
+        let mut registry = ModelRegistry::new();
+        registry
+            .add_custom_model(ModelInfo {
+                id: "ggml-custom.bin".to_string(),
+                name: "Custom".to_string(),
+                model_type: ModelType::Stt,
+                size_bytes: 1_000,
+                size_display: "1 KB".to_string(),
+                download_url: "https://example.com/ggml-custom.bin".to_string(),
+                status: ModelStatus::Available,
+                description: "Imported".to_string(),
+                requires: Vec::new(),
+                dependencies_met: true,
+                hardware_compatible: true,
+            })
+            .unwrap();
+
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("stt")).unwrap();
+        std::fs::write(dir.path().join("stt").join("ggml-base.en.bin"), b"model").unwrap();
+        std::fs::write(dir.path().join("stt").join("ggml-mine.bin"), b"model").unwrap();
+        (registry, dir)
+    }
+
+    #[test]
+    fn test_rename_custom_model_checks_name() {
+        let (mut registry, _dir) = stt_fixture();
+
+        let model =
+            rename_in_registry(&mut registry, "ggml-custom.bin", "  Dictation v3 ").unwrap();
+        assert_eq!(model.name, "Dictation v3");
+        assert_eq!(
+            registry.get_model("ggml-custom.bin").unwrap().name,
+            "Dictation v3"
+        );
+        // Keeping its own name, or changing only the case, is fine
+        assert!(rename_in_registry(&mut registry, "ggml-custom.bin", "dictation V3").is_ok());
+
+        let too_long = "x".repeat(MAX_MODEL_NAME_CHARS + 1);
+        let builtin_name = registry.get_model("ggml-base.en.bin").unwrap().name;
+        for name in ["", "   ", too_long.as_str(), builtin_name.as_str()] {
+            let err = rename_in_registry(&mut registry, "ggml-custom.bin", name).unwrap_err();
+            assert_eq!(err.code(), "invalid_input", "{:?}", name);
+        }
+
+        let err = rename_in_registry(&mut registry, "ggml-base.en.bin", "Mine").unwrap_err();
+        assert_eq!(err.code(), "invalid_input");
+        let err = rename_in_registry(&mut registry, "ggml-nope.bin", "Mine").unwrap_err();
+        assert_eq!(err.code(), "model_not_found");
+    }
+
+    #[test]
+    fn test_stt_model_availability() {
+        let (registry, dir) = stt_fixture();
+        let availability = |id| stt_model_availability(&registry, dir.path(), id);
+
+        assert_eq!(
+            availability("ggml-base.en.bin"),
+            ModelAvailability::Downloaded
+        );
+        // Put there by hand, not registered
+        assert_eq!(availability("ggml-mine.bin"), ModelAvailability::Downloaded);
+        assert!(matches!(
+            availability("ggml-small.en.bin"),
+            ModelAvailability::NotDownloaded(ref model) if model.id == "ggml-small.en.bin"
+        ));
+        assert!(matches!(
+            availability("ggml-custom.bin"),
+            ModelAvailability::NotDownloaded(_)
+        ));
+    }
+
+    #[test]
+    fn test_stt_model_availability_unknown() {
+        let (registry, dir) = stt_fixture();
+        let availability = |id| stt_model_availability(&registry, dir.path(), id);
+
+        assert_eq!(availability("ggml-nope.bin"), ModelAvailability::Unknown);
+        assert_eq!(availability(""), ModelAvailability::Unknown);
+        // A TTS model can't be used for dictation
+        assert_eq!(availability("kokoro-v1.0.onnx"), ModelAvailability::Unknown);
+    }
+
+    fn models_fixture() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        let stt = dir.path().join("stt");
+        let tts = dir.path().join("tts");
+        std::fs::create_dir_all(stt.join("ggml-base-encoder.mlmodelc")).unwrap();
+        std::fs::create_dir_all(&tts).unwrap();
+        std::fs::write(stt.join("ggml-base.bin"), vec![0u8; 100]).unwrap();
+        let weights = stt.join("ggml-base-encoder.mlmodelc").join("weights");
+        std::fs::write(weights, vec![0u8; 50]).unwrap();
+        std::fs::write(tts.join("kokoro-v1.0.onnx"), vec![0u8; 30]).unwrap();
+        std::fs::write(dir.path().join("ggml-large.bin.download"), vec![0u8; 5]).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), b"mine").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_delete_models_of_one_type() {
+        let dir = models_fixture();
+        let result = delete_models_in(dir.path(), Some(&ModelType::Stt)).unwrap();
+        assert_eq!(
+            result,
+            DeletionResult {
+                deleted_count: 2,
+                freed_bytes: 150,
+            }
+        );
+        assert!(dir.path().join("stt").is_dir());
+        let left = std::fs::read_dir(dir.path().join("stt")).unwrap().count();
+        assert_eq!(left, 0);
+        assert!(dir.path().join("tts/kokoro-v1.0.onnx").exists());
+        assert!(dir.path().join("ggml-large.bin.download").exists());
+    }
+
+    #[test]
+    fn test_delete_all_models_in_dir() {
+        let dir = models_fixture();
+        let result = delete_models_in(dir.path(), None).unwrap();
+        assert_eq!(result.deleted_count, 4);
+        assert_eq!(result.freed_bytes, 185);
+        assert!(!dir.path().join("ggml-large.bin.download").exists());
+        assert!(!dir.path().join("tts/kokoro-v1.0.onnx").exists());
+        // The folder may be shared with files that aren't models
+        assert!(dir.path().join("notes.txt").exists());
+
+        // Nothing downloaded yet
+        let empty = TempDir::new().unwrap();
+        let missing = empty.path().join("models");
+        assert_eq!(
+            delete_models_in(&missing, None).unwrap(),
+            DeletionResult::default()
+        );
+    }
+
+    #[test]
+    fn test_resolve_models_dir_default() {
+        assert_eq!(resolve_models_dir(None), default_models_dir());
+        assert_eq!(resolve_models_dir(Some("  ")), default_models_dir());
+    }
+
+    #[test]
+    fn test_resolve_models_dir_custom() {
+        assert_eq!(
+            resolve_models_dir(Some("/Volumes/Models")),
+            PathBuf::from("/Volumes/Models")
+        );
+    }
+
+    #[test]
+    fn test_validate_import_accepts_https() {
+        assert!(validate_import(
+            "https://huggingface.co/me/private/resolve/main/ggml-custom.bin",
+            "ggml-custom.bin",
+            "My Whisper",
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_validate_import_rejects_non_https() {
+        for url in [
+            "http://example.com/model.bin",
+            "ftp://example.com/m.bin",
+            "https://",
+        ] {
+            let err = validate_import(url, "model.bin", "Model").unwrap_err();
+            assert_eq!(err.code(), "invalid_input", "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_validate_import_rejects_path_ids() {
+        for id in [
+            "",
+            " model.bin",
+            "../model.bin",
+            "stt/model.bin",
+            "a\\b.bin",
+            ".hidden",
+        ] {
+            let err = validate_import("https://example.com/m.bin", id, "Model").unwrap_err();
+            assert_eq!(err.code(), "invalid_input", "{:?}", id);
+        }
+    }
+
+    #[test]
+    fn test_download_error_calls_out_size_limit() {
+        let err = download_error(
+            FileTooLargeError {
+                size: 12_000_000_000,
+                max: MAX_MODEL_SIZE,
+            }
+            .into(),
+        );
+        assert_eq!(err.code(), "invalid_input");
+        assert!(err.to_string().contains("12 GB"));
+
+        let err = download_error(anyhow::anyhow!("connection reset"));
+        assert_eq!(err.code(), "download_failed");
+    }
+
+    #[test]
+    fn test_ensure_writable_creates_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("shared").join("models");
+
+        ensure_writable(&dir).unwrap();
+
+        assert!(dir.is_dir());
+        // The probe file is cleaned up
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_ensure_writable_rejects_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("not-a-dir");
+        std::fs::write(&file, b"x").unwrap();
+
+        let err = ensure_writable(&file).unwrap_err();
+        assert_eq!(err.code(), "io_error");
+    }
+
+    #[test]
+    fn test_with_dependency_status() {
+        let registry = ModelRegistry::new();
+        let mut models = registry.get_all_models();
+        for model in &mut models {
+            if model.id == "ggml-base.en.bin" {
+                model.status = ModelStatus::Downloaded;
+            }
+        }
+
+        let models = with_dependency_status(models);
+        let met = |id: &str| models.iter().find(|m| m.id == id).unwrap().dependencies_met;
+        assert!(met("ggml-base.en-encoder.mlmodelc"));
+        assert!(!met("ggml-small.en-encoder.mlmodelc"));
+        assert!(!met("kokoro-v1.0.onnx"));
+        assert!(met("ggml-base.en.bin"));
+    }
+
+    #[test]
+    fn test_downloaded_dependents() {
+        let registry = ModelRegistry::new();
+        let dir = TempDir::new().unwrap();
+        assert!(downloaded_dependents(&registry, dir.path(), "voices-v1.0.bin").is_empty());
+
+        std::fs::create_dir_all(dir.path().join("tts")).unwrap();
+        std::fs::write(dir.path().join("tts").join("kokoro-v1.0.onnx"), b"model").unwrap();
+        assert_eq!(
+            downloaded_dependents(&registry, dir.path(), "voices-v1.0.bin"),
+            ["kokoro-v1.0.onnx"]
+        );
+        assert!(downloaded_dependents(&registry, dir.path(), "kokoro-v1.0.onnx").is_empty());
+    }
+
+    fn write_whisper_model(path: &Path) {
+        let mut header = b"lmgg".to_vec();
+        for value in [51865i32, 1500, 512, 8, 6, 448, 512, 8, 6, 80, 1] {
+            header.extend_from_slice(&value.to_le_bytes());
+        }
+        std::fs::write(path, header).unwrap();
+        // Sparse, so the test doesn't write 20 MB
+        let file = std::fs::OpenOptions::new().write(true).open(path).unwrap();
+        file.set_len(20 * 1024 * 1024).unwrap();
+    }
+
+    #[test]
+    fn test_import_models_from_dir() {
+        let source = TempDir::new().unwrap();
+        write_whisper_model(&source.path().join("ggml-base.en.bin"));
+        write_whisper_model(&source.path().join("ggml-finetuned.bin"));
+        std::fs::write(source.path().join("notes.bin"), b"not a model").unwrap();
+        std::fs::write(source.path().join("readme.txt"), b"hello").unwrap();
+        let models_dir = TempDir::new().unwrap();
+        let mut registry = ModelRegistry::new();
+
+        let result = import_models_from_dir(source.path(), models_dir.path(), &mut registry);
+        assert_eq!(result.imported, ["ggml-base.en.bin", "ggml-finetuned.bin"]);
+        assert_eq!(result.warnings.len(), 1, "{:?}", result.warnings);
+        assert!(result.warnings[0].contains("notes.bin"));
+        assert!(models_dir.path().join("stt/ggml-finetuned.bin").is_file());
+        let custom = registry.get_model("ggml-finetuned.bin").unwrap();
+        assert_eq!(custom.name, "finetuned");
+        assert_eq!(custom.size_bytes, 20 * 1024 * 1024);
+        assert_eq!(registry.get_custom_models().len(), 1);
+
+        // Importing again finds the same models without duplicating them
+        let again = import_models_from_dir(source.path(), models_dir.path(), &mut registry);
+        assert_eq!(again.imported, result.imported);
+        assert_eq!(registry.get_custom_models().len(), 1);
+    }
+}
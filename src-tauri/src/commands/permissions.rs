@@ -1,13 +1,34 @@
 use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::events;
+
+/// Where System Settings lists the apps allowed to control the computer
+const ACCESSIBILITY_SETTINGS_URL: &str =
+    "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility";
 
 #[derive(Debug, Clone, Serialize)]
 pub struct PermissionStatus {
     pub microphone: bool,
+    /// Whether Accessibility works, i.e. `accessibility_state` is `Granted`
     pub accessibility: bool,
+    pub accessibility_state: AccessibilityState,
+}
+
+/// Whether pasting and reading selections can use the Accessibility API
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessibilityState {
+    Granted,
+    Denied,
+    /// Allowed in System Settings but refused anyway. macOS ties the grant
+    /// to the app's signature and location, so an update or a move leaves
+    /// the old entry checked but no longer matching this copy.
+    GrantedButNotWorking,
 }
 
 /// Check accessibility permission using AXIsProcessTrusted() from ApplicationServices framework
-fn check_accessibility() -> bool {
+pub(crate) fn check_accessibility() -> bool {
     #[link(name = "ApplicationServices", kind = "framework")]
     extern "C" {
         fn AXIsProcessTrusted() -> bool;
@@ -15,17 +36,200 @@ fn check_accessibility() -> bool {
     unsafe { AXIsProcessTrusted() }
 }
 
+/// Try a harmless Accessibility call, reading the focused app off the
+/// system-wide element. False only when macOS refuses the API outright.
+fn probe_accessibility() -> bool {
+    use std::ffi::{c_char, c_void};
+
+    type CfTypeRef = *const c_void;
+
+    // kAXErrorAPIDisabled from HIServices/AXError.h
+    const AX_ERROR_API_DISABLED: i32 = -25211;
+    const CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXUIElementCreateSystemWide() -> CfTypeRef;
+        fn AXUIElementCopyAttributeValue(
+            element: CfTypeRef,
+            attribute: CfTypeRef,
+            value: *mut CfTypeRef,
+        ) -> i32;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFStringCreateWithCString(
+            allocator: CfTypeRef,
+            string: *const c_char,
+            encoding: u32,
+        ) -> CfTypeRef;
+        fn CFRelease(cf: CfTypeRef);
+    }
+
+    unsafe {
+        let system = AXUIElementCreateSystemWide();
+        let attribute = CFStringCreateWithCString(
+            std::ptr::null(),
+            c"AXFocusedApplication".as_ptr(),
+            CF_STRING_ENCODING_UTF8,
+        );
+        if system.is_null() || attribute.is_null() {
+            return false;
+        }
+
+        let mut value: CfTypeRef = std::ptr::null();
+        let result = AXUIElementCopyAttributeValue(system, attribute, &mut value);
+        if !value.is_null() {
+            CFRelease(value);
+        }
+        CFRelease(attribute);
+        CFRelease(system);
+
+        // No focused app or a busy one still means the call was allowed
+        result != AX_ERROR_API_DISABLED
+    }
+}
+
 /// Check microphone permission by verifying a default input device is available
-fn check_microphone() -> bool {
+pub(crate) fn check_microphone() -> bool {
     use cpal::traits::HostTrait;
     let host = cpal::default_host();
     host.default_input_device().is_some()
 }
 
+/// Written once Accessibility has worked, so a later refusal can be told
+/// apart from access that was never given
+fn accessibility_marker_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("com.blahcubed.app")
+        .join("accessibility-verified")
+}
+
+/// Whether Accessibility works right now, or why not
+pub(crate) fn accessibility_state() -> AccessibilityState {
+    let marker = accessibility_marker_path();
+    let state = classify_accessibility(
+        check_accessibility(),
+        probe_accessibility(),
+        marker.exists(),
+    );
+    if state == AccessibilityState::Granted && !marker.exists() {
+        if let Some(parent) = marker.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Err(e) = std::fs::write(&marker, "") {
+            tracing::debug!("Couldn't note that Accessibility works: {}", e);
+        }
+    }
+    state
+}
+
+/// Work out the Accessibility state from whether macOS says the app is
+/// `trusted`, whether an API call `works`, and whether access `worked_before`
+fn classify_accessibility(trusted: bool, works: bool, worked_before: bool) -> AccessibilityState {
+    match (trusted, works) {
+        (true, true) => AccessibilityState::Granted,
+        // Trusted but refused: the grant no longer matches this binary
+        (true, false) => AccessibilityState::GrantedButNotWorking,
+        // Refused after working before: most likely the app was updated
+        (false, _) if worked_before => AccessibilityState::GrantedButNotWorking,
+        (false, _) => AccessibilityState::Denied,
+    }
+}
+
+/// Check Accessibility before a hotkey action that needs it, and emit
+/// `permission-degraded` if the grant has gone stale so the action doesn't
+/// fail silently. The action goes ahead either way.
+pub(crate) fn check_accessibility_for_hotkey(app: &tauri::AppHandle) {
+    if accessibility_state() == AccessibilityState::GrantedButNotWorking {
+        tracing::warn!("Accessibility is allowed but not working; it needs granting again");
+        events::emit(
+            app,
+            events::PermissionDegraded {
+                permission: "accessibility".to_string(),
+                message: "Blah³ can't paste or read selections: its Accessibility access no \
+                          longer applies, usually after an update. Remove Blah³ from the \
+                          Accessibility list and add it again."
+                    .to_string(),
+            },
+        );
+    }
+}
+
+/// How to get a stale Accessibility grant working again
+#[derive(Debug, Clone, Serialize)]
+pub struct AccessibilityHint {
+    pub steps: Vec<String>,
+    /// Opens the Accessibility pane of System Settings
+    pub settings_url: String,
+    /// Clears the old grant from Terminal; the app can't do it itself
+    pub reset_command: String,
+}
+
+/// Step-by-step instructions for re-granting Accessibility after an update
+/// or move left the old grant behind
+#[tauri::command]
+pub fn reset_accessibility_hint(app: tauri::AppHandle) -> AccessibilityHint {
+    use tauri::Manager;
+    accessibility_hint(&app.config().identifier)
+}
+
+fn accessibility_hint(bundle_id: &str) -> AccessibilityHint {
+    AccessibilityHint {
+        steps: vec![
+            "Open System Settings > Privacy & Security > Accessibility.".to_string(),
+            "Select Blah³ in the list and remove it with the − button.".to_string(),
+            "Click +, choose Blah³ from Applications and make sure it is switched on.".to_string(),
+            "Quit and reopen Blah³.".to_string(),
+        ],
+        settings_url: ACCESSIBILITY_SETTINGS_URL.to_string(),
+        reset_command: format!("tccutil reset Accessibility {}", bundle_id),
+    }
+}
+
 #[tauri::command]
 pub fn check_permissions() -> PermissionStatus {
+    let accessibility_state = accessibility_state();
     PermissionStatus {
         microphone: check_microphone(),
-        accessibility: check_accessibility(),
+        accessibility: accessibility_state == AccessibilityState::Granted,
+        accessibility_state,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_accessibility() {
+        use AccessibilityState::*;
+        assert_eq!(classify_accessibility(true, true, false), Granted);
+        assert_eq!(
+            classify_accessibility(true, false, false),
+            GrantedButNotWorking
+        );
+        assert_eq!(
+            classify_accessibility(false, false, true),
+            GrantedButNotWorking
+        );
+        assert_eq!(classify_accessibility(false, false, false), Denied);
+        assert_eq!(
+            serde_json::to_value(GrantedButNotWorking).unwrap(),
+            serde_json::json!("granted_but_not_working")
+        );
+    }
+
+    #[test]
+    fn test_accessibility_hint() {
+        let hint = accessibility_hint("com.blahcubed.app");
+        assert_eq!(
+            hint.reset_command,
+            "tccutil reset Accessibility com.blahcubed.app"
+        );
+        assert!(hint.settings_url.ends_with("Privacy_Accessibility"));
+        assert_eq!(hint.steps.len(), 4);
     }
 }
@@ -1,43 +1,580 @@
 use serde::{Deserialize, Serialize};
-use std::sync::{atomic::{AtomicBool, Ordering}, Mutex, OnceLock};
+use std::collections::VecDeque;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex as TokioMutex;
 
-use crate::audio::capture::{AudioCapture, SilenceConfig};
-use crate::commands::settings::get_settings;
-use crate::engines::whisper::WhisperEngine;
+use crate::audio::capture::{
+    measure_input_level, record_for, AudioCapture, AudioDeviceInfo, InputLevel, RecordingLevels,
+    SilenceConfig, CAPTURE_SAMPLE_RATE,
+};
+use crate::audio::processing::{audio_duration_ms, audio_duration_secs};
+use crate::audio::silence::{self, NoiseProfile, SilenceDetector};
+use crate::commands::models::{resolve_models_dir, ModelType};
+use crate::commands::settings::{get_settings, modify_settings, AppSettings};
+use crate::dictation_timing::{self, DictationTimings};
+use crate::engine_status;
+use crate::engines::whisper::{
+    model_type_from_header, LanguageInfo, WhisperAdvancedConfig, WhisperEngine,
+};
+use crate::engines::{
+    DecodeOptions, DiarizedSegment, EngineRegistry, SpeechToText, Transcript, TranscriptSegment,
+};
+use crate::error::BlahError;
+use crate::events;
+use crate::hotkeys::HotkeyState;
+use crate::live_transcript::CurrentTranscript;
+use crate::models::hardware::HardwareDetector;
+use crate::text::locale::Locale;
+use crate::text::paragraphs::insert_paragraph_breaks;
 
 // Global state for recording
 static RECORDING_STATE: OnceLock<RecordingState> = OnceLock::new();
 
 struct RecordingState {
-    is_recording: AtomicBool,
     capture: Mutex<Option<AudioCapture>>,
+    // Recording stopped by the system (e.g. sleep), handed to the next stop_recording
+    interrupted: Mutex<Option<StopRecordingResult>>,
+    // Number and start time of the latest recording
+    started: Mutex<Option<(u32, Instant)>>,
+    recordings_started: AtomicU32,
+}
+
+impl RecordingState {
+    /// Whether a recording is waiting for `stop_recording`, including one
+    /// whose stream a silence auto-stop already closed
+    fn has_capture(&self) -> bool {
+        self.capture
+            .lock()
+            .map(|capture| capture.is_some())
+            .unwrap_or(false)
+    }
 }
 
 fn get_recording_state() -> &'static RecordingState {
     RECORDING_STATE.get_or_init(|| RecordingState {
-        is_recording: AtomicBool::new(false),
         capture: Mutex::new(None),
+        interrupted: Mutex::new(None),
+        started: Mutex::new(None),
+        recordings_started: AtomicU32::new(0),
     })
 }
 
+/// Session id and elapsed time of the recording started with
+/// `start_recording`, while it waits for `stop_recording`
+pub(crate) fn app_recording() -> Option<(u32, Duration)> {
+    let state = get_recording_state();
+    if !state.has_capture() {
+        return None;
+    }
+    let (session_id, started_at) = (*state.started.lock().ok()?)?;
+    Some((session_id, started_at.elapsed()))
+}
+
+// Transcriptions running right now
+static TRANSCRIPTIONS_RUNNING: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts a transcription as running until dropped
+pub(crate) struct TranscriptionRunning;
+
+impl TranscriptionRunning {
+    pub(crate) fn start() -> Self {
+        TRANSCRIPTIONS_RUNNING.fetch_add(1, Ordering::SeqCst);
+        Self
+    }
+}
+
+impl Drop for TranscriptionRunning {
+    fn drop(&mut self) {
+        TRANSCRIPTIONS_RUNNING.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Whether any recording is being transcribed
+pub(crate) fn is_transcribing() -> bool {
+    TRANSCRIPTIONS_RUNNING.load(Ordering::SeqCst) > 0
+}
+
+/// Builds an engine for a model file
+type SttLoader = Arc<dyn Fn(&str) -> Result<Box<dyn SpeechToText>, BlahError> + Send + Sync>;
+
+/// A loaded engine together with the model file it was built from.
+struct CachedSttEngine {
+    model_path: String,
+    engine: Arc<dyn SpeechToText>,
+}
+
+/// Loads STT engines and caches the most recently used ones, one unless
+/// `set_max_loaded` allows more. The loader is injectable so the command
+/// logic can be tested against a mock engine.
+pub(crate) struct SttProvider {
+    load: SttLoader,
+    /// Least recently used first
+    cached: TokioMutex<Vec<CachedSttEngine>>,
+    max_loaded: AtomicUsize,
+    /// Held for the length of a transcription, so only one at a time
+    /// competes for the CPU
+    transcribing: TokioMutex<()>,
+}
+
+impl SttProvider {
+    pub(crate) fn new(
+        load: impl Fn(&str) -> Result<Box<dyn SpeechToText>, BlahError> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            load: Arc::new(load),
+            cached: TokioMutex::new(Vec::new()),
+            max_loaded: AtomicUsize::new(1),
+            transcribing: TokioMutex::new(()),
+        }
+    }
+
+    /// Keep up to `max_loaded` models loaded from the next load on, so
+    /// dictations can switch between them without reloading
+    pub(crate) fn set_max_loaded(&self, max_loaded: usize) {
+        self.max_loaded.store(max_loaded.max(1), Ordering::SeqCst);
+    }
+
+    /// Engine for `model_path`, reloading it if a different model is
+    /// requested. Callers keep the returned engine for as long as they need
+    /// it, so a model switch never pulls it out from under a transcription.
+    pub(crate) async fn engine(
+        &self,
+        model_path: &str,
+    ) -> Result<Arc<dyn SpeechToText>, BlahError> {
+        Ok(self.engine_with_load_time(model_path).await?.0)
+    }
+
+    /// Like `engine`, also returning how long loading the model took:
+    /// zero when it was already loaded
+    pub(crate) async fn engine_with_load_time(
+        &self,
+        model_path: &str,
+    ) -> Result<(Arc<dyn SpeechToText>, Duration), BlahError> {
+        let mut cached = self.cached.lock().await;
+
+        if let Some(index) = cached.iter().position(|c| c.model_path == model_path) {
+            let hit = cached.remove(index);
+            let engine = Arc::clone(&hit.engine);
+            cached.push(hit);
+            engine_status::used(&model_id(model_path));
+            return Ok((engine, Duration::ZERO));
+        }
+
+        // Make room first, so the old model is freed before the new one loads
+        let max_loaded = self.max_loaded.load(Ordering::SeqCst);
+        let evict = (cached.len() + 1).saturating_sub(max_loaded);
+        for unloaded in cached.drain(..evict) {
+            tracing::info!("Unloading STT model {}", unloaded.model_path);
+            engine_status::unloaded(&model_id(&unloaded.model_path));
+        }
+
+        // Loading reads hundreds of megabytes, so it runs off the async
+        // runtime. The cache stays locked meanwhile, so a model is never
+        // loaded twice at once.
+        let start = Instant::now();
+        let load = Arc::clone(&self.load);
+        let path = model_path.to_string();
+        let engine: Arc<dyn SpeechToText> =
+            Arc::from(run_blocking("Model load", move || load(&path)).await??);
+        let load_time = start.elapsed();
+        let load_ms = load_time.as_millis();
+        tracing::info!("Loaded STT model {} in {}ms", model_path, load_ms);
+        let model_bytes = std::fs::metadata(model_path).map_or(0, |m| m.len());
+        engine_status::loaded(&model_id(model_path), ModelType::Stt, model_bytes);
+        cached.push(CachedSttEngine {
+            model_path: model_path.to_string(),
+            engine: Arc::clone(&engine),
+        });
+        Ok((engine, load_time))
+    }
+
+    /// Drop every loaded model, e.g. after their files were deleted.
+    /// Transcriptions already holding an engine finish with it.
+    pub(crate) async fn unload_all(&self) {
+        let mut cached = self.cached.lock().await;
+        for unloaded in cached.drain(..) {
+            tracing::info!("Unloading STT model {}", unloaded.model_path);
+            engine_status::unloaded(&model_id(&unloaded.model_path));
+        }
+    }
+
+    /// Run `work` from a blocking thread once no other transcription is
+    /// running, keeping others waiting until it returns
+    pub(crate) fn blocking_turn<T>(&self, work: impl FnOnce() -> T) -> T {
+        let _turn = self.transcribing.blocking_lock();
+        work()
+    }
+
+    /// Whether a model is loaded, without loading one
+    pub(crate) async fn is_loaded(&self) -> bool {
+        !self.cached.lock().await.is_empty()
+    }
+}
+
+/// How many hotkey dictations `get_recent_transcriptions` remembers
+const RECENT_TRANSCRIPTIONS_CAPACITY: usize = 20;
+
+// Dictations from this session, for the tray and re-pasting. Not persisted.
+static RECENT_TRANSCRIPTIONS: Mutex<RecentTranscriptions> =
+    Mutex::new(RecentTranscriptions::new(RECENT_TRANSCRIPTIONS_CAPACITY));
+
+/// Ring buffer of transcribed text, oldest first
+struct RecentTranscriptions {
+    capacity: usize,
+    texts: VecDeque<String>,
+}
+
+impl RecentTranscriptions {
+    const fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            texts: VecDeque::new(),
+        }
+    }
+
+    /// Remember `text`, dropping the oldest once full. Empty text is skipped.
+    fn push(&mut self, text: &str) {
+        if text.trim().is_empty() {
+            return;
+        }
+        if self.texts.len() == self.capacity {
+            self.texts.pop_front();
+        }
+        self.texts.push_back(text.to_string());
+    }
+
+    /// Up to `limit` texts, most recent first
+    fn recent(&self, limit: usize) -> Vec<String> {
+        self.texts.iter().rev().take(limit).cloned().collect()
+    }
+}
+
+/// Remember a finished hotkey dictation for `get_recent_transcriptions`
+pub(crate) fn remember_transcription(text: &str) {
+    match RECENT_TRANSCRIPTIONS.lock() {
+        Ok(mut recent) => recent.push(text),
+        Err(e) => tracing::warn!("Recent transcriptions unavailable: {}", e),
+    }
+}
+
+fn recent_transcriptions(limit: usize) -> Vec<String> {
+    RECENT_TRANSCRIPTIONS
+        .lock()
+        .map(|recent| recent.recent(limit))
+        .unwrap_or_default()
+}
+
+/// Load the Whisper model at `model_path` with the advanced settings
+pub(crate) fn load_whisper_engine(model_path: &str) -> Result<Box<dyn SpeechToText>, BlahError> {
+    if !std::path::Path::new(model_path).exists() {
+        return Err(BlahError::ModelNotDownloaded(format!(
+            "Model not found: {}. Please download it from the Models tab.",
+            model_path
+        )));
+    }
+
+    let config = get_settings()
+        .map(|settings| settings.whisper_advanced)
+        .unwrap_or_default();
+    let engine = WhisperEngine::with_config(model_path, &config).map_err(|e| {
+        BlahError::TranscriptionFailed(format!(
+            "Failed to load Whisper model '{}': {}",
+            model_path, e
+        ))
+    })?;
+    Ok(Box::new(engine))
+}
+
+/// Path of the STT model selected in settings
+pub(crate) fn selected_model_path(settings: &AppSettings) -> PathBuf {
+    stt_model_path(settings, &settings.stt_model)
+}
+
+fn stt_model_path(settings: &AppSettings, model: &str) -> PathBuf {
+    resolve_models_dir(settings.custom_models_dir.as_deref())
+        .join("stt")
+        .join(model)
+}
+
+fn model_file_name(model_path: &str) -> Option<String> {
+    Path::new(model_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+}
+
+/// Id of the model at `model_path`, as listed by `list_models`
+fn model_id(model_path: &str) -> String {
+    model_file_name(model_path).unwrap_or_else(|| model_path.to_string())
+}
+
+/// STT model for a dictation of `audio_secs`. With `adaptive_model` set,
+/// recordings shorter than its fast threshold use the fast model and those
+/// longer than its accurate threshold the accurate one; everything else
+/// uses `stt_model`.
+pub(crate) fn dictation_model(settings: &AppSettings, audio_secs: f32) -> &str {
+    match &settings.adaptive_model {
+        Some(adaptive) if audio_secs < adaptive.fast_below_secs => &adaptive.fast_model,
+        Some(adaptive) if audio_secs > adaptive.accurate_above_secs => &adaptive.accurate_model,
+        _ => &settings.stt_model,
+    }
+}
+
+/// The model a dictation of `audio_secs` is transcribed with and its path.
+/// An adaptive model that isn't downloaded falls back to `stt_model`.
+pub(crate) fn dictation_model_path(settings: &AppSettings, audio_secs: f32) -> (String, PathBuf) {
+    let model = dictation_model(settings, audio_secs);
+    let path = stt_model_path(settings, model);
+    if model != settings.stt_model && !path.exists() {
+        tracing::warn!(
+            "Adaptive model {} isn't downloaded, using {}",
+            model,
+            settings.stt_model
+        );
+        return (settings.stt_model.clone(), selected_model_path(settings));
+    }
+    (model.to_string(), path)
+}
+
+/// Share of RAM the loaded Whisper models may take together
+const LOADED_MODELS_RAM_SHARE: u64 = 4;
+
+/// How many of the models in `model_bytes` can stay loaded at once: two
+/// when the largest two fit in a quarter of `ram_gb`, else one
+fn models_to_keep_loaded(model_bytes: &[u64], ram_gb: u64) -> usize {
+    let mut sizes = model_bytes.to_vec();
+    sizes.sort_unstable_by(|a, b| b.cmp(a));
+    let largest_two: u64 = sizes.iter().take(2).sum();
+    let budget = ram_gb.saturating_mul(1024 * 1024 * 1024) / LOADED_MODELS_RAM_SHARE;
+    if sizes.len() > 1 && largest_two <= budget {
+        2
+    } else {
+        1
+    }
+}
+
+/// Let the provider keep the adaptive models loaded alongside `stt_model`
+/// when there is enough RAM for two, so switching by length doesn't
+/// reload a model every dictation
+pub(crate) async fn keep_adaptive_models_loaded(engines: &EngineRegistry, settings: &AppSettings) {
+    static RAM_GB: OnceLock<u64> = OnceLock::new();
+
+    let Some(adaptive) = &settings.adaptive_model else {
+        engines.whisper().set_max_loaded(1);
+        return;
+    };
+    let ram_gb = match RAM_GB.get() {
+        Some(ram_gb) => *ram_gb,
+        None => {
+            let detect = || HardwareDetector::detect().ram_gb;
+            let detected = tauri::async_runtime::spawn_blocking(detect)
+                .await
+                .unwrap_or(0);
+            *RAM_GB.get_or_init(|| detected)
+        }
+    };
+    let models = [
+        &settings.stt_model,
+        &adaptive.fast_model,
+        &adaptive.accurate_model,
+    ];
+    let model_bytes: Vec<u64> = models
+        .into_iter()
+        .filter_map(|model| std::fs::metadata(stt_model_path(settings, model)).ok())
+        .map(|metadata| metadata.len())
+        .collect();
+    engines
+        .whisper()
+        .set_max_loaded(models_to_keep_loaded(&model_bytes, ram_gb));
+}
+
+/// Whisper decoding options from the user's STT settings
+pub(crate) fn decode_options(settings: &AppSettings) -> DecodeOptions {
+    DecodeOptions {
+        short_utterance_secs: settings.stt_short_utterance_secs,
+        suppress_non_speech_tokens: settings.stt_suppress_non_speech_tokens,
+        max_chunk_secs: settings.stt_max_chunk_secs,
+        initial_prompt: settings.whisper_initial_prompt.clone(),
+        n_threads: settings.whisper_advanced.n_threads,
+    }
+}
+
+fn transcription_failed(e: anyhow::Error) -> BlahError {
+    BlahError::TranscriptionFailed(format!("Transcription failed: {}", e))
+}
+
+/// Run CPU-bound engine work on the blocking thread pool, so events and
+/// other commands keep flowing on the async runtime meanwhile
+async fn run_blocking<T: Send + 'static>(
+    what: &str,
+    work: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, BlahError> {
+    tauri::async_runtime::spawn_blocking(work)
+        .await
+        .map_err(|e| BlahError::Internal(format!("{} task failed: {}", what, e)))
+}
+
+/// Transcribe a clip with the engine for `model_path`, calling
+/// `on_progress` with the seconds decoded so far and the total. Fails with
+/// `Busy` while another transcription is running.
+pub(crate) async fn transcribe_with(
+    provider: &SttProvider,
+    audio: &[f32],
+    model_path: &str,
+    options: &DecodeOptions,
+    on_progress: impl FnMut(f32, f32) + Send + 'static,
+) -> Result<TranscriptionResult, BlahError> {
+    let Ok(_turn) = provider.transcribing.try_lock() else {
+        return Err(BlahError::Busy(
+            "Another transcription is running".to_string(),
+        ));
+    };
+    let _running = TranscriptionRunning::start();
+    let start = std::time::Instant::now();
+
+    let engine = provider.engine(model_path).await?;
+    let (audio, options) = (audio.to_vec(), options.clone());
+    let transcript = run_blocking("Transcription", move || {
+        engine.transcribe_with_progress(&audio, &options, Box::new(|_| {}), Box::new(on_progress))
+    })
+    .await?
+    .map_err(transcription_failed)?;
+
+    let duration_ms = start.elapsed().as_millis() as u64;
+    tracing::info!(
+        "Transcription completed in {}ms: {}",
+        duration_ms,
+        transcript.text
+    );
+
+    Ok(TranscriptionResult {
+        confidence: transcript.confidence(),
+        text: transcript.text,
+        duration_ms,
+        segments: Some(transcript.segments),
+        model: model_file_name(model_path),
+    })
+}
+
+/// Final text of a dictation: the transcript, with paragraph breaks at long
+/// pauses when enabled and the typography of the `locale` setting.
+/// Partial results are never reformatted, since later segments decide where
+/// the breaks go and whether a quote opens or closes.
+pub(crate) fn dictation_text(
+    text: &str,
+    segments: &[TranscriptSegment],
+    settings: &AppSettings,
+) -> String {
+    let text = if settings.paragraph_breaks_enabled && !segments.is_empty() {
+        insert_paragraph_breaks(segments, settings.paragraph_pause_secs)
+    } else {
+        text.to_string()
+    };
+    match Locale::from_setting(&settings.locale) {
+        Some(locale) => locale.apply(&text),
+        None => text,
+    }
+}
+
+/// Whether a dictation should be reviewed instead of auto-pasted: its
+/// confidence is below `threshold`. A threshold of 0 or a transcript
+/// without scores never needs review.
+pub(crate) fn needs_review(confidence: Option<f32>, threshold: f32) -> bool {
+    threshold > 0.0 && confidence.is_some_and(|confidence| confidence < threshold)
+}
+
+/// Transcribe a hotkey dictation, calling `on_partial` with the text so far
+/// each time a segment is decoded and `on_progress` as long recordings are
+/// worked through. Returns the final transcript, recording model load and
+/// inference time in `timings`. A dictation made while another
+/// transcription is running waits for it to finish.
+pub(crate) async fn transcribe_dictation(
+    provider: &SttProvider,
+    audio: &[f32],
+    model_path: &str,
+    options: &DecodeOptions,
+    mut on_partial: impl FnMut(&str) + Send + 'static,
+    on_progress: impl FnMut(f32, f32) + Send + 'static,
+    timings: &mut DictationTimings,
+) -> Result<Transcript, BlahError> {
+    let _turn = provider.transcribing.lock().await;
+    let _running = TranscriptionRunning::start();
+    let (engine, load_time) = provider.engine_with_load_time(model_path).await?;
+    timings.model_load_ms = dictation_timing::millis(load_time);
+
+    let mut accumulated_text = String::new();
+    let on_segment = Box::new(move |segment: &TranscriptSegment| {
+        accumulated_text.push_str(&segment.text);
+        on_partial(accumulated_text.trim());
+    });
+
+    let start = Instant::now();
+    let (audio, options) = (audio.to_vec(), options.clone());
+    let transcript = run_blocking("Transcription", move || {
+        engine.transcribe_with_progress(&audio, &options, on_segment, Box::new(on_progress))
+    })
+    .await?
+    .map_err(transcription_failed)?;
+    timings.set_inference(start.elapsed());
+    Ok(transcript)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranscriptionResult {
     pub text: String,
     pub duration_ms: u64,
+    /// Mean word confidence from 0 to 1, when the engine reports one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    /// Timed segments with per-word confidence
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segments: Option<Vec<TranscriptSegment>>,
+    /// File name of the model that transcribed it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StopRecordingResult {
     pub audio_data: Vec<f32>,
     pub silence_triggered: bool,
+    /// Peak and share of clipped samples
+    #[serde(flatten)]
+    pub levels: RecordingLevels,
+}
+
+/// Warn the user when a recording clipped enough to garble transcription
+pub(crate) fn warn_if_clipping(app: &AppHandle, levels: RecordingLevels) {
+    if !levels.is_clipping() {
+        return;
+    }
+
+    tracing::warn!(
+        "Recording clipped: {:.2}% of samples at full scale",
+        levels.clipped_ratio * 100.0
+    );
+    events::emit(
+        app,
+        events::AudioQualityWarning {
+            peak: levels.peak,
+            clipped_ratio: levels.clipped_ratio,
+            message: "Your microphone is too loud and the recording is distorted. Try lowering \
+                      the input volume in System Settings > Sound."
+                .to_string(),
+        },
+    );
 }
 
 #[tauri::command]
-pub async fn start_recording() -> Result<(), String> {
+pub async fn start_recording(app: AppHandle) -> Result<(), BlahError> {
     let state = get_recording_state();
 
-    if state.is_recording.load(Ordering::SeqCst) {
-        return Err("Already recording".to_string());
+    if state.has_capture() {
+        return Err(BlahError::Busy("Already recording".to_string()));
     }
 
     tracing::info!("Starting audio recording...");
@@ -54,58 +591,117 @@ pub async fn start_recording() -> Result<(), String> {
         enabled: settings.silence_detection_enabled,
         threshold: settings.silence_threshold,
         duration_secs: settings.silence_duration,
+        adaptive: settings.silence_adaptive,
     };
 
+    let capture_error = |message: &str, e: anyhow::Error| {
+        if !crate::commands::permissions::check_microphone() {
+            BlahError::PermissionDenied(format!("Microphone access unavailable: {}", e))
+        } else {
+            BlahError::AudioCaptureFailed(format!("{}: {}", message, e))
+        }
+    };
     let capture = AudioCapture::with_silence_config(silence_config)
-        .map_err(|e| format!("Failed to initialize audio capture: {}", e))?;
-    capture.start()
-        .map_err(|e| format!("Failed to start microphone recording: {}", e))?;
+        .map_err(|e| capture_error("Failed to initialize audio capture", e))?
+        .with_observer(crate::mic_indicator::observer(&app));
+    // Fails when the stream can't open, e.g. microphone access was denied
+    capture
+        .start()
+        .map_err(|e| capture_error("Failed to start microphone recording", e))?;
 
     {
         let mut capture_guard = state.capture.lock()
-            .map_err(|e| format!("Internal error: audio state lock poisoned: {}", e))?;
+            .map_err(|e| BlahError::Internal(format!("Internal error: audio state lock poisoned: {}", e)))?;
         *capture_guard = Some(capture);
     }
+    let session_id = state.recordings_started.fetch_add(1, Ordering::Relaxed) + 1;
+    if let Ok(mut started) = state.started.lock() {
+        *started = Some((session_id, Instant::now()));
+    }
 
-    state.is_recording.store(true, Ordering::SeqCst);
+    if let Ok(mut interrupted) = state.interrupted.lock() {
+        *interrupted = None;
+    }
     tracing::info!("Recording started");
 
     Ok(())
 }
 
+/// Stop the recording, returning its audio. A recording the system already
+/// stopped (see `interrupt_recording`) is returned as it was captured.
 #[tauri::command]
-pub async fn stop_recording() -> Result<StopRecordingResult, String> {
+pub async fn stop_recording(app: AppHandle) -> Result<StopRecordingResult, BlahError> {
     let state = get_recording_state();
 
-    if !state.is_recording.load(Ordering::SeqCst) {
-        return Err("Not recording".to_string());
+    let interrupted = state
+        .interrupted
+        .lock()
+        .ok()
+        .and_then(|mut guard| guard.take());
+    if let Some(result) = interrupted {
+        tracing::info!("Returning recording interrupted by the system");
+        return Ok(result);
+    }
+
+    if !state.has_capture() {
+        return Err(BlahError::InvalidInput("Not recording".to_string()));
+    }
+
+    let result = stop_capture(state)?;
+    warn_if_clipping(&app, result.levels);
+    Ok(result)
+}
+
+/// Stop a recording started with `start_recording` without waiting for the
+/// frontend, keeping its audio for the next `stop_recording` call. Returns
+/// whether a recording was stopped.
+pub(crate) fn interrupt_recording() -> bool {
+    let state = get_recording_state();
+    if !state.has_capture() {
+        return false;
+    }
+
+    match stop_capture(state) {
+        Ok(result) => {
+            if let Ok(mut interrupted) = state.interrupted.lock() {
+                *interrupted = Some(result);
+            }
+            true
+        }
+        Err(e) => {
+            tracing::error!("Failed to stop interrupted recording: {}", e);
+            false
+        }
     }
+}
 
+fn stop_capture(state: &RecordingState) -> Result<StopRecordingResult, BlahError> {
     tracing::info!("Stopping audio recording...");
 
-    let (audio_data, silence_triggered) = {
+    let (audio_data, silence_triggered, levels) = {
         let mut capture_guard = state.capture.lock()
-            .map_err(|e| format!("Internal error: audio state lock poisoned: {}", e))?;
+            .map_err(|e| BlahError::Internal(format!("Internal error: audio state lock poisoned: {}", e)))?;
         if let Some(capture) = capture_guard.take() {
             let triggered = capture.is_silence_triggered();
-            let data = capture.stop()
-                .map_err(|e| format!("Failed to stop audio capture: {}", e))?;
-            (data, triggered)
+            let (data, levels) = capture.stop_with_levels()
+                .map_err(|e| BlahError::AudioCaptureFailed(format!("Failed to stop audio capture: {}", e)))?;
+            (data, triggered, levels)
         } else {
-            (Vec::new(), false)
+            (Vec::new(), false, RecordingLevels::default())
         }
     };
 
-    state.is_recording.store(false, Ordering::SeqCst);
     tracing::info!(
-        "Recording stopped (silence_triggered: {}), captured {} samples",
+        "Recording stopped (silence_triggered: {}), captured {} samples ({:.2}s)",
         silence_triggered,
-        audio_data.len()
+        audio_data.len(),
+        audio_duration_secs(audio_data.len(), CAPTURE_SAMPLE_RATE)
     );
 
     Ok(StopRecordingResult {
         audio_data,
         silence_triggered,
+        levels,
     })
 }
 
@@ -129,33 +725,1136 @@ pub fn is_silence_triggered() -> bool {
     }
 }
 
-/// Check if currently recording.
+/// Seconds of ambient noise recorded by `calibrate_silence_threshold` by default
+const DEFAULT_CALIBRATION_SECS: f32 = 2.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SilenceCalibration {
+    pub threshold: f32,
+    pub threshold_db: f32,
+}
+
+/// Measure ambient noise and save a silence threshold just above it.
+/// The user should stay quiet while this runs.
+#[tauri::command]
+pub async fn calibrate_silence_threshold(
+    duration_secs: Option<f32>,
+) -> Result<SilenceCalibration, BlahError> {
+    if get_recording_state().has_capture() {
+        return Err(BlahError::Busy("Can't calibrate while recording".to_string()));
+    }
+
+    let settings = get_settings()?;
+    let duration = duration_secs.unwrap_or(DEFAULT_CALIBRATION_SECS).clamp(0.5, 10.0);
+    let mut detector = SilenceDetector::new(
+        settings.silence_threshold,
+        settings.silence_duration,
+        CAPTURE_SAMPLE_RATE,
+    );
+
+    let (detector, threshold_db) = tokio::task::spawn_blocking(move || {
+        let db = detector.calibrate_from_microphone(duration)?;
+        Ok::<_, anyhow::Error>((detector, db))
+    })
+    .await
+    .map_err(|e| BlahError::Internal(format!("Calibration task failed: {}", e)))?
+    .map_err(|e| BlahError::AudioCaptureFailed(format!("Failed to record ambient noise: {}", e)))?;
+
+    modify_settings(|settings| {
+        settings.silence_threshold = detector.threshold();
+        Ok(())
+    })?;
+
+    Ok(SilenceCalibration {
+        threshold: detector.threshold(),
+        threshold_db,
+    })
+}
+
+/// Guided one-shot calibration: record `duration_secs` of room noise while
+/// the user stays quiet, save the recommended silence threshold and return
+/// the measurements. Emits `noise-profile-recording` while listening and
+/// `noise-profile-complete` with the result.
+#[tauri::command]
+pub async fn record_ambient_noise_profile(
+    app: AppHandle,
+    duration_secs: f32,
+) -> Result<NoiseProfile, BlahError> {
+    if !duration_secs.is_finite() || duration_secs <= 0.0 {
+        return Err(BlahError::InvalidInput(
+            "Noise profile duration must be a positive number of seconds".to_string(),
+        ));
+    }
+    if get_recording_state().has_capture() {
+        let message = "Can't profile noise while recording".to_string();
+        return Err(BlahError::Busy(message));
+    }
+
+    let duration_secs = duration_secs.clamp(0.5, 10.0);
+    events::emit(&app, events::NoiseProfileRecording { duration_secs });
+
+    let duration = std::time::Duration::from_secs_f32(duration_secs);
+    let ambient = tokio::task::spawn_blocking(move || record_for(duration))
+        .await
+        .map_err(|e| BlahError::Internal(format!("Noise profile task failed: {}", e)))?
+        .map_err(|e| {
+            BlahError::AudioCaptureFailed(format!("Failed to record ambient noise: {}", e))
+        })?;
+
+    let profile = NoiseProfile::measure(&ambient);
+    tracing::info!(
+        "Noise profile: RMS {:.4} ({:.1} dB), peak {:.4}, threshold {:.4}",
+        profile.rms,
+        profile.db,
+        profile.peak,
+        profile.recommended_threshold
+    );
+
+    modify_settings(|settings| {
+        settings.silence_threshold = profile.recommended_threshold;
+        Ok(())
+    })?;
+
+    events::emit(&app, events::NoiseProfileComplete(profile));
+
+    Ok(profile)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SilenceSuggestion {
+    pub ambient_rms: f32,
+    pub threshold: f32,
+    pub threshold_db: f32,
+}
+
+/// Update silence detection settings and apply them to any recording in
+/// progress, so auto-stop can be tuned without starting over.
+#[tauri::command]
+pub async fn set_silence_config(
+    app: AppHandle,
+    threshold: f32,
+    duration: f32,
+    enabled: bool,
+) -> Result<(), BlahError> {
+    if !threshold.is_finite() || !duration.is_finite() {
+        return Err(BlahError::InvalidInput(
+            "Silence threshold and duration must be numbers".to_string(),
+        ));
+    }
+
+    let silence_config = modify_settings(|settings| {
+        let silence_config = SilenceConfig {
+            enabled,
+            threshold: threshold.clamp(
+                silence::MIN_SILENCE_THRESHOLD,
+                silence::MAX_SILENCE_THRESHOLD,
+            ),
+            duration_secs: duration
+                .clamp(silence::MIN_SILENCE_DURATION, silence::MAX_SILENCE_DURATION),
+            adaptive: settings.silence_adaptive,
+        };
+        settings.silence_detection_enabled = silence_config.enabled;
+        settings.silence_threshold = silence_config.threshold;
+        settings.silence_duration = silence_config.duration_secs;
+        Ok(silence_config)
+    })?;
+
+    let apply = |capture: &AudioCapture| {
+        capture.set_silence_config(silence_config.clone()).map_err(|e| {
+            BlahError::AudioCaptureFailed(format!("Failed to update silence detection: {}", e))
+        })
+    };
+
+    // Recording started from the main window
+    {
+        let capture_guard = get_recording_state().capture.lock()
+            .map_err(|e| BlahError::Internal(format!("Failed to acquire capture lock: {}", e)))?;
+        if let Some(ref capture) = *capture_guard {
+            apply(capture)?;
+        }
+    }
+
+    // Recording started from the dictation hotkey
+    let hotkey_state = app.state::<Arc<HotkeyState>>();
+    let capture_guard = hotkey_state.audio_capture.lock().await;
+    if let Some(ref capture) = *capture_guard {
+        apply(capture)?;
+    }
+
+    Ok(())
+}
+
+/// How long `test_input_level` listens
+const INPUT_LEVEL_TEST_SECS: f32 = 3.0;
+
+/// Record three seconds from the microphone and report its quietest,
+/// average and loudest level, for the onboarding microphone check.
+#[tauri::command]
+pub async fn test_input_level() -> Result<InputLevel, BlahError> {
+    if get_recording_state().has_capture() {
+        return Err(BlahError::Busy(
+            "Can't test the microphone while recording".to_string(),
+        ));
+    }
+
+    let duration = std::time::Duration::from_secs_f32(INPUT_LEVEL_TEST_SECS);
+    let samples = tokio::task::spawn_blocking(move || record_for(duration))
+        .await
+        .map_err(|e| BlahError::Internal(format!("Input level task failed: {}", e)))?
+        .map_err(|e| {
+            BlahError::AudioCaptureFailed(format!("Failed to record from the microphone: {}", e))
+        })?;
+
+    let level = measure_input_level(&samples, CAPTURE_SAMPLE_RATE);
+    tracing::info!(
+        "Input level: min {:.1} dB, avg {:.1} dB, peak {:.1} dB",
+        level.min_db,
+        level.avg_db,
+        level.peak_db
+    );
+
+    Ok(level)
+}
+
+/// Record a couple of seconds of ambient noise and suggest a silence
+/// threshold a margin above it. Nothing is saved; the suggestion is returned
+/// and emitted so the caller can offer to apply it via `set_silence_config`.
+/// Works mid-recording, as long as the user pauses while it listens.
+#[tauri::command]
+pub async fn calibrate_silence(app: AppHandle) -> Result<SilenceSuggestion, BlahError> {
+    let duration = std::time::Duration::from_secs_f32(DEFAULT_CALIBRATION_SECS);
+    let ambient = tokio::task::spawn_blocking(move || record_for(duration))
+        .await
+        .map_err(|e| BlahError::Internal(format!("Calibration task failed: {}", e)))?
+        .map_err(|e| {
+            BlahError::AudioCaptureFailed(format!("Failed to record ambient noise: {}", e))
+        })?;
+
+    let ambient_rms = silence::calculate_rms(&ambient);
+    let threshold = silence::suggest_threshold(ambient_rms);
+    let suggestion = SilenceSuggestion {
+        ambient_rms,
+        threshold,
+        threshold_db: silence::rms_to_db(threshold),
+    };
+    tracing::info!(
+        "Suggested silence threshold {:.4} for ambient RMS {:.4}",
+        suggestion.threshold,
+        suggestion.ambient_rms
+    );
+
+    events::emit(&app, events::SilenceThresholdSuggested(suggestion.clone()));
+
+    Ok(suggestion)
+}
+
+/// Background noise floor (dB) measured by adaptive silence detection during
+/// the current recording. `None` when not recording, not adaptive, or no
+/// audio has arrived yet.
+#[tauri::command]
+pub async fn get_noise_floor_db(app: AppHandle) -> Result<Option<f32>, BlahError> {
+    {
+        let capture_guard = get_recording_state().capture.lock()
+            .map_err(|e| BlahError::Internal(format!("Failed to acquire capture lock: {}", e)))?;
+        if let Some(ref capture) = *capture_guard {
+            return Ok(capture.noise_floor_db());
+        }
+    }
+
+    let hotkey_state = app.state::<Arc<HotkeyState>>();
+    let capture_guard = hotkey_state.audio_capture.lock().await;
+    Ok(capture_guard.as_ref().and_then(AudioCapture::noise_floor_db))
+}
+
+/// Audio input devices, with the one recordings use marked as default
+#[tauri::command]
+pub fn list_input_devices() -> Result<Vec<AudioDeviceInfo>, BlahError> {
+    crate::audio::capture::list_input_devices()
+        .map_err(|e| BlahError::AudioCaptureFailed(format!("Failed to list input devices: {}", e)))
+}
+
+/// Languages the selected STT model can transcribe, for the language picker
+#[tauri::command]
+pub fn get_supported_languages() -> Vec<LanguageInfo> {
+    let settings = get_settings().unwrap_or_default();
+    WhisperEngine::languages_for_model(&settings.stt_model)
+}
+
+/// Check if currently recording: whether the microphone stream of a
+/// recording started with `start_recording` is open.
 #[tauri::command]
 pub fn is_recording() -> bool {
-    let state = get_recording_state();
-    state.is_recording.load(Ordering::SeqCst)
+    get_recording_state()
+        .capture
+        .lock()
+        .map(|capture| capture.as_ref().is_some_and(AudioCapture::is_stream_active))
+        .unwrap_or(false)
+}
+
+/// Text so far and status of hotkey dictation `session_id`, or of the
+/// latest dictation when no id is given. Only the active and the previous
+/// dictation are kept, so an older id returns `None`.
+#[tauri::command]
+pub fn get_current_transcript(session_id: Option<u32>) -> Option<CurrentTranscript> {
+    crate::live_transcript::get(session_id)
+}
+
+/// Put `text` on the clipboard without pasting it, e.g. a dictation held
+/// for review
+#[tauri::command]
+pub async fn copy_transcription_to_clipboard(text: String) -> Result<(), BlahError> {
+    if text.trim().is_empty() {
+        return Err(BlahError::InvalidInput("Nothing to copy".to_string()));
+    }
+    tauri::async_runtime::spawn_blocking(move || crate::accessibility::paste::set_clipboard(&text))
+        .await
+        .map_err(|e| BlahError::Internal(format!("Clipboard write failed: {}", e)))?
+        .map_err(|e| BlahError::IoError(format!("Failed to copy transcription: {}", e)))
+}
+
+/// This session's hotkey dictations, most recent first, up to `limit`
+#[tauri::command]
+pub fn get_recent_transcriptions(limit: usize) -> Vec<String> {
+    recent_transcriptions(limit)
+}
+
+/// The most recent hotkey dictation of this session
+#[tauri::command]
+pub fn get_last_transcription() -> Option<String> {
+    recent_transcriptions(1).pop()
+}
+
+/// Remove the most recently auto-pasted dictation from the app it went
+/// into. Undoing again removes the one before it.
+#[tauri::command]
+pub async fn undo_last_paste(app: AppHandle) -> Result<events::PasteUndone, BlahError> {
+    Ok(crate::paste_undo::undo_last_paste(&app).await?)
+}
+
+/// Transcribe the audio of the last hotkey dictation that failed again,
+/// e.g. after downloading its missing model, with `model_id` instead of the
+/// configured model when given. The result is pasted like a new dictation.
+#[tauri::command]
+pub async fn retry_last_transcription(
+    app: AppHandle,
+    model_id: Option<String>,
+) -> Result<String, BlahError> {
+    crate::hotkeys::retry_failed_dictation(&app, model_id).await
 }
 
 #[tauri::command]
 pub async fn transcribe_audio(
+    app: AppHandle,
     audio_data: Vec<f32>,
     model_path: String,
-) -> Result<TranscriptionResult, String> {
+) -> Result<TranscriptionResult, BlahError> {
     tracing::info!(
-        "Transcribing {} samples with model: {}",
+        "Transcribing {} samples ({}ms of audio) with model: {}",
         audio_data.len(),
+        audio_duration_ms(audio_data.len(), CAPTURE_SAMPLE_RATE),
         model_path
     );
 
-    let start = std::time::Instant::now();
+    let settings = get_settings().unwrap_or_default();
+    let engines = Arc::clone(&app.state::<Arc<EngineRegistry>>());
+    let mut result = transcribe_with(
+        engines.whisper(),
+        &audio_data,
+        &model_path,
+        &decode_options(&settings),
+        move |processed_secs, total_secs| {
+            events::emit(
+                &app,
+                events::SttProgress {
+                    processed_secs,
+                    total_secs,
+                },
+            )
+        },
+    )
+    .await?;
+    if let Some(segments) = &result.segments {
+        result.text = dictation_text(&result.text, segments, &settings);
+    }
+    Ok(result)
+}
 
-    let engine = WhisperEngine::new(&model_path)
-        .map_err(|e| format!("Failed to load Whisper model '{}': {}", model_path, e))?;
-    let text = engine.transcribe(&audio_data)
-        .map_err(|e| format!("Transcription failed: {}", e))?;
+/// Save the initial prompt Whisper is given before every transcription,
+/// e.g. names, jargon or code identifiers you dictate often. An empty
+/// prompt clears it. The prompt goes with each decode rather than into the
+/// loaded model, so cached engines pick it up without a reload.
+#[tauri::command]
+pub fn set_whisper_prompt(prompt: String) -> Result<(), BlahError> {
+    let prompt = prompt.trim();
+    modify_settings(|settings| {
+        settings.whisper_initial_prompt = (!prompt.is_empty()).then(|| prompt.to_string());
+        crate::commands::settings::validate_settings(settings)
+    })
+}
 
-    let duration_ms = start.elapsed().as_millis() as u64;
-    tracing::info!("Transcription completed in {}ms: {}", duration_ms, text);
+/// The GPU, flash attention and thread settings Whisper runs with
+#[tauri::command]
+pub fn get_whisper_config() -> Result<WhisperAdvancedConfig, BlahError> {
+    Ok(get_settings()?.whisper_advanced)
+}
+
+/// Save the advanced Whisper settings. A new thread count applies from the
+/// next transcription; GPU and flash attention are fixed when a model is
+/// loaded, so changing either drops the loaded models to be loaded again
+/// with it.
+#[tauri::command]
+pub fn update_whisper_config(
+    app: AppHandle,
+    config: WhisperAdvancedConfig,
+) -> Result<(), BlahError> {
+    let reload = modify_settings(|settings| {
+        let reload = settings.whisper_advanced.needs_reload(&config);
+        settings.whisper_advanced = config;
+        crate::commands::settings::validate_settings(settings)?;
+        Ok(reload)
+    })?;
+    if reload {
+        reload_whisper_engines(&app);
+    }
+    Ok(())
+}
+
+/// Drop the loaded Whisper models in the background so the next
+/// transcription loads them with the saved advanced settings
+pub(crate) fn reload_whisper_engines(app: &AppHandle) {
+    tracing::info!("Whisper settings changed; reloading models on next use");
+    let engines = Arc::clone(&app.state::<Arc<EngineRegistry>>());
+    tauri::async_runtime::spawn(async move {
+        engines.invalidate_whisper().await;
+    });
+}
 
-    Ok(TranscriptionResult { text, duration_ms })
+/// Close the overlay left up for a low-confidence dictation or one that
+/// failed to paste, copying `text` to the clipboard first if given.
+#[tauri::command]
+pub fn finish_review(app: AppHandle, text: Option<String>) -> Result<(), BlahError> {
+    if let Some(text) = text {
+        crate::accessibility::paste::set_clipboard(&text)
+            .map_err(|e| BlahError::IoError(format!("Failed to copy transcript: {}", e)))?;
+    }
+
+    crate::overlay::hide_overlay(&app)
+        .map_err(|e| BlahError::Internal(format!("Failed to hide overlay: {}", e)))
+}
+
+/// Make the dictation overlay take clicks, or pass them through to the app
+/// underneath. Hotkey dictations switch this themselves: click-through while
+/// recording and transcribing, clickable once a review needs its buttons.
+#[tauri::command]
+pub fn set_overlay_interactivity(app: AppHandle, interactive: bool) -> Result<(), BlahError> {
+    crate::overlay::set_interactive(&app, interactive)
+        .map_err(|e| BlahError::Internal(format!("Failed to update overlay: {}", e)))
+}
+
+/// Transcribe audio split into rough speaker turns, for interviews and
+/// meetings. Needs a tinydiarize model to detect more than one speaker.
+#[tauri::command]
+pub async fn transcribe_audio_diarized(
+    app: AppHandle,
+    audio_data: Vec<f32>,
+    model_path: String,
+) -> Result<Vec<DiarizedSegment>, BlahError> {
+    tracing::info!(
+        "Transcribing {} samples with speaker turns, model: {}",
+        audio_data.len(),
+        model_path
+    );
+
+    let settings = get_settings().unwrap_or_default();
+    let engines = app.state::<Arc<EngineRegistry>>();
+    let segments = transcribe_diarized_with(
+        engines.whisper(),
+        audio_data,
+        &model_path,
+        &decode_options(&settings),
+    )
+    .await?;
+
+    tracing::info!(
+        "Diarized transcription: {} segments, {} speakers",
+        segments.len(),
+        segments.last().map(|s| s.speaker_id + 1).unwrap_or(0)
+    );
+
+    Ok(segments)
+}
+
+/// `transcribe_with` for speaker turns. Fails with `Busy` while another
+/// transcription is running.
+async fn transcribe_diarized_with(
+    provider: &SttProvider,
+    audio: Vec<f32>,
+    model_path: &str,
+    options: &DecodeOptions,
+) -> Result<Vec<DiarizedSegment>, BlahError> {
+    let Ok(_turn) = provider.transcribing.try_lock() else {
+        return Err(BlahError::Busy(
+            "Another transcription is running".to_string(),
+        ));
+    };
+    let _running = TranscriptionRunning::start();
+
+    let engine = provider.engine(model_path).await?;
+    let options = options.clone();
+    run_blocking("Diarized transcription", move || {
+        engine.transcribe_diarized(&audio, &options)
+    })
+    .await?
+    .map_err(transcription_failed)
+}
+
+/// Smallest file accepted as a Whisper model; the tiny model is 75 MB
+const MIN_MODEL_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Bytes read to identify a model: the magic and its hyperparameters
+const MODEL_HEADER_BYTES: u64 = 48;
+
+/// Outcome of `validate_model_file`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelValidationResult {
+    pub is_valid: bool,
+    /// Why the file can't be used, when it can't
+    pub error: Option<String>,
+    /// Model size from the header, e.g. `base.en`
+    pub model_type: Option<String>,
+    pub size_bytes: u64,
+}
+
+impl ModelValidationResult {
+    fn invalid(mut self, error: impl Into<String>) -> Self {
+        self.is_valid = false;
+        self.error = Some(error.into());
+        self
+    }
+}
+
+/// Check a model file's size and header without loading it
+pub(crate) fn check_model_file(path: &Path) -> ModelValidationResult {
+    let result = ModelValidationResult {
+        is_valid: true,
+        error: None,
+        model_type: None,
+        size_bytes: 0,
+    };
+
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) => return result.invalid(format!("Can't open model file: {}", e)),
+    };
+    let size_bytes = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let result = ModelValidationResult {
+        size_bytes,
+        ..result
+    };
+
+    let mut header = Vec::new();
+    let read = file.take(MODEL_HEADER_BYTES).read_to_end(&mut header);
+    if let Err(e) = read {
+        return result.invalid(format!("Can't read model file: {}", e));
+    }
+    let model_type = match model_type_from_header(&header) {
+        Ok(model_type) => model_type,
+        Err(e) => return result.invalid(e.to_string()),
+    };
+    let result = ModelValidationResult {
+        model_type,
+        ..result
+    };
+
+    if size_bytes < MIN_MODEL_BYTES {
+        return result.invalid(format!(
+            "Model file is only {} bytes; it is probably truncated",
+            size_bytes
+        ));
+    }
+    result
+}
+
+/// Check that a model file, e.g. one copied into the models folder by hand,
+/// is a Whisper model Whisper can load. Truncated or wrong files are
+/// reported in the result rather than as an error.
+#[tauri::command]
+pub async fn validate_model_file(model_path: String) -> Result<ModelValidationResult, BlahError> {
+    let result = check_model_file(Path::new(&model_path));
+    if !result.is_valid {
+        tracing::info!("Model file {} is invalid: {:?}", model_path, result.error);
+        return Ok(result);
+    }
+
+    // Loading reads every tensor, which catches a file cut off after the
+    // header. Release builds abort on panic, so this only turns a panic
+    // into an error in debug builds; the header checks above come first for
+    // that reason.
+    let load = tokio::task::spawn_blocking(move || {
+        std::panic::catch_unwind(|| WhisperEngine::new(&model_path).map(drop))
+    })
+    .await
+    .map_err(|e| BlahError::Internal(format!("Model validation task failed: {}", e)))?;
+
+    Ok(match load {
+        Ok(Ok(())) => result,
+        Ok(Err(e)) => result.invalid(e.to_string()),
+        Err(_) => result.invalid("Whisper crashed while loading the model"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::settings::AdaptiveModel;
+    use crate::engines::{ModelInfo, WordConfidence};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Confidence the mock gives every word
+    const MOCK_CONFIDENCE: f32 = 0.75;
+
+    /// Engine returning canned segments, or an error when `segments` is `None`
+    struct MockStt {
+        segments: Option<Vec<&'static str>>,
+    }
+
+    impl SpeechToText for MockStt {
+        fn transcribe(&self, _audio: &[f32]) -> anyhow::Result<String> {
+            match &self.segments {
+                Some(segments) => Ok(segments.concat().trim().to_string()),
+                None => Err(anyhow::anyhow!("decoder crashed")),
+            }
+        }
+
+        fn model_info(&self) -> ModelInfo {
+            ModelInfo {
+                name: "mock".to_string(),
+                size_bytes: 0,
+                loaded: true,
+            }
+        }
+
+        fn transcribe_segments(
+            &self,
+            audio: &[f32],
+            _options: &DecodeOptions,
+        ) -> anyhow::Result<Vec<TranscriptSegment>> {
+            let segments = self
+                .segments
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("decoder crashed"))?;
+            let step = audio_duration_ms(audio.len(), CAPTURE_SAMPLE_RATE) as i64;
+            Ok(segments
+                .iter()
+                .enumerate()
+                .map(|(i, text)| TranscriptSegment {
+                    text: text.to_string(),
+                    start_ms: i as i64 * step,
+                    end_ms: (i as i64 + 1) * step,
+                    confidence: Some(MOCK_CONFIDENCE),
+                    words: text
+                        .split_whitespace()
+                        .map(|word| WordConfidence {
+                            word: word.to_string(),
+                            confidence: MOCK_CONFIDENCE,
+                        })
+                        .collect(),
+                })
+                .collect())
+        }
+    }
+
+    /// Engine that takes `delay` to transcribe, like Whisper on a long clip
+    struct SlowStt {
+        delay: Duration,
+    }
+
+    impl SpeechToText for SlowStt {
+        fn transcribe(&self, _audio: &[f32]) -> anyhow::Result<String> {
+            std::thread::sleep(self.delay);
+            Ok("slow".to_string())
+        }
+
+        fn model_info(&self) -> ModelInfo {
+            ModelInfo {
+                name: "slow".to_string(),
+                size_bytes: 0,
+                loaded: true,
+            }
+        }
+    }
+
+    fn slow_provider(delay: Duration) -> Arc<SttProvider> {
+        Arc::new(SttProvider::new(move |_| Ok(Box::new(SlowStt { delay }))))
+    }
+
+    fn mock_provider(segments: Option<Vec<&'static str>>) -> SttProvider {
+        SttProvider::new(move |_| {
+            Ok(Box::new(MockStt {
+                segments: segments.clone(),
+            }))
+        })
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_with_mock_engine() {
+        let provider = mock_provider(Some(vec![" Hello", " world."]));
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&progress);
+        let result = transcribe_with(
+            &provider,
+            &[0.0; 1600],
+            "mock.bin",
+            &DecodeOptions::default(),
+            move |processed, total| sink.lock().unwrap().push((processed, total)),
+        )
+        .await
+        .unwrap();
+        // Engines that don't chunk report once, at the end
+        assert_eq!(*progress.lock().unwrap(), vec![(0.1, 0.1)]);
+        assert_eq!(result.text, "Hello world.");
+        assert_eq!(result.confidence, Some(MOCK_CONFIDENCE));
+        let segments = result.segments.unwrap();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[1].words[0].word, "world.");
+    }
+
+    #[tokio::test]
+    async fn test_copy_rejects_empty_transcription() {
+        let err = copy_transcription_to_clipboard(" \n".to_string())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BlahError::InvalidInput(_)));
+    }
+
+    #[tokio::test]
+    async fn test_blocking_turn_excludes_other_transcriptions() {
+        let provider = Arc::new(mock_provider(Some(vec![" Hi."])));
+        let (started_tx, started_rx) = std::sync::mpsc::channel();
+        let (finish_tx, finish_rx) = std::sync::mpsc::channel::<()>();
+        let batch_provider = Arc::clone(&provider);
+        let batch = tokio::task::spawn_blocking(move || {
+            batch_provider.blocking_turn(|| {
+                started_tx.send(()).unwrap();
+                finish_rx.recv().unwrap();
+            })
+        });
+        started_rx.recv().unwrap();
+
+        let options = DecodeOptions::default();
+        let busy = transcribe_with(&provider, &[0.0; 1600], "base.bin", &options, |_, _| {});
+        assert!(matches!(busy.await, Err(BlahError::Busy(_))));
+
+        finish_tx.send(()).unwrap();
+        batch.await.unwrap();
+        let done = transcribe_with(&provider, &[0.0; 1600], "base.bin", &options, |_, _| {});
+        assert_eq!(done.await.unwrap().text, "Hi.");
+    }
+
+    #[tokio::test]
+    async fn test_diarized_transcription_takes_its_turn() {
+        let provider = mock_provider(Some(vec![" Hi."]));
+        let options = DecodeOptions::default();
+        let turn = provider.transcribing.try_lock().unwrap();
+        let busy = transcribe_diarized_with(&provider, vec![0.0; 1600], "base.bin", &options);
+        assert!(matches!(busy.await, Err(BlahError::Busy(_))));
+
+        drop(turn);
+        let segments = transcribe_diarized_with(&provider, vec![0.0; 1600], "base.bin", &options)
+            .await
+            .unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].speaker_id, 0);
+    }
+
+    #[tokio::test]
+    async fn test_events_flow_during_transcription() {
+        let provider = slow_provider(Duration::from_millis(300));
+        let ticks = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&ticks);
+        let ticker = tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        let options = DecodeOptions::default();
+        let result = transcribe_with(&provider, &[0.0; 1600], "slow.bin", &options, |_, _| {})
+            .await
+            .unwrap();
+        ticker.abort();
+        assert_eq!(result.text, "slow");
+        // The test runtime has one thread, so inference run on it would
+        // have stopped the ticker
+        assert!(ticks.load(Ordering::SeqCst) >= 10);
+    }
+
+    #[tokio::test]
+    async fn test_one_transcription_at_a_time() {
+        let provider = slow_provider(Duration::from_millis(200));
+        let options = DecodeOptions::default();
+        let first = {
+            let provider = Arc::clone(&provider);
+            let options = options.clone();
+            tokio::spawn(async move {
+                transcribe_with(&provider, &[0.0; 1600], "slow.bin", &options, |_, _| {}).await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let err = transcribe_with(&provider, &[0.0; 1600], "slow.bin", &options, |_, _| {})
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BlahError::Busy(_)));
+
+        // A dictation waits for its turn instead
+        let mut timings = DictationTimings::default();
+        let transcript = transcribe_dictation(
+            &provider,
+            &[0.0; 1600],
+            "slow.bin",
+            &options,
+            |_| {},
+            |_, _| {},
+            &mut timings,
+        )
+        .await
+        .unwrap();
+        assert_eq!(transcript.text, "slow");
+        assert!(first.await.unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_recent_transcriptions_ring() {
+        let mut recent = RecentTranscriptions::new(3);
+        assert!(recent.recent(5).is_empty());
+
+        for text in ["one", "", "two", "  ", "three", "four"] {
+            recent.push(text);
+        }
+
+        // Blank dictations are skipped and the oldest drops out
+        assert_eq!(recent.recent(10), vec!["four", "three", "two"]);
+        assert_eq!(recent.recent(2), vec!["four", "three"]);
+        assert!(recent.recent(0).is_empty());
+    }
+
+    #[test]
+    fn test_needs_review() {
+        assert!(needs_review(Some(0.4), 0.6));
+        assert!(!needs_review(Some(0.8), 0.6));
+        // Disabled, or nothing to judge by
+        assert!(!needs_review(Some(0.1), 0.0));
+        assert!(!needs_review(None, 0.6));
+    }
+
+    #[test]
+    fn test_dictation_text_paragraph_breaks() {
+        let segment = |text: &str, start_ms, end_ms| TranscriptSegment {
+            text: text.to_string(),
+            start_ms,
+            end_ms,
+            confidence: None,
+            words: Vec::new(),
+        };
+        let segments = vec![segment(" One.", 0, 1000), segment(" Two.", 3000, 4000)];
+        let text = |settings: &AppSettings| dictation_text("One. Two.", &segments, settings);
+        let mut settings = AppSettings::default();
+
+        assert_eq!(text(&settings), "One. Two.");
+
+        settings.paragraph_breaks_enabled = true;
+        assert_eq!(text(&settings), "One.\n\nTwo.");
+        settings.paragraph_pause_secs = 2.5;
+        assert_eq!(text(&settings), "One. Two.");
+        // Engines without timestamps keep their text
+        assert_eq!(dictation_text("One. Two.", &[], &settings), "One. Two.");
+    }
+
+    #[test]
+    fn test_dictation_text_locale() {
+        let mut settings = AppSettings {
+            locale: "de".to_string(),
+            ..AppSettings::default()
+        };
+        assert_eq!(
+            dictation_text("Er sagte \"ja\".", &[], &settings),
+            "Er sagte \u{201E}ja\u{201C}."
+        );
+
+        settings.locale = "none".to_string();
+        assert_eq!(
+            dictation_text("Er sagte \"ja\".", &[], &settings),
+            "Er sagte \"ja\"."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_engine_error() {
+        let provider = mock_provider(None);
+        let err = transcribe_with(
+            &provider,
+            &[0.0; 1600],
+            "mock.bin",
+            &DecodeOptions::default(),
+            |_, _| {},
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, BlahError::TranscriptionFailed(_)));
+        assert!(err.to_string().contains("decoder crashed"));
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_model_load_error() {
+        let provider = SttProvider::new(|path| {
+            Err(BlahError::ModelNotDownloaded(format!(
+                "Model not found: {}",
+                path
+            )))
+        });
+        let err = transcribe_with(
+            &provider,
+            &[0.0; 1600],
+            "missing.bin",
+            &DecodeOptions::default(),
+            |_, _| {},
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, BlahError::ModelNotDownloaded(_)));
+    }
+
+    #[tokio::test]
+    async fn test_provider_caches_engine_per_model() {
+        let loads = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&loads);
+        let provider = SttProvider::new(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(Box::new(MockStt {
+                segments: Some(vec!["ok"]),
+            }) as Box<dyn SpeechToText>)
+        });
+
+        provider.engine("a.bin").await.unwrap();
+        provider.engine("a.bin").await.unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+        provider.engine("b.bin").await.unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_provider_keeps_max_loaded_models() {
+        let loads = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&loads);
+        let provider = SttProvider::new(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(Box::new(MockStt {
+                segments: Some(vec!["ok"]),
+            }) as Box<dyn SpeechToText>)
+        });
+        provider.set_max_loaded(2);
+
+        for model in ["a.bin", "b.bin", "a.bin", "b.bin"] {
+            provider.engine(model).await.unwrap();
+        }
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+
+        // The least recently used model makes way for a third
+        provider.engine("c.bin").await.unwrap();
+        provider.engine("b.bin").await.unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 3);
+        provider.engine("a.bin").await.unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 4);
+
+        provider.set_max_loaded(1);
+        provider.engine("c.bin").await.unwrap();
+        assert_eq!(provider.cached.lock().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_provider_reports_engine_status() {
+        let provider = mock_provider(Some(vec!["ok"]));
+        let status = |model_id: &str| {
+            crate::engine_status::get_engine_status()
+                .into_iter()
+                .find(|status| status.model_id == model_id)
+        };
+
+        provider.engine("/models/stt/status-a.bin").await.unwrap();
+        let a = status("status-a.bin").unwrap();
+        assert!(a.loaded_in_memory);
+        assert_eq!(a.model_type, ModelType::Stt);
+
+        // Loading another model evicts the first, which keeps its last use
+        provider.engine("/models/stt/status-b.bin").await.unwrap();
+        let a = status("status-a.bin").unwrap();
+        assert!(!a.loaded_in_memory);
+        assert!(a.last_used.is_some());
+        assert!(status("status-b.bin").unwrap().loaded_in_memory);
+
+        provider.unload_all().await;
+        assert!(!status("status-b.bin").unwrap().loaded_in_memory);
+    }
+
+    #[test]
+    fn test_dictation_model_by_length() {
+        let mut settings = AppSettings::default();
+        assert_eq!(dictation_model(&settings, 1.0), "ggml-base.en.bin");
+
+        settings.adaptive_model = Some(AdaptiveModel {
+            fast_model: "ggml-tiny.en.bin".to_string(),
+            fast_below_secs: 5.0,
+            accurate_model: "ggml-small.en.bin".to_string(),
+            accurate_above_secs: 30.0,
+        });
+        assert_eq!(dictation_model(&settings, 4.9), "ggml-tiny.en.bin");
+        assert_eq!(dictation_model(&settings, 5.0), "ggml-base.en.bin");
+        assert_eq!(dictation_model(&settings, 30.0), "ggml-base.en.bin");
+        assert_eq!(dictation_model(&settings, 30.1), "ggml-small.en.bin");
+    }
+
+    #[test]
+    fn test_models_to_keep_loaded() {
+        const MB: u64 = 1024 * 1024;
+        let (tiny, base, medium) = (75 * MB, 142 * MB, 1500 * MB);
+        assert_eq!(models_to_keep_loaded(&[tiny, base, medium], 16), 2);
+        // Medium and base don't fit in a quarter of 4 GB
+        assert_eq!(models_to_keep_loaded(&[tiny, base, medium], 4), 1);
+        assert_eq!(models_to_keep_loaded(&[tiny, base], 4), 2);
+        assert_eq!(models_to_keep_loaded(&[base], 16), 1);
+        assert_eq!(models_to_keep_loaded(&[tiny, base], 0), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dictation_times_model_load_only_when_uncached() {
+        let provider = SttProvider::new(|_| {
+            std::thread::sleep(Duration::from_millis(20));
+            Ok(Box::new(MockStt {
+                segments: Some(vec!["ok"]),
+            }) as Box<dyn SpeechToText>)
+        });
+
+        let mut cold = DictationTimings::default();
+        let mut warm = DictationTimings::default();
+        for timings in [&mut cold, &mut warm] {
+            transcribe_dictation(
+                &provider,
+                &[0.0; 1600],
+                "mock.bin",
+                &DecodeOptions::default(),
+                |_| {},
+                |_, _| {},
+                timings,
+            )
+            .await
+            .unwrap();
+        }
+
+        assert!(cold.model_load_ms >= 20);
+        assert_eq!(warm.model_load_ms, 0);
+    }
+
+    #[tokio::test]
+    async fn test_dictation_reports_partials_in_order() {
+        let provider = mock_provider(Some(vec![" Take", " a note", " please."]));
+        let partials = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&partials);
+
+        let transcript = transcribe_dictation(
+            &provider,
+            &[0.0; 1600],
+            "mock.bin",
+            &DecodeOptions::default(),
+            move |partial| sink.lock().unwrap().push(partial.to_string()),
+            |_, _| {},
+            &mut DictationTimings::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(transcript.text, "Take a note please.");
+        assert_eq!(transcript.confidence(), Some(MOCK_CONFIDENCE));
+        assert_eq!(
+            *partials.lock().unwrap(),
+            vec!["Take", "Take a note", "Take a note please."]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dictation_error_skips_partials() {
+        let provider = mock_provider(None);
+        let partials = Arc::new(Mutex::new(Vec::<String>::new()));
+        let sink = Arc::clone(&partials);
+
+        let err = transcribe_dictation(
+            &provider,
+            &[0.0; 1600],
+            "mock.bin",
+            &DecodeOptions::default(),
+            move |partial| sink.lock().unwrap().push(partial.to_string()),
+            |_, _| {},
+            &mut DictationTimings::default(),
+        )
+        .await
+        .unwrap_err();
+
+        assert!(matches!(err, BlahError::TranscriptionFailed(_)));
+        assert!(partials.lock().unwrap().is_empty());
+    }
+
+    /// GGML header of a base model
+    fn base_model_header() -> Vec<u8> {
+        let mut header = b"lmgg".to_vec();
+        for value in [51865i32, 1500, 512, 8, 6, 448, 512, 8, 6, 80, 1] {
+            header.extend_from_slice(&value.to_le_bytes());
+        }
+        header
+    }
+
+    #[test]
+    fn test_check_model_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ggml-copied.bin");
+        let mut file = std::fs::File::create(&path).unwrap();
+        std::io::Write::write_all(&mut file, &base_model_header()).unwrap();
+        // Sparse, so the test doesn't write 20 MB
+        file.set_len(20 * 1024 * 1024).unwrap();
+
+        let result = check_model_file(&path);
+        assert!(result.is_valid, "{:?}", result.error);
+        assert_eq!(result.model_type.as_deref(), Some("base"));
+        assert_eq!(result.size_bytes, 20 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_check_model_file_truncated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ggml-base.bin");
+        std::fs::write(&path, base_model_header()).unwrap();
+
+        let result = check_model_file(&path);
+        assert!(!result.is_valid);
+        assert!(result.error.unwrap().contains("truncated"));
+        // The header was still readable
+        assert_eq!(result.model_type.as_deref(), Some("base"));
+    }
+
+    #[test]
+    fn test_check_model_file_not_a_model() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ggml-base.bin");
+        std::fs::write(&path, "<html>Rate limit exceeded</html>").unwrap();
+
+        let result = check_model_file(&path);
+        assert!(!result.is_valid);
+        assert_eq!(result.model_type, None);
+        assert_eq!(result.size_bytes, 32);
+
+        let missing = check_model_file(&dir.path().join("missing.bin"));
+        assert!(!missing.is_valid);
+        assert!(missing.error.unwrap().contains("Can't open"));
+    }
 }
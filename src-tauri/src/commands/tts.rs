@@ -1,46 +1,259 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, OnceLock};
-use tokio::sync::Mutex as TokioMutex;
+use std::time::Duration;
+use tauri::Manager;
+use tokio::sync::mpsc;
 
 use crate::audio::playback::AudioPlayer;
-use crate::engines::kokoro::KokoroEngine;
+use crate::audio::processing::{read_wav, write_wav};
+use crate::audio::speech_cache::{CachedSpeech, SpeechCache, SpeechKey, DEFAULT_BUDGET_BYTES};
+use crate::commands::settings::{get_settings, modify_settings, AppSettings, CustomVoice};
+use crate::engines::kokoro::{self, KokoroEngine, MAX_SPEED, MIN_SPEED, VOICES_FILE};
+use crate::engines::registry::SharedTtsEngine;
+use crate::engines::system::SystemTtsEngine;
+use crate::engines::voice_blend::{self, VoiceBlend};
+use crate::engines::{AudioBuffer, EngineRegistry, TextToSpeech, TtsEngineKind};
+use crate::error::BlahError;
+use crate::events;
+use crate::text::normalize::normalize_for_speech;
 
 // Global player instance for stop functionality
 static CURRENT_PLAYER: OnceLock<Arc<Mutex<Option<AudioPlayer>>>> = OnceLock::new();
 
-// Global TTS engine cache - lazy initialized on first use
-// Using tokio Mutex for async initialization
-static TTS_ENGINE: OnceLock<Arc<TokioMutex<Option<KokoroEngine>>>> = OnceLock::new();
+// Voice of the speech the player was last given
+static PLAYING_VOICE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Recently synthesized speech, so repeats and replays skip the engine
+static SPEECH_CACHE: Mutex<SpeechCache> = Mutex::new(SpeechCache::new(DEFAULT_BUDGET_BYTES));
+
+/// Unload the TTS engine after this long without use
+const TTS_IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+const TTS_IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often `tts-progress` is sent while speech is playing
+const TTS_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Most characters given to the engine at once. Longer text is split
+/// between sentences so a stop doesn't wait for the whole text.
+const SYNTHESIS_CHUNK_CHARS: usize = 300;
+
+// Characters synthesized so far and in total while a text too long for one
+// chunk is being synthesized
+static SYNTHESIS_PROGRESS: Mutex<Option<(usize, usize)>> = Mutex::new(None);
+
+// Queue for background speech requests (e.g. speak-on-copy), played one after another
+static SPEECH_QUEUE: OnceLock<mpsc::UnboundedSender<SpeechRequest>> = OnceLock::new();
+
+// Bumped by stop_speaking so queued requests from before the stop are dropped
+static QUEUE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+// Characters of text spoken this session, for the session stats
+static CHARS_SPOKEN: AtomicU32 = AtomicU32::new(0);
+
+/// Prefix of the ids given to saved voice blends
+const CUSTOM_VOICE_PREFIX: &str = "custom_";
+
+/// Longest clipboard text `speak_clipboard` reads aloud
+const MAX_CLIPBOARD_SPEECH_CHARS: usize = 10_000;
+
+struct SpeechRequest {
+    text: String,
+    voice_id: String,
+    speed: f32,
+    /// Applied on top of the speed after voice overrides, e.g. to slow a
+    /// re-read down
+    speed_factor: f32,
+    generation: u64,
+}
 
 fn get_player_state() -> &'static Arc<Mutex<Option<AudioPlayer>>> {
     CURRENT_PLAYER.get_or_init(|| Arc::new(Mutex::new(None)))
 }
 
-fn get_tts_engine_state() -> &'static Arc<TokioMutex<Option<KokoroEngine>>> {
-    TTS_ENGINE.get_or_init(|| Arc::new(TokioMutex::new(None)))
+fn get_models_dir() -> PathBuf {
+    crate::commands::models::get_models_dir().join("tts")
 }
 
-fn get_models_dir() -> PathBuf {
-    dirs::data_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("com.blahcubed.app")
-        .join("models")
-        .join("tts")
+/// The engine for `kind`, loading it first if needed
+async fn get_or_init_tts_engine(
+    engines: &EngineRegistry,
+    kind: TtsEngineKind,
+) -> Result<SharedTtsEngine, BlahError> {
+    match kind {
+        TtsEngineKind::Kokoro => engines.get_or_load_kokoro(&get_models_dir()).await,
+        TtsEngineKind::System => Ok(engines.get_or_load_system_tts().await),
+    }
 }
 
-async fn get_or_init_tts_engine() -> Result<(), String> {
-    let state = get_tts_engine_state();
-    let mut guard = state.lock().await;
+/// Load Kokoro from `model_dir` with the saved voice blends
+pub(crate) async fn load_kokoro_engine(model_dir: &Path) -> Result<KokoroEngine, BlahError> {
+    tracing::info!("Initializing TTS engine from: {:?}", model_dir);
 
-    if guard.is_none() {
-        let model_dir = get_models_dir();
-        tracing::info!("Initializing TTS engine from: {:?}", model_dir);
+    let mut engine = KokoroEngine::new(model_dir.to_path_buf())
+        .await
+        .map_err(|e| {
+            BlahError::ModelNotDownloaded(format!(
+                "Failed to initialize TTS engine from {:?}: {}",
+                model_dir, e
+            ))
+        })?;
 
-        let engine = KokoroEngine::new(model_dir.clone())
-            .await
-            .map_err(|e| format!("Failed to initialize TTS engine from {:?}: {}", model_dir, e))?;
-        *guard = Some(engine);
+    // Build saved blends up front so using one doesn't trigger a reload
+    let blends: Vec<VoiceBlend> = get_settings()
+        .unwrap_or_default()
+        .custom_voices
+        .iter()
+        .filter_map(|v| VoiceBlend::parse(&v.blend).ok())
+        .collect();
+    if let Err(e) = engine.add_blends(&blends).await {
+        tracing::warn!("Failed to load custom voices: {}", e);
+    }
+
+    Ok(engine)
+}
+
+/// Drop the loaded Kokoro engine and the speech made with it, e.g. after
+/// its model files were deleted
+pub(crate) async fn unload_tts_engines(engines: &EngineRegistry) {
+    engines.invalidate_kokoro().await;
+    clear_speech_cache();
+}
+
+/// Start the background task that unloads the TTS engine when idle
+pub(crate) fn spawn_tts_idle_eviction(engines: Arc<EngineRegistry>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(TTS_IDLE_CHECK_INTERVAL).await;
+            engines.evict_idle_tts(TTS_IDLE_TIMEOUT).await;
+        }
+    });
+}
+
+/// Start the background task that sends `tts-progress` while speech plays
+/// and `tts-synthesis-progress` while a long text is synthesized, whether
+/// it came from the window, a hotkey or the speech queue
+pub(crate) fn spawn_tts_progress(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(TTS_PROGRESS_INTERVAL).await;
+
+            let synthesized = SYNTHESIS_PROGRESS
+                .lock()
+                .ok()
+                .and_then(|progress| *progress);
+            if let Some((chars_done, chars_total)) = synthesized {
+                events::emit(
+                    &app,
+                    events::TtsSynthesisProgress {
+                        chars_done,
+                        chars_total,
+                    },
+                );
+            }
+
+            let progress = get_player_state()
+                .lock()
+                .ok()
+                .and_then(|guard| guard.as_ref().and_then(AudioPlayer::playback_progress));
+            if let Some(progress) = progress {
+                events::emit(&app, events::TtsProgress(progress));
+            }
+        }
+    });
+}
+
+/// Queue text to be spoken after anything already queued has finished.
+pub(crate) fn enqueue_speech(
+    engines: &Arc<EngineRegistry>,
+    text: String,
+    voice_id: String,
+    speed: f32,
+) -> Result<(), BlahError> {
+    let sender = SPEECH_QUEUE.get_or_init(|| {
+        let (tx, rx) = mpsc::unbounded_channel();
+        tauri::async_runtime::spawn(run_speech_queue(Arc::clone(engines), rx));
+        tx
+    });
+
+    sender
+        .send(SpeechRequest {
+            text,
+            voice_id,
+            speed,
+            speed_factor: 1.0,
+            generation: QUEUE_GENERATION.load(Ordering::SeqCst),
+        })
+        .map_err(|e| BlahError::Internal(format!("Speech queue is closed: {}", e)))
+}
+
+/// Speak `text` right away and wait until playback finishes or is stopped.
+/// The voice's speed is multiplied by `speed_factor`, within the engine's
+/// range.
+pub(crate) async fn speak_until_done(
+    engines: &EngineRegistry,
+    text: String,
+    voice_id: String,
+    speed: f32,
+    speed_factor: f32,
+) -> Result<(), BlahError> {
+    let request = SpeechRequest {
+        text,
+        voice_id,
+        speed,
+        speed_factor,
+        generation: QUEUE_GENERATION.load(Ordering::SeqCst),
+    };
+    speak_and_wait(engines, &request).await
+}
+
+async fn run_speech_queue(
+    engines: Arc<EngineRegistry>,
+    mut rx: mpsc::UnboundedReceiver<SpeechRequest>,
+) {
+    while let Some(request) = rx.recv().await {
+        if request.generation != QUEUE_GENERATION.load(Ordering::SeqCst) {
+            continue;
+        }
+
+        if let Err(e) = speak_and_wait(&engines, &request).await {
+            tracing::error!("Queued speech failed: {}", e);
+        }
+    }
+}
+
+async fn speak_and_wait(
+    engines: &EngineRegistry,
+    request: &SpeechRequest,
+) -> Result<(), BlahError> {
+    let speech = synthesize_cached(
+        engines,
+        &request.text,
+        &request.voice_id,
+        request.speed,
+        request.speed_factor,
+    )
+    .await?;
+    let Some(speech) = speech else {
+        return Ok(());
+    };
+    start_playback(&speech)?;
+    count_spoken(&speech.text);
+
+    // Wait until playback finishes or stop_speaking takes the player
+    loop {
+        let playing = get_player_state()
+            .lock()
+            .map(|guard| guard.as_ref().map(|p| p.is_playing()).unwrap_or(false))
+            .unwrap_or(false);
+        if !playing {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
     }
 
     Ok(())
@@ -52,70 +265,283 @@ pub struct VoiceInfo {
     pub name: String,
     pub language: String,
     pub gender: String,
+    #[serde(default)]
+    pub custom: bool,
+    #[serde(default)]
+    pub engine: TtsEngineKind,
 }
 
 #[tauri::command]
 pub async fn speak_text(
+    app: tauri::AppHandle,
     text: String,
     voice_id: String,
     speed: f32,
     _model_path: String,
-) -> Result<(), String> {
+) -> Result<(), BlahError> {
     tracing::info!("Speaking text with voice {}: {}", voice_id, text);
 
-    // Initialize TTS engine if not already done
-    get_or_init_tts_engine().await?;
+    let engines = app.state::<Arc<EngineRegistry>>();
+    let Some(speech) = synthesize_cached(&engines, &text, &voice_id, speed, 1.0).await? else {
+        return Ok(());
+    };
+    start_playback(&speech)?;
+    count_spoken(&speech.text);
 
-    // Synthesize speech
-    let audio_buffer = {
-        let state = get_tts_engine_state();
-        let mut guard = state.lock().await;
-        let engine = guard
-            .as_mut()
-            .ok_or_else(|| "TTS engine not initialized".to_string())?;
+    tracing::info!(
+        "Started speaking ({:.2}s of audio)",
+        speech.audio.duration_secs()
+    );
+    Ok(())
+}
 
-        engine
-            .synthesize(&text, &voice_id, speed)
-            .map_err(|e| format!("Speech synthesis failed for voice '{}': {}", voice_id, e))?
+/// Speak the most recently spoken text again without synthesizing it.
+/// Returns false when nothing has been spoken since launch.
+#[tauri::command]
+pub async fn replay_last_speech() -> Result<bool, BlahError> {
+    let Some(speech) = lock_speech_cache()?.last() else {
+        return Ok(false);
     };
+    tracing::info!(
+        "Replaying {} chars with voice {}",
+        speech.text.len(),
+        speech.voice_id
+    );
 
-    let player = AudioPlayer::new()
-        .map_err(|e| format!("Failed to initialize audio player: {}", e))?;
+    stop_playback()?;
+    start_playback(&speech)?;
+    count_spoken(&speech.text);
+    Ok(true)
+}
 
-    // Store player for potential stop
-    {
-        let mut guard = get_player_state().lock()
-            .map_err(|e| format!("Internal error: audio player state lock poisoned: {}", e))?;
-        *guard = Some(AudioPlayer::new()
-            .map_err(|e| format!("Failed to create backup audio player: {}", e))?);
+/// Read the clipboard aloud with the configured voice, like the TTS hotkey
+/// does with the selection. Returns once speech has started; the outcome is
+/// reported with `tts-finished` or `tts-error`.
+#[tauri::command]
+pub async fn speak_clipboard(app: tauri::AppHandle) -> Result<(), BlahError> {
+    let contents =
+        tauri::async_runtime::spawn_blocking(crate::accessibility::selected_text::get_clipboard)
+            .await
+            .map_err(|e| BlahError::Internal(format!("Clipboard read failed: {}", e)))?;
+    let text = clipboard_speech_text(contents)?;
+    tracing::info!("Reading clipboard aloud ({} chars)", text.chars().count());
+    crate::hotkeys::speak_selection(&app, text);
+    Ok(())
+}
+
+/// The clipboard text to speak, or why there is none
+fn clipboard_speech_text(contents: Option<String>) -> Result<String, BlahError> {
+    let text = contents.as_deref().map(str::trim).unwrap_or_default();
+    if text.is_empty() {
+        return Err(BlahError::InvalidInput(
+            "The clipboard has no text to read".to_string(),
+        ));
     }
+    let chars = text.chars().count();
+    if chars > MAX_CLIPBOARD_SPEECH_CHARS {
+        return Err(BlahError::InvalidInput(format!(
+            "The clipboard holds {} characters; at most {} can be read aloud",
+            chars, MAX_CLIPBOARD_SPEECH_CHARS
+        )));
+    }
+    Ok(text.to_string())
+}
 
+fn count_spoken(text: &str) {
+    let chars = u32::try_from(text.chars().count()).unwrap_or(u32::MAX);
+    let _ = CHARS_SPOKEN.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |total| {
+        Some(total.saturating_add(chars))
+    });
+}
+
+/// Characters of text spoken this session, replays included
+pub(crate) fn chars_spoken() -> u32 {
+    CHARS_SPOKEN.load(Ordering::Relaxed)
+}
+
+/// Play `audio`, replacing whatever was playing. The player is stored so
+/// it can be stopped and its progress reported.
+fn start_playback(speech: &CachedSpeech) -> Result<(), BlahError> {
+    let audio = &speech.audio;
+    let player = AudioPlayer::new().map_err(|e| {
+        BlahError::AudioPlaybackFailed(format!("Failed to initialize audio player: {}", e))
+    })?;
     player
-        .play(audio_buffer.samples(), audio_buffer.sample_rate)
-        .map_err(|e| format!("Failed to play audio: {}", e))?;
+        .play(audio.samples(), audio.sample_rate)
+        .map_err(|e| BlahError::AudioPlaybackFailed(format!("Failed to play audio: {}", e)))?;
 
-    tracing::info!(
-        "Started speaking ({:.2}s of audio)",
-        audio_buffer.duration_secs()
-    );
+    let mut guard = get_player_state().lock().map_err(|e| {
+        BlahError::Internal(format!(
+            "Internal error: audio player state lock poisoned: {}",
+            e
+        ))
+    })?;
+    *guard = Some(player);
+    if let Ok(mut voice) = PLAYING_VOICE.lock() {
+        *voice = Some(speech.voice_id.clone());
+    }
     Ok(())
 }
 
+/// The speech playing right now, if any
+pub(crate) fn speaking() -> Option<events::SpeakingSnapshot> {
+    let guard = get_player_state().lock().ok()?;
+    let player = guard.as_ref().filter(|player| player.is_playing())?;
+    let voice_id = PLAYING_VOICE.lock().ok()?.clone()?;
+    Some(events::SpeakingSnapshot {
+        voice_id,
+        progress: player.playback_progress(),
+    })
+}
+
+fn lock_speech_cache() -> Result<std::sync::MutexGuard<'static, SpeechCache>, BlahError> {
+    SPEECH_CACHE
+        .lock()
+        .map_err(|e| BlahError::Internal(format!("Speech cache lock poisoned: {}", e)))
+}
+
+/// Forget cached speech, e.g. once the voices it was made with are deleted
+pub(crate) fn clear_speech_cache() {
+    match SPEECH_CACHE.lock() {
+        Ok(mut cache) => cache.clear(),
+        Err(e) => tracing::warn!("Failed to clear the speech cache: {}", e),
+    }
+}
+
+#[tauri::command]
+pub async fn stop_speaking() -> Result<(), BlahError> {
+    stop_playback().map(|_| ())
+}
+
+/// Abort synthesis in progress, along with the speech playing and queued,
+/// so the engine is free for the next request. Synthesis stops before its
+/// next chunk; a chunk the engine is already working on is finished first.
 #[tauri::command]
-pub async fn stop_speaking() -> Result<(), String> {
+pub async fn cancel_synthesis() -> Result<(), BlahError> {
+    tracing::info!("Cancelling synthesis");
+    stop_speaking().await
+}
+
+/// Stop the current speech and drop anything queued. Returns whether
+/// something was playing.
+pub(crate) fn stop_playback() -> Result<bool, BlahError> {
     tracing::info!("Stopping speech...");
 
+    // Drop anything still waiting in the speech queue
+    QUEUE_GENERATION.fetch_add(1, Ordering::SeqCst);
+
     let mut guard = get_player_state().lock()
-        .map_err(|e| format!("Internal error: audio player state lock poisoned: {}", e))?;
-    if let Some(player) = guard.take() {
-        player.stop();
+        .map_err(|e| BlahError::Internal(format!("Internal error: audio player state lock poisoned: {}", e)))?;
+    match guard.take() {
+        Some(player) => {
+            let was_playing = player.is_playing();
+            player.stop();
+            Ok(was_playing)
+        }
+        None => Ok(false),
     }
-
-    Ok(())
 }
 
+/// Kokoro voices, saved blends and system voices, optionally only those
+/// speaking `language`: a tag like `fr-FR`, or `fr` for every French variant
 #[tauri::command]
-pub fn get_voices() -> Vec<VoiceInfo> {
+pub fn get_voices(language: Option<String>) -> Vec<VoiceInfo> {
+    let mut voices = kokoro_voices();
+
+    let settings = get_settings().unwrap_or_default();
+    voices.extend(settings.custom_voices.iter().map(custom_voice_info));
+    voices.extend(system_voices());
+
+    if let Some(language) = language.as_deref().map(str::trim).filter(|l| !l.is_empty()) {
+        voices.retain(|voice| language_matches(&voice.language, language));
+    }
+    voices
+}
+
+/// Whether a voice's language tag is `filter`, or a variant of it when
+/// `filter` has no region
+fn language_matches(tag: &str, filter: &str) -> bool {
+    if tag.eq_ignore_ascii_case(filter) {
+        return true;
+    }
+    !filter.contains('-')
+        && tag
+            .split_once('-')
+            .is_some_and(|(primary, _)| primary.eq_ignore_ascii_case(filter))
+}
+
+/// Voice ids in the downloaded Kokoro voice pack; empty before it is downloaded
+fn installed_kokoro_voices() -> Vec<String> {
+    let path = get_models_dir().join(VOICES_FILE);
+    if !path.exists() {
+        return Vec::new();
+    }
+    voice_blend::read_voice_names(&path).unwrap_or_else(|e| {
+        tracing::warn!("Failed to read Kokoro voice names: {}", e);
+        Vec::new()
+    })
+}
+
+/// Every voice in the downloaded voice pack, or the English voices it ships
+/// with until it is downloaded
+fn kokoro_voices() -> Vec<VoiceInfo> {
+    let installed = installed_kokoro_voices();
+    if installed.is_empty() {
+        builtin_voices()
+    } else {
+        kokoro_voice_infos(installed)
+    }
+}
+
+/// Describe voice pack entries, sorted by id so each language stays
+/// together with English first. Ids without a known language prefix are
+/// left out.
+fn kokoro_voice_infos(ids: Vec<String>) -> Vec<VoiceInfo> {
+    let mut voices: Vec<VoiceInfo> = ids
+        .into_iter()
+        .filter_map(|id| {
+            let language = kokoro::voice_language(&id)?;
+            Some(VoiceInfo {
+                name: kokoro::voice_display_name(&id),
+                language: language.to_string(),
+                gender: kokoro::voice_gender(&id).to_string(),
+                custom: false,
+                engine: TtsEngineKind::Kokoro,
+                id,
+            })
+        })
+        .collect();
+    voices.sort_by(|a, b| a.id.cmp(&b.id));
+    voices
+}
+
+/// English voices installed on the system; empty where `say` isn't available
+fn system_voices() -> Vec<VoiceInfo> {
+    let voices = match SystemTtsEngine::list_voices() {
+        Ok(voices) => voices,
+        Err(e) => {
+            tracing::debug!("System voices unavailable: {}", e);
+            return Vec::new();
+        }
+    };
+
+    voices
+        .into_iter()
+        .filter(|voice| voice.locale.starts_with("en"))
+        .map(|voice| VoiceInfo {
+            id: voice.id(),
+            language: voice.language(),
+            name: voice.name,
+            // `say` doesn't report gender
+            gender: "Unknown".to_string(),
+            custom: false,
+            engine: TtsEngineKind::System,
+        })
+        .collect()
+}
+
+/// English Kokoro voices, listed before the voice pack is downloaded
+fn builtin_voices() -> Vec<VoiceInfo> {
     // Kokoro-82M voices - subset of the 54 available
     vec![
         VoiceInfo {
@@ -123,48 +549,1281 @@ pub fn get_voices() -> Vec<VoiceInfo> {
             name: "Heart".to_string(),
             language: "en-US".to_string(),
             gender: "Female".to_string(),
+            custom: false,
+            engine: TtsEngineKind::Kokoro,
         },
         VoiceInfo {
             id: "af_bella".to_string(),
             name: "Bella".to_string(),
             language: "en-US".to_string(),
             gender: "Female".to_string(),
+            custom: false,
+            engine: TtsEngineKind::Kokoro,
         },
         VoiceInfo {
             id: "af_nicole".to_string(),
             name: "Nicole".to_string(),
             language: "en-US".to_string(),
             gender: "Female".to_string(),
+            custom: false,
+            engine: TtsEngineKind::Kokoro,
         },
         VoiceInfo {
             id: "af_sky".to_string(),
             name: "Sky".to_string(),
             language: "en-US".to_string(),
             gender: "Female".to_string(),
+            custom: false,
+            engine: TtsEngineKind::Kokoro,
         },
         VoiceInfo {
             id: "am_adam".to_string(),
             name: "Adam".to_string(),
             language: "en-US".to_string(),
             gender: "Male".to_string(),
+            custom: false,
+            engine: TtsEngineKind::Kokoro,
         },
         VoiceInfo {
             id: "am_michael".to_string(),
             name: "Michael".to_string(),
             language: "en-US".to_string(),
             gender: "Male".to_string(),
+            custom: false,
+            engine: TtsEngineKind::Kokoro,
         },
         VoiceInfo {
             id: "bf_emma".to_string(),
             name: "Emma".to_string(),
             language: "en-GB".to_string(),
             gender: "Female".to_string(),
+            custom: false,
+            engine: TtsEngineKind::Kokoro,
         },
         VoiceInfo {
             id: "bm_george".to_string(),
             name: "George".to_string(),
             language: "en-GB".to_string(),
             gender: "Male".to_string(),
+            custom: false,
+            engine: TtsEngineKind::Kokoro,
         },
     ]
 }
+
+/// The most heavily weighted voice in a blend
+fn dominant_voice(blend: &VoiceBlend) -> Option<&str> {
+    blend
+        .components()
+        .iter()
+        .max_by(|a, b| a.weight.total_cmp(&b.weight))
+        .map(|c| c.voice.as_str())
+}
+
+/// Describe a saved blend using its dominant voice's language
+fn custom_voice_info(voice: &CustomVoice) -> VoiceInfo {
+    let blend = VoiceBlend::parse(&voice.blend).ok();
+    let components = blend
+        .as_ref()
+        .map(|blend| blend.components().to_vec())
+        .unwrap_or_default();
+
+    let language = blend
+        .as_ref()
+        .and_then(dominant_voice)
+        .and_then(kokoro::voice_language)
+        .unwrap_or("en-US");
+
+    let genders: Vec<Option<char>> = components.iter().map(|c| c.voice.chars().nth(1)).collect();
+    let gender = match genders.first() {
+        Some(Some('f')) if genders.iter().all(|g| *g == Some('f')) => "Female",
+        Some(Some('m')) if genders.iter().all(|g| *g == Some('m')) => "Male",
+        _ => "Mixed",
+    };
+
+    VoiceInfo {
+        id: voice.id.clone(),
+        name: voice.name.clone(),
+        language: language.to_string(),
+        gender: gender.to_string(),
+        custom: true,
+        engine: TtsEngineKind::Kokoro,
+    }
+}
+
+/// Speech ready for the engine: normalized text, resolved voice and speed
+struct PreparedSpeech {
+    speech: String,
+    voice: String,
+    speed: f32,
+}
+
+/// Synthesize `text`, or reuse the audio when the same speech was
+/// synthesized recently. Returns `None` when nothing is left to speak.
+async fn synthesize_cached(
+    engines: &EngineRegistry,
+    text: &str,
+    voice_id: &str,
+    speed: f32,
+    speed_factor: f32,
+) -> Result<Option<Arc<CachedSpeech>>, BlahError> {
+    let settings = get_settings().unwrap_or_default();
+    let Some(mut prepared) = prepare_speech(text, voice_id, speed, &settings)? else {
+        return Ok(None);
+    };
+    prepared.speed = (prepared.speed * speed_factor).clamp(MIN_SPEED, MAX_SPEED);
+
+    let key = SpeechKey::new(&prepared.speech, &prepared.voice, prepared.speed);
+    if let Some(cached) = lock_speech_cache()?.get(&key) {
+        tracing::debug!("Using cached speech for voice {}", voice_id);
+        return Ok(Some(cached));
+    }
+
+    // Initialize the voice's engine if not already done
+    let engine = get_or_init_tts_engine(engines, TtsEngineKind::for_voice(voice_id)).await?;
+    let Some(audio) = synthesize_chunked(engine, prepared, voice_id, SYNTHESIS_CHUNK_CHARS).await?
+    else {
+        tracing::info!("Synthesis stopped before it finished");
+        return Ok(None);
+    };
+    engines.touch_tts();
+
+    let speech = CachedSpeech {
+        text: text.to_string(),
+        voice_id: voice_id.to_string(),
+        audio,
+    };
+    let mut cache = lock_speech_cache()?;
+    let speech = cache.insert(key, speech);
+    tracing::debug!("Speech cache holds {} KB", cache.used_bytes() / 1024);
+    Ok(Some(speech))
+}
+
+/// Normalize text for the synthesizer, `None` if nothing speakable is left.
+/// Only the synthesized copy is normalized; callers keep the original for display.
+fn prepare_speech(
+    text: &str,
+    voice_id: &str,
+    speed: f32,
+    settings: &AppSettings,
+) -> Result<Option<PreparedSpeech>, BlahError> {
+    let voice = resolve_voice_id(voice_id, &settings.custom_voices)?;
+    let speed = resolve_speed(
+        voice_id,
+        speed,
+        &settings.voice_speed_overrides,
+        &settings.custom_voices,
+    );
+
+    let speech = normalize_for_speech(text, &settings.tts_normalization);
+    if speech.trim().is_empty() {
+        tracing::debug!("Nothing to speak after normalization");
+        return Ok(None);
+    }
+
+    // Allowed, since it may be on purpose, but worth a note in the log
+    let language = base_voice(voice_id, &settings.custom_voices)
+        .and_then(|voice| kokoro::voice_language(&voice))
+        .filter(|language| !language.starts_with("en"));
+    if let Some(language) = language {
+        if looks_english(&speech) {
+            tracing::warn!(
+                "Reading English text with {} voice {}; pronunciation will be off",
+                language,
+                voice_id
+            );
+        }
+    }
+
+    Ok(Some(PreparedSpeech {
+        speech,
+        voice,
+        speed,
+    }))
+}
+
+/// Synthesize `prepared` on the blocking thread pool, `max_chunk_chars` at
+/// a time. The engine is locked only while it works on a chunk, and a
+/// `stop_speaking` in between ends synthesis early with `None`.
+async fn synthesize_chunked(
+    engine: SharedTtsEngine,
+    prepared: PreparedSpeech,
+    voice_id: &str,
+    max_chunk_chars: usize,
+) -> Result<Option<AudioBuffer>, BlahError> {
+    let generation = QUEUE_GENERATION.load(Ordering::SeqCst);
+    let voice_id = voice_id.to_string();
+    let runtime = tokio::runtime::Handle::current();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let chunks = speech_chunks(&prepared.speech, max_chunk_chars);
+        let chars_total = prepared.speech.chars().count();
+        let mut chars_done = 0;
+        let mut samples = Vec::new();
+        let mut sample_rate = 0;
+
+        let result = chunks.iter().try_for_each(|chunk| {
+            if QUEUE_GENERATION.load(Ordering::SeqCst) != generation {
+                return Err(None);
+            }
+            if chunks.len() > 1 {
+                set_synthesis_progress(Some((chars_done, chars_total)));
+            }
+            let audio = {
+                let mut engine = engine.blocking_lock();
+                // Cancelled while another synthesis had the engine
+                if QUEUE_GENERATION.load(Ordering::SeqCst) != generation {
+                    return Err(None);
+                }
+                runtime.block_on(synthesize_prepared(
+                    engine.as_mut(),
+                    chunk,
+                    &prepared,
+                    &voice_id,
+                ))
+            };
+            let audio = audio.map_err(Some)?;
+            samples.extend_from_slice(audio.samples());
+            sample_rate = audio.sample_rate;
+            chars_done += chunk.chars().count();
+            Ok(())
+        });
+        set_synthesis_progress(None);
+
+        match result {
+            Ok(()) => Ok(Some(AudioBuffer::new(samples, sample_rate))),
+            Err(None) => Ok(None),
+            Err(Some(e)) => Err(e),
+        }
+    })
+    .await
+    .map_err(|e| BlahError::Internal(format!("Synthesis task failed: {}", e)))?
+}
+
+fn set_synthesis_progress(progress: Option<(usize, usize)>) {
+    if let Ok(mut current) = SYNTHESIS_PROGRESS.lock() {
+        *current = progress;
+    }
+}
+
+/// `text` split between sentences into chunks of at most `max_chars`,
+/// except that a single longer sentence is kept whole
+fn speech_chunks(text: &str, max_chars: usize) -> Vec<&str> {
+    let mut sentence_ends: Vec<usize> = text
+        .char_indices()
+        .map(|(i, c)| (c, i + c.len_utf8()))
+        .filter(|&(c, end)| {
+            let next = text[end..].chars().next();
+            matches!(c, '.' | '!' | '?' | '\n') && next.is_none_or(char::is_whitespace)
+        })
+        .map(|(_, end)| end)
+        .collect();
+    sentence_ends.push(text.len());
+
+    let mut chunks = Vec::new();
+    let (mut start, mut end) = (0, 0);
+    for sentence_end in sentence_ends {
+        if end > start && text[start..sentence_end].chars().count() > max_chars {
+            chunks.push(&text[start..end]);
+            start = end;
+        }
+        end = sentence_end;
+    }
+    chunks.push(&text[start..end]);
+    chunks
+        .into_iter()
+        .map(str::trim)
+        .filter(|chunk| !chunk.is_empty())
+        .collect()
+}
+
+async fn synthesize_prepared(
+    engine: &mut dyn TextToSpeech,
+    speech: &str,
+    prepared: &PreparedSpeech,
+    voice_id: &str,
+) -> Result<AudioBuffer, BlahError> {
+    let PreparedSpeech { voice, speed, .. } = prepared;
+    tracing::debug!("Synthesizing with voice {} at {}x", voice, speed);
+    engine.synthesize(speech, voice, *speed).await.map_err(|e| {
+        BlahError::SynthesisFailed(format!(
+            "Speech synthesis failed for voice '{}': {}",
+            voice_id, e
+        ))
+    })
+}
+
+/// Common English words; text made up largely of these is English
+const COMMON_ENGLISH_WORDS: &[&str] = &[
+    "a", "and", "are", "be", "for", "have", "i", "in", "is", "it", "not", "of", "on", "that",
+    "the", "this", "to", "was", "with", "you",
+];
+
+/// Rough check that `text` is English, for the wrong-language voice warning
+fn looks_english(text: &str) -> bool {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphabetic() && c != '\'')
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    let common = words
+        .iter()
+        .filter(|word| COMMON_ENGLISH_WORDS.contains(&word.as_str()))
+        .count();
+    common > 0 && common * 8 >= words.len()
+}
+
+/// Words per minute of speech at 1.0x
+const AVERAGE_WPM: f32 = 150.0;
+
+/// How long a text will take to read aloud
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct EstimatedDuration {
+    pub estimated_secs: f32,
+    pub word_count: usize,
+    pub char_count: usize,
+}
+
+/// Predict how long `text` takes to speak at `speed`, without synthesizing
+/// it. Counted after normalization, so numbers and abbreviations count as
+/// the words they are read as.
+#[tauri::command]
+pub fn estimate_reading_time(text: String, speed: f32) -> Result<EstimatedDuration, BlahError> {
+    if !(speed.is_finite() && speed > 0.0) {
+        return Err(BlahError::InvalidInput(format!(
+            "Speed must be a positive number, got {}",
+            speed
+        )));
+    }
+
+    let settings = get_settings().unwrap_or_default();
+    let speech = normalize_for_speech(&text, &settings.tts_normalization);
+    // The engine can't speak faster or slower than this either
+    let speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+    Ok(estimate_duration(&speech, speed))
+}
+
+fn estimate_duration(speech: &str, speed: f32) -> EstimatedDuration {
+    let word_count = speech.split_whitespace().count();
+    EstimatedDuration {
+        estimated_secs: word_count as f32 / (AVERAGE_WPM * speed) * 60.0,
+        word_count,
+        char_count: speech.chars().count(),
+    }
+}
+
+/// Speed to use for `voice_id`, applying the overrides from settings on top
+/// of `default_speed` (normally `tts_speed`).
+pub(crate) fn effective_speed(voice_id: &str, default_speed: f32) -> f32 {
+    let settings = get_settings().unwrap_or_default();
+    resolve_speed(
+        voice_id,
+        default_speed,
+        &settings.voice_speed_overrides,
+        &settings.custom_voices,
+    )
+}
+
+/// Override for the voice itself, else for its language prefix, else
+/// `default_speed`, clamped to the engine's range. Blends and saved blends
+/// take the language of their dominant voice.
+fn resolve_speed(
+    voice_id: &str,
+    default_speed: f32,
+    overrides: &HashMap<String, f32>,
+    custom_voices: &[CustomVoice],
+) -> f32 {
+    let speed = overrides
+        .get(voice_id)
+        .or_else(|| {
+            // Language prefixes are Kokoro's naming scheme
+            if TtsEngineKind::for_voice(voice_id) != TtsEngineKind::Kokoro {
+                return None;
+            }
+            let language = language_prefix(&base_voice(voice_id, custom_voices)?)?;
+            overrides.get(&language)
+        })
+        .copied()
+        .unwrap_or(default_speed);
+
+    speed.clamp(MIN_SPEED, MAX_SPEED)
+}
+
+/// The single voice whose language `voice_id` speaks: the voice itself, or
+/// the dominant voice of a blend or saved custom voice
+fn base_voice(voice_id: &str, custom_voices: &[CustomVoice]) -> Option<String> {
+    let spec = if voice_id.starts_with(CUSTOM_VOICE_PREFIX) {
+        custom_voices.iter().find(|v| v.id == voice_id)?.blend.as_str()
+    } else {
+        voice_id
+    };
+
+    if voice_blend::is_blend(spec) {
+        let blend = VoiceBlend::parse(spec).ok()?;
+        dominant_voice(&blend).map(str::to_string)
+    } else {
+        Some(spec.to_string())
+    }
+}
+
+/// Kokoro voice ids start with a language letter: `a` American, `b` British...
+fn language_prefix(voice: &str) -> Option<String> {
+    voice
+        .chars()
+        .next()
+        .filter(char::is_ascii_lowercase)
+        .map(String::from)
+}
+
+/// Set the default speed for a voice id or one-letter language prefix, or
+/// clear it with `None`.
+#[tauri::command]
+pub fn set_voice_speed_override(voice_id: String, speed: Option<f32>) -> Result<(), BlahError> {
+    let voice_id = voice_id.trim().to_string();
+    if voice_id.is_empty() {
+        return Err(BlahError::InvalidInput("Voice id is empty".to_string()));
+    }
+
+    modify_settings(|settings| {
+        match speed {
+            Some(speed) if !speed.is_finite() => {
+                return Err(BlahError::InvalidInput(format!("Invalid speed: {}", speed)));
+            }
+            Some(speed) => {
+                let speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+                tracing::info!("Speed override for {}: {}x", voice_id, speed);
+                settings.voice_speed_overrides.insert(voice_id, speed);
+            }
+            None => {
+                tracing::info!("Cleared speed override for {}", voice_id);
+                settings.voice_speed_overrides.remove(&voice_id);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Map a saved custom voice id to its blend; other voice ids pass through
+fn resolve_voice_id(voice_id: &str, custom_voices: &[CustomVoice]) -> Result<String, BlahError> {
+    if !voice_id.starts_with(CUSTOM_VOICE_PREFIX) {
+        return Ok(voice_id.to_string());
+    }
+
+    custom_voices
+        .iter()
+        .find(|v| v.id == voice_id)
+        .map(|v| v.blend.clone())
+        .ok_or_else(|| BlahError::InvalidInput(format!("Custom voice not found: {}", voice_id)))
+}
+
+/// Parse a blend and check its voices exist in the downloaded voice pack
+fn validate_blend(blend: &str) -> Result<(), BlahError> {
+    let blend = VoiceBlend::parse(blend).map_err(|e| BlahError::InvalidInput(e.to_string()))?;
+
+    let voices_path = get_models_dir().join(VOICES_FILE);
+    if !voices_path.exists() {
+        return Err(BlahError::ModelNotDownloaded(format!(
+            "Voice file not found: {}",
+            voices_path.display()
+        )));
+    }
+    let available = voice_blend::read_voice_names(&voices_path)
+        .map_err(|e| BlahError::IoError(format!("Failed to read voice file: {}", e)))?;
+    blend
+        .validate(&available)
+        .map_err(|e| BlahError::InvalidInput(e.to_string()))
+}
+
+/// Id for a new custom voice, derived from its name and unique among `existing`
+fn custom_voice_id(name: &str, existing: &[CustomVoice]) -> String {
+    let slug = name
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+    let base = format!(
+        "{}{}",
+        CUSTOM_VOICE_PREFIX,
+        if slug.is_empty() { "voice" } else { slug.as_str() }
+    );
+
+    let mut id = base.clone();
+    let mut n = 2;
+    while existing.iter().any(|v| v.id == id) {
+        id = format!("{}_{}", base, n);
+        n += 1;
+    }
+    id
+}
+
+#[tauri::command]
+pub fn list_custom_voices() -> Result<Vec<CustomVoice>, BlahError> {
+    Ok(get_settings()?.custom_voices)
+}
+
+/// Create a custom voice, or update it when `id` names an existing one
+#[tauri::command]
+pub fn save_custom_voice(
+    id: Option<String>,
+    name: String,
+    blend: String,
+) -> Result<CustomVoice, BlahError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(BlahError::InvalidInput("Custom voice name is empty".to_string()));
+    }
+    validate_blend(&blend)?;
+
+    let voice = modify_settings(|settings| {
+        let voice = match id {
+            Some(id) => {
+                let voice = settings
+                    .custom_voices
+                    .iter_mut()
+                    .find(|v| v.id == id)
+                    .ok_or_else(|| {
+                        BlahError::InvalidInput(format!("Custom voice not found: {}", id))
+                    })?;
+                voice.name = name;
+                voice.blend = blend;
+                voice.clone()
+            }
+            None => {
+                let voice = CustomVoice {
+                    id: custom_voice_id(&name, &settings.custom_voices),
+                    name,
+                    blend,
+                };
+                settings.custom_voices.push(voice.clone());
+                voice
+            }
+        };
+        Ok(voice)
+    })?;
+    tracing::info!("Saved custom voice {}: {}", voice.id, voice.blend);
+
+    Ok(voice)
+}
+
+#[tauri::command]
+pub fn delete_custom_voice(id: String) -> Result<(), BlahError> {
+    modify_settings(|settings| {
+        let count = settings.custom_voices.len();
+        settings.custom_voices.retain(|v| v.id != id);
+        if settings.custom_voices.len() == count {
+            return Err(BlahError::InvalidInput(format!(
+                "Custom voice not found: {}",
+                id
+            )));
+        }
+
+        // Don't leave the default voice pointing at a blend that's gone
+        if settings.tts_voice == id {
+            settings.tts_voice = kokoro::fallback_voice(&installed_kokoro_voices());
+        }
+        Ok(())
+    })?;
+    tracing::info!("Deleted custom voice {}", id);
+
+    if let Err(e) = remove_previews(&previews_dir(), &id) {
+        tracing::warn!("Failed to remove preview for {}: {}", id, e);
+    }
+
+    Ok(())
+}
+
+/// Sentence spoken by `preview_voice`
+const PREVIEW_TEXT: &str = "Hello! This is how I sound reading your text aloud.";
+
+/// A cached voice preview
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TtsCacheEntry {
+    pub voice_id: String,
+    /// Hash of the preview text and the voice it resolves to, so a preview
+    /// is made again when either changes
+    pub preview_text_hash: String,
+    pub file_size_bytes: u64,
+    /// UTC, e.g. `2024-05-01T09:30:00Z`
+    pub created_at: String,
+}
+
+/// Where voice previews are cached, one WAV file per voice
+fn previews_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("com.blahcubed.app")
+        .join("previews")
+}
+
+fn preview_hash(voice: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    PREVIEW_TEXT.hash(&mut hasher);
+    voice.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// `<voice id>-<hash>.wav`; voice ids that can't be file names aren't cached
+fn preview_path(dir: &Path, voice_id: &str, hash: &str) -> Option<PathBuf> {
+    let usable =
+        !voice_id.is_empty() && !voice_id.starts_with('.') && !voice_id.contains(['/', '\\']);
+    usable.then(|| dir.join(format!("{}-{}.wav", voice_id, hash)))
+}
+
+/// Voice id and hash of a preview file name, `None` for anything else
+fn parse_preview_name(name: &str) -> Option<(&str, &str)> {
+    let (voice_id, hash) = name.strip_suffix(".wav")?.rsplit_once('-')?;
+    let is_hash = hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit());
+    (is_hash && !voice_id.is_empty()).then_some((voice_id, hash))
+}
+
+/// Speak a short sample sentence in `voice_id` at normal speed. The audio is
+/// cached, so previewing a voice again doesn't load the engine.
+#[tauri::command]
+pub async fn preview_voice(app: tauri::AppHandle, voice_id: String) -> Result<(), BlahError> {
+    let settings = get_settings().unwrap_or_default();
+    let voice = resolve_voice_id(&voice_id, &settings.custom_voices)?;
+    let path = preview_path(&previews_dir(), &voice_id, &preview_hash(&voice));
+
+    let cached = path
+        .as_deref()
+        .filter(|path| path.exists())
+        .and_then(|path| read_wav(path).ok());
+    let (samples, sample_rate) = match cached {
+        Some(audio) => audio,
+        None => {
+            let engines = app.state::<Arc<EngineRegistry>>();
+            let kind = TtsEngineKind::for_voice(&voice_id);
+            let engine = get_or_init_tts_engine(&engines, kind).await?;
+            let prepared = PreparedSpeech {
+                speech: PREVIEW_TEXT.to_string(),
+                voice,
+                speed: 1.0,
+            };
+            let audio = synthesize_chunked(engine, prepared, &voice_id, SYNTHESIS_CHUNK_CHARS);
+            let Some(audio) = audio.await? else {
+                return Ok(());
+            };
+            engines.touch_tts();
+
+            if let Some(path) = &path {
+                // A failed write only costs synthesizing again next time
+                let written = std::fs::create_dir_all(previews_dir())
+                    .map_err(anyhow::Error::from)
+                    .and_then(|()| write_wav(path, audio.samples(), audio.sample_rate));
+                if let Err(e) = written {
+                    tracing::warn!("Failed to cache preview for {}: {}", voice_id, e);
+                }
+            }
+            (audio.samples().to_vec(), audio.sample_rate)
+        }
+    };
+
+    stop_playback()?;
+    let player = AudioPlayer::new().map_err(|e| {
+        BlahError::AudioPlaybackFailed(format!("Failed to initialize audio player: {}", e))
+    })?;
+    player
+        .play(&samples, sample_rate)
+        .map_err(|e| BlahError::AudioPlaybackFailed(format!("Failed to play audio: {}", e)))?;
+    let mut guard = get_player_state().lock().map_err(|e| {
+        BlahError::Internal(format!(
+            "Internal error: audio player state lock poisoned: {}",
+            e
+        ))
+    })?;
+    *guard = Some(player);
+
+    Ok(())
+}
+
+/// Cached voice previews, by voice id
+#[tauri::command]
+pub fn list_tts_cache_entries() -> Vec<TtsCacheEntry> {
+    cache_entries(&previews_dir())
+}
+
+/// Delete every cached preview, returning the bytes freed
+#[tauri::command]
+pub fn clear_tts_cache() -> Result<u64, BlahError> {
+    let freed = clear_previews(&previews_dir())
+        .map_err(|e| BlahError::IoError(format!("Failed to clear voice previews: {}", e)))?;
+    tracing::info!("Cleared voice previews ({} bytes)", freed);
+    Ok(freed)
+}
+
+/// Delete the cached preview of `voice_id`, if there is one
+#[tauri::command]
+pub fn clear_tts_cache_entry(voice_id: String) -> Result<(), BlahError> {
+    remove_previews(&previews_dir(), &voice_id).map_err(|e| {
+        BlahError::IoError(format!("Failed to delete preview for {}: {}", voice_id, e))
+    })
+}
+
+fn cache_entries(dir: &Path) -> Vec<TtsCacheEntry> {
+    let Ok(files) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut entries: Vec<TtsCacheEntry> = files
+        .flatten()
+        .filter_map(|file| {
+            let name = file.file_name().to_string_lossy().to_string();
+            let (voice_id, hash) = parse_preview_name(&name)?;
+            let metadata = file.metadata().ok().filter(|m| m.is_file())?;
+            Some(TtsCacheEntry {
+                voice_id: voice_id.to_string(),
+                preview_text_hash: hash.to_string(),
+                file_size_bytes: metadata.len(),
+                created_at: metadata
+                    .created()
+                    .or_else(|_| metadata.modified())
+                    .map(format_utc)
+                    .unwrap_or_default(),
+            })
+        })
+        .collect();
+    entries.sort_by(|a, b| a.voice_id.cmp(&b.voice_id));
+    entries
+}
+
+/// Delete every file in `dir`, returning the bytes freed. A missing
+/// directory frees nothing.
+fn clear_previews(dir: &Path) -> std::io::Result<u64> {
+    let files = match std::fs::read_dir(dir) {
+        Ok(files) => files,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+
+    let mut freed = 0;
+    for file in files.flatten() {
+        let metadata = file.metadata()?;
+        if metadata.is_file() {
+            std::fs::remove_file(file.path())?;
+            freed += metadata.len();
+        }
+    }
+    Ok(freed)
+}
+
+/// Delete the previews of `voice_id`; an older preview of a since-edited
+/// blend may sit next to the current one
+fn remove_previews(dir: &Path, voice_id: &str) -> std::io::Result<()> {
+    for entry in cache_entries(dir) {
+        if entry.voice_id == voice_id {
+            if let Some(path) = preview_path(dir, voice_id, &entry.preview_text_hash) {
+                std::fs::remove_file(path)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `time` as a UTC `YYYY-MM-DDTHH:MM:SSZ` timestamp
+pub(crate) fn format_utc(time: std::time::SystemTime) -> String {
+    let secs = time
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    // Civil-from-days conversion (Howard Hinnant's algorithm), as in build.rs
+    let days = (secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    let time_of_day = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time_of_day / 3600,
+        time_of_day / 60 % 60,
+        time_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+    use tokio::sync::Mutex as TokioMutex;
+
+    fn custom_voice(id: &str, blend: &str) -> CustomVoice {
+        CustomVoice {
+            id: id.to_string(),
+            name: "Test".to_string(),
+            blend: blend.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_clipboard_speech_text() {
+        assert_eq!(
+            clipboard_speech_text(Some("  Read this.\n".to_string())).unwrap(),
+            "Read this."
+        );
+        assert!(clipboard_speech_text(None).is_err());
+        assert!(clipboard_speech_text(Some(" \n\t".to_string())).is_err());
+
+        let longest = "é".repeat(MAX_CLIPBOARD_SPEECH_CHARS);
+        assert!(clipboard_speech_text(Some(longest.clone())).is_ok());
+        let err = clipboard_speech_text(Some(longest + "!")).unwrap_err();
+        assert!(matches!(err, BlahError::InvalidInput(_)));
+    }
+
+    #[test]
+    fn test_custom_voice_id_from_name() {
+        assert_eq!(custom_voice_id("Bella & Sky", &[]), "custom_bella_sky");
+        assert_eq!(custom_voice_id("  ", &[]), "custom_voice");
+    }
+
+    #[test]
+    fn test_custom_voice_id_is_unique() {
+        let existing = vec![
+            custom_voice("custom_bella_sky", "af_bella+af_sky"),
+            custom_voice("custom_bella_sky_2", "af_bella+af_sky"),
+        ];
+        assert_eq!(custom_voice_id("Bella Sky", &existing), "custom_bella_sky_3");
+    }
+
+    #[test]
+    fn test_custom_voice_info() {
+        let info = custom_voice_info(&custom_voice("custom_a", "af_bella:0.3+bf_emma:0.7"));
+        assert!(info.custom);
+        assert_eq!(info.language, "en-GB");
+        assert_eq!(info.gender, "Female");
+
+        let info = custom_voice_info(&custom_voice("custom_b", "af_bella+am_adam"));
+        assert_eq!(info.gender, "Mixed");
+    }
+
+    #[test]
+    fn test_builtin_voices_pass_through() {
+        assert_eq!(resolve_voice_id("af_heart", &[]).unwrap(), "af_heart");
+        assert_eq!(
+            resolve_voice_id("af_bella:0.5+af_sky:0.5", &[]).unwrap(),
+            "af_bella:0.5+af_sky:0.5"
+        );
+    }
+
+    fn speed_map(entries: &[(&str, f32)]) -> HashMap<String, f32> {
+        entries.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+    }
+
+    #[test]
+    fn test_resolve_speed_precedence() {
+        let overrides = speed_map(&[("bf_emma", 0.8), ("b", 1.2)]);
+        assert_eq!(resolve_speed("bf_emma", 1.0, &overrides, &[]), 0.8);
+        assert_eq!(resolve_speed("bm_george", 1.0, &overrides, &[]), 1.2);
+        assert_eq!(resolve_speed("af_heart", 1.1, &overrides, &[]), 1.1);
+        assert_eq!(resolve_speed("af_heart", 1.1, &HashMap::new(), &[]), 1.1);
+    }
+
+    #[test]
+    fn test_resolve_speed_blend_uses_dominant_language() {
+        let overrides = speed_map(&[("b", 1.3), ("a", 0.9)]);
+        assert_eq!(
+            resolve_speed("af_bella:0.3+bf_emma:0.7", 1.0, &overrides, &[]),
+            1.3
+        );
+
+        let exact = speed_map(&[("af_bella:0.3+bf_emma:0.7", 2.0), ("b", 1.3)]);
+        assert_eq!(
+            resolve_speed("af_bella:0.3+bf_emma:0.7", 1.0, &exact, &[]),
+            2.0
+        );
+
+        let custom = [custom_voice("custom_mix", "af_bella:0.6+bf_emma:0.4")];
+        assert_eq!(resolve_speed("custom_mix", 1.0, &overrides, &custom), 0.9);
+        assert_eq!(resolve_speed("custom_gone", 1.0, &overrides, &custom), 1.0);
+    }
+
+    #[test]
+    fn test_resolve_speed_system_voice() {
+        let overrides = speed_map(&[("s", 2.0), ("system:Samantha", 1.4)]);
+        assert_eq!(resolve_speed("system:Samantha", 1.0, &overrides, &[]), 1.4);
+        // "s" is not a language prefix for system voices
+        assert_eq!(resolve_speed("system:Daniel", 1.0, &overrides, &[]), 1.0);
+    }
+
+    #[test]
+    fn test_resolve_speed_clamps_to_engine_range() {
+        let overrides = speed_map(&[("af_heart", 10.0)]);
+        assert_eq!(resolve_speed("af_heart", 1.0, &overrides, &[]), MAX_SPEED);
+        assert_eq!(resolve_speed("am_adam", 0.0, &overrides, &[]), MIN_SPEED);
+    }
+
+    /// Prepare and synthesize `text` the way `synthesize_cached` does,
+    /// without the cache
+    async fn synthesize_speech(
+        engine: &mut dyn TextToSpeech,
+        text: &str,
+        voice_id: &str,
+        speed: f32,
+        settings: &AppSettings,
+    ) -> Result<Option<AudioBuffer>, BlahError> {
+        match prepare_speech(text, voice_id, speed, settings)? {
+            Some(prepared) => synthesize_prepared(engine, &prepared.speech, &prepared, voice_id)
+                .await
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Records what it was asked to say instead of running a model
+    #[derive(Default)]
+    struct MockTts {
+        calls: Vec<(String, String, f32)>,
+        fail: bool,
+    }
+
+    impl TextToSpeech for MockTts {
+        fn synthesize<'a>(
+            &'a mut self,
+            text: &'a str,
+            voice: &'a str,
+            speed: f32,
+        ) -> futures_util::future::BoxFuture<'a, anyhow::Result<AudioBuffer>> {
+            self.calls.push((text.to_string(), voice.to_string(), speed));
+            let result = if self.fail {
+                Err(anyhow::anyhow!("model exploded"))
+            } else {
+                Ok(AudioBuffer::new(vec![0.0; 2400], 24000))
+            };
+            Box::pin(async move { result })
+        }
+
+        fn available_voices(&self) -> Vec<crate::engines::VoiceInfo> {
+            Vec::new()
+        }
+
+        fn model_info(&self) -> crate::engines::ModelInfo {
+            crate::engines::ModelInfo {
+                name: "Mock".to_string(),
+                size_bytes: 0,
+                loaded: true,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_synthesize_speech_passes_request_to_engine() {
+        let mut engine = MockTts::default();
+        let settings = AppSettings::default();
+
+        let audio = synthesize_speech(&mut engine, "Hello there", "af_heart", 1.5, &settings)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(audio.sample_rate, 24000);
+        assert_eq!(
+            engine.calls,
+            vec![("Hello there".to_string(), "af_heart".to_string(), 1.5)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_synthesize_speech_resolves_custom_voice_and_speed() {
+        let mut engine = MockTts::default();
+        let settings = AppSettings {
+            custom_voices: vec![custom_voice("custom_mix", "af_bella:0.3+bf_emma:0.7")],
+            voice_speed_overrides: speed_map(&[("b", 1.25)]),
+            ..Default::default()
+        };
+
+        synthesize_speech(&mut engine, "Hi", "custom_mix", 1.0, &settings)
+            .await
+            .unwrap();
+
+        assert_eq!(engine.calls[0].1, "af_bella:0.3+bf_emma:0.7");
+        assert_eq!(engine.calls[0].2, 1.25);
+    }
+
+    #[tokio::test]
+    async fn test_synthesize_speech_skips_empty_text() {
+        let mut engine = MockTts::default();
+        let settings = AppSettings::default();
+
+        let audio = synthesize_speech(&mut engine, "   ", "af_heart", 1.0, &settings)
+            .await
+            .unwrap();
+
+        assert!(audio.is_none());
+        assert!(engine.calls.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_synthesize_speech_errors() {
+        let settings = AppSettings::default();
+
+        let mut engine = MockTts::default();
+        let err = synthesize_speech(&mut engine, "Hi", "custom_missing", 1.0, &settings)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BlahError::InvalidInput(_)));
+        assert!(engine.calls.is_empty());
+
+        let mut engine = MockTts {
+            fail: true,
+            ..Default::default()
+        };
+        let err = synthesize_speech(&mut engine, "Hi", "af_heart", 1.0, &settings)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BlahError::SynthesisFailed(_)));
+    }
+
+    #[test]
+    fn test_speech_chunks() {
+        let text = "One two. Three four! Five six? Seven.";
+        assert_eq!(speech_chunks(text, 100), vec![text]);
+        assert_eq!(
+            speech_chunks(text, 20),
+            vec!["One two. Three four!", "Five six? Seven."]
+        );
+        // A sentence longer than a chunk is kept whole
+        assert_eq!(
+            speech_chunks("A very long sentence here. Short.", 10),
+            vec!["A very long sentence here.", "Short."]
+        );
+        // Dots inside numbers and names don't end a sentence
+        assert_eq!(
+            speech_chunks("Version 1.5 of example.com is out.", 10),
+            vec!["Version 1.5 of example.com is out."]
+        );
+        assert!(speech_chunks("  ", 10).is_empty());
+    }
+
+    /// Takes a while per chunk, blocking the thread like a real model
+    struct SlowTts {
+        calls: Arc<AtomicU32>,
+    }
+
+    impl TextToSpeech for SlowTts {
+        fn synthesize<'a>(
+            &'a mut self,
+            _text: &'a str,
+            _voice: &'a str,
+            _speed: f32,
+        ) -> futures_util::future::BoxFuture<'a, anyhow::Result<AudioBuffer>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(50));
+            Box::pin(async { Ok(AudioBuffer::new(vec![0.0; 240], 24000)) })
+        }
+
+        fn available_voices(&self) -> Vec<crate::engines::VoiceInfo> {
+            Vec::new()
+        }
+
+        fn model_info(&self) -> crate::engines::ModelInfo {
+            crate::engines::ModelInfo {
+                name: "Slow".to_string(),
+                size_bytes: 0,
+                loaded: true,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stop_speaking_interrupts_synthesis() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let engine: SharedTtsEngine = Arc::new(TokioMutex::new(Box::new(SlowTts {
+            calls: calls.clone(),
+        })));
+        let prepared = PreparedSpeech {
+            speech: "This is a sentence. ".repeat(40),
+            voice: "af_heart".to_string(),
+            speed: 1.0,
+        };
+
+        let start = Instant::now();
+        let synthesis = tokio::spawn(synthesize_chunked(engine.clone(), prepared, "af_heart", 20));
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        // The engine isn't held between chunks
+        let _ = engine.lock().await;
+        stop_speaking().await.unwrap();
+
+        let audio = synthesis.await.unwrap().unwrap();
+        assert!(audio.is_none());
+        assert!(start.elapsed() < Duration::from_millis(600));
+        assert!(calls.load(Ordering::SeqCst) < 10);
+        assert!(SYNTHESIS_PROGRESS.lock().unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_synthesis_waiting_for_engine() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let engine: SharedTtsEngine = Arc::new(TokioMutex::new(Box::new(SlowTts {
+            calls: calls.clone(),
+        })));
+        let prepared = PreparedSpeech {
+            speech: "Waiting for the engine.".to_string(),
+            voice: "af_heart".to_string(),
+            speed: 1.0,
+        };
+
+        // Another synthesis has the engine until after the cancel
+        let busy = engine.lock().await;
+        let synthesis = synthesize_chunked(engine.clone(), prepared, "af_heart", 300);
+        let synthesis = tokio::spawn(synthesis);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        cancel_synthesis().await.unwrap();
+        drop(busy);
+
+        assert!(synthesis.await.unwrap().unwrap().is_none());
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_estimate_duration() {
+        let estimate = estimate_duration("one two three four five", 1.0);
+        assert_eq!(estimate.word_count, 5);
+        assert_eq!(estimate.char_count, 23);
+        assert!((estimate.estimated_secs - 2.0).abs() < 1e-4);
+
+        // Twice as fast takes half as long
+        let faster = estimate_duration("one two three four five", 2.0);
+        assert!((faster.estimated_secs - 1.0).abs() < 1e-4);
+
+        let empty = estimate_duration("  ", 1.0);
+        assert_eq!(empty.word_count, 0);
+        assert_eq!(empty.estimated_secs, 0.0);
+    }
+
+    #[test]
+    fn test_estimate_reading_time_counts_spoken_words() {
+        // "42" is read as "forty-two" after normalization
+        let speech = normalize_for_speech("Chapter 42", &Default::default());
+        assert!(estimate_duration(&speech, 1.0).char_count > "Chapter 42".len());
+
+        assert!(estimate_reading_time("Hi".to_string(), 0.0).is_err());
+        assert!(estimate_reading_time("Hi".to_string(), f32::NAN).is_err());
+    }
+
+    #[test]
+    fn test_parse_preview_name() {
+        assert_eq!(
+            parse_preview_name("af_heart-0123456789abcdef.wav"),
+            Some(("af_heart", "0123456789abcdef"))
+        );
+        // Voice ids may contain dashes and spaces
+        assert_eq!(
+            parse_preview_name("system:Eddy (en-US)-0123456789abcdef.wav"),
+            Some(("system:Eddy (en-US)", "0123456789abcdef"))
+        );
+        assert_eq!(parse_preview_name("af_heart-0123.wav"), None);
+        assert_eq!(parse_preview_name("af_heart-0123456789abcdef.tmp"), None);
+        assert_eq!(parse_preview_name("-0123456789abcdef.wav"), None);
+        assert_eq!(parse_preview_name(".DS_Store"), None);
+    }
+
+    #[test]
+    fn test_preview_path_rejects_unusable_ids() {
+        let dir = Path::new("/previews");
+        let hash = preview_hash("af_heart");
+        assert_eq!(
+            preview_path(dir, "af_heart", &hash),
+            Some(dir.join(format!("af_heart-{}.wav", hash)))
+        );
+        assert_eq!(preview_path(dir, "../escape", &hash), None);
+        assert_eq!(preview_path(dir, "", &hash), None);
+        assert_ne!(preview_hash("af_heart"), preview_hash("af_bella"));
+    }
+
+    #[test]
+    fn test_preview_cache_entries_and_clearing() {
+        let dir = tempfile::tempdir().unwrap();
+        let heart = preview_path(dir.path(), "af_heart", &preview_hash("af_heart")).unwrap();
+        let blend = preview_path(dir.path(), "custom_mix", &preview_hash("af_bella:1.0")).unwrap();
+        let old_blend =
+            preview_path(dir.path(), "custom_mix", &preview_hash("af_sky:1.0")).unwrap();
+        std::fs::write(&heart, vec![0u8; 100]).unwrap();
+        std::fs::write(&blend, vec![0u8; 40]).unwrap();
+        std::fs::write(&old_blend, vec![0u8; 30]).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "x").unwrap();
+
+        let entries = cache_entries(dir.path());
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].voice_id, "af_heart");
+        assert_eq!(entries[0].file_size_bytes, 100);
+        assert_eq!(entries[0].preview_text_hash, preview_hash("af_heart"));
+        assert!(entries[0].created_at.ends_with('Z'));
+
+        // Removing a voice removes all of its previews
+        remove_previews(dir.path(), "custom_mix").unwrap();
+        let entries = cache_entries(dir.path());
+        assert_eq!(entries.len(), 1);
+        remove_previews(dir.path(), "af_missing").unwrap();
+
+        // Clearing deletes everything in the directory
+        assert_eq!(clear_previews(dir.path()).unwrap(), 101);
+        assert!(cache_entries(dir.path()).is_empty());
+        assert_eq!(clear_previews(&dir.path().join("missing")).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_format_utc() {
+        let at = |secs| std::time::UNIX_EPOCH + Duration::from_secs(secs);
+        assert_eq!(format_utc(at(0)), "1970-01-01T00:00:00Z");
+        assert_eq!(format_utc(at(1_714_555_800)), "2024-05-01T09:30:00Z");
+        assert_eq!(format_utc(at(951_825_599)), "2000-02-29T11:59:59Z");
+    }
+
+    #[test]
+    fn test_kokoro_voice_infos_cover_every_language() {
+        let ids = [
+            "zf_xiaobei",
+            "ff_siwis",
+            "af_heart",
+            "jm_kumo",
+            "bm_george",
+            "xx_odd",
+        ];
+        let voices = kokoro_voice_infos(ids.iter().map(|id| id.to_string()).collect());
+
+        let listed: Vec<&str> = voices.iter().map(|v| v.id.as_str()).collect();
+        assert_eq!(
+            listed,
+            ["af_heart", "bm_george", "ff_siwis", "jm_kumo", "zf_xiaobei"]
+        );
+        assert_eq!(voices[2].name, "Siwis");
+        assert_eq!(voices[2].language, "fr-FR");
+        assert_eq!(voices[3].gender, "Male");
+    }
+
+    #[test]
+    fn test_language_matches() {
+        assert!(language_matches("en-US", "en"));
+        assert!(language_matches("en-GB", "EN-gb"));
+        assert!(language_matches("ja", "ja"));
+        assert!(!language_matches("en-US", "en-GB"));
+        assert!(!language_matches("fr-FR", "en"));
+        assert!(!language_matches("ja", "ja-JP"));
+    }
+
+    #[test]
+    fn test_custom_voice_info_non_english_language() {
+        let info = custom_voice_info(&custom_voice("custom_fr", "ff_siwis:0.8+af_bella:0.2"));
+        assert_eq!(info.language, "fr-FR");
+    }
+
+    #[test]
+    fn test_looks_english() {
+        assert!(looks_english("This is the text you copied from the page."));
+        assert!(!looks_english(
+            "Bonjour, je suis très content de vous voir."
+        ));
+        assert!(!looks_english("こんにちは"));
+        assert!(!looks_english(""));
+    }
+}
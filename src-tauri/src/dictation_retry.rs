@@ -0,0 +1,153 @@
+//! The audio of the last hotkey dictation that failed to transcribe, e.g.
+//! because its model isn't downloaded, so it can be retried instead of
+//! dictated again.
+//!
+//! Only the most recent failure is kept, in memory. It is dropped by the
+//! next successful dictation or once `failed_dictation_ttl_secs` pass, and
+//! very long recordings keep only their first ten minutes.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::accessibility::FrontmostAppInfo;
+use crate::audio::capture::CAPTURE_SAMPLE_RATE;
+use crate::audio::processing::audio_duration_secs;
+use crate::events;
+
+/// Longest recording kept for a retry, about 38 MB of samples
+const MAX_STASHED_SECS: u32 = 600;
+
+static STASH: Mutex<Option<FailedDictation>> = Mutex::new(None);
+
+/// A dictation whose transcription failed
+#[derive(Debug, Clone)]
+pub struct FailedDictation {
+    /// Hotkey session the dictation was recorded in, see `live_transcript`
+    pub session_id: u32,
+    pub audio: Vec<f32>,
+    /// Why transcription failed, as shown to the user
+    pub reason: String,
+    /// App the dictation would have been pasted into
+    pub target_app: Option<FrontmostAppInfo>,
+    failed_at: Instant,
+}
+
+/// Shorten `audio` to at most `max_samples`, keeping the start. Returns
+/// whether anything was cut.
+fn cap_audio(audio: &mut Vec<f32>, max_samples: usize) -> bool {
+    if audio.len() <= max_samples {
+        return false;
+    }
+    audio.truncate(max_samples);
+    audio.shrink_to_fit();
+    true
+}
+
+/// The failure in `slot` if it happened within `ttl` of `now`. An expired
+/// failure is dropped.
+fn take_unexpired(
+    slot: &mut Option<FailedDictation>,
+    now: Instant,
+    ttl: Duration,
+) -> Option<FailedDictation> {
+    let failed = slot.take()?;
+    if now.saturating_duration_since(failed.failed_at) > ttl {
+        tracing::info!("Dropping failed dictation older than {}s", ttl.as_secs());
+        return None;
+    }
+    Some(failed)
+}
+
+/// Keep the audio of dictation `session_id`, which failed to transcribe for
+/// `reason`, replacing any earlier one. Returns the event announcing the
+/// retry.
+pub fn stash(
+    session_id: u32,
+    mut audio: Vec<f32>,
+    reason: String,
+    target_app: Option<FrontmostAppInfo>,
+) -> events::SttRetryAvailable {
+    let max_samples = (MAX_STASHED_SECS * CAPTURE_SAMPLE_RATE) as usize;
+    let truncated = cap_audio(&mut audio, max_samples);
+    if truncated {
+        tracing::warn!(
+            "Keeping only the first {}s of the failed dictation",
+            MAX_STASHED_SECS
+        );
+    }
+
+    let available = events::SttRetryAvailable {
+        reason: reason.clone(),
+        audio_secs: audio_duration_secs(audio.len(), CAPTURE_SAMPLE_RATE),
+        truncated,
+    };
+    let failed = FailedDictation {
+        session_id,
+        audio,
+        reason,
+        target_app,
+        failed_at: Instant::now(),
+    };
+    match STASH.lock() {
+        Ok(mut stash) => *stash = Some(failed),
+        Err(e) => tracing::warn!("Failed dictation not kept: {}", e),
+    }
+    available
+}
+
+/// Take the failed dictation to retry it, unless it is older than `ttl`
+pub fn take(ttl: Duration) -> Option<FailedDictation> {
+    let mut stash = STASH.lock().ok()?;
+    take_unexpired(&mut stash, Instant::now(), ttl)
+}
+
+/// Forget the failed dictation, after one succeeded
+pub fn clear() {
+    if let Ok(mut stash) = STASH.lock() {
+        stash.take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(600);
+
+    fn failed(failed_at: Instant) -> FailedDictation {
+        FailedDictation {
+            session_id: 1,
+            audio: vec![0.1; 1600],
+            reason: "Model not downloaded".to_string(),
+            target_app: None,
+            failed_at,
+        }
+    }
+
+    #[test]
+    fn test_take_unexpired() {
+        let now = Instant::now();
+        let mut slot = Some(failed(now));
+        let taken = take_unexpired(&mut slot, now + Duration::from_secs(60), TTL).unwrap();
+        assert_eq!(taken.audio.len(), 1600);
+        assert!(slot.is_none());
+        assert!(take_unexpired(&mut slot, now, TTL).is_none());
+    }
+
+    #[test]
+    fn test_expired_failure_is_dropped() {
+        let now = Instant::now();
+        let mut slot = Some(failed(now));
+        let later = now + TTL + Duration::from_secs(1);
+        assert!(take_unexpired(&mut slot, later, TTL).is_none());
+        assert!(slot.is_none());
+    }
+
+    #[test]
+    fn test_cap_audio_keeps_start() {
+        let mut audio: Vec<f32> = (0..10).map(|i| i as f32).collect();
+        assert!(!cap_audio(&mut audio, 10));
+        assert!(cap_audio(&mut audio, 4));
+        assert_eq!(audio, vec![0.0, 1.0, 2.0, 3.0]);
+    }
+}
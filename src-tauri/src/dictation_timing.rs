@@ -0,0 +1,247 @@
+//! Where the time goes in a hotkey dictation, so "dictation is slow" can be
+//! traced to model loading, inference or pasting. The last few sessions are
+//! kept in memory for `get_diagnostics`.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// How many dictations `get_diagnostics` reports on
+const HISTORY_CAPACITY: usize = 50;
+
+static HISTORY: Mutex<TimingHistory> = Mutex::new(TimingHistory::new(HISTORY_CAPACITY));
+
+/// Timings of one dictation, in milliseconds
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DictationTimings {
+    /// File name of the Whisper model that transcribed it
+    pub model: String,
+    /// Length of the recorded audio
+    pub capture_ms: u64,
+    /// From releasing the hotkey to the engine being asked to transcribe
+    pub stop_to_transcribe_ms: u64,
+    /// Loading the Whisper model; 0 when it was already loaded
+    pub model_load_ms: u64,
+    pub inference_ms: u64,
+    /// Inference time over audio length; below 1 is faster than realtime
+    pub realtime_factor: f32,
+    /// Turning the transcript into the final text
+    pub post_processing_ms: u64,
+    /// Running the post-dictation action; `None` when nothing was pasted,
+    /// and always `None` in `stt-result`, which is sent before pasting
+    pub paste_ms: Option<u64>,
+}
+
+impl DictationTimings {
+    /// Record how long inference took on `capture_ms` of audio
+    pub fn set_inference(&mut self, elapsed: Duration) {
+        self.inference_ms = millis(elapsed);
+        self.realtime_factor = if self.capture_ms == 0 {
+            0.0
+        } else {
+            elapsed.as_secs_f32() * 1000.0 / self.capture_ms as f32
+        };
+    }
+}
+
+/// Whole milliseconds in `duration`
+pub fn millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+/// Spread of one pipeline stage across the recorded dictations
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct StageStats {
+    pub mean_ms: u64,
+    pub median_ms: u64,
+    pub max_ms: u64,
+}
+
+impl StageStats {
+    /// `None` without any values
+    fn from_values(mut values: Vec<u64>) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_unstable();
+        let total: u64 = values.iter().sum();
+        Some(Self {
+            mean_ms: total / values.len() as u64,
+            median_ms: values[values.len() / 2],
+            max_ms: values[values.len() - 1],
+        })
+    }
+}
+
+/// Aggregates over the recorded dictations. Stages are `None` until a
+/// dictation has been recorded; `paste` only counts pasted dictations.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TimingSummary {
+    pub sessions: usize,
+    /// Dictations that had to load the model first
+    pub cold_starts: usize,
+    pub capture: Option<StageStats>,
+    pub stop_to_transcribe: Option<StageStats>,
+    pub model_load: Option<StageStats>,
+    pub inference: Option<StageStats>,
+    pub post_processing: Option<StageStats>,
+    pub paste: Option<StageStats>,
+    pub mean_realtime_factor: Option<f32>,
+}
+
+/// Recorded dictations with their aggregates
+#[derive(Debug, Clone, Serialize)]
+pub struct DictationDiagnostics {
+    /// Most recent first
+    pub sessions: Vec<DictationTimings>,
+    pub summary: TimingSummary,
+}
+
+/// Ring buffer of dictation timings, oldest first
+struct TimingHistory {
+    capacity: usize,
+    sessions: VecDeque<DictationTimings>,
+}
+
+impl TimingHistory {
+    const fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            sessions: VecDeque::new(),
+        }
+    }
+
+    fn push(&mut self, timings: DictationTimings) {
+        if self.sessions.len() == self.capacity {
+            self.sessions.pop_front();
+        }
+        self.sessions.push_back(timings);
+    }
+
+    fn summary(&self) -> TimingSummary {
+        let stage = |ms: fn(&DictationTimings) -> u64| {
+            StageStats::from_values(self.sessions.iter().map(ms).collect())
+        };
+        let factors: Vec<f32> = self.sessions.iter().map(|t| t.realtime_factor).collect();
+        let pastes: Vec<u64> = self.sessions.iter().filter_map(|t| t.paste_ms).collect();
+
+        TimingSummary {
+            sessions: self.sessions.len(),
+            cold_starts: self.sessions.iter().filter(|t| t.model_load_ms > 0).count(),
+            capture: stage(|t| t.capture_ms),
+            stop_to_transcribe: stage(|t| t.stop_to_transcribe_ms),
+            model_load: stage(|t| t.model_load_ms),
+            inference: stage(|t| t.inference_ms),
+            post_processing: stage(|t| t.post_processing_ms),
+            paste: StageStats::from_values(pastes),
+            mean_realtime_factor: (!factors.is_empty())
+                .then(|| factors.iter().sum::<f32>() / factors.len() as f32),
+        }
+    }
+
+    fn diagnostics(&self) -> DictationDiagnostics {
+        DictationDiagnostics {
+            sessions: self.sessions.iter().rev().cloned().collect(),
+            summary: self.summary(),
+        }
+    }
+}
+
+/// Keep the timings of a finished dictation
+pub fn record(timings: DictationTimings) {
+    tracing::debug!("Dictation timings: {:?}", timings);
+    match HISTORY.lock() {
+        Ok(mut history) => history.push(timings),
+        Err(e) => tracing::warn!("Dictation timings unavailable: {}", e),
+    }
+}
+
+/// The recorded dictations and their aggregates
+pub fn diagnostics() -> DictationDiagnostics {
+    HISTORY
+        .lock()
+        .map(|history| history.diagnostics())
+        .unwrap_or_else(|_| TimingHistory::new(0).diagnostics())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(load_ms: u64, inference_ms: u64, paste_ms: Option<u64>) -> DictationTimings {
+        let mut timings = DictationTimings {
+            capture_ms: 2000,
+            model_load_ms: load_ms,
+            paste_ms,
+            ..Default::default()
+        };
+        timings.set_inference(Duration::from_millis(inference_ms));
+        timings
+    }
+
+    #[test]
+    fn test_realtime_factor() {
+        assert_eq!(session(0, 500, None).realtime_factor, 0.25);
+
+        let mut silent = DictationTimings::default();
+        silent.set_inference(Duration::from_millis(300));
+        assert_eq!(silent.inference_ms, 300);
+        assert_eq!(silent.realtime_factor, 0.0);
+    }
+
+    #[test]
+    fn test_empty_summary() {
+        let history = TimingHistory::new(HISTORY_CAPACITY);
+        assert_eq!(history.summary(), TimingSummary::default());
+        assert!(history.diagnostics().sessions.is_empty());
+    }
+
+    #[test]
+    fn test_summary_aggregates_stages() {
+        let mut history = TimingHistory::new(HISTORY_CAPACITY);
+        history.push(session(1200, 1000, Some(40)));
+        history.push(session(0, 500, None));
+        history.push(session(0, 300, Some(20)));
+
+        let summary = history.summary();
+        assert_eq!(summary.sessions, 3);
+        assert_eq!(summary.cold_starts, 1);
+        assert_eq!(
+            summary.inference,
+            Some(StageStats {
+                mean_ms: 600,
+                median_ms: 500,
+                max_ms: 1000,
+            })
+        );
+        assert_eq!(summary.model_load.unwrap().max_ms, 1200);
+        assert_eq!(summary.model_load.unwrap().median_ms, 0);
+        // Only pasted dictations count towards paste time
+        assert_eq!(
+            summary.paste,
+            Some(StageStats {
+                mean_ms: 30,
+                median_ms: 40,
+                max_ms: 40,
+            })
+        );
+        assert!((summary.mean_realtime_factor.unwrap() - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_history_keeps_most_recent_sessions() {
+        let mut history = TimingHistory::new(2);
+        for inference_ms in [100, 200, 300] {
+            history.push(session(0, inference_ms, None));
+        }
+
+        let diagnostics = history.diagnostics();
+        let newest = &diagnostics.sessions;
+        assert_eq!(newest.len(), 2);
+        assert_eq!((newest[0].inference_ms, newest[1].inference_ms), (300, 200));
+        assert_eq!(diagnostics.summary.sessions, 2);
+        assert_eq!(diagnostics.summary.inference.unwrap().mean_ms, 250);
+    }
+}
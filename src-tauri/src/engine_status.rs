@@ -0,0 +1,193 @@
+//! Which models are loaded in memory rather than only on disk.
+//!
+//! The STT provider and the TTS engine cache report here when they load,
+//! use or drop a model. `get_engine_status` lists what they reported, and
+//! `engine-loaded` / `engine-unloaded` tell the Models tab when to refresh.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+use crate::commands::models::ModelType;
+use crate::commands::tts::format_utc;
+use crate::events;
+
+static APP: OnceLock<tauri::AppHandle> = OnceLock::new();
+
+static ENGINES: Mutex<EngineTable> = Mutex::new(EngineTable::new());
+
+/// A model that has been loaded this session
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EngineStatus {
+    pub model_id: String,
+    pub model_type: ModelType,
+    pub loaded_in_memory: bool,
+    /// UTC, e.g. `2024-05-01T09:30:00Z`
+    pub last_used: Option<String>,
+    /// Size of the model files, which is roughly what loading them takes
+    pub approx_memory_bytes: u64,
+}
+
+struct EngineRecord {
+    model_type: ModelType,
+    loaded: bool,
+    last_used: SystemTime,
+    approx_memory_bytes: u64,
+}
+
+/// Models by id, kept after they are unloaded so their last use is known
+struct EngineTable {
+    engines: BTreeMap<String, EngineRecord>,
+}
+
+impl EngineTable {
+    const fn new() -> Self {
+        Self {
+            engines: BTreeMap::new(),
+        }
+    }
+
+    /// Note `model_id` as loaded at `now`; false if it already was
+    fn loaded(
+        &mut self,
+        model_id: &str,
+        model_type: ModelType,
+        approx_memory_bytes: u64,
+        now: SystemTime,
+    ) -> bool {
+        let previous = self.engines.insert(
+            model_id.to_string(),
+            EngineRecord {
+                model_type,
+                loaded: true,
+                last_used: now,
+                approx_memory_bytes,
+            },
+        );
+        !previous.is_some_and(|record| record.loaded)
+    }
+
+    fn used(&mut self, model_id: &str, now: SystemTime) {
+        if let Some(record) = self.engines.get_mut(model_id) {
+            record.last_used = now;
+        }
+    }
+
+    /// Note `model_id` as unloaded, returning its type if it was loaded
+    fn unloaded(&mut self, model_id: &str) -> Option<ModelType> {
+        let record = self.engines.get_mut(model_id).filter(|r| r.loaded)?;
+        record.loaded = false;
+        Some(record.model_type.clone())
+    }
+
+    fn statuses(&self) -> Vec<EngineStatus> {
+        self.engines
+            .iter()
+            .map(|(model_id, record)| EngineStatus {
+                model_id: model_id.clone(),
+                model_type: record.model_type.clone(),
+                loaded_in_memory: record.loaded,
+                last_used: Some(format_utc(record.last_used)),
+                approx_memory_bytes: record.approx_memory_bytes,
+            })
+            .collect()
+    }
+}
+
+/// Send `engine-loaded` and `engine-unloaded` from now on
+pub(crate) fn init(app: tauri::AppHandle) {
+    let _ = APP.set(app);
+}
+
+/// `model_id` was loaded into memory
+pub(crate) fn loaded(model_id: &str, model_type: ModelType, approx_memory_bytes: u64) {
+    let Ok(mut engines) = ENGINES.lock() else {
+        return;
+    };
+    let now = SystemTime::now();
+    if !engines.loaded(model_id, model_type.clone(), approx_memory_bytes, now) {
+        return;
+    }
+    drop(engines);
+    if let Some(app) = APP.get() {
+        events::emit(
+            app,
+            events::EngineLoaded {
+                model_id: model_id.to_string(),
+                model_type,
+                approx_memory_bytes,
+            },
+        );
+    }
+}
+
+/// A loaded `model_id` was used again
+pub(crate) fn used(model_id: &str) {
+    if let Ok(mut engines) = ENGINES.lock() {
+        engines.used(model_id, SystemTime::now());
+    }
+}
+
+/// `model_id` was dropped from memory
+pub(crate) fn unloaded(model_id: &str) {
+    let model_type = match ENGINES.lock() {
+        Ok(mut engines) => engines.unloaded(model_id),
+        Err(_) => return,
+    };
+    let (Some(model_type), Some(app)) = (model_type, APP.get()) else {
+        return;
+    };
+    events::emit(
+        app,
+        events::EngineUnloaded {
+            model_id: model_id.to_string(),
+            model_type,
+        },
+    );
+}
+
+/// Every model loaded this session, whether it is still in memory and when
+/// it was last used
+#[tauri::command]
+pub fn get_engine_status() -> Vec<EngineStatus> {
+    ENGINES
+        .lock()
+        .map(|engines| engines.statuses())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn at(secs: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn test_engine_table_tracks_loads() {
+        let mut table = EngineTable::new();
+        assert!(table.loaded("ggml-base.en.bin", ModelType::Stt, 142, at(0)));
+        // Loading it again isn't a change
+        assert!(!table.loaded("ggml-base.en.bin", ModelType::Stt, 142, at(1)));
+        table.used("ggml-base.en.bin", at(60));
+
+        let status = &table.statuses()[0];
+        assert!(status.loaded_in_memory);
+        assert_eq!(status.last_used.as_deref(), Some("1970-01-01T00:01:00Z"));
+        assert_eq!(status.approx_memory_bytes, 142);
+
+        assert_eq!(table.unloaded("ggml-base.en.bin"), Some(ModelType::Stt));
+        assert_eq!(table.unloaded("ggml-base.en.bin"), None);
+        assert_eq!(table.unloaded("kokoro-v1.0.onnx"), None);
+
+        // Unloaded models keep their last use
+        let status = &table.statuses()[0];
+        assert!(!status.loaded_in_memory);
+        assert_eq!(status.last_used.as_deref(), Some("1970-01-01T00:01:00Z"));
+
+        assert!(table.loaded("ggml-base.en.bin", ModelType::Stt, 142, at(120)));
+    }
+}
@@ -0,0 +1,377 @@
+//! Splitting long clips into chunks and stitching their transcripts.
+//!
+//! Whisper decodes a 30-minute capture in one call with no progress signal
+//! and a large peak memory, so clips longer than the chunk length are cut at
+//! the quietest point near the end of each chunk and decoded one chunk at a
+//! time. A cut that still lands in speech overlaps the next chunk by a
+//! second so no word is lost; the words decoded twice are dropped when the
+//! transcripts are stitched back together.
+
+use anyhow::Result;
+use std::ops::Range;
+
+use super::confidence::mean_confidence;
+use super::{join_segments, Transcript, TranscriptSegment, STT_SAMPLE_RATE};
+use crate::audio::processing::{audio_duration_ms, audio_duration_secs};
+use crate::audio::silence::{calculate_rms, DEFAULT_SILENCE_THRESHOLD};
+
+/// Longest clip decoded in one call, in seconds
+pub const DEFAULT_CHUNK_SECS: f32 = 60.0;
+
+/// Range accepted for the chunk length setting, in seconds
+pub const MIN_CHUNK_SECS: f32 = 10.0;
+pub const MAX_CHUNK_SECS: f32 = 600.0;
+
+/// How far back from the end of a chunk to look for a quiet place to cut
+const SEAM_SEARCH_SECS: f32 = 10.0;
+
+/// RMS window used to find the quiet place
+const SEAM_WINDOW_SECS: f32 = 0.1;
+
+/// Audio shared by two chunks when no quiet place was found
+const CHUNK_OVERLAP_SECS: f32 = 1.0;
+
+/// Most words compared when removing a repeat across a seam
+const MAX_SEAM_WORDS: usize = 8;
+
+/// Sample ranges to decode, in order. Each is at most `max_chunk_secs`
+/// long; 0 or a clip that already fits gives a single range.
+pub fn plan_chunks(audio: &[f32], sample_rate: u32, max_chunk_secs: f32) -> Vec<Range<usize>> {
+    let samples = |secs: f32| (secs * sample_rate as f32) as usize;
+    let max_len = samples(max_chunk_secs.max(0.0));
+    let whole = 0..audio.len();
+    if max_len == 0 || audio.len() <= max_len {
+        return vec![whole];
+    }
+
+    let window = samples(SEAM_WINDOW_SECS).max(1);
+    // Bounded so every chunk moves the start forward
+    let search = samples(SEAM_SEARCH_SECS).min(max_len / 2);
+    let overlap = samples(CHUNK_OVERLAP_SECS).min(max_len / 4);
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while audio.len() - start > max_len {
+        let end = start + max_len;
+        let (offset, quiet) = quietest_point(&audio[end - search..end], window);
+        let cut = end - search + offset;
+        chunks.push(start..cut);
+        start = if quiet { cut } else { cut - overlap };
+    }
+    chunks.push(start..audio.len());
+
+    chunks
+}
+
+/// Middle of the quietest window in `region`, preferring later ones, and
+/// whether that window is silent
+fn quietest_point(region: &[f32], window: usize) -> (usize, bool) {
+    let mut quietest = (region.len(), f32::MAX);
+    for (i, samples) in region.chunks(window).enumerate() {
+        let rms = calculate_rms(samples);
+        if rms <= quietest.1 {
+            quietest = (i * window + samples.len() / 2, rms);
+        }
+    }
+
+    (quietest.0, quietest.1 < DEFAULT_SILENCE_THRESHOLD)
+}
+
+/// Decode 16 kHz `audio` a chunk at a time with `decode`, which gets each
+/// chunk and its start in milliseconds, and stitch the results. After each
+/// chunk `on_progress` gets the seconds decoded so far and the total.
+pub fn transcribe_in_chunks(
+    audio: &[f32],
+    max_chunk_secs: f32,
+    mut decode: impl FnMut(&[f32], i64) -> Result<Transcript>,
+    mut on_progress: impl FnMut(f32, f32),
+) -> Result<Transcript> {
+    let total_secs = audio_duration_secs(audio.len(), STT_SAMPLE_RATE);
+    let chunks = plan_chunks(audio, STT_SAMPLE_RATE, max_chunk_secs);
+    if chunks.len() > 1 {
+        tracing::info!(
+            "Transcribing {:.0}s of audio in {} chunks",
+            total_secs,
+            chunks.len()
+        );
+    }
+
+    let mut parts = Vec::with_capacity(chunks.len());
+    for range in chunks {
+        let offset_ms = audio_duration_ms(range.start, STT_SAMPLE_RATE) as i64;
+        let processed_secs = audio_duration_secs(range.end, STT_SAMPLE_RATE);
+        parts.push((offset_ms, decode(&audio[range], offset_ms)?));
+        on_progress(processed_secs, total_secs);
+    }
+
+    Ok(stitch(parts))
+}
+
+/// Join chunk transcripts given with their start in milliseconds: shift the
+/// timestamps onto the whole clip and drop what an overlap decoded twice.
+/// A single chunk is returned as it is.
+pub fn stitch(mut parts: Vec<(i64, Transcript)>) -> Transcript {
+    if parts.len() == 1 && parts[0].0 == 0 {
+        return parts.remove(0).1;
+    }
+
+    let mut segments: Vec<TranscriptSegment> = Vec::new();
+    for (offset_ms, transcript) in parts {
+        let covered_ms = segments.last().map(|segment| segment.end_ms);
+        let mut at_seam = true;
+
+        for mut segment in transcript.segments {
+            segment.start_ms += offset_ms;
+            segment.end_ms += offset_ms;
+
+            if let Some(covered_ms) = covered_ms {
+                // Wholly inside audio the previous chunk already decoded
+                if segment.end_ms <= covered_ms {
+                    continue;
+                }
+                if at_seam && segment.start_ms < covered_ms {
+                    drop_repeated_words(&segments, &mut segment);
+                }
+            }
+            at_seam = false;
+
+            if !segment.text.is_empty() {
+                segments.push(segment);
+            }
+        }
+    }
+
+    Transcript {
+        text: join_segments(&segments),
+        segments,
+    }
+}
+
+/// Remove the words at the start of `segment` that repeat the end of
+/// `previous`
+fn drop_repeated_words(previous: &[TranscriptSegment], segment: &mut TranscriptSegment) {
+    let tail: Vec<&str> = previous
+        .iter()
+        .rev()
+        .take(MAX_SEAM_WORDS)
+        .flat_map(|segment| segment.text.split_whitespace().rev())
+        .take(MAX_SEAM_WORDS)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    let words: Vec<&str> = segment.text.split_whitespace().collect();
+
+    let repeated = seam_overlap(&tail, &words);
+    if repeated == 0 {
+        return;
+    }
+
+    // Word scores only line up with the text when there is one per word
+    if segment.words.len() == words.len() {
+        segment.words.drain(..repeated);
+        segment.confidence = mean_confidence(&segment.words);
+    }
+    segment.text = words[repeated..].join(" ");
+}
+
+/// Number of words at the start of `next` that repeat the end of `previous`,
+/// ignoring case and punctuation
+pub fn seam_overlap(previous: &[&str], next: &[&str]) -> usize {
+    let normalize = |word: &str| {
+        word.trim_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase()
+    };
+
+    let longest = previous.len().min(next.len()).min(MAX_SEAM_WORDS);
+    (1..=longest)
+        .rev()
+        .find(|&n| {
+            previous[previous.len() - n..]
+                .iter()
+                .zip(&next[..n])
+                .all(|(a, b)| normalize(a) == normalize(b))
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engines::WordConfidence;
+
+    const RATE: u32 = 1000;
+
+    fn segment(text: &str, start_ms: i64, end_ms: i64) -> TranscriptSegment {
+        TranscriptSegment {
+            text: text.to_string(),
+            start_ms,
+            end_ms,
+            confidence: None,
+            words: Vec::new(),
+        }
+    }
+
+    fn transcript(segments: Vec<TranscriptSegment>) -> Transcript {
+        Transcript {
+            text: join_segments(&segments),
+            segments,
+        }
+    }
+
+    /// Constant "speech" with silent gaps over the given seconds
+    fn speech_with_gaps(secs: usize, gaps: &[Range<usize>]) -> Vec<f32> {
+        let mut audio = vec![0.5; secs * RATE as usize];
+        for gap in gaps {
+            audio[gap.start * RATE as usize..gap.end * RATE as usize].fill(0.0);
+        }
+        audio
+    }
+
+    #[test]
+    fn test_short_clip_is_one_chunk() {
+        let audio = vec![0.5; 30 * RATE as usize];
+        let whole = 0..audio.len();
+        assert_eq!(plan_chunks(&audio, RATE, 60.0), vec![whole.clone()]);
+        // 0 turns chunking off
+        assert_eq!(plan_chunks(&audio, RATE, 0.0), vec![whole]);
+    }
+
+    #[test]
+    fn test_chunks_cut_in_silence() {
+        // Pauses at 55-56 s and 110-111 s
+        let audio = speech_with_gaps(150, &[55..56, 110..111]);
+        let chunks = plan_chunks(&audio, RATE, 60.0);
+
+        assert_eq!(chunks.len(), 3);
+        for chunk in &chunks {
+            assert!(chunk.len() <= 60 * RATE as usize);
+        }
+        // Cuts fall inside the pauses and the chunks don't overlap
+        assert!((55_000..56_000).contains(&chunks[0].end));
+        assert_eq!(chunks[1].start, chunks[0].end);
+        assert!((110_000..111_000).contains(&chunks[1].end));
+        assert_eq!(chunks[2].start, chunks[1].end);
+        assert_eq!(chunks[2].end, audio.len());
+    }
+
+    #[test]
+    fn test_chunks_overlap_without_silence() {
+        let audio = speech_with_gaps(100, &[]);
+        let chunks = plan_chunks(&audio, RATE, 60.0);
+
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks[0].len() <= 60 * RATE as usize);
+        assert_eq!(chunks[0].end - chunks[1].start, RATE as usize);
+        assert_eq!(chunks[1].end, audio.len());
+    }
+
+    #[test]
+    fn test_seam_overlap() {
+        let previous = ["and", "then", "we", "went", "home."];
+        assert_eq!(seam_overlap(&previous, &["went", "home", "after"]), 2);
+        assert_eq!(seam_overlap(&previous, &["Home,", "sweet", "home"]), 1);
+        assert_eq!(seam_overlap(&previous, &["after", "dinner"]), 0);
+        assert_eq!(seam_overlap(&previous, &[]), 0);
+    }
+
+    #[test]
+    fn test_stitch_offsets_and_removes_repeats() {
+        let first = transcript(vec![
+            segment("The meeting started late.", 0, 3000),
+            segment("We talked about the budget", 3000, 60_000),
+        ]);
+        // The second chunk starts 1 s before the first one ended
+        let second = transcript(vec![
+            segment("the budget and the roadmap.", 0, 2500),
+            segment("Then we left.", 2500, 5000),
+        ]);
+
+        let stitched = stitch(vec![(0, first), (59_000, second)]);
+
+        assert_eq!(
+            stitched.text,
+            "The meeting started late. We talked about the budget and the roadmap. Then we left."
+        );
+        assert_eq!(stitched.segments[2].start_ms, 59_000);
+        assert_eq!(stitched.segments[3].end_ms, 64_000);
+    }
+
+    #[test]
+    fn test_stitch_drops_segments_decoded_twice() {
+        let first = transcript(vec![segment("One two three.", 0, 60_000)]);
+        let second = transcript(vec![
+            segment("three.", 0, 800),
+            segment("Four five.", 1000, 3000),
+        ]);
+
+        let stitched = stitch(vec![(0, first), (59_000, second)]);
+        assert_eq!(stitched.text, "One two three. Four five.");
+        assert_eq!(stitched.segments.len(), 2);
+    }
+
+    #[test]
+    fn test_stitch_trims_word_scores() {
+        let word = |word: &str, confidence| WordConfidence {
+            word: word.to_string(),
+            confidence,
+        };
+        let first = transcript(vec![segment("Hello there", 0, 60_000)]);
+        let mut repeated = segment("there friend", 0, 2000);
+        repeated.words = vec![word("there", 0.2), word("friend", 0.9)];
+        repeated.confidence = Some(0.55);
+
+        let stitched = stitch(vec![(0, first), (59_500, transcript(vec![repeated]))]);
+
+        assert_eq!(stitched.text, "Hello there friend");
+        assert_eq!(stitched.segments[1].words, vec![word("friend", 0.9)]);
+        assert_eq!(stitched.segments[1].confidence, Some(0.9));
+    }
+
+    #[test]
+    fn test_transcribe_in_chunks_reports_progress() {
+        // Two chunks at 16 kHz: 70 s of constant audio with a 60 s limit
+        let audio = vec![0.5; 70 * STT_SAMPLE_RATE as usize];
+        let mut offsets = Vec::new();
+        let mut progress = Vec::new();
+
+        let result = transcribe_in_chunks(
+            &audio,
+            60.0,
+            |chunk, offset_ms| {
+                offsets.push(offset_ms);
+                let end_ms = audio_duration_ms(chunk.len(), STT_SAMPLE_RATE) as i64;
+                let text = if offset_ms == 0 { "first" } else { "second" };
+                Ok(transcript(vec![segment(text, 0, end_ms)]))
+            },
+            |processed, total| progress.push((processed, total)),
+        )
+        .unwrap();
+
+        assert_eq!(result.text, "first second");
+        assert_eq!(offsets.len(), 2);
+        assert_eq!(offsets[0], 0);
+        assert_eq!(progress.len(), 2);
+        assert!(progress[0].0 < progress[1].0);
+        assert_eq!(progress[1], (70.0, 70.0));
+    }
+
+    #[test]
+    fn test_transcribe_in_chunks_stops_on_error() {
+        let audio = vec![0.5; 130 * STT_SAMPLE_RATE as usize];
+        let mut calls = 0;
+
+        let result = transcribe_in_chunks(
+            &audio,
+            60.0,
+            |_, _| {
+                calls += 1;
+                Err(anyhow::anyhow!("decoder crashed"))
+            },
+            |_, _| panic!("no progress after a failed chunk"),
+        );
+
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+}
@@ -0,0 +1,152 @@
+//! Confidence scores from Whisper's per-token probabilities.
+//!
+//! Whisper decodes sub-word tokens, so token probabilities are grouped into
+//! words first: a token starting with whitespace begins a new word, anything
+//! else continues the previous one. A word is only as certain as its least
+//! certain token, and segment and transcript scores average the words so a
+//! long word doesn't outweigh a short one.
+
+use super::{TranscriptSegment, WordConfidence};
+
+/// A decoded token and the probability Whisper gave it
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenProb {
+    pub text: String,
+    pub prob: f32,
+}
+
+impl TokenProb {
+    pub fn new(text: impl Into<String>, prob: f32) -> Self {
+        Self {
+            text: text.into(),
+            prob,
+        }
+    }
+}
+
+/// Timestamp and control tokens such as `[_BEG_]`, `[_TT_42]` or
+/// `<|endoftext|>`, which carry no text
+pub fn is_special_token(text: &str) -> bool {
+    text.starts_with("[_") || text.starts_with("<|")
+}
+
+/// Group tokens into words, each scored by its lowest token probability
+pub fn word_confidences(tokens: &[TokenProb]) -> Vec<WordConfidence> {
+    let mut words: Vec<WordConfidence> = Vec::new();
+
+    for token in tokens {
+        if is_special_token(&token.text) || token.text.is_empty() {
+            continue;
+        }
+        let prob = token.prob.clamp(0.0, 1.0);
+
+        let starts_word = token.text.starts_with(char::is_whitespace);
+        match words.last_mut() {
+            Some(word) if !starts_word => {
+                word.word.push_str(&token.text);
+                word.confidence = word.confidence.min(prob);
+            }
+            _ => words.push(WordConfidence {
+                word: token.text.trim_start().to_string(),
+                confidence: prob,
+            }),
+        }
+    }
+
+    words.retain(|word| !word.word.trim().is_empty());
+    for word in &mut words {
+        word.word = word.word.trim_end().to_string();
+    }
+    words
+}
+
+/// Mean confidence of `words`, or `None` when there are none
+pub fn mean_confidence<'a>(words: impl IntoIterator<Item = &'a WordConfidence>) -> Option<f32> {
+    let (sum, count) = words.into_iter().fold((0.0, 0usize), |(sum, count), word| {
+        (sum + word.confidence, count + 1)
+    });
+    (count > 0).then(|| sum / count as f32)
+}
+
+/// Mean confidence over every word of every segment, or `None` when the
+/// engine reported no word scores
+pub fn overall_confidence(segments: &[TranscriptSegment]) -> Option<f32> {
+    mean_confidence(segments.iter().flat_map(|segment| &segment.words))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(list: &[(&str, f32)]) -> Vec<TokenProb> {
+        list.iter()
+            .map(|(text, prob)| TokenProb::new(*text, *prob))
+            .collect()
+    }
+
+    fn segment(words: Vec<WordConfidence>) -> TranscriptSegment {
+        TranscriptSegment {
+            text: String::new(),
+            start_ms: 0,
+            end_ms: 0,
+            confidence: mean_confidence(&words),
+            words,
+        }
+    }
+
+    #[test]
+    fn test_word_confidences_join_sub_word_tokens() {
+        let words = word_confidences(&tokens(&[
+            ("[_BEG_]", 0.99),
+            (" Hello", 0.95),
+            (" wor", 0.9),
+            ("ld", 0.4),
+            ("!", 0.8),
+            ("[_TT_50]", 0.99),
+            ("<|endoftext|>", 0.99),
+        ]));
+
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[0].word, "Hello");
+        assert_eq!(words[0].confidence, 0.95);
+        // Scored by the least certain token
+        assert_eq!(words[1].word, "world!");
+        assert_eq!(words[1].confidence, 0.4);
+    }
+
+    #[test]
+    fn test_word_confidences_first_token_without_space() {
+        let words = word_confidences(&tokens(&[("Hi", 0.7), (" there", 0.9)]));
+        assert_eq!(words[0].word, "Hi");
+        assert_eq!(words[1].word, "there");
+    }
+
+    #[test]
+    fn test_word_confidences_clamps_probabilities() {
+        let words = word_confidences(&tokens(&[(" odd", 1.5), (" worse", -0.2)]));
+        assert_eq!(words[0].confidence, 1.0);
+        assert_eq!(words[1].confidence, 0.0);
+    }
+
+    #[test]
+    fn test_word_confidences_only_special_tokens() {
+        assert!(word_confidences(&tokens(&[("[_BEG_]", 0.9), (" ", 0.5)])).is_empty());
+    }
+
+    #[test]
+    fn test_overall_confidence_weights_words_equally() {
+        let first = word_confidences(&tokens(&[(" very", 1.0), (" sure", 1.0)]));
+        let second = word_confidences(&tokens(&[(" not", 0.4)]));
+        let segments = vec![segment(first), segment(second)];
+
+        assert_eq!(segments[0].confidence, Some(1.0));
+        assert_eq!(segments[1].confidence, Some(0.4));
+        assert!((overall_confidence(&segments).unwrap() - 0.8).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_overall_confidence_without_words() {
+        assert_eq!(overall_confidence(&[]), None);
+        assert_eq!(overall_confidence(&[segment(Vec::new())]), None);
+    }
+}
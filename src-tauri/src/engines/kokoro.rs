@@ -1,18 +1,96 @@
 #![allow(dead_code)]
 
 use anyhow::{anyhow, Result};
+use futures_util::future::BoxFuture;
 use kokoro_tiny::TtsEngine;
 use std::path::{Path, PathBuf};
 
+use super::voice_blend::{self, VoiceBlend};
 use super::{AudioBuffer, ModelInfo, TextToSpeech, VoiceInfo};
 
 const SAMPLE_RATE: u32 = 24000;
-const MIN_SPEED: f32 = 0.25;
-const MAX_SPEED: f32 = 5.0;
+pub const MIN_SPEED: f32 = 0.25;
+pub const MAX_SPEED: f32 = 5.0;
+
+pub const MODEL_FILE: &str = "kokoro-v1.0.onnx";
+pub const VOICES_FILE: &str = "voices-v1.0.bin";
+
+/// Voice pack with the stock voices plus every blend used so far
+const BLENDED_VOICES_FILE: &str = "voices-blended.bin";
+
+/// Voice used when nothing else is chosen
+pub const DEFAULT_VOICE: &str = "af_heart";
+
+/// Kokoro voice ids start with a language letter and a gender letter, e.g.
+/// `ff_siwis` is a French female voice. Language tags are BCP 47, matching
+/// what system voices report.
+const VOICE_LANGUAGES: &[(char, &str)] = &[
+    ('a', "en-US"),
+    ('b', "en-GB"),
+    ('e', "es"),
+    ('f', "fr-FR"),
+    ('h', "hi"),
+    ('i', "it"),
+    ('j', "ja"),
+    ('p', "pt-BR"),
+    ('z', "zh"),
+];
+
+/// Language tag of a Kokoro voice id, `None` for an unknown prefix
+pub fn voice_language(voice_id: &str) -> Option<&'static str> {
+    let prefix = voice_id.chars().next()?;
+    VOICE_LANGUAGES
+        .iter()
+        .find(|(letter, _)| *letter == prefix)
+        .map(|(_, tag)| *tag)
+}
+
+pub fn is_english_voice(voice_id: &str) -> bool {
+    voice_language(voice_id).is_some_and(|tag| tag.starts_with("en"))
+}
+
+/// "Female", "Male" or "Unknown" from the second letter of the id
+pub fn voice_gender(voice_id: &str) -> &'static str {
+    match voice_id.chars().nth(1) {
+        Some('f') => "Female",
+        Some('m') => "Male",
+        _ => "Unknown",
+    }
+}
+
+/// Display name from the id, e.g. `ff_siwis` -> "Siwis"
+pub fn voice_display_name(voice_id: &str) -> String {
+    let name = voice_id.split_once('_').map_or(voice_id, |(_, name)| name);
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => voice_id.to_string(),
+    }
+}
+
+/// Voice to fall back to when the chosen one is gone: the default if it is
+/// installed, else the first installed English voice. Never picks a
+/// non-English voice, since those mispronounce English text.
+pub fn fallback_voice<S: AsRef<str>>(installed: &[S]) -> String {
+    let mut english: Vec<&str> = installed
+        .iter()
+        .map(|voice| voice.as_ref())
+        .filter(|voice| is_english_voice(voice))
+        .collect();
+    english.sort_unstable();
+
+    if english.is_empty() || english.contains(&DEFAULT_VOICE) {
+        DEFAULT_VOICE.to_string()
+    } else {
+        english[0].to_string()
+    }
+}
 
 pub struct KokoroEngine {
     tts: TtsEngine,
     model_dir: PathBuf,
+    // Blends present in the currently loaded voice pack
+    blends: Vec<VoiceBlend>,
 }
 
 impl KokoroEngine {
@@ -47,10 +125,66 @@ impl KokoroEngine {
 
         tracing::info!("Kokoro TTS loaded successfully");
 
-        Ok(Self { tts, model_dir })
+        Ok(Self {
+            tts,
+            model_dir,
+            blends: Vec::new(),
+        })
+    }
+
+    /// Make blended voices available to `synthesize`.
+    ///
+    /// kokoro-tiny only takes voices by name, so the blends are interpolated
+    /// into a copy of the voice pack and the engine is reloaded from it.
+    pub async fn add_blends(&mut self, blends: &[VoiceBlend]) -> Result<()> {
+        let mut all = self.blends.clone();
+        for blend in blends {
+            if !all.iter().any(|b| b.style_name() == blend.style_name()) {
+                all.push(blend.clone());
+            }
+        }
+        if all.len() == self.blends.len() {
+            return Ok(());
+        }
+
+        let mut pack = voice_blend::read_voice_pack(&self.model_dir.join(VOICES_FILE))?;
+        for blend in &all {
+            let style = blend.mix(&pack)?;
+            pack.insert(blend.style_name(), style);
+        }
+
+        let blended_path = self.model_dir.join(BLENDED_VOICES_FILE);
+        let temp_path = self.model_dir.join(format!("{}.tmp", BLENDED_VOICES_FILE));
+        voice_blend::write_voice_pack(&temp_path, &pack)?;
+        std::fs::rename(&temp_path, &blended_path)?;
+
+        tracing::info!("Reloading Kokoro TTS with {} blended voice(s)", all.len());
+        self.tts = TtsEngine::with_paths(
+            self.model_dir.join(MODEL_FILE).to_string_lossy().as_ref(),
+            blended_path.to_string_lossy().as_ref(),
+        )
+        .await
+        .map_err(|e| anyhow!("Failed to load blended voices: {}", e))?;
+        self.blends = all;
+
+        Ok(())
     }
 
-    pub fn synthesize(&mut self, text: &str, voice_id: &str, speed: f32) -> Result<AudioBuffer> {
+    /// Synthesize `text` with a voice id or a blend such as `af_bella:0.5+af_sky:0.5`
+    pub async fn synthesize(
+        &mut self,
+        text: &str,
+        voice_id: &str,
+        speed: f32,
+    ) -> Result<AudioBuffer> {
+        let voice = if voice_blend::is_blend(voice_id) {
+            let blend = VoiceBlend::parse(voice_id)?;
+            self.add_blends(std::slice::from_ref(&blend)).await?;
+            blend.style_name()
+        } else {
+            voice_id.to_string()
+        };
+
         // Clamp speed to safe range
         let clamped_speed = speed.clamp(MIN_SPEED, MAX_SPEED);
 
@@ -72,7 +206,7 @@ impl KokoroEngine {
 
         let samples = self
             .tts
-            .synthesize(text, Some(voice_id))
+            .synthesize(text, Some(&voice))
             .map_err(|e| anyhow!("TTS synthesis failed: {}", e))?;
 
         // Apply speed adjustment by modifying the effective sample rate
@@ -84,12 +218,13 @@ impl KokoroEngine {
 }
 
 impl TextToSpeech for KokoroEngine {
-    fn synthesize(&self, _text: &str, _voice: &str, _speed: f32) -> Result<AudioBuffer> {
-        // TextToSpeech trait requires &self, but kokoro-tiny needs &mut self
-        // This is a limitation we work around in the command layer
-        Err(anyhow!(
-            "Use KokoroEngine::synthesize directly with &mut self"
-        ))
+    fn synthesize<'a>(
+        &'a mut self,
+        text: &'a str,
+        voice: &'a str,
+        speed: f32,
+    ) -> BoxFuture<'a, Result<AudioBuffer>> {
+        Box::pin(KokoroEngine::synthesize(self, text, voice, speed))
     }
 
     fn available_voices(&self) -> Vec<VoiceInfo> {
@@ -196,6 +331,45 @@ mod tests {
     use super::*;
     use tempfile::tempdir;
 
+    #[test]
+    fn test_voice_language() {
+        assert_eq!(voice_language("af_heart"), Some("en-US"));
+        assert_eq!(voice_language("bm_george"), Some("en-GB"));
+        assert_eq!(voice_language("ff_siwis"), Some("fr-FR"));
+        assert_eq!(voice_language("jf_alpha"), Some("ja"));
+        assert_eq!(voice_language("zm_yunxi"), Some("zh"));
+        assert_eq!(voice_language("pm_alex"), Some("pt-BR"));
+        assert_eq!(voice_language("xx_unknown"), None);
+        assert_eq!(voice_language(""), None);
+
+        assert!(is_english_voice("bf_emma"));
+        assert!(!is_english_voice("if_sara"));
+    }
+
+    #[test]
+    fn test_voice_gender_and_name() {
+        assert_eq!(voice_gender("hf_alpha"), "Female");
+        assert_eq!(voice_gender("em_alex"), "Male");
+        assert_eq!(voice_gender("x"), "Unknown");
+        assert_eq!(voice_display_name("ff_siwis"), "Siwis");
+        assert_eq!(voice_display_name("noprefix"), "Noprefix");
+    }
+
+    #[test]
+    fn test_fallback_voice_prefers_installed_english() {
+        assert_eq!(
+            fallback_voice(&["ff_siwis", "af_heart", "bf_emma"]),
+            "af_heart"
+        );
+        assert_eq!(
+            fallback_voice(&["ff_siwis", "bf_emma", "am_adam"]),
+            "am_adam"
+        );
+        // Nothing English installed (or nothing installed): the default, never French
+        assert_eq!(fallback_voice(&["ff_siwis", "jf_alpha"]), DEFAULT_VOICE);
+        assert_eq!(fallback_voice::<&str>(&[]), DEFAULT_VOICE);
+    }
+
     #[test]
     fn test_sample_rate_constant() {
         assert_eq!(SAMPLE_RATE, 24000);
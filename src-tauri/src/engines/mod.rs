@@ -1,11 +1,21 @@
 #![allow(dead_code)]
 
+pub mod chunking;
+pub mod confidence;
 pub mod whisper;
 pub mod kokoro;
+pub mod registry;
+pub mod system;
+pub mod voice_blend;
+
+pub use registry::EngineRegistry;
 
 use anyhow::Result;
+use futures_util::future::BoxFuture;
 use serde::{Deserialize, Serialize};
 
+use crate::audio::processing::{audio_duration_ms, audio_duration_secs};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelInfo {
     pub name: String,
@@ -13,10 +23,199 @@ pub struct ModelInfo {
     pub loaded: bool,
 }
 
-/// Trait for Speech-to-Text engines
+/// A decoded span of speech with its position in the source audio
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TranscriptSegment {
+    pub text: String,
+    pub start_ms: i64,
+    pub end_ms: i64,
+    /// Mean confidence of `words`, from 0 to 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    /// Words with their confidence, when the engine reports token probabilities
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<WordConfidence>,
+}
+
+/// A word and how sure the engine was of it, from 0 to 1
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WordConfidence {
+    pub word: String,
+    pub confidence: f32,
+}
+
+/// Full text of a transcription together with its segments
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transcript {
+    pub text: String,
+    pub segments: Vec<TranscriptSegment>,
+}
+
+impl Transcript {
+    /// Mean word confidence, or `None` when the engine reported none
+    pub fn confidence(&self) -> Option<f32> {
+        confidence::overall_confidence(&self.segments)
+    }
+
+    /// Every word with its confidence, in order
+    pub fn words(&self) -> Vec<WordConfidence> {
+        self.segments
+            .iter()
+            .flat_map(|segment| segment.words.iter().cloned())
+            .collect()
+    }
+}
+
+/// A transcript segment attributed to a speaker. Speaker ids count up from 0
+/// in order of appearance and only mark turns, not identities.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DiarizedSegment {
+    pub speaker_id: u32,
+    pub text: String,
+    pub start_ms: i64,
+    pub end_ms: i64,
+}
+
+/// Tunable decoding behaviour, usually taken from the STT settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodeOptions {
+    /// Clips shorter than this many seconds are decoded as a single segment
+    /// without cross-segment context. 0 disables the heuristic.
+    pub short_utterance_secs: f32,
+    /// Stop Whisper from emitting non-speech tokens such as music notation
+    pub suppress_non_speech_tokens: bool,
+    /// Longest clip decoded in one call, in seconds. Engines that chunk
+    /// split anything longer; 0 decodes every clip in one call.
+    pub max_chunk_secs: f32,
+    /// Text Whisper treats as coming before the audio, biasing it towards
+    /// names and terms it contains
+    pub initial_prompt: Option<String>,
+    /// Threads to decode with; 0 leaves the choice to the engine
+    pub n_threads: u32,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        Self {
+            short_utterance_secs: 0.0,
+            suppress_non_speech_tokens: false,
+            max_chunk_secs: chunking::DEFAULT_CHUNK_SECS,
+            initial_prompt: None,
+            n_threads: 0,
+        }
+    }
+}
+
+/// Callback fired with each segment as it is decoded
+pub type SegmentCallback = Box<dyn FnMut(&TranscriptSegment) + Send>;
+
+/// Callback fired with the seconds of audio decoded so far and the total
+pub type ProgressCallback = Box<dyn FnMut(f32, f32) + Send>;
+
+/// Trait for Speech-to-Text engines. Object-safe so the command layer can
+/// hold a `Box<dyn SpeechToText>` and tests can swap in a mock.
+///
+/// Only `transcribe` and `model_info` are required; the other methods fall
+/// back to it, which is what engines without segment timing want anyway.
 pub trait SpeechToText: Send + Sync {
     fn transcribe(&self, audio: &[f32]) -> Result<String>;
     fn model_info(&self) -> ModelInfo;
+
+    /// Transcribe with explicit decoding options. Engines that have no
+    /// tunables ignore them.
+    fn transcribe_with_options(&self, audio: &[f32], options: &DecodeOptions) -> Result<String> {
+        let _ = options;
+        self.transcribe(audio)
+    }
+
+    /// Transcribe keeping segment boundaries. Defaults to one segment
+    /// covering the whole clip.
+    fn transcribe_segments(
+        &self,
+        audio: &[f32],
+        options: &DecodeOptions,
+    ) -> Result<Vec<TranscriptSegment>> {
+        let text = self.transcribe_with_options(audio, options)?;
+        Ok(vec![TranscriptSegment {
+            text,
+            start_ms: 0,
+            end_ms: audio_duration_ms(audio.len(), STT_SAMPLE_RATE) as i64,
+            confidence: None,
+            words: Vec::new(),
+        }])
+    }
+
+    /// Transcribe into the full text plus its segments, with word
+    /// confidence where the engine has it.
+    fn transcribe_detailed(&self, audio: &[f32], options: &DecodeOptions) -> Result<Transcript> {
+        let segments = self.transcribe_segments(audio, options)?;
+        Ok(Transcript {
+            text: join_segments(&segments),
+            segments,
+        })
+    }
+
+    /// Transcribe, calling `on_segment` as each segment is decoded, and
+    /// return the whole transcript. Defaults to a single callback per
+    /// segment at the end.
+    fn transcribe_streaming(
+        &self,
+        audio: &[f32],
+        options: &DecodeOptions,
+        mut on_segment: SegmentCallback,
+    ) -> Result<Transcript> {
+        let transcript = self.transcribe_detailed(audio, options)?;
+        for segment in &transcript.segments {
+            on_segment(segment);
+        }
+        Ok(transcript)
+    }
+
+    /// Like `transcribe_streaming`, also calling `on_progress` as the clip
+    /// is decoded. Defaults to a single report once it is done.
+    fn transcribe_with_progress(
+        &self,
+        audio: &[f32],
+        options: &DecodeOptions,
+        on_segment: SegmentCallback,
+        mut on_progress: ProgressCallback,
+    ) -> Result<Transcript> {
+        let transcript = self.transcribe_streaming(audio, options, on_segment)?;
+        let total_secs = audio_duration_secs(audio.len(), STT_SAMPLE_RATE);
+        on_progress(total_secs, total_secs);
+        Ok(transcript)
+    }
+
+    /// Transcribe split into speaker turns. Defaults to a single speaker.
+    fn transcribe_diarized(
+        &self,
+        audio: &[f32],
+        options: &DecodeOptions,
+    ) -> Result<Vec<DiarizedSegment>> {
+        Ok(self
+            .transcribe_segments(audio, options)?
+            .into_iter()
+            .map(|segment| DiarizedSegment {
+                speaker_id: 0,
+                text: segment.text,
+                start_ms: segment.start_ms,
+                end_ms: segment.end_ms,
+            })
+            .collect())
+    }
+}
+
+/// Sample rate STT engines expect their input at
+pub const STT_SAMPLE_RATE: u32 = 16000;
+
+/// Full transcript text from decoded segments
+pub fn join_segments(segments: &[TranscriptSegment]) -> String {
+    segments
+        .iter()
+        .map(|segment| segment.text.trim())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 /// Audio buffer for TTS output
@@ -39,7 +238,7 @@ impl AudioBuffer {
     }
 
     pub fn duration_secs(&self) -> f32 {
-        self.samples.len() as f32 / self.sample_rate as f32
+        crate::audio::processing::audio_duration_secs(self.samples.len(), self.sample_rate)
     }
 }
 
@@ -50,9 +249,60 @@ pub struct VoiceInfo {
     pub language: String,
 }
 
+/// Which TTS backend a voice belongs to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TtsEngineKind {
+    /// Kokoro-82M, downloaded from the Models tab
+    #[default]
+    Kokoro,
+    /// macOS system voices, no download needed
+    System,
+}
+
+impl TtsEngineKind {
+    /// The engine that owns `voice_id`; saved blends are Kokoro voices
+    pub fn for_voice(voice_id: &str) -> Self {
+        if voice_id.starts_with(system::SYSTEM_VOICE_PREFIX) {
+            TtsEngineKind::System
+        } else {
+            TtsEngineKind::Kokoro
+        }
+    }
+}
+
 /// Trait for Text-to-Speech engines
 pub trait TextToSpeech: Send + Sync {
-    fn synthesize(&self, text: &str, voice: &str, speed: f32) -> Result<AudioBuffer>;
+    /// Synthesize `text` with `voice` at `speed`. Takes `&mut self` and
+    /// returns a boxed future because engines may need to load voices first.
+    fn synthesize<'a>(
+        &'a mut self,
+        text: &'a str,
+        voice: &'a str,
+        speed: f32,
+    ) -> BoxFuture<'a, Result<AudioBuffer>>;
     fn available_voices(&self) -> Vec<VoiceInfo>;
     fn model_info(&self) -> ModelInfo;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tts_engine_for_voice() {
+        assert_eq!(TtsEngineKind::for_voice("af_heart"), TtsEngineKind::Kokoro);
+        assert_eq!(
+            TtsEngineKind::for_voice("af_bella:0.5+af_sky:0.5"),
+            TtsEngineKind::Kokoro
+        );
+        assert_eq!(
+            TtsEngineKind::for_voice("custom_mix"),
+            TtsEngineKind::Kokoro
+        );
+        assert_eq!(
+            TtsEngineKind::for_voice("system:Samantha"),
+            TtsEngineKind::System
+        );
+    }
+}
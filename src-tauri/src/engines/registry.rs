@@ -0,0 +1,297 @@
+//! Loaded speech engines, shared through Tauri managed state.
+//!
+//! Loading a Whisper or Kokoro model takes seconds and hundreds of
+//! megabytes, so every command, hotkey and background task goes through the
+//! one `EngineRegistry` managed by the app. It loads engines on first use
+//! and drops them when their files or load-time settings change.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex as TokioMutex;
+
+use crate::commands::models::ModelType;
+use crate::commands::stt::{load_whisper_engine, SttProvider};
+use crate::commands::tts::load_kokoro_engine;
+use crate::engine_status;
+use crate::engines::kokoro::{self, VOICES_FILE};
+use crate::engines::system::SystemTtsEngine;
+use crate::engines::{SpeechToText, TextToSpeech, TtsEngineKind};
+use crate::error::BlahError;
+
+/// One loaded TTS engine, locked for each chunk it synthesizes
+pub type SharedTtsEngine = Arc<TokioMutex<Box<dyn TextToSpeech>>>;
+
+/// Loaded TTS engines by backend
+#[derive(Default)]
+struct LoadedTts {
+    engines: HashMap<TtsEngineKind, SharedTtsEngine>,
+    /// Folder the loaded Kokoro model was read from
+    kokoro_dir: Option<PathBuf>,
+}
+
+pub struct EngineRegistry {
+    /// Keeps the most recently used Whisper models loaded
+    whisper: SttProvider,
+    tts: TokioMutex<LoadedTts>,
+    /// Last time a TTS engine was used, for idle eviction
+    tts_last_used: Mutex<Option<Instant>>,
+}
+
+impl Default for EngineRegistry {
+    fn default() -> Self {
+        Self::with_whisper(SttProvider::new(load_whisper_engine))
+    }
+}
+
+impl EngineRegistry {
+    /// A registry whose Whisper models come from `whisper`, e.g. one with a
+    /// mock loader
+    pub(crate) fn with_whisper(whisper: SttProvider) -> Self {
+        Self {
+            whisper,
+            tts: TokioMutex::new(LoadedTts::default()),
+            tts_last_used: Mutex::new(None),
+        }
+    }
+
+    /// The Whisper provider, for transcriptions that report load times or
+    /// keep several models loaded
+    pub(crate) fn whisper(&self) -> &SttProvider {
+        &self.whisper
+    }
+
+    /// Engine for the Whisper model at `model_path`, loading it if needed
+    pub async fn get_or_load_whisper(
+        &self,
+        model_path: &str,
+    ) -> Result<Arc<dyn SpeechToText>, BlahError> {
+        self.whisper.engine(model_path).await
+    }
+
+    /// Kokoro engine for the model in `model_dir`, loading it first if it
+    /// isn't loaded or was loaded from another folder
+    pub async fn get_or_load_kokoro(&self, model_dir: &Path) -> Result<SharedTtsEngine, BlahError> {
+        let mut tts = self.tts.lock().await;
+
+        let loaded = tts
+            .engines
+            .get(&TtsEngineKind::Kokoro)
+            .filter(|_| tts.kokoro_dir.as_deref() == Some(model_dir))
+            .cloned();
+        let engine = match loaded {
+            Some(engine) => engine,
+            None => {
+                if tts.engines.remove(&TtsEngineKind::Kokoro).is_some() {
+                    engine_status::unloaded(kokoro::MODEL_FILE);
+                }
+                let engine = load_kokoro_engine(model_dir).await?;
+                let engine: SharedTtsEngine = Arc::new(TokioMutex::new(Box::new(engine)));
+                let model_bytes = kokoro_model_bytes(model_dir);
+                engine_status::loaded(kokoro::MODEL_FILE, ModelType::Tts, model_bytes);
+                tts.engines
+                    .insert(TtsEngineKind::Kokoro, Arc::clone(&engine));
+                tts.kokoro_dir = Some(model_dir.to_path_buf());
+                engine
+            }
+        };
+
+        self.touch_tts();
+        engine_status::used(kokoro::MODEL_FILE);
+        Ok(engine)
+    }
+
+    /// The macOS system voice engine, created on first use
+    pub async fn get_or_load_system_tts(&self) -> SharedTtsEngine {
+        let mut tts = self.tts.lock().await;
+        let engine = tts
+            .engines
+            .entry(TtsEngineKind::System)
+            .or_insert_with(|| Arc::new(TokioMutex::new(Box::new(SystemTtsEngine::new()))))
+            .clone();
+        self.touch_tts();
+        engine
+    }
+
+    /// Note that a TTS engine was just used, putting off idle eviction
+    pub(crate) fn touch_tts(&self) {
+        if let Ok(mut last_used) = self.tts_last_used.lock() {
+            *last_used = Some(Instant::now());
+        }
+    }
+
+    /// Drop every loaded Whisper model, e.g. after their files were deleted
+    /// or the GPU settings changed. Transcriptions already holding an
+    /// engine finish with it.
+    pub async fn invalidate_whisper(&self) {
+        self.whisper.unload_all().await;
+    }
+
+    /// Drop the loaded Kokoro engine so its next use loads it again.
+    /// Speech already holding the engine finishes with it.
+    pub async fn invalidate_kokoro(&self) {
+        let mut tts = self.tts.lock().await;
+        if tts.engines.remove(&TtsEngineKind::Kokoro).is_some() {
+            tracing::info!("Unloading Kokoro TTS engine");
+            engine_status::unloaded(kokoro::MODEL_FILE);
+        }
+        tts.kokoro_dir = None;
+    }
+
+    /// Drop every loaded engine
+    pub async fn invalidate_all(&self) {
+        self.invalidate_whisper().await;
+        let mut tts = self.tts.lock().await;
+        unload_tts(&mut tts);
+        if let Ok(mut last_used) = self.tts_last_used.lock() {
+            *last_used = None;
+        }
+    }
+
+    /// Whether a Whisper model is loaded, without loading one
+    pub async fn is_whisper_loaded(&self) -> bool {
+        self.whisper.is_loaded().await
+    }
+
+    /// Whether any TTS engine is loaded, without loading one
+    pub async fn is_tts_loaded(&self) -> bool {
+        !self.tts.lock().await.engines.is_empty()
+    }
+
+    /// Drop the TTS engines if they haven't been used for `timeout`.
+    /// Skips eviction while a synthesis is holding an engine.
+    pub(crate) async fn evict_idle_tts(&self, timeout: Duration) {
+        let idle = self
+            .tts_last_used
+            .lock()
+            .map(|last_used| last_used.is_some_and(|t| t.elapsed() >= timeout))
+            .unwrap_or(false);
+        if !idle {
+            return;
+        }
+
+        let Ok(mut tts) = self.tts.try_lock() else {
+            return;
+        };
+        if tts
+            .engines
+            .values()
+            .any(|engine| engine.try_lock().is_err())
+        {
+            return;
+        }
+        if !tts.engines.is_empty() {
+            unload_tts(&mut tts);
+            tracing::info!("Unloaded idle TTS engines");
+        }
+        if let Ok(mut last_used) = self.tts_last_used.lock() {
+            *last_used = None;
+        }
+    }
+}
+
+/// Drop every engine in `tts`, reporting Kokoro as unloaded
+fn unload_tts(tts: &mut LoadedTts) {
+    if tts.engines.contains_key(&TtsEngineKind::Kokoro) {
+        engine_status::unloaded(kokoro::MODEL_FILE);
+    }
+    tts.engines.clear();
+    tts.kokoro_dir = None;
+}
+
+/// Size of the Kokoro model and voice files in `model_dir`, roughly what
+/// the engine takes in memory
+fn kokoro_model_bytes(model_dir: &Path) -> u64 {
+    [kokoro::MODEL_FILE, VOICES_FILE]
+        .iter()
+        .filter_map(|file| std::fs::metadata(model_dir.join(file)).ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engines::ModelInfo;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct MockStt;
+
+    impl SpeechToText for MockStt {
+        fn transcribe(&self, _audio: &[f32]) -> anyhow::Result<String> {
+            Ok(String::new())
+        }
+
+        fn model_info(&self) -> ModelInfo {
+            ModelInfo {
+                name: "mock".to_string(),
+                size_bytes: 0,
+                loaded: true,
+            }
+        }
+    }
+
+    /// A registry with a mock Whisper loader, and how often it has loaded
+    fn mock_registry() -> (EngineRegistry, Arc<AtomicUsize>) {
+        let loads = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&loads);
+        let whisper = SttProvider::new(move |_path| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(Box::new(MockStt) as Box<dyn SpeechToText>)
+        });
+        (EngineRegistry::with_whisper(whisper), loads)
+    }
+
+    #[tokio::test]
+    async fn test_registry_invalidates_whisper() {
+        let (registry, loads) = mock_registry();
+        assert!(!registry.is_whisper_loaded().await);
+
+        registry.get_or_load_whisper("base.bin").await.unwrap();
+        registry.get_or_load_whisper("base.bin").await.unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+        assert!(registry.is_whisper_loaded().await);
+
+        registry.invalidate_whisper().await;
+        assert!(!registry.is_whisper_loaded().await);
+        registry.get_or_load_whisper("base.bin").await.unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_registry_invalidate_all() {
+        let (registry, _) = mock_registry();
+        registry.get_or_load_whisper("base.bin").await.unwrap();
+        let system = registry.get_or_load_system_tts().await;
+        let again = registry.get_or_load_system_tts().await;
+        assert!(Arc::ptr_eq(&system, &again));
+
+        // Only Kokoro goes; the system voices have no model to reload
+        registry.invalidate_kokoro().await;
+        assert!(registry.is_tts_loaded().await);
+
+        registry.invalidate_all().await;
+        assert!(!registry.is_whisper_loaded().await);
+        assert!(!registry.is_tts_loaded().await);
+    }
+
+    #[tokio::test]
+    async fn test_registry_evicts_idle_tts() {
+        let (registry, _) = mock_registry();
+        registry.get_or_load_system_tts().await;
+
+        registry.evict_idle_tts(Duration::from_secs(60)).await;
+        assert!(registry.is_tts_loaded().await);
+
+        // Not while a synthesis holds the engine
+        let engine = registry.get_or_load_system_tts().await;
+        let held = engine.lock().await;
+        registry.evict_idle_tts(Duration::ZERO).await;
+        assert!(registry.is_tts_loaded().await);
+
+        drop(held);
+        registry.evict_idle_tts(Duration::ZERO).await;
+        assert!(!registry.is_tts_loaded().await);
+    }
+}
@@ -0,0 +1,241 @@
+//! macOS system voices through the `say` command.
+//!
+//! A zero-download alternative to Kokoro: it uses whatever voices are
+//! installed under System Settings > Accessibility > Spoken Content. Speech
+//! is rendered to a temporary WAV file and played through the same player as
+//! Kokoro output, so stopping and the TTS events behave the same.
+
+use anyhow::{anyhow, Result};
+use futures_util::future::BoxFuture;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::audio::processing::read_wav;
+
+use super::kokoro::{MAX_SPEED, MIN_SPEED};
+use super::{AudioBuffer, ModelInfo, TextToSpeech, VoiceInfo};
+
+/// Prefix of system voice ids, e.g. `system:Samantha`
+pub const SYSTEM_VOICE_PREFIX: &str = "system:";
+
+/// Speaking rate of system voices at 1.0x, in words per minute
+const BASE_WORDS_PER_MINUTE: f32 = 175.0;
+
+/// Sample rate `say` renders at, matching Kokoro's output
+const SAMPLE_RATE: u32 = 24000;
+
+// Keeps concurrent renders from sharing a temporary file
+static RENDER_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// An installed system voice as listed by `say -v ?`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SystemVoice {
+    pub name: String,
+    /// Locale as printed by `say`, e.g. `en_US`
+    pub locale: String,
+}
+
+impl SystemVoice {
+    pub fn id(&self) -> String {
+        format!("{}{}", SYSTEM_VOICE_PREFIX, self.name)
+    }
+
+    /// Language tag in the same form as Kokoro voices, e.g. `en-US`
+    pub fn language(&self) -> String {
+        self.locale.replace('_', "-")
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SystemTtsEngine;
+
+impl SystemTtsEngine {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Voices installed on this Mac. Fails where `say` isn't available.
+    pub fn list_voices() -> Result<Vec<SystemVoice>> {
+        let output = std::process::Command::new("say")
+            .args(["-v", "?"])
+            .output()
+            .map_err(|e| anyhow!("Failed to run say: {}", e))?;
+
+        if !output.status.success() {
+            return Err(anyhow!(
+                "say failed to list voices: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        Ok(parse_voice_list(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    async fn render(&self, text: &str, voice: &str, speed: f32) -> Result<AudioBuffer> {
+        let path = render_path();
+
+        let mut command = Command::new("say");
+        if let Some(name) = voice_name(voice) {
+            command.args(["-v", name]);
+        }
+        command
+            .arg("-r")
+            .arg(words_per_minute(speed).to_string())
+            .arg("-o")
+            .arg(&path)
+            .arg("--file-format=WAVE")
+            .arg(format!("--data-format=LEF32@{}", SAMPLE_RATE))
+            // Read the text from stdin so it is never parsed as options
+            .args(["-f", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+
+        tracing::debug!(
+            "Rendering system speech with voice '{}' at {}x",
+            voice,
+            speed
+        );
+
+        let mut child = command
+            .spawn()
+            .map_err(|e| anyhow!("Failed to run say: {}", e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes()).await?;
+        }
+        let output = child.wait_with_output().await?;
+
+        let result = if output.status.success() {
+            read_wav(&path).map(|(samples, sample_rate)| AudioBuffer::new(samples, sample_rate))
+        } else {
+            Err(anyhow!(
+                "say failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        };
+
+        if let Err(e) = std::fs::remove_file(&path) {
+            tracing::debug!("Failed to remove {:?}: {}", path, e);
+        }
+
+        result
+    }
+}
+
+impl TextToSpeech for SystemTtsEngine {
+    fn synthesize<'a>(
+        &'a mut self,
+        text: &'a str,
+        voice: &'a str,
+        speed: f32,
+    ) -> BoxFuture<'a, Result<AudioBuffer>> {
+        Box::pin(self.render(text, voice, speed))
+    }
+
+    fn available_voices(&self) -> Vec<VoiceInfo> {
+        Self::list_voices()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|voice| VoiceInfo {
+                id: voice.id(),
+                language: voice.language(),
+                name: voice.name,
+            })
+            .collect()
+    }
+
+    fn model_info(&self) -> ModelInfo {
+        ModelInfo {
+            name: "System voices".to_string(),
+            size_bytes: 0,
+            loaded: true,
+        }
+    }
+}
+
+/// Parse `say -v ?` output, one voice per line:
+/// `Eddy (English (UK))  en_GB    # Hello! My name is Eddy.`
+pub fn parse_voice_list(output: &str) -> Vec<SystemVoice> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let entry = line.split_once('#').map_or(line, |(entry, _)| entry);
+            let (name, locale) = entry.trim().rsplit_once(char::is_whitespace)?;
+            let name = name.trim();
+            if name.is_empty() || !locale.contains('_') {
+                return None;
+            }
+
+            Some(SystemVoice {
+                name: name.to_string(),
+                locale: locale.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// `say` voice name for a voice id; `None` uses the system default voice
+fn voice_name(voice_id: &str) -> Option<&str> {
+    let name = voice_id
+        .strip_prefix(SYSTEM_VOICE_PREFIX)
+        .unwrap_or(voice_id)
+        .trim();
+    (!name.is_empty()).then_some(name)
+}
+
+/// Map a speed multiplier to the `say -r` rate. Unlike Kokoro, the rate
+/// changes the speaking pace without shifting pitch.
+pub fn words_per_minute(speed: f32) -> u32 {
+    (BASE_WORDS_PER_MINUTE * speed.clamp(MIN_SPEED, MAX_SPEED)).round() as u32
+}
+
+fn render_path() -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "blah3-say-{}-{}.wav",
+        std::process::id(),
+        RENDER_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_voice_list() {
+        let output = "\
+Albert              en_US    # Hello! My name is Albert.
+Eddy (English (UK)) en_GB    # Hello! My name is Eddy.
+Amélie              fr_CA    # Bonjour, je m’appelle Amélie.
+
+garbage line
+";
+        let voices = parse_voice_list(output);
+        assert_eq!(voices.len(), 3);
+        assert_eq!(voices[0].name, "Albert");
+        assert_eq!(voices[0].id(), "system:Albert");
+        assert_eq!(voices[1].name, "Eddy (English (UK))");
+        assert_eq!(voices[1].language(), "en-GB");
+        assert_eq!(voices[2].locale, "fr_CA");
+    }
+
+    #[test]
+    fn test_voice_name() {
+        assert_eq!(voice_name("system:Samantha"), Some("Samantha"));
+        assert_eq!(voice_name("Samantha"), Some("Samantha"));
+        assert_eq!(voice_name("system:"), None);
+    }
+
+    #[test]
+    fn test_words_per_minute() {
+        assert_eq!(words_per_minute(1.0), 175);
+        assert_eq!(words_per_minute(2.0), 350);
+        assert_eq!(words_per_minute(0.5), 88);
+        // Clamped to the same range as Kokoro
+        assert_eq!(words_per_minute(0.0), words_per_minute(MIN_SPEED));
+        assert_eq!(words_per_minute(10.0), words_per_minute(MAX_SPEED));
+    }
+}
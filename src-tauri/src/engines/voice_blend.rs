@@ -0,0 +1,472 @@
+//! Blended Kokoro voices, e.g. `af_bella:0.5+af_sky:0.5`.
+//!
+//! Kokoro voices are style vectors stored in `voices-v1.0.bin`, an npz
+//! archive with one float32 `.npy` array per voice. A blend is the weighted
+//! sum of its component vectors, written back into a voice pack under a
+//! generated name so the engine can use it like any other voice.
+
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// Most voices a single blend may mix
+pub const MAX_BLEND_VOICES: usize = 3;
+
+const NPY_MAGIC: &[u8] = b"\x93NUMPY";
+
+/// A voice's style vectors, as stored in the voice pack
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyleArray {
+    pub shape: Vec<usize>,
+    pub data: Vec<f32>,
+}
+
+/// All voices in a voice pack, keyed by voice id
+pub type VoicePack = BTreeMap<String, StyleArray>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlendComponent {
+    pub voice: String,
+    pub weight: f32,
+}
+
+/// A parsed blend with weights normalized to sum to 1.0
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoiceBlend {
+    components: Vec<BlendComponent>,
+}
+
+/// Whether a voice id uses the blend syntax rather than naming a single voice
+pub fn is_blend(voice_id: &str) -> bool {
+    voice_id.contains('+')
+}
+
+impl VoiceBlend {
+    /// Parse `voice[:weight]+voice[:weight]...`. A missing weight counts as 1.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut components = Vec::new();
+
+        for part in spec.split('+') {
+            let part = part.trim();
+            let (voice, weight) = match part.split_once(':') {
+                Some((voice, weight)) => {
+                    let weight: f32 = weight.trim().parse().map_err(|_| {
+                        anyhow!("Invalid weight '{}' in voice blend", weight.trim())
+                    })?;
+                    (voice.trim(), weight)
+                }
+                None => (part, 1.0),
+            };
+
+            if voice.is_empty() {
+                return Err(anyhow!("Empty voice name in blend '{}'", spec));
+            }
+            if !weight.is_finite() || weight <= 0.0 {
+                return Err(anyhow!("Weight for '{}' must be a positive number", voice));
+            }
+            if components.iter().any(|c: &BlendComponent| c.voice == voice) {
+                return Err(anyhow!("Voice '{}' appears more than once in blend", voice));
+            }
+
+            components.push(BlendComponent {
+                voice: voice.to_string(),
+                weight,
+            });
+        }
+
+        if components.len() < 2 {
+            return Err(anyhow!("A voice blend needs at least 2 voices"));
+        }
+        if components.len() > MAX_BLEND_VOICES {
+            return Err(anyhow!(
+                "A voice blend can mix at most {} voices",
+                MAX_BLEND_VOICES
+            ));
+        }
+
+        let total: f32 = components.iter().map(|c| c.weight).sum();
+        for component in &mut components {
+            component.weight /= total;
+        }
+        // Order doesn't change the result, so "a+b" and "b+a" share a name
+        components.sort_by(|a, b| a.voice.cmp(&b.voice));
+
+        Ok(Self { components })
+    }
+
+    pub fn components(&self) -> &[BlendComponent] {
+        &self.components
+    }
+
+    /// Check that every component names a voice in `available`
+    pub fn validate<S: AsRef<str>>(&self, available: &[S]) -> Result<()> {
+        for component in &self.components {
+            if !available.iter().any(|v| v.as_ref() == component.voice) {
+                return Err(anyhow!("Unknown voice in blend: {}", component.voice));
+            }
+        }
+        Ok(())
+    }
+
+    /// Name the blended style is stored under in the voice pack
+    pub fn style_name(&self) -> String {
+        let parts: Vec<String> = self
+            .components
+            .iter()
+            .map(|c| format!("{}_{}", c.voice, (c.weight * 1000.0).round() as u32))
+            .collect();
+        format!("blend_{}", parts.join("_"))
+    }
+
+    /// Interpolate the component style vectors from `pack`
+    pub fn mix(&self, pack: &VoicePack) -> Result<StyleArray> {
+        let mut mixed: Option<StyleArray> = None;
+
+        for component in &self.components {
+            let style = pack
+                .get(&component.voice)
+                .ok_or_else(|| anyhow!("Unknown voice in blend: {}", component.voice))?;
+
+            let mixed = mixed.get_or_insert_with(|| StyleArray {
+                shape: style.shape.clone(),
+                data: vec![0.0; style.data.len()],
+            });
+            if mixed.shape != style.shape {
+                return Err(anyhow!(
+                    "Voice '{}' has shape {:?}, expected {:?}",
+                    component.voice,
+                    style.shape,
+                    mixed.shape
+                ));
+            }
+
+            for (out, value) in mixed.data.iter_mut().zip(&style.data) {
+                *out += value * component.weight;
+            }
+        }
+
+        mixed.ok_or_else(|| anyhow!("Empty voice blend"))
+    }
+}
+
+/// Voice ids in a voice pack, without reading the style data
+pub fn read_voice_names(path: &Path) -> Result<Vec<String>> {
+    let archive = zip::ZipArchive::new(File::open(path)?)?;
+    Ok(archive
+        .file_names()
+        .filter_map(|name| name.strip_suffix(".npy"))
+        .map(|name| name.to_string())
+        .collect())
+}
+
+pub fn read_voice_pack(path: &Path) -> Result<VoicePack> {
+    let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+    let mut pack = VoicePack::new();
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let Some(voice) = entry.name().strip_suffix(".npy").map(|s| s.to_string()) else {
+            continue;
+        };
+
+        let mut bytes = Vec::with_capacity(entry.size() as usize);
+        entry.read_to_end(&mut bytes)?;
+        let style = parse_npy(&bytes).map_err(|e| anyhow!("Voice '{}': {}", voice, e))?;
+        pack.insert(voice, style);
+    }
+
+    Ok(pack)
+}
+
+/// Write a voice pack in the same npz layout `voices-v1.0.bin` uses
+pub fn write_voice_pack(path: &Path, pack: &VoicePack) -> Result<()> {
+    let mut zip = zip::ZipWriter::new(File::create(path)?);
+    let options =
+        zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+
+    for (voice, style) in pack {
+        zip.start_file(format!("{}.npy", voice), options)?;
+        zip.write_all(&encode_npy(style))?;
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+/// Decode a little-endian float32, C-ordered `.npy` array
+fn parse_npy(bytes: &[u8]) -> Result<StyleArray> {
+    if bytes.len() < 10 || &bytes[..6] != NPY_MAGIC {
+        return Err(anyhow!("Not an npy array"));
+    }
+
+    let (header_len, header_start) = match bytes[6] {
+        1 => (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10),
+        2 | 3 if bytes.len() >= 12 => (
+            u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize,
+            12,
+        ),
+        version => return Err(anyhow!("Unsupported npy version {}", version)),
+    };
+    let data_start = header_start + header_len;
+    let header = bytes
+        .get(header_start..data_start)
+        .ok_or_else(|| anyhow!("Truncated npy header"))?;
+    let header = std::str::from_utf8(header)?;
+
+    if !header.contains("'descr': '<f4'") {
+        return Err(anyhow!("Expected float32 data, header: {}", header.trim()));
+    }
+    if !header.contains("'fortran_order': False") {
+        return Err(anyhow!("Fortran-ordered arrays aren't supported"));
+    }
+
+    let shape_start = header
+        .find("'shape': (")
+        .ok_or_else(|| anyhow!("Missing shape in npy header"))?
+        + "'shape': (".len();
+    let shape_end = header[shape_start..]
+        .find(')')
+        .ok_or_else(|| anyhow!("Malformed shape in npy header"))?
+        + shape_start;
+    let shape = header[shape_start..shape_end]
+        .split(',')
+        .map(str::trim)
+        .filter(|dim| !dim.is_empty())
+        .map(|dim| dim.parse::<usize>())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let data = &bytes[data_start..];
+    let expected = shape.iter().product::<usize>() * 4;
+    if data.len() != expected {
+        return Err(anyhow!(
+            "Expected {} bytes of data, found {}",
+            expected,
+            data.len()
+        ));
+    }
+
+    let data = data
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect();
+    Ok(StyleArray { shape, data })
+}
+
+fn encode_npy(style: &StyleArray) -> Vec<u8> {
+    let shape = match style.shape.as_slice() {
+        [dim] => format!("({},)", dim),
+        dims => format!(
+            "({})",
+            dims.iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+    let mut header = format!(
+        "{{'descr': '<f4', 'fortran_order': False, 'shape': {}, }}",
+        shape
+    );
+    // numpy pads the header with spaces so the data starts 64-byte aligned
+    let unpadded = NPY_MAGIC.len() + 4 + header.len() + 1;
+    header.push_str(&" ".repeat((64 - unpadded % 64) % 64));
+    header.push('\n');
+
+    let mut bytes = Vec::with_capacity(NPY_MAGIC.len() + 4 + header.len() + style.data.len() * 4);
+    bytes.extend_from_slice(NPY_MAGIC);
+    bytes.extend_from_slice(&[1, 0]);
+    bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(header.as_bytes());
+    for value in &style.data {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn weights(blend: &VoiceBlend) -> Vec<(&str, f32)> {
+        blend
+            .components()
+            .iter()
+            .map(|c| (c.voice.as_str(), c.weight))
+            .collect()
+    }
+
+    #[test]
+    fn test_is_blend() {
+        assert!(is_blend("af_bella:0.5+af_sky:0.5"));
+        assert!(is_blend("af_bella+af_sky"));
+        assert!(!is_blend("af_bella"));
+    }
+
+    #[test]
+    fn test_parse_blend() {
+        let blend = VoiceBlend::parse("af_bella:0.5+af_sky:0.5").unwrap();
+        assert_eq!(weights(&blend), vec![("af_bella", 0.5), ("af_sky", 0.5)]);
+    }
+
+    #[test]
+    fn test_parse_normalizes_weights() {
+        let blend = VoiceBlend::parse("af_bella:3 + af_sky:1").unwrap();
+        assert_eq!(weights(&blend), vec![("af_bella", 0.75), ("af_sky", 0.25)]);
+
+        let blend = VoiceBlend::parse("af_bella:0.2+af_sky:0.2").unwrap();
+        assert_eq!(weights(&blend), vec![("af_bella", 0.5), ("af_sky", 0.5)]);
+    }
+
+    #[test]
+    fn test_parse_missing_weight_defaults_to_one() {
+        let blend = VoiceBlend::parse("af_bella+af_sky:3").unwrap();
+        assert_eq!(weights(&blend), vec![("af_bella", 0.25), ("af_sky", 0.75)]);
+    }
+
+    #[test]
+    fn test_parse_orders_components() {
+        let a = VoiceBlend::parse("af_sky:0.3+af_bella:0.7").unwrap();
+        let b = VoiceBlend::parse("af_bella:0.7+af_sky:0.3").unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.style_name(), "blend_af_bella_700_af_sky_300");
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_blends() {
+        for spec in [
+            "af_bella",
+            "af_bella:1",
+            "af_bella:0.5+",
+            "+af_sky",
+            "af_bella:abc+af_sky",
+            "af_bella:0+af_sky:1",
+            "af_bella:-1+af_sky:1",
+            "af_bella:NaN+af_sky:1",
+            "af_bella+af_bella",
+            "af_bella+af_sky+am_adam+bf_emma",
+        ] {
+            assert!(VoiceBlend::parse(spec).is_err(), "should reject {}", spec);
+        }
+    }
+
+    #[test]
+    fn test_parse_allows_three_voices() {
+        let blend = VoiceBlend::parse("af_bella+af_sky+am_adam").unwrap();
+        assert_eq!(blend.components().len(), 3);
+        let total: f32 = blend.components().iter().map(|c| c.weight).sum();
+        assert!((total - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_validate_known_voices() {
+        let blend = VoiceBlend::parse("af_bella+af_sky").unwrap();
+        assert!(blend.validate(&["af_bella", "af_sky", "am_adam"]).is_ok());
+
+        let err = blend.validate(&["af_bella"]).unwrap_err();
+        assert!(err.to_string().contains("af_sky"));
+    }
+
+    #[test]
+    fn test_mix_interpolates_styles() {
+        let mut pack = VoicePack::new();
+        pack.insert(
+            "af_bella".to_string(),
+            StyleArray {
+                shape: vec![2, 1],
+                data: vec![1.0, 2.0],
+            },
+        );
+        pack.insert(
+            "af_sky".to_string(),
+            StyleArray {
+                shape: vec![2, 1],
+                data: vec![3.0, 6.0],
+            },
+        );
+
+        let blend = VoiceBlend::parse("af_bella:0.75+af_sky:0.25").unwrap();
+        let mixed = blend.mix(&pack).unwrap();
+        assert_eq!(mixed.shape, vec![2, 1]);
+        assert_eq!(mixed.data, vec![1.5, 3.0]);
+    }
+
+    #[test]
+    fn test_mix_rejects_mismatched_shapes() {
+        let mut pack = VoicePack::new();
+        pack.insert(
+            "af_bella".to_string(),
+            StyleArray {
+                shape: vec![2],
+                data: vec![1.0, 2.0],
+            },
+        );
+        pack.insert(
+            "af_sky".to_string(),
+            StyleArray {
+                shape: vec![3],
+                data: vec![1.0, 2.0, 3.0],
+            },
+        );
+
+        let blend = VoiceBlend::parse("af_bella+af_sky").unwrap();
+        assert!(blend.mix(&pack).is_err());
+    }
+
+    #[test]
+    fn test_npy_roundtrip() {
+        let style = StyleArray {
+            shape: vec![2, 1, 3],
+            data: vec![0.5, -1.25, 2.0, 0.0, 3.5, -0.125],
+        };
+        let bytes = encode_npy(&style);
+
+        // Data is 64-byte aligned, like numpy writes it
+        let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        assert_eq!((10 + header_len) % 64, 0);
+        assert_eq!(parse_npy(&bytes).unwrap(), style);
+    }
+
+    #[test]
+    fn test_parse_npy_rejects_other_dtypes() {
+        let mut bytes = encode_npy(&StyleArray {
+            shape: vec![1],
+            data: vec![1.0],
+        });
+        let header_end = bytes.len() - 4;
+        let header = String::from_utf8(bytes[10..header_end].to_vec()).unwrap();
+        bytes.splice(10..header_end, header.replace("<f4", "<f8").into_bytes());
+
+        assert!(parse_npy(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_voice_pack_roundtrip() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("voices.bin");
+
+        let mut pack = VoicePack::new();
+        pack.insert(
+            "af_bella".to_string(),
+            StyleArray {
+                shape: vec![2],
+                data: vec![1.0, 2.0],
+            },
+        );
+        pack.insert(
+            "blend_af_bella_500_af_sky_500".to_string(),
+            StyleArray {
+                shape: vec![2],
+                data: vec![3.0, 4.0],
+            },
+        );
+        write_voice_pack(&path, &pack).unwrap();
+
+        assert_eq!(read_voice_pack(&path).unwrap(), pack);
+        let mut names = read_voice_names(&path).unwrap();
+        names.sort();
+        assert_eq!(names, vec!["af_bella", "blend_af_bella_500_af_sky_500"]);
+    }
+}
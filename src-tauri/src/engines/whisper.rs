@@ -1,9 +1,278 @@
 use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex, OnceLock};
 use whisper_rs::{
     FullParams, SamplingStrategy, SegmentCallbackData, WhisperContext, WhisperContextParameters,
+    WhisperState,
 };
 
-use super::{ModelInfo, SpeechToText};
+use crate::audio::processing::audio_duration_secs;
+
+use super::chunking::transcribe_in_chunks;
+use super::confidence::{mean_confidence, word_confidences, TokenProb};
+use super::{
+    DecodeOptions, DiarizedSegment, ModelInfo, ProgressCallback, SegmentCallback, SpeechToText,
+    Transcript, TranscriptSegment, WordConfidence, STT_SAMPLE_RATE,
+};
+
+/// Languages Whisper can transcribe, as (code, name), in the order of the
+/// language table in whisper.cpp
+const WHISPER_LANGUAGES: &[(&str, &str)] = &[
+    ("en", "English"),
+    ("zh", "Chinese"),
+    ("de", "German"),
+    ("es", "Spanish"),
+    ("ru", "Russian"),
+    ("ko", "Korean"),
+    ("fr", "French"),
+    ("ja", "Japanese"),
+    ("pt", "Portuguese"),
+    ("tr", "Turkish"),
+    ("pl", "Polish"),
+    ("ca", "Catalan"),
+    ("nl", "Dutch"),
+    ("ar", "Arabic"),
+    ("sv", "Swedish"),
+    ("it", "Italian"),
+    ("id", "Indonesian"),
+    ("hi", "Hindi"),
+    ("fi", "Finnish"),
+    ("vi", "Vietnamese"),
+    ("he", "Hebrew"),
+    ("uk", "Ukrainian"),
+    ("el", "Greek"),
+    ("ms", "Malay"),
+    ("cs", "Czech"),
+    ("ro", "Romanian"),
+    ("da", "Danish"),
+    ("hu", "Hungarian"),
+    ("ta", "Tamil"),
+    ("no", "Norwegian"),
+    ("th", "Thai"),
+    ("ur", "Urdu"),
+    ("hr", "Croatian"),
+    ("bg", "Bulgarian"),
+    ("lt", "Lithuanian"),
+    ("la", "Latin"),
+    ("mi", "Maori"),
+    ("ml", "Malayalam"),
+    ("cy", "Welsh"),
+    ("sk", "Slovak"),
+    ("te", "Telugu"),
+    ("fa", "Persian"),
+    ("lv", "Latvian"),
+    ("bn", "Bengali"),
+    ("sr", "Serbian"),
+    ("az", "Azerbaijani"),
+    ("sl", "Slovenian"),
+    ("kn", "Kannada"),
+    ("et", "Estonian"),
+    ("mk", "Macedonian"),
+    ("br", "Breton"),
+    ("eu", "Basque"),
+    ("is", "Icelandic"),
+    ("hy", "Armenian"),
+    ("ne", "Nepali"),
+    ("mn", "Mongolian"),
+    ("bs", "Bosnian"),
+    ("kk", "Kazakh"),
+    ("sq", "Albanian"),
+    ("sw", "Swahili"),
+    ("gl", "Galician"),
+    ("mr", "Marathi"),
+    ("pa", "Punjabi"),
+    ("si", "Sinhala"),
+    ("km", "Khmer"),
+    ("sn", "Shona"),
+    ("yo", "Yoruba"),
+    ("so", "Somali"),
+    ("af", "Afrikaans"),
+    ("oc", "Occitan"),
+    ("ka", "Georgian"),
+    ("be", "Belarusian"),
+    ("tg", "Tajik"),
+    ("sd", "Sindhi"),
+    ("gu", "Gujarati"),
+    ("am", "Amharic"),
+    ("yi", "Yiddish"),
+    ("lo", "Lao"),
+    ("uz", "Uzbek"),
+    ("fo", "Faroese"),
+    ("ht", "Haitian Creole"),
+    ("ps", "Pashto"),
+    ("tk", "Turkmen"),
+    ("nn", "Nynorsk"),
+    ("mt", "Maltese"),
+    ("sa", "Sanskrit"),
+    ("lb", "Luxembourgish"),
+    ("my", "Myanmar"),
+    ("bo", "Tibetan"),
+    ("tl", "Tagalog"),
+    ("mg", "Malagasy"),
+    ("as", "Assamese"),
+    ("tt", "Tatar"),
+    ("haw", "Hawaiian"),
+    ("ln", "Lingala"),
+    ("ha", "Hausa"),
+    ("ba", "Bashkir"),
+    ("jw", "Javanese"),
+    ("su", "Sundanese"),
+    ("yue", "Cantonese"),
+];
+
+/// Only large-v3 models were trained with the Cantonese token
+const CANTONESE_CODE: &str = "yue";
+
+static LANGUAGES: OnceLock<Vec<LanguageInfo>> = OnceLock::new();
+
+/// A language Whisper can transcribe
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LanguageInfo {
+    /// Code passed to Whisper, e.g. `de`
+    pub code: String,
+    pub name: String,
+}
+
+/// Whether `model_id` is an English-only model such as `ggml-base.en.bin`
+pub fn is_english_only_model(model_id: &str) -> bool {
+    model_id.contains(".en.") || model_id.contains(".en-") || model_id.ends_with(".en")
+}
+
+/// Which languages a Whisper model was trained on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelLanguageSupport {
+    EnglishOnly,
+    Multilingual,
+}
+
+impl ModelLanguageSupport {
+    /// Language support of the model file `model_id`, from its name
+    pub fn for_model(model_id: &str) -> Self {
+        if is_english_only_model(model_id) {
+            Self::EnglishOnly
+        } else {
+            Self::Multilingual
+        }
+    }
+}
+
+/// Start of a whisper.cpp GGML model: "ggml" written as a little-endian u32
+const GGML_MAGIC: &[u8; 4] = b"lmgg";
+
+/// The same magic read as text, as some converters write it
+const GGML_MAGIC_TEXT: &[u8; 4] = b"ggml";
+
+/// Start of a GGUF model
+const GGUF_MAGIC: &[u8; 4] = b"GGUF";
+
+/// Vocabulary size of the English-only models
+const ENGLISH_ONLY_VOCAB: i32 = 51864;
+
+/// Model size from the start of a Whisper model file, e.g. `base.en` or
+/// `large-v3`. Fails when the file isn't GGML or GGUF; GGUF files and
+/// unknown layer counts give `None`.
+pub fn model_type_from_header(header: &[u8]) -> Result<Option<String>> {
+    let magic = header
+        .get(..4)
+        .ok_or_else(|| anyhow!("File is too short to be a Whisper model"))?;
+    if magic == GGUF_MAGIC {
+        return Ok(None);
+    }
+    if magic != GGML_MAGIC && magic != GGML_MAGIC_TEXT {
+        return Err(anyhow!("Not a GGML or GGUF model file"));
+    }
+
+    // Hyperparameters follow the magic as little-endian i32s
+    let hparam = |index: usize| {
+        let start = 4 + index * 4;
+        header
+            .get(start..start + 4)
+            .map(|bytes| i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .ok_or_else(|| anyhow!("Model header is truncated"))
+    };
+    let n_vocab = hparam(0)?;
+    let n_audio_layer = hparam(4)?;
+    let n_mels = hparam(9)?;
+
+    let size = match n_audio_layer {
+        4 => "tiny",
+        6 => "base",
+        12 => "small",
+        24 => "medium",
+        32 if n_mels == 128 => "large-v3",
+        32 => "large",
+        _ => return Ok(None),
+    };
+    if n_vocab == ENGLISH_ONLY_VOCAB {
+        Ok(Some(format!("{}.en", size)))
+    } else {
+        Ok(Some(size.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SegmentMode {
+    /// Normal decoding with context carried between segments
+    Multi,
+    /// One segment, no context: faster and more accurate for short commands
+    Single,
+}
+
+/// Longest initial prompt accepted. Whisper only reads the last 224 tokens
+/// of a prompt, which is roughly this many characters of English.
+pub const MAX_INITIAL_PROMPT_CHARS: usize = 1000;
+
+/// The prompt from `options` worth passing to Whisper, if any
+pub fn initial_prompt(options: &DecodeOptions) -> Option<&str> {
+    options
+        .initial_prompt
+        .as_deref()
+        .map(str::trim)
+        .filter(|prompt| !prompt.is_empty())
+}
+
+/// Pick the segment mode for a clip of `audio_secs` seconds
+pub fn select_segment_mode(audio_secs: f32, short_utterance_secs: f32) -> SegmentMode {
+    if short_utterance_secs > 0.0 && audio_secs < short_utterance_secs {
+        SegmentMode::Single
+    } else {
+        SegmentMode::Multi
+    }
+}
+
+/// Most decoding threads accepted in `WhisperAdvancedConfig`
+pub const MAX_WHISPER_THREADS: u32 = 64;
+
+/// Whisper parameters safe to expose to power users. `use_gpu` and
+/// `flash_attention` are fixed when a model is loaded; `n_threads` applies
+/// to each decode.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WhisperAdvancedConfig {
+    /// Run the model on the GPU (Metal) when one is available
+    pub use_gpu: bool,
+    pub flash_attention: bool,
+    /// Threads to decode with; 0 leaves the choice to whisper.cpp
+    pub n_threads: u32,
+}
+
+impl Default for WhisperAdvancedConfig {
+    fn default() -> Self {
+        Self {
+            use_gpu: true,
+            flash_attention: false,
+            n_threads: 0,
+        }
+    }
+}
+
+impl WhisperAdvancedConfig {
+    /// Whether a model loaded with `self` has to be loaded again to use
+    /// `other`
+    pub fn needs_reload(&self, other: &WhisperAdvancedConfig) -> bool {
+        self.use_gpu != other.use_gpu || self.flash_attention != other.flash_attention
+    }
+}
 
 pub struct WhisperEngine {
     ctx: WhisperContext,
@@ -12,9 +281,19 @@ pub struct WhisperEngine {
 
 impl WhisperEngine {
     pub fn new(model_path: &str) -> Result<Self> {
+        Self::with_config(model_path, &WhisperAdvancedConfig::default())
+    }
+
+    /// Load the model at `model_path` with the GPU and attention choices
+    /// in `config`
+    pub fn with_config(model_path: &str, config: &WhisperAdvancedConfig) -> Result<Self> {
         tracing::info!("Loading Whisper model from: {}", model_path);
 
-        let ctx = WhisperContext::new_with_params(model_path, WhisperContextParameters::default())
+        let mut params = WhisperContextParameters::default();
+        params
+            .use_gpu(config.use_gpu)
+            .flash_attn(config.flash_attention);
+        let ctx = WhisperContext::new_with_params(model_path, params)
             .map_err(|e| anyhow!("Failed to load Whisper model: {}", e))?;
 
         tracing::info!("Whisper model loaded successfully");
@@ -25,7 +304,63 @@ impl WhisperEngine {
         })
     }
 
-    pub fn transcribe(&self, audio: &[f32]) -> Result<String> {
+    /// Every language in Whisper's language table
+    pub fn supported_languages() -> Vec<LanguageInfo> {
+        LANGUAGES
+            .get_or_init(|| {
+                WHISPER_LANGUAGES
+                    .iter()
+                    .map(|(code, name)| LanguageInfo {
+                        code: code.to_string(),
+                        name: name.to_string(),
+                    })
+                    .collect()
+            })
+            .clone()
+    }
+
+    /// Languages the model file `model_id` can transcribe: English-only
+    /// (`.en`) models return just English.
+    pub fn languages_for_model(model_id: &str) -> Vec<LanguageInfo> {
+        let languages = Self::supported_languages();
+        if ModelLanguageSupport::for_model(model_id) == ModelLanguageSupport::EnglishOnly {
+            return languages.into_iter().filter(|l| l.code == "en").collect();
+        }
+
+        let has_cantonese = model_id.contains("large-v3");
+        languages
+            .into_iter()
+            .filter(|l| has_cantonese || l.code != CANTONESE_CODE)
+            .collect()
+    }
+
+    /// Whether the loaded model was trained on English only or on every
+    /// language, going by its file name
+    pub fn model_language(&self) -> ModelLanguageSupport {
+        let file_name = std::path::Path::new(&self.model_path)
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        ModelLanguageSupport::for_model(&file_name)
+    }
+
+    /// Whether the loaded model can transcribe languages other than English
+    pub fn is_multilingual(&self) -> bool {
+        self.model_language() == ModelLanguageSupport::Multilingual
+    }
+
+    /// Transcribe with an initial prompt, e.g. a list of product names or
+    /// medical terms the dictation is likely to contain
+    pub fn transcribe_with_prompt(&self, audio: &[f32], initial_prompt: &str) -> Result<String> {
+        let options = DecodeOptions {
+            initial_prompt: Some(initial_prompt.to_string()),
+            ..DecodeOptions::default()
+        };
+        self.transcribe_with_options(audio, &options)
+    }
+
+    /// Build the decoding parameters shared by every transcription mode.
+    fn default_params<'a, 'b>(audio: &[f32], options: &DecodeOptions) -> FullParams<'a, 'b> {
         let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
 
         params.set_language(Some("en"));
@@ -34,8 +369,34 @@ impl WhisperEngine {
         params.set_print_realtime(false);
         params.set_print_timestamps(false);
         params.set_suppress_blank(true);
-        params.set_single_segment(false);
+        params.set_suppress_non_speech_tokens(options.suppress_non_speech_tokens);
+        if let Some(prompt) = initial_prompt(options) {
+            params.set_initial_prompt(prompt);
+        }
+        if options.n_threads > 0 {
+            params.set_n_threads(options.n_threads as i32);
+        }
 
+        let audio_secs = audio_duration_secs(audio.len(), STT_SAMPLE_RATE);
+        match select_segment_mode(audio_secs, options.short_utterance_secs) {
+            SegmentMode::Single => {
+                tracing::debug!(
+                    "Short utterance ({:.2}s), using single-segment mode",
+                    audio_secs
+                );
+                params.set_single_segment(true);
+                params.set_no_context(true);
+            }
+            SegmentMode::Multi => {
+                params.set_single_segment(false);
+            }
+        }
+
+        params
+    }
+
+    /// Run Whisper over `audio` and return the state holding the segments
+    fn decode(&self, params: FullParams, audio: &[f32]) -> Result<WhisperState> {
         let mut state = self
             .ctx
             .create_state()
@@ -45,6 +406,11 @@ impl WhisperEngine {
             .full(params, audio)
             .map_err(|e| anyhow!("Transcription failed: {}", e))?;
 
+        Ok(state)
+    }
+
+    /// Concatenated text of every decoded segment
+    fn full_text(state: &WhisperState) -> Result<String> {
         let num_segments = state
             .full_n_segments()
             .map_err(|e| anyhow!("Failed to get segment count: {}", e))?;
@@ -60,51 +426,101 @@ impl WhisperEngine {
         Ok(text.trim().to_string())
     }
 
-    /// Transcribe audio with a callback fired as each segment is decoded.
-    /// The callback receives `SegmentCallbackData` with segment text and timestamps.
-    pub fn transcribe_streaming<F>(&self, audio: &[f32], on_segment: F) -> Result<String>
-    where
-        F: FnMut(SegmentCallbackData) + 'static,
-    {
-        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
-
-        params.set_language(Some("en"));
-        params.set_print_progress(false);
-        params.set_print_special(false);
-        params.set_print_realtime(false);
-        params.set_print_timestamps(false);
-        params.set_suppress_blank(true);
-        params.set_single_segment(false);
-        params.set_segment_callback_safe_lossy(on_segment);
+    /// Words of segment `i` scored from the probability of each token
+    fn segment_words(state: &WhisperState, i: i32) -> Result<Vec<WordConfidence>> {
+        let num_tokens = state
+            .full_n_tokens(i)
+            .map_err(|e| anyhow!("Failed to get token count of segment {}: {}", i, e))?;
 
-        let mut state = self
-            .ctx
-            .create_state()
-            .map_err(|e| anyhow!("Failed to create Whisper state: {}", e))?;
+        let mut tokens = Vec::with_capacity(num_tokens.max(0) as usize);
+        for j in 0..num_tokens {
+            // Lossy: a multi-byte character can be split across tokens
+            let text = state
+                .full_get_token_text_lossy(i, j)
+                .map_err(|e| anyhow!("Failed to get token {} of segment {}: {}", j, i, e))?;
+            let prob = state
+                .full_get_token_prob(i, j)
+                .map_err(|e| anyhow!("Failed to get probability of token {}: {}", j, e))?;
+            tokens.push(TokenProb::new(text, prob));
+        }
 
-        state
-            .full(params, audio)
-            .map_err(|e| anyhow!("Transcription failed: {}", e))?;
+        Ok(word_confidences(&tokens))
+    }
 
+    /// Decoded segments paired with the speaker-turn flag of each.
+    /// Timestamps are converted from Whisper's 10 ms units to milliseconds.
+    fn segments(state: &WhisperState) -> Result<Vec<(TranscriptSegment, bool)>> {
         let num_segments = state
             .full_n_segments()
             .map_err(|e| anyhow!("Failed to get segment count: {}", e))?;
 
-        let mut text = String::new();
+        let mut segments = Vec::with_capacity(num_segments.max(0) as usize);
         for i in 0..num_segments {
-            let segment = state
+            let text = state
                 .full_get_segment_text(i)
                 .map_err(|e| anyhow!("Failed to get segment {}: {}", i, e))?;
-            text.push_str(&segment);
+            let t0 = state
+                .full_get_segment_t0(i)
+                .map_err(|e| anyhow!("Failed to get start time of segment {}: {}", i, e))?;
+            let t1 = state
+                .full_get_segment_t1(i)
+                .map_err(|e| anyhow!("Failed to get end time of segment {}: {}", i, e))?;
+            let turn_next = state.full_get_segment_speaker_turn_next(i);
+            let words = Self::segment_words(state, i)?;
+
+            segments.push((
+                TranscriptSegment {
+                    text: text.trim().to_string(),
+                    start_ms: t0 * 10,
+                    end_ms: t1 * 10,
+                    confidence: mean_confidence(&words),
+                    words,
+                },
+                turn_next,
+            ));
         }
 
-        Ok(text.trim().to_string())
+        Ok(segments)
+    }
+
+    /// Full text and segments of a finished decode
+    fn transcript(state: &WhisperState) -> Result<Transcript> {
+        Ok(Transcript {
+            text: Self::full_text(state)?,
+            segments: Self::segments(state)?
+                .into_iter()
+                .map(|(segment, _)| segment)
+                .collect(),
+        })
     }
 }
 
+/// Number speakers from segments paired with Whisper's "speaker turn next"
+/// flag: the segment after a turn starts a new speaker.
+pub fn assign_speakers(
+    segments: impl IntoIterator<Item = (TranscriptSegment, bool)>,
+) -> Vec<DiarizedSegment> {
+    let mut speaker_id = 0;
+    segments
+        .into_iter()
+        .map(|(segment, turn_next)| {
+            let diarized = DiarizedSegment {
+                speaker_id,
+                text: segment.text,
+                start_ms: segment.start_ms,
+                end_ms: segment.end_ms,
+            };
+            if turn_next {
+                speaker_id += 1;
+            }
+            diarized
+        })
+        .collect()
+}
+
 impl SpeechToText for WhisperEngine {
     fn transcribe(&self, audio: &[f32]) -> Result<String> {
-        self.transcribe(audio)
+        self.transcribe_with_options(audio, &DecodeOptions::default())
     }
 
     fn model_info(&self) -> ModelInfo {
@@ -118,4 +534,279 @@ impl SpeechToText for WhisperEngine {
             loaded: true,
         }
     }
+
+    fn transcribe_with_options(&self, audio: &[f32], options: &DecodeOptions) -> Result<String> {
+        let state = self.decode(Self::default_params(audio, options), audio)?;
+        Self::full_text(&state)
+    }
+
+    fn transcribe_segments(
+        &self,
+        audio: &[f32],
+        options: &DecodeOptions,
+    ) -> Result<Vec<TranscriptSegment>> {
+        let state = self.decode(Self::default_params(audio, options), audio)?;
+        Ok(Self::segments(&state)?
+            .into_iter()
+            .map(|(segment, _)| segment)
+            .collect())
+    }
+
+    fn transcribe_detailed(&self, audio: &[f32], options: &DecodeOptions) -> Result<Transcript> {
+        let state = self.decode(Self::default_params(audio, options), audio)?;
+        Self::transcript(&state)
+    }
+
+    /// Segments passed to `on_segment` have no word scores yet; those are
+    /// only in the returned transcript.
+    fn transcribe_streaming(
+        &self,
+        audio: &[f32],
+        options: &DecodeOptions,
+        mut on_segment: SegmentCallback,
+    ) -> Result<Transcript> {
+        let mut params = Self::default_params(audio, options);
+        params.set_segment_callback_safe_lossy(move |data: SegmentCallbackData| {
+            on_segment(&TranscriptSegment {
+                text: data.text,
+                start_ms: data.start_timestamp * 10,
+                end_ms: data.end_timestamp * 10,
+                confidence: None,
+                words: Vec::new(),
+            });
+        });
+
+        let state = self.decode(params, audio)?;
+        Self::transcript(&state)
+    }
+
+    /// Clips longer than `options.max_chunk_secs` are decoded a chunk at a
+    /// time, so a long recording reports progress as it goes instead of
+    /// blocking in a single call. Segments are passed to `on_segment` with
+    /// timestamps on the whole clip.
+    fn transcribe_with_progress(
+        &self,
+        audio: &[f32],
+        options: &DecodeOptions,
+        on_segment: SegmentCallback,
+        on_progress: ProgressCallback,
+    ) -> Result<Transcript> {
+        // Shared by the decode of every chunk
+        let on_segment = Arc::new(Mutex::new(on_segment));
+
+        transcribe_in_chunks(
+            audio,
+            options.max_chunk_secs,
+            |chunk, offset_ms| {
+                let on_segment = Arc::clone(&on_segment);
+                let on_chunk_segment = Box::new(move |segment: &TranscriptSegment| {
+                    let segment = TranscriptSegment {
+                        start_ms: segment.start_ms + offset_ms,
+                        end_ms: segment.end_ms + offset_ms,
+                        ..segment.clone()
+                    };
+                    if let Ok(mut on_segment) = on_segment.lock() {
+                        on_segment(&segment);
+                    }
+                });
+                self.transcribe_streaming(chunk, options, on_chunk_segment)
+            },
+            on_progress,
+        )
+    }
+
+    /// Whisper has no real diarization; this relies on the speaker-turn token
+    /// predicted by tinydiarize (`*-tdrz`) models, so other models return a
+    /// single speaker. Good enough to separate an interview, not to tell
+    /// who is speaking.
+    fn transcribe_diarized(
+        &self,
+        audio: &[f32],
+        options: &DecodeOptions,
+    ) -> Result<Vec<DiarizedSegment>> {
+        let mut params = Self::default_params(audio, options);
+        params.set_tdrz_enable(true);
+
+        let state = self.decode(params, audio)?;
+        Ok(assign_speakers(Self::segments(&state)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segment_mode_around_threshold() {
+        assert_eq!(select_segment_mode(1.0, 3.0), SegmentMode::Single);
+        assert_eq!(select_segment_mode(2.99, 3.0), SegmentMode::Single);
+        assert_eq!(select_segment_mode(3.0, 3.0), SegmentMode::Multi);
+        assert_eq!(select_segment_mode(3.01, 3.0), SegmentMode::Multi);
+        assert_eq!(select_segment_mode(30.0, 3.0), SegmentMode::Multi);
+    }
+
+    #[test]
+    fn test_segment_mode_disabled() {
+        // A zero threshold turns the heuristic off entirely
+        assert_eq!(select_segment_mode(0.5, 0.0), SegmentMode::Multi);
+        assert_eq!(select_segment_mode(0.0, 0.0), SegmentMode::Multi);
+    }
+
+    fn segment(text: &str, start_ms: i64, end_ms: i64) -> TranscriptSegment {
+        TranscriptSegment {
+            text: text.to_string(),
+            start_ms,
+            end_ms,
+            confidence: None,
+            words: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_assign_speakers_increments_after_turn() {
+        let diarized = assign_speakers(vec![
+            (segment("How are you?", 0, 1000), true),
+            (segment("Fine, thanks.", 1000, 2000), false),
+            (segment("And you?", 2000, 2500), true),
+            (segment("Good.", 2500, 3000), false),
+        ]);
+
+        let speakers: Vec<u32> = diarized.iter().map(|s| s.speaker_id).collect();
+        assert_eq!(speakers, vec![0, 1, 1, 2]);
+        assert_eq!(diarized[1].text, "Fine, thanks.");
+        assert_eq!(diarized[3].start_ms, 2500);
+        assert_eq!(diarized[3].end_ms, 3000);
+    }
+
+    #[test]
+    fn test_assign_speakers_without_turns() {
+        let diarized = assign_speakers(vec![
+            (segment("One", 0, 500), false),
+            (segment("Two", 500, 900), false),
+        ]);
+        assert!(diarized.iter().all(|s| s.speaker_id == 0));
+        assert!(assign_speakers(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_supported_languages() {
+        let languages = WhisperEngine::supported_languages();
+        assert_eq!(languages.len(), 100);
+        assert_eq!(
+            languages[0],
+            LanguageInfo {
+                code: "en".to_string(),
+                name: "English".to_string(),
+            }
+        );
+        assert!(languages
+            .iter()
+            .any(|l| l.code == "de" && l.name == "German"));
+    }
+
+    #[test]
+    fn test_english_only_models() {
+        assert!(is_english_only_model("ggml-base.en.bin"));
+        assert!(is_english_only_model("ggml-tiny.en-encoder.mlmodelc"));
+        assert!(!is_english_only_model("ggml-base.bin"));
+        assert!(!is_english_only_model("ggml-large-v3.bin"));
+
+        assert_eq!(
+            ModelLanguageSupport::for_model("ggml-base.en.bin"),
+            ModelLanguageSupport::EnglishOnly
+        );
+        assert_eq!(
+            ModelLanguageSupport::for_model("ggml-large-v3.bin"),
+            ModelLanguageSupport::Multilingual
+        );
+
+        let languages = WhisperEngine::languages_for_model("ggml-small.en.bin");
+        assert_eq!(languages.len(), 1);
+        assert_eq!(languages[0].code, "en");
+    }
+
+    /// GGML header with the given vocabulary, audio layers and mel bands
+    fn ggml_header(vocab: i32, layers: i32, mels: i32) -> Vec<u8> {
+        let mut header = GGML_MAGIC.to_vec();
+        for value in [vocab, 1500, 512, 8, layers, 448, 512, 8, 6, mels, 1] {
+            header.extend_from_slice(&value.to_le_bytes());
+        }
+        header
+    }
+
+    #[test]
+    fn test_model_type_from_header() {
+        // Unknown types come back empty
+        let model_type =
+            |header: &[u8]| model_type_from_header(header).unwrap().unwrap_or_default();
+
+        assert_eq!(model_type(&ggml_header(51865, 6, 80)), "base");
+        assert_eq!(model_type(&ggml_header(51864, 4, 80)), "tiny.en");
+        assert_eq!(model_type(&ggml_header(51866, 32, 128)), "large-v3");
+        assert_eq!(model_type(&ggml_header(51865, 7, 80)), "");
+        assert_eq!(model_type(b"GGUF\x03\x00\x00\x00"), "");
+    }
+
+    #[test]
+    fn test_model_type_from_bad_header() {
+        assert!(model_type_from_header(b"").is_err());
+        assert!(model_type_from_header(b"<!DOCTYPE html>").is_err());
+        // Magic without the hyperparameters
+        assert!(model_type_from_header(b"lmgg\x01\x00").is_err());
+    }
+
+    #[test]
+    fn test_cantonese_only_for_large_v3() {
+        let base = WhisperEngine::languages_for_model("ggml-base.bin");
+        assert_eq!(base.len(), 99);
+        assert!(!base.iter().any(|l| l.code == "yue"));
+
+        let large = WhisperEngine::languages_for_model("ggml-large-v3.bin");
+        assert!(large.iter().any(|l| l.code == "yue"));
+    }
+
+    #[test]
+    fn test_default_decode_options() {
+        let options = DecodeOptions::default();
+        assert_eq!(options.short_utterance_secs, 0.0);
+        assert!(!options.suppress_non_speech_tokens);
+        assert_eq!(initial_prompt(&options), None);
+    }
+
+    #[test]
+    fn test_initial_prompt_trimmed() {
+        let with_prompt = |prompt: &str| DecodeOptions {
+            initial_prompt: Some(prompt.to_string()),
+            ..DecodeOptions::default()
+        };
+
+        let options = with_prompt("  Kubernetes, kubectl, etcd.\n");
+        assert_eq!(initial_prompt(&options), Some("Kubernetes, kubectl, etcd."));
+        // A blank prompt is no prompt
+        assert_eq!(initial_prompt(&with_prompt(" \n ")), None);
+    }
+
+    #[test]
+    fn test_whisper_config_needs_reload() {
+        let config = WhisperAdvancedConfig::default();
+        assert!(config.use_gpu);
+        assert_eq!(config.n_threads, 0);
+
+        // Threads apply per decode; the GPU and attention choices don't
+        let threads = WhisperAdvancedConfig {
+            n_threads: 4,
+            ..config.clone()
+        };
+        assert!(!config.needs_reload(&threads));
+        let cpu = WhisperAdvancedConfig {
+            use_gpu: false,
+            ..config.clone()
+        };
+        assert!(config.needs_reload(&cpu));
+        let flash = WhisperAdvancedConfig {
+            flash_attention: true,
+            ..config.clone()
+        };
+        assert!(config.needs_reload(&flash));
+    }
 }
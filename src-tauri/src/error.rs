@@ -0,0 +1,133 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+/// Error returned by every Tauri command.
+///
+/// Serialized to the frontend as `{ code, message }` so the UI can branch on
+/// `code` (e.g. offer a download for `model_not_downloaded`) and show `message`.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum BlahError {
+    #[error("{0}")]
+    ModelNotFound(String),
+    #[error("{0}")]
+    ModelNotDownloaded(String),
+    #[error("{0}")]
+    DownloadFailed(String),
+    #[error("{0}")]
+    TranscriptionFailed(String),
+    #[error("{0}")]
+    SynthesisFailed(String),
+    #[error("{0}")]
+    AudioCaptureFailed(String),
+    #[error("{0}")]
+    AudioPlaybackFailed(String),
+    #[error("{0}")]
+    PermissionDenied(String),
+    /// Accessibility access is needed and hasn't been granted, so the UI can
+    /// link to that pane of System Settings
+    #[error("{0}")]
+    AccessibilityDenied(String),
+    #[error("{0}")]
+    InvalidInput(String),
+    #[error("{0}")]
+    Busy(String),
+    /// A settings value was rejected; `field` names the setting
+    #[error("{message}")]
+    InvalidSetting { field: String, message: String },
+    #[error("{0}")]
+    IoError(String),
+    #[error("{0}")]
+    SettingsError(String),
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl BlahError {
+    /// Stable machine-readable identifier for the error kind
+    pub fn code(&self) -> &'static str {
+        match self {
+            BlahError::ModelNotFound(_) => "model_not_found",
+            BlahError::ModelNotDownloaded(_) => "model_not_downloaded",
+            BlahError::DownloadFailed(_) => "download_failed",
+            BlahError::TranscriptionFailed(_) => "transcription_failed",
+            BlahError::SynthesisFailed(_) => "synthesis_failed",
+            BlahError::AudioCaptureFailed(_) => "audio_capture_failed",
+            BlahError::AudioPlaybackFailed(_) => "audio_playback_failed",
+            BlahError::PermissionDenied(_) => "permission_denied",
+            BlahError::AccessibilityDenied(_) => "accessibility_denied",
+            BlahError::InvalidInput(_) => "invalid_input",
+            BlahError::Busy(_) => "busy",
+            BlahError::InvalidSetting { .. } => "invalid_setting",
+            BlahError::IoError(_) => "io_error",
+            BlahError::SettingsError(_) => "settings_error",
+            BlahError::Internal(_) => "internal",
+        }
+    }
+}
+
+impl Serialize for BlahError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("BlahError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        if let BlahError::InvalidSetting { field, .. } = self {
+            state.serialize_field("field", field)?;
+        }
+        state.end()
+    }
+}
+
+impl From<std::io::Error> for BlahError {
+    fn from(e: std::io::Error) -> Self {
+        BlahError::IoError(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_as_code_and_message() {
+        let error = BlahError::ModelNotDownloaded("Model not found: ggml-base.en.bin".to_string());
+        let json = serde_json::to_value(&error).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "code": "model_not_downloaded",
+                "message": "Model not found: ggml-base.en.bin",
+            })
+        );
+    }
+
+    #[test]
+    fn test_serialize_invalid_setting_with_field() {
+        let error = BlahError::InvalidSetting {
+            field: "tts_hotkey".to_string(),
+            message: "Already in use".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "code": "invalid_setting",
+                "message": "Already in use",
+                "field": "tts_hotkey",
+            })
+        );
+    }
+
+    #[test]
+    fn test_display_is_message() {
+        let error = BlahError::TranscriptionFailed("Transcription failed: boom".to_string());
+        assert_eq!(error.to_string(), "Transcription failed: boom");
+    }
+
+    #[test]
+    fn test_from_io_error() {
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+        let error: BlahError = io.into();
+        assert_eq!(error.code(), "io_error");
+        assert_eq!(error.to_string(), "no such file");
+    }
+}
@@ -0,0 +1,943 @@
+//! Typed events sent from the backend to the frontend.
+//!
+//! Every event is a struct with a fixed name and a serializable payload, and is
+//! sent with [`emit`] rather than a raw `app.emit("name", ...)`. The matching
+//! TypeScript definitions in `src/lib/events.ts` are generated from the table
+//! below by [`typescript_definitions`] (also available at runtime through the
+//! `export_event_types` command); a test fails when they drift. To regenerate
+//! them after changing an event, run:
+//!
+//! ```sh
+//! BLAH3_UPDATE_EVENT_TYPES=1 cargo test events
+//! ```
+
+use serde::Serialize;
+use tauri::{Emitter, Runtime};
+
+use crate::accessibility::FrontmostAppInfo;
+use crate::audio::capture::RecordingLevels;
+use crate::audio::playback::PlaybackProgress;
+use crate::audio::silence::NoiseProfile;
+use crate::commands::batch::BatchProgress;
+use crate::commands::models::ModelType;
+use crate::commands::permissions::PermissionStatus;
+use crate::commands::stt::SilenceSuggestion;
+use crate::dictation_timing::DictationTimings;
+use crate::engines::WordConfidence;
+use crate::hotkeys::DictationCancelReason;
+use crate::lifecycle::InterruptReason;
+use crate::models::download::DownloadProgress;
+use crate::models::hardware::ThermalState;
+use crate::paste_undo::UndoPasteError;
+
+/// An event the frontend can listen for.
+pub trait Event: Serialize + Clone {
+    /// Event name passed to `listen()` on the frontend
+    const NAME: &'static str;
+}
+
+/// Emit `event` to all windows, logging rather than failing if it can't be sent.
+pub fn emit<R: Runtime, E: Event>(emitter: &impl Emitter<R>, event: E) {
+    if let Err(e) = emitter.emit(E::NAME, event) {
+        tracing::warn!("Failed to emit {} event: {}", E::NAME, e);
+    }
+}
+
+/// Dictation started; `target_app` is where the text will be pasted.
+#[derive(Debug, Clone, Serialize)]
+pub struct SttRecordingStarted {
+    pub target_app: Option<FrontmostAppInfo>,
+}
+
+/// Dictation recording stopped, with the peak and clipping of the audio.
+#[derive(Debug, Clone, Serialize)]
+pub struct SttRecordingStopped(pub RecordingLevels);
+
+/// A recording clipped enough to hurt transcription.
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioQualityWarning {
+    pub peak: f32,
+    pub clipped_ratio: f32,
+    /// User-facing suggestion
+    pub message: String,
+}
+
+/// The system stopped a recording. Hotkey dictations are transcribed by the
+/// backend as usual; a recording from the app waits for `stop_recording`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SttRecordingInterrupted {
+    pub reason: InterruptReason,
+    pub hotkey: bool,
+}
+
+/// The Mac is about to sleep.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemWillSleep;
+
+/// The Mac woke from sleep and the hotkeys were registered again.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemDidWake;
+
+/// A microphone stream opened (`true`) or the last open one closed
+/// (`false`), from any capture.
+#[derive(Debug, Clone, Serialize)]
+pub struct MicrophoneInUse(pub bool);
+
+/// Recorded audio is being transcribed.
+#[derive(Debug, Clone, Serialize)]
+pub struct SttTranscribing;
+
+/// Microphone RMS level, roughly 0.0 to 1.0, for the overlay waveform.
+#[derive(Debug, Clone, Serialize)]
+pub struct SttAudioLevel(pub f32);
+
+/// Transcript decoded so far.
+#[derive(Debug, Clone, Serialize)]
+pub struct SttPartialResult(pub String);
+
+/// How much of a recording has been transcribed, in seconds. Long
+/// recordings are decoded in chunks and report after each one.
+#[derive(Debug, Clone, Serialize)]
+pub struct SttProgress {
+    pub processed_secs: f32,
+    pub total_secs: f32,
+}
+
+/// Final transcript.
+#[derive(Debug, Clone, Serialize)]
+pub struct SttResult {
+    pub text: String,
+    /// Mean word confidence from 0 to 1, when the engine reports one
+    pub confidence: Option<f32>,
+    /// Every word with its confidence, for flagging uncertain ones
+    pub words: Vec<WordConfidence>,
+    /// Confidence fell below `confidence_paste_threshold`, so the text was
+    /// held for review instead of auto-pasted
+    pub needs_review: bool,
+    /// Where the time went, up to this event
+    pub timings: DictationTimings,
+}
+
+/// An auto-pasted dictation went through.
+#[derive(Debug, Clone, Serialize)]
+pub struct SttPasted {
+    /// App the dictation was started in
+    pub target_app: Option<FrontmostAppInfo>,
+    /// Characters pasted, including any suffix
+    pub chars: usize,
+}
+
+/// An auto-paste failed; `text` is still there to be copied by hand.
+#[derive(Debug, Clone, Serialize)]
+pub struct SttPasteFailed {
+    pub error: String,
+    pub text: String,
+}
+
+/// A hotkey dictation was discarded without being transcribed.
+#[derive(Debug, Clone, Serialize)]
+pub struct SttCancelled {
+    pub reason: DictationCancelReason,
+}
+
+/// Dictation failed; the payload is a user-facing message.
+#[derive(Debug, Clone, Serialize)]
+pub struct SttError(pub String);
+
+/// The audio of a dictation that failed to transcribe was kept and can be
+/// transcribed again with `retry_last_transcription`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SttRetryAvailable {
+    pub reason: String,
+    pub audio_secs: f32,
+    /// Only the start of a very long recording was kept
+    pub truncated: bool,
+}
+
+/// Suggested silence threshold from `calibrate_silence`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SilenceThresholdSuggested(pub SilenceSuggestion);
+
+/// `record_ambient_noise_profile` started listening; the user should stay
+/// quiet for `duration_secs`.
+#[derive(Debug, Clone, Serialize)]
+pub struct NoiseProfileRecording {
+    pub duration_secs: f32,
+}
+
+/// Room noise measured and its threshold saved.
+#[derive(Debug, Clone, Serialize)]
+pub struct NoiseProfileComplete(pub NoiseProfile);
+
+/// An AppleScript call into System Events was killed after running too
+/// long, usually because of a pending permission prompt or a busy system.
+/// `script` names what it was for, e.g. "selected text".
+#[derive(Debug, Clone, Serialize)]
+pub struct AppleScriptTimedOut {
+    pub script: String,
+    pub timeout_ms: u64,
+}
+
+/// The STT model in settings couldn't be found at startup. `downloadable`
+/// is set when it is a registered model that only needs downloading.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfiguredModelMissing {
+    pub model_id: String,
+    pub downloadable: bool,
+}
+
+/// The last pasted dictation was removed from `app_name`, with Cmd+Z when
+/// `native_undo` is set or by deleting `chars` characters.
+#[derive(Debug, Clone, Serialize)]
+pub struct PasteUndone {
+    pub chars: usize,
+    pub app_name: String,
+    pub native_undo: bool,
+}
+
+/// The dictation overlay was shown for hotkey session `session_id`, whose
+/// text so far is available from `get_current_transcript`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DictationOverlayShown {
+    pub session_id: u32,
+}
+
+/// A paste couldn't be undone; `reason` says why.
+#[derive(Debug, Clone, Serialize)]
+pub struct PasteUndoFailed(pub UndoPasteError);
+
+/// `permission` is allowed in System Settings but macOS refuses it anyway,
+/// found when a hotkey action was about to use it.
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionDegraded {
+    pub permission: String,
+    /// User-facing explanation of how to fix it
+    pub message: String,
+}
+
+/// Speech started, with the voice and resolved speed it is read at.
+#[derive(Debug, Clone, Serialize)]
+pub struct TtsStarted {
+    pub text: String,
+    pub voice_id: String,
+    pub speed: f32,
+}
+
+/// Speech finished or was stopped.
+#[derive(Debug, Clone, Serialize)]
+pub struct TtsFinished;
+
+/// Where playback of the current speech is, sent a few times a second.
+#[derive(Debug, Clone, Serialize)]
+pub struct TtsProgress(pub PlaybackProgress);
+
+/// Progress through synthesizing a text too long for one engine call, sent
+/// a few times a second until the audio is ready
+#[derive(Debug, Clone, Serialize)]
+pub struct TtsSynthesisProgress {
+    pub chars_done: usize,
+    pub chars_total: usize,
+}
+
+/// Speech failed; the payload is a user-facing message.
+#[derive(Debug, Clone, Serialize)]
+pub struct TtsError(pub String);
+
+/// A key combination was pressed during `start_shortcut_capture`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ShortcutCaptured {
+    /// Backend form, e.g. `CommandOrControl+Shift+D`
+    pub shortcut_str: String,
+    /// Symbol form for display, e.g. `⌘ + ⇧ + D`
+    pub display: String,
+}
+
+/// Shortcut capture ended without a key combination being pressed.
+#[derive(Debug, Clone, Serialize)]
+pub struct ShortcutCaptureTimeout;
+
+/// A hotkey binding clashed with the other one or with a system shortcut
+/// at startup and was replaced by its default.
+#[derive(Debug, Clone, Serialize)]
+pub struct HotkeyConflict {
+    /// Settings field of the replaced binding, e.g. `tts_hotkey`
+    pub binding: String,
+    pub shortcut: String,
+    /// The other binding's field, or `system` for a reserved shortcut
+    pub conflicts_with: String,
+    pub fallback: String,
+}
+
+/// Progress of a model download.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelDownloadProgress {
+    pub model_id: String,
+    pub progress: DownloadProgress,
+}
+
+/// `cancel_all_downloads` stopped these model downloads.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelDownloadAllCancelled {
+    pub model_ids: Vec<String>,
+}
+
+/// A model was loaded into memory.
+#[derive(Debug, Clone, Serialize)]
+pub struct EngineLoaded {
+    pub model_id: String,
+    pub model_type: ModelType,
+    pub approx_memory_bytes: u64,
+}
+
+/// A model was dropped from memory, e.g. after sitting idle or to make room
+/// for another.
+#[derive(Debug, Clone, Serialize)]
+pub struct EngineUnloaded {
+    pub model_id: String,
+    pub model_type: ModelType,
+}
+
+/// The machine got hotter; transcription may slow down as it throttles.
+#[derive(Debug, Clone, Serialize)]
+pub struct ThermalStateChanged {
+    pub state: ThermalState,
+    pub previous: ThermalState,
+}
+
+/// A batch transcription file started or finished.
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchProgressUpdate(pub BatchProgress);
+
+/// What the app is doing right now, returned by `get_app_state` so a window
+/// that opens or reloads mid-dictation can catch up without waiting for the
+/// next event.
+#[derive(Debug, Clone, Serialize)]
+pub struct AppStateSnapshot {
+    pub recording: Option<RecordingSnapshot>,
+    /// A recording is being transcribed
+    pub transcribing: bool,
+    pub speaking: Option<SpeakingSnapshot>,
+    /// Model ids with a download in progress
+    pub active_downloads: Vec<String>,
+    pub loaded_engines: LoadedEngines,
+    pub permissions: PermissionStatus,
+}
+
+/// Where a recording was started from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingSource {
+    /// The dictation hotkey is held
+    Hotkey,
+    /// `start_recording` from the main window
+    App,
+}
+
+/// A recording in progress.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingSnapshot {
+    /// Counts the recordings from `source` since launch, so a window can
+    /// tell a new recording from the one it already knows about
+    pub session_id: u32,
+    pub elapsed_secs: f32,
+    pub source: RecordingSource,
+}
+
+/// Speech playing right now.
+#[derive(Debug, Clone, Serialize)]
+pub struct SpeakingSnapshot {
+    pub voice_id: String,
+    pub progress: Option<PlaybackProgress>,
+}
+
+/// Engines loaded in memory, i.e. ready without a load delay.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct LoadedEngines {
+    pub whisper: bool,
+    pub tts: bool,
+}
+
+/// Declares the name and TypeScript payload of each event, and collects them
+/// into [`EVENTS`] for the definition exporter.
+macro_rules! events {
+    ($($event:ident => $name:literal : $ts:literal,)*) => {
+        $(
+            impl Event for $event {
+                const NAME: &'static str = $name;
+            }
+        )*
+
+        /// (name, TypeScript payload) of every event
+        pub const EVENTS: &[(&str, &str)] = &[$(($name, $ts)),*];
+    };
+}
+
+events! {
+    SttRecordingStarted => "stt-recording-started": "{ target_app: FrontmostAppInfo | null }",
+    SttRecordingStopped => "stt-recording-stopped": "RecordingLevels",
+    AudioQualityWarning => "audio-quality-warning": "{ peak: number; clipped_ratio: number; message: string }",
+    SttRecordingInterrupted => "stt-recording-interrupted": "{ reason: InterruptReason; hotkey: boolean }",
+    MicrophoneInUse => "microphone-in-use": "boolean",
+    SttTranscribing => "stt-transcribing": "null",
+    SttAudioLevel => "stt-audio-level": "number",
+    SttPartialResult => "stt-partial-result": "string",
+    SttProgress => "stt-progress": "{ processed_secs: number; total_secs: number }",
+    SttResult => "stt-result": "{ text: string; confidence: number | null; words: WordConfidence[]; needs_review: boolean; timings: DictationTimings }",
+    SttPasted => "stt-pasted": "{ target_app: FrontmostAppInfo | null; chars: number }",
+    SttPasteFailed => "stt-paste-failed": "{ error: string; text: string }",
+    SttCancelled => "stt-cancelled": "{ reason: DictationCancelReason }",
+    SttError => "stt-error": "string",
+    DictationOverlayShown => "dictation-overlay-shown": "{ session_id: number }",
+    SttRetryAvailable => "stt-retry-available": "{ reason: string; audio_secs: number; truncated: boolean }",
+    SilenceThresholdSuggested => "silence-threshold-suggested": "SilenceSuggestion",
+    NoiseProfileRecording => "noise-profile-recording": "{ duration_secs: number }",
+    NoiseProfileComplete => "noise-profile-complete": "NoiseProfile",
+    TtsStarted => "tts-started": "{ text: string; voice_id: string; speed: number }",
+    TtsFinished => "tts-finished": "null",
+    TtsProgress => "tts-progress": "PlaybackProgress",
+    TtsSynthesisProgress => "tts-synthesis-progress": "{ chars_done: number; chars_total: number }",
+    TtsError => "tts-error": "string",
+    ShortcutCaptured => "shortcut-captured": "{ shortcut_str: string; display: string }",
+    ShortcutCaptureTimeout => "shortcut-capture-timeout": "null",
+    HotkeyConflict => "hotkey-conflict": "{ binding: string; shortcut: string; conflicts_with: string; fallback: string }",
+    ModelDownloadProgress => "model-download-progress": "{ model_id: string; progress: DownloadProgress }",
+    ModelDownloadAllCancelled => "model-download-all-cancelled": "{ model_ids: string[] }",
+    EngineLoaded => "engine-loaded": "{ model_id: string; model_type: \"stt\" | \"tts\"; approx_memory_bytes: number }",
+    EngineUnloaded => "engine-unloaded": "{ model_id: string; model_type: \"stt\" | \"tts\" }",
+    BatchProgressUpdate => "batch-progress": "BatchProgress",
+    ThermalStateChanged => "thermal-state-changed": "{ state: ThermalState; previous: ThermalState }",
+    SystemWillSleep => "system-will-sleep": "null",
+    SystemDidWake => "system-did-wake": "null",
+    AppleScriptTimedOut => "applescript-timed-out": "{ script: string; timeout_ms: number }",
+    ConfiguredModelMissing => "configured-model-missing": "{ model_id: string; downloadable: boolean }",
+    PasteUndone => "paste-undone": "{ chars: number; app_name: string; native_undo: boolean }",
+    PasteUndoFailed => "paste-undo-failed": "{ reason: UndoPasteFailure; message: string }",
+    PermissionDegraded => "permission-degraded": "{ permission: string; message: string }",
+}
+
+/// TypeScript declarations for payload types shared by several events
+const TS_PAYLOAD_TYPES: &str = r#"export interface FrontmostAppInfo {
+  name: string;
+  bundle_id: string;
+}
+
+export type InterruptReason = "system_sleep" | "app_quit";
+
+export type DictationCancelReason = "escape" | "no_speech";
+
+export type ThermalState = "nominal" | "fair" | "serious" | "critical";
+
+export type UndoPasteFailure = "nothing_to_undo" | "expired" | "target_changed" | "keystroke_failed";
+
+export type AccessibilityState = "granted" | "denied" | "granted_but_not_working";
+
+export interface RecordingLevels {
+  peak: number;
+  clipped_ratio: number;
+}
+
+export interface WordConfidence {
+  word: string;
+  confidence: number;
+}
+
+export interface DictationTimings {
+  model: string;
+  capture_ms: number;
+  stop_to_transcribe_ms: number;
+  model_load_ms: number;
+  inference_ms: number;
+  realtime_factor: number;
+  post_processing_ms: number;
+  paste_ms: number | null;
+}
+
+export interface SilenceSuggestion {
+  ambient_rms: number;
+  threshold: number;
+  threshold_db: number;
+}
+
+export interface NoiseProfile {
+  rms: number;
+  peak: number;
+  db: number;
+  recommended_threshold: number;
+}
+
+export interface PlaybackProgress {
+  elapsed: number;
+  total: number;
+  percentage: number;
+}
+
+export interface DownloadProgress {
+  downloaded: number;
+  total: number;
+  percentage: number;
+}
+
+export interface BatchProgress {
+  index: number;
+  total: number;
+  file_name: string;
+  status: "processing" | "succeeded" | "failed";
+  error: string | null;
+}
+
+export type RecordingSource = "hotkey" | "app";
+
+export interface RecordingSnapshot {
+  session_id: number;
+  elapsed_secs: number;
+  source: RecordingSource;
+}
+
+export interface SpeakingSnapshot {
+  voice_id: string;
+  progress: PlaybackProgress | null;
+}
+
+export interface AppStateSnapshot {
+  recording: RecordingSnapshot | null;
+  transcribing: boolean;
+  speaking: SpeakingSnapshot | null;
+  active_downloads: string[];
+  loaded_engines: { whisper: boolean; tts: boolean };
+  permissions: {
+    microphone: boolean;
+    accessibility: boolean;
+    accessibility_state: AccessibilityState;
+  };
+}
+"#;
+
+/// Contents of `src/lib/events.ts`: payload types plus an `EventPayloads`
+/// map from event name to payload.
+pub fn typescript_definitions() -> String {
+    let mut out = String::from(
+        "// Generated from src-tauri/src/events.rs - do not edit by hand.\n\
+         // Regenerate with `BLAH3_UPDATE_EVENT_TYPES=1 cargo test events`.\n\n",
+    );
+    out.push_str(TS_PAYLOAD_TYPES);
+    out.push_str("\nexport interface EventPayloads {\n");
+    for (name, ts) in EVENTS {
+        out.push_str(&format!("  \"{}\": {};\n", name, ts));
+    }
+    out.push_str("}\n\nexport type EventName = keyof EventPayloads;\n");
+    out
+}
+
+/// TypeScript definitions for every event, for tooling that keeps the
+/// frontend in sync without a Rust toolchain.
+#[tauri::command]
+pub fn export_event_types() -> String {
+    typescript_definitions()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::batch::BatchFileStatus;
+    use crate::commands::permissions::AccessibilityState;
+    use crate::paste_undo::UndoPasteFailure;
+    use serde_json::json;
+    use std::collections::HashSet;
+
+    fn payload<E: Event>(event: E) -> serde_json::Value {
+        serde_json::to_value(event).unwrap()
+    }
+
+    #[test]
+    fn test_event_names_are_unique() {
+        let names: HashSet<_> = EVENTS.iter().map(|(name, _)| name).collect();
+        assert_eq!(names.len(), EVENTS.len());
+        assert_eq!(SttResult::NAME, "stt-result");
+    }
+
+    #[test]
+    fn test_serialize_stt_events() {
+        let app = FrontmostAppInfo {
+            name: "Notes".to_string(),
+            bundle_id: "com.apple.Notes".to_string(),
+        };
+        assert_eq!(
+            payload(SttRecordingStarted {
+                target_app: Some(app.clone())
+            }),
+            json!({ "target_app": { "name": "Notes", "bundle_id": "com.apple.Notes" } })
+        );
+        assert_eq!(
+            payload(SttRecordingStarted { target_app: None }),
+            json!({ "target_app": null })
+        );
+        assert_eq!(
+            payload(SttRecordingStopped(RecordingLevels {
+                peak: 0.5,
+                clipped_ratio: 0.25,
+            })),
+            json!({ "peak": 0.5, "clipped_ratio": 0.25 })
+        );
+        assert_eq!(
+            payload(AudioQualityWarning {
+                peak: 1.0,
+                clipped_ratio: 0.5,
+                message: "Too loud".to_string(),
+            }),
+            json!({ "peak": 1.0, "clipped_ratio": 0.5, "message": "Too loud" })
+        );
+        assert_eq!(
+            payload(SttRecordingInterrupted {
+                reason: InterruptReason::SystemSleep,
+                hotkey: true,
+            }),
+            json!({ "reason": "system_sleep", "hotkey": true })
+        );
+        assert_eq!(payload(SystemWillSleep), json!(null));
+        assert_eq!(payload(SystemDidWake), json!(null));
+        assert_eq!(payload(MicrophoneInUse(true)), json!(true));
+        assert_eq!(payload(SttTranscribing), json!(null));
+        assert_eq!(payload(SttAudioLevel(0.5)), json!(0.5));
+        assert_eq!(payload(SttPartialResult("hel".to_string())), json!("hel"));
+        assert_eq!(
+            payload(SttProgress {
+                processed_secs: 60.0,
+                total_secs: 150.0,
+            }),
+            json!({ "processed_secs": 60.0, "total_secs": 150.0 })
+        );
+        assert_eq!(
+            payload(SttResult {
+                text: "hello".to_string(),
+                confidence: Some(0.5),
+                words: vec![WordConfidence {
+                    word: "hello".to_string(),
+                    confidence: 0.5,
+                }],
+                needs_review: true,
+                timings: DictationTimings {
+                    model: "ggml-base.en.bin".to_string(),
+                    capture_ms: 2000,
+                    inference_ms: 500,
+                    realtime_factor: 0.25,
+                    ..Default::default()
+                },
+            }),
+            json!({
+                "text": "hello",
+                "confidence": 0.5,
+                "words": [{ "word": "hello", "confidence": 0.5 }],
+                "needs_review": true,
+                "timings": {
+                    "model": "ggml-base.en.bin",
+                    "capture_ms": 2000,
+                    "stop_to_transcribe_ms": 0,
+                    "model_load_ms": 0,
+                    "inference_ms": 500,
+                    "realtime_factor": 0.25,
+                    "post_processing_ms": 0,
+                    "paste_ms": null
+                }
+            })
+        );
+        assert_eq!(
+            payload(SttPasted {
+                target_app: Some(app),
+                chars: 6,
+            }),
+            json!({
+                "target_app": { "name": "Notes", "bundle_id": "com.apple.Notes" },
+                "chars": 6
+            })
+        );
+        assert_eq!(
+            payload(SttPasteFailed {
+                error: "Keystroke failed".to_string(),
+                text: "hello".to_string(),
+            }),
+            json!({ "error": "Keystroke failed", "text": "hello" })
+        );
+        assert_eq!(
+            payload(SttCancelled {
+                reason: DictationCancelReason::NoSpeech,
+            }),
+            json!({ "reason": "no_speech" })
+        );
+        assert_eq!(payload(SttError("No audio".to_string())), json!("No audio"));
+        assert_eq!(
+            payload(SttRetryAvailable {
+                reason: "Model not downloaded".to_string(),
+                audio_secs: 2.5,
+                truncated: false,
+            }),
+            json!({ "reason": "Model not downloaded", "audio_secs": 2.5, "truncated": false })
+        );
+        assert_eq!(
+            payload(DictationOverlayShown { session_id: 4 }),
+            json!({ "session_id": 4 })
+        );
+        assert_eq!(
+            payload(PasteUndone {
+                chars: 12,
+                app_name: "Notes".to_string(),
+                native_undo: false,
+            }),
+            json!({ "chars": 12, "app_name": "Notes", "native_undo": false })
+        );
+        assert_eq!(
+            payload(PasteUndoFailed(UndoPasteError {
+                reason: UndoPasteFailure::Expired,
+                message: "Too late".to_string(),
+            })),
+            json!({ "reason": "expired", "message": "Too late" })
+        );
+        assert_eq!(
+            payload(PermissionDegraded {
+                permission: "accessibility".to_string(),
+                message: "Add Blah³ again".to_string(),
+            }),
+            json!({ "permission": "accessibility", "message": "Add Blah³ again" })
+        );
+    }
+
+    #[test]
+    fn test_serialize_tts_events() {
+        assert_eq!(
+            payload(TtsStarted {
+                text: "Hi".to_string(),
+                voice_id: "bf_emma".to_string(),
+                speed: 1.5,
+            }),
+            json!({ "text": "Hi", "voice_id": "bf_emma", "speed": 1.5 })
+        );
+        assert_eq!(payload(TtsFinished), json!(null));
+        assert_eq!(
+            payload(TtsProgress(PlaybackProgress {
+                elapsed: 2.5,
+                total: 10.0,
+                percentage: 25,
+            })),
+            json!({ "elapsed": 2.5, "total": 10.0, "percentage": 25 })
+        );
+        assert_eq!(
+            payload(TtsSynthesisProgress {
+                chars_done: 300,
+                chars_total: 1200,
+            }),
+            json!({ "chars_done": 300, "chars_total": 1200 })
+        );
+        assert_eq!(payload(TtsError("Nothing".to_string())), json!("Nothing"));
+    }
+
+    #[test]
+    fn test_serialize_shortcut_events() {
+        assert_eq!(
+            payload(ShortcutCaptured {
+                shortcut_str: "Alt+Space".to_string(),
+                display: "\u{2325} + Space".to_string(),
+            }),
+            json!({ "shortcut_str": "Alt+Space", "display": "\u{2325} + Space" })
+        );
+        assert_eq!(payload(ShortcutCaptureTimeout), json!(null));
+        assert_eq!(
+            payload(HotkeyConflict {
+                binding: "tts_hotkey".to_string(),
+                shortcut: "\u{2318} + \u{21E7} + D".to_string(),
+                conflicts_with: "stt_hotkey".to_string(),
+                fallback: "\u{2318} + \u{21E7} + S".to_string(),
+            }),
+            json!({
+                "binding": "tts_hotkey",
+                "shortcut": "\u{2318} + \u{21E7} + D",
+                "conflicts_with": "stt_hotkey",
+                "fallback": "\u{2318} + \u{21E7} + S"
+            })
+        );
+    }
+
+    #[test]
+    fn test_serialize_progress_events() {
+        assert_eq!(
+            payload(ModelDownloadProgress {
+                model_id: "ggml-base.en.bin".to_string(),
+                progress: DownloadProgress {
+                    downloaded: 50,
+                    total: 200,
+                    percentage: 25,
+                },
+            }),
+            json!({
+                "model_id": "ggml-base.en.bin",
+                "progress": { "downloaded": 50, "total": 200, "percentage": 25 }
+            })
+        );
+        assert_eq!(
+            payload(ModelDownloadAllCancelled {
+                model_ids: vec!["kokoro-v1.0.onnx".to_string()],
+            }),
+            json!({ "model_ids": ["kokoro-v1.0.onnx"] })
+        );
+        assert_eq!(
+            payload(EngineLoaded {
+                model_id: "ggml-base.en.bin".to_string(),
+                model_type: ModelType::Stt,
+                approx_memory_bytes: 147_951_465,
+            }),
+            json!({
+                "model_id": "ggml-base.en.bin",
+                "model_type": "stt",
+                "approx_memory_bytes": 147_951_465
+            })
+        );
+        assert_eq!(
+            payload(EngineUnloaded {
+                model_id: "kokoro-v1.0.onnx".to_string(),
+                model_type: ModelType::Tts,
+            }),
+            json!({ "model_id": "kokoro-v1.0.onnx", "model_type": "tts" })
+        );
+        assert_eq!(
+            payload(BatchProgressUpdate(BatchProgress {
+                index: 1,
+                total: 3,
+                file_name: "memo.wav".to_string(),
+                status: BatchFileStatus::Failed,
+                error: Some("bad header".to_string()),
+            })),
+            json!({
+                "index": 1,
+                "total": 3,
+                "file_name": "memo.wav",
+                "status": "failed",
+                "error": "bad header"
+            })
+        );
+        assert_eq!(
+            payload(SilenceThresholdSuggested(SilenceSuggestion {
+                ambient_rms: 0.5,
+                threshold: 0.25,
+                threshold_db: -12.0,
+            })),
+            json!({ "ambient_rms": 0.5, "threshold": 0.25, "threshold_db": -12.0 })
+        );
+        assert_eq!(
+            payload(NoiseProfileComplete(NoiseProfile {
+                rms: 0.5,
+                peak: 1.0,
+                db: -6.0,
+                recommended_threshold: 0.25,
+            })),
+            json!({ "rms": 0.5, "peak": 1.0, "db": -6.0, "recommended_threshold": 0.25 })
+        );
+        assert_eq!(
+            payload(AppleScriptTimedOut {
+                script: "selected text".to_string(),
+                timeout_ms: 1500,
+            }),
+            json!({ "script": "selected text", "timeout_ms": 1500 })
+        );
+        assert_eq!(
+            payload(ConfiguredModelMissing {
+                model_id: "ggml-small.en.bin".to_string(),
+                downloadable: true,
+            }),
+            json!({ "model_id": "ggml-small.en.bin", "downloadable": true })
+        );
+        assert_eq!(
+            payload(ThermalStateChanged {
+                state: ThermalState::Serious,
+                previous: ThermalState::Fair,
+            }),
+            json!({ "state": "serious", "previous": "fair" })
+        );
+    }
+
+    #[test]
+    fn test_serialize_app_state_snapshot() {
+        let snapshot = AppStateSnapshot {
+            recording: Some(RecordingSnapshot {
+                session_id: 3,
+                elapsed_secs: 1.5,
+                source: RecordingSource::Hotkey,
+            }),
+            transcribing: false,
+            speaking: Some(SpeakingSnapshot {
+                voice_id: "bf_emma".to_string(),
+                progress: Some(PlaybackProgress {
+                    elapsed: 2.5,
+                    total: 10.0,
+                    percentage: 25,
+                }),
+            }),
+            active_downloads: vec!["whisper-small".to_string()],
+            loaded_engines: LoadedEngines {
+                whisper: true,
+                tts: false,
+            },
+            permissions: PermissionStatus {
+                microphone: true,
+                accessibility: false,
+                accessibility_state: AccessibilityState::GrantedButNotWorking,
+            },
+        };
+        assert_eq!(
+            serde_json::to_value(&snapshot).unwrap(),
+            json!({
+                "recording": { "session_id": 3, "elapsed_secs": 1.5, "source": "hotkey" },
+                "transcribing": false,
+                "speaking": {
+                    "voice_id": "bf_emma",
+                    "progress": { "elapsed": 2.5, "total": 10.0, "percentage": 25 }
+                },
+                "active_downloads": ["whisper-small"],
+                "loaded_engines": { "whisper": true, "tts": false },
+                "permissions": {
+                    "microphone": true,
+                    "accessibility": false,
+                    "accessibility_state": "granted_but_not_working"
+                }
+            })
+        );
+
+        let idle = AppStateSnapshot {
+            recording: None,
+            speaking: None,
+            active_downloads: Vec::new(),
+            ..snapshot
+        };
+        let json = serde_json::to_value(&idle).unwrap();
+        assert_eq!(json["recording"], json!(null));
+        assert_eq!(json["speaking"], json!(null));
+        assert_eq!(
+            serde_json::to_value(RecordingSource::App).unwrap(),
+            json!("app")
+        );
+    }
+
+    #[test]
+    fn test_typescript_definitions_cover_all_events() {
+        let ts = typescript_definitions();
+        for (name, payload) in EVENTS {
+            assert!(ts.contains(&format!("\"{}\": {};", name, payload)));
+        }
+    }
+
+    #[test]
+    fn test_typescript_definitions_up_to_date() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../src/lib/events.ts");
+        let expected = typescript_definitions();
+
+        if std::env::var_os("BLAH3_UPDATE_EVENT_TYPES").is_some() {
+            std::fs::write(&path, &expected).unwrap();
+            return;
+        }
+
+        let actual = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            actual, expected,
+            "src/lib/events.ts is out of date; run BLAH3_UPDATE_EVENT_TYPES=1 cargo test events"
+        );
+    }
+}
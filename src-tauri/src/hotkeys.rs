@@ -1,26 +1,500 @@
-use std::sync::atomic::{AtomicBool, Ordering};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter, Manager};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
 use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
 
 use crate::accessibility::{self, FrontmostAppInfo};
-use crate::audio::capture::AudioCapture;
+use crate::audio::capture::{AudioCapture, RecordingLevels, CAPTURE_SAMPLE_RATE};
+use crate::audio::processing::{audio_duration_ms, audio_duration_secs};
+use crate::audio_levels;
 use crate::commands::settings::get_settings;
-use crate::overlay;
+use crate::commands::stt;
+use crate::dictation_timing::{self, DictationTimings};
+use crate::engines::EngineRegistry;
+use crate::error::BlahError;
+use crate::events;
+use crate::live_transcript::{self, TranscriptStatus};
+use crate::mic_indicator;
+use crate::models::download::DownloadManager;
+use crate::overlay::{self, OverlayLevel};
+use crate::post_action;
+use crate::text::spell::spell_out;
+
+/// How long `start_shortcut_capture` waits for a key combination
+const SHORTCUT_CAPTURE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Set while a shortcut capture is listening; the generation keeps a stale
+// timeout from ending a later capture
+static CAPTURING_SHORTCUT: AtomicBool = AtomicBool::new(false);
+static CAPTURE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Speed of a held read-aloud hotkey's slow re-read, relative to normal
+const TTS_HOLD_SLOW_FACTOR: f32 = 0.7;
+
+/// Longest selection a held read-aloud hotkey spells out; longer ones are
+/// read slowly instead
+const SPELL_MAX_CHARS: usize = 40;
+
+/// How often a read-aloud press is checked for being a hold
+const TTS_HOLD_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// What holding the read-aloud hotkey does instead of reading normally
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TtsHoldAction {
+    /// Read the selection at 0.7x the usual speed
+    #[default]
+    Slow,
+    /// Spell a short selection, such as a confirmation code, letter by
+    /// letter; longer selections are read slowly
+    Spell,
+    /// Holding reads like a tap
+    None,
+}
+
+/// One press of the read-aloud hotkey
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TtsPress {
+    id: u32,
+    pressed_at: Instant,
+    released_at: Option<Instant>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PressKind {
+    Tap,
+    Hold,
+}
 
 /// Shared state for tracking recording status
 pub struct HotkeyState {
-    pub is_recording: AtomicBool,
+    /// Set from dictation hotkey press to release. Whether the microphone
+    /// is actually recording comes from the capture, see `is_recording`.
+    pub key_held: AtomicBool,
     pub audio_capture: tokio::sync::Mutex<Option<AudioCapture>>,
+    /// Lookup of the app that was frontmost when the current dictation
+    /// started, where its text is pasted. Runs off the shortcut thread and
+    /// is awaited before pasting.
+    pub target_app: std::sync::Mutex<Option<TargetAppLookup>>,
+    /// Dictations started this session
+    pub recording_count: AtomicU32,
+    /// Time the dictation hotkey was held this session, in thousandths of
+    /// a second; see `total_recording_secs`
+    pub total_recording_secs: AtomicU32,
+    /// Characters of dictated text this session
+    pub chars_dictated: AtomicU32,
+    /// When the dictation hotkey was pressed, while it is held
+    pressed_at: std::sync::Mutex<Option<Instant>>,
+    /// Latest press of the read-aloud hotkey
+    tts_press: std::sync::Mutex<Option<TtsPress>>,
+    /// When the app launched
+    pub session_started: Instant,
 }
 
+/// Fixed-point scale of `HotkeyState::total_recording_secs`
+const RECORDING_SECS_SCALE: f32 = 1000.0;
+
+pub type TargetAppLookup = tauri::async_runtime::JoinHandle<Option<FrontmostAppInfo>>;
+
 impl Default for HotkeyState {
     fn default() -> Self {
         Self {
-            is_recording: AtomicBool::new(false),
+            key_held: AtomicBool::new(false),
             audio_capture: tokio::sync::Mutex::new(None),
+            target_app: std::sync::Mutex::new(None),
+            recording_count: AtomicU32::new(0),
+            total_recording_secs: AtomicU32::new(0),
+            chars_dictated: AtomicU32::new(0),
+            pressed_at: std::sync::Mutex::new(None),
+            tts_press: std::sync::Mutex::new(None),
+            session_started: Instant::now(),
+        }
+    }
+}
+
+impl HotkeyState {
+    /// Whether a dictation has its microphone stream open. False while the
+    /// capture is being created or stopped.
+    pub fn is_recording(&self) -> bool {
+        self.audio_capture
+            .try_lock()
+            .map(|guard| guard.as_ref().is_some_and(AudioCapture::is_stream_active))
+            .unwrap_or(false)
+    }
+
+    /// Count a dictation that was just started, returning its session id
+    fn recording_started(&self) -> u32 {
+        let session_id = self.recording_count.fetch_add(1, Ordering::Relaxed) + 1;
+        if let Ok(mut pressed_at) = self.pressed_at.lock() {
+            *pressed_at = Some(Instant::now());
+        }
+        session_id
+    }
+
+    /// Session id of the latest dictation, counting from 1
+    pub fn session_id(&self) -> u32 {
+        self.recording_count.load(Ordering::Relaxed)
+    }
+
+    /// Add the time since `recording_started` to the session total
+    fn recording_stopped(&self) {
+        let pressed_at = self.pressed_at.lock().ok().and_then(|mut p| p.take());
+        if let Some(pressed_at) = pressed_at {
+            self.add_recording_time(pressed_at.elapsed());
+        }
+    }
+
+    fn add_recording_time(&self, elapsed: Duration) {
+        let added = (elapsed.as_secs_f32() * RECORDING_SECS_SCALE) as u32;
+        saturating_add(&self.total_recording_secs, added);
+    }
+
+    /// How long the dictation hotkey has been held, while it is
+    pub(crate) fn held_for(&self) -> Option<Duration> {
+        let pressed_at = self.pressed_at.lock().ok()?;
+        pressed_at.map(|pressed_at| pressed_at.elapsed())
+    }
+
+    /// Time the dictation hotkey was held this session
+    pub fn total_recording_secs(&self) -> f32 {
+        self.total_recording_secs.load(Ordering::Relaxed) as f32 / RECORDING_SECS_SCALE
+    }
+
+    /// Note a press of the read-aloud hotkey, returning its id, or `None`
+    /// for the key repeating while it is still held
+    fn tts_pressed(&self) -> Option<u32> {
+        let mut press = self.tts_press.lock().ok()?;
+        if press.is_some_and(|p| p.released_at.is_none()) {
+            return None;
+        }
+        let id = press.map_or(1, |p| p.id.wrapping_add(1));
+        *press = Some(TtsPress {
+            id,
+            pressed_at: Instant::now(),
+            released_at: None,
+        });
+        Some(id)
+    }
+
+    fn tts_released(&self) {
+        if let Ok(mut press) = self.tts_press.lock() {
+            if let Some(press) = press.as_mut().filter(|p| p.released_at.is_none()) {
+                press.released_at = Some(Instant::now());
+            }
+        }
+    }
+
+    /// The read-aloud press `id`, unless a later press replaced it
+    fn tts_press(&self, id: u32) -> Option<TtsPress> {
+        let press = self.tts_press.lock().ok()?;
+        press.filter(|p| p.id == id)
+    }
+
+    /// Count the characters of a finished dictation
+    fn add_dictated_text(&self, text: &str) {
+        let chars = u32::try_from(text.chars().count()).unwrap_or(u32::MAX);
+        saturating_add(&self.chars_dictated, chars);
+    }
+}
+
+/// Add to a session counter, stopping at its maximum rather than wrapping
+fn saturating_add(counter: &AtomicU32, amount: u32) {
+    let _ = counter.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |total| {
+        Some(total.saturating_add(amount))
+    });
+}
+
+/// Why a hotkey dictation was discarded without transcribing it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DictationCancelReason {
+    /// Escape was pressed while the dictation hotkey was held
+    Escape,
+    /// The hotkey was held for `discard_silent_hold_secs` without any speech
+    NoSpeech,
+}
+
+/// Combinations macOS or every app already uses, which can't be hotkeys
+const RESERVED_SHORTCUTS: &[&str] = &[
+    "CommandOrControl+Q",
+    "CommandOrControl+W",
+    "CommandOrControl+Tab",
+    "CommandOrControl+Space",
+    "Control+Space",
+    "CommandOrControl+Alt+Escape",
+    "CommandOrControl+Shift+3",
+    "CommandOrControl+Shift+4",
+    "CommandOrControl+Shift+5",
+];
+
+/// A hotkey binding that can't be registered as configured
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ShortcutConflict {
+    /// Settings field of the binding, `stt_hotkey` or `tts_hotkey`
+    pub field: &'static str,
+    pub shortcut: Shortcut,
+    /// Field of the other binding, or `None` for a reserved system shortcut
+    pub conflicts_with: Option<&'static str>,
+    /// Default the binding falls back to at startup
+    pub fallback: Shortcut,
+}
+
+fn default_stt_shortcut() -> Shortcut {
+    Shortcut::new(Some(Modifiers::SUPER | Modifiers::SHIFT), Code::KeyD)
+}
+
+fn default_tts_shortcut() -> Shortcut {
+    Shortcut::new(Some(Modifiers::SUPER | Modifiers::SHIFT), Code::KeyS)
+}
+
+fn same_shortcut(a: &Shortcut, b: &Shortcut) -> bool {
+    a.mods == b.mods && a.key == b.key
+}
+
+/// Whether `shortcut` is on the system deny-list
+pub(crate) fn is_reserved(shortcut: &Shortcut) -> bool {
+    RESERVED_SHORTCUTS
+        .iter()
+        .filter_map(|reserved| parse_shortcut(reserved))
+        .any(|reserved| same_shortcut(&reserved, shortcut))
+}
+
+/// The first problem with a pair of bindings: a reserved combination, or
+/// both bindings parsing to the same shortcut (reported against the TTS
+/// binding unless that is already the default).
+fn find_conflict(stt: &Shortcut, tts: &Shortcut) -> Option<ShortcutConflict> {
+    let conflict = |field, shortcut: &Shortcut, conflicts_with, fallback| ShortcutConflict {
+        field,
+        shortcut: *shortcut,
+        conflicts_with,
+        fallback,
+    };
+
+    if is_reserved(stt) {
+        return Some(conflict("stt_hotkey", stt, None, default_stt_shortcut()));
+    }
+    if is_reserved(tts) {
+        return Some(conflict("tts_hotkey", tts, None, default_tts_shortcut()));
+    }
+    if same_shortcut(stt, tts) {
+        return Some(if same_shortcut(tts, &default_tts_shortcut()) {
+            conflict(
+                "stt_hotkey",
+                stt,
+                Some("tts_hotkey"),
+                default_stt_shortcut(),
+            )
+        } else {
+            conflict(
+                "tts_hotkey",
+                tts,
+                Some("stt_hotkey"),
+                default_tts_shortcut(),
+            )
+        });
+    }
+    None
+}
+
+/// Shortcuts to register for the configured bindings. Unparseable bindings
+/// use their default; conflicting ones fall back to their default and are
+/// returned so the user can be told.
+pub(crate) fn resolve_hotkeys(
+    stt_hotkey: &str,
+    tts_hotkey: &str,
+) -> (Shortcut, Shortcut, Vec<ShortcutConflict>) {
+    let mut stt = parse_shortcut(stt_hotkey).unwrap_or_else(default_stt_shortcut);
+    let mut tts = parse_shortcut(tts_hotkey).unwrap_or_else(default_tts_shortcut);
+    let mut conflicts = Vec::new();
+
+    // Each fix resets a binding to its default, and the defaults never
+    // conflict, so this settles within a few rounds
+    while let Some(conflict) = find_conflict(&stt, &tts) {
+        match conflict.field {
+            "stt_hotkey" => stt = conflict.fallback,
+            _ => tts = conflict.fallback,
         }
+        conflicts.push(conflict);
     }
+
+    (stt, tts, conflicts)
+}
+
+/// Reject hotkey settings that can't be registered, naming the field at fault
+pub(crate) fn validate_hotkeys(stt_hotkey: &str, tts_hotkey: &str) -> Result<(), BlahError> {
+    let stt = parse_shortcut(stt_hotkey).unwrap_or_else(default_stt_shortcut);
+    let tts = parse_shortcut(tts_hotkey).unwrap_or_else(default_tts_shortcut);
+
+    match find_conflict(&stt, &tts) {
+        None => Ok(()),
+        Some(conflict) => Err(BlahError::InvalidSetting {
+            field: conflict.field.to_string(),
+            message: match conflict.conflicts_with {
+                Some(_) => format!(
+                    "{} is already used by the other hotkey",
+                    display_shortcut(&conflict.shortcut)
+                ),
+                None => format!(
+                    "{} is reserved by macOS",
+                    display_shortcut(&conflict.shortcut)
+                ),
+            },
+        }),
+    }
+}
+
+/// The shortcut for an optional hotkey such as replay, or why it can't be
+/// used. These have no default to fall back to, so a clash with one of the
+/// `taken` shortcuts disables them instead.
+fn resolve_optional_hotkey(hotkey: &str, taken: &[&Shortcut]) -> Result<Shortcut, String> {
+    let Some(shortcut) = parse_shortcut(hotkey) else {
+        return Err(format!("{} isn't a valid shortcut", hotkey));
+    };
+    if is_reserved(&shortcut) {
+        let message = format!("{} is reserved by macOS", display_shortcut(&shortcut));
+        return Err(message);
+    }
+    if taken.iter().any(|other| same_shortcut(&shortcut, other)) {
+        return Err(format!(
+            "{} is already used by another hotkey",
+            display_shortcut(&shortcut)
+        ));
+    }
+    Ok(shortcut)
+}
+
+/// Optional shortcuts that can be registered alongside `stt` and `tts`,
+/// each `None` when unset or unusable
+struct OptionalHotkeys {
+    replay: Option<Shortcut>,
+    undo_paste: Option<Shortcut>,
+    tts_clipboard: Option<Shortcut>,
+}
+
+fn resolve_optional_hotkeys(
+    settings: &crate::commands::settings::AppSettings,
+    stt: &Shortcut,
+    tts: &Shortcut,
+) -> OptionalHotkeys {
+    let resolve = |name: &str, hotkey: Option<&str>, taken: &[&Shortcut]| {
+        let resolved = resolve_optional_hotkey(hotkey?, taken);
+        if let Err(message) = &resolved {
+            tracing::warn!("Not registering the {} hotkey: {}", name, message);
+        }
+        resolved.ok()
+    };
+
+    let replay = resolve("replay", settings.replay_hotkey.as_deref(), &[stt, tts]);
+    let mut taken = vec![stt, tts];
+    taken.extend(replay.as_ref());
+    let undo_paste = resolve("undo paste", settings.undo_paste_hotkey.as_deref(), &taken);
+    taken.extend(undo_paste.as_ref());
+    let tts_clipboard = resolve(
+        "read clipboard",
+        settings.tts_clipboard_hotkey.as_deref(),
+        &taken,
+    );
+    OptionalHotkeys {
+        replay,
+        undo_paste,
+        tts_clipboard,
+    }
+}
+
+/// Reject a replay hotkey that can't be registered alongside the others
+pub(crate) fn validate_replay_hotkey(
+    replay_hotkey: &str,
+    stt_hotkey: &str,
+    tts_hotkey: &str,
+) -> Result<(), BlahError> {
+    let (stt, tts, _) = resolve_hotkeys(stt_hotkey, tts_hotkey);
+    resolve_optional_hotkey(replay_hotkey, &[&stt, &tts])
+        .map(|_| ())
+        .map_err(|message| BlahError::InvalidSetting {
+            field: "replay_hotkey".to_string(),
+            message,
+        })
+}
+
+/// Reject an undo-paste hotkey that can't be registered alongside the
+/// others, including the replay hotkey when one is set
+pub(crate) fn validate_undo_paste_hotkey(
+    undo_paste_hotkey: &str,
+    stt_hotkey: &str,
+    tts_hotkey: &str,
+    replay_hotkey: Option<&str>,
+) -> Result<(), BlahError> {
+    let (stt, tts, _) = resolve_hotkeys(stt_hotkey, tts_hotkey);
+    let replay = replay_hotkey.and_then(parse_shortcut);
+    let mut taken = vec![&stt, &tts];
+    taken.extend(replay.as_ref());
+
+    resolve_optional_hotkey(undo_paste_hotkey, &taken)
+        .map(|_| ())
+        .map_err(|message| BlahError::InvalidSetting {
+            field: "undo_paste_hotkey".to_string(),
+            message,
+        })
+}
+
+/// Reject a read-clipboard hotkey that can't be registered alongside the
+/// others, including the replay and undo-paste hotkeys when set
+pub(crate) fn validate_tts_clipboard_hotkey(
+    tts_clipboard_hotkey: &str,
+    stt_hotkey: &str,
+    tts_hotkey: &str,
+    other_hotkeys: &[Option<&str>],
+) -> Result<(), BlahError> {
+    let (stt, tts, _) = resolve_hotkeys(stt_hotkey, tts_hotkey);
+    let others: Vec<Shortcut> = other_hotkeys
+        .iter()
+        .filter_map(|hotkey| hotkey.and_then(parse_shortcut))
+        .collect();
+    let mut taken = vec![&stt, &tts];
+    taken.extend(&others);
+
+    resolve_optional_hotkey(tts_clipboard_hotkey, &taken)
+        .map(|_| ())
+        .map_err(|message| BlahError::InvalidSetting {
+            field: "tts_clipboard_hotkey".to_string(),
+            message,
+        })
+}
+
+/// A global hotkey as currently configured, for diagnostics
+#[derive(Debug, Clone, Serialize)]
+pub struct HotkeyBinding {
+    /// `stt`, `tts`, `replay`, `undo_paste` or `tts_clipboard`
+    pub action: String,
+    pub shortcut: String,
+    /// Whether the shortcut is registered with the system right now
+    pub registered: bool,
+}
+
+/// The hotkeys in effect, after any conflicting binding has fallen back
+pub(crate) fn active_hotkeys(app: &AppHandle) -> Vec<HotkeyBinding> {
+    let settings = get_settings().unwrap_or_default();
+    let (stt_shortcut, tts_shortcut, _) =
+        resolve_hotkeys(&settings.stt_hotkey, &settings.tts_hotkey);
+    let optional = resolve_optional_hotkeys(&settings, &stt_shortcut, &tts_shortcut);
+
+    [("stt", stt_shortcut), ("tts", tts_shortcut)]
+        .into_iter()
+        .chain(optional.replay.map(|shortcut| ("replay", shortcut)))
+        .chain(optional.undo_paste.map(|shortcut| ("undo_paste", shortcut)))
+        .chain(
+            optional
+                .tts_clipboard
+                .map(|shortcut| ("tts_clipboard", shortcut)),
+        )
+        .map(|(action, shortcut)| HotkeyBinding {
+            action: action.to_string(),
+            shortcut: format_shortcut(&shortcut),
+            registered: app.global_shortcut().is_registered(shortcut),
+        })
+        .collect()
 }
 
 /// Register all global hotkeys (internal - registers shortcuts and handlers)
@@ -33,12 +507,47 @@ fn register_hotkeys_internal(app: &AppHandle) -> Result<(), Box<dyn std::error::
         }
     };
 
-    // Parse hotkeys from settings or use defaults
-    let stt_shortcut = parse_shortcut(&settings.stt_hotkey)
-        .unwrap_or_else(|| Shortcut::new(Some(Modifiers::SUPER | Modifiers::SHIFT), Code::KeyD));
-
-    let tts_shortcut = parse_shortcut(&settings.tts_hotkey)
-        .unwrap_or_else(|| Shortcut::new(Some(Modifiers::SUPER | Modifiers::SHIFT), Code::KeyS));
+    // Parse hotkeys from settings or use defaults, replacing any binding that
+    // clashes with the other one or with a system shortcut
+    let (stt_shortcut, tts_shortcut, conflicts) =
+        resolve_hotkeys(&settings.stt_hotkey, &settings.tts_hotkey);
+    let optional = resolve_optional_hotkeys(&settings, &stt_shortcut, &tts_shortcut);
+    let optional_dropped = (settings.replay_hotkey.is_some() && optional.replay.is_none())
+        || (settings.undo_paste_hotkey.is_some() && optional.undo_paste.is_none())
+        || (settings.tts_clipboard_hotkey.is_some() && optional.tts_clipboard.is_none());
+    if !conflicts.is_empty() || optional_dropped {
+        // Save the fallbacks, otherwise update_settings would reject every
+        // later change until the user fixed the bindings by hand
+        let saved = crate::commands::settings::modify_settings(|settings| {
+            settings.stt_hotkey = format_shortcut(&stt_shortcut);
+            settings.tts_hotkey = format_shortcut(&tts_shortcut);
+            settings.replay_hotkey = optional.replay.as_ref().map(format_shortcut);
+            settings.undo_paste_hotkey = optional.undo_paste.as_ref().map(format_shortcut);
+            settings.tts_clipboard_hotkey = optional.tts_clipboard.as_ref().map(format_shortcut);
+            Ok(())
+        });
+        if let Err(e) = saved {
+            tracing::warn!("Failed to save hotkey fallbacks: {}", e);
+        }
+    }
+    for conflict in conflicts {
+        tracing::warn!(
+            "{} {} conflicts with {}, using {} instead",
+            conflict.field,
+            format_shortcut(&conflict.shortcut),
+            conflict.conflicts_with.unwrap_or("a system shortcut"),
+            format_shortcut(&conflict.fallback)
+        );
+        events::emit(
+            app,
+            events::HotkeyConflict {
+                binding: conflict.field.to_string(),
+                shortcut: display_shortcut(&conflict.shortcut),
+                conflicts_with: conflict.conflicts_with.unwrap_or("system").to_string(),
+                fallback: display_shortcut(&conflict.fallback),
+            },
+        );
+    }
 
     tracing::info!("Registering STT hotkey: {:?}", stt_shortcut);
     tracing::info!("Registering TTS hotkey: {:?}", tts_shortcut);
@@ -52,6 +561,30 @@ fn register_hotkeys_internal(app: &AppHandle) -> Result<(), Box<dyn std::error::
         handle_tts_shortcut(app, shortcut, event.state);
     })?;
 
+    if let Some(replay_shortcut) = optional.replay {
+        tracing::info!("Registering replay hotkey: {:?}", replay_shortcut);
+        app.global_shortcut()
+            .on_shortcut(replay_shortcut, move |app, shortcut, event| {
+                handle_replay_shortcut(app, shortcut, event.state);
+            })?;
+    }
+
+    if let Some(undo_paste_shortcut) = optional.undo_paste {
+        tracing::info!("Registering undo paste hotkey: {:?}", undo_paste_shortcut);
+        app.global_shortcut()
+            .on_shortcut(undo_paste_shortcut, move |app, shortcut, event| {
+                handle_undo_paste_shortcut(app, shortcut, event.state);
+            })?;
+    }
+
+    if let Some(clipboard_shortcut) = optional.tts_clipboard {
+        tracing::info!("Registering clipboard hotkey: {:?}", clipboard_shortcut);
+        app.global_shortcut()
+            .on_shortcut(clipboard_shortcut, move |app, shortcut, event| {
+                handle_tts_clipboard_shortcut(app, shortcut, event.state);
+            })?;
+    }
+
     Ok(())
 }
 
@@ -75,309 +608,818 @@ pub fn refresh_hotkeys(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>
     Ok(())
 }
 
-/// Payload for stt-recording-started event
-#[derive(Clone, serde::Serialize)]
-struct SttRecordingStartedPayload {
-    target_app: Option<FrontmostAppInfo>,
-}
-
 /// Handle STT (dictation) shortcut - press to start, release to stop
 fn handle_stt_shortcut(app: &AppHandle, _shortcut: &Shortcut, event: ShortcutState) {
     let state = app.state::<Arc<HotkeyState>>();
 
     match event {
         ShortcutState::Pressed => {
-            if !state.is_recording.load(Ordering::SeqCst) {
+            if !state.key_held.swap(true, Ordering::SeqCst) {
                 tracing::info!("STT hotkey pressed - starting recording");
+                let session_id = state.recording_started();
+                live_transcript::start_recording(session_id);
 
-                // Capture frontmost app BEFORE showing overlay
-                let target_app = accessibility::get_frontmost_app();
-                tracing::debug!("Target app for dictation: {:?}", target_app);
-
-                state.is_recording.store(true, Ordering::SeqCst);
+                // Look up the frontmost app without blocking the shortcut
+                // thread; the overlay can't take focus, so it doesn't matter
+                // that it shows first
+                let app_for_target = app.clone();
+                let lookup = tauri::async_runtime::spawn(async move {
+                    let target_app = accessibility::get_frontmost_app_async().await;
+                    tracing::debug!("Target app for dictation: {:?}", target_app);
+                    let target_for_event = target_app.clone();
+                    let started = events::SttRecordingStarted {
+                        target_app: target_for_event,
+                    };
+                    events::emit(&app_for_target, started);
+                    target_app
+                });
+                if let Ok(mut target) = state.target_app.lock() {
+                    *target = Some(lookup);
+                }
 
                 // Show the dictation overlay
-                if let Err(e) = overlay::show_overlay(app) {
+                if let Err(e) = overlay::show_overlay(app, session_id) {
                     tracing::warn!("Failed to show dictation overlay: {}", e);
                 }
 
-                // Emit event to frontend with target app info
-                let payload = SttRecordingStartedPayload {
-                    target_app: target_app.clone(),
-                };
-                if let Err(e) = app.emit("stt-recording-started", payload) {
-                    tracing::warn!("Failed to emit stt-recording-started event: {}", e);
-                }
-
                 // Start audio capture in background
                 let app_handle = app.clone();
                 let state_clone = Arc::clone(&state);
                 tauri::async_runtime::spawn(async move {
-                    match AudioCapture::new() {
+                    // Held until the capture is stored, so a quick release waits for it
+                    let mut guard = state_clone.audio_capture.lock().await;
+                    let capture = AudioCapture::new()
+                        .map(|capture| capture.with_observer(mic_indicator::observer(&app_handle)));
+                    match capture {
                         Ok(capture) => {
                             if let Err(e) = capture.start() {
                                 tracing::error!("Failed to start audio capture: {}", e);
-                                if let Err(emit_err) = app_handle.emit("stt-error", format!("Failed to start microphone: {}", e)) {
-                                    tracing::warn!("Failed to emit error to UI: {}", emit_err);
-                                }
-                                // Hide overlay on error
-                                let _ = overlay::hide_overlay(&app_handle);
+                                state_clone.key_held.store(false, Ordering::SeqCst);
+                                let error = format!("Failed to start microphone: {}", e);
+                                microphone_failed(&app_handle, session_id, error);
                                 return;
                             }
-                            let mut guard = state_clone.audio_capture.lock().await;
                             *guard = Some(capture);
+                            drop(guard);
+                            register_cancel_shortcut(&app_handle);
 
-                            // Spawn audio level emission task for overlay visualization
+                            // Spawn audio level emission task for overlay visualization,
+                            // running until the hotkey is released or the stream
+                            // closes. It slows down while no window shows the levels,
+                            // and also discards a recording held for too long without
+                            // speech.
+                            let discard_after =
+                                get_settings().unwrap_or_default().discard_silent_hold_secs;
                             let app_for_levels = app_handle.clone();
                             let state_for_levels = Arc::clone(&state_clone);
                             tauri::async_runtime::spawn(async move {
                                 loop {
-                                    if !state_for_levels.is_recording.load(Ordering::SeqCst) {
+                                    let stopped = !state_for_levels.key_held.load(Ordering::SeqCst)
+                                        || state_for_levels.session_id() != session_id;
+                                    if stopped {
                                         break;
                                     }
-                                    let level = {
+                                    let (level, silent_too_long) = {
                                         let guard = state_for_levels.audio_capture.lock().await;
-                                        guard.as_ref().map(|c| c.current_level()).unwrap_or(0.0)
+                                        match guard.as_ref() {
+                                            Some(capture) if capture.is_stream_active() => (
+                                                capture.current_level(),
+                                                held_without_speech(
+                                                    state_for_levels.held_for(),
+                                                    capture.has_speech(),
+                                                    discard_after,
+                                                ),
+                                            ),
+                                            _ => break,
+                                        }
                                     };
-                                    let _ = app_for_levels.emit("stt-audio-level", level);
-                                    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+                                    if silent_too_long {
+                                        let reason = DictationCancelReason::NoSpeech;
+                                        cancel_dictation(&app_for_levels, reason);
+                                        break;
+                                    }
+                                    events::emit(&app_for_levels, events::SttAudioLevel(level));
+                                    audio_levels::sleep(audio_levels::current_interval()).await;
                                 }
                             });
                         }
                         Err(e) => {
                             tracing::error!("Failed to create audio capture: {}", e);
-                            if let Err(emit_err) = app_handle.emit("stt-error", format!("Microphone unavailable: {}", e)) {
-                                tracing::warn!("Failed to emit error to UI: {}", emit_err);
-                            }
-                            // Hide overlay on error
-                            let _ = overlay::hide_overlay(&app_handle);
+                            state_clone.key_held.store(false, Ordering::SeqCst);
+                            let error = format!("Microphone unavailable: {}", e);
+                            microphone_failed(&app_handle, session_id, error);
                         }
                     }
                 });
             }
         }
         ShortcutState::Released => {
-            if state.is_recording.load(Ordering::SeqCst) {
+            if stop_dictation(app) {
                 tracing::info!("STT hotkey released - stopping recording");
-                state.is_recording.store(false, Ordering::SeqCst);
-
-                // Emit event to frontend
-                if let Err(e) = app.emit("stt-recording-stopped", ()) {
-                    tracing::warn!("Failed to emit stt-recording-stopped event: {}", e);
-                }
+            }
+        }
+    }
+}
 
-                // Stop capture and transcribe in background
-                let app_handle = app.clone();
-                let state_clone = Arc::clone(&state);
-                tauri::async_runtime::spawn(async move {
-                    let audio_data = {
-                        let mut guard = state_clone.audio_capture.lock().await;
-                        if let Some(capture) = guard.take() {
-                            match capture.stop() {
-                                Ok(data) => data,
-                                Err(e) => {
-                                    tracing::error!("Failed to stop capture: {}", e);
-                                    if let Err(emit_err) = app_handle.emit("stt-error", format!("Recording error: {}", e)) {
-                                        tracing::warn!("Failed to emit error to UI: {}", emit_err);
-                                    }
-                                    return;
-                                }
-                            }
-                        } else {
-                            Vec::new()
-                        }
-                    };
+/// Stop a hotkey dictation and transcribe what was captured in the
+/// background. Returns whether a dictation was in progress.
+pub(crate) fn stop_dictation(app: &AppHandle) -> bool {
+    let state = app.state::<Arc<HotkeyState>>();
+    if !state.key_held.swap(false, Ordering::SeqCst) {
+        return false;
+    }
+    audio_levels::wake();
+    let released = Instant::now();
+    let session_id = state.session_id();
+    state.recording_stopped();
 
-                    if audio_data.is_empty() {
-                        tracing::warn!("No audio data captured");
-                        if let Err(e) = app_handle.emit("stt-error", "No audio captured. Please check microphone permissions.") {
-                            tracing::warn!("Failed to emit error to UI: {}", e);
-                        }
-                        // Hide overlay on error after brief delay
-                        let app_for_hide = app_handle.clone();
-                        tauri::async_runtime::spawn(async move {
-                            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                            let _ = overlay::hide_overlay(&app_for_hide);
-                        });
+    // Stop capture and transcribe in background
+    let app_handle = app.clone();
+    let state_clone = Arc::clone(&state);
+    let target_lookup = state
+        .target_app
+        .lock()
+        .ok()
+        .and_then(|mut target| target.take());
+    tauri::async_runtime::spawn(async move {
+        release_cancel_shortcut(&app_handle);
+        let target_app = match target_lookup {
+            Some(lookup) => lookup.await.ok().flatten(),
+            None => None,
+        };
+        let (audio_data, levels) = {
+            let mut guard = state_clone.audio_capture.lock().await;
+            if let Some(capture) = guard.take() {
+                match capture.stop_with_levels() {
+                    Ok(stopped) => stopped,
+                    Err(e) => {
+                        tracing::error!("Failed to stop capture: {}", e);
+                        live_transcript::abandon(session_id, TranscriptStatus::Failed);
+                        events::emit(&app_handle, events::SttRecordingStopped(RecordingLevels::default()));
+                        events::emit(&app_handle, events::SttError(format!("Recording error: {}", e)));
                         return;
                     }
+                }
+            } else {
+                // The microphone never started and that was already reported
+                events::emit(
+                    &app_handle,
+                    events::SttRecordingStopped(RecordingLevels::default()),
+                );
+                return;
+            }
+        };
 
-                    tracing::info!("Captured {} audio samples, transcribing...", audio_data.len());
-                    if let Err(e) = app_handle.emit("stt-transcribing", ()) {
-                        tracing::warn!("Failed to emit stt-transcribing event: {}", e);
-                    }
+        // Emit event to frontend
+        events::emit(&app_handle, events::SttRecordingStopped(levels));
+        stt::warn_if_clipping(&app_handle, levels);
 
-                    // Get model path from settings
-                    let settings = match get_settings() {
-                        Ok(s) => s,
-                        Err(e) => {
-                            tracing::warn!("Failed to load settings for transcription, using defaults: {}", e);
-                            crate::commands::settings::AppSettings::default()
-                        }
-                    };
-                    let models_dir = match dirs::data_dir() {
-                        Some(dir) => dir.join("com.blahcubed.app").join("models").join("stt"),
-                        None => {
-                            tracing::error!("Could not determine data directory");
-                            if let Err(e) = app_handle.emit("stt-error", "Could not find application data directory") {
-                                tracing::warn!("Failed to emit error to UI: {}", e);
-                            }
-                            // Hide overlay on error after brief delay
-                            let app_for_hide = app_handle.clone();
-                            tauri::async_runtime::spawn(async move {
-                                tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                                let _ = overlay::hide_overlay(&app_for_hide);
-                            });
-                            return;
-                        }
-                    };
-                    let model_path = models_dir.join(&settings.stt_model);
+        if audio_data.is_empty() {
+            tracing::warn!("No audio data captured");
+            live_transcript::abandon(session_id, TranscriptStatus::Failed);
+            events::emit(&app_handle, events::SttError("No audio captured. Please check microphone permissions.".to_string()));
+            show_overlay_notice(&app_handle, "No audio captured", OverlayLevel::Warning);
+            hide_overlay_after(&app_handle, NOTICE_DURATION);
+            return;
+        }
 
-                    if !model_path.exists() {
-                        let error_msg = format!("Model not found: {}. Please download it from the Models tab.", settings.stt_model);
-                        if let Err(e) = app_handle.emit("stt-error", &error_msg) {
-                            tracing::warn!("Failed to emit error to UI: {}", e);
-                        }
-                        // Hide overlay on error after brief delay
-                        let app_for_hide = app_handle.clone();
-                        tauri::async_runtime::spawn(async move {
-                            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                            let _ = overlay::hide_overlay(&app_for_hide);
-                        });
-                        return;
-                    }
+        tracing::info!(
+            "Captured {} audio samples ({:.2}s), transcribing...",
+            audio_data.len(),
+            audio_duration_secs(audio_data.len(), CAPTURE_SAMPLE_RATE)
+        );
 
-                    // Transcribe - use to_string_lossy() to safely handle non-UTF8 paths
-                    let model_path_str = model_path.to_string_lossy();
-                    match crate::engines::whisper::WhisperEngine::new(&model_path_str) {
-                        Ok(engine) => {
-                            let app_for_segments = app_handle.clone();
-                            let mut accumulated_text = String::new();
-                            let on_segment = move |data: whisper_rs::SegmentCallbackData| {
-                                accumulated_text.push_str(&data.text);
-                                let _ = app_for_segments.emit("stt-partial-result", accumulated_text.trim());
-                            };
-                            match engine.transcribe_streaming(&audio_data, on_segment) {
-                                Ok(text) => {
-                                    tracing::info!("Transcription: {}", text);
-                                    if let Err(e) = app_handle.emit("stt-result", &text) {
-                                        tracing::warn!("Failed to emit transcription result: {}", e);
-                                    }
+        // Get model path from settings
+        let settings = match get_settings() {
+            Ok(s) => s,
+            Err(e) => {
+                tracing::warn!("Failed to load settings for transcription, using defaults: {}", e);
+                crate::commands::settings::AppSettings::default()
+            }
+        };
+        let _ = transcribe_and_deliver(
+            &app_handle,
+            &state_clone,
+            session_id,
+            audio_data,
+            &settings,
+            target_app,
+            released,
+        )
+        .await;
+    });
 
-                                    // Auto-paste if enabled
-                                    if settings.auto_paste && !text.is_empty() {
-                                        if let Err(e) = accessibility::paste_text(&text) {
-                                            tracing::error!("Failed to auto-paste transcription: {}", e);
-                                        }
-                                    }
+    true
+}
 
-                                    // Hide overlay after a brief delay to show the result
-                                    let app_for_hide = app_handle.clone();
-                                    tauri::async_runtime::spawn(async move {
-                                        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                                        let _ = overlay::hide_overlay(&app_for_hide);
-                                    });
-                                }
-                                Err(e) => {
-                                    tracing::error!("Transcription failed: {}", e);
-                                    if let Err(emit_err) = app_handle.emit("stt-error", format!("Transcription failed: {}", e)) {
-                                        tracing::warn!("Failed to emit error to UI: {}", emit_err);
-                                    }
-                                    // Hide overlay on error after brief delay
-                                    let app_for_hide = app_handle.clone();
-                                    tauri::async_runtime::spawn(async move {
-                                        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                                        let _ = overlay::hide_overlay(&app_for_hide);
-                                    });
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            tracing::error!("Failed to load Whisper model: {}", e);
-                            if let Err(emit_err) = app_handle.emit("stt-error", format!("Failed to load speech model: {}", e)) {
-                                tracing::warn!("Failed to emit error to UI: {}", emit_err);
-                            }
-                            // Hide overlay on error after brief delay
-                            let app_for_hide = app_handle.clone();
-                            tauri::async_runtime::spawn(async move {
-                                tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                                let _ = overlay::hide_overlay(&app_for_hide);
-                            });
-                        }
-                    }
-                });
+/// Transcribe a dictation's audio, then paste it (or hold it for review)
+/// and hide the overlay. If transcription fails, the audio is kept so
+/// `retry_failed_dictation` can try again and the overlay stays up with
+/// the error. `released` is when recording stopped.
+async fn transcribe_and_deliver(
+    app_handle: &AppHandle,
+    state: &HotkeyState,
+    session_id: u32,
+    audio_data: Vec<f32>,
+    settings: &crate::commands::settings::AppSettings,
+    target_app: Option<FrontmostAppInfo>,
+    released: Instant,
+) -> Result<String, BlahError> {
+    events::emit(app_handle, events::SttTranscribing);
+    live_transcript::start_transcribing(session_id);
+    let audio_secs = audio_duration_secs(audio_data.len(), CAPTURE_SAMPLE_RATE);
+    let (model, model_path) = stt::dictation_model_path(settings, audio_secs);
+    if model != settings.stt_model {
+        tracing::info!("Using {} for {:.1}s of audio", model, audio_secs);
+    }
+    let engines = app_handle.state::<Arc<EngineRegistry>>();
+    stt::keep_adaptive_models_loaded(&engines, settings).await;
+
+    let app_for_segments = app_handle.clone();
+    let on_partial = move |text: &str| {
+        live_transcript::set_partial(session_id, text);
+        let partial = events::SttPartialResult(text.to_string());
+        events::emit(&app_for_segments, partial);
+        if let Err(e) = overlay::update_overlay_text(&app_for_segments, text) {
+            tracing::debug!("Failed to update overlay text: {}", e);
+        }
+    };
+    let app_for_progress = app_handle.clone();
+    let on_progress = move |processed_secs, total_secs| {
+        events::emit(
+            &app_for_progress,
+            events::SttProgress {
+                processed_secs,
+                total_secs,
+            },
+        );
+    };
+    let mut timings = DictationTimings {
+        model: model.clone(),
+        capture_ms: audio_duration_ms(audio_data.len(), CAPTURE_SAMPLE_RATE),
+        stop_to_transcribe_ms: dictation_timing::millis(released.elapsed()),
+        ..Default::default()
+    };
+    // Use to_string_lossy() to safely handle non-UTF8 paths
+    let result = stt::transcribe_dictation(
+        engines.whisper(),
+        &audio_data,
+        &model_path.to_string_lossy(),
+        &stt::decode_options(settings),
+        on_partial,
+        on_progress,
+        &mut timings,
+    )
+    .await;
+
+    let transcript = match result {
+        Ok(transcript) => transcript,
+        Err(e) => {
+            tracing::error!("Transcription failed: {}", e);
+            live_transcript::abandon(session_id, TranscriptStatus::Failed);
+            events::emit(app_handle, events::SttError(e.to_string()));
+
+            // Say what's wrong in the overlay when the user can do something about it
+            let downloading = app_handle
+                .state::<DownloadManager>()
+                .active()
+                .contains(&model);
+            if let Some((message, level)) = transcription_failure_notice(&e, downloading) {
+                show_overlay_notice(app_handle, message, level);
             }
+
+            // Keep the audio and leave the error up with a way to retry
+            let retry =
+                crate::dictation_retry::stash(session_id, audio_data, e.to_string(), target_app);
+            events::emit(app_handle, retry);
+            if let Err(e) = overlay::set_interactive(app_handle, true) {
+                tracing::warn!("Failed to make overlay clickable: {}", e);
+            }
+            return Err(e);
+        }
+    };
+    crate::dictation_retry::clear();
+
+    let post_processing = Instant::now();
+    let text = stt::dictation_text(&transcript.text, &transcript.segments, settings);
+    timings.post_processing_ms = dictation_timing::millis(post_processing.elapsed());
+    let confidence = transcript.confidence();
+    tracing::info!("Transcription: {} (confidence {:?})", text, confidence);
+    stt::remember_transcription(&text);
+    live_transcript::finish(session_id, &text);
+    state.add_dictated_text(&text);
+
+    // Uncertain dictations wait in the overlay instead of being pasted
+    let needs_review = settings.auto_paste
+        && !text.is_empty()
+        && stt::needs_review(confidence, settings.confidence_paste_threshold);
+    events::emit(
+        app_handle,
+        events::SttResult {
+            text: text.clone(),
+            confidence,
+            words: transcript.words(),
+            needs_review,
+            timings: timings.clone(),
+        },
+    );
+    if settings.copy_to_clipboard && !text.is_empty() {
+        if let Err(e) = accessibility::paste::set_clipboard(&text) {
+            tracing::warn!("Failed to copy transcription: {}", e);
+        }
+    }
+
+    // Auto-paste if enabled. A text that wasn't pasted stays in the
+    // overlay, which becomes clickable so it can be copied.
+    let mut keep_overlay = needs_review;
+    if needs_review {
+        tracing::info!("Low confidence transcription held for review");
+    } else if settings.auto_paste && !text.is_empty() {
+        let paste = Instant::now();
+        crate::commands::permissions::check_accessibility_for_hotkey(app_handle);
+        let pasted = post_action::run(app_handle, &text, settings, target_app.as_ref()).await;
+        timings.paste_ms = Some(dictation_timing::millis(paste.elapsed()));
+        keep_overlay = pasted.is_err();
+    }
+    dictation_timing::record(timings);
+
+    // The overlay stays up until the user dismisses it
+    if keep_overlay {
+        if let Err(e) = overlay::set_interactive(app_handle, true) {
+            tracing::warn!("Failed to make overlay clickable: {}", e);
         }
+    } else {
+        // Hide overlay after a brief delay to show the result
+        let app_for_hide = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+            let _ = overlay::hide_overlay(&app_for_hide);
+        });
     }
+    Ok(text)
 }
 
-/// Handle TTS (read aloud) shortcut - single press to read selection
-fn handle_tts_shortcut(app: &AppHandle, _shortcut: &Shortcut, event: ShortcutState) {
+/// How long a notice stays in the overlay before it is hidden
+const NOTICE_DURATION: Duration = Duration::from_secs(3);
+
+/// Report a dictation whose microphone didn't start. Without microphone
+/// access the overlay says so for a moment, since the main window showing
+/// the error is usually hidden; otherwise it is hidden right away.
+fn microphone_failed(app: &AppHandle, session_id: u32, error: String) {
+    live_transcript::abandon(session_id, TranscriptStatus::Failed);
+    events::emit(app, events::SttError(error));
+    if crate::commands::permissions::check_microphone() {
+        let _ = overlay::hide_overlay(app);
+        return;
+    }
+    show_overlay_notice(app, "No microphone access", OverlayLevel::Error);
+    hide_overlay_after(app, NOTICE_DURATION);
+}
+
+/// What to tell the user in the overlay when a dictation fails to
+/// transcribe with `error`, if it is something they can act on.
+/// `downloading` is whether the selected model is being downloaded.
+fn transcription_failure_notice(
+    error: &BlahError,
+    downloading: bool,
+) -> Option<(&'static str, OverlayLevel)> {
+    match error {
+        BlahError::ModelNotDownloaded(_) if downloading => {
+            Some(("Downloading model…", OverlayLevel::Info))
+        }
+        BlahError::ModelNotDownloaded(_) | BlahError::ModelNotFound(_) => {
+            Some(("Model not found", OverlayLevel::Error))
+        }
+        _ => None,
+    }
+}
+
+fn show_overlay_notice(app: &AppHandle, message: &str, level: OverlayLevel) {
+    if let Err(e) = overlay::show_overlay_with_text(app, message, level) {
+        tracing::warn!("Failed to show overlay message: {}", e);
+    }
+}
+
+fn hide_overlay_after(app: &AppHandle, delay: Duration) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(delay).await;
+        let _ = overlay::hide_overlay(&app);
+    });
+}
+
+/// Transcribe the audio of the last dictation that failed again, with
+/// `model_id` instead of the configured model when given, and deliver it
+/// like a new dictation
+pub(crate) async fn retry_failed_dictation(
+    app: &AppHandle,
+    model_id: Option<String>,
+) -> Result<String, BlahError> {
+    let state = Arc::clone(&app.state::<Arc<HotkeyState>>());
+    if state.key_held.load(Ordering::SeqCst) {
+        return Err(BlahError::Busy("Can't retry while dictating".to_string()));
+    }
+
+    let mut settings = get_settings()?;
+    let ttl = Duration::from_secs(settings.failed_dictation_ttl_secs);
+    let Some(failed) = crate::dictation_retry::take(ttl) else {
+        let message = "There is no failed dictation to retry".to_string();
+        return Err(BlahError::InvalidInput(message));
+    };
+    if let Some(model_id) = model_id {
+        // The chosen model is used whatever the recording's length
+        settings.stt_model = model_id;
+        settings.adaptive_model = None;
+    }
+    tracing::info!(
+        "Retrying dictation that failed with \"{}\" using {}",
+        failed.reason,
+        settings.stt_model
+    );
+
+    if let Err(e) = overlay::show_overlay(app, failed.session_id) {
+        tracing::warn!("Failed to show dictation overlay: {}", e);
+    }
+    if let Err(e) = overlay::set_interactive(app, false) {
+        tracing::warn!("Failed to make overlay click-through: {}", e);
+    }
+    let started = Instant::now();
+    transcribe_and_deliver(
+        app,
+        &state,
+        failed.session_id,
+        failed.audio,
+        &settings,
+        failed.target_app,
+        started,
+    )
+    .await
+}
+
+/// Whether a dictation held for `held_for` should be discarded because the
+/// silence detector hasn't heard speech within `discard_after_secs`. Never
+/// when the setting is 0 or silence detection is off.
+fn held_without_speech(
+    held_for: Option<Duration>,
+    has_speech: Option<bool>,
+    discard_after_secs: u64,
+) -> bool {
+    let Some(held_for) = held_for else {
+        return false;
+    };
+    discard_after_secs > 0
+        && has_speech == Some(false)
+        && held_for >= Duration::from_secs(discard_after_secs)
+}
+
+/// Discard a hotkey dictation without transcribing it, hiding the overlay.
+/// Returns whether a dictation was in progress.
+pub(crate) fn cancel_dictation(app: &AppHandle, reason: DictationCancelReason) -> bool {
+    let state = app.state::<Arc<HotkeyState>>();
+    if !state.key_held.swap(false, Ordering::SeqCst) {
+        return false;
+    }
+    audio_levels::wake();
+    tracing::info!("Dictation cancelled ({:?})", reason);
+    live_transcript::abandon(state.session_id(), TranscriptStatus::Cancelled);
+    state.recording_stopped();
+    if let Ok(mut target) = state.target_app.lock() {
+        target.take();
+    }
+
+    let app_handle = app.clone();
+    let state_clone = Arc::clone(&state);
+    tauri::async_runtime::spawn(async move {
+        release_cancel_shortcut(&app_handle);
+        let capture = state_clone.audio_capture.lock().await.take();
+        let levels = match capture.map(AudioCapture::stop_with_levels) {
+            Some(Ok((_, levels))) => levels,
+            Some(Err(e)) => {
+                tracing::warn!("Failed to stop cancelled capture: {}", e);
+                RecordingLevels::default()
+            }
+            None => RecordingLevels::default(),
+        };
+        events::emit(&app_handle, events::SttRecordingStopped(levels));
+        events::emit(&app_handle, events::SttCancelled { reason });
+        if let Err(e) = overlay::hide_overlay(&app_handle) {
+            tracing::warn!("Failed to hide dictation overlay: {}", e);
+        }
+    });
+
+    true
+}
+
+fn cancel_shortcut() -> Shortcut {
+    Shortcut::new(None, Code::Escape)
+}
+
+/// Take over Escape to cancel the dictation in progress, releasing it again
+/// if the hotkey was let go meanwhile. Must not run on the shortcut thread,
+/// since the plugin holds its lock while calling handlers.
+fn register_cancel_shortcut(app: &AppHandle) {
+    let shortcuts = app.global_shortcut();
+    let registered = shortcuts.on_shortcut(cancel_shortcut(), move |app, shortcut, event| {
+        handle_cancel_shortcut(app, shortcut, event.state);
+    });
+    if let Err(e) = registered {
+        tracing::warn!("Failed to register Escape to cancel dictation: {}", e);
+    }
+
+    let state = app.state::<Arc<HotkeyState>>();
+    if !state.key_held.load(Ordering::SeqCst) {
+        release_cancel_shortcut(app);
+    }
+}
+
+/// Handle Escape while dictating - press to discard the recording
+fn handle_cancel_shortcut(app: &AppHandle, _shortcut: &Shortcut, event: ShortcutState) {
     if event != ShortcutState::Pressed {
         return;
     }
 
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        cancel_dictation(&app_handle, DictationCancelReason::Escape);
+    });
+}
+
+/// Give Escape back to other apps once a dictation ends. Like
+/// `register_cancel_shortcut`, not for the shortcut thread.
+fn release_cancel_shortcut(app: &AppHandle) {
+    let shortcut = cancel_shortcut();
+    if !app.global_shortcut().is_registered(shortcut) {
+        return;
+    }
+    if let Err(e) = app.global_shortcut().unregister(shortcut) {
+        tracing::warn!("Failed to release Escape after dictation: {}", e);
+    }
+}
+
+/// Handle TTS (read aloud) shortcut - a tap reads the selection; holding
+/// the key for `tts_hold_threshold_ms` does `tts_hold_action` instead
+fn handle_tts_shortcut(app: &AppHandle, _shortcut: &Shortcut, event: ShortcutState) {
+    let state = app.state::<Arc<HotkeyState>>();
+    if event == ShortcutState::Released {
+        state.tts_released();
+        return;
+    }
+    let Some(press_id) = state.tts_pressed() else {
+        return;
+    };
+
     tracing::info!("TTS hotkey pressed - reading selection");
 
-    // Get selected text
-    let text = match accessibility::get_selected_text() {
-        Some(t) if !t.is_empty() => t,
-        _ => {
+    // Copying the selection runs AppleScript, so it happens off the
+    // shortcut thread, while the key may still be held
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        crate::commands::permissions::check_accessibility_for_hotkey(&app_handle);
+        let Some(text) = accessibility::get_selected_text_async().await else {
             tracing::warn!("No text selected for TTS");
-            if let Err(e) = app.emit("tts-error", "No text selected. Please select some text first.") {
-                tracing::warn!("Failed to emit tts-error event: {}", e);
-            }
+            let message = "No text selected. Please select some text first.".to_string();
+            events::emit(&app_handle, events::TtsError(message));
+            return;
+        };
+
+        let settings = get_settings().unwrap_or_default();
+        if settings.tts_hold_action == TtsHoldAction::None {
+            speak_selection(&app_handle, text);
             return;
         }
+        let threshold = Duration::from_millis(settings.tts_hold_threshold_ms);
+        match wait_for_press_kind(&app_handle, press_id, threshold).await {
+            PressKind::Tap => speak_selection(&app_handle, text),
+            PressKind::Hold => {
+                tracing::info!("TTS hotkey held - {:?}", settings.tts_hold_action);
+                let (speech, speed_factor) = hold_speech(&text, settings.tts_hold_action);
+                speak_selection_at(&app_handle, speech, speed_factor);
+            }
+        }
+    });
+}
+
+/// Wait until the read-aloud press `press_id` is released or held long
+/// enough to count as a hold
+async fn wait_for_press_kind(app: &AppHandle, press_id: u32, threshold: Duration) -> PressKind {
+    let state = app.state::<Arc<HotkeyState>>();
+    loop {
+        let press = state.tts_press(press_id);
+        if let Some(kind) = press_kind(press.as_ref(), Instant::now(), threshold) {
+            return kind;
+        }
+        tokio::time::sleep(TTS_HOLD_POLL_INTERVAL).await;
+    }
+}
+
+/// Whether `press` was a tap or a hold as of `now`, or `None` while it is
+/// still held but not yet for `threshold`. A press replaced by a later one
+/// counts as a tap.
+fn press_kind(press: Option<&TtsPress>, now: Instant, threshold: Duration) -> Option<PressKind> {
+    let Some(press) = press else {
+        return Some(PressKind::Tap);
     };
+    let held_for = press.released_at.unwrap_or(now) - press.pressed_at;
+    if held_for >= threshold {
+        Some(PressKind::Hold)
+    } else if press.released_at.is_some() {
+        Some(PressKind::Tap)
+    } else {
+        None
+    }
+}
 
-    tracing::info!("Selected text: {} chars", text.len());
-    if let Err(e) = app.emit("tts-started", &text) {
-        tracing::warn!("Failed to emit tts-started event: {}", e);
+/// Text to speak and speed factor for a held read-aloud hotkey
+fn hold_speech(text: &str, action: TtsHoldAction) -> (String, f32) {
+    match action {
+        TtsHoldAction::Spell if text.trim().chars().count() <= SPELL_MAX_CHARS => {
+            (spell_out(text), 1.0)
+        }
+        TtsHoldAction::Spell | TtsHoldAction::Slow => (text.to_string(), TTS_HOLD_SLOW_FACTOR),
+        TtsHoldAction::None => (text.to_string(), 1.0),
+    }
+}
+
+/// Handle the read clipboard shortcut - single press to speak what was
+/// last copied
+fn handle_tts_clipboard_shortcut(app: &AppHandle, _shortcut: &Shortcut, event: ShortcutState) {
+    if event != ShortcutState::Pressed {
+        return;
     }
 
-    // Speak in background
+    tracing::info!("Read clipboard hotkey pressed");
     let app_handle = app.clone();
     tauri::async_runtime::spawn(async move {
-        let settings = match get_settings() {
-            Ok(s) => s,
-            Err(e) => {
-                tracing::warn!("Failed to load settings for TTS, using defaults: {}", e);
-                crate::commands::settings::AppSettings::default()
+        if let Err(e) = crate::commands::tts::speak_clipboard(app_handle.clone()).await {
+            tracing::warn!("Not reading the clipboard: {}", e);
+            events::emit(&app_handle, events::TtsError(e.to_string()));
+        }
+    });
+}
+
+/// Handle the replay shortcut - single press to repeat the last speech
+fn handle_replay_shortcut(app: &AppHandle, _shortcut: &Shortcut, event: ShortcutState) {
+    if event != ShortcutState::Pressed {
+        return;
+    }
+
+    tracing::info!("Replay hotkey pressed");
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match crate::commands::tts::replay_last_speech().await {
+            Ok(true) => {}
+            Ok(false) => {
+                let message = "Nothing has been spoken yet.".to_string();
+                events::emit(&app_handle, events::TtsError(message));
             }
-        };
+            Err(e) => events::emit(&app_handle, events::TtsError(e.to_string())),
+        }
+    });
+}
+
+/// Handle the undo paste shortcut - single press to remove the last pasted
+/// dictation. The outcome is reported with `paste-undone` or
+/// `paste-undo-failed`.
+fn handle_undo_paste_shortcut(app: &AppHandle, _shortcut: &Shortcut, event: ShortcutState) {
+    if event != ShortcutState::Pressed {
+        return;
+    }
+
+    tracing::info!("Undo paste hotkey pressed");
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let _ = crate::paste_undo::undo_last_paste(&app_handle).await;
+    });
+}
+
+/// Speak selected or copied text with the configured voice, reporting it
+/// with `tts-started` and `tts-finished`
+pub(crate) fn speak_selection(app: &AppHandle, text: String) {
+    speak_selection_at(app, text, 1.0);
+}
 
-        // For now, emit that we would speak the text
-        // Full TTS integration requires kokoroxide
-        tracing::info!("Would speak with voice '{}' at {}x speed: {}",
-            settings.tts_voice, settings.tts_speed, &text);
+/// Like `speak_selection`, with the voice's speed multiplied by
+/// `speed_factor`
+fn speak_selection_at(app: &AppHandle, text: String, speed_factor: f32) {
+    tracing::info!("Selected text: {} chars", text.len());
+
+    let settings = match get_settings() {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::warn!("Failed to load settings for TTS, using defaults: {}", e);
+            crate::commands::settings::AppSettings::default()
+        }
+    };
+    let speed = crate::commands::tts::effective_speed(&settings.tts_voice, settings.tts_speed)
+        * speed_factor;
+    events::emit(
+        app,
+        events::TtsStarted {
+            text: text.clone(),
+            voice_id: settings.tts_voice.clone(),
+            speed,
+        },
+    );
 
-        // TODO: Implement actual TTS when kokoroxide is integrated
-        // let models_dir = dirs::data_dir()
-        //     .unwrap_or_default()
-        //     .join("com.blahcubed.app")
-        //     .join("models")
-        //     .join("tts");
-        // let model_path = models_dir.join("kokoro-v1.0.onnx");
+    // Speak in background through whichever engine owns the voice
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tracing::info!("Speaking with voice '{}' at {}x speed", settings.tts_voice, speed);
 
-        // Emit completion for now
-        if let Err(e) = app_handle.emit("tts-finished", ()) {
-            tracing::warn!("Failed to emit tts-finished event: {}", e);
+        let engines = app_handle.state::<Arc<EngineRegistry>>();
+        let result = crate::commands::tts::speak_until_done(
+            &engines,
+            text,
+            settings.tts_voice,
+            settings.tts_speed,
+            speed_factor,
+        )
+        .await;
+        match result {
+            Ok(()) => events::emit(&app_handle, events::TtsFinished),
+            Err(e) => {
+                tracing::error!("TTS hotkey speech failed: {}", e);
+                events::emit(&app_handle, events::TtsError(e.to_string()));
+            }
         }
     });
 }
 
-/// Parse a shortcut string like "CommandOrControl+Shift+D" into a Shortcut
-fn parse_shortcut(shortcut_str: &str) -> Option<Shortcut> {
-    let parts: Vec<&str> = shortcut_str.split('+').collect();
-    if parts.is_empty() {
-        return None;
-    }
+/// Modifiers in the order they are written, with their backend name and symbol
+const MODIFIER_NAMES: &[(Modifiers, &str, &str)] = &[
+    (Modifiers::SUPER, "CommandOrControl", "\u{2318}"),
+    (Modifiers::CONTROL, "Control", "\u{2303}"),
+    (Modifiers::SHIFT, "Shift", "\u{21E7}"),
+    (Modifiers::ALT, "Alt", "\u{2325}"),
+];
 
+/// Keys usable in a hotkey and their names in shortcut strings
+const KEY_NAMES: &[(Code, &str)] = &[
+    (Code::KeyA, "A"),
+    (Code::KeyB, "B"),
+    (Code::KeyC, "C"),
+    (Code::KeyD, "D"),
+    (Code::KeyE, "E"),
+    (Code::KeyF, "F"),
+    (Code::KeyG, "G"),
+    (Code::KeyH, "H"),
+    (Code::KeyI, "I"),
+    (Code::KeyJ, "J"),
+    (Code::KeyK, "K"),
+    (Code::KeyL, "L"),
+    (Code::KeyM, "M"),
+    (Code::KeyN, "N"),
+    (Code::KeyO, "O"),
+    (Code::KeyP, "P"),
+    (Code::KeyQ, "Q"),
+    (Code::KeyR, "R"),
+    (Code::KeyS, "S"),
+    (Code::KeyT, "T"),
+    (Code::KeyU, "U"),
+    (Code::KeyV, "V"),
+    (Code::KeyW, "W"),
+    (Code::KeyX, "X"),
+    (Code::KeyY, "Y"),
+    (Code::KeyZ, "Z"),
+    (Code::Digit0, "0"),
+    (Code::Digit1, "1"),
+    (Code::Digit2, "2"),
+    (Code::Digit3, "3"),
+    (Code::Digit4, "4"),
+    (Code::Digit5, "5"),
+    (Code::Digit6, "6"),
+    (Code::Digit7, "7"),
+    (Code::Digit8, "8"),
+    (Code::Digit9, "9"),
+    (Code::F1, "F1"),
+    (Code::F2, "F2"),
+    (Code::F3, "F3"),
+    (Code::F4, "F4"),
+    (Code::F5, "F5"),
+    (Code::F6, "F6"),
+    (Code::F7, "F7"),
+    (Code::F8, "F8"),
+    (Code::F9, "F9"),
+    (Code::F10, "F10"),
+    (Code::F11, "F11"),
+    (Code::F12, "F12"),
+    (Code::Space, "Space"),
+    (Code::Enter, "Enter"),
+    (Code::Escape, "Escape"),
+    (Code::Tab, "Tab"),
+    (Code::Backspace, "Backspace"),
+    (Code::ArrowUp, "Up"),
+    (Code::ArrowDown, "Down"),
+    (Code::ArrowLeft, "Left"),
+    (Code::ArrowRight, "Right"),
+];
+
+/// Parse a shortcut string like "CommandOrControl+Shift+D" into a Shortcut
+pub(crate) fn parse_shortcut(shortcut_str: &str) -> Option<Shortcut> {
     let mut modifiers = Modifiers::empty();
     let mut code = None;
 
-    for part in parts {
+    for part in shortcut_str.split('+') {
         let part = part.trim();
         match part.to_lowercase().as_str() {
             "command" | "commandorcontrol" | "cmd" | "super" => {
@@ -392,64 +1434,16 @@ fn parse_shortcut(shortcut_str: &str) -> Option<Shortcut> {
             "alt" | "option" => {
                 modifiers |= Modifiers::ALT;
             }
-            // Letters
-            "a" => code = Some(Code::KeyA),
-            "b" => code = Some(Code::KeyB),
-            "c" => code = Some(Code::KeyC),
-            "d" => code = Some(Code::KeyD),
-            "e" => code = Some(Code::KeyE),
-            "f" => code = Some(Code::KeyF),
-            "g" => code = Some(Code::KeyG),
-            "h" => code = Some(Code::KeyH),
-            "i" => code = Some(Code::KeyI),
-            "j" => code = Some(Code::KeyJ),
-            "k" => code = Some(Code::KeyK),
-            "l" => code = Some(Code::KeyL),
-            "m" => code = Some(Code::KeyM),
-            "n" => code = Some(Code::KeyN),
-            "o" => code = Some(Code::KeyO),
-            "p" => code = Some(Code::KeyP),
-            "q" => code = Some(Code::KeyQ),
-            "r" => code = Some(Code::KeyR),
-            "s" => code = Some(Code::KeyS),
-            "t" => code = Some(Code::KeyT),
-            "u" => code = Some(Code::KeyU),
-            "v" => code = Some(Code::KeyV),
-            "w" => code = Some(Code::KeyW),
-            "x" => code = Some(Code::KeyX),
-            "y" => code = Some(Code::KeyY),
-            "z" => code = Some(Code::KeyZ),
-            // Numbers
-            "0" => code = Some(Code::Digit0),
-            "1" => code = Some(Code::Digit1),
-            "2" => code = Some(Code::Digit2),
-            "3" => code = Some(Code::Digit3),
-            "4" => code = Some(Code::Digit4),
-            "5" => code = Some(Code::Digit5),
-            "6" => code = Some(Code::Digit6),
-            "7" => code = Some(Code::Digit7),
-            "8" => code = Some(Code::Digit8),
-            "9" => code = Some(Code::Digit9),
-            // Function keys
-            "f1" => code = Some(Code::F1),
-            "f2" => code = Some(Code::F2),
-            "f3" => code = Some(Code::F3),
-            "f4" => code = Some(Code::F4),
-            "f5" => code = Some(Code::F5),
-            "f6" => code = Some(Code::F6),
-            "f7" => code = Some(Code::F7),
-            "f8" => code = Some(Code::F8),
-            "f9" => code = Some(Code::F9),
-            "f10" => code = Some(Code::F10),
-            "f11" => code = Some(Code::F11),
-            "f12" => code = Some(Code::F12),
-            // Special keys
-            "space" => code = Some(Code::Space),
-            "enter" | "return" => code = Some(Code::Enter),
-            "escape" | "esc" => code = Some(Code::Escape),
-            "tab" => code = Some(Code::Tab),
-            "backspace" => code = Some(Code::Backspace),
-            _ => {}
+            "return" => code = Some(Code::Enter),
+            "esc" => code = Some(Code::Escape),
+            _ => {
+                if let Some((key, _)) = KEY_NAMES
+                    .iter()
+                    .find(|(_, name)| name.eq_ignore_ascii_case(part))
+                {
+                    code = Some(*key);
+                }
+            }
         }
     }
 
@@ -461,3 +1455,427 @@ fn parse_shortcut(shortcut_str: &str) -> Option<Shortcut> {
         }
     })
 }
+
+/// Format a shortcut the way `parse_shortcut` reads it, e.g. "CommandOrControl+Shift+D"
+pub(crate) fn format_shortcut(shortcut: &Shortcut) -> String {
+    let mut parts: Vec<String> = MODIFIER_NAMES
+        .iter()
+        .filter(|(modifier, _, _)| shortcut.mods.contains(*modifier))
+        .map(|(_, name, _)| name.to_string())
+        .collect();
+    parts.push(key_name(shortcut.key));
+    parts.join("+")
+}
+
+/// Human-readable form of a shortcut, e.g. "⌘ + ⇧ + D", matching the settings UI
+pub(crate) fn display_shortcut(shortcut: &Shortcut) -> String {
+    let mut parts: Vec<String> = MODIFIER_NAMES
+        .iter()
+        .filter(|(modifier, _, _)| shortcut.mods.contains(*modifier))
+        .map(|(_, _, symbol)| symbol.to_string())
+        .collect();
+    parts.push(key_name(shortcut.key));
+    parts.join(" + ")
+}
+
+fn key_name(code: Code) -> String {
+    KEY_NAMES
+        .iter()
+        .find(|(key, _)| *key == code)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| format!("{:?}", code))
+}
+
+/// Every modifier combination paired with every known key
+fn capture_candidates() -> Vec<Shortcut> {
+    let mut candidates = Vec::new();
+    for mask in 1..(1u32 << MODIFIER_NAMES.len()) {
+        let mut modifiers = Modifiers::empty();
+        for (bit, (modifier, _, _)) in MODIFIER_NAMES.iter().enumerate() {
+            if mask & (1 << bit) != 0 {
+                modifiers |= *modifier;
+            }
+        }
+        candidates.extend(
+            KEY_NAMES
+                .iter()
+                .map(|(key, _)| Shortcut::new(Some(modifiers), *key)),
+        );
+    }
+    candidates
+}
+
+/// Listen for the next key combination pressed anywhere and emit it as
+/// `shortcut-captured`, or `shortcut-capture-timeout` after
+/// `SHORTCUT_CAPTURE_TIMEOUT`.
+///
+/// Global shortcuts can only match combinations registered up front, so every
+/// modifier + key combination is registered for the duration of the capture,
+/// and the configured hotkeys are restored afterwards. A combination needs at
+/// least one modifier; modifier keys on their own never match.
+pub fn start_shortcut_capture(app: &AppHandle) -> Result<(), BlahError> {
+    let Some(generation) = begin_shortcut_capture() else {
+        return Err(BlahError::Busy("Already waiting for a shortcut".to_string()));
+    };
+
+    // Free the current hotkeys so they can be captured too
+    if let Err(e) = app.global_shortcut().unregister_all() {
+        tracing::warn!("Failed to unregister hotkeys for capture: {}", e);
+    }
+
+    let mut registered = 0;
+    for shortcut in capture_candidates() {
+        let result = app.global_shortcut().on_shortcut(shortcut, |app, shortcut, event| {
+            if event.state == ShortcutState::Pressed
+                && CAPTURING_SHORTCUT.swap(false, Ordering::SeqCst)
+            {
+                let app = app.clone();
+                let shortcut = *shortcut;
+                // Re-registering from inside a shortcut handler isn't safe
+                tauri::async_runtime::spawn(async move {
+                    finish_shortcut_capture(&app, Some(shortcut));
+                });
+            }
+        });
+        match result {
+            Ok(()) => registered += 1,
+            // Combinations taken by other apps or the system can't be captured
+            Err(e) => tracing::debug!("Can't capture {}: {}", format_shortcut(&shortcut), e),
+        }
+    }
+
+    if registered == 0 {
+        CAPTURING_SHORTCUT.store(false, Ordering::SeqCst);
+        finish_shortcut_capture(app, None);
+        return Err(BlahError::Internal("Failed to listen for shortcuts".to_string()));
+    }
+    tracing::info!("Waiting for a shortcut ({} combinations registered)", registered);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SHORTCUT_CAPTURE_TIMEOUT).await;
+        if CAPTURE_GENERATION.load(Ordering::SeqCst) == generation
+            && CAPTURING_SHORTCUT.swap(false, Ordering::SeqCst)
+        {
+            tracing::info!("Shortcut capture timed out");
+            finish_shortcut_capture(&app, None);
+        }
+    });
+
+    Ok(())
+}
+
+/// Mark a shortcut capture as listening. Returns its generation, or `None`
+/// if another capture is already listening.
+fn begin_shortcut_capture() -> Option<u64> {
+    if CAPTURING_SHORTCUT.swap(true, Ordering::SeqCst) {
+        return None;
+    }
+    Some(CAPTURE_GENERATION.fetch_add(1, Ordering::SeqCst) + 1)
+}
+
+/// Stop a shortcut capture that is still listening, e.g. because the
+/// recorder was closed, and put the configured hotkeys back
+pub fn cancel_shortcut_capture(app: &AppHandle) {
+    end_shortcut_capture(|| {
+        if let Err(e) = refresh_hotkeys(app) {
+            tracing::error!("Failed to restore hotkeys after capture: {}", e);
+        }
+    });
+}
+
+/// End the listening capture, if any, calling `restore` to register the
+/// configured hotkeys again. Its timeout won't fire for a later capture.
+/// Returns whether a capture was listening.
+fn end_shortcut_capture(restore: impl FnOnce()) -> bool {
+    CAPTURE_GENERATION.fetch_add(1, Ordering::SeqCst);
+    if !CAPTURING_SHORTCUT.swap(false, Ordering::SeqCst) {
+        return false;
+    }
+    tracing::info!("Shortcut capture cancelled");
+    restore();
+    true
+}
+
+/// Restore the configured hotkeys and report the captured shortcut, if any
+fn finish_shortcut_capture(app: &AppHandle, shortcut: Option<Shortcut>) {
+    if let Err(e) = refresh_hotkeys(app) {
+        tracing::error!("Failed to restore hotkeys after capture: {}", e);
+    }
+
+    match shortcut {
+        Some(shortcut) => {
+            let shortcut_str = format_shortcut(&shortcut);
+            tracing::info!("Captured shortcut {}", shortcut_str);
+            events::emit(
+                app,
+                events::ShortcutCaptured {
+                    shortcut_str,
+                    display: display_shortcut(&shortcut),
+                },
+            );
+        }
+        None => events::emit(app, events::ShortcutCaptureTimeout),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tts_press_kind() {
+        let threshold = Duration::from_millis(600);
+        let start = Instant::now();
+        let press = |held_ms: Option<u64>| TtsPress {
+            id: 1,
+            pressed_at: start,
+            released_at: held_ms.map(|ms| start + Duration::from_millis(ms)),
+        };
+        let at = |ms: u64| start + Duration::from_millis(ms);
+
+        // Still held, not long enough yet
+        assert_eq!(press_kind(Some(&press(None)), at(300), threshold), None);
+        assert_eq!(
+            press_kind(Some(&press(None)), at(600), threshold),
+            Some(PressKind::Hold)
+        );
+        assert_eq!(
+            press_kind(Some(&press(Some(200))), at(900), threshold),
+            Some(PressKind::Tap)
+        );
+        assert_eq!(
+            press_kind(Some(&press(Some(700))), at(900), threshold),
+            Some(PressKind::Hold)
+        );
+        // Replaced by a later press
+        assert_eq!(press_kind(None, at(0), threshold), Some(PressKind::Tap));
+    }
+
+    #[test]
+    fn test_tts_presses_ignore_key_repeat() {
+        let state = HotkeyState::default();
+        assert_eq!(state.tts_pressed(), Some(1));
+        assert_eq!(state.tts_pressed(), None);
+        state.tts_released();
+        assert!(state.tts_press(1).unwrap().released_at.is_some());
+        assert_eq!(state.tts_pressed(), Some(2));
+        assert_eq!(state.tts_press(1), None);
+    }
+
+    #[test]
+    fn test_hold_speech() {
+        assert_eq!(
+            hold_speech("AB1", TtsHoldAction::Spell),
+            ("A. B. one.".to_string(), 1.0)
+        );
+        let long = "a sentence far too long to be worth spelling out";
+        assert_eq!(
+            hold_speech(long, TtsHoldAction::Spell),
+            (long.to_string(), TTS_HOLD_SLOW_FACTOR)
+        );
+        assert_eq!(
+            hold_speech("AB1", TtsHoldAction::Slow),
+            ("AB1".to_string(), TTS_HOLD_SLOW_FACTOR)
+        );
+        assert_eq!(
+            serde_json::to_value(TtsHoldAction::None).unwrap(),
+            serde_json::json!("none")
+        );
+    }
+
+    #[test]
+    fn test_session_recording_totals() {
+        let state = HotkeyState::default();
+        state.add_recording_time(Duration::from_millis(1500));
+        state.add_recording_time(Duration::from_millis(250));
+        assert_eq!(state.total_recording_secs(), 1.75);
+
+        state.add_dictated_text("héllo");
+        state.add_dictated_text("");
+        assert_eq!(state.chars_dictated.load(Ordering::Relaxed), 5);
+
+        // A stop without a start adds nothing
+        state.recording_stopped();
+        assert_eq!(state.total_recording_secs(), 1.75);
+
+        state.recording_started();
+        state.recording_stopped();
+        assert_eq!(state.recording_count.load(Ordering::Relaxed), 1);
+        assert!(state.total_recording_secs() >= 1.75);
+    }
+
+    #[test]
+    fn test_held_without_speech() {
+        let held = |secs| Some(Duration::from_secs(secs));
+        assert!(held_without_speech(held(10), Some(false), 10));
+        assert!(!held_without_speech(held(9), Some(false), 10));
+        // Speech, silence detection off, the setting off, or a released key
+        assert!(!held_without_speech(held(30), Some(true), 10));
+        assert!(!held_without_speech(held(30), None, 10));
+        assert!(!held_without_speech(held(30), Some(false), 0));
+        assert!(!held_without_speech(None, Some(false), 10));
+    }
+
+    #[test]
+    fn test_transcription_failure_notice() {
+        let missing = BlahError::ModelNotDownloaded("Model not found".to_string());
+        assert_eq!(
+            transcription_failure_notice(&missing, true),
+            Some(("Downloading model…", OverlayLevel::Info))
+        );
+        assert_eq!(
+            transcription_failure_notice(&missing, false),
+            Some(("Model not found", OverlayLevel::Error))
+        );
+        let failed = BlahError::TranscriptionFailed("Decoder error".to_string());
+        assert_eq!(transcription_failure_notice(&failed, false), None);
+    }
+
+    #[test]
+    fn test_parse_shortcut() {
+        let shortcut = parse_shortcut("CommandOrControl+Shift+D").unwrap();
+        assert_eq!(shortcut.mods, Modifiers::SUPER | Modifiers::SHIFT);
+        assert_eq!(shortcut.key, Code::KeyD);
+
+        assert_eq!(parse_shortcut("cmd+alt+return").unwrap().key, Code::Enter);
+        assert_eq!(parse_shortcut("Control+Up").unwrap().key, Code::ArrowUp);
+        assert!(parse_shortcut("Shift").is_none());
+        assert!(parse_shortcut("").is_none());
+    }
+
+    #[test]
+    fn test_format_shortcut_round_trips() {
+        for text in ["CommandOrControl+Shift+D", "Control+Alt+F5", "Shift+Space", "Alt+Left"] {
+            let shortcut = parse_shortcut(text).unwrap();
+            assert_eq!(format_shortcut(&shortcut), text);
+        }
+        // Modifiers are written in a fixed order
+        let shortcut = parse_shortcut("shift+cmd+s").unwrap();
+        assert_eq!(format_shortcut(&shortcut), "CommandOrControl+Shift+S");
+    }
+
+    #[test]
+    fn test_display_shortcut() {
+        let shortcut = parse_shortcut("CommandOrControl+Shift+D").unwrap();
+        assert_eq!(display_shortcut(&shortcut), "\u{2318} + \u{21E7} + D");
+    }
+
+    #[test]
+    fn test_duplicate_hotkeys_via_aliases() {
+        let err = validate_hotkeys("Cmd+Shift+D", "Super+Shift+D").unwrap_err();
+        assert_eq!(err.code(), "invalid_setting");
+        assert!(
+            matches!(err, BlahError::InvalidSetting { ref field, .. } if field == "tts_hotkey")
+        );
+
+        let (stt, tts, conflicts) = resolve_hotkeys("Cmd+Shift+D", "Super+Shift+D");
+        assert_eq!(format_shortcut(&stt), "CommandOrControl+Shift+D");
+        assert_eq!(format_shortcut(&tts), "CommandOrControl+Shift+S");
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].field, "tts_hotkey");
+        assert_eq!(conflicts[0].conflicts_with, Some("stt_hotkey"));
+    }
+
+    #[test]
+    fn test_duplicate_of_default_tts_moves_stt() {
+        let (stt, tts, conflicts) = resolve_hotkeys("CommandOrControl+Shift+S", "cmd+shift+s");
+        assert_eq!(format_shortcut(&stt), "CommandOrControl+Shift+D");
+        assert_eq!(format_shortcut(&tts), "CommandOrControl+Shift+S");
+        assert_eq!(conflicts[0].field, "stt_hotkey");
+    }
+
+    #[test]
+    fn test_reserved_hotkeys() {
+        assert!(is_reserved(&parse_shortcut("Cmd+Q").unwrap()));
+        assert!(is_reserved(&parse_shortcut("Command+Space").unwrap()));
+        assert!(!is_reserved(&parse_shortcut("Cmd+Shift+Q").unwrap()));
+
+        let err = validate_hotkeys("Cmd+Space", "CommandOrControl+Shift+S").unwrap_err();
+        assert!(
+            matches!(err, BlahError::InvalidSetting { ref field, .. } if field == "stt_hotkey")
+        );
+
+        let (stt, _, conflicts) = resolve_hotkeys("Cmd+Q", "CommandOrControl+Shift+S");
+        assert_eq!(format_shortcut(&stt), "CommandOrControl+Shift+D");
+        assert_eq!(conflicts[0].conflicts_with, None);
+    }
+
+    #[test]
+    fn test_distinct_hotkeys_are_valid() {
+        assert!(validate_hotkeys("CommandOrControl+Shift+D", "CommandOrControl+Shift+S").is_ok());
+        let (_, _, conflicts) = resolve_hotkeys("Alt+Space", "Control+Alt+R");
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_replay_hotkey_conflicts() {
+        let defaults = ("CommandOrControl+Shift+D", "CommandOrControl+Shift+S");
+        assert!(validate_replay_hotkey("Alt+Shift+R", defaults.0, defaults.1).is_ok());
+
+        for replay in ["Super+Shift+S", "Cmd+Q", "Shift+Nope"] {
+            let err = validate_replay_hotkey(replay, defaults.0, defaults.1).unwrap_err();
+            assert!(
+                matches!(err, BlahError::InvalidSetting { ref field, .. } if field == "replay_hotkey")
+            );
+        }
+
+        // Checked against the dictation hotkey as it will be registered
+        let err = validate_replay_hotkey("CommandOrControl+Shift+D", "Cmd+Q", defaults.1);
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn test_undo_paste_hotkey_conflicts() {
+        let (stt, tts) = ("CommandOrControl+Shift+D", "CommandOrControl+Shift+S");
+        let replay = Some("Alt+Shift+R");
+        assert!(validate_undo_paste_hotkey("Alt+Shift+Z", stt, tts, replay).is_ok());
+        assert!(validate_undo_paste_hotkey("Alt+Shift+R", stt, tts, None).is_ok());
+
+        for undo in ["Super+Shift+D", "Alt+Shift+R", "Cmd+W"] {
+            let err = validate_undo_paste_hotkey(undo, stt, tts, replay).unwrap_err();
+            assert!(
+                matches!(err, BlahError::InvalidSetting { ref field, .. } if field == "undo_paste_hotkey")
+            );
+        }
+    }
+
+    #[test]
+    fn test_tts_clipboard_hotkey_conflicts() {
+        let (stt, tts) = ("CommandOrControl+Shift+D", "CommandOrControl+Shift+S");
+        let others = [Some("Alt+Shift+R"), Some("Alt+Shift+Z")];
+        assert!(validate_tts_clipboard_hotkey("Alt+Shift+C", stt, tts, &others).is_ok());
+        assert!(validate_tts_clipboard_hotkey("Alt+Shift+Z", stt, tts, &[None, None]).is_ok());
+
+        for hotkey in ["Super+Shift+S", "Alt+Shift+R", "Alt+Shift+Z", "Cmd+Q"] {
+            let err = validate_tts_clipboard_hotkey(hotkey, stt, tts, &others).unwrap_err();
+            assert!(
+                matches!(err, BlahError::InvalidSetting { ref field, .. } if field == "tts_clipboard_hotkey")
+            );
+        }
+    }
+
+    #[test]
+    fn test_capture_candidates_all_have_modifiers() {
+        let candidates = capture_candidates();
+        assert_eq!(candidates.len(), 15 * KEY_NAMES.len());
+        assert!(candidates.iter().all(|s| !s.mods.is_empty()));
+    }
+
+    #[test]
+    fn test_cancel_shortcut_capture_restores_hotkeys() {
+        let generation = begin_shortcut_capture().unwrap();
+        assert_eq!(begin_shortcut_capture(), None);
+
+        let mut restored = 0;
+        assert!(end_shortcut_capture(|| restored += 1));
+        assert_eq!(restored, 1);
+        // The capture's timeout no longer matches, and a new one can start
+        assert_ne!(CAPTURE_GENERATION.load(Ordering::SeqCst), generation);
+        assert!(!CAPTURING_SHORTCUT.load(Ordering::SeqCst));
+
+        // Once the capture is over there is nothing to restore
+        assert!(!end_shortcut_capture(|| restored += 1));
+        assert_eq!(restored, 1);
+    }
+}
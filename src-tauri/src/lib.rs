@@ -1,28 +1,43 @@
 mod accessibility;
+mod app_update;
 mod audio;
+mod audio_levels;
+mod clipboard_watcher;
 mod commands;
+mod dictation_retry;
+mod dictation_timing;
+mod engine_status;
 mod engines;
+mod error;
+mod events;
 mod hotkeys;
+mod lifecycle;
+mod live_transcript;
+mod logging;
+mod mic_indicator;
 mod models;
 mod overlay;
+mod paste_undo;
+mod post_action;
+mod text;
 
 use std::sync::Arc;
 
 use tauri::{
-    menu::{Menu, MenuItem},
+    menu::{CheckMenuItem, Menu, MenuItem},
     tray::TrayIconBuilder,
     Manager,
 };
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+/// Id of the menu bar icon
+pub(crate) const TRAY_ID: &str = "main";
 
 pub fn run() {
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(
-            std::env::var("RUST_LOG").unwrap_or_else(|_| "blah3=debug,info".into()),
-        ))
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    // Initialize tracing (stdout + rotating log file)
+    let log_level = commands::settings::get_settings()
+        .unwrap_or_default()
+        .log_level;
+    let log_filter = logging::init(&log_level);
 
     tracing::info!("Starting Blah³...");
 
@@ -34,23 +49,41 @@ pub fn run() {
             None, // No extra args
         ))
         .manage(Arc::new(hotkeys::HotkeyState::default()))
+        .manage(Arc::new(engines::EngineRegistry::default()))
+        .manage(models::download::DownloadManager::new())
+        .manage(log_filter)
         .setup(|app| {
             // Create tray menu
+            let settings = commands::settings::get_settings().unwrap_or_default();
+
             let show_i = MenuItem::with_id(app, "show", "Show Blah³", true, None::<&str>)?;
+            let speak_on_copy_i = CheckMenuItem::with_id(
+                app,
+                "speak_on_copy",
+                "Speak on Copy",
+                true,
+                settings.speak_on_copy,
+                None::<&str>,
+            )?;
             let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&show_i, &quit_i])?;
+            let menu = Menu::with_items(app, &[&show_i, &speak_on_copy_i, &quit_i])?;
+            app.manage(clipboard_watcher::SpeakOnCopyMenuItem(speak_on_copy_i.clone()));
 
             // Build tray icon
-            let _tray = TrayIconBuilder::new()
+            let _tray = TrayIconBuilder::with_id(TRAY_ID)
                 .menu(&menu)
-                .tooltip("Blah³ - Voice Toolkit")
+                .tooltip(mic_indicator::IDLE_TOOLTIP)
                 .on_menu_event(|app, event| match event.id.as_ref() {
                     "show" => {
                         if let Some(window) = app.get_webview_window("main") {
                             let _ = window.show();
                             let _ = window.set_focus();
+                            audio_levels::set_main_visible(true);
                         }
                     }
+                    "speak_on_copy" => {
+                        clipboard_watcher::toggle(app);
+                    }
                     "quit" => {
                         app.exit(0);
                     }
@@ -65,32 +98,164 @@ pub fn run() {
                 tracing::info!("Global hotkeys registered successfully");
             }
 
+            // Clean up temp files left behind by interrupted model downloads
+            models::download::sweep_stale_downloads(
+                &commands::models::get_models_dir(),
+                std::time::Duration::from_secs(24 * 60 * 60),
+            );
+
+            // Flag a configured speech model that isn't there any more
+            commands::models::report_missing_stt_model(app.handle());
+
+            engine_status::init(app.handle().clone());
+
+            // Unload the TTS model when it hasn't been used for a while
+            let engines = app.state::<Arc<engines::EngineRegistry>>();
+            commands::tts::spawn_tts_idle_eviction(Arc::clone(&engines));
+            commands::tts::spawn_tts_progress(app.handle().clone());
+
+            if settings.speak_on_copy {
+                clipboard_watcher::set_enabled(app.handle(), true);
+            }
+
+            // Surface hung System Events calls, e.g. a TTS press that read nothing
+            let timeout_handle = app.handle().clone();
+            accessibility::applescript::on_timeout(move |script| {
+                let timed_out = events::AppleScriptTimedOut {
+                    script: script.to_string(),
+                    timeout_ms: accessibility::applescript::SCRIPT_TIMEOUT.as_millis() as u64,
+                };
+                events::emit(&timeout_handle, timed_out);
+            });
+
+            // Stop recording on sleep, re-register hotkeys on wake
+            lifecycle::watch_power_events(app.handle().clone());
+
+            // Warn when the Mac starts throttling
+            commands::settings::spawn_thermal_monitor(app.handle().clone());
+
             // Show main window on startup (for development)
             #[cfg(debug_assertions)]
             if let Some(window) = app.get_webview_window("main") {
                 let _ = window.show();
             }
+            // The main window opens visible
+            if let Some(window) = app.get_webview_window("main") {
+                audio_levels::set_main_visible(window.is_visible().unwrap_or(false));
+            }
 
             Ok(())
         })
+        .on_window_event(|window, event| match event {
+            // Remember where the user went, for get_selection
+            tauri::WindowEvent::Focused(false) if window.label() == "main" => {
+                let own_bundle_id = window.config().identifier.clone();
+                tauri::async_runtime::spawn_blocking(move || {
+                    accessibility::frontmost_app::remember_previous_app(&own_bundle_id);
+                });
+            }
+            tauri::WindowEvent::Destroyed => {
+                audio_levels::unsubscribe(window.label());
+                if window.label() == "main" {
+                    audio_levels::set_main_visible(false);
+                }
+            }
+            _ => {}
+        })
         .invoke_handler(tauri::generate_handler![
             commands::stt::start_recording,
             commands::stt::stop_recording,
             commands::stt::transcribe_audio,
+            commands::stt::transcribe_audio_diarized,
+            commands::stt::finish_review,
+            commands::stt::set_whisper_prompt,
+            commands::stt::get_whisper_config,
+            commands::stt::update_whisper_config,
+            commands::stt::set_overlay_interactivity,
             commands::stt::is_silence_triggered,
             commands::stt::is_recording,
+            commands::stt::get_recent_transcriptions,
+            commands::stt::get_last_transcription,
+            commands::stt::get_current_transcript,
+            commands::stt::copy_transcription_to_clipboard,
+            commands::stt::undo_last_paste,
+            commands::stt::retry_last_transcription,
+            commands::stt::get_supported_languages,
+            commands::stt::calibrate_silence_threshold,
+            commands::stt::set_silence_config,
+            commands::stt::calibrate_silence,
+            commands::stt::record_ambient_noise_profile,
+            commands::stt::test_input_level,
+            commands::stt::get_noise_floor_db,
+            commands::stt::list_input_devices,
+            commands::stt::validate_model_file,
+            events::export_event_types,
+            audio_levels::subscribe_audio_levels,
+            commands::batch::transcribe_folder,
+            commands::batch::cancel_batch,
             commands::tts::speak_text,
             commands::tts::stop_speaking,
+            commands::tts::cancel_synthesis,
+            commands::tts::replay_last_speech,
+            commands::tts::speak_clipboard,
+            commands::tts::estimate_reading_time,
             commands::tts::get_voices,
+            commands::tts::list_custom_voices,
+            commands::tts::save_custom_voice,
+            commands::tts::delete_custom_voice,
+            commands::tts::set_voice_speed_override,
+            commands::tts::preview_voice,
+            commands::tts::list_tts_cache_entries,
+            commands::tts::clear_tts_cache,
+            commands::tts::clear_tts_cache_entry,
             commands::models::list_models,
+            engine_status::get_engine_status,
+            commands::models::can_run_model,
             commands::models::download_model,
+            commands::models::download_model_to_path,
+            commands::models::import_from_url,
+            commands::models::rename_custom_model,
+            commands::models::get_recommended_download_size,
+            commands::models::cancel_all_downloads,
+            commands::models::get_models_path,
             commands::models::delete_model,
             commands::models::get_model_status,
+            commands::models::delete_all_models,
             commands::settings::get_settings,
             commands::settings::update_settings,
+            commands::settings::update_settings_field,
+            commands::settings::migrate_from_competitor,
+            commands::settings::get_current_onboarding_step,
+            commands::settings::advance_onboarding_step,
+            commands::settings::skip_onboarding,
+            commands::settings::start_shortcut_capture,
+            commands::settings::cancel_shortcut_capture,
             commands::settings::get_hardware_info,
+            commands::settings::get_app_version,
+            commands::settings::check_for_app_updates,
+            commands::settings::set_log_level,
+            commands::settings::get_log_level,
+            commands::settings::get_log_file_path,
+            commands::settings::open_log_file,
+            commands::settings::open_models_directory,
+            commands::settings::open_settings_directory,
+            commands::settings::get_diagnostic_snapshot,
+            commands::settings::get_diagnostics,
+            commands::settings::get_session_stats,
+            commands::settings::get_app_state,
+            commands::settings::copy_diagnostics_to_clipboard,
+            commands::config::export_config,
+            commands::config::import_config,
             commands::permissions::check_permissions,
+            commands::permissions::reset_accessibility_hint,
+            commands::accessibility::get_selection,
+            commands::accessibility::paste_into_frontmost,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                lifecycle::shutdown(app);
+            }
+        });
 }
@@ -0,0 +1,229 @@
+//! App quit and system sleep/wake handling.
+//!
+//! On quit, recording and playback are stopped before the process tears
+//! down so the microphone is released cleanly. On sleep, an active recording
+//! is stopped and what was captured is transcribed, since the capture stream
+//! comes back garbled after wake. On wake, the hotkeys are registered again
+//! because macOS occasionally stops delivering them after sleep.
+
+// Sleep and wake notifications are only wired up on macOS
+#![cfg_attr(not(target_os = "macos"), allow(dead_code))]
+
+use serde::Serialize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+
+use crate::commands::{stt, tts};
+use crate::events;
+use crate::hotkeys::{self, HotkeyState};
+
+/// Why the system, not the user, stopped a recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterruptReason {
+    SystemSleep,
+    AppQuit,
+}
+
+/// Stop recording and playback before the app exits. Settings and the
+/// model registry are written synchronously when they change, so there is
+/// nothing left to flush.
+pub fn shutdown(app: &AppHandle) {
+    tracing::info!("Shutting down");
+
+    let state = app.state::<Arc<HotkeyState>>();
+    // Quitting from the tray mid-dictation; the audio is discarded
+    match state.audio_capture.try_lock() {
+        Ok(mut guard) => {
+            if let Some(capture) = guard.take() {
+                if let Err(e) = capture.stop() {
+                    tracing::warn!("Failed to stop dictation capture: {}", e);
+                }
+            }
+        }
+        Err(_) => tracing::warn!("Dictation capture busy, leaving it to process exit"),
+    }
+    if state.key_held.swap(false, Ordering::SeqCst) {
+        events::emit(
+            app,
+            events::SttRecordingInterrupted {
+                reason: InterruptReason::AppQuit,
+                hotkey: true,
+            },
+        );
+    }
+
+    if stt::interrupt_recording() {
+        events::emit(
+            app,
+            events::SttRecordingInterrupted {
+                reason: InterruptReason::AppQuit,
+                hotkey: false,
+            },
+        );
+    }
+
+    match tts::stop_playback() {
+        Ok(true) => events::emit(app, events::TtsFinished),
+        Ok(false) => {}
+        Err(e) => tracing::warn!("Failed to stop playback: {}", e),
+    }
+}
+
+/// The Mac is about to sleep: stop any recording and transcribe what was
+/// captured so far
+pub fn handle_sleep(app: &AppHandle) {
+    tracing::info!("System going to sleep");
+    events::emit(app, events::SystemWillSleep);
+
+    if hotkeys::stop_dictation(app) {
+        tracing::info!("Stopped dictation for system sleep");
+        events::emit(
+            app,
+            events::SttRecordingInterrupted {
+                reason: InterruptReason::SystemSleep,
+                hotkey: true,
+            },
+        );
+    }
+
+    // The frontend collects the audio with stop_recording and transcribes it
+    if stt::interrupt_recording() {
+        tracing::info!("Stopped recording for system sleep");
+        events::emit(
+            app,
+            events::SttRecordingInterrupted {
+                reason: InterruptReason::SystemSleep,
+                hotkey: false,
+            },
+        );
+    }
+}
+
+/// The Mac woke up: register the hotkeys again
+pub fn handle_wake(app: &AppHandle) {
+    tracing::info!("System woke from sleep");
+
+    if let Err(e) = hotkeys::refresh_hotkeys(app) {
+        tracing::error!("Failed to re-register hotkeys after wake: {}", e);
+    }
+    events::emit(app, events::SystemDidWake);
+}
+
+/// Call `handle_sleep` and `handle_wake` as the system sleeps and wakes
+#[cfg(target_os = "macos")]
+pub fn watch_power_events(app: AppHandle) {
+    if let Err(e) = std::thread::Builder::new()
+        .name("power-events".to_string())
+        .spawn(move || power::run(app))
+    {
+        tracing::error!("Failed to start power event thread: {}", e);
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn watch_power_events(_app: AppHandle) {}
+
+/// IOKit system power notifications, delivered on a dedicated run loop
+#[cfg(target_os = "macos")]
+mod power {
+    use std::ffi::c_void;
+    use tauri::AppHandle;
+
+    type IoConnect = u32;
+    type IoObject = u32;
+    type IoNotificationPort = *mut c_void;
+    type CfRunLoop = *mut c_void;
+    type CfRunLoopSource = *mut c_void;
+    type CfString = *const c_void;
+    type InterestCallback = extern "C" fn(*mut c_void, IoObject, u32, *mut c_void);
+
+    // iokit_common_msg() values from IOKit/IOMessage.h
+    const MESSAGE_CAN_SYSTEM_SLEEP: u32 = 0xE000_0270;
+    const MESSAGE_SYSTEM_WILL_SLEEP: u32 = 0xE000_0280;
+    const MESSAGE_SYSTEM_HAS_POWERED_ON: u32 = 0xE000_0300;
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IORegisterForSystemPower(
+            refcon: *mut c_void,
+            port: *mut IoNotificationPort,
+            callback: InterestCallback,
+            notifier: *mut IoObject,
+        ) -> IoConnect;
+        fn IOAllowPowerChange(kernel_port: IoConnect, notification_id: isize) -> i32;
+        fn IONotificationPortGetRunLoopSource(port: IoNotificationPort) -> CfRunLoopSource;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        static kCFRunLoopCommonModes: CfString;
+        fn CFRunLoopGetCurrent() -> CfRunLoop;
+        fn CFRunLoopAddSource(run_loop: CfRunLoop, source: CfRunLoopSource, mode: CfString);
+        fn CFRunLoopRun();
+    }
+
+    struct PowerContext {
+        app: AppHandle,
+        root_port: IoConnect,
+    }
+
+    extern "C" fn on_power_message(
+        refcon: *mut c_void,
+        _service: IoObject,
+        message_type: u32,
+        argument: *mut c_void,
+    ) {
+        // SAFETY: refcon is the PowerContext leaked in `run`, which lives as
+        // long as the process
+        let context = unsafe { &*(refcon as *const PowerContext) };
+
+        match message_type {
+            // Never veto idle sleep
+            MESSAGE_CAN_SYSTEM_SLEEP => unsafe {
+                IOAllowPowerChange(context.root_port, argument as isize);
+            },
+            MESSAGE_SYSTEM_WILL_SLEEP => {
+                super::handle_sleep(&context.app);
+                unsafe {
+                    IOAllowPowerChange(context.root_port, argument as isize);
+                }
+            }
+            MESSAGE_SYSTEM_HAS_POWERED_ON => super::handle_wake(&context.app),
+            _ => {}
+        }
+    }
+
+    /// Register for power notifications and run the loop delivering them.
+    /// Never returns unless registration fails.
+    pub fn run(app: AppHandle) {
+        let context = Box::into_raw(Box::new(PowerContext { app, root_port: 0 }));
+        let mut port: IoNotificationPort = std::ptr::null_mut();
+        let mut notifier: IoObject = 0;
+
+        unsafe {
+            let root_port = IORegisterForSystemPower(
+                context as *mut c_void,
+                &mut port,
+                on_power_message,
+                &mut notifier,
+            );
+            if root_port == 0 {
+                tracing::error!("Failed to register for system power notifications");
+                drop(Box::from_raw(context));
+                return;
+            }
+            // Set before the run loop starts, so no callback can see 0
+            (*context).root_port = root_port;
+
+            CFRunLoopAddSource(
+                CFRunLoopGetCurrent(),
+                IONotificationPortGetRunLoopSource(port),
+                kCFRunLoopCommonModes,
+            );
+            tracing::info!("Watching for system sleep and wake");
+            CFRunLoopRun();
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! The text of the hotkey dictation in progress, kept as segments are
+//! decoded so a window can pull it with `get_current_transcript` instead of
+//! relying only on `stt-partial-result` events, which a webview that is
+//! still loading misses.
+//!
+//! Only the active dictation and the one before it are kept. A dictation
+//! moves from active to previous once it is delivered, fails or is
+//! cancelled, and is dropped when the next one finishes.
+
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+static SESSIONS: Mutex<Sessions> = Mutex::new(Sessions::new());
+
+/// Where a dictation is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptStatus {
+    Recording,
+    Transcribing,
+    /// Transcribed; `text` is the final text
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl TranscriptStatus {
+    fn is_finished(self) -> bool {
+        matches!(self, Self::Done | Self::Failed | Self::Cancelled)
+    }
+}
+
+/// The text of a dictation so far
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CurrentTranscript {
+    /// `recording_count` of the dictation, see `HotkeyState`
+    pub session_id: u32,
+    pub status: TranscriptStatus,
+    pub text: String,
+}
+
+struct Sessions {
+    active: Option<CurrentTranscript>,
+    previous: Option<CurrentTranscript>,
+}
+
+impl Sessions {
+    const fn new() -> Self {
+        Self {
+            active: None,
+            previous: None,
+        }
+    }
+
+    /// Make `session_id` the active dictation with `status`, reopening it
+    /// with its text cleared if it already finished (a retry)
+    fn begin(&mut self, session_id: u32, status: TranscriptStatus) {
+        if let Some(active) = self.active.as_mut().filter(|a| a.session_id == session_id) {
+            active.status = status;
+            return;
+        }
+        self.previous.take_if(|p| p.session_id == session_id);
+        if let Some(unfinished) = self.active.take() {
+            self.previous = Some(unfinished);
+        }
+        self.active = Some(CurrentTranscript {
+            session_id,
+            status,
+            text: String::new(),
+        });
+    }
+
+    /// Replace the text of the active dictation, if it is `session_id`
+    fn set_text(&mut self, session_id: u32, text: &str) {
+        if let Some(active) = self.active.as_mut().filter(|a| a.session_id == session_id) {
+            text.clone_into(&mut active.text);
+        }
+    }
+
+    /// End the active dictation if it is `session_id`, with its final text
+    /// when it succeeded
+    fn finish(&mut self, session_id: u32, status: TranscriptStatus, text: Option<&str>) {
+        debug_assert!(status.is_finished());
+        let Some(mut finished) = self.active.take_if(|a| a.session_id == session_id) else {
+            return;
+        };
+        finished.status = status;
+        if let Some(text) = text {
+            text.clone_into(&mut finished.text);
+        }
+        self.previous = Some(finished);
+    }
+
+    /// `session_id` if it is still kept, or the active dictation (else the
+    /// previous one) when no id is given
+    fn get(&self, session_id: Option<u32>) -> Option<CurrentTranscript> {
+        let sessions = [self.active.as_ref(), self.previous.as_ref()];
+        let mut kept = sessions.into_iter().flatten();
+        match session_id {
+            Some(id) => kept.find(|s| s.session_id == id).cloned(),
+            None => kept.next().cloned(),
+        }
+    }
+}
+
+fn with_sessions<T>(f: impl FnOnce(&mut Sessions) -> T) -> Option<T> {
+    match SESSIONS.lock() {
+        Ok(mut sessions) => Some(f(&mut sessions)),
+        Err(e) => {
+            tracing::warn!("Live transcript unavailable: {}", e);
+            None
+        }
+    }
+}
+
+/// A dictation started recording
+pub fn start_recording(session_id: u32) {
+    with_sessions(|s| s.begin(session_id, TranscriptStatus::Recording));
+}
+
+/// A dictation's audio is being transcribed, for the first time or again
+pub fn start_transcribing(session_id: u32) {
+    with_sessions(|s| s.begin(session_id, TranscriptStatus::Transcribing));
+}
+
+/// Whisper decoded more of a dictation; `text` is everything so far
+pub fn set_partial(session_id: u32, text: &str) {
+    with_sessions(|s| s.set_text(session_id, text));
+}
+
+/// A dictation was transcribed to `text`
+pub fn finish(session_id: u32, text: &str) {
+    with_sessions(|s| s.finish(session_id, TranscriptStatus::Done, Some(text)));
+}
+
+/// A dictation failed or was cancelled before it was transcribed
+pub fn abandon(session_id: u32, status: TranscriptStatus) {
+    with_sessions(|s| s.finish(session_id, status, None));
+}
+
+/// The text of dictation `session_id`, or of the latest one
+pub fn get(session_id: Option<u32>) -> Option<CurrentTranscript> {
+    with_sessions(|s| s.get(session_id)).flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_moves_through_statuses() {
+        let mut sessions = Sessions::new();
+        sessions.begin(1, TranscriptStatus::Recording);
+        assert_eq!(
+            sessions.get(None).unwrap().status,
+            TranscriptStatus::Recording
+        );
+
+        sessions.begin(1, TranscriptStatus::Transcribing);
+        sessions.set_text(1, "Hello");
+        sessions.set_text(1, "Hello world");
+        let current = sessions.get(Some(1)).unwrap();
+        assert_eq!(current.status, TranscriptStatus::Transcribing);
+        assert_eq!(current.text, "Hello world");
+
+        sessions.finish(1, TranscriptStatus::Done, Some("Hello, world."));
+        assert!(sessions.active.is_none());
+        let done = sessions.get(None).unwrap();
+        assert_eq!(done.status, TranscriptStatus::Done);
+        assert_eq!(done.text, "Hello, world.");
+    }
+
+    #[test]
+    fn test_only_active_and_previous_are_kept() {
+        let mut sessions = Sessions::new();
+        for id in 1..=3 {
+            sessions.begin(id, TranscriptStatus::Recording);
+            sessions.finish(id, TranscriptStatus::Done, Some("text"));
+        }
+        sessions.begin(4, TranscriptStatus::Recording);
+
+        assert_eq!(sessions.get(None).unwrap().session_id, 4);
+        assert_eq!(sessions.get(Some(3)).unwrap().session_id, 3);
+        assert!(sessions.get(Some(2)).is_none());
+        assert!(sessions.get(Some(1)).is_none());
+    }
+
+    #[test]
+    fn test_stale_updates_are_ignored() {
+        let mut sessions = Sessions::new();
+        sessions.begin(1, TranscriptStatus::Recording);
+        sessions.finish(1, TranscriptStatus::Cancelled, None);
+        sessions.begin(2, TranscriptStatus::Recording);
+
+        sessions.set_text(1, "late segment");
+        sessions.finish(1, TranscriptStatus::Done, Some("late"));
+        let cancelled = sessions.get(Some(1)).unwrap();
+        assert_eq!(cancelled.status, TranscriptStatus::Cancelled);
+        assert!(cancelled.text.is_empty());
+        assert_eq!(sessions.get(None).unwrap().session_id, 2);
+    }
+
+    #[test]
+    fn test_retry_reopens_failed_session() {
+        let mut sessions = Sessions::new();
+        sessions.begin(1, TranscriptStatus::Transcribing);
+        sessions.set_text(1, "partial");
+        sessions.finish(1, TranscriptStatus::Failed, None);
+        assert_eq!(sessions.get(Some(1)).unwrap().text, "partial");
+
+        sessions.begin(1, TranscriptStatus::Transcribing);
+        let retried = sessions.get(None).unwrap();
+        assert_eq!(retried.status, TranscriptStatus::Transcribing);
+        assert!(retried.text.is_empty());
+        assert!(sessions.previous.is_none());
+    }
+
+    #[test]
+    fn test_status_serializes_snake_case() {
+        let current = CurrentTranscript {
+            session_id: 7,
+            status: TranscriptStatus::Transcribing,
+            text: "Hi".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(&current).unwrap(),
+            serde_json::json!({ "session_id": 7, "status": "transcribing", "text": "Hi" })
+        );
+    }
+}
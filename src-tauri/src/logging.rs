@@ -0,0 +1,253 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::{
+    layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Registry,
+};
+
+use crate::error::BlahError;
+
+pub const LOG_FILE_NAME: &str = "blah3.log";
+
+/// Levels `set_log_level` accepts, least detailed first
+pub const LOG_LEVELS: [&str; 5] = ["error", "warn", "info", "debug", "trace"];
+
+pub const DEFAULT_LOG_LEVEL: &str = "debug";
+
+/// Rotate the log once it grows past this size
+const MAX_LOG_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Number of log files kept, including the active one
+const MAX_LOG_FILES: usize = 5;
+
+// Keeps the background log writer alive for the lifetime of the process
+static LOG_GUARD: OnceLock<WorkerGuard> = OnceLock::new();
+
+pub fn get_log_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("com.blahcubed.app")
+        .join("logs")
+}
+
+pub fn get_log_file_path() -> PathBuf {
+    get_log_dir().join(LOG_FILE_NAME)
+}
+
+/// Changes the log filter of the running app; kept in Tauri state
+pub struct LogFilterHandle(reload::Handle<EnvFilter, Registry>);
+
+impl LogFilterHandle {
+    /// Log at `level` from now on, one of `LOG_LEVELS`
+    pub fn set_level(&self, level: &str) -> Result<(), BlahError> {
+        self.0
+            .reload(level_filter(level))
+            .map_err(|e| BlahError::Internal(format!("Failed to change the log level: {}", e)))
+    }
+}
+
+/// Filter for logging this app at `level`. Other crates log at `info`, or
+/// at `level` when that is quieter.
+fn level_filter(level: &str) -> EnvFilter {
+    let others = if matches!(level, "error" | "warn") {
+        level
+    } else {
+        "info"
+    };
+    EnvFilter::new(format!("blah3={},{}", level, others))
+}
+
+/// Set up tracing to stdout and to a size-rotated log file, logging at
+/// `level` unless `RUST_LOG` says otherwise. Falls back to stdout only if
+/// the log file can't be opened.
+pub fn init(level: &str) -> LogFilterHandle {
+    let filter = match std::env::var("RUST_LOG") {
+        Ok(directives) => EnvFilter::new(directives),
+        Err(_) => level_filter(level),
+    };
+    let (filter, handle) = reload::Layer::new(filter);
+
+    let file_layer = match SizeRotatingWriter::new(&get_log_dir(), MAX_LOG_BYTES, MAX_LOG_FILES) {
+        Ok(writer) => {
+            let (non_blocking, guard) = tracing_appender::non_blocking(writer);
+            let _ = LOG_GUARD.set(guard);
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .with_ansi(false)
+                    .with_writer(non_blocking),
+            )
+        }
+        Err(e) => {
+            eprintln!("Failed to open log file, logging to stdout only: {}", e);
+            None
+        }
+    };
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .with(file_layer)
+        .init();
+
+    LogFilterHandle(handle)
+}
+
+/// Log file writer that rotates by size: `blah3.log` is the active file and
+/// older content moves to `blah3.log.1`, `blah3.log.2`, ... up to `max_files`.
+pub struct SizeRotatingWriter {
+    dir: PathBuf,
+    file: File,
+    written: u64,
+    max_bytes: u64,
+    max_files: usize,
+}
+
+impl SizeRotatingWriter {
+    pub fn new(dir: &Path, max_bytes: u64, max_files: usize) -> io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(LOG_FILE_NAME);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            file,
+            written,
+            max_bytes,
+            max_files: max_files.max(1),
+        })
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        if index == 0 {
+            self.dir.join(LOG_FILE_NAME)
+        } else {
+            self.dir.join(format!("{}.{}", LOG_FILE_NAME, index))
+        }
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        // Drop the oldest file, then shift every other one up by one
+        let oldest = self.rotated_path(self.max_files - 1);
+        if oldest.exists() {
+            std::fs::remove_file(&oldest)?;
+        }
+        for index in (0..self.max_files - 1).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                std::fs::rename(&from, self.rotated_path(index + 1))?;
+            }
+        }
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.rotated_path(0))?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for SizeRotatingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn log_files(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_level_filter() {
+        assert_eq!(level_filter("trace").to_string(), "blah3=trace,info");
+        assert_eq!(level_filter("info").to_string(), "blah3=info,info");
+        assert_eq!(level_filter("warn").to_string(), "blah3=warn,warn");
+    }
+
+    #[test]
+    fn test_writer_appends_without_rotating() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut writer = SizeRotatingWriter::new(temp_dir.path(), 100, 3).unwrap();
+
+        writer.write_all(b"first line\n").unwrap();
+        writer.write_all(b"second line\n").unwrap();
+        writer.flush().unwrap();
+
+        assert_eq!(log_files(temp_dir.path()), vec![LOG_FILE_NAME]);
+        let content = std::fs::read_to_string(temp_dir.path().join(LOG_FILE_NAME)).unwrap();
+        assert_eq!(content, "first line\nsecond line\n");
+    }
+
+    #[test]
+    fn test_writer_rotates_at_size_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut writer = SizeRotatingWriter::new(temp_dir.path(), 10, 3).unwrap();
+
+        writer.write_all(b"aaaaaaaa\n").unwrap();
+        writer.write_all(b"bbbbbbbb\n").unwrap();
+        writer.flush().unwrap();
+
+        let active = std::fs::read_to_string(temp_dir.path().join(LOG_FILE_NAME)).unwrap();
+        let previous = std::fs::read_to_string(temp_dir.path().join("blah3.log.1")).unwrap();
+        assert_eq!(active, "bbbbbbbb\n");
+        assert_eq!(previous, "aaaaaaaa\n");
+    }
+
+    #[test]
+    fn test_writer_keeps_at_most_max_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut writer = SizeRotatingWriter::new(temp_dir.path(), 10, 3).unwrap();
+
+        for line in ["one\n", "two\n", "three\n", "four\n", "five\n"] {
+            writer.write_all(line.repeat(2).as_bytes()).unwrap();
+        }
+        writer.flush().unwrap();
+
+        assert_eq!(
+            log_files(temp_dir.path()),
+            vec!["blah3.log", "blah3.log.1", "blah3.log.2"]
+        );
+        // The oldest entries were dropped
+        let oldest = std::fs::read_to_string(temp_dir.path().join("blah3.log.2")).unwrap();
+        assert_eq!(oldest, "three\nthree\n");
+    }
+
+    #[test]
+    fn test_writer_resumes_existing_file_size() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join(LOG_FILE_NAME), b"123456789").unwrap();
+
+        let mut writer = SizeRotatingWriter::new(temp_dir.path(), 10, 3).unwrap();
+        writer.write_all(b"abc").unwrap();
+        writer.flush().unwrap();
+
+        // The pre-existing bytes count towards the limit
+        assert!(temp_dir.path().join("blah3.log.1").exists());
+    }
+}
@@ -0,0 +1,109 @@
+//! Microphone-in-use indicator.
+//!
+//! Driven by capture stream transitions rather than a recording flag, so it
+//! can't keep showing "recording" after the stream failed to open or was
+//! closed by a silence auto-stop. Shown as a dot next to the tray icon and
+//! sent to the frontend as `microphone-in-use`.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tauri::AppHandle;
+
+use crate::audio::capture::StreamEvent;
+use crate::events;
+
+/// Tray tooltip while the microphone is idle
+pub const IDLE_TOOLTIP: &str = "Blah³ - Voice Toolkit";
+
+const IN_USE_TOOLTIP: &str = "Blah³ - Microphone in use";
+
+/// Streams open across every capture; the microphone is in use while this
+/// is above zero
+static OPEN_STREAMS: OpenStreams = OpenStreams::new();
+
+/// Counts open streams from their `StreamEvent`s
+#[derive(Debug)]
+pub struct OpenStreams(AtomicUsize);
+
+impl OpenStreams {
+    pub const fn new() -> Self {
+        Self(AtomicUsize::new(0))
+    }
+
+    /// Apply a stream event, returning whether the microphone is in use
+    /// when that changed
+    pub fn update(&self, event: &StreamEvent) -> Option<bool> {
+        match event {
+            StreamEvent::Opened => (self.0.fetch_add(1, Ordering::SeqCst) == 0).then_some(true),
+            // A stream only closes after it opened, so this never underflows
+            StreamEvent::Closed => (self.0.fetch_sub(1, Ordering::SeqCst) == 1).then_some(false),
+            StreamEvent::Failed(_) => None,
+        }
+    }
+
+    pub fn in_use(&self) -> bool {
+        self.0.load(Ordering::SeqCst) > 0
+    }
+}
+
+/// Observer for `AudioCapture::with_observer` keeping the indicator in sync
+pub fn observer(app: &AppHandle) -> impl Fn(&StreamEvent) + Send + Sync + 'static {
+    let app = app.clone();
+    move |event| {
+        if let Some(in_use) = OPEN_STREAMS.update(event) {
+            show(&app, in_use);
+        }
+    }
+}
+
+/// Whether any capture has its stream open
+pub fn is_in_use() -> bool {
+    OPEN_STREAMS.in_use()
+}
+
+fn show(app: &AppHandle, in_use: bool) {
+    tracing::info!("Microphone {}", if in_use { "in use" } else { "released" });
+
+    if let Some(tray) = app.tray_by_id(crate::TRAY_ID) {
+        if let Err(e) = tray.set_title(in_use.then_some("●")) {
+            tracing::warn!("Failed to update tray title: {}", e);
+        }
+        let tooltip = if in_use { IN_USE_TOOLTIP } else { IDLE_TOOLTIP };
+        if let Err(e) = tray.set_tooltip(Some(tooltip)) {
+            tracing::warn!("Failed to update tray tooltip: {}", e);
+        }
+    }
+
+    events::emit(app, events::MicrophoneInUse(in_use));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_streams_transitions() {
+        let streams = OpenStreams::new();
+
+        assert_eq!(streams.update(&StreamEvent::Opened), Some(true));
+        // A second capture while the first is open changes nothing
+        assert_eq!(streams.update(&StreamEvent::Opened), None);
+        assert_eq!(streams.update(&StreamEvent::Closed), None);
+        assert!(streams.in_use());
+        assert_eq!(streams.update(&StreamEvent::Closed), Some(false));
+        assert!(!streams.in_use());
+    }
+
+    #[test]
+    fn test_open_streams_ignores_failed_open() {
+        let streams = OpenStreams::new();
+
+        // A denied microphone never shows as in use
+        let failed = StreamEvent::Failed("Microphone access denied".to_string());
+        assert_eq!(streams.update(&failed), None);
+        assert!(!streams.in_use());
+
+        assert_eq!(streams.update(&StreamEvent::Opened), Some(true));
+        assert_eq!(streams.update(&failed), None);
+        assert!(streams.in_use());
+    }
+}
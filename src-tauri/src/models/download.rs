@@ -1,18 +1,160 @@
 #![allow(dead_code)]
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use futures_util::StreamExt;
-use std::path::Path;
+use reqwest::header::{ACCEPT_RANGES, CONTENT_LENGTH, CONTENT_RANGE, ETAG, RANGE};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Name of the per-directory file caching download metadata (ETags)
+const MODEL_VERSIONS_FILE: &str = "model_versions.json";
+
+/// Suffix for files still being downloaded
+const DOWNLOAD_SUFFIX: &str = ".download";
+
+/// Suffix for directories a zip is being extracted into
+const EXTRACT_SUFFIX: &str = ".extracting";
+
+/// Largest model file that will be downloaded (10 GB)
+pub const MAX_MODEL_SIZE: u64 = 10_000_000_000;
+
+/// Most bytes a zip may expand to (10 GB)
+const MAX_EXTRACTED_SIZE: u64 = MAX_MODEL_SIZE;
+
+/// Files every compiled CoreML model (`.mlmodelc`) contains
+const COREML_REQUIRED_FILES: &[&str] = &["coremldata.bin"];
+
+/// How long to wait for the server to accept a connection
+pub const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 30;
+
+/// How long a download may go without receiving any data
+pub const DEFAULT_READ_TIMEOUT_SECS: u64 = 60;
 
 pub struct ModelDownloader {
     client: reqwest::Client,
+    /// Refuse files larger than this many bytes
+    max_size: Option<u64>,
+    connect_timeout: Duration,
+    read_timeout: Duration,
 }
 
 impl ModelDownloader {
     pub fn new() -> Self {
+        Self::with_timeouts(DEFAULT_CONNECT_TIMEOUT_SECS, DEFAULT_READ_TIMEOUT_SECS)
+    }
+
+    /// Downloader giving up on a connection after `connect_timeout_secs`,
+    /// and on a download that stalls for `read_timeout_secs`. The read
+    /// timeout is per read, so large files can take as long as they need.
+    pub fn with_timeouts(connect_timeout_secs: u64, read_timeout_secs: u64) -> Self {
+        let connect_timeout = Duration::from_secs(connect_timeout_secs);
+        let read_timeout = Duration::from_secs(read_timeout_secs);
+        // Like reqwest::Client::new, which fails only if TLS can't be set up
+        let client = Self::client_builder(connect_timeout, read_timeout)
+            .build()
+            .expect("Failed to create HTTP client");
+
         Self {
-            client: reqwest::Client::new(),
+            client,
+            max_size: None,
+            connect_timeout,
+            read_timeout,
+        }
+    }
+
+    /// Downloader sending every request through the proxy at `proxy_url`,
+    /// e.g. `http://proxy.corp:8080`, instead of the system proxy settings.
+    /// Hosts listed in the `NO_PROXY` environment variable are still reached
+    /// directly.
+    pub fn with_proxy(proxy_url: &str) -> Result<Self> {
+        let proxy = reqwest::Proxy::all(proxy_url)
+            .map_err(|e| anyhow!("Invalid proxy URL: {}", e))?
+            .no_proxy(reqwest::NoProxy::from_env());
+        let connect_timeout = Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECS);
+        let read_timeout = Duration::from_secs(DEFAULT_READ_TIMEOUT_SECS);
+        let client = Self::client_builder(connect_timeout, read_timeout)
+            .proxy(proxy)
+            .build()
+            .map_err(|e| anyhow!("Failed to create HTTP client: {}", e))?;
+
+        Ok(Self {
+            client,
+            max_size: None,
+            connect_timeout,
+            read_timeout,
+        })
+    }
+
+    fn client_builder(connect_timeout: Duration, read_timeout: Duration) -> reqwest::ClientBuilder {
+        reqwest::Client::builder()
+            .connect_timeout(connect_timeout)
+            .read_timeout(read_timeout)
+    }
+
+    /// `error`, described as a timeout if that is what it was
+    fn describe_timeout(&self, error: anyhow::Error) -> anyhow::Error {
+        let timed_out = error
+            .chain()
+            .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+            .find(|e| e.is_timeout());
+        let Some(cause) = timed_out else {
+            return error;
+        };
+
+        let timeout = if cause.is_connect() {
+            DownloadTimedOut::Connect(self.connect_timeout)
+        } else {
+            DownloadTimedOut::Read(self.read_timeout)
+        };
+        error.context(timeout)
+    }
+
+    /// Fail downloads of files larger than `max_size` bytes, both up front
+    /// from `Content-Length` and while streaming in case the header is
+    /// missing or wrong
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Fail with `FileTooLargeError` if `size` is over the limit
+    pub fn check_size(&self, size: u64) -> Result<()> {
+        match self.max_size {
+            Some(max) if size > max => Err(FileTooLargeError { size, max }.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Size of the file at `url` from a HEAD request, or `None` when the
+    /// server doesn't say
+    pub async fn remote_size(&self, url: &str) -> Result<Option<u64>> {
+        let response = self
+            .client
+            .head(url)
+            .send()
+            .await
+            .map_err(|e| self.describe_timeout(e.into()))?;
+        let status = response.status();
+
+        if status == StatusCode::METHOD_NOT_ALLOWED {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(HttpStatusError(status).into());
         }
+
+        // HEAD responses have no body, so read the header rather than content_length()
+        Ok(response
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok()))
     }
 
     pub async fn download<F>(
@@ -21,6 +163,15 @@ impl ModelDownloader {
         dest: &Path,
         progress_callback: F,
     ) -> Result<()>
+    where
+        F: Fn(DownloadProgress) + Send + 'static,
+    {
+        self.download_once(url, dest, progress_callback)
+            .await
+            .map_err(|e| self.describe_timeout(e))
+    }
+
+    async fn download_once<F>(&self, url: &str, dest: &Path, progress_callback: F) -> Result<()>
     where
         F: Fn(DownloadProgress) + Send + 'static,
     {
@@ -30,6 +181,7 @@ impl ModelDownloader {
         let total_size = response.content_length().unwrap_or(0);
 
         tracing::info!("Download size: {} bytes", total_size);
+        self.check_size(total_size)?;
 
         let mut file = tokio::fs::File::create(dest).await?;
         let mut downloaded: u64 = 0;
@@ -37,27 +189,27 @@ impl ModelDownloader {
 
         while let Some(chunk) = stream.next().await {
             let chunk = chunk?;
-            tokio::io::AsyncWriteExt::write_all(&mut file, &chunk).await?;
-
             downloaded += chunk.len() as u64;
+            self.check_size(downloaded)?;
 
-            let progress = DownloadProgress {
-                downloaded,
-                total: total_size,
-                percentage: if total_size > 0 {
-                    (downloaded as f64 / total_size as f64 * 100.0) as u8
-                } else {
-                    0
-                },
-            };
+            tokio::io::AsyncWriteExt::write_all(&mut file, &chunk).await?;
 
-            progress_callback(progress);
+            progress_callback(DownloadProgress::new(downloaded, total_size));
         }
+        // tokio finishes the last write in the background unless flushed
+        tokio::io::AsyncWriteExt::flush(&mut file).await?;
 
         tracing::info!("Download complete: {:?}", dest);
         Ok(())
     }
 
+    /// Download `url` into `dest`, retrying up to `max_retries` times after
+    /// the first attempt with exponential backoff.
+    ///
+    /// Each attempt resumes from whatever earlier attempts left on disk, so
+    /// progress picks up at the resumed offset. Only errors that may go away on
+    /// their own (network failures, 5xx, 408/429) are retried; a 404 or a full
+    /// disk fails straight away.
     pub async fn download_with_retry<F>(
         &self,
         url: &str,
@@ -68,24 +220,319 @@ impl ModelDownloader {
     where
         F: Fn(DownloadProgress) + Send + Clone + 'static,
     {
-        let mut last_error = None;
+        let mut retries = 0;
 
-        for attempt in 0..max_retries {
-            if attempt > 0 {
-                tracing::info!("Retry attempt {} for {}", attempt, url);
-                tokio::time::sleep(tokio::time::Duration::from_secs(2_u64.pow(attempt))).await;
-            }
-
-            match self.download(url, dest, progress_callback.clone()).await {
+        loop {
+            match self.download_attempt(url, dest, progress_callback.clone()).await {
                 Ok(()) => return Ok(()),
+                Err(e) if retries < max_retries && is_retryable(&e) => {
+                    retries += 1;
+                    let delay = retry_delay(retries);
+                    tracing::warn!(
+                        "Download attempt {} of {} failed: {}; retrying in {:?}",
+                        retries,
+                        max_retries + 1,
+                        e,
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                }
                 Err(e) => {
-                    tracing::warn!("Download attempt {} failed: {}", attempt + 1, e);
-                    last_error = Some(e);
+                    if !is_retryable(&e) {
+                        tracing::warn!("Download failed with a permanent error: {}", e);
+                    }
+                    return Err(self.describe_timeout(e));
+                }
+            }
+        }
+    }
+
+    /// One download attempt: check any partial file, then continue into it
+    async fn download_attempt<F>(&self, url: &str, dest: &Path, progress_callback: F) -> Result<()>
+    where
+        F: Fn(DownloadProgress) + Send + 'static,
+    {
+        match self.verify_partial_download(dest, url).await {
+            Ok(VerifyResult::Complete) => return Ok(()),
+            Ok(_) => {}
+            // Keep the partial file: the next attempt may be able to verify it
+            Err(e) if is_retryable(&e) => return Err(e),
+            Err(e) => {
+                // Can't vouch for the existing bytes, so don't build on them
+                tracing::warn!("Failed to verify partial download {:?}: {}", dest, e);
+                if dest.exists() {
+                    std::fs::remove_file(dest)?;
                 }
             }
         }
 
-        Err(last_error.unwrap())
+        self.download_resumable(url, dest, progress_callback).await
+    }
+
+    /// Check whether a partially downloaded file at `path` can be resumed.
+    ///
+    /// Asks the server for the byte range we already have. A 416 response, a
+    /// server that can't serve ranges, a remote file smaller than ours, or an
+    /// ETag that differs from the one cached when the download started all
+    /// mean the local bytes can't be trusted, so the file is deleted.
+    pub async fn verify_partial_download(&self, path: &Path, url: &str) -> Result<VerifyResult> {
+        let local_size = match std::fs::metadata(path) {
+            Ok(metadata) => metadata.len(),
+            Err(_) => return Ok(VerifyResult::NoPartial),
+        };
+        if local_size == 0 {
+            let _ = std::fs::remove_file(path);
+            return Ok(VerifyResult::NoPartial);
+        }
+
+        let response = self
+            .client
+            .head(url)
+            .header(RANGE, format!("bytes=0-{}", local_size - 1))
+            .send()
+            .await?;
+
+        let headers = response.headers();
+        let probe = RangeProbe {
+            status: response.status(),
+            // HEAD responses have no body, so read the header rather than content_length()
+            content_length: headers
+                .get(CONTENT_LENGTH)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse().ok()),
+            total_size: headers
+                .get(CONTENT_RANGE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_content_range_total),
+            accepts_ranges: headers
+                .get(ACCEPT_RANGES)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.contains("bytes"))
+                .unwrap_or(false),
+            etag: headers
+                .get(ETAG)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_string()),
+        };
+
+        let cached_etag = ModelVersions::load_for(path)
+            .get(path)
+            .filter(|entry| entry.url == url)
+            .and_then(|entry| entry.etag.clone());
+
+        let result = probe.evaluate(local_size, cached_etag.as_deref());
+        if result == VerifyResult::StartOver {
+            tracing::warn!(
+                "Partial download {:?} can't be resumed (status {}), starting over",
+                path,
+                probe.status
+            );
+            std::fs::remove_file(path)?;
+        } else {
+            tracing::info!("Partial download {:?} verified: {:?}", path, result);
+        }
+
+        Ok(result)
+    }
+
+    /// Download `url` into `dest`, continuing from any bytes already on disk.
+    ///
+    /// Call `verify_partial_download` first; this trusts the existing bytes.
+    pub async fn download_resumable<F>(
+        &self,
+        url: &str,
+        dest: &Path,
+        progress_callback: F,
+    ) -> Result<()>
+    where
+        F: Fn(DownloadProgress) + Send + 'static,
+    {
+        let offset = std::fs::metadata(dest).map(|m| m.len()).unwrap_or(0);
+
+        let mut request = self.client.get(url);
+        if offset > 0 {
+            tracing::info!("Resuming download of {} from byte {}", url, offset);
+            request = request.header(RANGE, format!("bytes={}-", offset));
+        } else {
+            tracing::info!("Downloading from: {}", url);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+
+        if status == StatusCode::RANGE_NOT_SATISFIABLE {
+            // We already have every byte the server has
+            tracing::info!("Download already complete: {:?}", dest);
+            return Ok(());
+        }
+        if !status.is_success() {
+            return Err(HttpStatusError(status).into());
+        }
+
+        let resuming = offset > 0 && status == StatusCode::PARTIAL_CONTENT;
+        let etag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+
+        // Remember the ETag so a later resume can tell if the remote file changed
+        let mut versions = ModelVersions::load_for(dest);
+        versions.insert(
+            dest,
+            ModelVersion {
+                url: url.to_string(),
+                etag,
+            },
+        );
+        if let Err(e) = versions.save_for(dest) {
+            tracing::warn!("Failed to save {}: {}", MODEL_VERSIONS_FILE, e);
+        }
+
+        let start = if resuming { offset } else { 0 };
+        let total_size = response.content_length().map(|len| start + len).unwrap_or(0);
+
+        tracing::info!("Download size: {} bytes", total_size);
+        self.check_size(total_size)?;
+
+        let mut file = if resuming {
+            tokio::fs::OpenOptions::new().append(true).open(dest).await?
+        } else {
+            tokio::fs::File::create(dest).await?
+        };
+        let mut downloaded = start;
+        let mut stream = response.bytes_stream();
+
+        // Report the resumed offset straight away so progress carries on from
+        // where the previous attempt stopped rather than jumping later
+        progress_callback(DownloadProgress::new(downloaded, total_size));
+
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            downloaded += chunk.len() as u64;
+            self.check_size(downloaded)?;
+
+            tokio::io::AsyncWriteExt::write_all(&mut file, &chunk).await?;
+
+            progress_callback(DownloadProgress::new(downloaded, total_size));
+        }
+
+        tokio::io::AsyncWriteExt::flush(&mut file).await?;
+
+        tracing::info!("Download complete: {:?}", dest);
+        Ok(())
+    }
+}
+
+/// Outcome of checking a partial download against the server
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerifyResult {
+    /// Nothing on disk yet
+    NoPartial,
+    /// Existing bytes look good, continue from `offset`
+    Resume { offset: u64 },
+    /// The local file already has every byte
+    Complete,
+    /// The local file was deleted and must be downloaded from scratch
+    StartOver,
+}
+
+/// Response details from a range probe, separated out so the decision is testable
+struct RangeProbe {
+    status: StatusCode,
+    content_length: Option<u64>,
+    total_size: Option<u64>,
+    accepts_ranges: bool,
+    etag: Option<String>,
+}
+
+impl RangeProbe {
+    fn evaluate(&self, local_size: u64, cached_etag: Option<&str>) -> VerifyResult {
+        if self.status == StatusCode::RANGE_NOT_SATISFIABLE {
+            return VerifyResult::StartOver;
+        }
+
+        // The remote file changed since the partial download started
+        if let (Some(cached), Some(current)) = (cached_etag, self.etag.as_deref()) {
+            if cached != current {
+                return VerifyResult::StartOver;
+            }
+        }
+
+        let total = if self.status == StatusCode::PARTIAL_CONTENT {
+            // The server must return exactly the bytes we asked for
+            if self.content_length.is_some_and(|len| len != local_size) {
+                return VerifyResult::StartOver;
+            }
+            self.total_size
+        } else if self.status.is_success() && self.accepts_ranges {
+            // Range ignored on HEAD, but GET ranges are advertised
+            self.content_length
+        } else {
+            return VerifyResult::StartOver;
+        };
+
+        match total {
+            Some(total) if local_size > total => VerifyResult::StartOver,
+            Some(total) if local_size == total => VerifyResult::Complete,
+            _ => VerifyResult::Resume { offset: local_size },
+        }
+    }
+}
+
+/// Parse the total size from a `Content-Range: bytes 0-99/1234` header
+fn parse_content_range_total(value: &str) -> Option<u64> {
+    value.rsplit('/').next()?.trim().parse().ok()
+}
+
+/// Download metadata for one file, cached in `model_versions.json`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelVersion {
+    pub url: String,
+    pub etag: Option<String>,
+}
+
+/// Contents of a `model_versions.json`, keyed by file name.
+/// Each models subdirectory keeps its own file next to the downloads.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelVersions {
+    #[serde(flatten)]
+    entries: HashMap<String, ModelVersion>,
+}
+
+impl ModelVersions {
+    fn file_for(path: &Path) -> PathBuf {
+        path.parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(MODEL_VERSIONS_FILE)
+    }
+
+    fn key_for(path: &Path) -> String {
+        path.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    /// Load the versions file that sits next to `path`, or an empty one
+    pub fn load_for(path: &Path) -> Self {
+        std::fs::read_to_string(Self::file_for(path))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save_for(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(Self::file_for(path), content)?;
+        Ok(())
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&ModelVersion> {
+        self.entries.get(&Self::key_for(path))
+    }
+
+    pub fn insert(&mut self, path: &Path, version: ModelVersion) {
+        self.entries.insert(Self::key_for(path), version);
     }
 }
 
@@ -102,9 +549,233 @@ pub struct DownloadProgress {
     pub percentage: u8,
 }
 
+impl DownloadProgress {
+    fn new(downloaded: u64, total: u64) -> Self {
+        Self {
+            downloaded,
+            total,
+            percentage: if total > 0 {
+                (downloaded as f64 / total as f64 * 100.0) as u8
+            } else {
+                0
+            },
+        }
+    }
+}
+
+/// Cancellation tokens of the downloads in progress, keyed by model id.
+/// Kept in Tauri managed state so every download can be cancelled at once.
+#[derive(Debug, Clone, Default)]
+pub struct DownloadManager {
+    tokens: Arc<Mutex<HashMap<String, CancellationToken>>>,
+}
+
+impl DownloadManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a download of `model_id`. The token is removed again when the
+    /// returned guard is dropped. Fails if the model is already downloading.
+    pub fn register(&self, model_id: &str) -> Result<ActiveDownload> {
+        let mut tokens = self.lock();
+        if tokens.contains_key(model_id) {
+            return Err(anyhow!("{} is already downloading", model_id));
+        }
+
+        let token = CancellationToken::new();
+        tokens.insert(model_id.to_string(), token.clone());
+        Ok(ActiveDownload {
+            manager: self.clone(),
+            model_id: model_id.to_string(),
+            token,
+        })
+    }
+
+    /// Cancel every download in progress, returning their model ids sorted
+    pub fn cancel_all(&self) -> Vec<String> {
+        let tokens = self.lock();
+        let mut cancelled: Vec<String> = tokens
+            .iter()
+            .filter(|(_, token)| !token.is_cancelled())
+            .map(|(model_id, token)| {
+                token.cancel();
+                model_id.clone()
+            })
+            .collect();
+        cancelled.sort();
+        cancelled
+    }
+
+    /// Model ids with a download in progress
+    pub fn active(&self) -> Vec<String> {
+        let mut active: Vec<String> = self.lock().keys().cloned().collect();
+        active.sort();
+        active
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, CancellationToken>> {
+        self.tokens.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A registered download; unregisters itself when dropped
+#[derive(Debug)]
+pub struct ActiveDownload {
+    manager: DownloadManager,
+    model_id: String,
+    token: CancellationToken,
+}
+
+impl ActiveDownload {
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl Drop for ActiveDownload {
+    fn drop(&mut self) {
+        self.manager.lock().remove(&self.model_id);
+    }
+}
+
+/// The server answered a download request with an error status
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HttpStatusError(pub StatusCode);
+
+impl std::fmt::Display for HttpStatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Download failed with HTTP status {}", self.0)
+    }
+}
+
+impl std::error::Error for HttpStatusError {}
+
+/// A download was given up on because the server stopped responding
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DownloadTimedOut {
+    /// No connection within the connect timeout
+    Connect(Duration),
+    /// No data for the read timeout
+    Read(Duration),
+}
+
+impl std::fmt::Display for DownloadTimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Connect(timeout) => write!(
+                f,
+                "Download timed out: couldn't connect to the server within {}s",
+                timeout.as_secs()
+            ),
+            Self::Read(timeout) => write!(
+                f,
+                "Download timed out: no data received for {}s. Check your connection and try again.",
+                timeout.as_secs()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DownloadTimedOut {}
+
+/// The file to download is over the downloader's size limit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FileTooLargeError {
+    pub size: u64,
+    pub max: u64,
+}
+
+impl std::fmt::Display for FileTooLargeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "File is {}, over the {} limit",
+            format_size(self.size),
+            format_size(self.max)
+        )
+    }
+}
+
+impl std::error::Error for FileTooLargeError {}
+
+/// Human-readable size in decimal units, like the registry's "142 MB" or
+/// "1.5 GB"
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [(u64, &str); 3] = [(1_000_000_000, "GB"), (1_000_000, "MB"), (1_000, "KB")];
+
+    for (unit, suffix) in UNITS {
+        if bytes >= unit {
+            let value = bytes as f64 / unit as f64;
+            // One decimal below 10, where it still matters
+            let value = if value < 10.0 {
+                format!("{:.1}", value)
+            } else {
+                format!("{:.0}", value)
+            };
+            return format!("{} {}", value.trim_end_matches(".0"), suffix);
+        }
+    }
+    format!("{} bytes", bytes)
+}
+
+/// Whether a download error might succeed if tried again.
+///
+/// Network failures, timeouts, server errors and rate limiting are retryable.
+/// Other HTTP errors (404, 403, ...) and local I/O errors such as a full disk
+/// are permanent.
+pub fn is_retryable(error: &anyhow::Error) -> bool {
+    for cause in error.chain() {
+        if let Some(HttpStatusError(status)) = cause.downcast_ref::<HttpStatusError>() {
+            return is_retryable_status(*status);
+        }
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            return match e.status() {
+                Some(status) => is_retryable_status(status),
+                None => e.is_timeout() || e.is_connect() || e.is_request() || e.is_body(),
+            };
+        }
+        if let Some(e) = cause.downcast_ref::<std::io::Error>() {
+            return is_retryable_io(e);
+        }
+    }
+    false
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+fn is_retryable_io(error: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+
+    matches!(
+        error.kind(),
+        ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::TimedOut
+            | ErrorKind::Interrupted
+            | ErrorKind::UnexpectedEof
+    )
+}
+
+/// Backoff before retry number `retry` (1-based): 2s, 4s, 8s, ... capped at 30s
+fn retry_delay(retry: u32) -> std::time::Duration {
+    std::time::Duration::from_secs(2_u64.saturating_pow(retry).min(30))
+}
+
 /// Extract a zip file to a directory
 /// For CoreML models, the zip contains a .mlmodelc directory structure
 pub fn extract_zip(zip_path: &Path, dest_dir: &Path) -> Result<()> {
+    extract_zip_limited(zip_path, dest_dir, MAX_EXTRACTED_SIZE)
+}
+
+/// Like `extract_zip`, failing once the extracted files add up to more
+/// than `max_bytes`. Sizes are counted as the data is written rather than
+/// taken from the archive's headers, which a zip bomb can lie about.
+fn extract_zip_limited(zip_path: &Path, dest_dir: &Path, max_bytes: u64) -> Result<()> {
     tracing::info!("Extracting zip: {:?} to {:?}", zip_path, dest_dir);
 
     let file = std::fs::File::open(zip_path)?;
@@ -112,38 +783,54 @@ pub fn extract_zip(zip_path: &Path, dest_dir: &Path) -> Result<()> {
 
     // Create destination directory
     std::fs::create_dir_all(dest_dir)?;
+    let dest_root = dest_dir.canonicalize()?;
 
+    // Reject the whole archive up front if any entry would escape dest_dir
+    let mut entries = Vec::new();
     for i in 0..archive.len() {
-        let mut file = archive.by_index(i)?;
-        let outpath = match file.enclosed_name() {
-            Some(path) => {
-                // The zip contains paths like "ggml-base.en-encoder.mlmodelc/..."
-                // We want to extract to dest_dir directly, stripping the top-level dir
-                let components: Vec<_> = path.components().collect();
-                if components.len() > 1 {
-                    // Skip the first component (the .mlmodelc dir name in the zip)
-                    let relative: std::path::PathBuf = components[1..].iter().collect();
-                    dest_dir.join(relative)
-                } else {
-                    // This is the top-level directory entry, skip it
-                    continue;
-                }
-            }
-            None => continue,
+        let file = archive.by_index(i)?;
+        let path = file
+            .enclosed_name()
+            .ok_or_else(|| anyhow!("Zip entry {:?} points outside the archive", file.name()))?
+            .to_path_buf();
+        if !is_metadata_entry(&path) {
+            entries.push((i, path, file.is_dir()));
+        }
+    }
+
+    // CoreML zips usually hold one "<name>.mlmodelc/" folder, unpacked
+    // into dest_dir itself; anything else is unpacked as it is
+    let root = common_root(&entries);
+    let mut extracted: u64 = 0;
+    for (i, path, is_dir) in entries {
+        let relative = match &root {
+            Some(root) => path.strip_prefix(root)?.to_path_buf(),
+            None => path,
         };
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+        let outpath = dest_dir.join(&relative);
 
-        if file.name().ends_with('/') {
-            // It's a directory
+        let mut file = archive.by_index(i)?;
+        if is_dir {
             std::fs::create_dir_all(&outpath)?;
+            ensure_within(&dest_root, &outpath)?;
         } else {
-            // It's a file
-            if let Some(parent) = outpath.parent() {
-                if !parent.exists() {
-                    std::fs::create_dir_all(parent)?;
-                }
-            }
+            let parent = outpath.parent().unwrap_or(dest_dir);
+            std::fs::create_dir_all(parent)?;
+            ensure_within(&dest_root, parent)?;
+
+            let remaining = max_bytes - extracted;
             let mut outfile = std::fs::File::create(&outpath)?;
-            std::io::copy(&mut file, &mut outfile)?;
+            let written = std::io::copy(&mut (&mut file).take(remaining + 1), &mut outfile)?;
+            if written > remaining {
+                return Err(anyhow!(
+                    "Zip expands to more than {} and may be a zip bomb",
+                    format_size(max_bytes)
+                ));
+            }
+            extracted += written;
         }
 
         // Set permissions on Unix
@@ -160,6 +847,169 @@ pub fn extract_zip(zip_path: &Path, dest_dir: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Folders macOS adds when it zips something, which aren't part of the model
+fn is_metadata_entry(path: &Path) -> bool {
+    path.components()
+        .next()
+        .is_some_and(|c| c.as_os_str() == "__MACOSX")
+        || path.file_name().is_some_and(|name| name == ".DS_Store")
+}
+
+/// The folder every entry is inside, if there is exactly one and no file
+/// sits next to it at the top of the archive
+fn common_root(entries: &[(usize, PathBuf, bool)]) -> Option<PathBuf> {
+    let mut root: Option<&std::ffi::OsStr> = None;
+    for (_, path, is_dir) in entries {
+        let mut components = path.components();
+        let first = components.next()?.as_os_str();
+        // A file at the top level means there's no folder to strip
+        if !is_dir && components.next().is_none() {
+            return None;
+        }
+        match root {
+            Some(root) if root != first => return None,
+            _ => root = Some(first),
+        }
+    }
+    root.map(PathBuf::from)
+}
+
+/// Fail unless `path`, which must exist, resolves to somewhere inside
+/// `dest_root`, so symlinks can't redirect a write elsewhere
+fn ensure_within(dest_root: &Path, path: &Path) -> Result<()> {
+    if path.canonicalize()?.starts_with(dest_root) {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Zip entry {:?} points outside the destination",
+            path
+        ))
+    }
+}
+
+/// Fail unless `dir` looks like a compiled CoreML model
+pub fn validate_coreml_bundle(dir: &Path) -> Result<()> {
+    let missing: Vec<&str> = COREML_REQUIRED_FILES
+        .iter()
+        .copied()
+        .filter(|file| !dir.join(file).is_file())
+        .collect();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Not a compiled CoreML model, missing {}",
+            missing.join(", ")
+        ))
+    }
+}
+
+/// Extract a zip into `dest_dir` atomically: everything is unpacked into a
+/// sibling temp directory first and only renamed into place once extraction
+/// succeeded, so a failure never leaves a partial directory behind. A
+/// `.mlmodelc` destination must also hold a compiled CoreML model.
+pub fn extract_zip_atomic(zip_path: &Path, dest_dir: &Path) -> Result<()> {
+    let temp_dir = with_suffix(dest_dir, EXTRACT_SUFFIX);
+    if temp_dir.exists() {
+        std::fs::remove_dir_all(&temp_dir)?;
+    }
+
+    let extracted = extract_zip(zip_path, &temp_dir).and_then(|()| {
+        if dest_dir.extension().is_some_and(|ext| ext == "mlmodelc") {
+            validate_coreml_bundle(&temp_dir)?;
+        }
+        Ok(())
+    });
+    if let Err(e) = extracted {
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        return Err(e);
+    }
+
+    if dest_dir.exists() {
+        std::fs::remove_dir_all(dest_dir)?;
+    }
+    std::fs::rename(&temp_dir, dest_dir)?;
+    Ok(())
+}
+
+/// Whether `name` is a file the downloader leaves next to the model
+/// folders: a partial download or extraction, or the version cache
+pub fn is_download_artifact(name: &str) -> bool {
+    name == MODEL_VERSIONS_FILE || name.ends_with(DOWNLOAD_SUFFIX) || name.ends_with(EXTRACT_SUFFIX)
+}
+
+/// Path a download for `dest` is streamed into before it's complete
+pub fn temp_download_path(dest: &Path) -> PathBuf {
+    with_suffix(dest, DOWNLOAD_SUFFIX)
+}
+
+/// Move a completed download into its final location
+pub fn finalize_download(temp_path: &Path, dest: &Path) -> Result<()> {
+    if !temp_path.exists() {
+        return Err(anyhow!("Download file missing: {:?}", temp_path));
+    }
+
+    std::fs::rename(temp_path, dest)?;
+    tracing::info!("Download moved into place: {:?}", dest);
+    Ok(())
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Delete leftover download temp files (`.download`, `.zip`) and half-done
+/// extraction directories older than `max_age` under `models_dir`.
+/// Returns the paths that were removed.
+pub fn sweep_stale_downloads(models_dir: &Path, max_age: std::time::Duration) -> Vec<PathBuf> {
+    let mut removed = Vec::new();
+    sweep_dir(models_dir, max_age, &mut removed);
+
+    for path in &removed {
+        tracing::info!("Removed stale download temp file: {:?}", path);
+    }
+    removed
+}
+
+fn sweep_dir(dir: &Path, max_age: std::time::Duration, removed: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+
+        let is_stale = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .map(|age| age >= max_age)
+            .unwrap_or(false);
+
+        if metadata.is_dir() {
+            if name.ends_with(EXTRACT_SUFFIX) {
+                if is_stale && std::fs::remove_dir_all(&path).is_ok() {
+                    removed.push(path);
+                }
+            } else if !name.ends_with(".mlmodelc") {
+                // Installed CoreML bundles never contain our temp files
+                sweep_dir(&path, max_age, removed);
+            }
+        } else if (name.ends_with(DOWNLOAD_SUFFIX) || name.ends_with(".zip"))
+            && is_stale
+            && std::fs::remove_file(&path).is_ok()
+        {
+            removed.push(path);
+        }
+    }
+}
+
 /// Check if a model ID represents a CoreML model
 pub fn is_coreml_model(model_id: &str) -> bool {
     model_id.ends_with(".mlmodelc")
@@ -175,6 +1025,8 @@ mod tests {
     use super::*;
     use std::io::Write;
     use tempfile::TempDir;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
 
     #[test]
     fn test_is_coreml_model() {
@@ -248,6 +1100,305 @@ mod tests {
         assert!(result.is_err());
     }
 
+    fn probe(status: u16, content_length: Option<u64>, total_size: Option<u64>) -> RangeProbe {
+        RangeProbe {
+            status: StatusCode::from_u16(status).unwrap(),
+            content_length,
+            total_size,
+            accepts_ranges: false,
+            etag: None,
+        }
+    }
+
+    #[test]
+    fn test_verify_416_starts_over() {
+        let result = probe(416, None, None).evaluate(500, None);
+        assert_eq!(result, VerifyResult::StartOver);
+    }
+
+    #[test]
+    fn test_verify_partial_content_resumes() {
+        let result = probe(206, Some(500), Some(1000)).evaluate(500, None);
+        assert_eq!(result, VerifyResult::Resume { offset: 500 });
+    }
+
+    #[test]
+    fn test_verify_length_mismatch_starts_over() {
+        // Server returned fewer bytes than we asked for
+        let result = probe(206, Some(400), Some(1000)).evaluate(500, None);
+        assert_eq!(result, VerifyResult::StartOver);
+    }
+
+    #[test]
+    fn test_verify_complete_file() {
+        let result = probe(206, Some(1000), Some(1000)).evaluate(1000, None);
+        assert_eq!(result, VerifyResult::Complete);
+    }
+
+    #[test]
+    fn test_verify_no_range_support_starts_over() {
+        let result = probe(200, Some(1000), None).evaluate(500, None);
+        assert_eq!(result, VerifyResult::StartOver);
+
+        let mut with_ranges = probe(200, Some(1000), None);
+        with_ranges.accepts_ranges = true;
+        assert_eq!(with_ranges.evaluate(500, None), VerifyResult::Resume { offset: 500 });
+    }
+
+    #[test]
+    fn test_verify_etag_mismatch_starts_over() {
+        let mut changed = probe(206, Some(500), Some(1000));
+        changed.etag = Some("\"v2\"".to_string());
+        assert_eq!(changed.evaluate(500, Some("\"v1\"")), VerifyResult::StartOver);
+        assert_eq!(
+            changed.evaluate(500, Some("\"v2\"")),
+            VerifyResult::Resume { offset: 500 }
+        );
+    }
+
+    #[test]
+    fn test_parse_content_range_total() {
+        assert_eq!(parse_content_range_total("bytes 0-99/1234"), Some(1234));
+        assert_eq!(parse_content_range_total("bytes */1234"), Some(1234));
+        assert_eq!(parse_content_range_total("bytes 0-99/*"), None);
+    }
+
+    #[test]
+    fn test_model_versions_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let model_path = temp_dir.path().join("ggml-base.en.bin");
+
+        let mut versions = ModelVersions::load_for(&model_path);
+        assert!(versions.get(&model_path).is_none());
+
+        versions.insert(
+            &model_path,
+            ModelVersion {
+                url: "https://example.com/ggml-base.en.bin".to_string(),
+                etag: Some("\"abc123\"".to_string()),
+            },
+        );
+        versions.save_for(&model_path).unwrap();
+
+        assert!(temp_dir.path().join(MODEL_VERSIONS_FILE).exists());
+        let loaded = ModelVersions::load_for(&model_path);
+        assert_eq!(
+            loaded.get(&model_path).and_then(|v| v.etag.as_deref()),
+            Some("\"abc123\"")
+        );
+    }
+
+    fn make_zip(zip_path: &Path) {
+        let file = std::fs::File::create(zip_path).unwrap();
+        let mut zip = zip::ZipWriter::new(file);
+        let options =
+            zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        zip.add_directory("test.mlmodelc/", options).unwrap();
+        zip.start_file("test.mlmodelc/model.json", options).unwrap();
+        zip.write_all(b"{}").unwrap();
+        zip.start_file("test.mlmodelc/coremldata.bin", options)
+            .unwrap();
+        zip.write_all(b"coreml").unwrap();
+        zip.finish().unwrap();
+    }
+
+    /// Zip holding `files` as (name, contents), in that order
+    fn zip_with(zip_path: &Path, files: &[(&str, &[u8])]) {
+        let file = std::fs::File::create(zip_path).unwrap();
+        let mut zip = zip::ZipWriter::new(file);
+        let options =
+            zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        for (name, contents) in files {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(contents).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    fn set_age(path: &Path, age: std::time::Duration) {
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(std::time::SystemTime::now() - age).unwrap();
+    }
+
+    #[test]
+    fn test_temp_download_path() {
+        assert_eq!(
+            temp_download_path(Path::new("/models/stt/ggml-base.en.bin")),
+            PathBuf::from("/models/stt/ggml-base.en.bin.download")
+        );
+    }
+
+    #[test]
+    fn test_interrupted_download_is_not_installed() {
+        let temp_dir = TempDir::new().unwrap();
+        let dest = temp_dir.path().join("ggml-base.en.bin");
+        let temp_path = temp_download_path(&dest);
+
+        // Simulate a crash after streaming some bytes but before the rename
+        std::fs::write(&temp_path, b"half a model").unwrap();
+        assert!(!dest.exists());
+
+        finalize_download(&temp_path, &dest).unwrap();
+        assert!(dest.exists());
+        assert!(!temp_path.exists());
+        assert_eq!(std::fs::read(&dest).unwrap(), b"half a model");
+    }
+
+    #[test]
+    fn test_finalize_missing_download_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let dest = temp_dir.path().join("model.bin");
+
+        assert!(finalize_download(&temp_download_path(&dest), &dest).is_err());
+        assert!(!dest.exists());
+    }
+
+    #[test]
+    fn test_extract_zip_atomic() {
+        let temp_dir = TempDir::new().unwrap();
+        let zip_path = temp_dir.path().join("test.zip");
+        let dest_dir = temp_dir.path().join("test.mlmodelc");
+        make_zip(&zip_path);
+
+        extract_zip_atomic(&zip_path, &dest_dir).unwrap();
+
+        assert!(dest_dir.join("model.json").exists());
+        assert!(!with_suffix(&dest_dir, EXTRACT_SUFFIX).exists());
+    }
+
+    #[test]
+    fn test_extract_zip_atomic_failure_leaves_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let zip_path = temp_dir.path().join("corrupt.zip");
+        let dest_dir = temp_dir.path().join("test.mlmodelc");
+
+        // A truncated download
+        std::fs::write(&zip_path, b"PK\x03\x04 not really a zip").unwrap();
+
+        assert!(extract_zip_atomic(&zip_path, &dest_dir).is_err());
+        assert!(!dest_dir.exists());
+        assert!(!with_suffix(&dest_dir, EXTRACT_SUFFIX).exists());
+    }
+
+    #[test]
+    fn test_extract_zip_atomic_rejects_non_coreml() {
+        let temp_dir = TempDir::new().unwrap();
+        let zip_path = temp_dir.path().join("test.zip");
+        let dest_dir = temp_dir.path().join("test.mlmodelc");
+        zip_with(&zip_path, &[("test.mlmodelc/readme.txt", b"not a model")]);
+
+        let err = extract_zip_atomic(&zip_path, &dest_dir).unwrap_err();
+        assert!(err.to_string().contains("coremldata.bin"));
+        assert!(!dest_dir.exists());
+        assert!(!with_suffix(&dest_dir, EXTRACT_SUFFIX).exists());
+    }
+
+    #[test]
+    fn test_extract_zip_rejects_traversal() {
+        let temp_dir = TempDir::new().unwrap();
+        let zip_path = temp_dir.path().join("evil.zip");
+        let dest_dir = temp_dir.path().join("a/b/extracted");
+        zip_with(
+            &zip_path,
+            &[
+                ("test.mlmodelc/coremldata.bin", b"coreml"),
+                ("../../evil.txt", b"gotcha"),
+            ],
+        );
+
+        let err = extract_zip(&zip_path, &dest_dir).unwrap_err();
+        assert!(err.to_string().contains("outside"));
+        assert!(!temp_dir.path().join("a/evil.txt").exists());
+        // Nothing is extracted from an archive with a bad entry
+        assert!(!dest_dir.join("coremldata.bin").exists());
+    }
+
+    #[test]
+    fn test_extract_zip_flat_layout() {
+        let temp_dir = TempDir::new().unwrap();
+        let zip_path = temp_dir.path().join("flat.zip");
+        let dest_dir = temp_dir.path().join("test.mlmodelc");
+        zip_with(
+            &zip_path,
+            &[
+                ("coremldata.bin", b"coreml"),
+                ("weights/weight.bin", b"weights"),
+                ("__MACOSX/._coremldata.bin", b"resource fork"),
+            ],
+        );
+
+        extract_zip_atomic(&zip_path, &dest_dir).unwrap();
+        assert_eq!(
+            std::fs::read(dest_dir.join("coremldata.bin")).unwrap(),
+            b"coreml"
+        );
+        assert!(dest_dir.join("weights/weight.bin").exists());
+        assert!(!dest_dir.join("__MACOSX").exists());
+    }
+
+    #[test]
+    fn test_extract_zip_stops_oversized_archive() {
+        let temp_dir = TempDir::new().unwrap();
+        let zip_path = temp_dir.path().join("bomb.zip");
+        let dest_dir = temp_dir.path().join("extracted");
+        zip_with(
+            &zip_path,
+            &[
+                ("test.mlmodelc/a.bin", &[0; 600]),
+                ("test.mlmodelc/b.bin", &[0; 600]),
+            ],
+        );
+
+        let err = extract_zip_limited(&zip_path, &dest_dir, 1000).unwrap_err();
+        assert!(err.to_string().contains("zip bomb"));
+        extract_zip_limited(&zip_path, &dest_dir, 1200).unwrap();
+    }
+
+    #[test]
+    fn test_common_root() {
+        let entry = |path: &str, is_dir: bool| (0, PathBuf::from(path), is_dir);
+        let nested = [
+            entry("test.mlmodelc", true),
+            entry("test.mlmodelc/coremldata.bin", false),
+        ];
+        assert_eq!(common_root(&nested), Some(PathBuf::from("test.mlmodelc")));
+
+        let flat = [
+            entry("coremldata.bin", false),
+            entry("weights/weight.bin", false),
+        ];
+        assert_eq!(common_root(&flat), None);
+        let two_folders = [entry("a/x.bin", false), entry("b/y.bin", false)];
+        assert_eq!(common_root(&two_folders), None);
+    }
+
+    #[test]
+    fn test_sweep_stale_downloads() {
+        let temp_dir = TempDir::new().unwrap();
+        let stt_dir = temp_dir.path().join("stt");
+        std::fs::create_dir_all(&stt_dir).unwrap();
+        let day = std::time::Duration::from_secs(24 * 60 * 60);
+
+        let stale_download = stt_dir.join("ggml-base.en.bin.download");
+        let stale_zip = stt_dir.join("ggml-base.en-encoder.mlmodelc.zip");
+        let fresh_download = stt_dir.join("ggml-tiny.en.bin.download");
+        let installed = stt_dir.join("ggml-small.en.bin");
+        for path in [&stale_download, &stale_zip, &fresh_download, &installed] {
+            std::fs::write(path, b"bytes").unwrap();
+        }
+        set_age(&stale_download, day * 2);
+        set_age(&stale_zip, day * 2);
+        set_age(&installed, day * 30);
+
+        let removed = sweep_stale_downloads(temp_dir.path(), day);
+
+        assert_eq!(removed.len(), 2);
+        assert!(!stale_download.exists());
+        assert!(!stale_zip.exists());
+        assert!(fresh_download.exists());
+        assert!(installed.exists());
+    }
+
     #[test]
     fn test_download_progress_percentage() {
         let progress = DownloadProgress {
@@ -264,4 +1415,226 @@ mod tests {
         };
         assert_eq!(progress_complete.percentage, 100);
     }
+
+    #[test]
+    fn test_http_status_retryable() {
+        let retryable = |status| is_retryable(&HttpStatusError(status).into());
+
+        assert!(retryable(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(retryable(StatusCode::BAD_GATEWAY));
+        assert!(retryable(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(retryable(StatusCode::REQUEST_TIMEOUT));
+        assert!(!retryable(StatusCode::NOT_FOUND));
+        assert!(!retryable(StatusCode::FORBIDDEN));
+        assert!(!retryable(StatusCode::GONE));
+    }
+
+    #[test]
+    fn test_io_errors_retryable() {
+        use std::io::{Error, ErrorKind};
+
+        let retryable = |kind| is_retryable(&Error::from(kind).into());
+
+        assert!(retryable(ErrorKind::ConnectionReset));
+        assert!(retryable(ErrorKind::TimedOut));
+        assert!(retryable(ErrorKind::UnexpectedEof));
+        assert!(!retryable(ErrorKind::StorageFull));
+        assert!(!retryable(ErrorKind::PermissionDenied));
+        assert!(!retryable(ErrorKind::NotFound));
+    }
+
+    #[test]
+    fn test_retryable_looks_through_context() {
+        let err = anyhow::Error::from(HttpStatusError(StatusCode::SERVICE_UNAVAILABLE))
+            .context("Failed to download ggml-medium.bin");
+        assert!(is_retryable(&err));
+
+        // Errors we know nothing about aren't retried
+        assert!(!is_retryable(&anyhow!("Download file missing")));
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off() {
+        assert_eq!(retry_delay(1), std::time::Duration::from_secs(2));
+        assert_eq!(retry_delay(2), std::time::Duration::from_secs(4));
+        assert_eq!(retry_delay(3), std::time::Duration::from_secs(8));
+        assert_eq!(retry_delay(10), std::time::Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_download_progress_new() {
+        let progress = DownloadProgress::new(750, 1000);
+        assert_eq!(progress.percentage, 75);
+        assert_eq!(DownloadProgress::new(10, 0).percentage, 0);
+    }
+
+    #[test]
+    fn test_download_manager_cancel_all() {
+        let manager = DownloadManager::new();
+        let base = manager.register("ggml-base.en.bin").unwrap();
+        let kokoro = manager.register("kokoro-v1.0.onnx").unwrap();
+
+        assert_eq!(
+            manager.cancel_all(),
+            vec!["ggml-base.en.bin", "kokoro-v1.0.onnx"]
+        );
+        assert!(base.token().is_cancelled());
+        assert!(kokoro.token().is_cancelled());
+
+        // Already cancelled downloads aren't reported twice
+        assert!(manager.cancel_all().is_empty());
+    }
+
+    #[test]
+    fn test_download_manager_unregisters_on_drop() {
+        let manager = DownloadManager::new();
+        let download = manager.register("ggml-base.en.bin").unwrap();
+        assert!(manager.register("ggml-base.en.bin").is_err());
+        assert_eq!(manager.active(), vec!["ggml-base.en.bin"]);
+
+        drop(download);
+        assert!(manager.active().is_empty());
+        assert!(manager.register("ggml-base.en.bin").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_download_through_proxy() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy_url = format!("http://{}", listener.local_addr().unwrap());
+
+        // Stand-in proxy that answers the request itself and reports the
+        // request line it was sent
+        let proxy = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = socket.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            socket
+                .write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello",
+                )
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&request)
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string()
+        });
+
+        let temp_dir = TempDir::new().unwrap();
+        let dest = temp_dir.path().join("model.bin");
+        let downloader = ModelDownloader::with_proxy(&proxy_url).unwrap();
+        downloader
+            .download("http://models.invalid/model.bin", &dest, |_| {})
+            .await
+            .unwrap();
+
+        // A proxied request names the full target URL
+        assert_eq!(
+            proxy.await.unwrap(),
+            "GET http://models.invalid/model.bin HTTP/1.1"
+        );
+        assert_eq!(std::fs::read(&dest).unwrap(), b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_stalled_download_times_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/model.bin", listener.local_addr().unwrap());
+
+        // Sends the headers, then never the body
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await.unwrap();
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 20\r\n\r\n")
+                .await
+                .unwrap();
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        });
+
+        let temp_dir = TempDir::new().unwrap();
+        let dest = temp_dir.path().join("model.bin");
+        let err = ModelDownloader::with_timeouts(5, 1)
+            .download(&url, &dest, |_| {})
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            err.downcast_ref::<DownloadTimedOut>(),
+            Some(&DownloadTimedOut::Read(Duration::from_secs(1)))
+        );
+        assert!(err.to_string().starts_with("Download timed out"));
+        // Still worth another attempt
+        assert!(is_retryable(&err));
+        server.abort();
+    }
+
+    #[test]
+    fn test_with_proxy_rejects_invalid_url() {
+        assert!(ModelDownloader::with_proxy("not a proxy").is_err());
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 bytes");
+        assert_eq!(format_size(39_000_000), "39 MB");
+        assert_eq!(format_size(1_500_000_000), "1.5 GB");
+        assert_eq!(format_size(1_000_000_000), "1 GB");
+        assert_eq!(format_size(MAX_MODEL_SIZE), "10 GB");
+    }
+
+    #[test]
+    fn test_check_size() {
+        assert!(ModelDownloader::new().check_size(u64::MAX).is_ok());
+
+        let downloader = ModelDownloader::new().with_max_size(100);
+        assert!(downloader.check_size(100).is_ok());
+        let err = downloader.check_size(101).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<FileTooLargeError>(),
+            Some(&FileTooLargeError {
+                size: 101,
+                max: 100
+            })
+        );
+        assert!(!is_retryable(&err));
+    }
+
+    #[tokio::test]
+    async fn test_download_refuses_oversized_content_length() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/model.bin", listener.local_addr().unwrap());
+
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await.unwrap();
+            let _ = socket
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 20\r\nConnection: close\r\n\r\n")
+                .await;
+        });
+
+        let temp_dir = TempDir::new().unwrap();
+        let dest = temp_dir.path().join("model.bin");
+        let downloader = ModelDownloader::new().with_max_size(10);
+        let err = downloader
+            .download_with_retry(&url, &dest, |_| {}, 3)
+            .await
+            .unwrap_err();
+
+        // Refused before anything is written, without retrying
+        assert!(err.downcast_ref::<FileTooLargeError>().is_some());
+        assert!(!dest.exists());
+        server.await.unwrap();
+    }
 }
@@ -3,6 +3,8 @@
 use serde::{Deserialize, Serialize};
 use sysinfo::System;
 
+use super::registry::ModelRegistry;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HardwareProfile {
     pub chip: ChipType,
@@ -11,7 +13,14 @@ pub struct HardwareProfile {
     pub cpu_cores: usize,
     pub has_neural_engine: bool,
     pub has_metal: bool,
+    pub gpu_name: Option<String>,
+    /// Acceleration backends that are both compiled in and available on
+    /// this machine, e.g. "metal", "coreml", "cuda", "vulkan"
+    pub acceleration: Vec<String>,
     pub recommended_tier: Tier,
+    /// How hot the machine is right now; throttling slows transcription
+    #[serde(default)]
+    pub thermal_state: ThermalState,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -19,15 +28,42 @@ pub struct HardwareProfile {
 pub enum ChipType {
     AppleSilicon,
     Intel,
+    Amd,
     Unknown,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// How capable the machine is, from least to most. Ordered, so
+/// `Tier::Power > Tier::Standard`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "lowercase")]
 pub enum Tier {
-    Lite,     // Intel or 8GB Apple Silicon
-    Standard, // 16GB Apple Silicon
-    Power,    // 32GB+ Apple Silicon
+    Lite,     // Intel Mac, 8GB Apple Silicon, or a small PC without GPU acceleration
+    Standard, // 16GB Apple Silicon, or a PC with 16GB and a GPU or 8+ cores
+    Power,    // 32GB+ Apple Silicon, or a PC with 32GB and a GPU
+}
+
+/// The system's thermal pressure, from cool to throttling hard. Ordered, so
+/// `a > b` means `a` is worse.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum ThermalState {
+    #[default]
+    Nominal,
+    Fair,
+    Serious,
+    Critical,
+}
+
+impl ThermalState {
+    /// From `NSProcessInfoThermalState` (0 nominal to 3 critical)
+    pub fn from_raw(raw: isize) -> Self {
+        match raw {
+            i if i <= 0 => Self::Nominal,
+            1 => Self::Fair,
+            2 => Self::Serious,
+            _ => Self::Critical,
+        }
+    }
 }
 
 pub struct HardwareDetector;
@@ -42,10 +78,21 @@ impl HardwareDetector {
         let cpu_cores = sys.cpus().len();
 
         // Detect chip type
-        let (chip, chip_name, has_neural_engine, has_metal) = Self::detect_chip();
+        let (chip, chip_name, has_neural_engine, has_metal) = Self::detect_chip(&sys);
+
+        // Detect GPU acceleration usable by whisper.cpp
+        let (gpu_name, available) = Self::detect_gpu();
+        let mut acceleration = Vec::new();
+        if has_metal && cfg!(feature = "apple-silicon") {
+            acceleration.push("metal".to_string());
+        }
+        if has_neural_engine && cfg!(feature = "apple-silicon") {
+            acceleration.push("coreml".to_string());
+        }
+        acceleration.extend(usable_acceleration(&compiled_acceleration(), &available));
 
         // Determine recommended tier
-        let recommended_tier = Self::determine_tier(&chip, ram_gb);
+        let recommended_tier = Self::determine_tier(&chip, ram_gb, cpu_cores, &acceleration);
 
         HardwareProfile {
             chip,
@@ -54,11 +101,64 @@ impl HardwareDetector {
             cpu_cores,
             has_neural_engine,
             has_metal,
+            gpu_name,
+            acceleration,
             recommended_tier,
+            thermal_state: Self::detect_thermal_state(),
         }
     }
 
-    fn detect_chip() -> (ChipType, String, bool, bool) {
+    /// Current thermal state. Read from `NSProcessInfo.thermalState`, which
+    /// reports the same four levels macOS uses to decide how hard to
+    /// throttle; always `Nominal` elsewhere.
+    pub fn detect_thermal_state() -> ThermalState {
+        #[cfg(target_os = "macos")]
+        {
+            use std::ffi::{c_char, c_void};
+
+            #[link(name = "Foundation", kind = "framework")]
+            extern "C" {}
+
+            #[link(name = "objc")]
+            extern "C" {
+                fn objc_getClass(name: *const c_char) -> *mut c_void;
+                fn sel_registerName(name: *const c_char) -> *mut c_void;
+                fn objc_msgSend();
+            }
+
+            unsafe {
+                let class = objc_getClass(b"NSProcessInfo\0".as_ptr() as *const c_char);
+                if class.is_null() {
+                    return ThermalState::Nominal;
+                }
+
+                let send_id: unsafe extern "C" fn(*mut c_void, *mut c_void) -> *mut c_void =
+                    std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+                let process_info = send_id(
+                    class,
+                    sel_registerName(b"processInfo\0".as_ptr() as *const c_char),
+                );
+                if process_info.is_null() {
+                    return ThermalState::Nominal;
+                }
+
+                let send_state: unsafe extern "C" fn(*mut c_void, *mut c_void) -> isize =
+                    std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+                let state = send_state(
+                    process_info,
+                    sel_registerName(b"thermalState\0".as_ptr() as *const c_char),
+                );
+                ThermalState::from_raw(state)
+            }
+        }
+
+        #[cfg(not(target_os = "macos"))]
+        {
+            ThermalState::Nominal
+        }
+    }
+
+    fn detect_chip(sys: &System) -> (ChipType, String, bool, bool) {
         #[cfg(target_os = "macos")]
         {
             use std::process::Command;
@@ -120,10 +220,71 @@ impl HardwareDetector {
             }
         }
 
+        #[cfg(not(target_os = "macos"))]
+        {
+            let brand = sys
+                .cpus()
+                .first()
+                .map(|cpu| cpu.brand().trim().to_string())
+                .unwrap_or_default();
+
+            if !brand.is_empty() {
+                return (chip_from_brand(&brand), brand, false, false);
+            }
+        }
+
+        #[cfg(target_os = "macos")]
+        let _ = sys;
+
         (ChipType::Unknown, "Unknown".to_string(), false, false)
     }
 
-    fn determine_tier(chip: &ChipType, ram_gb: u64) -> Tier {
+    /// Find a discrete GPU and the acceleration runtimes installed for it.
+    /// Returns the GPU name and available backends ("cuda", "vulkan").
+    fn detect_gpu() -> (Option<String>, Vec<String>) {
+        #[cfg(target_os = "macos")]
+        {
+            // Metal/CoreML are handled by the chip detection
+            (None, Vec::new())
+        }
+
+        #[cfg(not(target_os = "macos"))]
+        {
+            use std::process::Command;
+
+            let mut available = Vec::new();
+
+            // nvidia-smi ships with the NVIDIA driver on both Windows and Linux
+            let gpu_name = Command::new("nvidia-smi")
+                .args(["--query-gpu=name", "--format=csv,noheader"])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .and_then(|output| {
+                    String::from_utf8_lossy(&output.stdout)
+                        .lines()
+                        .next()
+                        .map(|line| line.trim().to_string())
+                })
+                .filter(|name| !name.is_empty());
+            if gpu_name.is_some() {
+                available.push("cuda".to_string());
+            }
+
+            if vulkan_loader_present() {
+                available.push("vulkan".to_string());
+            }
+
+            (gpu_name, available)
+        }
+    }
+
+    fn determine_tier(
+        chip: &ChipType,
+        ram_gb: u64,
+        cpu_cores: usize,
+        acceleration: &[String],
+    ) -> Tier {
         match chip {
             ChipType::AppleSilicon => {
                 if ram_gb >= 32 {
@@ -134,14 +295,85 @@ impl HardwareDetector {
                     Tier::Lite
                 }
             }
-            ChipType::Intel | ChipType::Unknown => Tier::Lite,
+            // Intel Macs have no usable GPU backend for whisper.cpp
+            ChipType::Intel if cfg!(target_os = "macos") => Tier::Lite,
+            ChipType::Intel | ChipType::Amd | ChipType::Unknown => {
+                let has_gpu = acceleration.iter().any(|a| a == "cuda" || a == "vulkan");
+                if has_gpu && ram_gb >= 32 {
+                    Tier::Power
+                } else if ram_gb >= 16 && (has_gpu || cpu_cores >= 8) {
+                    Tier::Standard
+                } else {
+                    Tier::Lite
+                }
+            }
         }
     }
 }
 
+/// Classify a CPU brand string such as "AMD Ryzen 9 7950X"
+fn chip_from_brand(brand: &str) -> ChipType {
+    let brand = brand.to_lowercase();
+    if brand.contains("apple") {
+        ChipType::AppleSilicon
+    } else if brand.contains("intel") {
+        ChipType::Intel
+    } else if brand.contains("amd") {
+        ChipType::Amd
+    } else {
+        ChipType::Unknown
+    }
+}
+
+/// GPU backends this build of whisper.cpp was compiled with
+fn compiled_acceleration() -> Vec<String> {
+    let mut compiled = Vec::new();
+    if cfg!(feature = "cuda") {
+        compiled.push("cuda".to_string());
+    }
+    if cfg!(feature = "vulkan") {
+        compiled.push("vulkan".to_string());
+    }
+    compiled
+}
+
+/// Backends that are both compiled in and have a runtime on this machine
+fn usable_acceleration(compiled: &[String], available: &[String]) -> Vec<String> {
+    compiled
+        .iter()
+        .filter(|backend| available.contains(backend))
+        .cloned()
+        .collect()
+}
+
+#[cfg(not(target_os = "macos"))]
+fn vulkan_loader_present() -> bool {
+    #[cfg(target_os = "windows")]
+    let candidates = ["C:\\Windows\\System32\\vulkan-1.dll"];
+
+    #[cfg(not(target_os = "windows"))]
+    let candidates = [
+        "/usr/lib/libvulkan.so.1",
+        "/usr/lib64/libvulkan.so.1",
+        "/usr/lib/x86_64-linux-gnu/libvulkan.so.1",
+        "/usr/lib/aarch64-linux-gnu/libvulkan.so.1",
+    ];
+
+    candidates
+        .iter()
+        .any(|path| std::path::Path::new(path).exists())
+}
+
 impl HardwareProfile {
     pub fn recommended_stt_model(&self) -> &str {
+        let has_gpu = self
+            .acceleration
+            .iter()
+            .any(|a| a == "cuda" || a == "vulkan");
+
         match self.recommended_tier {
+            // A discrete GPU makes the larger model fast enough for dictation
+            Tier::Power if has_gpu => "ggml-small.en.bin",
             Tier::Power => "ggml-base.en.bin",
             Tier::Standard => "ggml-base.en.bin",
             Tier::Lite => "ggml-tiny.en.bin",
@@ -154,4 +386,194 @@ impl HardwareProfile {
             Tier::Lite => "kokoro-v1.0.onnx",
         }
     }
+
+    /// Whether there is RAM to load a model of `size_bytes` with twice its
+    /// size to spare, the headroom inference needs on top of the weights
+    pub fn has_memory_for(&self, size_bytes: u64) -> bool {
+        self.ram_gb * 1024 * 1024 * 1024 > size_bytes.saturating_mul(2)
+    }
+
+    /// Whether this machine has the memory to run the model `model_id`.
+    /// Unknown models can't be run.
+    pub fn can_run_model(&self, model_id: &str) -> bool {
+        ModelRegistry::default()
+            .get_model(model_id)
+            .is_some_and(|model| self.has_memory_for(model.size_bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backends(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn profile(
+        chip: ChipType,
+        ram_gb: u64,
+        cpu_cores: usize,
+        acceleration: &[&str],
+    ) -> HardwareProfile {
+        let acceleration = backends(acceleration);
+        let recommended_tier =
+            HardwareDetector::determine_tier(&chip, ram_gb, cpu_cores, &acceleration);
+        HardwareProfile {
+            chip,
+            chip_name: "Test".to_string(),
+            ram_gb,
+            cpu_cores,
+            has_neural_engine: false,
+            has_metal: false,
+            gpu_name: None,
+            acceleration,
+            recommended_tier,
+            thermal_state: ThermalState::Nominal,
+        }
+    }
+
+    #[test]
+    fn test_thermal_state_from_raw() {
+        assert_eq!(ThermalState::from_raw(0), ThermalState::Nominal);
+        assert_eq!(ThermalState::from_raw(1), ThermalState::Fair);
+        assert_eq!(ThermalState::from_raw(2), ThermalState::Serious);
+        assert_eq!(ThermalState::from_raw(3), ThermalState::Critical);
+        // Unknown values are clamped to the nearest level
+        assert_eq!(ThermalState::from_raw(-1), ThermalState::Nominal);
+        assert_eq!(ThermalState::from_raw(7), ThermalState::Critical);
+    }
+
+    #[test]
+    fn test_thermal_state_ordering() {
+        assert!(ThermalState::Critical > ThermalState::Serious);
+        assert!(ThermalState::Fair > ThermalState::Nominal);
+        assert_eq!(
+            serde_json::to_value(ThermalState::Serious).unwrap(),
+            serde_json::json!("serious")
+        );
+    }
+
+    #[test]
+    fn test_tier_ordering() {
+        assert!(Tier::Power > Tier::Standard);
+        assert!(Tier::Standard > Tier::Lite);
+        assert_eq!(
+            [Tier::Standard, Tier::Power, Tier::Lite].iter().max(),
+            Some(&Tier::Power)
+        );
+    }
+
+    #[test]
+    fn test_has_memory_for() {
+        let gib = 1024 * 1024 * 1024;
+        let machine = profile(ChipType::AppleSilicon, 8, 8, &[]);
+        assert!(machine.has_memory_for(3 * gib));
+        // Exactly half the RAM leaves no headroom
+        assert!(!machine.has_memory_for(4 * gib));
+        assert!(!machine.has_memory_for(u64::MAX));
+    }
+
+    #[test]
+    fn test_can_run_model() {
+        let machine = profile(ChipType::AppleSilicon, 8, 8, &[]);
+        assert!(machine.can_run_model("ggml-base.en.bin"));
+        assert!(!machine.can_run_model("ggml-made-up.bin"));
+    }
+
+    #[test]
+    fn test_apple_silicon_tiers() {
+        let tier = |ram| HardwareDetector::determine_tier(&ChipType::AppleSilicon, ram, 8, &[]);
+        assert_eq!(tier(8), Tier::Lite);
+        assert_eq!(tier(16), Tier::Standard);
+        assert_eq!(tier(32), Tier::Power);
+        assert_eq!(tier(64), Tier::Power);
+    }
+
+    #[test]
+    fn test_pc_tiers_with_gpu() {
+        let cuda = backends(&["cuda"]);
+        assert_eq!(
+            HardwareDetector::determine_tier(&ChipType::Amd, 64, 16, &cuda),
+            Tier::Power
+        );
+        assert_eq!(
+            HardwareDetector::determine_tier(&ChipType::Amd, 16, 4, &cuda),
+            Tier::Standard
+        );
+        assert_eq!(
+            HardwareDetector::determine_tier(&ChipType::Amd, 8, 16, &cuda),
+            Tier::Lite
+        );
+
+        let vulkan = backends(&["vulkan"]);
+        assert_eq!(
+            HardwareDetector::determine_tier(&ChipType::Unknown, 32, 4, &vulkan),
+            Tier::Power
+        );
+    }
+
+    #[test]
+    fn test_pc_tiers_cpu_only() {
+        assert_eq!(
+            HardwareDetector::determine_tier(&ChipType::Amd, 32, 16, &[]),
+            Tier::Standard
+        );
+        assert_eq!(
+            HardwareDetector::determine_tier(&ChipType::Amd, 32, 4, &[]),
+            Tier::Lite
+        );
+        assert_eq!(
+            HardwareDetector::determine_tier(&ChipType::Unknown, 8, 16, &[]),
+            Tier::Lite
+        );
+    }
+
+    #[test]
+    fn test_chip_from_brand() {
+        assert_eq!(
+            chip_from_brand("AMD Ryzen 9 7950X 16-Core Processor"),
+            ChipType::Amd
+        );
+        assert_eq!(
+            chip_from_brand("13th Gen Intel(R) Core(TM) i9-13900K"),
+            ChipType::Intel
+        );
+        assert_eq!(chip_from_brand("Apple M2"), ChipType::AppleSilicon);
+        assert_eq!(
+            chip_from_brand("Qualcomm Snapdragon X Elite"),
+            ChipType::Unknown
+        );
+    }
+
+    #[test]
+    fn test_usable_acceleration_requires_compiled_and_available() {
+        let compiled = backends(&["cuda"]);
+        assert_eq!(
+            usable_acceleration(&compiled, &backends(&["cuda", "vulkan"])),
+            backends(&["cuda"])
+        );
+        assert!(usable_acceleration(&compiled, &backends(&["vulkan"])).is_empty());
+        assert!(usable_acceleration(&[], &backends(&["cuda"])).is_empty());
+    }
+
+    #[test]
+    fn test_recommended_stt_model() {
+        assert_eq!(
+            profile(ChipType::Amd, 64, 16, &["cuda"]).recommended_stt_model(),
+            "ggml-small.en.bin"
+        );
+        assert_eq!(
+            profile(ChipType::AppleSilicon, 64, 12, &["metal"]).recommended_stt_model(),
+            "ggml-base.en.bin"
+        );
+        assert_eq!(
+            profile(ChipType::Amd, 32, 16, &[]).recommended_stt_model(),
+            "ggml-base.en.bin"
+        );
+        assert_eq!(
+            profile(ChipType::Intel, 8, 4, &[]).recommended_stt_model(),
+            "ggml-tiny.en.bin"
+        );
+    }
 }
@@ -1,14 +1,26 @@
 #![allow(dead_code)]
 
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+
 use crate::commands::models::{ModelInfo, ModelStatus, ModelType};
+use crate::engines::kokoro::VOICES_FILE;
+use crate::models::hardware::HardwareProfile;
+
+/// File in the app data directory holding user-added models
+const CUSTOM_MODELS_FILE: &str = "custom_models.json";
 
 pub struct ModelRegistry {
     models: Vec<ModelInfo>,
+    /// Models added at runtime; the only ones written by `save_to_disk`
+    custom_models: Vec<ModelInfo>,
 }
 
 impl ModelRegistry {
+    /// Registry of the built-in models only
     pub fn new() -> Self {
         Self {
+            custom_models: Vec::new(),
             models: vec![
                 // STT Models (Whisper)
                 ModelInfo {
@@ -20,6 +32,9 @@ impl ModelRegistry {
                     download_url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-tiny.en.bin".to_string(),
                     status: ModelStatus::Available,
                     description: "Fastest model, good for quick drafts. ~30x realtime on M1.".to_string(),
+                    requires: Vec::new(),
+                    dependencies_met: true,
+                    hardware_compatible: true,
                 },
                 ModelInfo {
                     id: "ggml-base.en.bin".to_string(),
@@ -30,6 +45,9 @@ impl ModelRegistry {
                     download_url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-base.en.bin".to_string(),
                     status: ModelStatus::Available,
                     description: "Recommended default. Great balance of speed and accuracy. ~15x realtime on M1.".to_string(),
+                    requires: Vec::new(),
+                    dependencies_met: true,
+                    hardware_compatible: true,
                 },
                 ModelInfo {
                     id: "ggml-small.en.bin".to_string(),
@@ -40,6 +58,9 @@ impl ModelRegistry {
                     download_url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-small.en.bin".to_string(),
                     status: ModelStatus::Available,
                     description: "Excellent accuracy for important content. ~6x realtime on M1.".to_string(),
+                    requires: Vec::new(),
+                    dependencies_met: true,
+                    hardware_compatible: true,
                 },
                 ModelInfo {
                     id: "ggml-medium.en.bin".to_string(),
@@ -50,6 +71,9 @@ impl ModelRegistry {
                     download_url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-medium.en.bin".to_string(),
                     status: ModelStatus::Available,
                     description: "Maximum accuracy. ~2x realtime on M1.".to_string(),
+                    requires: Vec::new(),
+                    dependencies_met: true,
+                    hardware_compatible: true,
                 },
                 // CoreML Encoder Models (Apple Silicon acceleration)
                 ModelInfo {
@@ -61,6 +85,9 @@ impl ModelRegistry {
                     download_url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-tiny.en-encoder.mlmodelc.zip".to_string(),
                     status: ModelStatus::Available,
                     description: "CoreML encoder for Whisper Tiny. Enables Neural Engine acceleration on Apple Silicon.".to_string(),
+                    requires: vec!["ggml-tiny.en.bin".to_string()],
+                    dependencies_met: true,
+                    hardware_compatible: true,
                 },
                 ModelInfo {
                     id: "ggml-base.en-encoder.mlmodelc".to_string(),
@@ -71,6 +98,9 @@ impl ModelRegistry {
                     download_url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-base.en-encoder.mlmodelc.zip".to_string(),
                     status: ModelStatus::Available,
                     description: "CoreML encoder for Whisper Base. Enables Neural Engine acceleration on Apple Silicon.".to_string(),
+                    requires: vec!["ggml-base.en.bin".to_string()],
+                    dependencies_met: true,
+                    hardware_compatible: true,
                 },
                 ModelInfo {
                     id: "ggml-small.en-encoder.mlmodelc".to_string(),
@@ -81,6 +111,9 @@ impl ModelRegistry {
                     download_url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-small.en-encoder.mlmodelc.zip".to_string(),
                     status: ModelStatus::Available,
                     description: "CoreML encoder for Whisper Small. Enables Neural Engine acceleration on Apple Silicon.".to_string(),
+                    requires: vec!["ggml-small.en.bin".to_string()],
+                    dependencies_met: true,
+                    hardware_compatible: true,
                 },
                 // TTS Models (Kokoro)
                 ModelInfo {
@@ -92,6 +125,9 @@ impl ModelRegistry {
                     download_url: "https://huggingface.co/onnx-community/Kokoro-82M-v1.0-ONNX/resolve/main/kokoro-v1.0.onnx".to_string(),
                     status: ModelStatus::Available,
                     description: "High-quality TTS with 54 voices. Sub-0.3s generation per sentence.".to_string(),
+                    requires: vec!["voices-v1.0.bin".to_string()],
+                    dependencies_met: true,
+                    hardware_compatible: true,
                 },
                 ModelInfo {
                     id: "voices-v1.0.bin".to_string(),
@@ -102,47 +138,202 @@ impl ModelRegistry {
                     download_url: "https://huggingface.co/onnx-community/Kokoro-82M-v1.0-ONNX/resolve/main/voices-v1.0.bin".to_string(),
                     status: ModelStatus::Available,
                     description: "Voice style vectors for Kokoro TTS.".to_string(),
+                    requires: Vec::new(),
+                    dependencies_met: true,
+                    hardware_compatible: true,
                 },
             ],
         }
     }
 
+    /// Built-in models plus the custom models saved at `path`. A missing or
+    /// unreadable file just leaves the built-ins; custom entries reusing a
+    /// built-in id are ignored.
+    pub fn load_from_disk(path: &Path) -> Self {
+        let mut registry = Self::new();
+
+        let custom_models: Vec<ModelInfo> = match std::fs::read_to_string(path) {
+            Ok(content) => match serde_json::from_str(&content) {
+                Ok(models) => models,
+                Err(e) => {
+                    tracing::warn!("Ignoring invalid custom models file {:?}: {}", path, e);
+                    Vec::new()
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                tracing::warn!("Failed to read custom models file {:?}: {}", path, e);
+                Vec::new()
+            }
+        };
+
+        for model in custom_models {
+            if let Err(e) = registry.add_custom_model(model) {
+                tracing::warn!("Skipping custom model: {}", e);
+            }
+        }
+
+        registry
+    }
+
+    /// Write the custom models (not the built-ins) to `path`
+    pub fn save_to_disk(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(&self.custom_models)?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Add a model that isn't built in. Fails if the id is already taken.
+    pub fn add_custom_model(&mut self, model: ModelInfo) -> Result<()> {
+        if self.iter().any(|m| m.id == model.id) {
+            return Err(anyhow!("A model with id {} already exists", model.id));
+        }
+        self.custom_models.push(model);
+        Ok(())
+    }
+
+    /// Change the display name of a custom model, returning the updated
+    /// model. Built-in models keep their names.
+    pub fn rename_custom_model(&mut self, id: &str, name: &str) -> Result<ModelInfo> {
+        let Some(model) = self.custom_models.iter_mut().find(|m| m.id == id) else {
+            return Err(match self.models.iter().any(|m| m.id == id) {
+                true => anyhow!("Built-in model {} can't be renamed", id),
+                false => anyhow!("No custom model with id {}", id),
+            });
+        };
+        model.name = name.to_string();
+        Ok(model.clone())
+    }
+
+    pub fn get_custom_models(&self) -> Vec<ModelInfo> {
+        self.custom_models.clone()
+    }
+
+    /// Built-in models followed by custom ones
+    fn iter(&self) -> impl Iterator<Item = &ModelInfo> {
+        self.models.iter().chain(&self.custom_models)
+    }
+
     pub fn get_all_models(&self) -> Vec<ModelInfo> {
-        self.models.clone()
+        self.iter().cloned().collect()
     }
 
     pub fn get_model(&self, id: &str) -> Option<ModelInfo> {
-        self.models.iter().find(|m| m.id == id).cloned()
+        self.iter().find(|m| m.id == id).cloned()
     }
 
     pub fn get_stt_models(&self) -> Vec<ModelInfo> {
-        self.models
-            .iter()
+        self.iter()
             .filter(|m| m.model_type == ModelType::Stt)
             .cloned()
             .collect()
     }
 
     pub fn get_tts_models(&self) -> Vec<ModelInfo> {
-        self.models
-            .iter()
+        self.iter()
             .filter(|m| m.model_type == ModelType::Tts)
             .cloned()
             .collect()
     }
+
+    /// Models to download on this machine: the recommended speech and voice
+    /// models, plus the Kokoro voices the voice model reads from
+    pub fn get_recommended_models(&self, profile: &HardwareProfile) -> Vec<ModelInfo> {
+        [
+            profile.recommended_stt_model(),
+            profile.recommended_tts_model(),
+            VOICES_FILE,
+        ]
+        .into_iter()
+        .filter_map(|id| self.get_model(id))
+        .collect()
+    }
+
+    /// Combined download size of the given models in bytes. Unknown ids,
+    /// and models whose size isn't known, add nothing.
+    pub fn total_size_bytes(&self, model_ids: &[&str]) -> u64 {
+        self.iter()
+            .filter(|m| model_ids.contains(&m.id.as_str()))
+            .map(|m| m.size_bytes)
+            .sum()
+    }
+
+    /// What `id` needs, directly or through another dependency, that
+    /// `is_downloaded` says is missing. Each dependency comes before the
+    /// models that need it, so they can be downloaded in order.
+    pub fn missing_dependencies(
+        &self,
+        id: &str,
+        is_downloaded: impl Fn(&ModelInfo) -> bool,
+    ) -> Vec<ModelInfo> {
+        fn visit(
+            registry: &ModelRegistry,
+            id: &str,
+            is_downloaded: &dyn Fn(&ModelInfo) -> bool,
+            seen: &mut Vec<String>,
+            missing: &mut Vec<ModelInfo>,
+        ) {
+            let Some(model) = registry.get_model(id) else {
+                return;
+            };
+            for dependency in &model.requires {
+                if seen.contains(dependency) {
+                    continue;
+                }
+                seen.push(dependency.clone());
+                visit(registry, dependency, is_downloaded, seen, missing);
+                if let Some(required) = registry.get_model(dependency) {
+                    if !is_downloaded(&required) {
+                        missing.push(required);
+                    }
+                }
+            }
+        }
+
+        let (mut seen, mut missing) = (vec![id.to_string()], Vec::new());
+        visit(self, id, &is_downloaded, &mut seen, &mut missing);
+        missing
+    }
+
+    /// Models that list `id` in their `requires`
+    pub fn dependents(&self, id: &str) -> Vec<ModelInfo> {
+        self.iter()
+            .filter(|m| m.requires.iter().any(|r| r == id))
+            .cloned()
+            .collect()
+    }
+
+    /// Download size of everything `get_recommended_models` returns,
+    /// whether or not it is already downloaded
+    pub fn recommended_models_size(&self, profile: &HardwareProfile) -> u64 {
+        let models = self.get_recommended_models(profile);
+        let ids: Vec<&str> = models.iter().map(|m| m.id.as_str()).collect();
+        self.total_size_bytes(&ids)
+    }
 }
 
+/// Built-in models plus the custom models saved in the app data directory
 impl Default for ModelRegistry {
     fn default() -> Self {
-        Self::new()
+        Self::load_from_disk(&custom_models_path())
     }
 }
 
+/// Where custom models are saved
+pub fn custom_models_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("com.blahcubed.app")
+        .join(CUSTOM_MODELS_FILE)
+}
+
 /// Get CoreML encoder models only
 impl ModelRegistry {
     pub fn get_coreml_models(&self) -> Vec<ModelInfo> {
-        self.models
-            .iter()
+        self.iter()
             .filter(|m| m.id.ends_with(".mlmodelc"))
             .cloned()
             .collect()
@@ -150,8 +341,7 @@ impl ModelRegistry {
 
     /// Get the base whisper models (non-CoreML)
     pub fn get_whisper_models(&self) -> Vec<ModelInfo> {
-        self.models
-            .iter()
+        self.iter()
             .filter(|m| m.model_type == ModelType::Stt && m.id.ends_with(".bin"))
             .cloned()
             .collect()
@@ -243,6 +433,131 @@ mod tests {
         assert!(none.is_none());
     }
 
+    #[test]
+    fn test_total_size_bytes() {
+        let registry = ModelRegistry::new();
+        let tiny = registry.get_model("ggml-tiny.en.bin").unwrap().size_bytes;
+        let base = registry.get_model("ggml-base.en.bin").unwrap().size_bytes;
+
+        let total = registry.total_size_bytes(&["ggml-tiny.en.bin", "ggml-base.en.bin", "nope"]);
+        assert_eq!(total, tiny + base);
+        assert_eq!(registry.total_size_bytes(&[]), 0);
+    }
+
+    #[test]
+    fn test_recommended_models_size() {
+        use crate::models::hardware::{ChipType, ThermalState, Tier};
+
+        let profile = HardwareProfile {
+            chip: ChipType::Intel,
+            chip_name: "Test".to_string(),
+            ram_gb: 8,
+            cpu_cores: 4,
+            has_neural_engine: false,
+            has_metal: false,
+            gpu_name: None,
+            acceleration: Vec::new(),
+            recommended_tier: Tier::Lite,
+            thermal_state: ThermalState::Nominal,
+        };
+        let registry = ModelRegistry::new();
+
+        let ids: Vec<String> = registry
+            .get_recommended_models(&profile)
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        let recommended = ["ggml-tiny.en.bin", "kokoro-v1.0.onnx", VOICES_FILE];
+        assert_eq!(ids, recommended);
+
+        let expected = registry.total_size_bytes(&recommended);
+        assert_eq!(registry.recommended_models_size(&profile), expected);
+        assert!(expected > 0);
+    }
+
+    #[test]
+    fn test_dependencies_are_registered_models() {
+        let registry = ModelRegistry::new();
+        for model in registry.get_all_models() {
+            for dependency in &model.requires {
+                assert_ne!(dependency, &model.id, "{} requires itself", model.id);
+                assert!(
+                    registry.get_model(dependency).is_some(),
+                    "{} requires unknown model {}",
+                    model.id,
+                    dependency
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_dependency_graph_is_acyclic() {
+        fn check(registry: &ModelRegistry, id: &str, path: &mut Vec<String>) {
+            assert!(
+                !path.iter().any(|p| p == id),
+                "Dependency cycle: {} -> {}",
+                path.join(" -> "),
+                id
+            );
+            path.push(id.to_string());
+            for dependency in registry.get_model(id).unwrap().requires {
+                check(registry, &dependency, path);
+            }
+            path.pop();
+        }
+
+        let registry = ModelRegistry::new();
+        for model in registry.get_all_models() {
+            check(&registry, &model.id, &mut Vec::new());
+        }
+    }
+
+    #[test]
+    fn test_missing_dependencies() {
+        let registry = ModelRegistry::new();
+        let ids = |models: Vec<ModelInfo>| models.into_iter().map(|m| m.id).collect::<Vec<_>>();
+
+        assert_eq!(
+            ids(registry.missing_dependencies("kokoro-v1.0.onnx", |_| false)),
+            [VOICES_FILE]
+        );
+        assert!(registry
+            .missing_dependencies("kokoro-v1.0.onnx", |m| m.id == VOICES_FILE)
+            .is_empty());
+        assert_eq!(
+            ids(registry.missing_dependencies("ggml-base.en-encoder.mlmodelc", |_| false)),
+            ["ggml-base.en.bin"]
+        );
+        assert!(registry
+            .missing_dependencies("ggml-base.en.bin", |_| false)
+            .is_empty());
+        assert!(registry.missing_dependencies("nope", |_| false).is_empty());
+
+        // Dependencies of dependencies come first
+        let mut registry = ModelRegistry::new();
+        let mut custom = registry.get_model("ggml-tiny.en.bin").unwrap();
+        custom.id = "ggml-tiny.en-finetune.bin".to_string();
+        custom.requires = vec!["ggml-tiny.en-encoder.mlmodelc".to_string()];
+        registry.add_custom_model(custom).unwrap();
+        assert_eq!(
+            ids(registry.missing_dependencies("ggml-tiny.en-finetune.bin", |_| false)),
+            ["ggml-tiny.en.bin", "ggml-tiny.en-encoder.mlmodelc"]
+        );
+    }
+
+    #[test]
+    fn test_dependents() {
+        let registry = ModelRegistry::new();
+        let dependents: Vec<String> = registry
+            .dependents(VOICES_FILE)
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        assert_eq!(dependents, ["kokoro-v1.0.onnx"]);
+        assert!(registry.dependents("kokoro-v1.0.onnx").is_empty());
+    }
+
     #[test]
     fn test_stt_models_count() {
         let registry = ModelRegistry::new();
@@ -279,4 +594,100 @@ mod tests {
             );
         }
     }
+
+    fn custom_model(id: &str) -> ModelInfo {
+        ModelInfo {
+            id: id.to_string(),
+            name: "Fine-tuned Whisper".to_string(),
+            model_type: ModelType::Stt,
+            size_bytes: 1_000,
+            size_display: "1 KB".to_string(),
+            download_url: "https://example.com/custom.bin".to_string(),
+            status: ModelStatus::Available,
+            description: "Custom model".to_string(),
+            requires: Vec::new(),
+            dependencies_met: true,
+            hardware_compatible: true,
+        }
+    }
+
+    #[test]
+    fn test_custom_models_round_trip() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join(CUSTOM_MODELS_FILE);
+
+        let mut registry = ModelRegistry::new();
+        registry
+            .add_custom_model(custom_model("ggml-custom.bin"))
+            .unwrap();
+        registry.save_to_disk(&path).unwrap();
+
+        // Only the custom model is written
+        let saved: Vec<ModelInfo> =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.len(), 1);
+
+        let loaded = ModelRegistry::load_from_disk(&path);
+        assert_eq!(
+            loaded.get_all_models().len(),
+            ModelRegistry::new().get_all_models().len() + 1
+        );
+        assert!(loaded.get_model("ggml-custom.bin").is_some());
+        assert_eq!(loaded.get_custom_models().len(), 1);
+    }
+
+    #[test]
+    fn test_load_ignores_missing_and_invalid_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join(CUSTOM_MODELS_FILE);
+        let builtin_count = ModelRegistry::new().get_all_models().len();
+
+        assert_eq!(
+            ModelRegistry::load_from_disk(&path).get_all_models().len(),
+            builtin_count
+        );
+
+        std::fs::write(&path, "not json").unwrap();
+        assert_eq!(
+            ModelRegistry::load_from_disk(&path).get_all_models().len(),
+            builtin_count
+        );
+    }
+
+    #[test]
+    fn test_rename_custom_model() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join(CUSTOM_MODELS_FILE);
+
+        let mut registry = ModelRegistry::new();
+        registry
+            .add_custom_model(custom_model("ggml-custom.bin"))
+            .unwrap();
+        let renamed = registry
+            .rename_custom_model("ggml-custom.bin", "Medical Whisper")
+            .unwrap();
+        assert_eq!(renamed.name, "Medical Whisper");
+        registry.save_to_disk(&path).unwrap();
+
+        let loaded = ModelRegistry::load_from_disk(&path);
+        let model = loaded.get_model("ggml-custom.bin").unwrap();
+        assert_eq!(model.name, "Medical Whisper");
+
+        // Only custom models can be renamed
+        assert!(registry
+            .rename_custom_model("ggml-base.en.bin", "Mine")
+            .is_err());
+        assert!(registry
+            .rename_custom_model("ggml-nope.bin", "Mine")
+            .is_err());
+    }
+
+    #[test]
+    fn test_custom_model_cannot_shadow_builtin() {
+        let mut registry = ModelRegistry::new();
+        assert!(registry
+            .add_custom_model(custom_model("ggml-base.en.bin"))
+            .is_err());
+        assert!(registry.get_custom_models().is_empty());
+    }
 }
@@ -1,36 +1,197 @@
 use tauri::{AppHandle, Manager};
 
-/// Show the dictation overlay window positioned at top-center of screen
-pub fn show_overlay(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
-    if let Some(window) = app.get_webview_window("dictation-overlay") {
-        // Get the primary monitor to center horizontally at top
-        if let Some(monitor) = window.current_monitor()? {
-            let monitor_size = monitor.size();
-            let window_size = window.outer_size()?;
+use crate::events;
 
-            // Position at top-center with some padding from the top
-            let x = (monitor_size.width as i32 - window_size.width as i32) / 2;
-            let y = 50; // 50px from top
+/// How a message shown with `show_overlay_with_text` is styled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayLevel {
+    Info,
+    Warning,
+    Error,
+}
 
-            window.set_position(tauri::Position::Physical(tauri::PhysicalPosition { x, y }))?;
+impl OverlayLevel {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
         }
+    }
+}
 
-        window.show()?;
-        tracing::debug!("Dictation overlay shown");
-    } else {
-        tracing::warn!("Dictation overlay window not found");
+/// Show the dictation overlay window positioned at top-center of screen.
+/// It starts out click-through and can never take keyboard focus, so the
+/// app being dictated into stays frontmost for the paste. The overlay is
+/// told `session_id` so it can pull the dictation's text if it missed the
+/// events while loading.
+pub fn show_overlay(app: &AppHandle, session_id: u32) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(window) = position_and_show(app)? {
+        // Drop a message left from an earlier dictation
+        window.eval(&message_script(None))?;
+        events::emit(app, events::DictationOverlayShown { session_id });
+        tracing::debug!("Dictation overlay shown for session {}", session_id);
+    }
+
+    Ok(())
+}
+
+/// Show the dictation overlay with `message`, e.g. "No microphone access",
+/// for when the main window that would show an error is hidden. The
+/// message is set before the page handles it, so an overlay that is still
+/// loading picks it up on mount.
+pub fn show_overlay_with_text(
+    app: &AppHandle,
+    message: &str,
+    level: OverlayLevel,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(window) = position_and_show(app)? {
+        window.eval(&message_script(Some((message, level))))?;
+        tracing::debug!(
+            "Dictation overlay shown with {:?} message: {}",
+            level,
+            message
+        );
     }
 
     Ok(())
 }
 
+fn position_and_show(app: &AppHandle) -> tauri::Result<Option<tauri::WebviewWindow>> {
+    let Some(window) = app.get_webview_window("dictation-overlay") else {
+        tracing::warn!("Dictation overlay window not found");
+        return Ok(None);
+    };
+
+    // A non-focusable window is ordered front without becoming key, like
+    // a nonactivating panel
+    window.set_focusable(false)?;
+    window.set_ignore_cursor_events(true)?;
+
+    // Get the primary monitor to center horizontally at top
+    if let Some(monitor) = window.current_monitor()? {
+        let monitor_size = monitor.size();
+        let window_size = window.outer_size()?;
+
+        // Position at top-center with some padding from the top
+        let x = (monitor_size.width as i32 - window_size.width as i32) / 2;
+        let y = 50; // 50px from top
+
+        window.set_position(tauri::Position::Physical(tauri::PhysicalPosition { x, y }))?;
+    }
+
+    window.show()?;
+    crate::audio_levels::set_overlay_visible(true);
+    Ok(Some(window))
+}
+
+/// Script storing the overlay's message in `window.__overlayMessage` and
+/// handing it to the page if it is listening; `None` clears it
+fn message_script(message: Option<(&str, OverlayLevel)>) -> String {
+    let value = match message {
+        Some((text, level)) => format!(
+            "{{ text: {}, level: \"{}\" }}",
+            js_string_literal(text),
+            level.as_str()
+        ),
+        None => "null".to_string(),
+    };
+    format!(
+        "window.__overlayMessage = {}; window.__showOverlayMessage && window.__showOverlayMessage(window.__overlayMessage)",
+        value
+    )
+}
+
 /// Hide the dictation overlay window
 pub fn hide_overlay(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
     if let Some(window) = app.get_webview_window("dictation-overlay") {
         window.hide()?;
+        crate::audio_levels::set_overlay_visible(false);
         tracing::debug!("Dictation overlay hidden");
     }
 
     Ok(())
 }
 
+/// Let clicks reach the overlay's buttons, or pass them through to whatever
+/// is underneath. Only the review phase has buttons to click.
+pub fn set_interactive(
+    app: &AppHandle,
+    interactive: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(window) = app.get_webview_window("dictation-overlay") {
+        window.set_ignore_cursor_events(!interactive)?;
+        tracing::debug!("Dictation overlay interactive: {}", interactive);
+    }
+
+    Ok(())
+}
+
+/// Push partial transcription text into the overlay's webview
+pub fn update_overlay_text(app: &AppHandle, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(window) = app.get_webview_window("dictation-overlay") {
+        let script = format!(
+            "window.__updateTranscriptText && window.__updateTranscriptText({})",
+            js_string_literal(text)
+        );
+        window.eval(&script)?;
+    }
+
+    Ok(())
+}
+
+/// Encode text as a quoted JavaScript string literal so it can't break out of
+/// the `eval` call. JSON string syntax is valid JS and escapes quotes,
+/// backslashes and control characters; `<` is escaped as well so the text
+/// can never close a surrounding script tag.
+fn js_string_literal(text: &str) -> String {
+    serde_json::to_string(text)
+        .unwrap_or_else(|_| "\"\"".to_string())
+        .replace('<', "\\u003c")
+        .replace('\u{2028}', "\\u2028")
+        .replace('\u{2029}', "\\u2029")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_js_string_literal_plain_text() {
+        assert_eq!(js_string_literal("hello world"), "\"hello world\"");
+    }
+
+    #[test]
+    fn test_js_string_literal_escapes_quotes_and_backslashes() {
+        assert_eq!(
+            js_string_literal(r#"it's "quoted" \ done"#),
+            r#""it's \"quoted\" \\ done""#
+        );
+    }
+
+    #[test]
+    fn test_js_string_literal_cannot_break_out() {
+        let literal = js_string_literal("'); alert(1); ('\n</script><script>alert(2)</script>");
+        assert!(literal.starts_with('"') && literal.ends_with('"'));
+        assert!(!literal.contains('\n'));
+        assert!(!literal.contains('<'));
+        // Every inner double quote is escaped
+        let inner = &literal[1..literal.len() - 1];
+        assert!(!inner.replace("\\\"", "").contains('"'));
+    }
+
+    #[test]
+    fn test_message_script() {
+        let script = message_script(Some(("Model \"small\" not found", OverlayLevel::Error)));
+        assert!(script.starts_with(
+            r#"window.__overlayMessage = { text: "Model \"small\" not found", level: "error" };"#
+        ));
+        assert!(script.ends_with("window.__showOverlayMessage(window.__overlayMessage)"));
+        assert!(message_script(None).starts_with("window.__overlayMessage = null;"));
+    }
+
+    #[test]
+    fn test_js_string_literal_line_separators() {
+        assert_eq!(js_string_literal("a\u{2028}b"), "\"a\\u2028b\"");
+    }
+}
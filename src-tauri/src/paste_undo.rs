@@ -0,0 +1,299 @@
+//! Undoing auto-pasted dictations, for when Whisper pastes something wrong.
+//!
+//! Each paste is remembered with its length and the app it went into. An
+//! undo re-activates that app if needed and either presses Delete once per
+//! pasted character or, with `use_native_undo`, presses Cmd+Z once. Undoing
+//! again removes the paste before that.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::accessibility::{self, FrontmostAppInfo};
+use crate::commands::settings::get_settings;
+use crate::error::BlahError;
+use crate::events;
+
+/// How many pastes can be undone one after another
+const HISTORY_CAPACITY: usize = 10;
+
+static HISTORY: Mutex<PasteHistory> = Mutex::new(PasteHistory::new(HISTORY_CAPACITY));
+
+/// A dictation pasted into another app
+#[derive(Debug, Clone)]
+struct Paste {
+    /// Characters pasted, suffix included
+    chars: usize,
+    /// App the dictation was pasted into, when known
+    target_app: Option<FrontmostAppInfo>,
+    pasted_at: Instant,
+}
+
+/// Why a paste couldn't be undone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UndoPasteFailure {
+    NothingToUndo,
+    /// The paste is older than `undo_paste_window_secs`
+    Expired,
+    /// The app the text went into couldn't be brought back to the front,
+    /// or isn't known
+    TargetChanged,
+    /// The keystrokes couldn't be sent, e.g. without accessibility access
+    KeystrokeFailed,
+}
+
+/// A refused or failed undo, with a message for the user
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UndoPasteError {
+    pub reason: UndoPasteFailure,
+    pub message: String,
+}
+
+impl UndoPasteError {
+    fn new(reason: UndoPasteFailure, message: impl Into<String>) -> Self {
+        Self {
+            reason,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<UndoPasteError> for BlahError {
+    fn from(error: UndoPasteError) -> Self {
+        match error.reason {
+            UndoPasteFailure::KeystrokeFailed => BlahError::PermissionDenied(error.message),
+            _ => BlahError::InvalidInput(error.message),
+        }
+    }
+}
+
+/// Recent pastes, oldest first
+struct PasteHistory {
+    capacity: usize,
+    pastes: VecDeque<Paste>,
+}
+
+impl PasteHistory {
+    const fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            pastes: VecDeque::new(),
+        }
+    }
+
+    fn push(&mut self, paste: Paste) {
+        if self.pastes.len() == self.capacity {
+            self.pastes.pop_front();
+        }
+        self.pastes.push_back(paste);
+    }
+
+    /// Take the most recent paste if it was pasted within `window` of `now`.
+    /// An expired paste clears the history, since every older one has
+    /// expired too.
+    fn take_undoable(&mut self, now: Instant, window: Duration) -> Result<Paste, UndoPasteError> {
+        let Some(paste) = self.pastes.pop_back() else {
+            return Err(UndoPasteError::new(
+                UndoPasteFailure::NothingToUndo,
+                "There is no pasted dictation to undo.",
+            ));
+        };
+        if now.saturating_duration_since(paste.pasted_at) > window {
+            self.pastes.clear();
+            return Err(UndoPasteError::new(
+                UndoPasteFailure::Expired,
+                format!(
+                    "The last dictation was pasted more than {}s ago, so it wasn't undone.",
+                    window.as_secs()
+                ),
+            ));
+        }
+        Ok(paste)
+    }
+
+    fn clear(&mut self) {
+        self.pastes.clear();
+    }
+}
+
+/// Remember a dictation that was just pasted into `target_app`
+pub fn remember(pasted_text: &str, target_app: Option<&FrontmostAppInfo>) {
+    let paste = Paste {
+        chars: pasted_text.chars().count(),
+        target_app: target_app.cloned(),
+        pasted_at: Instant::now(),
+    };
+    if let Ok(mut history) = HISTORY.lock() {
+        history.push(paste);
+    }
+}
+
+/// Forget every paste, once something was typed after them (e.g. Enter
+/// sent a chat message) so deleting characters would remove the wrong text
+pub fn forget_all() {
+    if let Ok(mut history) = HISTORY.lock() {
+        history.clear();
+    }
+}
+
+/// Undo the most recent paste, emitting `paste-undone` or
+/// `paste-undo-failed` so the UI can say what happened
+pub async fn undo_last_paste(app: &AppHandle) -> Result<events::PasteUndone, UndoPasteError> {
+    let result = tauri::async_runtime::spawn_blocking(undo_blocking)
+        .await
+        .unwrap_or_else(|e| {
+            Err(UndoPasteError::new(
+                UndoPasteFailure::KeystrokeFailed,
+                format!("Undo was interrupted: {}", e),
+            ))
+        });
+
+    match &result {
+        Ok(undone) => {
+            tracing::info!(
+                "Undid paste of {} chars in {}",
+                undone.chars,
+                undone.app_name
+            );
+            events::emit(app, undone.clone());
+        }
+        Err(e) => {
+            tracing::warn!("Couldn't undo paste: {}", e.message);
+            events::emit(app, events::PasteUndoFailed(e.clone()));
+        }
+    }
+    result
+}
+
+fn undo_blocking() -> Result<events::PasteUndone, UndoPasteError> {
+    let settings = get_settings().unwrap_or_default();
+    let window = Duration::from_secs(settings.undo_paste_window_secs);
+    let paste = HISTORY
+        .lock()
+        .map_err(|e| UndoPasteError::new(UndoPasteFailure::NothingToUndo, e.to_string()))?
+        .take_undoable(Instant::now(), window)?;
+
+    let target = bring_to_front(paste.target_app.as_ref())?;
+
+    let sent = if settings.use_native_undo {
+        accessibility::paste::press_undo()
+    } else {
+        accessibility::paste::delete_backward(paste.chars)
+    };
+    sent.map_err(|e| {
+        UndoPasteError::new(
+            UndoPasteFailure::KeystrokeFailed,
+            format!("Couldn't send the undo keystrokes: {}", e),
+        )
+    })?;
+
+    Ok(events::PasteUndone {
+        chars: paste.chars,
+        app_name: target.name.clone(),
+        native_undo: settings.use_native_undo,
+    })
+}
+
+/// Make sure `target` is frontmost, activating it if the user switched
+/// away. Keystrokes are never sent to an app that can't be confirmed.
+fn bring_to_front(target: Option<&FrontmostAppInfo>) -> Result<&FrontmostAppInfo, UndoPasteError> {
+    let Some(target) = target else {
+        return Err(UndoPasteError::new(
+            UndoPasteFailure::TargetChanged,
+            "The app the dictation was pasted into isn't known, so it wasn't undone.",
+        ));
+    };
+
+    let frontmost = accessibility::get_frontmost_app();
+    if accessibility::paste::is_same_app(Some(target), frontmost.as_ref()) {
+        return Ok(target);
+    }
+
+    tracing::info!("Re-activating {} to undo a paste", target.name);
+    let activated = accessibility::paste::activate_app(target);
+    let frontmost = accessibility::get_frontmost_app();
+    if activated.is_err() || !accessibility::paste::is_same_app(Some(target), frontmost.as_ref()) {
+        return Err(UndoPasteError::new(
+            UndoPasteFailure::TargetChanged,
+            format!("Couldn't switch back to {} to undo the paste.", target.name),
+        ));
+    }
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_secs(30);
+
+    fn paste(chars: usize, pasted_at: Instant) -> Paste {
+        Paste {
+            chars,
+            target_app: None,
+            pasted_at,
+        }
+    }
+
+    #[test]
+    fn test_undo_takes_most_recent_paste_first() {
+        let now = Instant::now();
+        let mut history = PasteHistory::new(HISTORY_CAPACITY);
+        history.push(paste(5, now));
+        history.push(paste(12, now));
+
+        assert_eq!(history.take_undoable(now, WINDOW).unwrap().chars, 12);
+        assert_eq!(history.take_undoable(now, WINDOW).unwrap().chars, 5);
+        let err = history.take_undoable(now, WINDOW).unwrap_err();
+        assert_eq!(err.reason, UndoPasteFailure::NothingToUndo);
+    }
+
+    #[test]
+    fn test_expired_paste_clears_history() {
+        let start = Instant::now();
+        let mut history = PasteHistory::new(HISTORY_CAPACITY);
+        history.push(paste(5, start));
+        history.push(paste(12, start));
+
+        let later = start + WINDOW + Duration::from_secs(1);
+        let err = history.take_undoable(later, WINDOW).unwrap_err();
+        assert_eq!(err.reason, UndoPasteFailure::Expired);
+        assert!(err.message.contains("30s"));
+
+        let err = history.take_undoable(later, WINDOW).unwrap_err();
+        assert_eq!(err.reason, UndoPasteFailure::NothingToUndo);
+    }
+
+    #[test]
+    fn test_history_keeps_latest_pastes() {
+        let now = Instant::now();
+        let mut history = PasteHistory::new(2);
+        for chars in [1, 2, 3] {
+            history.push(paste(chars, now));
+        }
+
+        assert_eq!(history.take_undoable(now, WINDOW).unwrap().chars, 3);
+        assert_eq!(history.take_undoable(now, WINDOW).unwrap().chars, 2);
+        assert!(history.take_undoable(now, WINDOW).is_err());
+    }
+
+    #[test]
+    fn test_unknown_target_is_refused() {
+        let err = bring_to_front(None).unwrap_err();
+        assert_eq!(err.reason, UndoPasteFailure::TargetChanged);
+    }
+
+    #[test]
+    fn test_failure_serializes_reason() {
+        let error = UndoPasteError::new(UndoPasteFailure::TargetChanged, "Switched apps");
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({ "reason": "target_changed", "message": "Switched apps" })
+        );
+        assert_eq!(BlahError::from(error).code(), "invalid_input");
+    }
+}
@@ -0,0 +1,221 @@
+//! What happens to a hotkey dictation once it is transcribed: pasted into
+//! the app it was dictated for, copied, or sent to a URL such as a web
+//! search.
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::accessibility::{self, FrontmostAppInfo};
+use crate::audio::playback::{self, AudioPlayer};
+use crate::commands::settings::AppSettings;
+use crate::events;
+use crate::paste_undo;
+
+/// Marks where the transcription goes in a URL template
+pub const TEXT_PLACEHOLDER: &str = "{text}";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PostAction {
+    /// Paste, pressing Enter only if `press_enter_after_paste` is set
+    #[default]
+    Paste,
+    PasteAndEnter,
+    /// Open `post_action_url_template` with the transcription filled in
+    OpenUrlTemplate,
+    CopyOnly,
+}
+
+/// Check that `template` can be filled in and opened
+pub fn validate_url_template(template: &str) -> Result<(), String> {
+    if !template.contains(TEXT_PLACEHOLDER) {
+        return Err(format!(
+            "URL template needs a {} placeholder for the transcription",
+            TEXT_PLACEHOLDER
+        ));
+    }
+    // A scheme keeps the opener from taking the template for a file or flag
+    let scheme = template.split_once(':').map(|(scheme, _)| scheme);
+    let valid_scheme = scheme.is_some_and(|scheme| {
+        scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    });
+    if !valid_scheme {
+        return Err("URL template must start with a scheme such as https://".to_string());
+    }
+    Ok(())
+}
+
+/// `template` with every placeholder replaced by the percent-encoded `text`
+pub fn expand_url_template(template: &str, text: &str) -> Result<String, String> {
+    validate_url_template(template)?;
+    Ok(template.replace(TEXT_PLACEHOLDER, &percent_encode(text)))
+}
+
+/// Percent-encode everything but the characters RFC 3986 leaves unreserved,
+/// so spaces, `&`, `#` and non-ASCII text survive as one query value
+fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Run the configured action on a finished dictation. `target_app` is the
+/// app that was frontmost when the dictation started. Fails with the reason
+/// when a paste didn't go through, so the text can be offered for copying
+/// instead; other actions only log their failures.
+pub async fn run(
+    app: &AppHandle,
+    text: &str,
+    settings: &AppSettings,
+    target_app: Option<&FrontmostAppInfo>,
+) -> Result<(), String> {
+    let press_enter = match settings.post_action {
+        PostAction::Paste => settings.press_enter_after_paste,
+        PostAction::PasteAndEnter => true,
+        PostAction::CopyOnly => {
+            if let Err(e) = accessibility::paste::set_clipboard(text) {
+                tracing::error!("Failed to copy transcription: {}", e);
+            }
+            return Ok(());
+        }
+        PostAction::OpenUrlTemplate => {
+            let template = settings.post_action_url_template.as_deref().unwrap_or("");
+            let url = match expand_url_template(template, text) {
+                Ok(url) => url,
+                Err(e) => {
+                    tracing::error!("Can't open dictation URL: {}", e);
+                    return Ok(());
+                }
+            };
+            tracing::info!("Opening dictation URL from template {}", template);
+            let opened = crate::commands::settings::open_with_system(app, url.as_ref()).await;
+            if let Err(e) = opened {
+                tracing::error!("Failed to open dictation URL: {}", e);
+            }
+            return Ok(());
+        }
+    };
+
+    match paste(text, settings, target_app, press_enter) {
+        Ok(chars) => {
+            events::emit(
+                app,
+                events::SttPasted {
+                    target_app: target_app.cloned(),
+                    chars,
+                },
+            );
+            if settings.paste_confirmation_sound {
+                play_chime();
+            }
+            Ok(())
+        }
+        Err(error) => {
+            events::emit(
+                app,
+                events::SttPasteFailed {
+                    error: error.clone(),
+                    text: text.to_string(),
+                },
+            );
+            Err(error)
+        }
+    }
+}
+
+/// Play the paste confirmation chime without waiting for it
+fn play_chime() {
+    let played = AudioPlayer::new()
+        .and_then(|player| player.play(&playback::completion_chime(), playback::CHIME_SAMPLE_RATE));
+    if let Err(e) = played {
+        tracing::warn!("Failed to play paste chime: {}", e);
+    }
+}
+
+/// Paste a dictation with the configured suffix, then press Enter if asked.
+/// Enter only follows a paste that went through, into `target_app`.
+/// Returns the number of characters pasted.
+fn paste(
+    text: &str,
+    settings: &AppSettings,
+    target_app: Option<&FrontmostAppInfo>,
+    press_enter: bool,
+) -> Result<usize, String> {
+    // The overlay can't take focus, so a different frontmost app means the
+    // user switched away after pressing the hotkey
+    let frontmost = accessibility::get_frontmost_app();
+    if target_app.is_some() && !accessibility::paste::is_same_app(target_app, frontmost.as_ref()) {
+        tracing::warn!(
+            "Pasting into {:?} rather than the dictation target {:?}",
+            frontmost.map(|app| app.name),
+            target_app.map(|app| &app.name)
+        );
+    }
+
+    let pasted = settings.paste_suffix.apply(text);
+    if let Err(e) = accessibility::paste_text(&pasted) {
+        tracing::error!("Failed to auto-paste transcription: {}", e);
+        return Err(e);
+    }
+    paste_undo::remember(&pasted, target_app);
+    let chars = pasted.chars().count();
+    if !press_enter {
+        return Ok(chars);
+    }
+
+    match accessibility::paste::press_enter_after_paste(target_app) {
+        Ok(pressed) => {
+            tracing::info!("Enter after paste: {}", pressed);
+            // A sent message can't be deleted character by character
+            if pressed {
+                paste_undo::forget_all();
+            }
+        }
+        Err(e) => tracing::error!("Failed to press Enter after paste: {}", e),
+    }
+    Ok(chars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEARCH: &str = "https://www.google.com/search?q={text}";
+
+    #[test]
+    fn test_expand_url_template_encodes_text() {
+        assert_eq!(
+            expand_url_template(SEARCH, "rust async & await").unwrap(),
+            "https://www.google.com/search?q=rust%20async%20%26%20await"
+        );
+        assert_eq!(
+            expand_url_template(SEARCH, "café #1?").unwrap(),
+            "https://www.google.com/search?q=caf%C3%A9%20%231%3F"
+        );
+        assert_eq!(
+            expand_url_template("https://example.com/{text}?again={text}", "a/b").unwrap(),
+            "https://example.com/a%2Fb?again=a%2Fb"
+        );
+    }
+
+    #[test]
+    fn test_url_template_needs_placeholder_and_scheme() {
+        assert!(validate_url_template(SEARCH).is_ok());
+        assert!(validate_url_template("raycast://extensions/search?fallbackText={text}").is_ok());
+
+        assert!(validate_url_template("https://www.google.com/search?q=").is_err());
+        assert!(validate_url_template("https://www.google.com/search?q={TEXT}").is_err());
+        assert!(validate_url_template("www.google.com/search?q={text}").is_err());
+        assert!(validate_url_template("-a Safari {text}").is_err());
+        assert!(expand_url_template("", "query").is_err());
+    }
+}
@@ -0,0 +1,386 @@
+//! Locale-specific typography for finished dictations: quotation marks,
+//! spacing before punctuation and number formats.
+//!
+//! Whisper writes straight quotes and English-style numbers whatever the
+//! language. Each supported language has a rule set that rewrites the
+//! final text of a dictation; partial results are left alone. To support
+//! another language, add a `Locale` variant, recognize it in `from_tag`,
+//! give it rules in `apply` and a table of test cases.
+
+use std::sync::OnceLock;
+
+/// `locale` setting that follows the system's language
+pub const SYSTEM_LOCALE: &str = "system";
+
+/// `locale` setting that leaves dictations as Whisper wrote them
+pub const NO_LOCALE: &str = "none";
+
+/// Espace fine insécable, before `;`, `!` and `?` and inside guillemets
+const NARROW_NBSP: char = '\u{202F}';
+
+/// Espace insécable, before `:`
+const NBSP: char = '\u{A0}';
+
+/// A language with its own typography rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    English,
+    French,
+    German,
+}
+
+/// Quotation marks of a language. Without `single`, every straight single
+/// quote is an apostrophe.
+struct Quotes {
+    open: &'static str,
+    close: &'static str,
+    single: Option<(char, char)>,
+}
+
+const ENGLISH_QUOTES: Quotes = Quotes {
+    open: "\u{201C}",
+    close: "\u{201D}",
+    single: Some(('\u{2018}', '\u{2019}')),
+};
+
+const FRENCH_QUOTES: Quotes = Quotes {
+    open: "\u{AB}\u{202F}",
+    close: "\u{202F}\u{BB}",
+    single: None,
+};
+
+const GERMAN_QUOTES: Quotes = Quotes {
+    open: "\u{201E}",
+    close: "\u{201C}",
+    single: None,
+};
+
+const APOSTROPHE: char = '\u{2019}';
+
+impl Locale {
+    /// The rules for a BCP 47 or POSIX tag such as `fr`, `de-AT` or
+    /// `en_GB.UTF-8`, by its language
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag.trim().split(['-', '_', '.', '@']).next()?;
+        match language.to_ascii_lowercase().as_str() {
+            "en" => Some(Self::English),
+            "fr" => Some(Self::French),
+            "de" => Some(Self::German),
+            _ => None,
+        }
+    }
+
+    /// The rules for the `locale` setting: a tag, `system` or `none`
+    pub fn from_setting(setting: &str) -> Option<Self> {
+        match setting {
+            SYSTEM_LOCALE => system_locale().and_then(Self::from_tag),
+            NO_LOCALE => None,
+            tag => Self::from_tag(tag),
+        }
+    }
+
+    /// `text` with this language's typography
+    pub fn apply(self, text: &str) -> String {
+        match self {
+            Self::English => curl_quotes(text, &ENGLISH_QUOTES),
+            Self::French => {
+                let text = curl_quotes(text, &FRENCH_QUOTES);
+                let text = space_french_punctuation(&text);
+                localize_numbers(&text, NARROW_NBSP, ',')
+            }
+            Self::German => {
+                let text = curl_quotes(text, &GERMAN_QUOTES);
+                localize_numbers(&text, '.', ',')
+            }
+        }
+    }
+}
+
+/// The system's locale, e.g. `fr_FR`, looked up once
+pub fn system_locale() -> Option<&'static str> {
+    static SYSTEM_LOCALE: OnceLock<Option<String>> = OnceLock::new();
+    SYSTEM_LOCALE.get_or_init(detect_system_locale).as_deref()
+}
+
+fn detect_system_locale() -> Option<String> {
+    // Apps started from Finder don't get LANG, so ask for the user's
+    // region setting first
+    #[cfg(target_os = "macos")]
+    {
+        let output = std::process::Command::new("defaults")
+            .args(["read", "-g", "AppleLocale"])
+            .output();
+        if let Ok(output) = output {
+            let locale = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if output.status.success() && !locale.is_empty() {
+                return Some(locale);
+            }
+        }
+    }
+
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|locale| !locale.is_empty() && locale != "C" && locale != "POSIX")
+}
+
+/// Whether a quote after `previous` opens a quotation: at the start of the
+/// text, after a space or after an opening bracket or dash
+fn opens_quote(previous: Option<char>) -> bool {
+    previous.is_none_or(|c| c.is_whitespace() || "([{\u{2014}\u{2013}/".contains(c))
+}
+
+/// Replace straight quotes with the language's quotation marks, and
+/// apostrophes inside words with `’`. Double quotes alternate between
+/// opening and closing, starting with whichever fits where they are.
+fn curl_quotes(text: &str, quotes: &Quotes) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut in_quote = false;
+    let mut skip_space = false;
+
+    for (i, &c) in chars.iter().enumerate() {
+        if skip_space && c.is_whitespace() {
+            continue;
+        }
+        skip_space = false;
+        let previous = i.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(i + 1).copied();
+
+        match c {
+            '"' if !in_quote && opens_quote(previous) => {
+                out.push_str(quotes.open);
+                in_quote = true;
+                // Guillemets bring their own space
+                skip_space = quotes.open.ends_with(char::is_whitespace);
+            }
+            '"' => {
+                if quotes.close.starts_with(char::is_whitespace) {
+                    out.truncate(out.trim_end().len());
+                }
+                out.push_str(quotes.close);
+                in_quote = false;
+            }
+            '\'' => {
+                let in_word = previous.is_some_and(char::is_alphanumeric)
+                    && next.is_some_and(char::is_alphanumeric);
+                let quote = match quotes.single {
+                    Some((open, _)) if !in_word && opens_quote(previous) => open,
+                    Some((_, close)) if !in_word => close,
+                    _ => APOSTROPHE,
+                };
+                out.push(quote);
+            }
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// Put a non-breaking space before `;`, `!`, `?` and `:`, replacing any
+/// ordinary space there. Only punctuation that ends a clause is spaced, so
+/// times like `10:30` and URLs keep their colons.
+fn space_french_punctuation(text: &str) -> String {
+    const SPACED: [char; 4] = [';', '!', '?', ':'];
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+
+    for (i, &c) in chars.iter().enumerate() {
+        let previous = i.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(i + 1).copied();
+        let ends_clause = next.is_none_or(|n| {
+            n.is_whitespace() || SPACED.contains(&n) || ".,\"\u{BB}\u{201D})".contains(n)
+        });
+        // `?!` is spaced once, before the first mark
+        let follows_mark = previous.is_some_and(|p| SPACED.contains(&p));
+
+        if SPACED.contains(&c) && ends_clause && !follows_mark {
+            let kept = out.trim_end_matches([' ', NBSP, NARROW_NBSP]).len();
+            out.truncate(kept);
+            if !out.is_empty() && !out.ends_with('\n') {
+                out.push(if c == ':' { NBSP } else { NARROW_NBSP });
+            }
+        }
+        out.push(c);
+    }
+
+    out
+}
+
+/// Rewrite English-formatted numbers such as `1,234.56` with `group` and
+/// `decimal` separators. A number that already reads correctly in the
+/// target format is left alone, so in German `3.14` becomes `3,14` but
+/// `1.500` and `1,5` stay as they are. Numbers right after a letter, like
+/// the `2.0` in `v2.0`, are left alone too.
+fn localize_numbers(text: &str, group: char, decimal: char) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+        out.push_str(&rest[..start]);
+        let after_letter = out.chars().next_back().is_some_and(char::is_alphanumeric);
+        let token_len = rest[start..]
+            .find(|c: char| !(c.is_ascii_digit() || c == ',' || c == '.'))
+            .unwrap_or(rest.len() - start);
+        let token = &rest[start..start + token_len];
+        // A separator at the end is punctuation, like a full stop
+        let number = token.trim_end_matches(['.', ',']);
+
+        match localized_number(number, group, decimal).filter(|_| !after_letter) {
+            Some(localized) => out.push_str(&localized),
+            None => out.push_str(number),
+        }
+        out.push_str(&token[number.len()..]);
+        rest = &rest[start + token_len..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// `number` in the target format, if it is an English-formatted number
+/// with separators that can't be read in the target format as it is
+fn localized_number(number: &str, group: char, decimal: char) -> Option<String> {
+    if !number.contains([',', '.'])
+        || !is_number_in(number, ',', '.')
+        || is_number_in(number, group, decimal)
+    {
+        return None;
+    }
+
+    let (integer, fraction) = match number.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (number, None),
+    };
+    let mut localized = integer.replace(',', &group.to_string());
+    if let Some(fraction) = fraction {
+        localized.push(decimal);
+        localized.push_str(fraction);
+    }
+    Some(localized)
+}
+
+/// Whether `number` is written with `group` between thousands and
+/// `decimal` before the fraction
+fn is_number_in(number: &str, group: char, decimal: char) -> bool {
+    let all_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let (integer, fraction) = match number.split_once(decimal) {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (number, None),
+    };
+    if fraction.is_some_and(|fraction| !all_digits(fraction)) {
+        return false;
+    }
+
+    let mut groups = integer.split(group);
+    let first = groups.next().unwrap_or_default();
+    let rest: Vec<&str> = groups.collect();
+    if !all_digits(first) {
+        return false;
+    }
+    rest.is_empty() || (first.len() <= 3 && rest.iter().all(|g| g.len() == 3 && all_digits(g)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(locale: Locale, cases: &[(&str, &str)]) {
+        for (input, expected) in cases {
+            assert_eq!(locale.apply(input), *expected, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_english_rules() {
+        check(
+            Locale::English,
+            &[
+                ("", ""),
+                (
+                    "No quotes, 1,234.56 left alone.",
+                    "No quotes, 1,234.56 left alone.",
+                ),
+                ("\"Hello\"", "\u{201C}Hello\u{201D}"),
+                (
+                    "She said \"hi\" and left.",
+                    "She said \u{201C}hi\u{201D} and left.",
+                ),
+                (
+                    "(\"quoted\") and \"unbalanced",
+                    "(\u{201C}quoted\u{201D}) and \u{201C}unbalanced",
+                ),
+                ("say \"hi\"", "say \u{201C}hi\u{201D}"),
+                ("don't", "don\u{2019}t"),
+                ("'single' quotes", "\u{2018}single\u{2019} quotes"),
+                ("the dogs' bone", "the dogs\u{2019} bone"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_french_rules() {
+        check(
+            Locale::French,
+            &[
+                ("", ""),
+                (
+                    "Il a dit \"bonjour\".",
+                    "Il a dit \u{AB}\u{202F}bonjour\u{202F}\u{BB}.",
+                ),
+                ("\" espaces \"", "\u{AB}\u{202F}espaces\u{202F}\u{BB}"),
+                ("Vraiment ?", "Vraiment\u{202F}?"),
+                ("Vraiment?", "Vraiment\u{202F}?"),
+                ("Quoi ?!", "Quoi\u{202F}?!"),
+                ("Attention : danger", "Attention\u{A0}: danger"),
+                ("Oui; non", "Oui\u{202F}; non"),
+                (
+                    "À 10:30 sur https://example.com",
+                    "À 10:30 sur https://example.com",
+                ),
+                ("?", "?"),
+                ("l'homme", "l\u{2019}homme"),
+                ("Prix : 1,234.56 euros", "Prix\u{A0}: 1\u{202F}234,56 euros"),
+                ("3.14 et 1,5 et 1,234", "3,14 et 1,5 et 1,234"),
+                ("Le 12.05.2024.", "Le 12.05.2024."),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_german_rules() {
+        check(
+            Locale::German,
+            &[
+                ("", ""),
+                ("\"Hallo\"", "\u{201E}Hallo\u{201C}"),
+                (
+                    "Er sagte \"ja\", dann ging er.",
+                    "Er sagte \u{201E}ja\u{201C}, dann ging er.",
+                ),
+                ("Wie geht's?", "Wie geht\u{2019}s?"),
+                ("Das kostet 1,234.56 Euro.", "Das kostet 1.234,56 Euro."),
+                ("Pi ist 3.14.", "Pi ist 3,14."),
+                ("1,234,567", "1.234.567"),
+                ("1.500 und 1,5 bleiben", "1.500 und 1,5 bleiben"),
+                (
+                    "Version v2.0 und 192.168.1.1",
+                    "Version v2.0 und 192.168.1.1",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_locale_from_tag() {
+        assert_eq!(Locale::from_tag("fr"), Some(Locale::French));
+        assert_eq!(Locale::from_tag("de-AT"), Some(Locale::German));
+        assert_eq!(Locale::from_tag("en_GB.UTF-8"), Some(Locale::English));
+        assert_eq!(Locale::from_tag("FR_ca"), Some(Locale::French));
+        assert_eq!(Locale::from_tag("ja_JP"), None);
+        assert_eq!(Locale::from_tag(""), None);
+        assert_eq!(Locale::from_setting(NO_LOCALE), None);
+        assert_eq!(Locale::from_setting("de"), Some(Locale::German));
+    }
+}
@@ -0,0 +1,4 @@
+pub mod locale;
+pub mod normalize;
+pub mod paragraphs;
+pub mod spell;
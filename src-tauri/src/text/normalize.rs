@@ -0,0 +1,1062 @@
+//! Clean up text before it's handed to the TTS engine.
+//!
+//! Text selected in a browser or markdown editor is full of syntax that reads
+//! badly aloud ("asterisk asterisk bold"), URLs that get spelled out and
+//! numbers in forms the phonemizer guesses at. Everything here is a pure
+//! `&str -> String` transform; the caller keeps the original text for display.
+
+use serde::{Deserialize, Serialize};
+
+/// Which normalization passes to run. Each category can be turned off on its own.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NormalizeOptions {
+    /// Strip markdown and HTML syntax (emphasis, headings, links, tags)
+    #[serde(default = "default_true")]
+    pub markup: bool,
+    /// Read URLs as "link to example dot com"
+    #[serde(default = "default_true")]
+    pub urls: bool,
+    /// Expand abbreviations like "e.g." and "vs."
+    #[serde(default = "default_true")]
+    pub abbreviations: bool,
+    /// Spell out currency, percentages, dates, ordinals and "3.5x"
+    #[serde(default = "default_true")]
+    pub numbers: bool,
+    /// Collapse repeated whitespace and line breaks
+    #[serde(default = "default_true")]
+    pub whitespace: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for NormalizeOptions {
+    fn default() -> Self {
+        Self {
+            markup: true,
+            urls: true,
+            abbreviations: true,
+            numbers: true,
+            whitespace: true,
+        }
+    }
+}
+
+/// Run the enabled passes over `text`
+pub fn normalize_for_speech(text: &str, options: &NormalizeOptions) -> String {
+    let mut text = text.to_string();
+
+    if options.markup {
+        text = strip_markup(&text);
+    }
+    if options.urls {
+        text = map_words(&text, speak_url);
+    }
+    if options.abbreviations {
+        text = map_words(&text, expand_abbreviation);
+    }
+    if options.numbers {
+        text = map_words(&text, speak_number);
+    }
+    if options.whitespace {
+        text = collapse_whitespace(&text);
+    }
+
+    text
+}
+
+// ---------------------------------------------------------------------------
+// Markup
+// ---------------------------------------------------------------------------
+
+fn strip_markup(text: &str) -> String {
+    let text = strip_markdown_lines(text);
+    let text = strip_links(&text);
+    let text = strip_html(&text);
+    strip_emphasis(&text)
+}
+
+/// Line-level markdown: code fences, headings, quotes, bullets and rules
+fn strip_markdown_lines(text: &str) -> String {
+    let mut lines = Vec::new();
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") || is_horizontal_rule(trimmed) {
+            lines.push(String::new());
+            continue;
+        }
+
+        let mut rest = trimmed;
+        while let Some(stripped) = rest.strip_prefix('>') {
+            rest = stripped.trim_start();
+        }
+
+        let hashes = rest.chars().take_while(|&c| c == '#').count();
+        if (1..=6).contains(&hashes) && rest[hashes..].starts_with(' ') {
+            rest = rest[hashes..].trim_start();
+        }
+
+        for bullet in ["- ", "* ", "+ "] {
+            if let Some(stripped) = rest.strip_prefix(bullet) {
+                rest = stripped.trim_start();
+                break;
+            }
+        }
+
+        lines.push(rest.to_string());
+    }
+
+    lines.join("\n")
+}
+
+fn is_horizontal_rule(line: &str) -> bool {
+    let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|&m| compact.chars().all(|c| c == m))
+}
+
+/// `[text](url)` and `![alt](url)` become just the text
+fn strip_links(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let is_image = chars[i] == '!' && chars.get(i + 1) == Some(&'[');
+        let start = if is_image { i + 1 } else { i };
+
+        if chars[start] == '[' {
+            if let Some((label, end)) = parse_link(&chars, start) {
+                out.push_str(&label);
+                i = end;
+                continue;
+            }
+        }
+
+        out.push(chars[i]);
+        i += 1;
+    }
+
+    out
+}
+
+/// Parse `[label](target)` starting at `start`, returning the label and the
+/// index just past the closing parenthesis
+fn parse_link(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let close_label = (start + 1..chars.len()).find(|&j| chars[j] == ']' || chars[j] == '\n')?;
+    if chars[close_label] != ']' || chars.get(close_label + 1) != Some(&'(') {
+        return None;
+    }
+
+    let close_target =
+        (close_label + 2..chars.len()).find(|&j| chars[j] == ')' || chars[j].is_whitespace())?;
+    if chars[close_target] != ')' {
+        return None;
+    }
+
+    let label: String = chars[start + 1..close_label].iter().collect();
+    Some((label, close_target + 1))
+}
+
+const BLOCK_TAGS: &[&str] = &[
+    "br", "p", "div", "li", "ul", "ol", "tr", "h1", "h2", "h3", "h4", "h5", "h6",
+];
+
+/// Drop HTML tags and decode the common entities
+fn strip_html(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i] == '<' {
+            if let Some((name, end)) = parse_tag(&chars, i) {
+                // Block-level tags separate text, inline ones don't
+                if BLOCK_TAGS.contains(&name.as_str()) {
+                    out.push('\n');
+                }
+                i = end;
+                continue;
+            }
+        }
+
+        out.push(chars[i]);
+        i += 1;
+    }
+
+    decode_entities(&out)
+}
+
+/// Parse a tag like `<b>`, `</p>` or `<br/>` at `start`, returning its
+/// lowercase name and the index just past `>`
+fn parse_tag(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let mut i = start + 1;
+    if chars.get(i) == Some(&'/') {
+        i += 1;
+    }
+    if !chars.get(i)?.is_ascii_alphabetic() {
+        return None;
+    }
+
+    let name_start = i;
+    while i < chars.len() && chars[i].is_ascii_alphanumeric() {
+        i += 1;
+    }
+    let name: String = chars[name_start..i]
+        .iter()
+        .collect::<String>()
+        .to_lowercase();
+
+    while i < chars.len() {
+        match chars[i] {
+            '>' => return Some((name, i + 1)),
+            '<' | '\n' => return None,
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+fn decode_entities(text: &str) -> String {
+    const ENTITIES: &[(&str, &str)] = &[
+        ("&nbsp;", " "),
+        ("&lt;", "<"),
+        ("&gt;", ">"),
+        ("&quot;", "\""),
+        ("&#39;", "'"),
+        ("&apos;", "'"),
+        ("&mdash;", " - "),
+        ("&ndash;", " - "),
+        // Last, so "&amp;lt;" decodes to "&lt;" rather than "<"
+        ("&amp;", "&"),
+    ];
+
+    ENTITIES
+        .iter()
+        .fold(text.to_string(), |text, (entity, replacement)| {
+            text.replace(entity, replacement)
+        })
+}
+
+/// Remove `*`, `_` and `~~` runs that open or close emphasis. A run counts as
+/// a marker when it sits at a word boundary on exactly one side, which leaves
+/// `snake_case` and `2 * 3` alone.
+fn strip_emphasis(text: &str) -> String {
+    let chars: Vec<char> = text.chars().filter(|&c| c != '`').collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if !matches!(c, '*' | '_' | '~') {
+            out.push(c);
+            i += 1;
+            continue;
+        }
+
+        let run_end = (i..chars.len())
+            .find(|&j| chars[j] != c)
+            .unwrap_or(chars.len());
+        let run_len = run_end - i;
+
+        let is_boundary = |ch: Option<&char>| !matches!(ch, Some(ch) if ch.is_alphanumeric());
+        let before = is_boundary(i.checked_sub(1).and_then(|j| chars.get(j)));
+        let after = is_boundary(chars.get(run_end));
+
+        let valid_run = if c == '~' { run_len == 2 } else { run_len <= 3 };
+        if !(valid_run && before != after) {
+            out.extend(&chars[i..run_end]);
+        }
+        i = run_end;
+    }
+
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Word-level passes
+// ---------------------------------------------------------------------------
+
+/// Apply `f` to each whitespace-separated word, keeping the whitespace as is.
+/// `f` returns `None` to leave a word unchanged.
+fn map_words(text: &str, f: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut word_start = None;
+
+    for (i, c) in text.char_indices() {
+        if c.is_whitespace() {
+            if let Some(start) = word_start.take() {
+                let word = &text[start..i];
+                out.push_str(&f(word).unwrap_or_else(|| word.to_string()));
+            }
+            out.push(c);
+        } else if word_start.is_none() {
+            word_start = Some(i);
+        }
+    }
+    if let Some(start) = word_start {
+        let word = &text[start..];
+        out.push_str(&f(word).unwrap_or_else(|| word.to_string()));
+    }
+
+    out
+}
+
+const OPENING_PUNCTUATION: &[char] = &['(', '[', '<', '"', '\'', '“', '‘'];
+const CLOSING_PUNCTUATION: &[char] = &[
+    ')', ']', '>', '"', '\'', '”', '’', ',', '.', ';', ':', '!', '?',
+];
+
+/// Split a word into leading punctuation, core and trailing punctuation
+fn split_punctuation<'a>(word: &'a str, closing: &[char]) -> (&'a str, &'a str, &'a str) {
+    let core_start = word.len() - word.trim_start_matches(OPENING_PUNCTUATION).len();
+    let rest = &word[core_start..];
+    let core_end = rest.trim_end_matches(closing).len();
+    (&word[..core_start], &rest[..core_end], &rest[core_end..])
+}
+
+fn speak_url(word: &str) -> Option<String> {
+    let (prefix, core, suffix) = split_punctuation(word, CLOSING_PUNCTUATION);
+    let lower = core.to_lowercase();
+
+    let without_scheme = ["https://", "http://"]
+        .iter()
+        .find_map(|scheme| lower.strip_prefix(scheme))
+        .or_else(|| lower.starts_with("www.").then_some(lower.as_str()))?;
+    let host = without_scheme
+        .split(['/', '?', '#', ':'])
+        .next()
+        .unwrap_or_default();
+    let host = host.strip_prefix("www.").unwrap_or(host);
+    if host.is_empty() {
+        return None;
+    }
+
+    Some(format!(
+        "{}link to {}{}",
+        prefix,
+        host.split('.')
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" dot "),
+        suffix
+    ))
+}
+
+fn expand_abbreviation(word: &str) -> Option<String> {
+    const ABBREVIATIONS: &[(&str, &str)] = &[
+        ("e.g.", "for example"),
+        ("eg.", "for example"),
+        ("i.e.", "that is"),
+        ("ie.", "that is"),
+        ("etc.", "et cetera"),
+        ("vs.", "versus"),
+        ("vs", "versus"),
+        ("approx.", "approximately"),
+        ("dr.", "Doctor"),
+        ("mr.", "Mister"),
+        ("mrs.", "Missus"),
+        ("prof.", "Professor"),
+        ("jr.", "Junior"),
+        ("sr.", "Senior"),
+        ("w/", "with"),
+        ("w/o", "without"),
+        ("&", "and"),
+    ];
+
+    // Periods belong to the abbreviation, so only strip other punctuation
+    let closing: Vec<char> = CLOSING_PUNCTUATION
+        .iter()
+        .copied()
+        .filter(|&c| c != '.')
+        .collect();
+    let (prefix, core, suffix) = split_punctuation(word, &closing);
+    let lower = core.to_lowercase();
+
+    ABBREVIATIONS
+        .iter()
+        .find(|(abbreviation, _)| *abbreviation == lower)
+        .map(|(_, expansion)| format!("{}{}{}", prefix, expansion, suffix))
+}
+
+// ---------------------------------------------------------------------------
+// Numbers
+// ---------------------------------------------------------------------------
+
+struct Currency {
+    symbol: char,
+    singular: &'static str,
+    plural: &'static str,
+    minor_singular: &'static str,
+    minor_plural: &'static str,
+}
+
+const CURRENCIES: &[Currency] = &[
+    Currency {
+        symbol: '$',
+        singular: "dollar",
+        plural: "dollars",
+        minor_singular: "cent",
+        minor_plural: "cents",
+    },
+    Currency {
+        symbol: '€',
+        singular: "euro",
+        plural: "euros",
+        minor_singular: "cent",
+        minor_plural: "cents",
+    },
+    Currency {
+        symbol: '£',
+        singular: "pound",
+        plural: "pounds",
+        minor_singular: "penny",
+        minor_plural: "pence",
+    },
+];
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+fn speak_number(word: &str) -> Option<String> {
+    let (prefix, core, suffix) = split_punctuation(word, CLOSING_PUNCTUATION);
+    if !core.chars().any(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let spoken = speak_currency(core)
+        .or_else(|| speak_date(core))
+        .or_else(|| {
+            core.strip_suffix('%')
+                .and_then(speak_plain_number)
+                .map(|n| format!("{} percent", n))
+        })
+        .or_else(|| {
+            core.strip_suffix(['x', '×'])
+                .and_then(speak_plain_number)
+                .map(|n| format!("{} times", n))
+        })
+        .or_else(|| speak_ordinal(core))
+        .or_else(|| speak_year(core))
+        .or_else(|| speak_plain_number(core))?;
+
+    Some(format!("{}{}{}", prefix, spoken, suffix))
+}
+
+/// "$1,234.50" -> "one thousand two hundred thirty-four dollars and fifty cents",
+/// "$2.5M" -> "two point five million dollars"
+fn speak_currency(core: &str) -> Option<String> {
+    let mut chars = core.chars();
+    let symbol = chars.next()?;
+    let currency = CURRENCIES.iter().find(|c| c.symbol == symbol)?;
+    let amount = chars.as_str();
+
+    let scale = match amount.chars().last()? {
+        'k' | 'K' => Some("thousand"),
+        'm' | 'M' => Some("million"),
+        'b' | 'B' => Some("billion"),
+        _ => None,
+    };
+    if let Some(scale) = scale {
+        let number = speak_plain_number(&amount[..amount.len() - 1])?;
+        return Some(format!("{} {} {}", number, scale, currency.plural));
+    }
+
+    let (whole, fraction) = parse_number(amount)?;
+    let minor = match fraction.as_deref() {
+        None | Some("00") => 0,
+        Some(digits) if digits.len() == 2 => digits.parse::<u64>().ok()?,
+        // Not a price with cents, e.g. "$1.5", so read the decimal
+        Some(_) => return speak_plain_number(amount).map(|n| format!("{} {}", n, currency.plural)),
+    };
+
+    let major = match whole {
+        0 if minor > 0 => None,
+        1 => Some(format!("one {}", currency.singular)),
+        n => Some(format!("{} {}", number_to_words(n), currency.plural)),
+    };
+    let minor = (minor > 0).then(|| {
+        let unit = if minor == 1 {
+            currency.minor_singular
+        } else {
+            currency.minor_plural
+        };
+        format!("{} {}", number_to_words(minor), unit)
+    });
+
+    match (major, minor) {
+        (Some(major), Some(minor)) => Some(format!("{} and {}", major, minor)),
+        (Some(major), None) => Some(major),
+        (None, Some(minor)) => Some(minor),
+        (None, None) => None,
+    }
+}
+
+/// ISO `2024-03-15`, or `3/15/2024` (read as day/month when the first part is over 12)
+fn speak_date(core: &str) -> Option<String> {
+    let parts: Vec<&str> = if core.contains('-') {
+        core.split('-').collect()
+    } else {
+        core.split('/').collect()
+    };
+    if parts.len() != 3
+        || parts
+            .iter()
+            .any(|p| p.is_empty() || !p.bytes().all(|b| b.is_ascii_digit()))
+    {
+        return None;
+    }
+
+    let numbers: Vec<u32> = parts
+        .iter()
+        .map(|p| p.parse().ok())
+        .collect::<Option<_>>()?;
+    let (year, month, day) = if core.contains('-') {
+        if parts[0].len() != 4 {
+            return None;
+        }
+        (numbers[0], numbers[1], numbers[2])
+    } else {
+        if parts[2].len() != 4 {
+            return None;
+        }
+        if numbers[0] > 12 {
+            (numbers[2], numbers[1], numbers[0])
+        } else {
+            (numbers[2], numbers[0], numbers[1])
+        }
+    };
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    Some(format!(
+        "{} {}, {}",
+        MONTHS[month as usize - 1],
+        ordinal_words(day as u64),
+        year_words(year as u64)
+    ))
+}
+
+/// "1st" -> "first", "23rd" -> "twenty-third"
+fn speak_ordinal(core: &str) -> Option<String> {
+    let digits = ["st", "nd", "rd", "th"]
+        .iter()
+        .find_map(|suffix| core.strip_suffix(suffix))?;
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok().map(ordinal_words)
+}
+
+/// Four-digit numbers that look like years are read in pairs: "nineteen ninety-nine"
+fn speak_year(core: &str) -> Option<String> {
+    if core.len() != 4 || !core.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let year: u64 = core.parse().ok()?;
+    (1100..=2099).contains(&year).then(|| year_words(year))
+}
+
+/// "1,234" -> "one thousand two hundred thirty-four", "-3.14" -> "minus three point one four"
+fn speak_plain_number(core: &str) -> Option<String> {
+    let (sign, digits) = match core.strip_prefix(['-', '−']) {
+        Some(rest) => ("minus ", rest),
+        None => ("", core),
+    };
+
+    let (whole, fraction) = parse_number(digits)?;
+    let mut spoken = format!("{}{}", sign, number_to_words(whole));
+    if let Some(fraction) = fraction {
+        spoken.push_str(" point");
+        for digit in fraction.chars() {
+            spoken.push(' ');
+            spoken.push_str(ONES[digit.to_digit(10)? as usize]);
+        }
+    }
+    Some(spoken)
+}
+
+/// Parse `1234`, `1,234` or `1,234.56` into the whole part and fraction digits
+fn parse_number(text: &str) -> Option<(u64, Option<String>)> {
+    let (whole, fraction) = match text.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (text, None),
+    };
+
+    if whole.is_empty() {
+        return None;
+    }
+    if let Some(fraction) = fraction {
+        if fraction.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+    }
+
+    let groups: Vec<&str> = whole.split(',').collect();
+    let grouped_ok = groups.len() == 1
+        || (!groups[0].is_empty()
+            && groups[0].len() <= 3
+            && groups[1..].iter().all(|g| g.len() == 3));
+    if !grouped_ok || !groups.iter().all(|g| g.bytes().all(|b| b.is_ascii_digit())) {
+        return None;
+    }
+
+    let whole: u64 = groups.concat().parse().ok()?;
+    Some((whole, fraction.map(|f| f.to_string())))
+}
+
+const ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+const SCALES: [(u64, &str); 4] = [
+    (1_000_000_000_000, "trillion"),
+    (1_000_000_000, "billion"),
+    (1_000_000, "million"),
+    (1_000, "thousand"),
+];
+
+fn number_to_words(n: u64) -> String {
+    if n < 20 {
+        return ONES[n as usize].to_string();
+    }
+    if n < 100 {
+        let tens = TENS[(n / 10) as usize];
+        return match n % 10 {
+            0 => tens.to_string(),
+            ones => format!("{}-{}", tens, ONES[ones as usize]),
+        };
+    }
+    if n < 1000 {
+        let hundreds = format!("{} hundred", ONES[(n / 100) as usize]);
+        return match n % 100 {
+            0 => hundreds,
+            rest => format!("{} {}", hundreds, number_to_words(rest)),
+        };
+    }
+
+    let (scale, name) = SCALES
+        .iter()
+        .find(|(scale, _)| n >= *scale)
+        .copied()
+        .unwrap_or((1_000, "thousand"));
+    let head = format!("{} {}", number_to_words(n / scale), name);
+    match n % scale {
+        0 => head,
+        rest => format!("{} {}", head, number_to_words(rest)),
+    }
+}
+
+fn ordinal_words(n: u64) -> String {
+    let cardinal = number_to_words(n);
+    let split = cardinal.rfind(['-', ' ']).map(|i| i + 1).unwrap_or(0);
+    let (head, last) = cardinal.split_at(split);
+
+    let last = match last {
+        "one" => "first".to_string(),
+        "two" => "second".to_string(),
+        "three" => "third".to_string(),
+        "five" => "fifth".to_string(),
+        "eight" => "eighth".to_string(),
+        "nine" => "ninth".to_string(),
+        "twelve" => "twelfth".to_string(),
+        word if word.ends_with('y') => format!("{}ieth", &word[..word.len() - 1]),
+        word => format!("{}th", word),
+    };
+    format!("{}{}", head, last)
+}
+
+fn year_words(year: u64) -> String {
+    if (2000..=2009).contains(&year) || !(1100..=2099).contains(&year) {
+        return number_to_words(year);
+    }
+
+    let century = number_to_words(year / 100);
+    match year % 100 {
+        0 => format!("{} hundred", century),
+        rest if rest < 10 => format!("{} oh {}", century, ONES[rest as usize]),
+        rest => format!("{} {}", century, number_to_words(rest)),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Whitespace
+// ---------------------------------------------------------------------------
+
+/// Collapse whitespace runs to a single space. Paragraph breaks become a
+/// sentence break so headings and list items don't run into the next line.
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut newlines = 0;
+    let mut in_whitespace = false;
+
+    for c in text.chars() {
+        if c.is_whitespace() {
+            in_whitespace = true;
+            if c == '\n' {
+                newlines += 1;
+            }
+            continue;
+        }
+
+        if in_whitespace && !out.is_empty() {
+            let ends_sentence = out.ends_with(['.', '!', '?', ':', ';', ',']);
+            if newlines >= 2 && !ends_sentence {
+                out.push('.');
+            }
+            out.push(' ');
+        }
+        in_whitespace = false;
+        newlines = 0;
+        out.push(c);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn only(f: impl FnOnce(&mut NormalizeOptions)) -> NormalizeOptions {
+        let mut options = NormalizeOptions {
+            markup: false,
+            urls: false,
+            abbreviations: false,
+            numbers: false,
+            whitespace: false,
+        };
+        f(&mut options);
+        options
+    }
+
+    fn markup(text: &str) -> String {
+        normalize_for_speech(text, &only(|o| o.markup = true))
+    }
+
+    fn numbers(text: &str) -> String {
+        normalize_for_speech(text, &only(|o| o.numbers = true))
+    }
+
+    // Options
+
+    #[test]
+    fn test_all_disabled_is_identity() {
+        let text = "**Bold** at https://github.com/x costs $5, e.g.\n\n  ok";
+        assert_eq!(normalize_for_speech(text, &only(|_| {})), text);
+    }
+
+    #[test]
+    fn test_default_enables_everything() {
+        let text = "## Pricing\n\nSee **[the docs](https://docs.rs/foo)** e.g. it costs $5.";
+        assert_eq!(
+            normalize_for_speech(text, &NormalizeOptions::default()),
+            "Pricing. See the docs for example it costs five dollars."
+        );
+    }
+
+    #[test]
+    fn test_options_deserialize_missing_fields_as_enabled() {
+        let options: NormalizeOptions = serde_json::from_str(r#"{"urls": false}"#).unwrap();
+        assert!(options.markup);
+        assert!(!options.urls);
+        assert!(options.numbers);
+    }
+
+    // Markup
+
+    #[test]
+    fn test_strip_emphasis() {
+        assert_eq!(markup("**bold** and *italic*"), "bold and italic");
+        assert_eq!(markup("__bold__ and _italic_"), "bold and italic");
+        assert_eq!(markup("***both***"), "both");
+        assert_eq!(markup("~~struck~~ out"), "struck out");
+        assert_eq!(markup("(**bold**), then"), "(bold), then");
+    }
+
+    #[test]
+    fn test_emphasis_keeps_non_markers() {
+        assert_eq!(markup("snake_case_name"), "snake_case_name");
+        assert_eq!(markup("2 * 3 = 6"), "2 * 3 = 6");
+        assert_eq!(markup("about ~5 minutes"), "about ~5 minutes");
+    }
+
+    #[test]
+    fn test_strip_inline_code() {
+        assert_eq!(markup("run `cargo test` now"), "run cargo test now");
+    }
+
+    #[test]
+    fn test_strip_code_fences() {
+        assert_eq!(markup("```rust\nlet x = 1;\n```"), "\nlet x = 1;\n");
+    }
+
+    #[test]
+    fn test_strip_headings_quotes_and_bullets() {
+        assert_eq!(markup("# Title"), "Title");
+        assert_eq!(markup("### Sub title"), "Sub title");
+        assert_eq!(markup("> quoted\n>> nested"), "quoted\nnested");
+        assert_eq!(markup("- one\n* two\n+ three"), "one\ntwo\nthree");
+        // Not a heading without the space
+        assert_eq!(markup("#hashtag"), "#hashtag");
+    }
+
+    #[test]
+    fn test_strip_horizontal_rules() {
+        assert_eq!(markup("above\n---\nbelow"), "above\n\nbelow");
+        assert_eq!(markup("above\n* * *\nbelow"), "above\n\nbelow");
+    }
+
+    #[test]
+    fn test_strip_links_and_images() {
+        assert_eq!(
+            markup("see [the guide](https://example.com/guide) here"),
+            "see the guide here"
+        );
+        assert_eq!(markup("![a cat](cat.png)"), "a cat");
+        // Brackets that aren't links stay
+        assert_eq!(markup("array[0] (zero)"), "array[0] (zero)");
+    }
+
+    #[test]
+    fn test_strip_html() {
+        assert_eq!(markup("<b>bold</b> text"), "bold text");
+        assert_eq!(markup("<p>one</p><p>two</p>"), "\none\n\ntwo\n");
+        assert_eq!(markup("line<br/>break"), "line\nbreak");
+        assert_eq!(markup(r#"<a href="https://x.com">link</a>"#), "link");
+        // Comparisons aren't tags
+        assert_eq!(markup("a < b and c > d"), "a < b and c > d");
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(markup("Tom &amp; Jerry"), "Tom & Jerry");
+        assert_eq!(markup("&lt;div&gt;"), "<div>");
+        assert_eq!(markup("a&nbsp;b"), "a b");
+        assert_eq!(markup("&amp;lt;"), "&lt;");
+    }
+
+    // URLs
+
+    #[test]
+    fn test_speak_url() {
+        let options = only(|o| o.urls = true);
+        assert_eq!(
+            normalize_for_speech("see https://github.com/foo/bar?x=1 now", &options),
+            "see link to github dot com now"
+        );
+        assert_eq!(
+            normalize_for_speech("www.example.co.uk/page", &options),
+            "link to example dot co dot uk"
+        );
+        assert_eq!(
+            normalize_for_speech("(http://localhost:8080/path).", &options),
+            "(link to localhost)."
+        );
+    }
+
+    #[test]
+    fn test_non_urls_untouched() {
+        let options = only(|o| o.urls = true);
+        assert_eq!(
+            normalize_for_speech("file.txt and https:// alone", &options),
+            "file.txt and https:// alone"
+        );
+    }
+
+    // Abbreviations
+
+    #[test]
+    fn test_expand_abbreviations() {
+        let options = only(|o| o.abbreviations = true);
+        assert_eq!(
+            normalize_for_speech("fruit, e.g., apples vs. pears", &options),
+            "fruit, for example, apples versus pears"
+        );
+        assert_eq!(
+            normalize_for_speech("Dr. Smith w/ Mr. Jones", &options),
+            "Doctor Smith with Mister Jones"
+        );
+        assert_eq!(
+            normalize_for_speech("salt & pepper, etc.", &options),
+            "salt and pepper, et cetera"
+        );
+    }
+
+    #[test]
+    fn test_abbreviations_need_whole_word() {
+        let options = only(|o| o.abbreviations = true);
+        assert_eq!(
+            normalize_for_speech("drive vsync eggs", &options),
+            "drive vsync eggs"
+        );
+    }
+
+    // Numbers
+
+    #[test]
+    fn test_number_to_words() {
+        assert_eq!(number_to_words(0), "zero");
+        assert_eq!(number_to_words(13), "thirteen");
+        assert_eq!(number_to_words(40), "forty");
+        assert_eq!(number_to_words(42), "forty-two");
+        assert_eq!(number_to_words(100), "one hundred");
+        assert_eq!(number_to_words(305), "three hundred five");
+        assert_eq!(
+            number_to_words(1_234),
+            "one thousand two hundred thirty-four"
+        );
+        assert_eq!(number_to_words(2_000_000), "two million");
+        assert_eq!(number_to_words(7_000_000_019), "seven billion nineteen");
+    }
+
+    #[test]
+    fn test_ordinal_words() {
+        assert_eq!(ordinal_words(1), "first");
+        assert_eq!(ordinal_words(2), "second");
+        assert_eq!(ordinal_words(12), "twelfth");
+        assert_eq!(ordinal_words(20), "twentieth");
+        assert_eq!(ordinal_words(23), "twenty-third");
+        assert_eq!(ordinal_words(100), "one hundredth");
+    }
+
+    #[test]
+    fn test_year_words() {
+        assert_eq!(year_words(1999), "nineteen ninety-nine");
+        assert_eq!(year_words(1900), "nineteen hundred");
+        assert_eq!(year_words(1905), "nineteen oh five");
+        assert_eq!(year_words(2005), "two thousand five");
+        assert_eq!(year_words(2024), "twenty twenty-four");
+    }
+
+    #[test]
+    fn test_speak_currency() {
+        assert_eq!(numbers("$5"), "five dollars");
+        assert_eq!(numbers("$1"), "one dollar");
+        assert_eq!(numbers("$12.50"), "twelve dollars and fifty cents");
+        assert_eq!(numbers("$1.01"), "one dollar and one cent");
+        assert_eq!(numbers("$0.99"), "ninety-nine cents");
+        assert_eq!(numbers("$3.00"), "three dollars");
+        assert_eq!(numbers("$1,250"), "one thousand two hundred fifty dollars");
+        assert_eq!(numbers("£20"), "twenty pounds");
+        assert_eq!(numbers("€2.5M"), "two point five million euros");
+        assert_eq!(numbers("$10k,"), "ten thousand dollars,");
+    }
+
+    #[test]
+    fn test_speak_percent_and_multiplier() {
+        assert_eq!(numbers("50%"), "fifty percent");
+        assert_eq!(numbers("12.5%"), "twelve point five percent");
+        assert_eq!(numbers("3.5x faster"), "three point five times faster");
+        assert_eq!(numbers("2×"), "two times");
+    }
+
+    #[test]
+    fn test_speak_dates() {
+        assert_eq!(numbers("2024-03-15"), "March fifteenth, twenty twenty-four");
+        assert_eq!(
+            numbers("on 7/4/1776."),
+            "on July fourth, seventeen seventy-six."
+        );
+        // First part over 12 means day/month
+        assert_eq!(
+            numbers("25/12/2023"),
+            "December twenty-fifth, twenty twenty-three"
+        );
+        // Invalid dates are left alone
+        assert_eq!(numbers("2024-13-01"), "2024-13-01");
+    }
+
+    #[test]
+    fn test_speak_ordinals_and_years() {
+        assert_eq!(numbers("the 21st century"), "the twenty-first century");
+        assert_eq!(numbers("in 1999,"), "in nineteen ninety-nine,");
+        assert_eq!(numbers("5000 items"), "five thousand items");
+    }
+
+    #[test]
+    fn test_speak_plain_numbers() {
+        assert_eq!(numbers("3.14"), "three point one four");
+        assert_eq!(numbers("-7"), "minus seven");
+        assert_eq!(numbers("(42)"), "(forty-two)");
+        assert_eq!(
+            numbers("1,234,567"),
+            "one million two hundred thirty-four thousand five hundred sixty-seven"
+        );
+    }
+
+    #[test]
+    fn test_numbers_left_alone() {
+        assert_eq!(numbers("v1.2.3"), "v1.2.3");
+        assert_eq!(numbers("1.2.3"), "1.2.3");
+        assert_eq!(numbers("12,34"), "12,34");
+        assert_eq!(numbers("abc123"), "abc123");
+        assert_eq!(numbers("10:30"), "10:30");
+    }
+
+    // Whitespace
+
+    #[test]
+    fn test_collapse_whitespace() {
+        let options = only(|o| o.whitespace = true);
+        assert_eq!(
+            normalize_for_speech("  lots   of\t\tspace  ", &options),
+            "lots of space"
+        );
+        assert_eq!(
+            normalize_for_speech("wrapped\nline", &options),
+            "wrapped line"
+        );
+    }
+
+    #[test]
+    fn test_paragraph_breaks_become_sentences() {
+        let options = only(|o| o.whitespace = true);
+        assert_eq!(
+            normalize_for_speech("Heading\n\nBody text.\n\n\nMore", &options),
+            "Heading. Body text. More"
+        );
+    }
+}
@@ -0,0 +1,121 @@
+//! Paragraph breaks for long dictations.
+//!
+//! Whisper's segments carry timestamps, so a long pause between two
+//! segments is visible as a gap between one's end and the next one's start.
+//! A gap only becomes a paragraph break when the segment before it ends a
+//! sentence, so pausing mid-sentence to think doesn't split it.
+
+use crate::engines::TranscriptSegment;
+
+/// Pause that starts a new paragraph, in seconds
+pub const DEFAULT_PARAGRAPH_PAUSE_SECS: f32 = 1.2;
+
+/// Join segments like `join_segments`, but with a blank line wherever the
+/// gap after a sentence-ending segment is longer than `pause_secs`
+pub fn insert_paragraph_breaks(segments: &[TranscriptSegment], pause_secs: f32) -> String {
+    let pause_ms = (pause_secs * 1000.0).round() as i64;
+    let mut text = String::new();
+    let mut previous: Option<(&str, i64)> = None;
+
+    for segment in segments {
+        let segment_text = segment.text.trim();
+        if segment_text.is_empty() {
+            continue;
+        }
+
+        if let Some((previous_text, previous_end)) = previous {
+            let gap_ms = segment.start_ms - previous_end;
+            if gap_ms > pause_ms && ends_sentence(previous_text) {
+                text.push_str("\n\n");
+            } else {
+                text.push(' ');
+            }
+        }
+        text.push_str(segment_text);
+        previous = Some((segment_text, segment.end_ms));
+    }
+
+    text
+}
+
+/// Whether `text` ends with sentence-final punctuation, allowing for a
+/// closing quote or bracket after it
+fn ends_sentence(text: &str) -> bool {
+    text.trim_end_matches(['"', '\'', ')', ']', '”', '’'])
+        .ends_with(['.', '!', '?', '…'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(text: &str, start_ms: i64, end_ms: i64) -> TranscriptSegment {
+        TranscriptSegment {
+            text: text.to_string(),
+            start_ms,
+            end_ms,
+            confidence: None,
+            words: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_breaks_only_after_long_pauses() {
+        let segments = vec![
+            segment(" First sentence.", 0, 2000),
+            // 1.19 s: below the threshold
+            segment(" Still the first paragraph.", 3190, 5000),
+            // 1.21 s: above it
+            segment(" A new paragraph.", 6210, 8000),
+            // Exactly the threshold doesn't count
+            segment(" Same paragraph.", 9200, 10_000),
+        ];
+
+        assert_eq!(
+            insert_paragraph_breaks(&segments, DEFAULT_PARAGRAPH_PAUSE_SECS),
+            "First sentence. Still the first paragraph.\n\nA new paragraph. Same paragraph."
+        );
+    }
+
+    #[test]
+    fn test_no_break_mid_sentence() {
+        let segments = vec![
+            segment(" I was thinking about", 0, 1500),
+            segment(" what to say next.", 5000, 6500),
+            segment(" Then I said it!", 9000, 10_000),
+        ];
+
+        assert_eq!(
+            insert_paragraph_breaks(&segments, 1.2),
+            "I was thinking about what to say next.\n\nThen I said it!"
+        );
+    }
+
+    #[test]
+    fn test_sentence_end_inside_quotes() {
+        let segments = vec![
+            segment(" She said \"stop.\"", 0, 1000),
+            segment(" Everyone stopped.", 3000, 4000),
+            segment(" (Really?)", 6000, 7000),
+            segment(" Yes.", 9000, 9500),
+        ];
+
+        assert_eq!(
+            insert_paragraph_breaks(&segments, 1.2),
+            "She said \"stop.\"\n\nEveryone stopped.\n\n(Really?)\n\nYes."
+        );
+    }
+
+    #[test]
+    fn test_skips_empty_segments() {
+        let segments = vec![
+            segment(" One.", 0, 1000),
+            segment("  ", 1100, 4000),
+            segment(" Two.", 4100, 5000),
+        ];
+
+        // The gap is measured from the last segment with text
+        assert_eq!(insert_paragraph_breaks(&segments, 1.2), "One.\n\nTwo.");
+        assert_eq!(insert_paragraph_breaks(&[], 1.2), "");
+    }
+}
@@ -0,0 +1,78 @@
+//! Spelling text out one character at a time.
+//!
+//! Holding the read-aloud hotkey on a short selection such as a confirmation
+//! code reads it letter by letter. Each character becomes its own sentence,
+//! so the voice pauses between them, and digits and symbols are written as
+//! words so the engine doesn't read `42` as "forty-two" or skip a dash.
+
+/// Names read for digits, by value
+const DIGIT_NAMES: [&str; 10] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+/// Names read for symbols that commonly appear in codes
+const SYMBOL_NAMES: &[(char, &str)] = &[
+    (' ', "space"),
+    ('-', "dash"),
+    ('_', "underscore"),
+    ('.', "dot"),
+    (',', "comma"),
+    (':', "colon"),
+    (';', "semicolon"),
+    ('@', "at"),
+    ('#', "hash"),
+    ('/', "slash"),
+    ('\\', "backslash"),
+    ('+', "plus"),
+    ('=', "equals"),
+    ('*', "star"),
+    ('&', "and"),
+    ('%', "percent"),
+    ('$', "dollar"),
+    ('!', "exclamation mark"),
+    ('?', "question mark"),
+    ('\'', "apostrophe"),
+    ('"', "quote"),
+    ('(', "open bracket"),
+    (')', "close bracket"),
+];
+
+/// `text` spelled out for speech, e.g. `A4-x` becomes `A. four. dash. X.`.
+/// Letters are read as capitals, which engines say as the letter name, and
+/// surrounding whitespace is dropped.
+pub fn spell_out(text: &str) -> String {
+    let words: Vec<String> = text.trim().chars().filter_map(spoken_char).collect();
+    if words.is_empty() {
+        return String::new();
+    }
+    format!("{}.", words.join(". "))
+}
+
+/// How a single character is read, `None` for control characters
+fn spoken_char(c: char) -> Option<String> {
+    if let Some(digit) = c.to_digit(10) {
+        return Some(DIGIT_NAMES[digit as usize].to_string());
+    }
+    let c = if c.is_whitespace() { ' ' } else { c };
+    if let Some((_, name)) = SYMBOL_NAMES.iter().find(|(symbol, _)| *symbol == c) {
+        return Some(name.to_string());
+    }
+    if c.is_control() {
+        return None;
+    }
+    Some(c.to_uppercase().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spell_out() {
+        assert_eq!(spell_out("A4-x"), "A. four. dash. X.");
+        assert_eq!(spell_out(" 1 2\n"), "one. space. two.");
+        assert_eq!(spell_out("a_b@c.io"), "A. underscore. B. at. C. dot. I. O.");
+        assert_eq!(spell_out("ß€"), "SS. €.");
+        assert_eq!(spell_out("  "), "");
+    }
+}
@@ -1,24 +1,64 @@
-import { useEffect, useState, useRef } from "react";
-import { listen } from "@tauri-apps/api/event";
-
-type OverlayState = "recording" | "transcribing" | "result" | "error";
-
-interface FrontmostAppInfo {
-  name: string;
-  bundle_id: string;
+import { Fragment, useEffect, useState, useRef } from "react";
+import {
+  errorMessage,
+  onEvent,
+  settings,
+  stt,
+  type CurrentTranscript,
+  type WordConfidence,
+} from "../lib/tauri";
+
+// "review": confidence was too low to auto-paste, so the text waits here
+// "paste_failed": the paste didn't go through, so the text waits to be copied
+type OverlayState =
+  | "recording"
+  | "transcribing"
+  | "result"
+  | "pasted"
+  | "review"
+  | "paste_failed"
+  | "error";
+
+// Words below this confidence are underlined in the review overlay
+const UNCERTAIN_WORD_CONFIDENCE = 0.5;
+
+// Message shown by the backend with show_overlay_with_text
+interface OverlayMessage {
+  text: string;
+  level: "info" | "warning" | "error";
 }
 
-interface RecordingStartedPayload {
-  target_app: FrontmostAppInfo | null;
+const MESSAGE_CLASSES: Record<OverlayMessage["level"], string> = {
+  info: "text-sky-300 bg-sky-500/10",
+  warning: "text-amber-300 bg-amber-500/10",
+  error: "text-red-300 bg-red-500/10",
+};
+
+declare global {
+  interface Window {
+    __updateTranscriptText?: (text: string) => void;
+    // Set by the backend before it calls __showOverlayMessage, so a page
+    // that loads afterwards still finds it
+    __overlayMessage?: OverlayMessage | null;
+    __showOverlayMessage?: (message: OverlayMessage | null) => void;
+  }
 }
 
 export default function DictationOverlay() {
   const [state, setState] = useState<OverlayState>("recording");
   const [targetApp, setTargetApp] = useState<string | null>(null);
   const [result, setResult] = useState<string>("");
+  const [words, setWords] = useState<WordConfidence[]>([]);
   const [error, setError] = useState<string>("");
+  // App and character count of the last auto-paste
+  const [pasted, setPasted] = useState<{ app: string | null; chars: number } | null>(null);
+  // The failed dictation's audio was kept, so it can be transcribed again
+  const [retryable, setRetryable] = useState(false);
   const [partialResult, setPartialResult] = useState<string>("");
   const [elapsedTime, setElapsedTime] = useState(0);
+  const [message, setMessage] = useState<OverlayMessage | null>(null);
+  // The microphone stream closed mid-dictation, e.g. on a silence auto-stop
+  const [micClosed, setMicClosed] = useState(false);
   const canvasRef = useRef<HTMLCanvasElement>(null);
   const animationRef = useRef<number | null>(null);
   const levelHistoryRef = useRef<number[]>(new Array(40).fill(0));
@@ -28,18 +68,45 @@ export default function DictationOverlay() {
   useEffect(() => {
     const unlisteners: (() => void)[] = [];
 
+    // Catch up with a dictation whose events arrived before the listeners
+    const applyTranscript = (transcript: CurrentTranscript | null) => {
+      switch (transcript?.status) {
+        case "recording":
+          setState("recording");
+          break;
+        case "transcribing":
+          setState("transcribing");
+          setPartialResult(transcript.text);
+          break;
+        case "done":
+          setState("result");
+          setResult(transcript.text);
+          break;
+      }
+    };
+
     const setup = async () => {
       unlisteners.push(
-        await listen<RecordingStartedPayload>("stt-recording-started", (event) => {
+        await onEvent("dictation-overlay-shown", (payload) => {
+          stt.getCurrentTranscript(payload.session_id).then(applyTranscript).catch(console.error);
+        })
+      );
+
+      unlisteners.push(
+        await onEvent("stt-recording-started", (payload) => {
           setState("recording");
+          setMicClosed(false);
           setResult("");
+          setWords([]);
           setError("");
+          setPasted(null);
+          setRetryable(false);
           setPartialResult("");
           setElapsedTime(0);
           startTimeRef.current = Date.now();
           levelHistoryRef.current = new Array(40).fill(0);
-          if (event.payload?.target_app) {
-            setTargetApp(event.payload.target_app.name);
+          if (payload.target_app) {
+            setTargetApp(payload.target_app.name);
           } else {
             setTargetApp(null);
           }
@@ -47,40 +114,71 @@ export default function DictationOverlay() {
       );
 
       unlisteners.push(
-        await listen("stt-recording-stopped", () => {
+        await onEvent("stt-recording-stopped", () => {
           // Stay in recording state until transcribing starts
         })
       );
 
       unlisteners.push(
-        await listen("stt-transcribing", () => {
+        await onEvent("stt-transcribing", () => {
           setState("transcribing");
+          setRetryable(false);
         })
       );
 
       unlisteners.push(
-        await listen<string>("stt-result", (event) => {
-          setState("result");
-          setResult(event.payload || "");
+        await onEvent("stt-result", (payload) => {
+          setState(payload.needs_review ? "review" : "result");
+          setResult(payload.text || "");
+          setWords(payload.words);
+        })
+      );
+
+      unlisteners.push(
+        await onEvent("stt-pasted", (payload) => {
+          setState("pasted");
+          setPasted({ app: payload.target_app?.name ?? null, chars: payload.chars });
+        })
+      );
+
+      unlisteners.push(
+        await onEvent("stt-paste-failed", (payload) => {
+          setState("paste_failed");
+          setResult(payload.text);
+          setError(payload.error);
         })
       );
 
       unlisteners.push(
-        await listen<string>("stt-error", (event) => {
+        await onEvent("stt-error", (payload) => {
           setState("error");
-          setError(event.payload || "Unknown error");
+          setError(payload || "Unknown error");
         })
       );
 
       unlisteners.push(
-        await listen<string>("stt-partial-result", (event) => {
-          setPartialResult(event.payload || "");
+        await onEvent("stt-retry-available", () => {
+          setRetryable(true);
+        })
+      );
+
+      unlisteners.push(
+        await onEvent("stt-partial-result", (payload) => {
+          setPartialResult(payload || "");
+        })
+      );
+
+      unlisteners.push(
+        await onEvent("microphone-in-use", (inUse) => {
+          if (!inUse) {
+            setMicClosed(true);
+          }
         })
       );
 
       unlisteners.push(
-        await listen<number>("stt-audio-level", (event) => {
-          levelHistoryRef.current.push(event.payload);
+        await onEvent("stt-audio-level", (payload) => {
+          levelHistoryRef.current.push(payload);
           if (levelHistoryRef.current.length > 40) {
             levelHistoryRef.current.shift();
           }
@@ -88,9 +186,32 @@ export default function DictationOverlay() {
       );
     };
 
-    setup();
+    setup().then(() => stt.getCurrentTranscript()).then(applyTranscript).catch(console.error);
+
+    // Catch up with a dictation that started before the overlay loaded
+    settings
+      .getAppState()
+      .then((app) => {
+        if (app.recording?.source === "hotkey") {
+          setState("recording");
+          startTimeRef.current = Date.now() - app.recording.elapsed_secs * 1000;
+          setElapsedTime(Math.floor(app.recording.elapsed_secs));
+        } else if (app.transcribing) {
+          setState("transcribing");
+        }
+      })
+      .catch((err) => console.error("Failed to get app state:", err));
+
+    // Called by the backend via window.eval() as Whisper decodes segments
+    window.__updateTranscriptText = (text: string) => {
+      setPartialResult(text);
+    };
+    window.__showOverlayMessage = setMessage;
+    setMessage(window.__overlayMessage ?? null);
 
     return () => {
+      delete window.__updateTranscriptText;
+      delete window.__showOverlayMessage;
       unlisteners.forEach((unlisten) => unlisten());
       if (animationRef.current) {
         cancelAnimationFrame(animationRef.current);
@@ -100,14 +221,14 @@ export default function DictationOverlay() {
 
   // Elapsed time counter during recording
   useEffect(() => {
-    if (state !== "recording") return;
+    if (state !== "recording" || micClosed) return;
 
     const interval = setInterval(() => {
       setElapsedTime(Math.floor((Date.now() - startTimeRef.current) / 1000));
     }, 1000);
 
     return () => clearInterval(interval);
-  }, [state]);
+  }, [state, micClosed]);
 
   // Canvas waveform animation driven by real audio levels
   useEffect(() => {
@@ -162,6 +283,24 @@ export default function DictationOverlay() {
     };
   }, [state]);
 
+  // Copy the reviewed or unpasted text (or just close) and hide the overlay
+  const handleFinishReview = async (copy: boolean) => {
+    try {
+      await stt.finishReview(copy ? result : null);
+    } catch (err) {
+      console.error("Failed to finish review:", errorMessage(err));
+    }
+  };
+
+  // Transcribe the failed dictation again, e.g. after downloading its model
+  const handleRetry = async () => {
+    try {
+      await stt.retryLastTranscription(null);
+    } catch (err) {
+      console.error("Retry failed:", errorMessage(err));
+    }
+  };
+
   const formatTime = (seconds: number) => {
     const mins = Math.floor(seconds / 60);
     const secs = seconds % 60;
@@ -176,6 +315,11 @@ export default function DictationOverlay() {
         return "bg-amber-500/20 border-amber-500/50";
       case "result":
         return "bg-green-500/20 border-green-500/50";
+      case "pasted":
+        return "bg-green-500/20 border-green-500/50 animate-flash";
+      case "review":
+        return "bg-amber-500/20 border-amber-500/50";
+      case "paste_failed":
       case "error":
         return "bg-red-500/20 border-red-500/50";
     }
@@ -188,7 +332,11 @@ export default function DictationOverlay() {
       case "transcribing":
         return "bg-amber-500";
       case "result":
+      case "pasted":
         return "bg-green-500";
+      case "review":
+        return "bg-amber-500";
+      case "paste_failed":
       case "error":
         return "bg-red-500";
     }
@@ -197,11 +345,17 @@ export default function DictationOverlay() {
   const getStateLabel = () => {
     switch (state) {
       case "recording":
-        return "Recording";
+        return micClosed ? "Microphone off" : "Recording";
       case "transcribing":
         return "Transcribing";
       case "result":
         return "Transcribed";
+      case "pasted":
+        return "Pasted";
+      case "review":
+        return "Check before pasting";
+      case "paste_failed":
+        return "Couldn't paste";
       case "error":
         return "Error";
     }
@@ -221,7 +375,7 @@ export default function DictationOverlay() {
         {/* Top: Status bar */}
         <div className="flex items-center justify-between px-4 py-2 border-b border-white/5">
           <div className="flex items-center gap-2">
-            <div className={`w-2 h-2 rounded-full ${getDotColor()} ${state === "recording" ? "animate-pulse" : ""}`} />
+            <div className={`w-2 h-2 rounded-full ${getDotColor()} ${state === "recording" && !micClosed ? "animate-pulse" : ""}`} />
             <span className="text-xs font-medium text-white/90">
               {getStateLabel()}
             </span>
@@ -240,6 +394,13 @@ export default function DictationOverlay() {
           </div>
         </div>
 
+        {/* Message from the backend, e.g. no microphone access */}
+        {message && (
+          <div className={`px-4 py-1.5 text-xs ${MESSAGE_CLASSES[message.level]}`}>
+            {message.text}
+          </div>
+        )}
+
         {/* Middle: Visualization area */}
         <div className="px-4 py-3 flex items-center justify-center min-h-[64px]">
           {state === "recording" ? (
@@ -265,12 +426,76 @@ export default function DictationOverlay() {
               </svg>
               <span className="text-sm text-green-400 font-medium">Done</span>
             </div>
+          ) : state === "pasted" ? (
+            <div className="flex items-center gap-2">
+              <svg className="w-5 h-5 text-green-400 flex-shrink-0" fill="none" viewBox="0 0 24 24" stroke="currentColor" strokeWidth={2}>
+                <path strokeLinecap="round" strokeLinejoin="round" d="M5 13l4 4L19 7" />
+              </svg>
+              <span className="text-sm text-green-400 font-medium">
+                {pasted?.app ? `Pasted into ${pasted.app}` : "Pasted"}
+              </span>
+              {pasted && (
+                <span className="text-[10px] text-white/40 ml-auto">{pasted.chars} characters</span>
+              )}
+            </div>
+          ) : state === "paste_failed" ? (
+            <div className="flex items-center justify-between w-full gap-2">
+              <span className="text-xs text-red-300 line-clamp-2">{error}</span>
+              <div className="flex items-center gap-1.5 flex-shrink-0">
+                <button
+                  onClick={() => handleFinishReview(true)}
+                  className="text-[10px] px-2 py-0.5 rounded bg-white/10 hover:bg-white/20 text-white/80"
+                >
+                  Copy
+                </button>
+                <button
+                  onClick={() => handleFinishReview(false)}
+                  className="text-[10px] px-2 py-0.5 rounded bg-white/5 hover:bg-white/15 text-white/60"
+                >
+                  Dismiss
+                </button>
+              </div>
+            </div>
+          ) : state === "review" ? (
+            <div className="flex items-center justify-between w-full gap-2">
+              <span className="text-xs text-amber-300">Not sure about this one, so it wasn't pasted</span>
+              <div className="flex items-center gap-1.5 flex-shrink-0">
+                <button
+                  onClick={() => handleFinishReview(true)}
+                  className="text-[10px] px-2 py-0.5 rounded bg-white/10 hover:bg-white/20 text-white/80"
+                >
+                  Copy
+                </button>
+                <button
+                  onClick={() => handleFinishReview(false)}
+                  className="text-[10px] px-2 py-0.5 rounded bg-white/5 hover:bg-white/15 text-white/60"
+                >
+                  Dismiss
+                </button>
+              </div>
+            </div>
           ) : (
             <div className="flex items-center gap-2">
               <svg className="w-5 h-5 text-red-400 flex-shrink-0" fill="none" viewBox="0 0 24 24" stroke="currentColor" strokeWidth={2}>
                 <path strokeLinecap="round" strokeLinejoin="round" d="M12 9v2m0 4h.01m-6.938 4h13.856c1.54 0 2.502-1.667 1.732-2.5L13.732 4.5c-.77-.833-2.694-.833-3.464 0L3.34 16.5c-.77.833.192 2.5 1.732 2.5z" />
               </svg>
               <span className="text-sm text-red-400">Something went wrong</span>
+              {retryable && (
+                <div className="flex items-center gap-1.5 ml-auto flex-shrink-0">
+                  <button
+                    onClick={handleRetry}
+                    className="text-[10px] px-2 py-0.5 rounded bg-white/10 hover:bg-white/20 text-white/80"
+                  >
+                    Retry
+                  </button>
+                  <button
+                    onClick={() => handleFinishReview(false)}
+                    className="text-[10px] px-2 py-0.5 rounded bg-white/5 hover:bg-white/15 text-white/60"
+                  >
+                    Dismiss
+                  </button>
+                </div>
+              )}
             </div>
           )}
         </div>
@@ -278,7 +503,9 @@ export default function DictationOverlay() {
         {/* Bottom: Transcript area */}
         <div className="px-4 py-2.5 border-t border-white/5 min-h-[40px] max-h-[72px]">
           {state === "recording" && (
-            <p className="text-xs text-white/40 italic">Listening...</p>
+            <p className="text-xs text-white/40 italic">
+              {micClosed ? "Stopped on silence, release to transcribe" : "Listening..."}
+            </p>
           )}
           {state === "transcribing" && (
             partialResult ? (
@@ -289,11 +516,32 @@ export default function DictationOverlay() {
               <p className="text-xs text-white/40 italic">Transcribing your speech...</p>
             )
           )}
-          {state === "result" && (
+          {(state === "result" || state === "pasted" || state === "paste_failed") && (
             <p className="text-sm text-white/90 leading-snug line-clamp-3 break-words">
               {result || "No speech detected"}
             </p>
           )}
+          {state === "review" && (
+            <p className="text-sm text-white/90 leading-snug line-clamp-3 break-words">
+              {words.length > 0
+                ? words.map((w, i) => (
+                    <Fragment key={i}>
+                      {i > 0 && " "}
+                      <span
+                        className={
+                          w.confidence < UNCERTAIN_WORD_CONFIDENCE
+                            ? "underline decoration-amber-400 decoration-wavy text-amber-200"
+                            : undefined
+                        }
+                        title={`${Math.round(w.confidence * 100)}% confident`}
+                      >
+                        {w.word}
+                      </span>
+                    </Fragment>
+                  ))
+                : result}
+            </p>
+          )}
           {state === "error" && (
             <p className="text-xs text-red-300 leading-snug line-clamp-3 break-words">
               {error}
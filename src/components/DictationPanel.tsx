@@ -3,7 +3,16 @@ import { useSTT } from "../hooks/useSTT";
 import WaveformViz from "./WaveformViz";
 
 export default function DictationPanel() {
-  const { isRecording, isTranscribing, transcript, error, startRecording, stopRecording } = useSTT();
+  const {
+    isRecording,
+    isTranscribing,
+    progress,
+    transcript,
+    error,
+    qualityWarning,
+    startRecording,
+    stopRecording,
+  } = useSTT();
   const [copied, setCopied] = useState(false);
 
   const handleCopy = async () => {
@@ -42,7 +51,9 @@ export default function DictationPanel() {
           {isRecording
             ? "Recording... Click to stop"
             : isTranscribing
-            ? "Transcribing..."
+            ? progress !== null && progress < 1
+              ? `Transcribing... ${Math.round(progress * 100)}%`
+              : "Transcribing..."
             : "Click to start recording"}
         </p>
       </div>
@@ -70,6 +81,13 @@ export default function DictationPanel() {
         </div>
       )}
 
+      {/* Clipping Warning */}
+      {qualityWarning && !isRecording && (
+        <div className="bg-amber-500/10 border border-amber-500/20 rounded-lg p-4">
+          <p className="text-amber-400 text-sm">{qualityWarning}</p>
+        </div>
+      )}
+
       {/* Error Display */}
       {error && (
         <div className="bg-red-500/10 border border-red-500/20 rounded-lg p-4">
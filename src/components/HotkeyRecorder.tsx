@@ -1,4 +1,5 @@
 import { useState, useEffect, useRef, useCallback } from "react";
+import { onEvent, settings } from "../lib/tauri";
 
 interface HotkeyRecorderProps {
   value: string;
@@ -136,6 +137,23 @@ export default function HotkeyRecorder({ value, onChange }: HotkeyRecorderProps)
       }
     };
 
+    // Combinations pressed while another app is focused are captured by the
+    // backend, which also catches ones the webview never sees
+    const unlisteners: Promise<() => void>[] = [
+      onEvent("shortcut-captured", (payload) => {
+        onChange(payload.shortcut_str);
+        setIsRecording(false);
+        setCurrentModifiers([]);
+      }),
+      onEvent("shortcut-capture-timeout", () => {
+        setIsRecording(false);
+        setCurrentModifiers([]);
+      }),
+    ];
+    const capture = settings.startShortcutCapture().catch((err) => {
+      console.error("Failed to start shortcut capture:", err);
+    });
+
     window.addEventListener("keydown", handleKeyDown, true);
     window.addEventListener("keyup", handleKeyUp, true);
     document.addEventListener("mousedown", handleClickOutside);
@@ -144,6 +162,12 @@ export default function HotkeyRecorder({ value, onChange }: HotkeyRecorderProps)
       window.removeEventListener("keydown", handleKeyDown, true);
       window.removeEventListener("keyup", handleKeyUp, true);
       document.removeEventListener("mousedown", handleClickOutside);
+      unlisteners.forEach((unlisten) => unlisten.then((fn) => fn()));
+      // Escape, a click outside or a combination the webview caught ends
+      // recording here; release the backend's hold on every shortcut too
+      capture.then(() => settings.cancelShortcutCapture()).catch((err) => {
+        console.error("Failed to cancel shortcut capture:", err);
+      });
     };
   }, [isRecording, getModifiersFromEvent, onChange]);
 
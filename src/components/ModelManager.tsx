@@ -1,7 +1,71 @@
+import { useState } from "react";
+import { join } from "@tauri-apps/api/path";
 import { useModels } from "../hooks/useModels";
+import {
+  errorMessage,
+  models as modelCommands,
+  settings as settingsCommands,
+  stt,
+  type EngineStatus,
+} from "../lib/tauri";
 
 export default function ModelManager() {
-  const { models, downloadProgress, downloadModel, deleteModel, isDownloading } = useModels();
+  const {
+    models,
+    engineStatus,
+    downloadProgress,
+    downloadModel,
+    importModel,
+    deleteModel,
+    deleteAllModels,
+    isDownloading,
+    hasActiveDownloads,
+    cancelAllDownloads,
+  } = useModels();
+
+  const modelName = (id: string) => models.find((m) => m.id === id)?.name ?? id;
+
+  // Ask before also downloading required models that are missing
+  const downloadWithConsent = (model: Model) => {
+    const missing = model.requires.filter(
+      (id) => models.find((m) => m.id === id)?.status !== "downloaded"
+    );
+    const includeDependencies =
+      missing.length > 0 &&
+      window.confirm(
+        `${model.name} also needs ${missing.map(modelName).join(", ")}. Download ${missing.length === 1 ? "it" : "them"} too?`
+      );
+    downloadModel(model.id, includeDependencies);
+  };
+
+  // Warn before deleting something a downloaded model needs
+  const deleteWithConsent = (model: Model) => {
+    const dependents = models.filter(
+      (m) => m.status === "downloaded" && m.requires.includes(model.id)
+    );
+    if (
+      dependents.length === 0 ||
+      window.confirm(
+        `${dependents.map((m) => m.name).join(", ")} won't work without ${model.name}. Delete it anyway?`
+      )
+    ) {
+      deleteModel(model.id);
+    }
+  };
+
+  const downloadedCount = models.filter((m) => m.status === "downloaded").length;
+  const [deleteAllError, setDeleteAllError] = useState<string | null>(null);
+
+  const deleteAllWithConsent = async () => {
+    if (
+      !window.confirm(
+        `Delete all ${downloadedCount} downloaded models? Dictation and speech won't work until you download them again.`
+      )
+    ) {
+      return;
+    }
+    setDeleteAllError(await deleteAllModels());
+  };
 
   // Separate CoreML acceleration models from regular models
   const sttModels = models.filter((m) => m.model_type === "stt" && !m.id.includes(".mlmodelc"));
@@ -10,6 +74,28 @@ export default function ModelManager() {
 
   return (
     <div className="space-y-6">
+      {hasActiveDownloads && (
+        <div className="flex justify-end">
+          <button
+            onClick={cancelAllDownloads}
+            className="px-3 py-1.5 text-sm bg-slate-700 hover:bg-slate-600 text-slate-200 rounded transition-colors"
+          >
+            Cancel all downloads
+          </button>
+        </div>
+      )}
+      {!hasActiveDownloads && downloadedCount > 0 && (
+        <div className="flex items-center justify-end gap-3">
+          {deleteAllError && <p className="text-xs text-red-400">{deleteAllError}</p>}
+          <button
+            onClick={deleteAllWithConsent}
+            className="px-3 py-1.5 text-sm bg-red-600/20 hover:bg-red-600/30 text-red-400 rounded transition-colors"
+          >
+            Delete all models
+          </button>
+        </div>
+      )}
+
       {/* STT Models */}
       <section>
         <h2 className="text-lg font-semibold text-slate-200 mb-3">Speech-to-Text Models</h2>
@@ -18,9 +104,11 @@ export default function ModelManager() {
             <ModelCard
               key={model.id}
               model={model}
+              engine={engineStatus[model.id]}
               progress={downloadProgress[model.id]}
-              onDownload={() => downloadModel(model.id)}
-              onDelete={() => deleteModel(model.id)}
+              onDownload={() => downloadWithConsent(model)}
+              onDelete={() => deleteWithConsent(model)}
+              onValidate={() => validateSttModel(model.id)}
               isDownloading={isDownloading(model.id)}
             />
           ))}
@@ -41,8 +129,8 @@ export default function ModelManager() {
                 key={model.id}
                 model={model}
                 progress={downloadProgress[model.id]}
-                onDownload={() => downloadModel(model.id)}
-                onDelete={() => deleteModel(model.id)}
+                onDownload={() => downloadWithConsent(model)}
+                onDelete={() => deleteWithConsent(model)}
                 isDownloading={isDownloading(model.id)}
                 isCoreML={true}
               />
@@ -59,26 +147,116 @@ export default function ModelManager() {
             <ModelCard
               key={model.id}
               model={model}
+              engine={engineStatus[model.id]}
               progress={downloadProgress[model.id]}
-              onDownload={() => downloadModel(model.id)}
-              onDelete={() => deleteModel(model.id)}
+              onDownload={() => downloadWithConsent(model)}
+              onDelete={() => deleteWithConsent(model)}
               isDownloading={isDownloading(model.id)}
             />
           ))}
         </div>
       </section>
 
+      <ImportModelForm
+        onImport={importModel}
+        progress={downloadProgress}
+        isDownloading={isDownloading}
+      />
+
       {/* Storage Info */}
       <div className="bg-slate-800/50 rounded-lg p-4">
         <h3 className="text-sm font-medium text-slate-300 mb-2">Storage</h3>
         <p className="text-xs text-slate-400">
           Models are stored in ~/Library/Application Support/com.blahcubed.app/models/
         </p>
+        <button
+          onClick={() => settingsCommands.openModelsDirectory().catch(console.error)}
+          className="text-xs text-sky-400 hover:text-sky-300 mt-2"
+        >
+          Open models folder
+        </button>
       </div>
     </div>
   );
 }
 
+interface ImportModelFormProps {
+  onImport: (
+    url: string,
+    modelId: string,
+    modelType: "stt" | "tts",
+    name: string
+  ) => Promise<string | null>;
+  progress: Record<string, number>;
+  isDownloading: (modelId: string) => boolean;
+}
+
+// Download a model that isn't listed, e.g. from a private HuggingFace repo
+function ImportModelForm({ onImport, progress, isDownloading }: ImportModelFormProps) {
+  const [url, setUrl] = useState("");
+  const [name, setName] = useState("");
+  const [modelType, setModelType] = useState<"stt" | "tts">("stt");
+  const [error, setError] = useState<string | null>(null);
+
+  // The file name at the end of the URL becomes the model id
+  const modelId = url.split("?")[0].split("/").pop() ?? "";
+  const importing = modelId !== "" && isDownloading(modelId);
+
+  const handleImport = async () => {
+    setError(null);
+    const err = await onImport(url.trim(), modelId, modelType, name.trim() || modelId);
+    if (err) {
+      setError(err);
+    } else {
+      setUrl("");
+      setName("");
+    }
+  };
+
+  return (
+    <section>
+      <h2 className="text-lg font-semibold text-slate-200 mb-1">Import from URL</h2>
+      <p className="text-xs text-slate-400 mb-3">
+        Download a model from any https:// link, up to 10 GB.
+      </p>
+      <div className="bg-slate-800 rounded-lg p-4 space-y-2">
+        <input
+          type="text"
+          value={url}
+          onChange={(e) => setUrl(e.target.value)}
+          placeholder="https://huggingface.co/.../resolve/main/ggml-model.bin"
+          className="w-full px-3 py-1.5 text-sm bg-slate-700 text-slate-100 rounded border border-slate-600 focus:outline-none focus:border-sky-500"
+        />
+        <div className="flex space-x-2">
+          <input
+            type="text"
+            value={name}
+            onChange={(e) => setName(e.target.value)}
+            placeholder="Display name"
+            className="flex-1 px-3 py-1.5 text-sm bg-slate-700 text-slate-100 rounded border border-slate-600 focus:outline-none focus:border-sky-500"
+          />
+          <select
+            value={modelType}
+            onChange={(e) => setModelType(e.target.value as "stt" | "tts")}
+            className="px-2 py-1.5 text-sm bg-slate-700 text-slate-100 rounded border border-slate-600"
+          >
+            <option value="stt">Speech-to-Text</option>
+            <option value="tts">Text-to-Speech</option>
+          </select>
+          <button
+            onClick={handleImport}
+            disabled={!url.trim().startsWith("https://") || !modelId || importing}
+            className="px-3 py-1.5 text-sm bg-sky-500 text-white rounded hover:bg-sky-600 transition-colors disabled:opacity-50"
+          >
+            {importing ? `${progress[modelId] || 0}%` : "Import"}
+          </button>
+        </div>
+        {error && <p className="text-xs text-red-400">{error}</p>}
+      </div>
+    </section>
+  );
+}
+
 interface Model {
   id: string;
   name: string;
@@ -86,19 +264,62 @@ interface Model {
   size_display: string;
   description: string;
   status: string;
+  requires: string[];
+  dependencies_met: boolean;
+  hardware_compatible: boolean;
+}
+
+function formatMegabytes(bytes: number): string {
+  return `${Math.round(bytes / 1_000_000)} MB`;
+}
+
+// Check a downloaded Whisper model, returning a message for the card
+async function validateSttModel(modelId: string): Promise<string> {
+  try {
+    const modelPath = await join(await modelCommands.getPath(), "stt", modelId);
+    const result = await stt.validateModelFile(modelPath);
+    if (!result.is_valid) {
+      return `Invalid: ${result.error}`;
+    }
+    return result.model_type ? `OK (${result.model_type})` : "OK";
+  } catch (err) {
+    return `Check failed: ${errorMessage(err)}`;
+  }
 }
 
 interface ModelCardProps {
   model: Model;
+  // Set once the model has been loaded this session
+  engine?: EngineStatus;
   progress?: number;
   onDownload: () => void;
   onDelete: () => void;
+  // Check the downloaded file, resolving to a short status message
+  onValidate?: () => Promise<string>;
   isDownloading: boolean;
   isCoreML?: boolean;
 }
 
-function ModelCard({ model, progress, onDownload, onDelete, isDownloading, isCoreML }: ModelCardProps) {
+function ModelCard({
+  model,
+  engine,
+  progress,
+  onDownload,
+  onDelete,
+  onValidate,
+  isDownloading,
+  isCoreML,
+}: ModelCardProps) {
   const isDownloaded = model.status === "downloaded";
+  const [validation, setValidation] = useState<string | null>(null);
+  const [validating, setValidating] = useState(false);
+
+  const handleValidate = async () => {
+    if (!onValidate) return;
+    setValidating(true);
+    setValidation(await onValidate());
+    setValidating(false);
+  };
 
   return (
     <div className={`rounded-lg p-4 ${isCoreML ? "bg-purple-900/20 border border-purple-800/30" : "bg-slate-800"}`}>
@@ -116,9 +337,42 @@ function ModelCard({ model, progress, onDownload, onDelete, isDownloading, isCor
                 Downloaded
               </span>
             )}
+            {engine?.loaded_in_memory && (
+              <span
+                className="px-2 py-0.5 text-xs bg-sky-500/20 text-sky-400 rounded-full"
+                title={`Using about ${formatMegabytes(engine.approx_memory_bytes)} of memory`}
+              >
+                In memory
+              </span>
+            )}
+            {!model.hardware_compatible && (
+              <span
+                className="text-amber-400"
+                title="This model needs more memory than this computer can spare and may be slow or fail to load"
+              >
+                ⚠️
+              </span>
+            )}
           </div>
           <p className="text-sm text-slate-400 mt-1">{model.description}</p>
-          <p className="text-xs text-slate-500 mt-1">{model.size_display}</p>
+          <p className="text-xs text-slate-500 mt-1">
+            {model.size_display}
+            {engine?.last_used && ` · Last used ${new Date(engine.last_used).toLocaleTimeString()}`}
+          </p>
+          {isDownloaded && !model.dependencies_met && (
+            <p className="text-xs text-amber-400 mt-1">
+              Needs {model.requires.join(", ")} to work
+            </p>
+          )}
+          {validation && (
+            <p
+              className={`text-xs mt-1 ${
+                validation.startsWith("OK") ? "text-green-400" : "text-red-400"
+              }`}
+            >
+              {validation}
+            </p>
+          )}
         </div>
 
         <div className="ml-4">
@@ -133,12 +387,23 @@ function ModelCard({ model, progress, onDownload, onDelete, isDownloading, isCor
               <p className="text-xs text-slate-400 text-center mt-1">{progress || 0}%</p>
             </div>
           ) : isDownloaded ? (
-            <button
-              onClick={onDelete}
-              className="px-3 py-1.5 text-sm bg-red-500/10 text-red-400 rounded hover:bg-red-500/20 transition-colors"
-            >
-              Delete
-            </button>
+            <div className="flex space-x-2">
+              {onValidate && (
+                <button
+                  onClick={handleValidate}
+                  disabled={validating}
+                  className="px-3 py-1.5 text-sm bg-slate-700 text-slate-200 rounded hover:bg-slate-600 transition-colors disabled:opacity-50"
+                >
+                  {validating ? "Checking..." : "Check"}
+                </button>
+              )}
+              <button
+                onClick={onDelete}
+                className="px-3 py-1.5 text-sm bg-red-500/10 text-red-400 rounded hover:bg-red-500/20 transition-colors"
+              >
+                Delete
+              </button>
+            </div>
           ) : (
             <button
               onClick={onDownload}
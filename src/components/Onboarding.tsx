@@ -1,10 +1,25 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import { listen } from "@tauri-apps/api/event";
 import { open } from "@tauri-apps/plugin-shell";
 import { usePermissions } from "../hooks/usePermissions";
-
-type Step = "welcome" | "permissions" | "models" | "hotkeys" | "complete";
+import {
+  errorMessage,
+  onEvent,
+  settings,
+  stt,
+  tts,
+  type InputLevel,
+  type OnboardingStep,
+} from "../lib/tauri";
+
+const STEPS: OnboardingStep[] = [
+  "welcome",
+  "permissions",
+  "model_download",
+  "hotkey_setup",
+  "voice_test",
+  "complete",
+];
 
 interface OnboardingProps {
   onComplete: () => void;
@@ -19,26 +34,31 @@ interface Model {
 }
 
 export default function Onboarding({ onComplete }: OnboardingProps) {
-  const [step, setStep] = useState<Step>("welcome");
+  const [step, setStep] = useState<OnboardingStep>("welcome");
+  // Furthest step saved; steps before it were gone back to and aren't saved again
+  const [reached, setReached] = useState<OnboardingStep>("welcome");
   const [models, setModels] = useState<Model[]>([]);
   const [downloadProgress, setDownloadProgress] = useState<Record<string, number>>({});
   const [downloading, setDownloading] = useState<Set<string>>(new Set());
   const [modelsReady, setModelsReady] = useState(false);
 
-  // Load models on mount
+  // Load models and resume where setup was left on mount
   useEffect(() => {
     loadModels();
-
-    const unlisten = listen<[string, { percentage: number }]>(
-      "model-download-progress",
-      (event) => {
-        const [modelId, progress] = event.payload;
-        setDownloadProgress((prev) => ({
-          ...prev,
-          [modelId]: progress.percentage,
-        }));
-      }
-    );
+    settings
+      .getOnboardingStep()
+      .then((saved) => {
+        setStep(saved);
+        setReached(saved);
+      })
+      .catch((err) => console.error("Failed to load onboarding step:", err));
+
+    const unlisten = onEvent("model-download-progress", ({ model_id, progress }) => {
+      setDownloadProgress((prev) => ({
+        ...prev,
+        [model_id]: progress.percentage,
+      }));
+    });
 
     return () => {
       unlisten.then((fn) => fn());
@@ -92,17 +112,16 @@ export default function Onboarding({ onComplete }: OnboardingProps) {
     await downloadModel("voices-v1.0.bin");
   };
 
-  const handleComplete = async () => {
+  // Reaching the last step already marked onboarding as done
+  const handleComplete = () => onComplete();
+
+  const handleSkip = async () => {
     try {
-      const settings = await invoke<Record<string, unknown>>("get_settings");
-      await invoke("update_settings", {
-        settings: { ...settings, onboarding_completed: true },
-      });
-      onComplete();
+      await settings.skipOnboarding();
     } catch (err) {
       console.error("Failed to save onboarding status:", err);
-      onComplete();
     }
+    onComplete();
   };
 
   const openSystemPreferences = async (pane: string) => {
@@ -128,19 +147,29 @@ export default function Onboarding({ onComplete }: OnboardingProps) {
     }
   };
 
-  const nextStep = () => {
-    const steps: Step[] = ["welcome", "permissions", "models", "hotkeys", "complete"];
-    const currentIndex = steps.indexOf(step);
-    if (currentIndex < steps.length - 1) {
-      setStep(steps[currentIndex + 1]);
+  const nextStep = async () => {
+    const currentIndex = STEPS.indexOf(step);
+    if (currentIndex >= STEPS.length - 1) {
+      return;
+    }
+    if (step !== reached) {
+      setStep(STEPS[currentIndex + 1]);
+      return;
+    }
+    try {
+      const next = await settings.advanceOnboarding();
+      setReached(next);
+      setStep(next);
+    } catch (err) {
+      console.error("Failed to save onboarding step:", err);
+      setStep(STEPS[currentIndex + 1]);
     }
   };
 
   const prevStep = () => {
-    const steps: Step[] = ["welcome", "permissions", "models", "hotkeys", "complete"];
-    const currentIndex = steps.indexOf(step);
+    const currentIndex = STEPS.indexOf(step);
     if (currentIndex > 0) {
-      setStep(steps[currentIndex - 1]);
+      setStep(STEPS[currentIndex - 1]);
     }
   };
 
@@ -149,11 +178,11 @@ export default function Onboarding({ onComplete }: OnboardingProps) {
       <div className="w-full max-w-lg">
         {/* Progress dots */}
         <div className="flex justify-center gap-2 mb-8">
-          {["welcome", "permissions", "models", "hotkeys", "complete"].map((s, i) => (
+          {STEPS.map((s, i) => (
             <div
               key={s}
               className={`w-2 h-2 rounded-full transition-colors ${
-                s === step ? "bg-sky-500" : i < ["welcome", "permissions", "models", "hotkeys", "complete"].indexOf(step) ? "bg-sky-500/50" : "bg-slate-700"
+                s === step ? "bg-sky-500" : i < STEPS.indexOf(step) ? "bg-sky-500/50" : "bg-slate-700"
               }`}
             />
           ))}
@@ -162,7 +191,7 @@ export default function Onboarding({ onComplete }: OnboardingProps) {
         {/* Step content */}
         <div className="bg-slate-800 rounded-2xl p-8 shadow-2xl">
           {step === "welcome" && (
-            <WelcomeStep onNext={nextStep} />
+            <WelcomeStep onNext={nextStep} onSkip={handleSkip} />
           )}
           {step === "permissions" && (
             <PermissionsStep
@@ -171,7 +200,7 @@ export default function Onboarding({ onComplete }: OnboardingProps) {
               openSystemPreferences={openSystemPreferences}
             />
           )}
-          {step === "models" && (
+          {step === "model_download" && (
             <ModelsStep
               models={models}
               downloadProgress={downloadProgress}
@@ -182,9 +211,12 @@ export default function Onboarding({ onComplete }: OnboardingProps) {
               onBack={prevStep}
             />
           )}
-          {step === "hotkeys" && (
+          {step === "hotkey_setup" && (
             <HotkeysStep onNext={nextStep} onBack={prevStep} />
           )}
+          {step === "voice_test" && (
+            <VoiceTestStep onNext={nextStep} onBack={prevStep} />
+          )}
           {step === "complete" && (
             <CompleteStep onComplete={handleComplete} onBack={prevStep} />
           )}
@@ -194,7 +226,7 @@ export default function Onboarding({ onComplete }: OnboardingProps) {
   );
 }
 
-function WelcomeStep({ onNext }: { onNext: () => void }) {
+function WelcomeStep({ onNext, onSkip }: { onNext: () => void; onSkip: () => void }) {
   return (
     <div className="text-center">
       <div className="w-20 h-20 mx-auto mb-6 bg-gradient-to-br from-sky-400 to-indigo-500 rounded-2xl flex items-center justify-center">
@@ -218,6 +250,12 @@ function WelcomeStep({ onNext }: { onNext: () => void }) {
       >
         Get Started
       </button>
+      <button
+        onClick={onSkip}
+        className="mt-3 text-xs text-slate-500 hover:text-slate-300"
+      >
+        Skip setup
+      </button>
     </div>
   );
 }
@@ -271,6 +309,8 @@ function PermissionsStep({
         />
       </div>
 
+      <MicLevelTest />
+
       <p className="text-xs text-slate-500 mb-6 text-center">
         After granting permissions, you may need to restart Blah³ for changes to take effect.
       </p>
@@ -293,6 +333,58 @@ function PermissionsStep({
   );
 }
 
+// Three-second microphone check: quiet means no access or a muted input,
+// a peak near 0 dB means the input gain is high enough to clip
+function MicLevelTest() {
+  const [testing, setTesting] = useState(false);
+  const [level, setLevel] = useState<InputLevel | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  const runTest = async () => {
+    setTesting(true);
+    setError(null);
+    try {
+      setLevel(await stt.testInputLevel());
+    } catch (err) {
+      setError(errorMessage(err));
+    } finally {
+      setTesting(false);
+    }
+  };
+
+  const verdict = !level
+    ? null
+    : level.peak_db < -50
+      ? { text: "We can't hear you. Check microphone access and input volume.", color: "text-red-400" }
+      : level.peak_db > -3
+        ? { text: "Very loud: lower the input volume to avoid distortion.", color: "text-amber-400" }
+        : { text: "Sounds good!", color: "text-green-400" };
+
+  return (
+    <div className="bg-slate-700/50 rounded-lg p-4 mb-6">
+      <div className="flex items-center justify-between gap-3">
+        <div>
+          <h3 className="font-medium text-white">Test your microphone</h3>
+          <p className="text-xs text-slate-400">Speak normally for 3 seconds</p>
+        </div>
+        <button
+          onClick={runTest}
+          disabled={testing}
+          className="px-3 py-1.5 text-sm bg-slate-600 hover:bg-slate-500 disabled:opacity-50 text-white rounded transition-colors"
+        >
+          {testing ? "Listening..." : "Test"}
+        </button>
+      </div>
+      {level && verdict && (
+        <p className={`text-xs mt-2 ${verdict.color}`}>
+          {verdict.text} (average {level.avg_db.toFixed(0)} dB, peak {level.peak_db.toFixed(0)} dB)
+        </p>
+      )}
+      {error && <p className="text-xs mt-2 text-red-400">{error}</p>}
+    </div>
+  );
+}
+
 function PermissionCard({
   icon,
   title,
@@ -561,6 +653,61 @@ function HotkeyCard({
   );
 }
 
+function VoiceTestStep({ onNext, onBack }: { onNext: () => void; onBack: () => void }) {
+  const [playing, setPlaying] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const playSample = async () => {
+    setPlaying(true);
+    setError(null);
+    try {
+      const { tts_voice } = await settings.get();
+      await tts.preview(tts_voice);
+    } catch (err) {
+      setError(errorMessage(err));
+    } finally {
+      setPlaying(false);
+    }
+  };
+
+  return (
+    <div>
+      <h2 className="text-xl font-bold text-white mb-2">Test the Voice</h2>
+      <p className="text-slate-400 text-sm mb-6">
+        Play a sample to check your speakers and hear the voice used for reading aloud.
+      </p>
+
+      <button
+        onClick={playSample}
+        disabled={playing}
+        className="w-full py-3 mb-2 bg-slate-700 hover:bg-slate-600 disabled:opacity-50 text-white font-medium rounded-lg transition-colors"
+      >
+        {playing ? "Loading voice..." : "▶ Play sample"}
+      </button>
+      {error && <p className="text-xs text-red-400 mb-2">{error}</p>}
+
+      <p className="text-xs text-slate-500 mb-6 text-center">
+        More voices are available in Settings.
+      </p>
+
+      <div className="flex gap-3">
+        <button
+          onClick={onBack}
+          className="flex-1 py-3 bg-slate-700 hover:bg-slate-600 text-white font-medium rounded-lg transition-colors"
+        >
+          Back
+        </button>
+        <button
+          onClick={onNext}
+          className="flex-1 py-3 bg-sky-500 hover:bg-sky-600 text-white font-medium rounded-lg transition-colors"
+        >
+          Continue
+        </button>
+      </div>
+    </div>
+  );
+}
+
 function CompleteStep({ onComplete, onBack }: { onComplete: () => void; onBack: () => void }) {
   return (
     <div className="text-center">
@@ -1,10 +1,98 @@
-import { useState } from "react";
+import { useEffect, useState } from "react";
+import { open } from "@tauri-apps/plugin-shell";
 import { useTTS } from "../hooks/useTTS";
+import {
+  accessibility,
+  errorMessage,
+  isBlahError,
+  tts,
+  type BlahError,
+  type EstimatedDuration,
+} from "../lib/tauri";
 import VoicePreview from "./VoicePreview";
 
 export default function ScreenReader() {
-  const { isSpeaking, voices, selectedVoice, speed, speak, stop, setSelectedVoice, setSpeed } = useTTS();
+  const {
+    isSpeaking,
+    voices,
+    selectedVoice,
+    engine,
+    speed,
+    speedOverrides,
+    progress,
+    synthesisPercent,
+    speak,
+    stop,
+    setSelectedVoice,
+    setEngine,
+    setSpeed,
+    saveBlend,
+    deleteCustomVoice,
+    setVoiceSpeedOverride,
+  } = useTTS();
   const [text, setText] = useState("");
+  const [blendName, setBlendName] = useState("");
+  const [blendSpec, setBlendSpec] = useState("");
+  const [blendError, setBlendError] = useState<string | null>(null);
+  const [languageFilter, setLanguageFilter] = useState("");
+  const [estimate, setEstimate] = useState<EstimatedDuration | null>(null);
+  const [selectionError, setSelectionError] = useState<BlahError | string | null>(null);
+
+  // Re-estimate once typing pauses
+  useEffect(() => {
+    if (!text.trim()) {
+      setEstimate(null);
+      return;
+    }
+    const timer = setTimeout(() => {
+      tts.estimateReadingTime(text, speed).then(setEstimate).catch(console.error);
+    }, 300);
+    return () => clearTimeout(timer);
+  }, [text, speed]);
+
+  const selectedCustomVoice = voices.find((v) => v.id === selectedVoice && v.custom);
+  const voiceSpeed = speedOverrides[selectedVoice];
+  const engineVoices = voices.filter((v) => v.engine === engine);
+  const languages = [...new Set(engineVoices.map((v) => v.language))];
+  const shownVoices = engineVoices.filter((v) => !languageFilter || v.language === languageFilter);
+  // Voices arrive sorted by language, so grouping keeps that order
+  const voiceGroups = shownVoices.reduce<Map<string, typeof shownVoices>>((groups, voice) => {
+    groups.set(voice.language, [...(groups.get(voice.language) ?? []), voice]);
+    return groups;
+  }, new Map());
+  const selectedLanguage = voices.find((v) => v.id === selectedVoice)?.language;
+
+  const handleSaveBlend = async () => {
+    try {
+      await saveBlend(blendName, blendSpec);
+      setBlendName("");
+      setBlendSpec("");
+      setBlendError(null);
+    } catch (err) {
+      setBlendError(errorMessage(err));
+    }
+  };
+
+  // Clicking here put this window in front, so read from the app before it
+  const handleUseSelection = async () => {
+    try {
+      const selection = await accessibility.getSelection(true);
+      setSelectionError(selection ? null : "Nothing is selected in the other app.");
+      if (selection) {
+        setText(selection);
+      }
+    } catch (err) {
+      setSelectionError(isBlahError(err) ? err : String(err));
+    }
+  };
+
+  const openAccessibilitySettings = async () => {
+    try {
+      await open("x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility");
+    } catch {
+      await open("x-apple.systempreferences:com.apple.settings.PrivacySecurity.extension-point?Accessibility");
+    }
+  };
 
   const handleSpeak = () => {
     if (text.trim()) {
@@ -16,13 +104,36 @@ export default function ScreenReader() {
     <div className="space-y-6">
       {/* Text Input */}
       <div className="space-y-2">
-        <label className="block text-sm font-medium text-slate-300">Text to speak</label>
+        <div className="flex items-center justify-between">
+          <label className="block text-sm font-medium text-slate-300">Text to speak</label>
+          <button
+            onClick={handleUseSelection}
+            className="text-xs text-sky-400 hover:text-sky-300 transition-colors"
+          >
+            Use selection
+          </button>
+        </div>
         <textarea
           value={text}
           onChange={(e) => setText(e.target.value)}
           placeholder="Enter text to read aloud, or select text anywhere and press ⌘+⇧+S"
           className="w-full h-32 px-3 py-2 bg-slate-800 border border-slate-700 rounded-lg text-slate-100 placeholder-slate-500 focus:outline-none focus:ring-2 focus:ring-sky-500 resize-none"
         />
+        {selectionError && (
+          <p className="text-xs text-amber-400">
+            {errorMessage(selectionError)}
+            {typeof selectionError !== "string" && selectionError.code === "accessibility_denied" && (
+              <button onClick={openAccessibilitySettings} className="ml-2 text-sky-400 hover:text-sky-300">
+                Open Settings
+              </button>
+            )}
+          </p>
+        )}
+        {estimate && estimate.word_count > 0 && (
+          <p className="text-xs text-slate-500 text-right">
+            {estimate.word_count} words · about {formatSeconds(estimate.estimated_secs)}
+          </p>
+        )}
       </div>
 
       {/* Controls */}
@@ -50,21 +161,130 @@ export default function ScreenReader() {
         </button>
       </div>
 
+      {synthesisPercent !== null && !progress && (
+        <p className="text-xs text-slate-500 text-right">Preparing speech… {synthesisPercent}%</p>
+      )}
+
+      {progress && (
+        <div className="space-y-1">
+          <div className="h-1.5 bg-slate-800 rounded-full overflow-hidden">
+            <div
+              className="h-full bg-sky-500 transition-all"
+              style={{ width: `${progress.percentage}%` }}
+            />
+          </div>
+          <p className="text-xs text-slate-500 text-right">
+            {formatSeconds(progress.elapsed)} / {formatSeconds(progress.total)}
+          </p>
+        </div>
+      )}
+
       {/* Voice Selection */}
       <div className="space-y-2">
-        <label className="block text-sm font-medium text-slate-300">Voice</label>
-        <div className="grid grid-cols-2 gap-2">
-          {voices.map((voice) => (
-            <VoicePreview
-              key={voice.id}
-              voice={voice}
-              isSelected={selectedVoice === voice.id}
-              onSelect={() => setSelectedVoice(voice.id)}
-            />
-          ))}
+        <div className="flex items-center justify-between">
+          <label className="block text-sm font-medium text-slate-300">Voice</label>
+          <div className="flex text-xs bg-slate-800 rounded-lg p-0.5">
+            {(["kokoro", "system"] as const).map((e) => (
+              <button
+                key={e}
+                onClick={() => setEngine(e)}
+                className={`px-2 py-1 rounded-md ${
+                  engine === e ? "bg-sky-500 text-white" : "text-slate-400 hover:text-slate-200"
+                }`}
+              >
+                {e === "kokoro" ? "Kokoro" : "System"}
+              </button>
+            ))}
+          </div>
+        </div>
+        {engine === "system" && engineVoices.length === 0 && (
+          <p className="text-xs text-slate-500">No system voices found.</p>
+        )}
+        {languages.length > 1 && (
+          <select
+            value={languageFilter}
+            onChange={(e) => setLanguageFilter(e.target.value)}
+            className="px-2 py-1 bg-slate-800 border border-slate-700 rounded text-sm text-slate-100 focus:outline-none focus:ring-2 focus:ring-sky-500"
+          >
+            <option value="">All languages</option>
+            {languages.map((language) => (
+              <option key={language} value={language}>
+                {languageName(language)}
+              </option>
+            ))}
+          </select>
+        )}
+        {[...voiceGroups].map(([language, group]) => (
+          <div key={language} className="space-y-1">
+            {voiceGroups.size > 1 && (
+              <p className="text-xs font-medium text-slate-500">{languageName(language)}</p>
+            )}
+            <div className="grid grid-cols-2 gap-2">
+              {group.map((voice) => (
+                <VoicePreview
+                  key={voice.id}
+                  voice={voice}
+                  isSelected={selectedVoice === voice.id}
+                  onSelect={() => setSelectedVoice(voice.id)}
+                />
+              ))}
+            </div>
+          </div>
+        ))}
+        {selectedLanguage && !selectedLanguage.startsWith("en") && (
+          <p className="text-xs text-amber-400">
+            This is a {languageName(selectedLanguage)} voice; English text will be read with off
+            pronunciation.
+          </p>
+        )}
+        <div className="flex space-x-3">
+          <button
+            onClick={() => tts.preview(selectedVoice).catch(console.error)}
+            className="text-xs text-sky-400 hover:text-sky-300"
+          >
+            Preview voice
+          </button>
+          {selectedCustomVoice && (
+            <button
+              onClick={() => deleteCustomVoice(selectedCustomVoice.id)}
+              className="text-xs text-red-400 hover:text-red-300"
+            >
+              Delete "{selectedCustomVoice.name}"
+            </button>
+          )}
         </div>
       </div>
 
+      {/* Voice Blends (Kokoro only) */}
+      {engine === "kokoro" && (
+        <div className="space-y-2">
+          <label className="block text-sm font-medium text-slate-300">New voice blend</label>
+          <div className="flex space-x-2">
+            <input
+              value={blendName}
+              onChange={(e) => setBlendName(e.target.value)}
+              placeholder="Name"
+              className="w-1/3 px-3 py-2 bg-slate-800 border border-slate-700 rounded-lg text-sm text-slate-100 placeholder-slate-500 focus:outline-none focus:ring-2 focus:ring-sky-500"
+            />
+            <input
+              value={blendSpec}
+              onChange={(e) => setBlendSpec(e.target.value)}
+              placeholder="af_bella:0.5+af_sky:0.5"
+              className="flex-1 px-3 py-2 bg-slate-800 border border-slate-700 rounded-lg text-sm text-slate-100 placeholder-slate-500 focus:outline-none focus:ring-2 focus:ring-sky-500"
+            />
+            <button
+              onClick={handleSaveBlend}
+              disabled={!blendName.trim() || !blendSpec.trim()}
+              className="px-3 py-2 bg-sky-500 hover:bg-sky-600 disabled:bg-slate-700 disabled:text-slate-400 text-white rounded-lg text-sm"
+            >
+              Save
+            </button>
+          </div>
+          {blendError && <p className="text-xs text-red-400">{blendError}</p>}
+          <p className="text-xs text-slate-500">Mix up to 3 voices; weights are normalized.</p>
+        </div>
+      )}
+
       {/* Speed Control */}
       <div className="space-y-2">
         <div className="flex items-center justify-between">
@@ -85,6 +305,28 @@ export default function ScreenReader() {
           <span>1.0x</span>
           <span>2.0x</span>
         </div>
+        <div className="flex items-center justify-between text-xs">
+          {voiceSpeed !== undefined ? (
+            <>
+              <span className="text-slate-400">
+                This voice always reads at {voiceSpeed.toFixed(1)}x
+              </span>
+              <button
+                onClick={() => setVoiceSpeedOverride(selectedVoice, null)}
+                className="text-slate-400 hover:text-slate-200"
+              >
+                Clear
+              </button>
+            </>
+          ) : (
+            <button
+              onClick={() => setVoiceSpeedOverride(selectedVoice, speed)}
+              className="text-sky-400 hover:text-sky-300"
+            >
+              Use {speed.toFixed(1)}x for this voice
+            </button>
+          )}
+        </div>
       </div>
 
       {/* Hotkey Hint */}
@@ -99,3 +341,18 @@ export default function ScreenReader() {
     </div>
   );
 }
+
+// "fr-FR" -> "French (France)"
+function languageName(tag: string): string {
+  try {
+    return new Intl.DisplayNames(["en"], { type: "language" }).of(tag) ?? tag;
+  } catch {
+    return tag;
+  }
+}
+
+// 75.4 -> "1:15"
+function formatSeconds(secs: number): string {
+  const whole = Math.floor(secs);
+  return `${Math.floor(whole / 60)}:${String(whole % 60).padStart(2, "0")}`;
+}
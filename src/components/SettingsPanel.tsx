@@ -1,25 +1,103 @@
 import { useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { homeDir, join } from "@tauri-apps/api/path";
 import { enable as enableAutostart, disable as disableAutostart, isEnabled as isAutostartEnabled } from "@tauri-apps/plugin-autostart";
 import { open } from "@tauri-apps/plugin-shell";
 import HotkeyRecorder from "./HotkeyRecorder";
 import { usePermissions } from "../hooks/usePermissions";
+import {
+  accessibility,
+  errorMessage,
+  isBlahError,
+  onEvent,
+  stt,
+  tts,
+  type AccessibilityHint,
+  type AdaptiveModel,
+  type OnboardingStep,
+  type PostAction,
+  models,
+  settings as settingsCommands,
+  type LogLevel,
+  type MigrationReport,
+  type MigrationSource,
+  type ModelInfo,
+  type SectionResult,
+  type SettingsWarning,
+  type ThermalState,
+  type TtsCacheEntry,
+  type TtsHoldAction,
+  type UpdateInfo,
+  type WhisperAdvancedConfig,
+} from "../lib/tauri";
 
 interface Settings {
   stt_hotkey: string;
   tts_hotkey: string;
+  tts_hold_action: TtsHoldAction;
+  tts_hold_threshold_ms: number;
+  replay_hotkey: string | null;
+  tts_clipboard_hotkey: string | null;
   stt_model: string;
   tts_voice: string;
   tts_speed: number;
+  tts_engine: "kokoro" | "system";
+  voice_speed_overrides: Record<string, number>;
   auto_paste: boolean;
+  copy_to_clipboard: boolean;
+  paste_suffix: "none" | "space" | "newline";
+  press_enter_after_paste: boolean;
+  // Chime once a dictation has been pasted
+  paste_confirmation_sound: boolean;
+  post_action: PostAction;
+  post_action_url_template: string | null;
+  undo_paste_hotkey: string | null;
+  use_native_undo: boolean;
+  undo_paste_window_secs: number;
   launch_at_login: boolean;
   menu_bar_mode: boolean;
   // Silence detection settings
   silence_detection_enabled: boolean;
   silence_threshold: number;
   silence_duration: number;
+  silence_adaptive: boolean;
+  discard_silent_hold_secs: number;
+  failed_dictation_ttl_secs: number;
+  adaptive_model: AdaptiveModel | null;
+  // Whisper decoding
+  stt_short_utterance_secs: number;
+  stt_suppress_non_speech_tokens: boolean;
+  stt_max_chunk_secs: number;
+  whisper_initial_prompt: string | null;
+  whisper_advanced: WhisperAdvancedConfig;
+  confidence_paste_threshold: number;
+  diarization_enabled: boolean;
+  paragraph_breaks_enabled: boolean;
+  paragraph_pause_secs: number;
+  locale: string;
   // Onboarding
   onboarding_completed: boolean;
+  onboarding_step: OnboardingStep;
+  // Speak-on-copy (clipboard watcher)
+  speak_on_copy: boolean;
+  speak_on_copy_max_chars: number;
+  // Saved voice blends
+  custom_voices: { id: string; name: string; blend: string }[];
+  // Models location override
+  custom_models_dir: string | null;
+  download_max_retries: number;
+  http_proxy: string | null;
+  // Text clean-up before speech
+  tts_normalization: NormalizeOptions;
+  log_level: LogLevel;
+}
+
+interface NormalizeOptions {
+  markup: boolean;
+  urls: boolean;
+  abbreviations: boolean;
+  numbers: boolean;
+  whitespace: boolean;
 }
 
 interface HardwareProfile {
@@ -29,19 +107,82 @@ interface HardwareProfile {
   cpu_cores: number;
   has_neural_engine: boolean;
   has_metal: boolean;
+  gpu_name: string | null;
+  acceleration: string[];
   recommended_tier: string;
+  thermal_state: ThermalState;
+}
+
+// Seconds of room noise recorded when calibrating silence detection
+const NOISE_PROFILE_SECS = 3;
+
+// Starting point for the "Open URL" dictation action
+const DEFAULT_URL_TEMPLATE = "https://www.google.com/search?q={text}";
+
+interface AppVersion {
+  version: string;
+  build_date: string;
+  git_sha: string | null;
 }
 
 export default function SettingsPanel() {
   const [settings, setSettings] = useState<Settings | null>(null);
   const [hardware, setHardware] = useState<HardwareProfile | null>(null);
+  const [appVersion, setAppVersion] = useState<AppVersion | null>(null);
+  const [update, setUpdate] = useState<UpdateInfo | null>(null);
   const [saving, setSaving] = useState(false);
+  const [calibrating, setCalibrating] = useState(false);
+  // Room noise level from the last calibration
+  const [noiseDb, setNoiseDb] = useState<number | null>(null);
+  // Rejected setting from the last save, shown next to its control
+  const [fieldError, setFieldError] = useState<{ field: string; message: string } | null>(null);
   const permissions = usePermissions();
+  // Steps for re-granting Accessibility once it stops working after an update
+  const [accessibilityHint, setAccessibilityHint] = useState<AccessibilityHint | null>(null);
+  const [configPath, setConfigPath] = useState("~/Desktop/blah3-config.json");
+  const [configMerge, setConfigMerge] = useState(true);
+  const [configStatus, setConfigStatus] = useState<string | null>(null);
+  const [previews, setPreviews] = useState<TtsCacheEntry[]>([]);
+  const [sttModels, setSttModels] = useState<ModelInfo[]>([]);
+  // Model chosen but not downloaded yet, offered for download
+  const [modelPrompt, setModelPrompt] = useState<SettingsWarning | null>(null);
+  const [downloadingModel, setDownloadingModel] = useState(false);
+
+  const accessibilityStale = permissions?.accessibility_state === "granted_but_not_working";
+  useEffect(() => {
+    if (accessibilityStale && !accessibilityHint) {
+      accessibility.resetHint().then(setAccessibilityHint).catch(console.error);
+    }
+  }, [accessibilityStale, accessibilityHint]);
 
   useEffect(() => {
     loadSettings();
     loadHardware();
+    loadPreviews();
+    loadSttModels();
+    invoke<AppVersion>("get_app_version").then(setAppVersion).catch(console.error);
+    // Offline is fine; the check just tries again next time
+    settingsCommands.checkForAppUpdates().then(setUpdate).catch(console.error);
     syncAutostartState();
+
+    // A clashing hotkey was replaced by its default at startup
+    const unlisten = onEvent("hotkey-conflict", (conflict) => {
+      setFieldError({
+        field: conflict.binding,
+        message: `${conflict.shortcut} conflicted with ${
+          conflict.conflicts_with === "system" ? "a system shortcut" : "the other hotkey"
+        }, so ${conflict.fallback} is used instead.`,
+      });
+      loadSettings();
+    });
+    // Keep the thermal state current while the panel is open
+    const unlistenThermal = onEvent("thermal-state-changed", ({ state }) => {
+      setHardware((current) => (current ? { ...current, thermal_state: state } : current));
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+      unlistenThermal.then((fn) => fn());
+    };
   }, []);
 
   // Sync the settings with actual autostart state on mount
@@ -79,22 +220,125 @@ export default function SettingsPanel() {
     }
   };
 
+  const loadSttModels = () => {
+    models
+      .list()
+      .then((all) => setSttModels(all.filter((m) => m.model_type === "stt" && !m.id.includes(".mlmodelc"))))
+      .catch(console.error);
+  };
+
+  const downloadPromptedModel = async () => {
+    if (!modelPrompt) return;
+    setDownloadingModel(true);
+    try {
+      await models.download(modelPrompt.model_id);
+      setModelPrompt(null);
+      loadSttModels();
+    } catch (err) {
+      console.error("Failed to download model:", err);
+    } finally {
+      setDownloadingModel(false);
+    }
+  };
+
+  const loadPreviews = () => {
+    tts.listCacheEntries().then(setPreviews).catch(console.error);
+  };
+
+  const clearPreviews = async (voiceId?: string) => {
+    try {
+      await (voiceId ? tts.clearCacheEntry(voiceId) : tts.clearCache());
+    } catch (err) {
+      console.error("Failed to clear voice previews:", err);
+    }
+    loadPreviews();
+  };
+
+  const exportConfig = async () => {
+    try {
+      await invoke("export_config", { destPath: await expandHome(configPath) });
+      setConfigStatus("Configuration exported.");
+    } catch (err) {
+      setConfigStatus(errorMessage(err));
+    }
+  };
+
+  const importConfig = async () => {
+    try {
+      const results = await invoke<SectionResult[]>("import_config", {
+        path: await expandHome(configPath),
+        merge: configMerge,
+      });
+      setConfigStatus(results.map(describeSection).join(" "));
+      loadSettings();
+    } catch (err) {
+      setConfigStatus(errorMessage(err));
+    }
+  };
+
+  const migrateFrom = async (source: MigrationSource) => {
+    try {
+      setConfigStatus(describeMigration(await settingsCommands.migrateFromCompetitor(source)));
+      loadSettings();
+    } catch (err) {
+      setConfigStatus(errorMessage(err));
+    }
+  };
+
   const saveSettings = async (newSettings: Settings) => {
     setSaving(true);
     try {
-      await invoke("update_settings", { settings: newSettings });
+      const warnings = await invoke<SettingsWarning[]>("update_settings", { settings: newSettings });
       setSettings(newSettings);
+      setFieldError(null);
+      setModelPrompt(warnings.find((w) => w.kind === "model_not_downloaded") ?? null);
     } catch (err) {
       console.error("Failed to save settings:", err);
+      if (isBlahError(err) && err.code === "invalid_setting" && err.field) {
+        setFieldError({ field: err.field, message: err.message });
+      }
     } finally {
       setSaving(false);
     }
   };
 
-  const updateSetting = <K extends keyof Settings>(key: K, value: Settings[K]) => {
-    if (settings) {
-      const newSettings = { ...settings, [key]: value };
-      saveSettings(newSettings);
+  // Record a few seconds of ambient noise; the backend saves the new threshold
+  const calibrateSilence = async () => {
+    setCalibrating(true);
+    try {
+      const profile = await stt.recordAmbientNoiseProfile(NOISE_PROFILE_SECS);
+      setNoiseDb(profile.db);
+      setSettings((current) =>
+        current ? { ...current, silence_threshold: profile.recommended_threshold } : current
+      );
+    } catch (err) {
+      console.error("Failed to calibrate silence threshold:", err);
+    } finally {
+      setCalibrating(false);
+    }
+  };
+
+  // Saves just this field, so quick changes to several controls don't undo each other
+  const updateSetting = async <K extends keyof Settings>(key: K, value: Settings[K]) => {
+    if (!settings) {
+      return;
+    }
+    // Only a full save reports that a newly chosen model still needs downloading
+    if (key === "stt_model") {
+      saveSettings({ ...settings, [key]: value });
+      return;
+    }
+    setSaving(true);
+    try {
+      setSettings(await invoke<Settings>("update_settings_field", { field: key, value }));
+      setFieldError(null);
+    } catch (err) {
+      console.error(`Failed to save ${key}:`, err);
+      if (isBlahError(err) && err.code === "invalid_setting" && err.field) {
+        setFieldError({ field: err.field, message: err.message });
+      }
+    } finally {
+      setSaving(false);
     }
   };
 
@@ -180,7 +424,32 @@ export default function SettingsPanel() {
               <p className="text-slate-500">Recommended Tier</p>
               <p className="text-slate-200 capitalize">{hardware.recommended_tier}</p>
             </div>
+            <div>
+              <p className="text-slate-500">Thermal State</p>
+              <p
+                className={`capitalize ${
+                  hardware.thermal_state === "serious" || hardware.thermal_state === "critical"
+                    ? "text-amber-400"
+                    : "text-slate-200"
+                }`}
+              >
+                {hardware.thermal_state}
+              </p>
+            </div>
           </div>
+          {(hardware.thermal_state === "serious" || hardware.thermal_state === "critical") && (
+            <p className="text-xs text-amber-400 mt-2">
+              Your Mac is running hot; transcription and speech may be slower until it cools down.
+            </p>
+          )}
+          {hardware.gpu_name && (
+            <p className="text-xs text-slate-400 mt-2">GPU: {hardware.gpu_name}</p>
+          )}
+          {hardware.acceleration.length > 0 && (
+            <p className="text-xs text-slate-400 mt-1">
+              Acceleration: {hardware.acceleration.join(", ")}
+            </p>
+          )}
           {hardware.has_neural_engine && (
             <p className="text-xs text-green-400 mt-2">✓ Neural Engine available for CoreML acceleration</p>
           )}
@@ -206,8 +475,26 @@ export default function SettingsPanel() {
             title="Accessibility Access"
             description="Required to read selected text and paste transcriptions"
             granted={permissions?.accessibility}
+            needsRegrant={accessibilityStale}
             onOpen={() => openSystemPreferences("Privacy_Accessibility")}
           />
+          {accessibilityStale && accessibilityHint && (
+            <div className="bg-amber-500/10 border border-amber-500/30 rounded-lg p-3">
+              <p className="text-xs text-amber-300 mb-2">
+                Accessibility is switched on for Blah³ but macOS is refusing it, which usually
+                happens after an update. To fix it:
+              </p>
+              <ol className="list-decimal list-inside space-y-1 text-xs text-slate-300">
+                {accessibilityHint.steps.map((step) => (
+                  <li key={step}>{step}</li>
+                ))}
+              </ol>
+              <p className="text-xs text-slate-400 mt-2">
+                Or clear the old entry from Terminal:{" "}
+                <code className="text-slate-300">{accessibilityHint.reset_command}</code>
+              </p>
+            </div>
+          )}
         </div>
         <p className="text-xs text-slate-500 mt-3">
           After changing permissions, you may need to restart Blah³.
@@ -230,6 +517,121 @@ export default function SettingsPanel() {
               onChange={(value) => updateSetting("tts_hotkey", value)}
             />
           </SettingRow>
+          {fieldError && (fieldError.field === "stt_hotkey" || fieldError.field === "tts_hotkey") && (
+            <p className="text-xs text-red-400">
+              {fieldError.field === "stt_hotkey" ? "Dictation" : "Read aloud"} hotkey: {fieldError.message}
+            </p>
+          )}
+          <SettingRow label="Holding Read Aloud">
+            <select
+              value={settings.tts_hold_action}
+              onChange={(e) => updateSetting("tts_hold_action", e.target.value as TtsHoldAction)}
+              className="px-2 py-1 bg-slate-800 border border-slate-700 rounded text-sm text-slate-100 focus:outline-none focus:ring-2 focus:ring-sky-500"
+            >
+              <option value="slow">Reads slowly</option>
+              <option value="spell">Spells out short text</option>
+              <option value="none">Reads normally</option>
+            </select>
+          </SettingRow>
+          {settings.tts_hold_action !== "none" && (
+            <SettingRow label="Hold for">
+              <div className="flex items-center gap-2">
+                <input
+                  type="range"
+                  min="200"
+                  max="3000"
+                  step="100"
+                  value={settings.tts_hold_threshold_ms}
+                  onChange={(e) => updateSetting("tts_hold_threshold_ms", Number(e.target.value))}
+                  className="w-24 accent-sky-500"
+                />
+                <span className="text-sm text-slate-400 w-12">
+                  {(settings.tts_hold_threshold_ms / 1000).toFixed(1)}s
+                </span>
+              </div>
+            </SettingRow>
+          )}
+          {fieldError?.field === "tts_hold_threshold_ms" && (
+            <p className="text-xs text-red-400">Hold time: {fieldError.message}</p>
+          )}
+          <SettingRow label="Replay Last Speech">
+            {settings.replay_hotkey === null ? (
+              <button
+                onClick={() => updateSetting("replay_hotkey", "CommandOrControl+Shift+R")}
+                className="px-3 py-1 text-xs bg-slate-700 hover:bg-slate-600 text-slate-200 rounded"
+              >
+                Add hotkey
+              </button>
+            ) : (
+              <div className="flex items-center gap-2">
+                <HotkeyRecorder
+                  value={settings.replay_hotkey}
+                  onChange={(value) => updateSetting("replay_hotkey", value)}
+                />
+                <button
+                  onClick={() => updateSetting("replay_hotkey", null)}
+                  className="text-xs text-sky-400 hover:text-sky-300"
+                >
+                  Remove
+                </button>
+              </div>
+            )}
+          </SettingRow>
+          {fieldError?.field === "replay_hotkey" && (
+            <p className="text-xs text-red-400">Replay hotkey: {fieldError.message}</p>
+          )}
+          <SettingRow label="Undo Last Paste">
+            {settings.undo_paste_hotkey === null ? (
+              <button
+                onClick={() => updateSetting("undo_paste_hotkey", "CommandOrControl+Alt+Z")}
+                className="px-3 py-1 text-xs bg-slate-700 hover:bg-slate-600 text-slate-200 rounded"
+              >
+                Add hotkey
+              </button>
+            ) : (
+              <div className="flex items-center gap-2">
+                <HotkeyRecorder
+                  value={settings.undo_paste_hotkey}
+                  onChange={(value) => updateSetting("undo_paste_hotkey", value)}
+                />
+                <button
+                  onClick={() => updateSetting("undo_paste_hotkey", null)}
+                  className="text-xs text-sky-400 hover:text-sky-300"
+                >
+                  Remove
+                </button>
+              </div>
+            )}
+          </SettingRow>
+          {fieldError?.field === "undo_paste_hotkey" && (
+            <p className="text-xs text-red-400">Undo paste hotkey: {fieldError.message}</p>
+          )}
+          <SettingRow label="Read Clipboard Aloud">
+            {settings.tts_clipboard_hotkey === null ? (
+              <button
+                onClick={() => updateSetting("tts_clipboard_hotkey", "CommandOrControl+Alt+C")}
+                className="px-3 py-1 text-xs bg-slate-700 hover:bg-slate-600 text-slate-200 rounded"
+              >
+                Add hotkey
+              </button>
+            ) : (
+              <div className="flex items-center gap-2">
+                <HotkeyRecorder
+                  value={settings.tts_clipboard_hotkey}
+                  onChange={(value) => updateSetting("tts_clipboard_hotkey", value)}
+                />
+                <button
+                  onClick={() => updateSetting("tts_clipboard_hotkey", null)}
+                  className="text-xs text-sky-400 hover:text-sky-300"
+                >
+                  Remove
+                </button>
+              </div>
+            )}
+          </SettingRow>
+          {fieldError?.field === "tts_clipboard_hotkey" && (
+            <p className="text-xs text-red-400">Read clipboard hotkey: {fieldError.message}</p>
+          )}
         </div>
       </section>
 
@@ -243,6 +645,157 @@ export default function SettingsPanel() {
               onChange={(v) => updateSetting("auto_paste", v)}
             />
           </SettingRow>
+          <SettingRow label="Copy transcription to clipboard">
+            <Toggle
+              checked={settings.copy_to_clipboard}
+              onChange={(v) => updateSetting("copy_to_clipboard", v)}
+            />
+          </SettingRow>
+          {settings.auto_paste && (
+            <SettingRow label="Dictation action">
+              <select
+                value={settings.post_action}
+                onChange={(e) => {
+                  const action = e.target.value as PostAction;
+                  // The template is required for URL actions, so start from a web search
+                  const template =
+                    settings.post_action_url_template ??
+                    (action === "open_url_template" ? DEFAULT_URL_TEMPLATE : null);
+                  saveSettings({ ...settings, post_action: action, post_action_url_template: template });
+                }}
+                className="px-2 py-1 bg-slate-800 border border-slate-700 rounded text-sm text-slate-100 focus:outline-none focus:ring-2 focus:ring-sky-500"
+              >
+                <option value="paste">Paste</option>
+                <option value="paste_and_enter">Paste and press Enter</option>
+                <option value="open_url_template">Open URL (search)</option>
+                <option value="copy_only">Copy only</option>
+              </select>
+            </SettingRow>
+          )}
+          {settings.auto_paste && settings.post_action === "open_url_template" && (
+            <div>
+              <label className="block text-sm text-slate-300 mb-1">URL template</label>
+              <input
+                type="text"
+                key={settings.post_action_url_template ?? ""}
+                defaultValue={settings.post_action_url_template ?? ""}
+                placeholder={DEFAULT_URL_TEMPLATE}
+                onBlur={(e) => {
+                  const value = e.target.value.trim() || null;
+                  if (value !== settings.post_action_url_template) {
+                    updateSetting("post_action_url_template", value);
+                  }
+                }}
+                className="w-full px-2 py-1 bg-slate-800 border border-slate-700 rounded text-sm text-slate-100 placeholder-slate-500 focus:outline-none focus:ring-2 focus:ring-sky-500"
+              />
+            </div>
+          )}
+          {fieldError?.field === "post_action_url_template" && (
+            <p className="text-xs text-red-400">{fieldError.message}</p>
+          )}
+          {settings.auto_paste && (
+            <SettingRow label="Review below confidence">
+              <div className="flex items-center gap-2">
+                <input
+                  type="range"
+                  min="0"
+                  max="0.9"
+                  step="0.05"
+                  value={settings.confidence_paste_threshold}
+                  onChange={(e) => updateSetting("confidence_paste_threshold", parseFloat(e.target.value))}
+                  className="w-24 accent-sky-500"
+                />
+                <span className="text-sm text-slate-400 w-12">
+                  {settings.confidence_paste_threshold > 0
+                    ? `${Math.round(settings.confidence_paste_threshold * 100)}%`
+                    : "Off"}
+                </span>
+              </div>
+            </SettingRow>
+          )}
+          {settings.auto_paste && (
+            <SettingRow label="After pasted text">
+              <select
+                value={settings.paste_suffix}
+                onChange={(e) => updateSetting("paste_suffix", e.target.value as Settings["paste_suffix"])}
+                className="px-2 py-1 bg-slate-800 border border-slate-700 rounded text-sm text-slate-100 focus:outline-none focus:ring-2 focus:ring-sky-500"
+              >
+                <option value="none">Nothing</option>
+                <option value="space">Space</option>
+                <option value="newline">New line</option>
+              </select>
+            </SettingRow>
+          )}
+          {settings.auto_paste && settings.post_action === "paste" && (
+            <SettingRow label="Press Enter after pasting">
+              <Toggle
+                checked={settings.press_enter_after_paste}
+                onChange={(v) => updateSetting("press_enter_after_paste", v)}
+              />
+            </SettingRow>
+          )}
+          {settings.auto_paste && (
+            <SettingRow label="Chime after pasting">
+              <Toggle
+                checked={settings.paste_confirmation_sound}
+                onChange={(v) => updateSetting("paste_confirmation_sound", v)}
+              />
+            </SettingRow>
+          )}
+          {settings.auto_paste && (
+            <SettingRow label="Undo pastes with Cmd+Z">
+              <Toggle
+                checked={settings.use_native_undo}
+                onChange={(v) => updateSetting("use_native_undo", v)}
+              />
+            </SettingRow>
+          )}
+          {settings.auto_paste && (
+            <SettingRow label="Pastes can be undone for">
+              <select
+                value={settings.undo_paste_window_secs}
+                onChange={(e) => updateSetting("undo_paste_window_secs", Number(e.target.value))}
+                className="px-2 py-1 bg-slate-800 border border-slate-700 rounded text-sm text-slate-100 focus:outline-none focus:ring-2 focus:ring-sky-500"
+              >
+                <option value={15}>15 seconds</option>
+                <option value={30}>30 seconds</option>
+                <option value={60}>1 minute</option>
+                <option value={300}>5 minutes</option>
+              </select>
+            </SettingRow>
+          )}
+          <SettingRow label="Discard silent recordings after">
+            <select
+              value={settings.discard_silent_hold_secs}
+              onChange={(e) => updateSetting("discard_silent_hold_secs", Number(e.target.value))}
+              className="px-2 py-1 bg-slate-800 border border-slate-700 rounded text-sm text-slate-100 focus:outline-none focus:ring-2 focus:ring-sky-500"
+            >
+              <option value={0}>Never</option>
+              <option value={5}>5 seconds</option>
+              <option value={10}>10 seconds</option>
+              <option value={30}>30 seconds</option>
+            </select>
+          </SettingRow>
+          <p className="text-xs text-slate-500">
+            Press Escape while holding the dictation hotkey to discard a recording.
+          </p>
+          <SettingRow label="Keep failed dictations for retry">
+            <select
+              value={settings.failed_dictation_ttl_secs}
+              onChange={(e) => updateSetting("failed_dictation_ttl_secs", Number(e.target.value))}
+              className="px-2 py-1 bg-slate-800 border border-slate-700 rounded text-sm text-slate-100 focus:outline-none focus:ring-2 focus:ring-sky-500"
+            >
+              <option value={300}>5 minutes</option>
+              <option value={600}>10 minutes</option>
+              <option value={3600}>1 hour</option>
+            </select>
+          </SettingRow>
+          <SettingRow label="Speak copied text">
+            <Toggle
+              checked={settings.speak_on_copy}
+              onChange={(v) => updateSetting("speak_on_copy", v)}
+            />
+          </SettingRow>
           <SettingRow label="Launch at login">
             <Toggle
               checked={settings.launch_at_login}
@@ -287,24 +840,503 @@ export default function SettingsPanel() {
                   <span className="text-sm text-slate-400 w-12">{settings.silence_duration}s</span>
                 </div>
               </SettingRow>
-              <SettingRow label="Sensitivity">
+              <SettingRow label="Adapt to background noise">
+                <Toggle
+                  checked={settings.silence_adaptive}
+                  onChange={(v) => updateSetting("silence_adaptive", v)}
+                />
+              </SettingRow>
+              {!settings.silence_adaptive && (
+                <>
+                  <SettingRow label="Sensitivity">
+                    <div className="flex items-center gap-2">
+                      <input
+                        type="range"
+                        min="0.001"
+                        max="0.1"
+                        step="0.005"
+                        value={settings.silence_threshold}
+                        onChange={(e) => updateSetting("silence_threshold", parseFloat(e.target.value))}
+                        className="w-24 accent-sky-500"
+                      />
+                      <span className="text-sm text-slate-400 w-16">
+                        {settings.silence_threshold < 0.02 ? "High" : settings.silence_threshold < 0.05 ? "Medium" : "Low"}
+                      </span>
+                    </div>
+                  </SettingRow>
+                  <SettingRow label="Calibrate to room noise">
+                    <button
+                      onClick={calibrateSilence}
+                      disabled={calibrating}
+                      className="px-3 py-1 text-xs bg-slate-700 hover:bg-slate-600 disabled:opacity-50 text-slate-200 rounded"
+                    >
+                      {calibrating ? "Listening… stay quiet" : "Calibrate"}
+                    </button>
+                    {noiseDb !== null && !calibrating && (
+                      <span className="ml-2 text-xs text-slate-400">Room noise {noiseDb.toFixed(0)} dB</span>
+                    )}
+                  </SettingRow>
+                </>
+              )}
+            </>
+          )}
+        </div>
+      </section>
+
+      {/* Advanced STT */}
+      <section>
+        <h2 className="text-lg font-semibold text-slate-200 mb-3">Advanced Transcription</h2>
+        <p className="text-xs text-slate-400 mb-3">
+          Short clips are decoded as a single segment, which is faster and more accurate for commands.
+        </p>
+        <div className="space-y-3">
+          <SettingRow label="Speech model">
+            <select
+              value={settings.stt_model}
+              onChange={(e) => updateSetting("stt_model", e.target.value)}
+              className="px-2 py-1 bg-slate-800 border border-slate-700 rounded text-sm text-slate-100 focus:outline-none focus:ring-2 focus:ring-sky-500"
+            >
+              {!sttModels.some((m) => m.id === settings.stt_model) && (
+                <option value={settings.stt_model}>{settings.stt_model}</option>
+              )}
+              {sttModels.map((m) => (
+                <option key={m.id} value={m.id}>
+                  {m.name}
+                  {m.status === "downloaded" ? "" : " (not downloaded)"}
+                </option>
+              ))}
+            </select>
+          </SettingRow>
+          {fieldError?.field === "stt_model" && (
+            <p className="text-xs text-red-400">{fieldError.message}</p>
+          )}
+          {modelPrompt && (
+            <div className="flex items-center gap-2 text-xs text-amber-400">
+              <span>{modelPrompt.model_name} isn't downloaded yet, so dictation won't work until it is.</span>
+              <button
+                onClick={downloadPromptedModel}
+                disabled={downloadingModel}
+                className="px-3 py-1 text-xs bg-slate-700 hover:bg-slate-600 disabled:opacity-50 text-slate-200 rounded"
+              >
+                {downloadingModel ? "Downloading..." : "Download now"}
+              </button>
+            </div>
+          )}
+          <SettingRow label="Pick model by length">
+            <Toggle
+              checked={settings.adaptive_model !== null}
+              onChange={(v) =>
+                updateSetting(
+                  "adaptive_model",
+                  v
+                    ? {
+                        fast_model: "ggml-tiny.en.bin",
+                        fast_below_secs: 5,
+                        accurate_model: "ggml-small.en.bin",
+                        accurate_above_secs: 30,
+                      }
+                    : null
+                )
+              }
+            />
+          </SettingRow>
+          {settings.adaptive_model && (
+            <>
+              <SettingRow label={`Under ${settings.adaptive_model.fast_below_secs}s`}>
                 <div className="flex items-center gap-2">
                   <input
                     type="range"
-                    min="0.001"
-                    max="0.1"
-                    step="0.005"
-                    value={settings.silence_threshold}
-                    onChange={(e) => updateSetting("silence_threshold", parseFloat(e.target.value))}
-                    className="w-24 accent-sky-500"
+                    min="1"
+                    max="30"
+                    step="1"
+                    value={settings.adaptive_model.fast_below_secs}
+                    onChange={(e) =>
+                      updateSetting("adaptive_model", {
+                        ...settings.adaptive_model!,
+                        fast_below_secs: parseFloat(e.target.value),
+                      })
+                    }
+                    className="w-20 accent-sky-500"
                   />
-                  <span className="text-sm text-slate-400 w-16">
-                    {settings.silence_threshold < 0.02 ? "High" : settings.silence_threshold < 0.05 ? "Medium" : "Low"}
-                  </span>
+                  <select
+                    value={settings.adaptive_model.fast_model}
+                    onChange={(e) =>
+                      updateSetting("adaptive_model", { ...settings.adaptive_model!, fast_model: e.target.value })
+                    }
+                    className="px-2 py-1 bg-slate-800 border border-slate-700 rounded text-sm text-slate-100 focus:outline-none focus:ring-2 focus:ring-sky-500"
+                  >
+                    {sttModels.map((m) => (
+                      <option key={m.id} value={m.id}>
+                        {m.name}
+                        {m.status === "downloaded" ? "" : " (not downloaded)"}
+                      </option>
+                    ))}
+                  </select>
+                </div>
+              </SettingRow>
+              <SettingRow label={`Over ${settings.adaptive_model.accurate_above_secs}s`}>
+                <div className="flex items-center gap-2">
+                  <input
+                    type="range"
+                    min="5"
+                    max="300"
+                    step="5"
+                    value={settings.adaptive_model.accurate_above_secs}
+                    onChange={(e) =>
+                      updateSetting("adaptive_model", {
+                        ...settings.adaptive_model!,
+                        accurate_above_secs: parseFloat(e.target.value),
+                      })
+                    }
+                    className="w-20 accent-sky-500"
+                  />
+                  <select
+                    value={settings.adaptive_model.accurate_model}
+                    onChange={(e) =>
+                      updateSetting("adaptive_model", { ...settings.adaptive_model!, accurate_model: e.target.value })
+                    }
+                    className="px-2 py-1 bg-slate-800 border border-slate-700 rounded text-sm text-slate-100 focus:outline-none focus:ring-2 focus:ring-sky-500"
+                  >
+                    {sttModels.map((m) => (
+                      <option key={m.id} value={m.id}>
+                        {m.name}
+                        {m.status === "downloaded" ? "" : " (not downloaded)"}
+                      </option>
+                    ))}
+                  </select>
                 </div>
               </SettingRow>
+              <p className="text-xs text-slate-400">
+                Other recordings use the speech model above. Models that aren't downloaded are skipped.
+              </p>
             </>
           )}
+          {fieldError?.field === "adaptive_model" && (
+            <p className="text-xs text-red-400">{fieldError.message}</p>
+          )}
+          <SettingRow label="Short utterance threshold">
+            <div className="flex items-center gap-2">
+              <input
+                type="range"
+                min="0"
+                max="10"
+                step="0.5"
+                value={settings.stt_short_utterance_secs}
+                onChange={(e) => updateSetting("stt_short_utterance_secs", parseFloat(e.target.value))}
+                className="w-24 accent-sky-500"
+              />
+              <span className="text-sm text-slate-400 w-12">
+                {settings.stt_short_utterance_secs > 0 ? `${settings.stt_short_utterance_secs}s` : "Off"}
+              </span>
+            </div>
+          </SettingRow>
+          <SettingRow label="Chunk length for long recordings">
+            <div className="flex items-center gap-2">
+              <input
+                type="range"
+                min="10"
+                max="600"
+                step="10"
+                value={settings.stt_max_chunk_secs}
+                onChange={(e) => updateSetting("stt_max_chunk_secs", parseFloat(e.target.value))}
+                className="w-24 accent-sky-500"
+              />
+              <span className="text-sm text-slate-400 w-12">{settings.stt_max_chunk_secs}s</span>
+            </div>
+          </SettingRow>
+          {fieldError?.field === "stt_max_chunk_secs" && (
+            <p className="text-xs text-red-400">{fieldError.message}</p>
+          )}
+          <SettingRow label="Suppress non-speech tokens">
+            <Toggle
+              checked={settings.stt_suppress_non_speech_tokens}
+              onChange={(v) => updateSetting("stt_suppress_non_speech_tokens", v)}
+            />
+          </SettingRow>
+          <SettingRow label="Separate speakers (experimental)">
+            <Toggle
+              checked={settings.diarization_enabled}
+              onChange={(v) => updateSetting("diarization_enabled", v)}
+            />
+          </SettingRow>
+          <SettingRow label="Paragraph breaks at long pauses">
+            <Toggle
+              checked={settings.paragraph_breaks_enabled}
+              onChange={(v) => updateSetting("paragraph_breaks_enabled", v)}
+            />
+          </SettingRow>
+          {settings.paragraph_breaks_enabled && (
+            <SettingRow label="Paragraph pause">
+              <div className="flex items-center gap-2">
+                <input
+                  type="range"
+                  min="0.5"
+                  max="5"
+                  step="0.1"
+                  value={settings.paragraph_pause_secs}
+                  onChange={(e) => updateSetting("paragraph_pause_secs", parseFloat(e.target.value))}
+                  className="w-24 accent-sky-500"
+                />
+                <span className="text-sm text-slate-400 w-12">{settings.paragraph_pause_secs}s</span>
+              </div>
+            </SettingRow>
+          )}
+          {fieldError?.field === "paragraph_pause_secs" && (
+            <p className="text-xs text-red-400">{fieldError.message}</p>
+          )}
+          <SettingRow label="Punctuation style">
+            <select
+              value={settings.locale}
+              onChange={(e) => updateSetting("locale", e.target.value)}
+              className="px-2 py-1 bg-slate-800 border border-slate-700 rounded text-sm text-slate-100 focus:outline-none focus:ring-2 focus:ring-sky-500"
+            >
+              <option value="system">System language</option>
+              <option value="none">As transcribed</option>
+              <option value="en">English</option>
+              <option value="fr">Français</option>
+              <option value="de">Deutsch</option>
+            </select>
+          </SettingRow>
+          {fieldError?.field === "locale" && (
+            <p className="text-xs text-red-400">{fieldError.message}</p>
+          )}
+          <div>
+            <label className="block text-sm text-slate-300 mb-1">Vocabulary hint</label>
+            <textarea
+              key={settings.whisper_initial_prompt ?? ""}
+              defaultValue={settings.whisper_initial_prompt ?? ""}
+              placeholder="Names and terms you dictate often, e.g. Kubernetes, kubectl, etcd"
+              rows={2}
+              maxLength={1000}
+              onBlur={(e) => {
+                const value = e.target.value.trim() || null;
+                if (value !== settings.whisper_initial_prompt) {
+                  updateSetting("whisper_initial_prompt", value);
+                }
+              }}
+              className="w-full px-2 py-1 bg-slate-800 border border-slate-700 rounded text-sm text-slate-100 placeholder-slate-500 focus:outline-none focus:ring-2 focus:ring-sky-500"
+            />
+          </div>
+          {fieldError?.field === "whisper_initial_prompt" && (
+            <p className="text-xs text-red-400">{fieldError.message}</p>
+          )}
+          <SettingRow label="Use GPU (reloads the model)">
+            <Toggle
+              checked={settings.whisper_advanced.use_gpu}
+              onChange={(v) =>
+                updateSetting("whisper_advanced", { ...settings.whisper_advanced, use_gpu: v })
+              }
+            />
+          </SettingRow>
+          <SettingRow label="Flash attention (reloads the model)">
+            <Toggle
+              checked={settings.whisper_advanced.flash_attention}
+              onChange={(v) =>
+                updateSetting("whisper_advanced", { ...settings.whisper_advanced, flash_attention: v })
+              }
+            />
+          </SettingRow>
+          <SettingRow label="Decoding threads">
+            <select
+              value={settings.whisper_advanced.n_threads}
+              onChange={(e) =>
+                updateSetting("whisper_advanced", {
+                  ...settings.whisper_advanced,
+                  n_threads: parseInt(e.target.value, 10),
+                })
+              }
+              className="px-2 py-1 bg-slate-800 border border-slate-700 rounded text-sm text-slate-100 focus:outline-none focus:ring-2 focus:ring-sky-500"
+            >
+              {[...new Set([0, 1, 2, 4, 6, 8, 12, 16, settings.whisper_advanced.n_threads])]
+                .sort((a, b) => a - b)
+                .map((n) => (
+                  <option key={n} value={n}>
+                    {n === 0 ? "Automatic" : n}
+                  </option>
+                ))}
+            </select>
+          </SettingRow>
+          {fieldError?.field === "whisper_advanced.n_threads" && (
+            <p className="text-xs text-red-400">{fieldError.message}</p>
+          )}
+        </div>
+      </section>
+
+      {/* Reading */}
+      <section>
+        <h2 className="text-lg font-semibold text-slate-200 mb-3">Reading</h2>
+        <p className="text-xs text-slate-400 mb-3">
+          Clean up text before it is read aloud. The original text is never changed.
+        </p>
+        <div className="space-y-3">
+          {(
+            [
+              ["markup", "Strip markdown and HTML"],
+              ["urls", "Read links as their domain"],
+              ["abbreviations", "Expand abbreviations"],
+              ["numbers", "Speak numbers, dates and currency"],
+              ["whitespace", "Collapse extra whitespace"],
+            ] as [keyof NormalizeOptions, string][]
+          ).map(([key, label]) => (
+            <SettingRow key={key} label={label}>
+              <Toggle
+                checked={settings.tts_normalization[key]}
+                onChange={(v) =>
+                  updateSetting("tts_normalization", { ...settings.tts_normalization, [key]: v })
+                }
+              />
+            </SettingRow>
+          ))}
+        </div>
+        <div className="mt-4">
+          <div className="flex items-center justify-between mb-2">
+            <p className="text-sm text-slate-300">
+              Voice previews
+              <span className="text-xs text-slate-500 ml-2">
+                {formatBytes(previews.reduce((sum, p) => sum + p.file_size_bytes, 0))}
+              </span>
+            </p>
+            {previews.length > 0 && (
+              <button
+                onClick={() => clearPreviews()}
+                className="text-xs text-red-400 hover:text-red-300"
+              >
+                Clear all
+              </button>
+            )}
+          </div>
+          {previews.length === 0 ? (
+            <p className="text-xs text-slate-500">No cached previews.</p>
+          ) : (
+            <ul className="space-y-1">
+              {previews.map((preview) => (
+                <li
+                  key={`${preview.voice_id}-${preview.preview_text_hash}`}
+                  className="flex items-center justify-between text-xs"
+                >
+                  <span className="text-slate-300">{preview.voice_id}</span>
+                  <span className="flex items-center space-x-3 text-slate-500">
+                    <span>{formatBytes(preview.file_size_bytes)}</span>
+                    <button
+                      onClick={() => clearPreviews(preview.voice_id)}
+                      className="text-slate-400 hover:text-red-400"
+                      aria-label={`Delete preview for ${preview.voice_id}`}
+                    >
+                      ✕
+                    </button>
+                  </span>
+                </li>
+              ))}
+            </ul>
+          )}
+        </div>
+      </section>
+
+      {/* Storage */}
+      <section>
+        <h2 className="text-lg font-semibold text-slate-200 mb-3">Storage</h2>
+        <p className="text-xs text-slate-400 mb-3">
+          Keep models on an external drive or network share. Leave empty for the default location.
+        </p>
+        <div className="space-y-3">
+          <SettingRow label="Models folder">
+            <input
+              type="text"
+              key={settings.custom_models_dir ?? ""}
+              defaultValue={settings.custom_models_dir ?? ""}
+              placeholder="Default"
+              onBlur={(e) => {
+                const value = e.target.value.trim() || null;
+                if (value !== settings.custom_models_dir) {
+                  updateSetting("custom_models_dir", value);
+                }
+              }}
+              className="w-56 px-2 py-1 bg-slate-800 border border-slate-700 rounded text-sm text-slate-100 placeholder-slate-500 focus:outline-none focus:ring-2 focus:ring-sky-500"
+            />
+          </SettingRow>
+          <SettingRow label="Download retries">
+            <select
+              value={settings.download_max_retries}
+              onChange={(e) => updateSetting("download_max_retries", parseInt(e.target.value, 10))}
+              className="px-2 py-1 bg-slate-800 border border-slate-700 rounded text-sm text-slate-100 focus:outline-none focus:ring-2 focus:ring-sky-500"
+            >
+              {[0, 1, 3, 5, 10].map((n) => (
+                <option key={n} value={n}>
+                  {n === 0 ? "Off" : n}
+                </option>
+              ))}
+            </select>
+          </SettingRow>
+          <SettingRow label="Download proxy">
+            <input
+              type="text"
+              key={settings.http_proxy ?? ""}
+              defaultValue={settings.http_proxy ?? ""}
+              placeholder="System settings"
+              onBlur={(e) => {
+                const value = e.target.value.trim() || null;
+                if (value !== settings.http_proxy) {
+                  updateSetting("http_proxy", value);
+                }
+              }}
+              className="w-56 px-2 py-1 bg-slate-800 border border-slate-700 rounded text-sm text-slate-100 placeholder-slate-500 focus:outline-none focus:ring-2 focus:ring-sky-500"
+            />
+          </SettingRow>
+          {fieldError?.field === "http_proxy" && (
+            <p className="text-xs text-red-400">{fieldError.message}</p>
+          )}
+        </div>
+      </section>
+
+      {/* Backup */}
+      <section>
+        <h2 className="text-lg font-semibold text-slate-200 mb-3">Backup</h2>
+        <p className="text-xs text-slate-400 mb-3">
+          Move hotkeys, voices and custom models to another Mac. Models are downloaded again there.
+        </p>
+        <div className="space-y-3">
+          <SettingRow label="File">
+            <input
+              type="text"
+              value={configPath}
+              onChange={(e) => setConfigPath(e.target.value)}
+              className="w-56 px-2 py-1 bg-slate-800 border border-slate-700 rounded text-sm text-slate-100 placeholder-slate-500 focus:outline-none focus:ring-2 focus:ring-sky-500"
+            />
+          </SettingRow>
+          <SettingRow label="Keep existing blends and models on import">
+            <Toggle checked={configMerge} onChange={setConfigMerge} />
+          </SettingRow>
+          <div className="flex gap-2 justify-end">
+            <button
+              onClick={exportConfig}
+              className="px-3 py-1 text-sm bg-slate-700 hover:bg-slate-600 text-slate-100 rounded"
+            >
+              Export
+            </button>
+            <button
+              onClick={importConfig}
+              className="px-3 py-1 text-sm bg-sky-600 hover:bg-sky-500 text-white rounded"
+            >
+              Import
+            </button>
+          </div>
+          <SettingRow label="Bring over models and settings from">
+            <div className="flex gap-2">
+              <button
+                onClick={() => migrateFrom("super_whisper")}
+                className="px-3 py-1 text-sm bg-slate-700 hover:bg-slate-600 text-slate-100 rounded"
+              >
+                SuperWhisper
+              </button>
+              <button
+                onClick={() => migrateFrom("whisper_dictation")}
+                className="px-3 py-1 text-sm bg-slate-700 hover:bg-slate-600 text-slate-100 rounded"
+              >
+                Whisper Dictation
+              </button>
+            </div>
+          </SettingRow>
+          {configStatus && <p className="text-xs text-slate-400">{configStatus}</p>}
         </div>
       </section>
 
@@ -315,13 +1347,96 @@ export default function SettingsPanel() {
 
       {/* About */}
       <section className="text-center pt-4 border-t border-slate-800">
-        <p className="text-sm text-slate-400">Blah³ v0.1.0</p>
+        <p className="text-sm text-slate-400">Blah³ v{appVersion?.version ?? "0.1.0"}</p>
+        {appVersion && (
+          <p className="text-xs text-slate-500 mt-1">
+            Built {appVersion.build_date}
+            {appVersion.git_sha && ` (${appVersion.git_sha})`}
+          </p>
+        )}
+        {update && (
+          <button
+            onClick={() => open(update.release_url).catch(console.error)}
+            className="block mx-auto text-xs text-green-400 hover:text-green-300 mt-1"
+          >
+            Version {update.version} is available
+          </button>
+        )}
         <p className="text-xs text-slate-500 mt-1">Local Voice Toolkit for macOS</p>
+        <button
+          onClick={() => invoke("open_log_file").catch(console.error)}
+          className="text-xs text-sky-400 hover:text-sky-300 mt-2"
+        >
+          Show log file
+        </button>
+        <label className="block text-xs text-slate-500 mt-1">
+          Log detail
+          <select
+            value={settings.log_level}
+            onChange={(e) => updateSetting("log_level", e.target.value as LogLevel)}
+            className="ml-2 px-1 py-0.5 bg-slate-800 border border-slate-700 rounded text-xs text-slate-300 focus:outline-none focus:ring-2 focus:ring-sky-500"
+          >
+            {(["error", "warn", "info", "debug", "trace"] as const).map((level) => (
+              <option key={level} value={level}>
+                {level}
+              </option>
+            ))}
+          </select>
+        </label>
+        <button
+          onClick={() => invoke("open_settings_directory").catch(console.error)}
+          className="block mx-auto text-xs text-sky-400 hover:text-sky-300 mt-1"
+        >
+          Open settings folder
+        </button>
+        {import.meta.env.DEV && (
+          <button
+            onClick={() => invoke("copy_diagnostics_to_clipboard").catch(console.error)}
+            className="block mx-auto text-xs text-sky-400 hover:text-sky-300 mt-1"
+          >
+            Copy diagnostics
+          </button>
+        )}
       </section>
     </div>
   );
 }
 
+function formatBytes(bytes: number): string {
+  if (bytes >= 1_000_000) return `${(bytes / 1_000_000).toFixed(1)} MB`;
+  if (bytes >= 1_000) return `${Math.round(bytes / 1_000)} KB`;
+  return `${bytes} bytes`;
+}
+
+const SECTION_LABELS: Record<SectionResult["section"], string> = {
+  settings: "Settings",
+  custom_voices: "Voice blends",
+  voice_speed_overrides: "Speed overrides",
+  custom_models: "Custom models",
+};
+
+function describeSection(result: SectionResult): string {
+  const label = SECTION_LABELS[result.section];
+  if (result.error) return `${label}: ${result.error}.`;
+  const skipped = result.skipped > 0 ? `, ${result.skipped} skipped` : "";
+  return result.section === "settings"
+    ? `${label} imported.`
+    : `${label}: ${result.imported} imported${skipped}.`;
+}
+
+function describeMigration(report: MigrationReport): string {
+  const models = report.models_imported.length;
+  const copied = report.settings_copied.length;
+  const warnings = report.warnings.length > 0 ? ` ${report.warnings.join(". ")}.` : "";
+  return `${models} model${models === 1 ? "" : "s"} and ${copied} setting${copied === 1 ? "" : "s"} imported.${warnings}`;
+}
+
+// The backend takes absolute paths
+async function expandHome(path: string): Promise<string> {
+  const trimmed = path.trim();
+  return trimmed.startsWith("~/") ? join(await homeDir(), trimmed.slice(2)) : trimmed;
+}
+
 function SettingRow({ label, children }: { label: string; children: React.ReactNode }) {
   return (
     <div className="flex items-center justify-between">
@@ -353,12 +1468,15 @@ function PermissionRow({
   title,
   description,
   granted,
+  needsRegrant = false,
   onOpen,
 }: {
   icon: string;
   title: string;
   description: string;
   granted?: boolean;
+  // Allowed in System Settings but not working
+  needsRegrant?: boolean;
   onOpen: () => void;
 }) {
   return (
@@ -368,7 +1486,11 @@ function PermissionRow({
         <div>
           <div className="flex items-center gap-2">
             <p className="text-sm text-slate-200">{title}</p>
-            {granted !== undefined && (
+            {needsRegrant ? (
+              <span className="text-xs px-1.5 py-0.5 rounded bg-amber-500/20 text-amber-400">
+                Needs Re-granting
+              </span>
+            ) : granted !== undefined && (
               <span className={`text-xs px-1.5 py-0.5 rounded ${
                 granted
                   ? "bg-green-500/20 text-green-400"
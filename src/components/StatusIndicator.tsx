@@ -1,7 +1,7 @@
 import { useEffect, useState, useCallback } from "react";
-import { listen } from "@tauri-apps/api/event";
 import { invoke } from "@tauri-apps/api/core";
 import FloatingOverlay from "./FloatingOverlay";
+import { onEvent, settings } from "../lib/tauri";
 
 type Status = "idle" | "recording" | "transcribing" | "speaking";
 
@@ -28,26 +28,26 @@ export default function StatusIndicator() {
 
     const setupListeners = async () => {
       // STT events
-      const unlisten1 = await listen("stt-recording-started", () => {
+      const unlisten1 = await onEvent("stt-recording-started", () => {
         setStatus("recording");
         setResultMessage(null);
         setErrorMessage(null);
       });
       unlisteners.push(unlisten1);
 
-      const unlisten2 = await listen("stt-recording-stopped", () => {
+      const unlisten2 = await onEvent("stt-recording-stopped", () => {
         // Don't set idle yet - wait for transcription to complete or error
       });
       unlisteners.push(unlisten2);
 
-      const unlisten3 = await listen("stt-transcribing", () => {
+      const unlisten3 = await onEvent("stt-transcribing", () => {
         setStatus("transcribing");
       });
       unlisteners.push(unlisten3);
 
-      const unlisten4 = await listen<string>("stt-result", (event) => {
+      const unlisten4 = await onEvent("stt-result", (payload) => {
         setStatus("idle");
-        const text = event.payload;
+        const text = payload.text;
         setResultMessage(
           text.length > 60 ? `"${text.slice(0, 60)}..."` : `"${text}"`
         );
@@ -56,36 +56,98 @@ export default function StatusIndicator() {
       });
       unlisteners.push(unlisten4);
 
-      const unlisten5 = await listen<string>("stt-error", (event) => {
+      const unlisten5 = await onEvent("stt-error", (payload) => {
         setStatus("idle");
-        setErrorMessage(event.payload);
+        setErrorMessage(payload);
         setTimeout(() => setErrorMessage(null), 5000);
       });
       unlisteners.push(unlisten5);
 
+      // Discarded with Escape, or held without speaking
+      const unlistenCancelled = await onEvent("stt-cancelled", ({ reason }) => {
+        setStatus("idle");
+        setResultMessage(
+          reason === "escape" ? "Dictation discarded" : "No speech heard, dictation discarded"
+        );
+        setTimeout(() => setResultMessage(null), 3000);
+      });
+      unlisteners.push(unlistenCancelled);
+
       // TTS events
-      const unlisten6 = await listen("tts-started", () => {
+      const unlisten6 = await onEvent("tts-started", () => {
         setStatus("speaking");
         setResultMessage(null);
         setErrorMessage(null);
       });
       unlisteners.push(unlisten6);
 
-      const unlisten7 = await listen("tts-finished", () => {
+      const unlisten7 = await onEvent("tts-finished", () => {
         setStatus("idle");
       });
       unlisteners.push(unlisten7);
 
-      const unlisten8 = await listen<string>("tts-error", (event) => {
+      const unlisten8 = await onEvent("tts-error", (payload) => {
         setStatus("idle");
-        setErrorMessage(event.payload);
+        setErrorMessage(payload);
         setTimeout(() => setErrorMessage(null), 5000);
       });
       unlisteners.push(unlisten8);
+
+      // macOS didn't answer in time, e.g. a permission prompt is waiting
+      const unlisten9 = await onEvent("applescript-timed-out", ({ script, timeout_ms }) => {
+        setErrorMessage(`macOS didn't respond within ${timeout_ms / 1000}s (${script})`);
+        setTimeout(() => setErrorMessage(null), 5000);
+      });
+      unlisteners.push(unlisten9);
+
+      // The speech model in settings isn't on disk
+      const unlisten10 = await onEvent("configured-model-missing", ({ model_id, downloadable }) => {
+        setErrorMessage(
+          downloadable
+            ? `Speech model ${model_id} isn't downloaded. Download it in the Models tab.`
+            : `Speech model ${model_id} can't be found. Choose another in Settings.`
+        );
+        setTimeout(() => setErrorMessage(null), 8000);
+      });
+      unlisteners.push(unlisten10);
+
+      // Undo of the last pasted dictation
+      const unlisten11 = await onEvent("paste-undone", ({ app_name }) => {
+        setResultMessage(`Removed the last dictation from ${app_name}`);
+        setTimeout(() => setResultMessage(null), 4000);
+      });
+      unlisteners.push(unlisten11);
+
+      const unlisten12 = await onEvent("paste-undo-failed", ({ message }) => {
+        setErrorMessage(message);
+        setTimeout(() => setErrorMessage(null), 5000);
+      });
+      unlisteners.push(unlisten12);
+
+      // Accessibility is allowed but macOS refuses it, so paste and selection won't work
+      const unlisten13 = await onEvent("permission-degraded", ({ message }) => {
+        setErrorMessage(message);
+        setTimeout(() => setErrorMessage(null), 8000);
+      });
+      unlisteners.push(unlisten13);
     };
 
     setupListeners();
 
+    // Catch up with whatever started before this window loaded
+    settings
+      .getAppState()
+      .then((state) => {
+        if (state.recording) {
+          setStatus("recording");
+        } else if (state.transcribing) {
+          setStatus("transcribing");
+        } else if (state.speaking) {
+          setStatus("speaking");
+        }
+      })
+      .catch((err) => console.error("Failed to get app state:", err));
+
     return () => {
       unlisteners.forEach((unlisten) => unlisten());
     };
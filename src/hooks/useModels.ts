@@ -1,6 +1,6 @@
 import { useState, useEffect, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import { listen } from "@tauri-apps/api/event";
+import { errorMessage, models as modelCommands, onEvent, type EngineStatus } from "../lib/tauri";
 
 interface Model {
   id: string;
@@ -11,33 +11,61 @@ interface Model {
   download_url: string;
   status: string;
   description: string;
+  requires: string[];
+  dependencies_met: boolean;
+  hardware_compatible: boolean;
 }
 
 export function useModels() {
   const [models, setModels] = useState<Model[]>([]);
   const [downloadProgress, setDownloadProgress] = useState<Record<string, number>>({});
   const [downloading, setDownloading] = useState<Set<string>>(new Set());
+  // Keyed by model id
+  const [engineStatus, setEngineStatus] = useState<Record<string, EngineStatus>>({});
 
   useEffect(() => {
     loadModels();
+    loadEngineStatus();
 
     // Listen for download progress events
-    const unlisten = listen<[string, { percentage: number }]>(
-      "model-download-progress",
-      (event) => {
-        const [modelId, progress] = event.payload;
-        setDownloadProgress((prev) => ({
-          ...prev,
-          [modelId]: progress.percentage,
-        }));
-      }
-    );
+    const unlisten = onEvent("model-download-progress", ({ model_id, progress }) => {
+      setDownloadProgress((prev) => ({
+        ...prev,
+        [model_id]: progress.percentage,
+      }));
+    });
+
+    // Downloads stopped by cancel_all_downloads, possibly from another view
+    const unlistenCancelled = onEvent("model-download-all-cancelled", ({ model_ids }) => {
+      setDownloading((prev) => new Set([...prev].filter((id) => !model_ids.includes(id))));
+      setDownloadProgress((prev) => {
+        const next = { ...prev };
+        model_ids.forEach((id) => delete next[id]);
+        return next;
+      });
+    });
+
+    // Models loaded or dropped by a dictation, speech or idle eviction
+    const unlistenLoaded = onEvent("engine-loaded", loadEngineStatus);
+    const unlistenUnloaded = onEvent("engine-unloaded", loadEngineStatus);
 
     return () => {
       unlisten.then((fn) => fn());
+      unlistenCancelled.then((fn) => fn());
+      unlistenLoaded.then((fn) => fn());
+      unlistenUnloaded.then((fn) => fn());
     };
   }, []);
 
+  const loadEngineStatus = async () => {
+    try {
+      const statuses = await modelCommands.getEngineStatus();
+      setEngineStatus(Object.fromEntries(statuses.map((status) => [status.model_id, status])));
+    } catch (err) {
+      console.error("Failed to load engine status:", err);
+    }
+  };
+
   const loadModels = async () => {
     try {
       const result = await invoke<Model[]>("list_models");
@@ -47,12 +75,12 @@ export function useModels() {
     }
   };
 
-  const downloadModel = useCallback(async (modelId: string) => {
+  const downloadModel = useCallback(async (modelId: string, includeDependencies = false) => {
     try {
       setDownloading((prev) => new Set(prev).add(modelId));
       setDownloadProgress((prev) => ({ ...prev, [modelId]: 0 }));
 
-      await invoke("download_model", { modelId });
+      await invoke("download_model", { modelId, includeDependencies });
 
       // Refresh models list
       await loadModels();
@@ -72,15 +100,71 @@ export function useModels() {
     }
   }, []);
 
+  // Returns an error message, or null once the model is downloaded
+  const importModel = useCallback(
+    async (url: string, modelId: string, modelType: "stt" | "tts", name: string) => {
+      try {
+        setDownloading((prev) => new Set(prev).add(modelId));
+        setDownloadProgress((prev) => ({ ...prev, [modelId]: 0 }));
+
+        await modelCommands.importFromUrl(url, modelId, modelType, name);
+        return null;
+      } catch (err) {
+        console.error("Failed to import model:", err);
+        return errorMessage(err);
+      } finally {
+        // Registered even if the download failed, so it can be retried
+        await loadModels();
+        setDownloading((prev) => {
+          const next = new Set(prev);
+          next.delete(modelId);
+          return next;
+        });
+        setDownloadProgress((prev) => {
+          const next = { ...prev };
+          delete next[modelId];
+          return next;
+        });
+      }
+    },
+    []
+  );
+
   const deleteModel = useCallback(async (modelId: string) => {
     try {
-      await invoke("delete_model", { modelId });
+      const broken = await invoke<string[]>("delete_model", { modelId });
+      if (broken.length > 0) {
+        console.warn(`Deleted ${modelId}, which ${broken.join(", ")} need`);
+      }
       await loadModels();
     } catch (err) {
       console.error("Failed to delete model:", err);
     }
   }, []);
 
+  // Returns an error message, or null once the models are deleted
+  const deleteAllModels = useCallback(async (modelType: "stt" | "tts" | null = null) => {
+    try {
+      const result = await modelCommands.deleteAll(modelType, true);
+      console.info(`Deleted ${result.deleted_count} models, freeing ${result.freed_bytes} bytes`);
+      return null;
+    } catch (err) {
+      console.error("Failed to delete models:", err);
+      return errorMessage(err);
+    } finally {
+      await loadModels();
+    }
+  }, []);
+
+  // Each pending downloadModel() call rejects and clears its own state
+  const cancelAllDownloads = useCallback(async () => {
+    try {
+      await invoke<string[]>("cancel_all_downloads");
+    } catch (err) {
+      console.error("Failed to cancel downloads:", err);
+    }
+  }, []);
+
   const isDownloading = useCallback(
     (modelId: string) => downloading.has(modelId),
     [downloading]
@@ -88,10 +172,15 @@ export function useModels() {
 
   return {
     models,
+    engineStatus,
     downloadProgress,
     downloadModel,
+    importModel,
     deleteModel,
+    deleteAllModels,
     isDownloading,
+    hasActiveDownloads: downloading.size > 0,
+    cancelAllDownloads,
     refreshModels: loadModels,
   };
 }
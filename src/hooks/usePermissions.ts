@@ -1,10 +1,6 @@
 import { useState, useEffect, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
-
-interface PermissionStatus {
-  microphone: boolean;
-  accessibility: boolean;
-}
+import type { PermissionStatus } from "../lib/tauri";
 
 export function usePermissions(pollIntervalMs = 2000) {
   const [permissions, setPermissions] = useState<PermissionStatus | null>(null);
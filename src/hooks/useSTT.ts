@@ -1,7 +1,7 @@
 import { useState, useCallback, useEffect, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import { listen } from "@tauri-apps/api/event";
-import { appDataDir, join } from "@tauri-apps/api/path";
+import { errorMessage, onEvent, settings as settingsCommands } from "../lib/tauri";
+import { join } from "@tauri-apps/api/path";
 
 interface TranscriptionResult {
   text: string;
@@ -11,6 +11,8 @@ interface TranscriptionResult {
 interface StopRecordingResult {
   audio_data: number[];
   silence_triggered: boolean;
+  peak: number;
+  clipped_ratio: number;
 }
 
 export function useSTT() {
@@ -19,6 +21,12 @@ export function useSTT() {
   const [transcript, setTranscript] = useState<string | null>(null);
   const [error, setError] = useState<string | null>(null);
   const [silenceTriggered, setSilenceTriggered] = useState(false);
+  // Set when the system stopped an in-app recording (sleep or quit)
+  const [interrupted, setInterrupted] = useState(false);
+  // Suggestion shown when the last recording clipped
+  const [qualityWarning, setQualityWarning] = useState<string | null>(null);
+  // Share of the recording transcribed so far, 0 to 1; long recordings go in chunks
+  const [progress, setProgress] = useState<number | null>(null);
   const silencePollingRef = useRef<ReturnType<typeof setInterval> | null>(null);
 
   // Listen for hotkey events from the backend
@@ -27,47 +35,80 @@ export function useSTT() {
 
     const setupListeners = async () => {
       // STT recording started (via hotkey)
-      const unlisten1 = await listen("stt-recording-started", () => {
+      const unlisten1 = await onEvent("stt-recording-started", () => {
         console.log("STT recording started via hotkey");
         setIsRecording(true);
         setError(null);
+        setQualityWarning(null);
       });
       unlisteners.push(unlisten1);
 
       // STT recording stopped (via hotkey)
-      const unlisten2 = await listen("stt-recording-stopped", () => {
+      const unlisten2 = await onEvent("stt-recording-stopped", () => {
         console.log("STT recording stopped via hotkey");
         setIsRecording(false);
       });
       unlisteners.push(unlisten2);
 
+      // Recording stopped by the system; hotkey dictations finish on their own
+      const unlistenInterrupted = await onEvent("stt-recording-interrupted", (payload) => {
+        console.log("STT recording interrupted:", payload.reason);
+        if (!payload.hotkey) {
+          setInterrupted(true);
+        }
+      });
+      unlisteners.push(unlistenInterrupted);
+
+      const unlistenQuality = await onEvent("audio-quality-warning", (payload) => {
+        console.warn("Audio quality warning:", payload);
+        setQualityWarning(payload.message);
+      });
+      unlisteners.push(unlistenQuality);
+
       // STT transcribing
-      const unlisten3 = await listen("stt-transcribing", () => {
+      const unlisten3 = await onEvent("stt-transcribing", () => {
         console.log("STT transcribing...");
         setIsTranscribing(true);
+        setProgress(null);
       });
       unlisteners.push(unlisten3);
 
+      const unlistenProgress = await onEvent("stt-progress", (payload) => {
+        setProgress(payload.total_secs > 0 ? payload.processed_secs / payload.total_secs : null);
+      });
+      unlisteners.push(unlistenProgress);
+
       // STT result
-      const unlisten4 = await listen<string>("stt-result", (event) => {
-        console.log("STT result:", event.payload);
-        setTranscript(event.payload);
+      const unlisten4 = await onEvent("stt-result", (payload) => {
+        console.log("STT result:", payload);
+        setTranscript(payload.text);
         setIsTranscribing(false);
+        setProgress(null);
       });
       unlisteners.push(unlisten4);
 
       // STT error
-      const unlisten5 = await listen<string>("stt-error", (event) => {
-        console.error("STT error:", event.payload);
-        setError(event.payload);
+      const unlisten5 = await onEvent("stt-error", (payload) => {
+        console.error("STT error:", payload);
+        setError(payload);
         setIsRecording(false);
         setIsTranscribing(false);
+        setProgress(null);
       });
       unlisteners.push(unlisten5);
     };
 
     setupListeners();
 
+    // Catch up with a recording or transcription that started before this window loaded
+    settingsCommands
+      .getAppState()
+      .then((state) => {
+        setIsRecording(state.recording !== null);
+        setIsTranscribing(state.transcribing);
+      })
+      .catch((err) => console.error("Failed to get app state:", err));
+
     return () => {
       unlisteners.forEach((unlisten) => unlisten());
     };
@@ -106,6 +147,16 @@ export function useSTT() {
     }
   }, [silenceTriggered, isRecording]);
 
+  // Transcribe what was captured before the system stopped the recording
+  useEffect(() => {
+    if (interrupted) {
+      setInterrupted(false);
+      if (isRecording) {
+        stopRecording();
+      }
+    }
+  }, [interrupted, isRecording]);
+
   // Clean up polling on unmount
   useEffect(() => {
     return () => stopSilencePolling();
@@ -120,7 +171,7 @@ export function useSTT() {
       setIsRecording(true);
       startSilencePolling();
     } catch (err) {
-      setError(String(err));
+      setError(errorMessage(err));
       console.error("Failed to start recording:", err);
     }
   }, [startSilencePolling]);
@@ -131,6 +182,7 @@ export function useSTT() {
       stopSilencePolling();
       setIsRecording(false);
       setIsTranscribing(true);
+      setProgress(null);
       setError(null);
 
       const result = await invoke<StopRecordingResult>("stop_recording");
@@ -152,10 +204,11 @@ export function useSTT() {
       setTranscript(transcription.text);
       setSilenceTriggered(false);
     } catch (err) {
-      setError(String(err));
+      setError(errorMessage(err));
       console.error("Failed to stop recording or transcribe:", err);
     } finally {
       setIsTranscribing(false);
+      setProgress(null);
     }
   }, [stopSilencePolling]);
 
@@ -167,9 +220,11 @@ export function useSTT() {
   return {
     isRecording,
     isTranscribing,
+    progress,
     transcript,
     error,
     silenceTriggered,
+    qualityWarning,
     startRecording,
     stopRecording,
     clearTranscript,
@@ -177,8 +232,6 @@ export function useSTT() {
 }
 
 async function getModelsDir(): Promise<string> {
-  // Use Tauri's path API to get the correct app data directory
-  // On macOS: ~/Library/Application Support/com.blahcubed.app/models
-  const dataDir = await appDataDir();
-  return await join(dataDir, "models");
+  // Ask the backend, which honours a custom models folder from settings
+  return await invoke<string>("get_models_path");
 }
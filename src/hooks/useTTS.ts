@@ -1,21 +1,34 @@
-import { useState, useEffect, useCallback } from "react";
+import { useState, useEffect, useCallback, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import { listen } from "@tauri-apps/api/event";
+import { errorMessage, onEvent, type PlaybackProgress } from "../lib/tauri";
 
 interface Voice {
   id: string;
   name: string;
   language: string;
   gender: string;
+  custom?: boolean;
+  engine: TtsEngine;
 }
 
+type TtsEngine = "kokoro" | "system";
+
 export function useTTS() {
   const [isSpeaking, setIsSpeaking] = useState(false);
   const [currentText, setCurrentText] = useState<string | null>(null);
   const [voices, setVoices] = useState<Voice[]>([]);
   const [selectedVoice, setSelectedVoice] = useState("af_heart");
+  const [engine, setEngine] = useState<TtsEngine>("kokoro");
   const [speed, setSpeed] = useState(1.0);
+  const [speedOverrides, setSpeedOverrides] = useState<Record<string, number>>({});
+  // Speed the current hotkey speech is read at, after overrides
+  const [activeSpeed, setActiveSpeed] = useState<number | null>(null);
   const [error, setError] = useState<string | null>(null);
+  const [progress, setProgress] = useState<PlaybackProgress | null>(null);
+  const progressTimeout = useRef<ReturnType<typeof setTimeout>>();
+  // Share of a long text synthesized so far, 0-100, before playback starts
+  const [synthesisPercent, setSynthesisPercent] = useState<number | null>(null);
+  const synthesisTimeout = useRef<ReturnType<typeof setTimeout>>();
 
   // Listen for hotkey events from the backend
   useEffect(() => {
@@ -23,25 +36,42 @@ export function useTTS() {
 
     const setupListeners = async () => {
       // TTS started (via hotkey)
-      const unlisten1 = await listen<string>("tts-started", (event) => {
-        console.log("TTS started via hotkey:", event.payload);
+      const unlisten1 = await onEvent("tts-started", (payload) => {
+        console.log("TTS started via hotkey:", payload);
         setIsSpeaking(true);
-        setCurrentText(event.payload);
+        setCurrentText(payload.text);
+        setActiveSpeed(payload.speed);
         setError(null);
       });
       unlisteners.push(unlisten1);
 
       // TTS finished
-      const unlisten2 = await listen("tts-finished", () => {
+      const unlisten2 = await onEvent("tts-finished", () => {
         console.log("TTS finished");
         setIsSpeaking(false);
       });
       unlisteners.push(unlisten2);
 
+      // Playback position; cleared once updates stop, since speech started
+      // from the window doesn't send tts-finished
+      const unlistenProgress = await onEvent("tts-progress", (payload) => {
+        setProgress(payload);
+        clearTimeout(progressTimeout.current);
+        progressTimeout.current = setTimeout(() => setProgress(null), 1000);
+      });
+      unlisteners.push(unlistenProgress);
+
+      const unlistenSynthesis = await onEvent("tts-synthesis-progress", (payload) => {
+        setSynthesisPercent(Math.round((payload.chars_done / payload.chars_total) * 100));
+        clearTimeout(synthesisTimeout.current);
+        synthesisTimeout.current = setTimeout(() => setSynthesisPercent(null), 1000);
+      });
+      unlisteners.push(unlistenSynthesis);
+
       // TTS error
-      const unlisten3 = await listen<string>("tts-error", (event) => {
-        console.error("TTS error:", event.payload);
-        setError(event.payload);
+      const unlisten3 = await onEvent("tts-error", (payload) => {
+        console.error("TTS error:", payload);
+        setError(payload);
         setIsSpeaking(false);
       });
       unlisteners.push(unlisten3);
@@ -51,6 +81,8 @@ export function useTTS() {
 
     return () => {
       unlisteners.forEach((unlisten) => unlisten());
+      clearTimeout(progressTimeout.current);
+      clearTimeout(synthesisTimeout.current);
     };
   }, []);
 
@@ -70,9 +102,16 @@ export function useTTS() {
 
   const loadSettings = async () => {
     try {
-      const settings = await invoke<{ tts_voice: string; tts_speed: number }>("get_settings");
+      const settings = await invoke<{
+        tts_voice: string;
+        tts_speed: number;
+        tts_engine: TtsEngine;
+        voice_speed_overrides: Record<string, number>;
+      }>("get_settings");
       setSelectedVoice(settings.tts_voice);
+      setEngine(settings.tts_engine ?? "kokoro");
       setSpeed(settings.tts_speed);
+      setSpeedOverrides(settings.voice_speed_overrides ?? {});
     } catch (err) {
       console.error("Failed to load settings:", err);
     }
@@ -93,7 +132,7 @@ export function useTTS() {
           modelPath,
         });
       } catch (err) {
-        setError(String(err));
+        setError(errorMessage(err));
         console.error("Failed to speak:", err);
       } finally {
         setIsSpeaking(false);
@@ -106,6 +145,7 @@ export function useTTS() {
     try {
       await invoke("stop_speaking");
       setIsSpeaking(false);
+      setProgress(null);
     } catch (err) {
       console.error("Failed to stop speaking:", err);
     }
@@ -123,6 +163,40 @@ export function useTTS() {
     }
   }, []);
 
+  // Switch backend, moving to its first English voice if the current one belongs to the other
+  const updateEngine = useCallback(
+    async (newEngine: TtsEngine) => {
+      setEngine(newEngine);
+      const current = voices.find((v) => v.id === selectedVoice);
+      const voiceId =
+        current?.engine === newEngine
+          ? selectedVoice
+          : voices.find((v) => v.engine === newEngine && v.language.startsWith("en"))?.id ??
+            selectedVoice;
+      setSelectedVoice(voiceId);
+      try {
+        const settings = await invoke<Record<string, unknown>>("get_settings");
+        await invoke("update_settings", {
+          settings: { ...settings, tts_engine: newEngine, tts_voice: voiceId },
+        });
+      } catch (err) {
+        console.error("Failed to save TTS engine:", err);
+      }
+    },
+    [voices, selectedVoice]
+  );
+
+  const saveBlend = useCallback(async (name: string, blend: string) => {
+    await invoke("save_custom_voice", { id: null, name, blend });
+    await loadVoices();
+  }, []);
+
+  const deleteCustomVoice = useCallback(async (id: string) => {
+    await invoke("delete_custom_voice", { id });
+    await loadVoices();
+    await loadSettings();
+  }, []);
+
   const updateSpeed = useCallback(async (newSpeed: number) => {
     setSpeed(newSpeed);
     try {
@@ -135,17 +209,36 @@ export function useTTS() {
     }
   }, []);
 
+  // Pass null to clear the override and fall back to the global speed
+  const setVoiceSpeedOverride = useCallback(async (voiceId: string, newSpeed: number | null) => {
+    try {
+      await invoke("set_voice_speed_override", { voiceId, speed: newSpeed });
+      await loadSettings();
+    } catch (err) {
+      console.error("Failed to save voice speed:", err);
+    }
+  }, []);
+
   return {
     isSpeaking,
     currentText,
     voices,
     selectedVoice,
+    engine,
     speed,
+    speedOverrides,
+    activeSpeed,
+    progress,
+    synthesisPercent,
     error,
     speak,
     stop,
     setSelectedVoice: updateVoice,
+    setEngine: updateEngine,
     setSpeed: updateSpeed,
+    saveBlend,
+    deleteCustomVoice,
+    setVoiceSpeedOverride,
   };
 }
 
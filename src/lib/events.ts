@@ -0,0 +1,142 @@
+// Generated from src-tauri/src/events.rs - do not edit by hand.
+// Regenerate with `BLAH3_UPDATE_EVENT_TYPES=1 cargo test events`.
+
+export interface FrontmostAppInfo {
+  name: string;
+  bundle_id: string;
+}
+
+export type InterruptReason = "system_sleep" | "app_quit";
+
+export type DictationCancelReason = "escape" | "no_speech";
+
+export type ThermalState = "nominal" | "fair" | "serious" | "critical";
+
+export type UndoPasteFailure = "nothing_to_undo" | "expired" | "target_changed" | "keystroke_failed";
+
+export type AccessibilityState = "granted" | "denied" | "granted_but_not_working";
+
+export interface RecordingLevels {
+  peak: number;
+  clipped_ratio: number;
+}
+
+export interface WordConfidence {
+  word: string;
+  confidence: number;
+}
+
+export interface DictationTimings {
+  model: string;
+  capture_ms: number;
+  stop_to_transcribe_ms: number;
+  model_load_ms: number;
+  inference_ms: number;
+  realtime_factor: number;
+  post_processing_ms: number;
+  paste_ms: number | null;
+}
+
+export interface SilenceSuggestion {
+  ambient_rms: number;
+  threshold: number;
+  threshold_db: number;
+}
+
+export interface NoiseProfile {
+  rms: number;
+  peak: number;
+  db: number;
+  recommended_threshold: number;
+}
+
+export interface PlaybackProgress {
+  elapsed: number;
+  total: number;
+  percentage: number;
+}
+
+export interface DownloadProgress {
+  downloaded: number;
+  total: number;
+  percentage: number;
+}
+
+export interface BatchProgress {
+  index: number;
+  total: number;
+  file_name: string;
+  status: "processing" | "succeeded" | "failed";
+  error: string | null;
+}
+
+export type RecordingSource = "hotkey" | "app";
+
+export interface RecordingSnapshot {
+  session_id: number;
+  elapsed_secs: number;
+  source: RecordingSource;
+}
+
+export interface SpeakingSnapshot {
+  voice_id: string;
+  progress: PlaybackProgress | null;
+}
+
+export interface AppStateSnapshot {
+  recording: RecordingSnapshot | null;
+  transcribing: boolean;
+  speaking: SpeakingSnapshot | null;
+  active_downloads: string[];
+  loaded_engines: { whisper: boolean; tts: boolean };
+  permissions: {
+    microphone: boolean;
+    accessibility: boolean;
+    accessibility_state: AccessibilityState;
+  };
+}
+
+export interface EventPayloads {
+  "stt-recording-started": { target_app: FrontmostAppInfo | null };
+  "stt-recording-stopped": RecordingLevels;
+  "audio-quality-warning": { peak: number; clipped_ratio: number; message: string };
+  "stt-recording-interrupted": { reason: InterruptReason; hotkey: boolean };
+  "microphone-in-use": boolean;
+  "stt-transcribing": null;
+  "stt-audio-level": number;
+  "stt-partial-result": string;
+  "stt-progress": { processed_secs: number; total_secs: number };
+  "stt-result": { text: string; confidence: number | null; words: WordConfidence[]; needs_review: boolean; timings: DictationTimings };
+  "stt-pasted": { target_app: FrontmostAppInfo | null; chars: number };
+  "stt-paste-failed": { error: string; text: string };
+  "stt-cancelled": { reason: DictationCancelReason };
+  "stt-error": string;
+  "dictation-overlay-shown": { session_id: number };
+  "stt-retry-available": { reason: string; audio_secs: number; truncated: boolean };
+  "silence-threshold-suggested": SilenceSuggestion;
+  "noise-profile-recording": { duration_secs: number };
+  "noise-profile-complete": NoiseProfile;
+  "tts-started": { text: string; voice_id: string; speed: number };
+  "tts-finished": null;
+  "tts-progress": PlaybackProgress;
+  "tts-synthesis-progress": { chars_done: number; chars_total: number };
+  "tts-error": string;
+  "shortcut-captured": { shortcut_str: string; display: string };
+  "shortcut-capture-timeout": null;
+  "hotkey-conflict": { binding: string; shortcut: string; conflicts_with: string; fallback: string };
+  "model-download-progress": { model_id: string; progress: DownloadProgress };
+  "model-download-all-cancelled": { model_ids: string[] };
+  "engine-loaded": { model_id: string; model_type: "stt" | "tts"; approx_memory_bytes: number };
+  "engine-unloaded": { model_id: string; model_type: "stt" | "tts" };
+  "batch-progress": BatchProgress;
+  "thermal-state-changed": { state: ThermalState; previous: ThermalState };
+  "system-will-sleep": null;
+  "system-did-wake": null;
+  "applescript-timed-out": { script: string; timeout_ms: number };
+  "configured-model-missing": { model_id: string; downloadable: boolean };
+  "paste-undone": { chars: number; app_name: string; native_undo: boolean };
+  "paste-undo-failed": { reason: UndoPasteFailure; message: string };
+  "permission-degraded": { permission: string; message: string };
+}
+
+export type EventName = keyof EventPayloads;
@@ -1,17 +1,103 @@
 import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import type {
+  AppStateSnapshot,
+  DictationTimings,
+  EventName,
+  EventPayloads,
+  NoiseProfile,
+  SilenceSuggestion,
+  ThermalState,
+  WordConfidence,
+} from "./events";
+
+export type {
+  AppStateSnapshot,
+  BatchProgress,
+  DictationTimings,
+  DownloadProgress,
+  EventName,
+  EventPayloads,
+  FrontmostAppInfo,
+  NoiseProfile,
+  PlaybackProgress,
+  RecordingLevels,
+  RecordingSnapshot,
+  RecordingSource,
+  SilenceSuggestion,
+  SpeakingSnapshot,
+  ThermalState,
+  WordConfidence,
+} from "./events";
 
 // Type definitions for Tauri commands
 
 export interface TranscriptionResult {
   text: string;
   duration_ms: number;
+  // Mean word confidence, 0 to 1
+  confidence?: number;
+  segments?: TranscriptSegment[];
+  // File name of the model that transcribed it
+  model?: string;
+}
+
+export interface TranscriptSegment {
+  text: string;
+  start_ms: number;
+  end_ms: number;
+  confidence?: number;
+  words?: WordConfidence[];
 }
 
+// TTS backend: downloaded Kokoro model or built-in macOS voices
+export type TtsEngine = "kokoro" | "system";
+
 export interface VoiceInfo {
   id: string;
   name: string;
   language: string;
   gender: string;
+  custom: boolean;
+  engine: TtsEngine;
+}
+
+// Models for short and long dictations; recordings in between use stt_model
+export interface AdaptiveModel {
+  fast_model: string;
+  fast_below_secs: number;
+  accurate_model: string;
+  accurate_above_secs: number;
+}
+
+// Whisper settings for power users; use_gpu and flash_attention reload the model
+export interface WhisperAdvancedConfig {
+  use_gpu: boolean;
+  flash_attention: boolean;
+  // Decoding threads (up to 64); 0 lets whisper.cpp choose
+  n_threads: number;
+}
+
+// Saved voice blend, e.g. "af_bella:0.5+af_sky:0.5"
+export interface CustomVoice {
+  id: string;
+  name: string;
+  blend: string;
+}
+
+// Predicted playback length, counted after text normalization
+export interface EstimatedDuration {
+  estimated_secs: number;
+  word_count: number;
+  char_count: number;
+}
+
+// Cached voice preview
+export interface TtsCacheEntry {
+  voice_id: string;
+  preview_text_hash: string;
+  file_size_bytes: number;
+  created_at: string;
 }
 
 export interface ModelInfo {
@@ -23,38 +109,352 @@ export interface ModelInfo {
   download_url: string;
   status: "available" | "downloaded" | "downloading";
   description: string;
+  // Ids of models that have to be downloaded too, e.g. Kokoro's voices
+  requires: string[];
+  dependencies_met: boolean;
+  // False when the model needs more than half of this machine's RAM
+  hardware_compatible: boolean;
 }
 
+export type PasteSuffix = "none" | "space" | "newline";
+
+export type PostAction = "paste" | "paste_and_enter" | "open_url_template" | "copy_only";
+
+// A setting that was saved but can't be used yet
+export type SettingsWarning = {
+  kind: "model_not_downloaded";
+  field: string;
+  model_id: string;
+  model_name: string;
+};
+
+// First-run setup screens, in order
+export type OnboardingStep =
+  | "welcome"
+  | "permissions"
+  | "model_download"
+  | "hotkey_setup"
+  | "voice_test"
+  | "complete";
+
+// "spell" spells short selections letter by letter and reads longer ones slowly
+export type TtsHoldAction = "slow" | "spell" | "none";
+
 export interface AppSettings {
   stt_hotkey: string;
   tts_hotkey: string;
+  // What holding the read-aloud hotkey for tts_hold_threshold_ms does
+  tts_hold_action: TtsHoldAction;
+  tts_hold_threshold_ms: number;
+  // Repeats the last spoken text; null for no hotkey
+  replay_hotkey: string | null;
+  // Reads the clipboard aloud; null for no hotkey
+  tts_clipboard_hotkey: string | null;
   stt_model: string;
   tts_voice: string;
   tts_speed: number;
+  tts_engine: TtsEngine;
+  // Speed per voice id or language prefix ("b" = British), over tts_speed
+  voice_speed_overrides: Record<string, number>;
   auto_paste: boolean;
+  // Also put every dictation on the clipboard, pasted or not
+  copy_to_clipboard: boolean;
+  // Added after auto-pasted dictations
+  paste_suffix: PasteSuffix;
+  // Sends chat messages; only pressed when the dictation's app is still frontmost
+  press_enter_after_paste: boolean;
+  paste_confirmation_sound: boolean;
+  // What an auto-pasted dictation does instead of a plain paste
+  post_action: PostAction;
+  // Needs a {text} placeholder, e.g. https://www.google.com/search?q={text}
+  post_action_url_template: string | null;
+  // Removes the last pasted dictation; outcome arrives as paste-undone or paste-undo-failed
+  undo_paste_hotkey: string | null;
+  // Cmd+Z instead of one Delete per pasted character
+  use_native_undo: boolean;
+  undo_paste_window_secs: number;
   launch_at_login: boolean;
   menu_bar_mode: boolean;
   // Silence detection settings
   silence_detection_enabled: boolean;
   silence_threshold: number;
   silence_duration: number;
+  silence_adaptive: boolean;
+  // Hold-to-dictate recordings with no speech after this long are discarded
+  // (stt-cancelled); 0 disables
+  discard_silent_hold_secs: number;
+  // How long a dictation that failed to transcribe can be retried
+  failed_dictation_ttl_secs: number;
+  // Pick the speech model by recording length instead of always stt_model
+  adaptive_model: AdaptiveModel | null;
+  // Whisper decoding
+  stt_short_utterance_secs: number;
+  stt_suppress_non_speech_tokens: boolean;
+  // Longer recordings are transcribed in chunks of this many seconds (10 to 600)
+  stt_max_chunk_secs: number;
+  // Names and terms Whisper should expect, up to 1000 characters
+  whisper_initial_prompt: string | null;
+  whisper_advanced: WhisperAdvancedConfig;
+  // Hold dictations below this confidence for review instead of pasting; 0 disables
+  confidence_paste_threshold: number;
+  // Split transcripts into speaker turns (needs a tinydiarize model)
+  diarization_enabled: boolean;
+  // Blank line between sentences separated by a pause longer than paragraph_pause_secs
+  paragraph_breaks_enabled: boolean;
+  paragraph_pause_secs: number;
+  // Typography of finished dictations: "system", "none" or a language tag such as "fr"
+  locale: string;
   // Onboarding
   onboarding_completed: boolean;
+  // Furthest setup step reached
+  onboarding_step: OnboardingStep;
+  // Speak-on-copy (clipboard watcher)
+  speak_on_copy: boolean;
+  speak_on_copy_max_chars: number;
+  // Saved voice blends
+  custom_voices: CustomVoice[];
+  // Models location override, null for the app data directory
+  custom_models_dir: string | null;
+  download_max_retries: number;
+  // Proxy for model downloads, null for the system proxy settings
+  http_proxy: string | null;
+  // Text clean-up before speech
+  tts_normalization: NormalizeOptions;
+  log_level: LogLevel;
+}
+
+export type LogLevel = "error" | "warn" | "info" | "debug" | "trace";
+
+export interface NormalizeOptions {
+  markup: boolean;
+  urls: boolean;
+  abbreviations: boolean;
+  numbers: boolean;
+  whitespace: boolean;
+}
+
+// Transcript segment with a speaker number; ids count up from 0 per turn
+export interface DiarizedSegment {
+  speaker_id: number;
+  text: string;
+  start_ms: number;
+  end_ms: number;
+}
+
+// Whisper language; `code` is what gets passed to the model
+export interface LanguageInfo {
+  code: string;
+  name: string;
+}
+
+// Outcome of checking a Whisper model file before use
+export interface ModelValidationResult {
+  is_valid: boolean;
+  error: string | null;
+  // Size from the file header, e.g. "base.en"
+  model_type: string | null;
+  size_bytes: number;
+}
+
+export type TranscriptStatus = "recording" | "transcribing" | "done" | "failed" | "cancelled";
+
+// Text so far of a hotkey dictation, pulled by windows that missed its events
+export interface CurrentTranscript {
+  session_id: number;
+  status: TranscriptStatus;
+  text: string;
 }
 
 export interface StopRecordingResult {
   audio_data: number[];
   silence_triggered: boolean;
+  // Largest sample magnitude and share of samples at full scale
+  peak: number;
+  clipped_ratio: number;
+}
+
+export interface AudioDeviceInfo {
+  name: string;
+  // The system default input, which recordings use
+  is_default: boolean;
+}
+
+// RMS levels in dB over 100 ms windows, floored at -100
+export interface InputLevel {
+  min_db: number;
+  avg_db: number;
+  peak_db: number;
 }
 
 export interface HardwareProfile {
-  chip: "applesilicon" | "intel" | "unknown";
+  chip: "applesilicon" | "intel" | "amd" | "unknown";
   chip_name: string;
   ram_gb: number;
   cpu_cores: number;
   has_neural_engine: boolean;
   has_metal: boolean;
+  gpu_name: string | null;
+  acceleration: string[];
   recommended_tier: "lite" | "standard" | "power";
+  thermal_state: ThermalState;
+}
+
+// granted_but_not_working: allowed in System Settings but refused, usually after an update
+export type AccessibilityState = "granted" | "denied" | "granted_but_not_working";
+
+// A model loaded this session, from get_engine_status
+export interface EngineStatus {
+  model_id: string;
+  model_type: "stt" | "tts";
+  loaded_in_memory: boolean;
+  // UTC, e.g. "2024-05-01T09:30:00Z"
+  last_used: string | null;
+  // Size of the model files, roughly what the loaded model takes
+  approx_memory_bytes: number;
+}
+
+// What delete_all_models removed
+export interface DeletionResult {
+  deleted_count: number;
+  freed_bytes: number;
+}
+
+export interface PermissionStatus {
+  microphone: boolean;
+  // True only when accessibility_state is "granted"
+  accessibility: boolean;
+  accessibility_state: AccessibilityState;
+}
+
+// How to re-grant Accessibility; reset_command is for the user to run in Terminal
+export interface AccessibilityHint {
+  steps: string[];
+  settings_url: string;
+  reset_command: string;
+}
+
+export interface HotkeyBinding {
+  action: "stt" | "tts" | "replay" | "undo_paste";
+  shortcut: string;
+  registered: boolean;
+}
+
+// Outcome of importing one part of a configuration bundle
+export interface SectionResult {
+  section: "settings" | "custom_voices" | "voice_speed_overrides" | "custom_models";
+  imported: number;
+  skipped: number;
+  error: string | null;
+}
+
+// Another dictation app to bring models and settings over from
+export type MigrationSource = "super_whisper" | "whisper_dictation" | { custom: string };
+
+export interface MigrationReport {
+  models_imported: string[];
+  // Our setting names, e.g. "stt_hotkey"
+  settings_copied: string[];
+  warnings: string[];
+}
+
+// App state for bug reports (debug builds only)
+export interface DiagnosticSnapshot {
+  settings: AppSettings;
+  hardware: HardwareProfile;
+  permissions: PermissionStatus;
+  models: ModelInfo[];
+  active_hotkeys: HotkeyBinding[];
+  is_recording: boolean;
+  microphone_in_use: boolean;
+  tts_engine_loaded: boolean;
+  whisper_engine_loaded: boolean;
+  dictation_timings: DictationDiagnostics;
+  app_version: string;
+}
+
+// A release newer than the running version
+export interface UpdateInfo {
+  version: string;
+  release_url: string;
+  // Markdown
+  release_notes: string | null;
+}
+
+// Activity since the app launched
+export interface SessionStats {
+  recording_count: number;
+  total_recording_secs: number;
+  total_chars_dictated: number;
+  total_tts_chars: number;
+  session_started_at: string;
+}
+
+// Spread of one dictation stage; null until a dictation has been timed
+export interface StageStats {
+  mean_ms: number;
+  median_ms: number;
+  max_ms: number;
+}
+
+// Recent dictation timings (most recent first) with their aggregates
+export interface DictationDiagnostics {
+  sessions: DictationTimings[];
+  summary: {
+    sessions: number;
+    cold_starts: number;
+    capture: StageStats | null;
+    stop_to_transcribe: StageStats | null;
+    model_load: StageStats | null;
+    inference: StageStats | null;
+    post_processing: StageStats | null;
+    paste: StageStats | null;
+    mean_realtime_factor: number | null;
+  };
+}
+
+// Error returned by every command
+export interface BlahError {
+  code:
+    | "model_not_found"
+    | "model_not_downloaded"
+    | "download_failed"
+    | "transcription_failed"
+    | "synthesis_failed"
+    | "audio_capture_failed"
+    | "audio_playback_failed"
+    | "permission_denied"
+    | "accessibility_denied"
+    | "invalid_input"
+    | "busy"
+    | "invalid_setting"
+    | "io_error"
+    | "settings_error"
+    | "internal";
+  message: string;
+  // Settings field at fault, for invalid_setting
+  field?: string;
+}
+
+export function isBlahError(err: unknown): err is BlahError {
+  return (
+    typeof err === "object" &&
+    err !== null &&
+    "code" in err &&
+    "message" in err
+  );
+}
+
+// Typed listener for backend events; names and payloads come from events.ts
+export function onEvent<K extends EventName>(
+  name: K,
+  handler: (payload: EventPayloads[K]) => void
+): Promise<UnlistenFn> {
+  return listen<EventPayloads[K]>(name, (event) => handler(event.payload));
+}
+
+// Human-readable message for anything thrown by invoke()
+export function errorMessage(err: unknown): string {
+  return isBlahError(err) ? err.message : String(err);
 }
 
 // STT Commands
@@ -63,8 +463,51 @@ export const stt = {
   stopRecording: () => invoke<StopRecordingResult>("stop_recording"),
   transcribe: (audioData: number[], modelPath: string) =>
     invoke<TranscriptionResult>("transcribe_audio", { audioData, modelPath }),
+  transcribeDiarized: (audioData: number[], modelPath: string) =>
+    invoke<DiarizedSegment[]>("transcribe_audio_diarized", { audioData, modelPath }),
   isSilenceTriggered: () => invoke<boolean>("is_silence_triggered"),
   isRecording: () => invoke<boolean>("is_recording"),
+  // stt-audio-level slows to a 500 ms heartbeat unless the overlay or main window is visible or a webview subscribes
+  subscribeAudioLevels: (subscribed: boolean) =>
+    invoke("subscribe_audio_levels", { subscribed }),
+  getRecentTranscriptions: (limit: number) =>
+    invoke<string[]>("get_recent_transcriptions", { limit }),
+  getLastTranscription: () => invoke<string | null>("get_last_transcription"),
+  // Without an id, the latest dictation; only it and the one before are kept
+  getCurrentTranscript: (sessionId?: number) =>
+    invoke<CurrentTranscript | null>("get_current_transcript", { sessionId: sessionId ?? null }),
+  copyToClipboard: (text: string) => invoke("copy_transcription_to_clipboard", { text }),
+  // Also emits paste-undone or paste-undo-failed
+  undoLastPaste: () => invoke<EventPayloads["paste-undone"]>("undo_last_paste"),
+  setSilenceConfig: (threshold: number, duration: number, enabled: boolean) =>
+    invoke("set_silence_config", { threshold, duration, enabled }),
+  calibrateSilence: () => invoke<SilenceSuggestion>("calibrate_silence"),
+  // Stay quiet while it listens; saves recommended_threshold as the silence threshold
+  recordAmbientNoiseProfile: (durationSecs: number) =>
+    invoke<NoiseProfile>("record_ambient_noise_profile", { durationSecs }),
+  getNoiseFloorDb: () => invoke<number | null>("get_noise_floor_db"),
+  listInputDevices: () => invoke<AudioDeviceInfo[]>("list_input_devices"),
+  getSupportedLanguages: () => invoke<LanguageInfo[]>("get_supported_languages"),
+  // Close the review overlay, copying the text when given
+  finishReview: (text: string | null) => invoke("finish_review", { text }),
+  // Transcribes the last failed dictation again (see stt-retry-available)
+  // and pastes it like a new one; returns the text
+  retryLastTranscription: (modelId: string | null) =>
+    invoke<string>("retry_last_transcription", { modelId }),
+  // Hotkey dictations switch this themselves; false lets clicks through
+  setOverlayInteractivity: (interactive: boolean) =>
+    invoke("set_overlay_interactivity", { interactive }),
+  // Records for 3 seconds
+  testInputLevel: () => invoke<InputLevel>("test_input_level"),
+  // Empty clears it; applies from the next transcription
+  setWhisperPrompt: (prompt: string) => invoke("set_whisper_prompt", { prompt }),
+  getWhisperConfig: () => invoke<WhisperAdvancedConfig>("get_whisper_config"),
+  // Changing use_gpu or flash_attention reloads the model on the next transcription
+  updateWhisperConfig: (config: WhisperAdvancedConfig) =>
+    invoke("update_whisper_config", { config }),
+  // Checks the header and size, then loads the model once
+  validateModelFile: (modelPath: string) =>
+    invoke<ModelValidationResult>("validate_model_file", { modelPath }),
 };
 
 // TTS Commands
@@ -72,20 +515,112 @@ export const tts = {
   speak: (text: string, voiceId: string, speed: number, modelPath: string) =>
     invoke("speak_text", { text, voiceId, speed, modelPath }),
   stop: () => invoke("stop_speaking"),
-  getVoices: () => invoke<VoiceInfo[]>("get_voices"),
+  // Also frees the engine from a long synthesis, stopping before its next chunk
+  cancelSynthesis: () => invoke("cancel_synthesis"),
+  // Plays the last speech again from memory; false if nothing was spoken yet
+  replayLast: () => invoke<boolean>("replay_last_speech"),
+  // Speaks the copied text with the configured voice; rejects when the clipboard is empty or too long
+  speakClipboard: () => invoke("speak_clipboard"),
+  // No synthesis involved, so cheap enough to call while typing
+  estimateReadingTime: (text: string, speed: number) =>
+    invoke<EstimatedDuration>("estimate_reading_time", { text, speed }),
+  // Language is a tag like "fr-FR", or "fr" for every French variant
+  getVoices: (language?: string) => invoke<VoiceInfo[]>("get_voices", { language: language ?? null }),
+  listCustomVoices: () => invoke<CustomVoice[]>("list_custom_voices"),
+  saveCustomVoice: (name: string, blend: string, id?: string) =>
+    invoke<CustomVoice>("save_custom_voice", { id: id ?? null, name, blend }),
+  deleteCustomVoice: (id: string) => invoke("delete_custom_voice", { id }),
+  // Pass null to clear the override
+  setVoiceSpeedOverride: (voiceId: string, speed: number | null) =>
+    invoke("set_voice_speed_override", { voiceId, speed }),
+  // Speaks a sample sentence, cached per voice
+  preview: (voiceId: string) => invoke("preview_voice", { voiceId }),
+  listCacheEntries: () => invoke<TtsCacheEntry[]>("list_tts_cache_entries"),
+  // Returns the bytes freed
+  clearCache: () => invoke<number>("clear_tts_cache"),
+  clearCacheEntry: (voiceId: string) => invoke("clear_tts_cache_entry", { voiceId }),
 };
 
 // Model Commands
 export const models = {
   list: () => invoke<ModelInfo[]>("list_models"),
-  download: (modelId: string) => invoke<string>("download_model", { modelId }),
-  delete: (modelId: string) => invoke("delete_model", { modelId }),
+  // With includeDependencies, missing required models are downloaded first
+  download: (modelId: string, includeDependencies = false) =>
+    invoke<string>("download_model", { modelId, includeDependencies }),
+  downloadToPath: (modelId: string, destDir: string) =>
+    invoke<string>("download_model_to_path", { modelId, destDir }),
+  // Register and download a model from any https:// URL (10 GB max)
+  importFromUrl: (url: string, modelId: string, modelType: "stt" | "tts", name: string) =>
+    invoke<string>("import_from_url", { url, modelId, modelType, name }),
+  // Bytes to download for the recommended models, downloaded or not
+  getRecommendedDownloadSize: () => invoke<number>("get_recommended_download_size"),
+  // Whether there's RAM for the model with room to spare; false for unknown ids
+  canRunModel: (modelId: string) => invoke<boolean>("can_run_model", { modelId }),
+  // Changes only the display name of an imported model
+  renameCustomModel: (modelId: string, newName: string) =>
+    invoke<ModelInfo>("rename_custom_model", { modelId, newName }),
+  // Returns the ids of the downloads that were stopped
+  cancelAll: () => invoke<string[]>("cancel_all_downloads"),
+  getPath: () => invoke<string>("get_models_path"),
+  // Resolves to the downloaded models that needed it
+  delete: (modelId: string) => invoke<string[]>("delete_model", { modelId }),
+  // Deletes every downloaded model, or those of one type; rejects unless confirm is true or while downloading
+  deleteAll: (modelType: "stt" | "tts" | null, confirm: boolean) =>
+    invoke<DeletionResult>("delete_all_models", { modelType, confirm }),
   getStatus: (modelId: string) => invoke<string>("get_model_status", { modelId }),
+  // Models loaded this session and whether they are still in memory; changes come as engine-loaded/engine-unloaded
+  getEngineStatus: () => invoke<EngineStatus[]>("get_engine_status"),
+};
+
+// Selection and paste in other apps; both reject with accessibility_denied without Accessibility access
+export const accessibility = {
+  // Null when nothing is selected. With fromPreviousApp, switches to the app the user was in when this window is in front
+  getSelection: (fromPreviousApp = false) =>
+    invoke<string | null>("get_selection", { fromPreviousApp }),
+  pasteIntoFrontmost: (text: string, activateBundleId?: string) =>
+    invoke("paste_into_frontmost", { text, activateBundleId }),
+  resetHint: () => invoke<AccessibilityHint>("reset_accessibility_hint"),
 };
 
 // Settings Commands
 export const settings = {
   get: () => invoke<AppSettings>("get_settings"),
-  update: (settings: AppSettings) => invoke("update_settings", { settings }),
+  // Rejects unknown models; warns about ones that still need downloading
+  update: (settings: AppSettings) => invoke<SettingsWarning[]>("update_settings", { settings }),
+  // Saves one setting without overwriting others changed meanwhile; dotted fields reach into maps, e.g. "voice_speed_overrides.af_heart"
+  updateField: (field: string, value: unknown) =>
+    invoke<AppSettings>("update_settings_field", { field, value }),
   getHardwareInfo: () => invoke<HardwareProfile>("get_hardware_info"),
+  // Result arrives as a shortcut-captured or shortcut-capture-timeout event
+  startShortcutCapture: () => invoke("start_shortcut_capture"),
+  // Gives the hotkeys back if the capture is still waiting
+  cancelShortcutCapture: () => invoke("cancel_shortcut_capture"),
+  // Debug builds only
+  getDiagnosticSnapshot: () => invoke<DiagnosticSnapshot>("get_diagnostic_snapshot"),
+  copyDiagnostics: () => invoke("copy_diagnostics_to_clipboard"),
+  // Where recent dictations spent their time; available in every build
+  getDiagnostics: () => invoke<DictationDiagnostics>("get_diagnostics"),
+  getSessionStats: () => invoke<SessionStats>("get_session_stats"),
+  // Null when up to date; GitHub is asked at most once a day
+  checkForAppUpdates: () => invoke<UpdateInfo | null>("check_for_app_updates"),
+  // Recording, transcription, speech, downloads and permissions right now; windows resync with it on mount
+  getAppState: () => invoke<AppStateSnapshot>("get_app_state"),
+  // Settings, voice blends and custom models as one JSON file; no model files or proxy credentials
+  exportConfig: (destPath: string) => invoke("export_config", { destPath }),
+  // With merge, existing blends, speed overrides and custom models are kept
+  importConfig: (path: string, merge: boolean) =>
+    invoke<SectionResult[]>("import_config", { path, merge }),
+  // Links the other app's Whisper models into ours and copies settings that match
+  migrateFromCompetitor: (source: MigrationSource) =>
+    invoke<MigrationReport>("migrate_from_competitor", { source }),
+  // Takes effect right away and is saved for later launches
+  setLogLevel: (level: LogLevel) => invoke("set_log_level", { level }),
+  getLogLevel: () => invoke<LogLevel>("get_log_level"),
+  // Open in Finder, creating the models folder if it doesn't exist yet
+  openModelsDirectory: () => invoke("open_models_directory"),
+  openSettingsDirectory: () => invoke("open_settings_directory"),
+  getOnboardingStep: () => invoke<OnboardingStep>("get_current_onboarding_step"),
+  // Saves and returns the step after the current one
+  advanceOnboarding: () => invoke<OnboardingStep>("advance_onboarding_step"),
+  skipOnboarding: () => invoke("skip_onboarding"),
 };
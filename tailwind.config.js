@@ -25,6 +25,7 @@ export default {
         "fade-in": "fade-in 0.2s ease-out",
         "slide-in-from-top": "slide-in-from-top 0.2s ease-out",
         "slide-in-from-bottom": "slide-in-from-bottom 0.2s ease-out",
+        flash: "flash 0.5s ease-out",
       },
       keyframes: {
         waveform: {
@@ -47,6 +48,10 @@ export default {
           "0%": { transform: "translateY(100%)" },
           "100%": { transform: "translateY(0)" },
         },
+        flash: {
+          "0%": { boxShadow: "0 0 0 0 rgba(34, 197, 94, 0.7)", transform: "scale(1.03)" },
+          "100%": { boxShadow: "0 0 0 12px rgba(34, 197, 94, 0)", transform: "scale(1)" },
+        },
       },
     },
   },